@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::auth::context::{AuthContext, AuthError};
+use crate::auth::hashing::sha256_hex;
+use crate::auth::jwt::JwtAuthenticator;
+use crate::auth::traits::AuthenticatorTrait;
+
+/// Prefix identifying an API key, so it can be told apart from a JWT.
+pub const API_KEY_PREFIX: &str = "dhk_";
+
+/// Length of the public, hex-encoded part of a key used to look it up.
+const LOOKUP_PREFIX_LEN: usize = 12;
+
+/// `last_used_at` is only written when the stored value is older than this,
+/// so that a busy key does not cause a database write on every request.
+const LAST_USED_RESOLUTION: Duration = Duration::seconds(60);
+
+/// A freshly generated API key. `key` is shown to the user once; only `prefix`
+/// and `hash` are stored.
+#[derive(Debug, Clone)]
+pub struct GeneratedApiKey {
+    /// Full key, `dhk_<prefix>_<secret>`.
+    pub key: String,
+    /// Public lookup prefix (`dhk_<12 hex chars>`).
+    pub prefix: String,
+    /// SHA-256 of the full key, hex-encoded.
+    pub hash: String,
+}
+
+impl GeneratedApiKey {
+    /// Generate a new random key.
+    pub fn generate() -> Self {
+        let lookup = Uuid::new_v4().simple().to_string();
+        let prefix = format!("{}{}", API_KEY_PREFIX, &lookup[..LOOKUP_PREFIX_LEN]);
+        let key = format!("{}_{}", prefix, Uuid::new_v4().simple());
+        let hash = hash_api_key(&key);
+        Self { key, prefix, hash }
+    }
+}
+
+/// Hash an API key for storage. Keys carry 122 bits of randomness, so a plain
+/// SHA-256 is sufficient and keeps per-request verification cheap.
+#[inline]
+pub fn hash_api_key(key: &str) -> String {
+    sha256_hex(key)
+}
+
+/// Return the lookup prefix of `token` if it is shaped like an API key.
+pub fn api_key_prefix(token: &str) -> Option<&str> {
+    let rest = token.strip_prefix(API_KEY_PREFIX)?;
+    let (lookup, secret) = rest.split_once('_')?;
+    if lookup.len() != LOOKUP_PREFIX_LEN || secret.is_empty() {
+        return None;
+    }
+    Some(&token[..API_KEY_PREFIX.len() + LOOKUP_PREFIX_LEN])
+}
+
+/// Compare two hex digests without short-circuiting on the first mismatch.
+fn digests_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+/// A stored API key together with the current roles of its owner.
+#[derive(Debug, Clone)]
+pub struct ApiKeyRecord {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub key_hash: String,
+    /// Scopes granted to the key when it was created.
+    pub scopes: Vec<String>,
+    /// Current roles of the owning user; a key never grants more than these.
+    pub owner_scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Storage for API keys, consulted by [`ApiKeyAuthenticator`].
+#[async_trait::async_trait]
+pub trait ApiKeyStore: Send + Sync + 'static {
+    /// Look up a key by its public prefix.
+    async fn find_by_prefix(&self, prefix: &str) -> Result<Option<ApiKeyRecord>, AuthError>;
+
+    /// Record that key `id` was used at `at`.
+    async fn record_use(&self, id: Uuid, at: DateTime<Utc>) -> Result<(), AuthError>;
+}
+
+/// Authenticates `dhk_` API keys and hands every other token to a fallback
+/// authenticator (normally the [`JwtAuthenticator`]).
+///
+/// The resulting context carries the intersection of the key's scopes and the
+/// owner's current roles, and `{"apiKeyId": ...}` as metadata.
+pub struct ApiKeyAuthenticator {
+    store: Arc<dyn ApiKeyStore>,
+    fallback: Option<Arc<dyn AuthenticatorTrait>>,
+}
+
+impl std::fmt::Debug for ApiKeyAuthenticator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKeyAuthenticator")
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl ApiKeyAuthenticator {
+    pub fn new(store: Arc<dyn ApiKeyStore>) -> Self {
+        Self {
+            store,
+            fallback: None,
+        }
+    }
+
+    /// Delegate tokens that are not API keys to `fallback`.
+    #[must_use]
+    pub fn with_fallback(mut self, fallback: Arc<dyn AuthenticatorTrait>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    async fn authenticate_key(&self, key: &str, prefix: &str) -> Result<AuthContext, AuthError> {
+        let record = self
+            .store
+            .find_by_prefix(prefix)
+            .await?
+            .ok_or(AuthError::AuthenticationFailed)?;
+        if !digests_match(&hash_api_key(key), &record.key_hash) {
+            return Err(AuthError::AuthenticationFailed);
+        }
+
+        let now = Utc::now();
+        if record.expires_at.is_some_and(|exp| exp <= now) {
+            return Err(AuthError::TokenExpired);
+        }
+
+        if record
+            .last_used_at
+            .is_none_or(|last| now - last >= LAST_USED_RESOLUTION)
+        {
+            if let Err(e) = self.store.record_use(record.id, now).await {
+                tracing::warn!(api_key_id = %record.id, error = %e, "failed to record API key use");
+            }
+        }
+
+        let scopes = record
+            .scopes
+            .into_iter()
+            .filter(|s| record.owner_scopes.contains(s))
+            .collect();
+        Ok(AuthContext::new(
+            Some(record.user_id),
+            scopes,
+            json!({ "apiKeyId": record.id }),
+        ))
+    }
+}
+
+#[async_trait::async_trait]
+impl AuthenticatorTrait for ApiKeyAuthenticator {
+    async fn authenticate(&self, token: Option<&str>) -> Result<AuthContext, AuthError> {
+        if let Some(raw) = token.filter(|t| !t.trim().is_empty()) {
+            let key = JwtAuthenticator::strip_bearer(raw);
+            if let Some(prefix) = api_key_prefix(key) {
+                return self.authenticate_key(key, prefix).await;
+            }
+        }
+        match &self.fallback {
+            Some(fallback) => fallback.authenticate(token).await,
+            None if token.is_some_and(|t| !t.trim().is_empty()) => {
+                Err(AuthError::InvalidTokenFormat)
+            }
+            None => Ok(AuthContext::anonymous()),
+        }
+    }
+}
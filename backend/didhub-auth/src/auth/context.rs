@@ -13,6 +13,18 @@ pub struct AuthContext {
     pub user_id: Option<Uuid>,
     pub scopes: Vec<String>,
     pub metadata: Value,
+    /// `jti` claim of the token this context was built from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_id: Option<String>,
+    /// `exp` claim (unix seconds) of the token this context was built from, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+    /// Admin acting as `user_id`, from the token's `act` claim (impersonation).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<Uuid>,
+    /// Service client a machine token was issued to, from the token's `client_id` claim.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<Uuid>,
 }
 
 impl AuthContext {
@@ -23,9 +35,47 @@ impl AuthContext {
             user_id,
             scopes,
             metadata,
+            token_id: None,
+            expires_at: None,
+            actor: None,
+            client_id: None,
         }
     }
 
+    /// Attach the identity and expiry of the token that produced this context.
+    #[inline]
+    pub fn with_token(mut self, token_id: Option<String>, expires_at: Option<u64>) -> Self {
+        self.token_id = token_id;
+        self.expires_at = expires_at;
+        self
+    }
+
+    /// Mark this context as an impersonation performed by `actor`.
+    #[inline]
+    pub fn with_actor(mut self, actor: Option<Uuid>) -> Self {
+        self.actor = actor;
+        self
+    }
+
+    /// Mark this context as a machine token of the service client `client_id`.
+    #[inline]
+    pub fn with_client(mut self, client_id: Option<Uuid>) -> Self {
+        self.client_id = client_id;
+        self
+    }
+
+    /// Indicates if the request was made by a service client rather than a user.
+    #[inline]
+    pub fn is_service(&self) -> bool {
+        self.client_id.is_some()
+    }
+
+    /// Indicates if the request was made by an admin impersonating `user_id`.
+    #[inline]
+    pub fn is_impersonated(&self) -> bool {
+        self.actor.is_some()
+    }
+
     /// Helper for anonymous requests.
     #[inline]
     pub fn anonymous() -> Self {
@@ -44,10 +94,10 @@ impl AuthContext {
         self.scopes.iter().any(|s| s == scope)
     }
 
-    /// Check if the user is an admin.
+    /// Check if the user is an admin (owners are admins too).
     #[inline]
     pub fn is_admin(&self) -> bool {
-        self.has_scope("admin")
+        self.has_scope("admin") || self.has_scope("owner")
     }
 }
 
@@ -68,6 +118,8 @@ pub enum AuthError {
     AuthenticationFailed,
     #[error("token expired")]
     TokenExpired,
+    #[error("token has been revoked")]
+    TokenRevoked,
     #[error("invalid token format")]
     InvalidTokenFormat,
     #[error("authentication subsystem is unavailable: {0}")]
@@ -87,4 +139,6 @@ pub enum PasswordError {
     InvalidHashFormat,
     #[error("client hash validation failed: expected 64 hex characters")]
     InvalidClientHash,
+    #[error("hash was peppered with version {0}, which is not configured")]
+    UnknownPepper(u32),
 }
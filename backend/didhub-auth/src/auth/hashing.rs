@@ -6,12 +6,68 @@ use argon2::{
     },
     Argon2,
 };
+use hmac::{Hmac, Mac};
 use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
 
 // ============================================================================
 // Password Hashing
 // ============================================================================
 
+/// Prefix of stored hashes whose input was peppered. It is followed by the pepper
+/// version and the Argon2 PHC string, e.g. `$dhp1$argon2id$v=19$...`.
+pub const PEPPER_PREFIX: &str = "$dhp";
+
+/// A server-side secret mixed into passwords before hashing.
+///
+/// The password is replaced by `HMAC-SHA256(pepper, password)` (hex) before it
+/// reaches Argon2, so a leaked database alone is not enough to test guesses.
+/// The version is stored in the hash prefix; hashes without the prefix are
+/// unpeppered, so both kinds can coexist while accounts are migrated on login.
+#[derive(Clone)]
+pub struct Pepper {
+    version: u32,
+    key: Vec<u8>,
+}
+
+impl Pepper {
+    pub fn new(version: u32, secret: impl AsRef<[u8]>) -> Self {
+        Self {
+            version,
+            key: secret.as_ref().to_vec(),
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    fn apply(&self, password: &str) -> String {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any length");
+        mac.update(password.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+impl fmt::Debug for Pepper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pepper")
+            .field("version", &self.version)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Split a peppered hash into its pepper version and PHC string.
+fn split_peppered(stored_hash: &str) -> Option<(u32, &str)> {
+    let rest = stored_hash.strip_prefix(PEPPER_PREFIX)?;
+    let end = rest.find('$')?;
+    let version = rest[..end].parse().ok()?;
+    Some((version, &rest[end..]))
+}
+
 /// Password hasher using Argon2id (the recommended variant for password hashing).
 #[derive(Debug, Clone)]
 pub struct Argon2Hasher {
@@ -21,6 +77,8 @@ pub struct Argon2Hasher {
     t_cost: u32,
     /// Parallelism factor (default: 1)
     p_cost: u32,
+    /// Pepper applied to new hashes; `None` hashes passwords as given.
+    pepper: Option<Pepper>,
 }
 
 impl Default for Argon2Hasher {
@@ -30,15 +88,104 @@ impl Default for Argon2Hasher {
             m_cost: 19456, // 19 MiB
             t_cost: 2,
             p_cost: 1,
+            pepper: None,
         }
     }
 }
 
+/// Upper bound for the memory cost picked by [`Argon2Hasher::tune`] (64 MiB), so
+/// that a burst of concurrent logins cannot exhaust the host's memory.
+const TUNE_MAX_MEMORY_KIB: u32 = 64 * 1024;
+
+/// Upper bound for the time cost picked by [`Argon2Hasher::tune`].
+const TUNE_MAX_TIME_COST: u32 = 10;
+
+/// Hasher used by the convenience functions below; `None` means the defaults.
+static DEFAULT_HASHER: RwLock<Option<Argon2Hasher>> = RwLock::new(None);
+
 impl Argon2Hasher {
     pub fn new() -> Self {
         Self::default()
     }
 
+    /// Pick costs so that hashing a password takes about `target_ms` on this host.
+    ///
+    /// Starts from the defaults (never going below them), doubles the memory cost
+    /// up to 64 MiB and then raises the time cost, as long as a measured hash stays
+    /// within the target. Parallelism is left at 1. This blocks for a few multiples
+    /// of `target_ms`, so call it off the async runtime.
+    pub fn tune(target_ms: u64) -> Self {
+        let target = Duration::from_millis(target_ms);
+        let mut best = Self::default();
+        if best.measure() >= target {
+            return best;
+        }
+        while best.m_cost * 2 <= TUNE_MAX_MEMORY_KIB {
+            let next = best.clone().with_memory_cost(best.m_cost * 2);
+            if next.measure() > target {
+                break;
+            }
+            best = next;
+        }
+        while best.t_cost < TUNE_MAX_TIME_COST {
+            let next = best.clone().with_time_cost(best.t_cost + 1);
+            if next.measure() > target {
+                break;
+            }
+            best = next;
+        }
+        best
+    }
+
+    fn measure(&self) -> Duration {
+        let start = Instant::now();
+        let _ = self.hash("didhub-argon2-benchmark");
+        start.elapsed()
+    }
+
+    /// Memory cost in KiB.
+    pub fn memory_cost(&self) -> u32 {
+        self.m_cost
+    }
+
+    /// Time cost (iterations).
+    pub fn time_cost(&self) -> u32 {
+        self.t_cost
+    }
+
+    /// Parallelism factor.
+    pub fn parallelism(&self) -> u32 {
+        self.p_cost
+    }
+
+    /// Costs in PHC parameter notation, e.g. `m=19456,t=2,p=1`.
+    pub fn params_string(&self) -> String {
+        format!("m={},t={},p={}", self.m_cost, self.t_cost, self.p_cost)
+    }
+
+    /// Parse costs written by [`Argon2Hasher::params_string`].
+    pub fn parse_params(params: &str) -> Result<Self, PasswordError> {
+        let mut hasher = Self::default();
+        for pair in params.split(',') {
+            let (name, value) = pair
+                .trim()
+                .split_once('=')
+                .ok_or(PasswordError::InvalidHashFormat)?;
+            let value: u32 = value
+                .parse()
+                .map_err(|_| PasswordError::InvalidHashFormat)?;
+            match name {
+                "m" => hasher.m_cost = value,
+                "t" => hasher.t_cost = value,
+                "p" => hasher.p_cost = value,
+                _ => return Err(PasswordError::InvalidHashFormat),
+            }
+        }
+        argon2::Params::new(hasher.m_cost, hasher.t_cost, hasher.p_cost, None)
+            .map_err(|_| PasswordError::InvalidHashFormat)?;
+        Ok(hasher)
+    }
+
     /// Configure memory cost in KiB.
     pub fn with_memory_cost(mut self, kib: u32) -> Self {
         self.m_cost = kib;
@@ -57,6 +204,18 @@ impl Argon2Hasher {
         self
     }
 
+    /// Pepper new hashes. Hashes made with this pepper's version can be verified;
+    /// unpeppered hashes still verify and are reported by [`Argon2Hasher::needs_rehash`].
+    pub fn with_pepper(mut self, pepper: Option<Pepper>) -> Self {
+        self.pepper = pepper;
+        self
+    }
+
+    /// Version of the pepper applied to new hashes, if any.
+    pub fn pepper_version(&self) -> Option<u32> {
+        self.pepper.as_ref().map(Pepper::version)
+    }
+
     fn argon2(&self) -> Argon2<'_> {
         Argon2::new(
             argon2::Algorithm::Argon2id,
@@ -66,15 +225,21 @@ impl Argon2Hasher {
         )
     }
 
-    /// Hash a password, returning the PHC-format hash string.
+    /// Hash a password, returning the PHC-format hash string (with the pepper
+    /// prefix if a pepper is configured).
     pub fn hash(&self, password: &str) -> Result<String, PasswordError> {
         let salt = SaltString::generate(&mut OsRng);
         let argon2 = self.argon2();
 
-        argon2
-            .hash_password(password.as_bytes(), &salt)
-            .map(|h| h.to_string())
-            .map_err(|e| PasswordError::HashingFailed(e.to_string()))
+        match &self.pepper {
+            Some(pepper) => argon2
+                .hash_password(pepper.apply(password).as_bytes(), &salt)
+                .map(|h| format!("{}{}{}", PEPPER_PREFIX, pepper.version, h)),
+            None => argon2
+                .hash_password(password.as_bytes(), &salt)
+                .map(|h| h.to_string()),
+        }
+        .map_err(|e| PasswordError::HashingFailed(e.to_string()))
     }
 
     /// Hash a client-side pre-hashed password.
@@ -84,15 +249,56 @@ impl Argon2Hasher {
     }
 
     /// Verify a password against a stored PHC-format hash.
+    ///
+    /// Legacy bcrypt hashes (`$2a$`, `$2b$`, `$2y$`) are verified with bcrypt;
+    /// callers should then replace them, see [`Argon2Hasher::needs_rehash`].
     pub fn verify(&self, password: &str, stored_hash: &str) -> Result<(), PasswordError> {
-        let parsed =
-            PasswordHash::new(stored_hash).map_err(|_| PasswordError::InvalidHashFormat)?;
+        if is_bcrypt_hash(stored_hash) {
+            return match bcrypt::verify(password, stored_hash) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(PasswordError::VerificationFailed),
+                Err(_) => Err(PasswordError::InvalidHashFormat),
+            };
+        }
+
+        let (input, phc) = match split_peppered(stored_hash) {
+            Some((version, phc)) => match &self.pepper {
+                Some(pepper) if pepper.version == version => (pepper.apply(password), phc),
+                _ => return Err(PasswordError::UnknownPepper(version)),
+            },
+            None => (password.to_string(), stored_hash),
+        };
+        let parsed = PasswordHash::new(phc).map_err(|_| PasswordError::InvalidHashFormat)?;
 
         self.argon2()
-            .verify_password(password.as_bytes(), &parsed)
+            .verify_password(input.as_bytes(), &parsed)
             .map_err(|_| PasswordError::VerificationFailed)
     }
 
+    /// Whether `stored_hash` should be replaced by a fresh hash from this hasher
+    /// after a successful verification: true for legacy bcrypt hashes, for hashes
+    /// peppered differently from this hasher (or not at all) and for Argon2 hashes
+    /// using a different variant or different parameters.
+    pub fn needs_rehash(&self, stored_hash: &str) -> bool {
+        let (version, phc) = match split_peppered(stored_hash) {
+            Some((version, phc)) => (Some(version), phc),
+            None => (None, stored_hash),
+        };
+        if version != self.pepper_version() {
+            return true;
+        }
+        let Ok(parsed) = PasswordHash::new(phc) else {
+            return true;
+        };
+        let Ok(params) = argon2::Params::try_from(&parsed) else {
+            return true;
+        };
+        parsed.algorithm != argon2::Algorithm::Argon2id.ident()
+            || params.m_cost() != self.m_cost
+            || params.t_cost() != self.t_cost
+            || params.p_cost() != self.p_cost
+    }
+
     /// Verify a client-side pre-hashed password against a stored hash.
     pub fn verify_client_prehash(
         &self,
@@ -123,6 +329,14 @@ pub fn validate_client_hash(hash: &str) -> Result<(), PasswordError> {
     Ok(())
 }
 
+/// Check if a stored hash was produced by bcrypt (as used by older deployments).
+#[inline]
+pub fn is_bcrypt_hash(stored_hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2y$"]
+        .iter()
+        .any(|prefix| stored_hash.starts_with(prefix))
+}
+
 /// Check if a string looks like a client-side SHA-256 hash (64 hex chars).
 #[inline]
 pub fn is_client_hash(input: &str) -> bool {
@@ -140,26 +354,46 @@ pub fn sha256_hex(input: &str) -> String {
 // Convenience Functions
 // ============================================================================
 
-/// Hash a password using default Argon2id parameters.
+/// Hasher used for new hashes and rehash checks by the functions below.
+pub fn default_hasher() -> Argon2Hasher {
+    DEFAULT_HASHER
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_default()
+}
+
+/// Replace the hasher returned by [`default_hasher`], e.g. with tuned costs.
+pub fn set_default_hasher(hasher: Argon2Hasher) {
+    *DEFAULT_HASHER.write().unwrap_or_else(|e| e.into_inner()) = Some(hasher);
+}
+
+/// Hash a password using the default hasher.
 #[inline]
 pub fn hash_password(password: &str) -> Result<String, PasswordError> {
-    Argon2Hasher::new().hash(password)
+    default_hasher().hash(password)
 }
 
-/// Hash a client pre-hashed password using default Argon2id parameters.
+/// Hash a client pre-hashed password using the default hasher.
 #[inline]
 pub fn hash_client_password(client_hash: &str) -> Result<String, PasswordError> {
-    Argon2Hasher::new().hash_client_prehash(client_hash)
+    default_hasher().hash_client_prehash(client_hash)
 }
 
-/// Verify a password against a stored hash using default parameters.
+/// Verify a password against a stored hash.
 #[inline]
 pub fn verify_password(password: &str, stored_hash: &str) -> Result<(), PasswordError> {
-    Argon2Hasher::new().verify(password, stored_hash)
+    default_hasher().verify(password, stored_hash)
 }
 
 /// Verify a client pre-hashed password against a stored hash.
 #[inline]
 pub fn verify_client_password(client_hash: &str, stored_hash: &str) -> Result<(), PasswordError> {
-    Argon2Hasher::new().verify_client_prehash(client_hash, stored_hash)
+    default_hasher().verify_client_prehash(client_hash, stored_hash)
+}
+
+/// Whether a stored hash should be upgraded to the default hasher's costs.
+#[inline]
+pub fn password_needs_rehash(stored_hash: &str) -> bool {
+    default_hasher().needs_rehash(stored_hash)
 }
@@ -0,0 +1,200 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use jsonwebtoken::jwk::{AlgorithmParameters, Jwk, JwkSet};
+use jsonwebtoken::{Algorithm, DecodingKey};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, warn};
+
+use crate::auth::context::AuthError;
+
+/// How long a fetched key set is trusted before it is fetched again.
+const DEFAULT_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Minimum time between two fetches, so that tokens with made-up `kid`s cannot
+/// make the server hammer the identity provider.
+const DEFAULT_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+struct CachedKeys {
+    keys: Vec<Jwk>,
+    fetched_at: Option<Instant>,
+    attempted_at: Option<Instant>,
+}
+
+/// A JWKS document fetched from `url` and cached in memory.
+///
+/// Keys are selected by the token's `kid`. A `kid` missing from the cached set
+/// triggers a refetch (rate limited by the minimum refresh interval), so keys
+/// rotated by the provider are picked up without a restart. If the provider is
+/// unreachable, previously fetched keys keep being used.
+#[derive(Debug)]
+pub struct JwksCache {
+    url: String,
+    client: reqwest::Client,
+    ttl: Duration,
+    min_refresh_interval: Duration,
+    cached: RwLock<CachedKeys>,
+    refresh: Mutex<()>,
+}
+
+impl JwksCache {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::builder()
+                .timeout(FETCH_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            ttl: DEFAULT_TTL,
+            min_refresh_interval: DEFAULT_MIN_REFRESH_INTERVAL,
+            cached: RwLock::new(CachedKeys::default()),
+            refresh: Mutex::new(()),
+        }
+    }
+
+    /// Set how long a fetched key set is used before it is fetched again.
+    #[must_use]
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Set the minimum time between two fetches.
+    #[must_use]
+    pub fn with_min_refresh_interval(mut self, interval: Duration) -> Self {
+        self.min_refresh_interval = interval;
+        self
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Decoding key and algorithm for a token whose header carries `kid` and `alg`.
+    pub(crate) async fn resolve(
+        &self,
+        kid: Option<&str>,
+        header_alg: Algorithm,
+    ) -> Result<(DecodingKey, Algorithm), AuthError> {
+        let jwk = self.find_key(kid).await?;
+        let algorithm = key_algorithm(&jwk, header_alg)?;
+        let key = DecodingKey::from_jwk(&jwk)
+            .map_err(|e| AuthError::Subsystem(format!("unusable JWKS key: {}", e)))?;
+        Ok((key, algorithm))
+    }
+
+    async fn find_key(&self, kid: Option<&str>) -> Result<Jwk, AuthError> {
+        {
+            let cached = self.cached.read().await;
+            if cached.fetched_at.is_some_and(|at| at.elapsed() < self.ttl) {
+                if let Some(jwk) = select(&cached.keys, kid) {
+                    return Ok(jwk.clone());
+                }
+            }
+        }
+        self.refresh(kid).await
+    }
+
+    async fn refresh(&self, kid: Option<&str>) -> Result<Jwk, AuthError> {
+        let _guard = self.refresh.lock().await;
+
+        // Another request may have refreshed the set while this one waited.
+        let cached_hit = {
+            let cached = self.cached.read().await;
+            let hit = select(&cached.keys, kid).cloned();
+            let fresh = cached.fetched_at.is_some_and(|at| at.elapsed() < self.ttl);
+            if let (true, Some(jwk)) = (fresh, &hit) {
+                return Ok(jwk.clone());
+            }
+            let may_fetch = cached
+                .attempted_at
+                .is_none_or(|at| at.elapsed() >= self.min_refresh_interval);
+            if !may_fetch {
+                return match (hit, cached.fetched_at) {
+                    (Some(jwk), _) => Ok(jwk),
+                    (None, Some(_)) => Err(AuthError::AuthenticationFailed),
+                    (None, None) => Err(AuthError::Subsystem(format!(
+                        "JWKS from {} is not available",
+                        self.url
+                    ))),
+                };
+            }
+            hit
+        };
+
+        self.cached.write().await.attempted_at = Some(Instant::now());
+        match self.fetch().await {
+            Ok(keys) => {
+                let found = select(&keys, kid).cloned();
+                let mut cached = self.cached.write().await;
+                cached.keys = keys;
+                cached.fetched_at = Some(Instant::now());
+                found.ok_or_else(|| {
+                    warn!(kid = ?kid, url = %self.url, "JWT authentication failed: no JWKS key for kid");
+                    AuthError::AuthenticationFailed
+                })
+            }
+            Err(e) => match cached_hit {
+                Some(jwk) => {
+                    warn!(error = %e, url = %self.url, "failed to refresh JWKS; using cached keys");
+                    Ok(jwk)
+                }
+                None => Err(e),
+            },
+        }
+    }
+
+    async fn fetch(&self) -> Result<Vec<Jwk>, AuthError> {
+        let unavailable =
+            |e: reqwest::Error| AuthError::Subsystem(format!("failed to fetch JWKS: {}", e));
+        let set: JwkSet = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(unavailable)?
+            .json()
+            .await
+            .map_err(unavailable)?;
+        debug!(url = %self.url, keys = set.keys.len(), "fetched JWKS");
+        Ok(set.keys)
+    }
+}
+
+/// The key named by `kid`, or the only key of the set when the token has no `kid`.
+fn select<'a>(keys: &'a [Jwk], kid: Option<&str>) -> Option<&'a Jwk> {
+    match kid {
+        Some(kid) => keys
+            .iter()
+            .find(|k| k.common.key_id.as_deref() == Some(kid)),
+        None if keys.len() == 1 => keys.first(),
+        None => None,
+    }
+}
+
+/// Algorithm to verify with: the key's own `alg` if it declares one, which must then
+/// match the token's. Symmetric keys and algorithms are never accepted from a JWKS.
+fn key_algorithm(jwk: &Jwk, header_alg: Algorithm) -> Result<Algorithm, AuthError> {
+    if matches!(jwk.algorithm, AlgorithmParameters::OctetKey(_)) {
+        return Err(AuthError::AuthenticationFailed);
+    }
+    let algorithm = match &jwk.common.key_algorithm {
+        Some(alg) => {
+            Algorithm::from_str(&alg.to_string()).map_err(|_| AuthError::AuthenticationFailed)?
+        }
+        None => header_alg,
+    };
+    if algorithm != header_alg
+        || matches!(
+            algorithm,
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512
+        )
+    {
+        return Err(AuthError::AuthenticationFailed);
+    }
+    Ok(algorithm)
+}
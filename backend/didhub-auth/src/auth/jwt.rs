@@ -1,25 +1,46 @@
 use crate::auth::context::{AuthContext, AuthError};
+use crate::auth::jwks::JwksCache;
+use crate::auth::revocation::RevocationStore;
 use crate::auth::traits::AuthenticatorTrait;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::Arc;
 use tracing::warn;
 use uuid::Uuid;
 
-/// JWT verification options. Supports HS256 (shared secret) and RS256 (RSA public key PEM).
+/// JWT verification options. Supports HS256 (shared secret), RS256 (RSA public key PEM)
+/// and keys fetched from a JWKS endpoint.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum JwtKey {
     /// HMAC-SHA256 shared secret
     Hs256(String),
     /// PEM-encoded RSA public key
     Rs256(String),
+    /// URL of a JWKS document; keys are selected by the token's `kid`
+    Jwks(String),
 }
 
 /// JWT-based authenticator supporting HS256 and RS256 algorithms.
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct JwtAuthenticator {
     key: JwtKey,
     /// Grace period in seconds for token expiration (default: 60)
     exp_grace_seconds: u64,
+    /// Denylist checked after signature verification, if configured.
+    revocations: Option<Arc<dyn RevocationStore>>,
+    /// Key cache for [`JwtKey::Jwks`].
+    jwks: Option<Arc<JwksCache>>,
+}
+
+impl std::fmt::Debug for JwtAuthenticator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtAuthenticator")
+            .field("key", &self.key)
+            .field("exp_grace_seconds", &self.exp_grace_seconds)
+            .field("revocations", &self.revocations.is_some())
+            .field("jwks", &self.jwks)
+            .finish()
+    }
 }
 
 impl JwtAuthenticator {
@@ -27,6 +48,8 @@ impl JwtAuthenticator {
         Self {
             key: JwtKey::Hs256(secret.into()),
             exp_grace_seconds: 60,
+            revocations: None,
+            jwks: None,
         }
     }
 
@@ -34,6 +57,25 @@ impl JwtAuthenticator {
         Self {
             key: JwtKey::Rs256(pem_public_key.into()),
             exp_grace_seconds: 60,
+            revocations: None,
+            jwks: None,
+        }
+    }
+
+    /// Verify tokens with keys fetched from the JWKS document at `url`.
+    ///
+    /// The document is fetched on first use and cached; see [`JwksCache`].
+    pub fn from_jwks_url(url: impl Into<String>) -> Self {
+        Self::from_jwks(JwksCache::new(url))
+    }
+
+    /// Verify tokens with keys from a preconfigured [`JwksCache`].
+    pub fn from_jwks(cache: JwksCache) -> Self {
+        Self {
+            key: JwtKey::Jwks(cache.url().to_string()),
+            exp_grace_seconds: 60,
+            revocations: None,
+            jwks: Some(Arc::new(cache)),
         }
     }
 
@@ -43,6 +85,45 @@ impl JwtAuthenticator {
         self
     }
 
+    /// Reject tokens found in `store` (by `jti`, or issued before a per-user cut-off).
+    pub fn with_revocation_store(mut self, store: Arc<dyn RevocationStore>) -> Self {
+        self.revocations = Some(store);
+        self
+    }
+
+    /// Check the decoded claims against the revocation store, if one is configured.
+    async fn check_revoked(&self, claims: &Claims) -> Result<(), AuthError> {
+        let Some(store) = &self.revocations else {
+            return Ok(());
+        };
+        if let Some(jti) = &claims.jti {
+            if store.is_token_revoked(jti).await? {
+                warn!(sub = ?claims.sub, "JWT authentication failed: token revoked");
+                return Err(AuthError::TokenRevoked);
+            }
+        }
+        // Service clients share the per-user cut-off, keyed by client id
+        let owners = [claims.sub.as_deref(), claims.client_id.as_deref()];
+        for owner in owners.into_iter().flatten() {
+            let Ok(owner) = Uuid::parse_str(owner) else {
+                continue;
+            };
+            if let Some(cutoff_ms) = store.user_tokens_revoked_before(owner).await? {
+                // Without `iat_ms` a token is taken to be issued at the start of its
+                // second; tokens without `iat` predate revocation support and cannot
+                // be dated.
+                let issued_ms = claims
+                    .iat_ms
+                    .or_else(|| claims.iat.map(|iat| iat.saturating_mul(1000)));
+                if issued_ms.is_none_or(|issued_ms| issued_ms < cutoff_ms) {
+                    warn!(sub = %owner, "JWT authentication failed: user sessions revoked");
+                    return Err(AuthError::TokenRevoked);
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Extract and validate claims from a decoded token.
     fn process_claims(&self, claims: Claims) -> Result<AuthContext, AuthError> {
         // Time-based validation
@@ -61,12 +142,32 @@ impl JwtAuthenticator {
             _ => vec!["user".into()],
         };
 
-        Ok(AuthContext::new(sub, scopes, Value::Null))
+        // RFC 8693 actor claim; a malformed one must not silently drop the marker
+        let actor = match claims.act {
+            Some(act) => Some(Uuid::parse_str(&act.sub).map_err(|_| {
+                warn!(sub = ?claims.sub, "JWT authentication failed: invalid act claim");
+                AuthError::AuthenticationFailed
+            })?),
+            None => None,
+        };
+
+        let client_id = match claims.client_id {
+            Some(id) => Some(Uuid::parse_str(&id).map_err(|_| {
+                warn!(client_id = %id, "JWT authentication failed: invalid client_id claim");
+                AuthError::AuthenticationFailed
+            })?),
+            None => None,
+        };
+
+        Ok(AuthContext::new(sub, scopes, Value::Null)
+            .with_token(claims.jti, claims.exp)
+            .with_actor(actor)
+            .with_client(client_id))
     }
 
     /// Strip the "Bearer " prefix from a token if present.
     #[inline]
-    fn strip_bearer(token: &str) -> &str {
+    pub(crate) fn strip_bearer(token: &str) -> &str {
         let token = token.trim();
         if token.len() > 7 && token[..7].eq_ignore_ascii_case("bearer ") {
             &token[7..]
@@ -80,10 +181,26 @@ impl JwtAuthenticator {
 struct Claims {
     sub: Option<String>,
     exp: Option<u64>,
+    /// Issued-at time (unix seconds)
+    iat: Option<u64>,
+    /// Issued-at time (unix milliseconds), set on tokens issued by this server so
+    /// they can be told apart from a revocation in the same second
+    iat_ms: Option<u64>,
+    /// Unique token id, used for revocation
+    jti: Option<String>,
     /// Space-separated scope string (OAuth2 style)
     scope: Option<String>,
     /// Array of scopes
     scopes: Option<Vec<String>>,
+    /// Party acting on behalf of `sub` (RFC 8693), set on impersonation tokens
+    act: Option<ActorClaim>,
+    /// Service client a client-credentials token was issued to
+    client_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ActorClaim {
+    sub: String,
 }
 
 #[async_trait::async_trait]
@@ -106,6 +223,17 @@ impl AuthenticatorTrait for JwtAuthenticator {
                     .map_err(|_| AuthError::Subsystem("invalid RSA public key".into()))?;
                 (key, Algorithm::RS256)
             }
+            JwtKey::Jwks(_) => {
+                let header = jsonwebtoken::decode_header(token).map_err(|e| {
+                    warn!(error = %e, "JWT header decoding failed");
+                    AuthError::InvalidTokenFormat
+                })?;
+                let jwks = self
+                    .jwks
+                    .as_ref()
+                    .ok_or_else(|| AuthError::Subsystem("JWKS key cache missing".into()))?;
+                jwks.resolve(header.kid.as_deref(), header.alg).await?
+            }
         };
 
         let mut validation = Validation::new(algorithm);
@@ -116,6 +244,7 @@ impl AuthenticatorTrait for JwtAuthenticator {
             AuthError::AuthenticationFailed
         })?;
 
+        self.check_revoked(&data.claims).await?;
         self.process_claims(data.claims)
     }
 }
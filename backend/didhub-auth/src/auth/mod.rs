@@ -1,14 +1,32 @@
+pub mod api_key;
 pub mod context;
 pub mod hashing;
+pub mod jwks;
 pub mod jwt;
+pub mod password_policy;
+pub mod permissions;
+pub mod revocation;
 pub mod traits;
 
+pub use api_key::{
+    api_key_prefix, hash_api_key, ApiKeyAuthenticator, ApiKeyRecord, ApiKeyStore, GeneratedApiKey,
+    API_KEY_PREFIX,
+};
 pub use context::{AuthContext, AuthError, PasswordError};
 pub use hashing::{
-    hash_client_password, hash_password, is_client_hash, sha256_hex, validate_client_hash,
-    verify_client_password, verify_password, Argon2Hasher, CLIENT_HASH_LENGTH,
+    default_hasher, hash_client_password, hash_password, is_bcrypt_hash, is_client_hash,
+    password_needs_rehash, set_default_hasher, sha256_hex, validate_client_hash,
+    verify_client_password, verify_password, Argon2Hasher, Pepper, CLIENT_HASH_LENGTH,
+    PEPPER_PREFIX,
 };
+pub use jwks::JwksCache;
 pub use jwt::{JwtAuthenticator, JwtKey};
+pub use password_policy::{
+    PasswordPolicy, PolicyViolation, PwnedPasswordsClient, PwnedPasswordsError,
+    PWNED_PASSWORDS_RANGE_URL, PWNED_PREFIX_LENGTH,
+};
+pub use permissions::{roles_from_names, Permission, Role};
+pub use revocation::RevocationStore;
 pub use traits::AuthenticatorTrait;
 
 use serde_json::Value;
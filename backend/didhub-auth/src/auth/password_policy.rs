@@ -0,0 +1,252 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::auth::hashing::{is_client_hash, sha256_hex};
+
+/// Passwords rejected by every policy, on top of any configured list.
+const COMMON_PASSWORDS: &[&str] = &[
+    "password",
+    "password1",
+    "password123",
+    "passw0rd",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "87654321",
+    "11111111",
+    "00000000",
+    "qwertyui",
+    "qwerty123",
+    "qwertyuiop",
+    "1q2w3e4r",
+    "1qaz2wsx",
+    "abc12345",
+    "abcd1234",
+    "iloveyou",
+    "sunshine",
+    "princess",
+    "football",
+    "baseball",
+    "welcome1",
+    "letmein1",
+    "trustno1",
+    "superman",
+    "starwars",
+    "changeme",
+    "admin123",
+    "didhub123",
+];
+
+/// Default endpoint of the Have I Been Pwned range API.
+pub const PWNED_PASSWORDS_RANGE_URL: &str = "https://api.pwnedpasswords.com/range";
+
+/// Length of the SHA-1 prefix sent to the range API.
+pub const PWNED_PREFIX_LENGTH: usize = 5;
+
+const PWNED_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A rule a password failed to satisfy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PolicyViolation {
+    #[error("password must be at least {0} characters long")]
+    TooShort(usize),
+    #[error("password must contain a lowercase letter")]
+    MissingLowercase,
+    #[error("password must contain an uppercase letter")]
+    MissingUppercase,
+    #[error("password must contain a digit")]
+    MissingDigit,
+    #[error("password must contain a symbol")]
+    MissingSymbol,
+    #[error("password is too common")]
+    Banned,
+}
+
+impl PolicyViolation {
+    /// Stable machine-readable code, used in validation payloads.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::TooShort(_) => "too_short",
+            Self::MissingLowercase => "missing_lowercase",
+            Self::MissingUppercase => "missing_uppercase",
+            Self::MissingDigit => "missing_digit",
+            Self::MissingSymbol => "missing_symbol",
+            Self::Banned => "banned",
+        }
+    }
+}
+
+/// Rules new passwords must satisfy.
+///
+/// Clients send SHA-256 pre-hashes (see [`crate::auth::hashing`]), so the
+/// server can only check those against the banned list with
+/// [`PasswordPolicy::check_client_hash`]. Length and character class rules are
+/// published to the frontend (the serialized form of this struct) and enforced
+/// there with the same semantics as [`PasswordPolicy::check`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    pub require_lowercase: bool,
+    pub require_uppercase: bool,
+    pub require_digit: bool,
+    pub require_symbol: bool,
+    /// Whether clients should check passwords against Have I Been Pwned.
+    pub check_breached: bool,
+    /// SHA-256 hex digests of banned passwords.
+    #[serde(skip)]
+    banned: HashSet<String>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            check_breached: false,
+            banned: COMMON_PASSWORDS.iter().map(|p| sha256_hex(p)).collect(),
+        }
+    }
+}
+
+impl PasswordPolicy {
+    /// Ban additional passwords. Blank entries are ignored.
+    #[must_use]
+    pub fn with_banned_passwords<I, S>(mut self, passwords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.banned.extend(
+            passwords
+                .into_iter()
+                .filter(|p| !p.as_ref().trim().is_empty())
+                .map(|p| sha256_hex(p.as_ref())),
+        );
+        self
+    }
+
+    /// Number of banned passwords, including the built-in list.
+    pub fn banned_count(&self) -> usize {
+        self.banned.len()
+    }
+
+    /// Check a plaintext password against every rule.
+    pub fn check(&self, password: &str) -> Vec<PolicyViolation> {
+        let mut violations = Vec::new();
+        if password.chars().count() < self.min_length {
+            violations.push(PolicyViolation::TooShort(self.min_length));
+        }
+        let rules = [
+            (
+                self.require_lowercase,
+                PolicyViolation::MissingLowercase,
+                char::is_lowercase as fn(char) -> bool,
+            ),
+            (
+                self.require_uppercase,
+                PolicyViolation::MissingUppercase,
+                char::is_uppercase,
+            ),
+            (
+                self.require_digit,
+                PolicyViolation::MissingDigit,
+                |c: char| c.is_ascii_digit(),
+            ),
+            (
+                self.require_symbol,
+                PolicyViolation::MissingSymbol,
+                |c: char| !c.is_alphanumeric() && !c.is_whitespace(),
+            ),
+        ];
+        for (required, violation, matches) in rules {
+            if required && !password.chars().any(matches) {
+                violations.push(violation);
+            }
+        }
+        if self.banned.contains(&sha256_hex(password)) {
+            violations.push(PolicyViolation::Banned);
+        }
+        violations
+    }
+
+    /// Check a client pre-hash against the banned list.
+    pub fn check_client_hash(&self, client_hash: &str) -> Result<(), PolicyViolation> {
+        if is_client_hash(client_hash) && self.banned.contains(&client_hash.to_ascii_lowercase()) {
+            return Err(PolicyViolation::Banned);
+        }
+        Ok(())
+    }
+}
+
+/// Error returned by [`PwnedPasswordsClient`].
+#[derive(Debug, Error)]
+pub enum PwnedPasswordsError {
+    #[error("hash prefix must be {PWNED_PREFIX_LENGTH} hex characters")]
+    InvalidPrefix,
+    #[error("range request failed: {0}")]
+    Request(#[from] reqwest::Error),
+}
+
+/// Client for the Have I Been Pwned k-anonymity range API.
+///
+/// Only the first five hex characters of a password's SHA-1 are sent; the
+/// caller compares the returned suffixes locally.
+#[derive(Debug, Clone)]
+pub struct PwnedPasswordsClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl Default for PwnedPasswordsClient {
+    fn default() -> Self {
+        Self::new(PWNED_PASSWORDS_RANGE_URL)
+    }
+}
+
+impl PwnedPasswordsClient {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            client: reqwest::Client::builder()
+                .timeout(PWNED_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Fetch the `(suffix, count)` pairs of breached hashes starting with `prefix`.
+    pub async fn range(&self, prefix: &str) -> Result<Vec<(String, u64)>, PwnedPasswordsError> {
+        if prefix.len() != PWNED_PREFIX_LENGTH || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(PwnedPasswordsError::InvalidPrefix);
+        }
+        let body = self
+            .client
+            .get(format!("{}/{}", self.base_url, prefix.to_ascii_uppercase()))
+            // Padding hides the real number of matches from anyone watching the traffic.
+            .header("Add-Padding", "true")
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())?
+            .text()
+            .await?;
+        Ok(parse_range(&body))
+    }
+}
+
+/// Parse a range response (`SUFFIX:COUNT` per line), dropping padding entries.
+fn parse_range(body: &str) -> Vec<(String, u64)> {
+    body.lines()
+        .filter_map(|line| {
+            let (suffix, count) = line.trim().split_once(':')?;
+            let count: u64 = count.trim().parse().ok()?;
+            (count > 0).then(|| (suffix.to_string(), count))
+        })
+        .collect()
+}
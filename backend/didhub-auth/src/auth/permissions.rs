@@ -0,0 +1,138 @@
+use serde::Serialize;
+
+use crate::auth::context::AuthContext;
+
+/// An action gated by role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Read systems, alters and other shared content.
+    ViewContent,
+    /// Create and edit one's own content.
+    EditOwnContent,
+    /// Approve or reject requests to become a system account.
+    ReviewSystemRequests,
+    /// Create, edit and delete user accounts.
+    ManageUsers,
+    /// Assign the moderator, member and viewer roles.
+    AssignRoles,
+    /// Instance settings, backups, updates and jobs.
+    ManageInstance,
+    /// Assign or remove the admin and owner roles.
+    ManageAdmins,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 7] = [
+        Permission::ViewContent,
+        Permission::EditOwnContent,
+        Permission::ReviewSystemRequests,
+        Permission::ManageUsers,
+        Permission::AssignRoles,
+        Permission::ManageInstance,
+        Permission::ManageAdmins,
+    ];
+}
+
+/// A role held by a user, stored by name in the user's `roles` and carried as a
+/// token scope.
+///
+/// Roles are ordered from most to least privileged; each role holds every
+/// permission of the roles below it. The member role keeps its historical name
+/// `user` (which also marks an account as approved). `system` is an account
+/// type, not a role, and grants no permissions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Role {
+    Owner,
+    Admin,
+    Moderator,
+    Member,
+    Viewer,
+}
+
+impl Role {
+    pub const ALL: [Role; 5] = [
+        Role::Owner,
+        Role::Admin,
+        Role::Moderator,
+        Role::Member,
+        Role::Viewer,
+    ];
+
+    /// Name stored in `users.roles` and in token scopes.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Owner => "owner",
+            Role::Admin => "admin",
+            Role::Moderator => "moderator",
+            Role::Member => "user",
+            Role::Viewer => "viewer",
+        }
+    }
+
+    /// Parse a stored role name; `member` is accepted as an alias for `user`.
+    pub fn from_name(name: &str) -> Option<Role> {
+        match name {
+            "owner" => Some(Role::Owner),
+            "admin" => Some(Role::Admin),
+            "moderator" => Some(Role::Moderator),
+            "user" | "member" => Some(Role::Member),
+            "viewer" => Some(Role::Viewer),
+            _ => None,
+        }
+    }
+
+    /// Permissions granted by this role, including those of lower roles.
+    pub fn permissions(&self) -> &'static [Permission] {
+        use Permission::*;
+        match self {
+            Role::Owner => &Permission::ALL,
+            Role::Admin => &[
+                ViewContent,
+                EditOwnContent,
+                ReviewSystemRequests,
+                ManageUsers,
+                AssignRoles,
+                ManageInstance,
+            ],
+            Role::Moderator => &[ViewContent, EditOwnContent, ReviewSystemRequests],
+            Role::Member => &[ViewContent, EditOwnContent],
+            Role::Viewer => &[ViewContent],
+        }
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.permissions().contains(&permission)
+    }
+
+    /// Permission needed to grant or remove this role.
+    pub fn required_to_assign(&self) -> Permission {
+        match self {
+            Role::Owner | Role::Admin => Permission::ManageAdmins,
+            _ => Permission::AssignRoles,
+        }
+    }
+}
+
+/// Roles named in `names`, ignoring anything that is not a role.
+pub fn roles_from_names<S: AsRef<str>>(names: &[S]) -> Vec<Role> {
+    let mut roles: Vec<Role> = names
+        .iter()
+        .filter_map(|n| Role::from_name(n.as_ref()))
+        .collect();
+    roles.sort();
+    roles.dedup();
+    roles
+}
+
+impl AuthContext {
+    /// Roles carried by this context's scopes, most privileged first.
+    pub fn roles(&self) -> Vec<Role> {
+        roles_from_names(&self.scopes)
+    }
+
+    /// Whether any of this context's roles grants `permission`.
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.roles().iter().any(|r| r.has_permission(permission))
+    }
+}
@@ -0,0 +1,24 @@
+use crate::auth::context::AuthError;
+use uuid::Uuid;
+
+/// Storage for revoked tokens, consulted by [`crate::auth::JwtAuthenticator`] on every request.
+///
+/// Tokens are revoked individually by their `jti` claim (logout), or all at once for a
+/// user by recording a cut-off time: tokens issued before it are rejected. The cut-off
+/// has millisecond precision so a token issued right after a revocation stays valid;
+/// tokens are dated by their `iat_ms` claim, or by `iat` when they lack one.
+#[async_trait::async_trait]
+pub trait RevocationStore: Send + Sync + 'static {
+    /// Revoke the token `jti`. `expires_at` (unix seconds) is the token's own expiry,
+    /// after which the entry can be forgotten.
+    async fn revoke_token(&self, jti: &str, expires_at: Option<u64>) -> Result<(), AuthError>;
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool, AuthError>;
+
+    /// Revoke every token issued to `user_id` (or to the service client with that id) up to now.
+    async fn revoke_user_tokens(&self, user_id: Uuid) -> Result<(), AuthError>;
+
+    /// Unix time in milliseconds before which tokens issued to `user_id` are revoked,
+    /// if any.
+    async fn user_tokens_revoked_before(&self, user_id: Uuid) -> Result<Option<u64>, AuthError>;
+}
@@ -1,9 +1,12 @@
 //! Lightweight authentication facade used by the backend service.
 //!
 //! Provides:
-//! - JWT token verification (HS256/RS256)
-//! - Password hashing with Argon2id
+//! - JWT token verification (HS256/RS256, or keys from a remote JWKS)
+//! - Scoped API keys for non-interactive clients
+//! - Roles and the permissions they grant
+//! - Password hashing with Argon2id (legacy bcrypt hashes are verified for upgrade)
 //! - Client-side hash validation (for pre-hashed passwords from frontend)
+//! - Password strength policies and Have I Been Pwned range lookups
 //! - Authentication context and error types
 
 pub mod auth;
@@ -0,0 +1,74 @@
+//! API key storage backed by the `api_keys` table.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use didhub_auth::auth::{ApiKeyRecord, ApiKeyStore, AuthError};
+use didhub_db::generated::api_keys as db_api_keys;
+use didhub_db::generated::users as db_users;
+use uuid::Uuid;
+
+fn subsystem(e: impl std::fmt::Display) -> AuthError {
+    AuthError::Subsystem(format!("api key store: {}", e))
+}
+
+fn parse_timestamp(value: Option<&str>) -> Result<Option<DateTime<Utc>>, AuthError> {
+    value
+        .map(|s| {
+            DateTime::parse_from_rfc3339(s)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(subsystem)
+        })
+        .transpose()
+}
+
+/// [`ApiKeyStore`] reading keys and their owners' roles from the database.
+#[derive(Debug, Clone)]
+pub struct DbApiKeyStore {
+    pool: Arc<didhub_db::DbPool>,
+}
+
+impl DbApiKeyStore {
+    pub fn new(pool: Arc<didhub_db::DbPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for DbApiKeyStore {
+    async fn find_by_prefix(&self, prefix: &str) -> Result<Option<ApiKeyRecord>, AuthError> {
+        let Some(key) = db_api_keys::find_first_by_key_prefix(&*self.pool, &prefix.to_string())
+            .await
+            .map_err(subsystem)?
+        else {
+            return Ok(None);
+        };
+        let Some(owner) = db_users::find_by_primary_key(&*self.pool, &key.user_id)
+            .await
+            .map_err(subsystem)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some(ApiKeyRecord {
+            id: key.id,
+            user_id: key.user_id,
+            scopes: serde_json::from_str(&key.scopes).map_err(subsystem)?,
+            owner_scopes: serde_json::from_str(&owner.roles).unwrap_or_default(),
+            expires_at: parse_timestamp(key.expires_at.as_deref())?,
+            last_used_at: parse_timestamp(key.last_used_at.as_deref())?,
+            key_hash: key.key_hash,
+        }))
+    }
+
+    async fn record_use(&self, id: Uuid, at: DateTime<Utc>) -> Result<(), AuthError> {
+        sqlx::query("UPDATE api_keys SET last_used_at = ? WHERE id = ?")
+            .bind(at.to_rfc3339())
+            .bind(id)
+            .execute(&*self.pool)
+            .await
+            .map_err(subsystem)?;
+        Ok(())
+    }
+}
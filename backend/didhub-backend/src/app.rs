@@ -1,12 +1,14 @@
 use std::sync::Arc;
 
 use crate::rate_limiter::RateLimiterManager;
+use axum::extract::{ConnectInfo, MatchedPath};
 use axum::http::HeaderValue;
 use axum::middleware::Next;
 use axum::{
-    body::Body, extract::DefaultBodyLimit, extract::Extension, http::Request, http::StatusCode,
-    middleware, response::IntoResponse, response::Response, routing::get, Router,
+    body::Body, extract::DefaultBodyLimit, extract::Extension, extract::Query, http::Request,
+    http::StatusCode, middleware, response::IntoResponse, response::Response, routing::get, Router,
 };
+use std::collections::HashMap;
 use std::convert::Infallible;
 
 use crate::{generated, state::AppState};
@@ -64,13 +66,43 @@ pub fn build_router_with_limiter(state: Arc<AppState>, limiter: RateLimiterManag
         .route(
             "/auth/refresh",
             axum::routing::post(crate::handlers::auth::refresh::refresh),
+        )
+        .route(
+            "/auth/device-login",
+            axum::routing::post(crate::handlers::auth::device_login::device_login),
+        )
+        .route(
+            "/auth/password-reset/request",
+            axum::routing::post(crate::handlers::auth::password_reset_request::request),
+        )
+        .route(
+            "/auth/password-reset/confirm",
+            axum::routing::post(crate::handlers::auth::password_reset_confirm::confirm),
+        )
+        .route(
+            "/oauth/introspect",
+            axum::routing::post(crate::handlers::oauth::introspect::introspect),
+        )
+        .route(
+            "/oauth/token",
+            axum::routing::post(crate::handlers::oauth::token::token),
+        )
+        .route(
+            "/oauth/device_authorization",
+            axum::routing::post(crate::handlers::oauth::device_authorization::device_authorization),
+        )
+        .route(
+            "/oauth/device",
+            axum::routing::get(crate::handlers::oauth::device::describe)
+                .post(crate::handlers::oauth::device::decide),
         );
     // register generated application routes
     let router = generated::routes::register_routes(router);
     // health and readiness endpoints
     let router = router
         .route("/health", get(health_handler))
-        .route("/ready", get(ready_handler));
+        .route("/ready", get(ready_handler))
+        .route("/metrics", get(metrics_handler));
     // Use rate limiter provided by main via Extension
     // clone state for middleware closure so the original `state` can still be used
     let mw_state = state.clone();
@@ -135,9 +167,16 @@ pub fn build_router_with_limiter(state: Arc<AppState>, limiter: RateLimiterManag
                 }
             },
         ))
+        .layer(middleware::from_fn(
+            crate::permissions::enforce_read_only_roles,
+        ))
+        .layer(middleware::from_fn(
+            crate::impersonation::audit_impersonated_requests,
+        ))
         .layer(middleware::from_fn(crate::csrf::csrf_protect))
         .layer(DefaultBodyLimit::max(DEFAULT_BODY_LIMIT))
-        .layer(Extension(state));
+        .layer(Extension(state))
+        .layer(middleware::from_fn(record_request_duration));
 
     // Serve embedded frontend files via fallback route
     Router::new()
@@ -145,6 +184,24 @@ pub fn build_router_with_limiter(state: Arc<AppState>, limiter: RateLimiterManag
         .fallback(crate::embedded_assets::serve_asset)
 }
 
+/// Time each request into the latency histogram, labelled by method and the
+/// matched route template.
+async fn record_request_duration(req: Request<Body>, next: Next) -> Response {
+    let method = req.method().clone();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_owned());
+    let started = std::time::Instant::now();
+    let response = next.run(req).await;
+    didhub_metrics::observe_http_request(
+        method.as_str(),
+        route.as_deref().unwrap_or(didhub_metrics::UNMATCHED_ROUTE),
+        started.elapsed(),
+    );
+    response
+}
+
 fn remote_addr_key(req: &Request<Body>) -> String {
     if let Some(ext) = req.extensions().get::<std::net::SocketAddr>() {
         return ext.ip().to_string();
@@ -152,18 +209,73 @@ fn remote_addr_key(req: &Request<Body>) -> String {
     "unknown".to_string()
 }
 
-async fn health_handler() -> impl IntoResponse {
+/// Whether `?deep=true` (or `?deep=1`) asks for dependency probes.
+fn wants_deep_check(query: &HashMap<String, String>) -> bool {
+    query.get("deep").is_some_and(|v| v == "true" || v == "1")
+}
+
+/// Run the dependency probes and answer 503 when any of them is down.
+async fn deep_check(state: &AppState) -> Response {
+    let checks = crate::health::check_dependencies(state).await;
+    let healthy = checks.iter().all(|check| check.up);
+    let status = if healthy {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = serde_json::json!({
+        "status": if healthy { "ok" } else { "fail" },
+        "checks": checks,
+    });
+    (status, axum::Json(body)).into_response()
+}
+
+async fn health_handler(
+    Extension(state): Extension<Arc<AppState>>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    if wants_deep_check(&query) {
+        return deep_check(&state).await;
+    }
     // Liveness: always return 200 OK when process is alive.
-    (axum::http::StatusCode::OK, "OK")
+    (StatusCode::OK, "OK").into_response()
 }
 
 async fn ready_handler(
-    axum::extract::Extension(_state): axum::extract::Extension<Arc<AppState>>,
-) -> impl IntoResponse {
+    Extension(state): Extension<Arc<AppState>>,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    if wants_deep_check(&query) {
+        return deep_check(&state).await;
+    }
     // Readiness: ensure the service is not in maintenance mode. We determine maintenance by checking whether
     // the authenticator is present. Tests may inject TestAuthenticator which counts as ready.
     // If the authenticator is absent (shouldn't happen with current wiring), return 503 Service Unavailable.
-    // Additionally, we could check DB connectivity here; for now we only ensure the authenticator exists.
+    // Dependency probes only run with ?deep=true so frequent orchestrator polls stay cheap.
     // Note: AppState always contains an authenticator in normal runs; the maintenance router will not use this handler.
-    (axum::http::StatusCode::OK, "OK")
+    (StatusCode::OK, "OK").into_response()
+}
+
+/// Prometheus metrics; `didhub_dependency_up` reflects the latest deep check, the
+/// rest comes from the default registry (process and runtime metrics). Guarded by
+/// the `metrics.bearer_token` / `metrics.allowed_ips` settings when they are set.
+async fn metrics_handler(
+    Extension(state): Extension<Arc<AppState>>,
+    req: Request<Body>,
+) -> Response {
+    let peer_ip = req
+        .extensions()
+        .get::<ConnectInfo<std::net::SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    if let Err(status) = state.metrics_access().check(req.headers(), peer_ip) {
+        return status.into_response();
+    }
+    (
+        [(
+            axum::http::header::CONTENT_TYPE,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )],
+        state.dependencies().render() + &didhub_metrics::render(),
+    )
+        .into_response()
 }
@@ -0,0 +1,146 @@
+//! Field-level diffs of entity updates for the audit trail, and their retention.
+//!
+//! Changes older than `audit.retention_days` are moved to the `audit_archive`
+//! table or deleted, depending on `audit.retention_mode`, by the
+//! [`AUDIT_RETENTION_JOB`] job. Entity history can still include archived rows.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use didhub_db::custom::audit_changes;
+use didhub_job_queue::{async_trait, JobExecutor, JobQueueError};
+use serde_json::{json, Map, Value};
+
+use crate::state::AppState;
+
+/// Job type of the executor archiving or purging old audit changes.
+pub const AUDIT_RETENTION_JOB: &str = "audit.retention";
+
+/// Fields of `after` whose value differs from `before`, as `{field: {old, new}}`.
+/// Both sides are expected to be JSON objects, e.g. serialized rows; fields only
+/// present on one side are reported with `null` on the other.
+pub fn field_diff(before: &Value, after: &Value) -> Map<String, Value> {
+    let empty = Map::new();
+    let before = before.as_object().unwrap_or(&empty);
+    let after = after.as_object().unwrap_or(&empty);
+
+    let mut changes = Map::new();
+    for (field, new) in after {
+        let old = before.get(field).unwrap_or(&Value::Null);
+        if old != new {
+            changes.insert(field.clone(), json!({ "old": old, "new": new }));
+        }
+    }
+    for (field, old) in before {
+        if !after.contains_key(field) {
+            changes.insert(field.clone(), json!({ "old": old, "new": Value::Null }));
+        }
+    }
+    changes
+}
+
+/// What happens to audit changes past the retention period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionMode {
+    /// Move them to `audit_archive`.
+    Archive,
+    /// Delete them.
+    Purge,
+}
+
+/// Settings taken from `audit.retention_days` and `audit.retention_mode`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRetention {
+    pub retention: Duration,
+    pub mode: RetentionMode,
+}
+
+impl Default for AuditRetention {
+    fn default() -> Self {
+        Self {
+            retention: Duration::from_secs(365 * 24 * 60 * 60),
+            mode: RetentionMode::Archive,
+        }
+    }
+}
+
+impl AuditRetention {
+    pub fn from_config(cfg: &didhub_config::Config) -> Self {
+        Self {
+            retention: Duration::from_secs(cfg.audit.retention_days * 24 * 60 * 60),
+            // Validated when the configuration is loaded
+            mode: match cfg.audit.retention_mode.as_str() {
+                "purge" => RetentionMode::Purge,
+                _ => RetentionMode::Archive,
+            },
+        }
+    }
+}
+
+/// Archive or purge the audit changes recorded longer than the retention period
+/// ago. Returns the number of rows moved or deleted.
+pub async fn apply_retention(
+    pool: &didhub_db::DbPool,
+    settings: &AuditRetention,
+) -> Result<u64, sqlx::Error> {
+    let retention = chrono::Duration::from_std(settings.retention).unwrap_or(chrono::Duration::MAX);
+    let now = Utc::now();
+    let cutoff = now
+        .checked_sub_signed(retention)
+        .unwrap_or(chrono::DateTime::<Utc>::MIN_UTC)
+        .to_rfc3339();
+    match settings.mode {
+        RetentionMode::Archive => {
+            audit_changes::archive_older_than(pool, &cutoff, &now.to_rfc3339()).await
+        }
+        RetentionMode::Purge => audit_changes::purge_older_than(pool, &cutoff).await,
+    }
+}
+
+/// Executor for [`AUDIT_RETENTION_JOB`]; schedule it under
+/// `[scheduler.jobs."audit.retention"]`.
+pub struct AuditRetentionExecutor {
+    state: AppState,
+}
+
+impl AuditRetentionExecutor {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl JobExecutor for AuditRetentionExecutor {
+    fn job_type(&self) -> &str {
+        AUDIT_RETENTION_JOB
+    }
+
+    async fn execute(&self, _payload: Value) -> Result<(), JobQueueError> {
+        let settings = self.state.audit_retention();
+        let count = apply_retention(&self.state.db_pool, &settings)
+            .await
+            .map_err(|e| JobQueueError::ExecutionFailed(e.to_string()))?;
+        match settings.mode {
+            RetentionMode::Archive => tracing::info!(count, "archived old audit changes"),
+            RetentionMode::Purge => tracing::info!(count, "purged old audit changes"),
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_reports_changed_added_and_removed_fields() {
+        let before = json!({ "name": "Willow", "age": "30", "notes": "x" });
+        let after = json!({ "name": "Rowan", "age": "30", "job": "gardener" });
+        let diff = field_diff(&before, &after);
+        assert_eq!(diff.len(), 3);
+        assert_eq!(diff["name"], json!({ "old": "Willow", "new": "Rowan" }));
+        assert_eq!(diff["job"], json!({ "old": null, "new": "gardener" }));
+        assert_eq!(diff["notes"], json!({ "old": "x", "new": null }));
+        assert!(field_diff(&after, &after).is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use didhub_auth::auth::{ApiKeyAuthenticator, ApiKeyStore, AuthenticatorTrait, RevocationStore};
 use sha2::{Digest, Sha256};
 use simple_asn1::{from_der, ASN1Block};
 
@@ -16,24 +17,55 @@ pub type AuthResult = Result<(Arc<dyn didhub_auth::auth::AuthenticatorTrait>, Au
 
 /// Build authenticator from config.
 ///
-/// Tries JWT_PEM (inline), JWT_PEM_PATH (file), then JWT_SECRET in order.
-pub fn build_authenticator_from_config(cfg: &didhub_config::Config) -> AuthResult {
+/// Tries JWT_PEM (inline), JWT_PEM_PATH (file), JWKS_URL, then JWT_SECRET in order.
+/// Every token is also checked against `revocations`. `dhk_` API keys are
+/// checked against `api_keys` instead of being decoded as JWTs.
+pub fn build_authenticator_from_config(
+    cfg: &didhub_config::Config,
+    revocations: Arc<dyn RevocationStore>,
+    api_keys: Arc<dyn ApiKeyStore>,
+) -> AuthResult {
+    let (jwt, info) = build_jwt_authenticator(cfg, revocations)?;
+    let auth = ApiKeyAuthenticator::new(api_keys).with_fallback(jwt);
+    Ok((Arc::new(auth) as Arc<dyn AuthenticatorTrait>, info))
+}
+
+fn build_jwt_authenticator(
+    cfg: &didhub_config::Config,
+    revocations: Arc<dyn RevocationStore>,
+) -> AuthResult {
     // Try inline PEM first
     if let Some(ref pem_inline) = cfg.auth.jwt_pem {
-        return build_rs256_auth(pem_inline.clone(), "RS256(inline)".into());
+        return build_rs256_auth(pem_inline.clone(), "RS256(inline)".into(), revocations);
     }
 
     // Try PEM from file path
     if let Some(ref pem_path) = cfg.auth.jwt_pem_path {
         let pem_content = std::fs::read_to_string(pem_path)
             .map_err(|e| format!("failed to read JWT_PEM_PATH '{pem_path}': {e}"))?;
-        return build_rs256_auth(pem_content, format!("RS256(path={pem_path})"));
+        return build_rs256_auth(pem_content, format!("RS256(path={pem_path})"), revocations);
+    }
+
+    // Try keys from a remote JWKS document
+    if let Some(ref url) = cfg.auth.jwks_url {
+        let auth = didhub_auth::auth::JwtAuthenticator::from_jwks_url(url.clone())
+            .with_revocation_store(revocations);
+        return Ok((
+            Arc::from(Box::new(auth) as Box<dyn didhub_auth::auth::AuthenticatorTrait>),
+            AuthKeyInfo {
+                mode: format!("JWKS(url={url})"),
+                fingerprint: None,
+                key_type: Some("JWKS".into()),
+                bits: None,
+            },
+        ));
     }
 
     // Try HS256 secret
     if let Some(ref secret) = cfg.auth.jwt_secret {
         let fingerprint = compute_fingerprint(secret.as_bytes());
-        let auth = didhub_auth::auth::JwtAuthenticator::new_hs256(secret.clone());
+        let auth = didhub_auth::auth::JwtAuthenticator::new_hs256(secret.clone())
+            .with_revocation_store(revocations);
         return Ok((
             Arc::from(Box::new(auth) as Box<dyn didhub_auth::auth::AuthenticatorTrait>),
             AuthKeyInfo {
@@ -46,19 +78,24 @@ pub fn build_authenticator_from_config(cfg: &didhub_config::Config) -> AuthResul
     }
 
     Err(
-        "no JWT configuration found: set JWT_PEM (inline) or JWT_PEM_PATH (file) or JWT_SECRET"
+        "no JWT configuration found: set JWT_PEM (inline) or JWT_PEM_PATH (file) or JWKS_URL or JWT_SECRET"
             .into(),
     )
 }
 
 /// Build RS256 authenticator from PEM content string.
-fn build_rs256_auth(pem_content: String, mode: String) -> AuthResult {
+fn build_rs256_auth(
+    pem_content: String,
+    mode: String,
+    revocations: Arc<dyn RevocationStore>,
+) -> AuthResult {
     let pem_parsed =
         pem::parse(pem_content.as_bytes()).map_err(|e| format!("failed to parse PEM: {e}"))?;
 
     let fingerprint = compute_fingerprint(pem_parsed.contents());
     let (key_type, bits) = extract_key_info(pem_parsed.contents());
-    let auth = didhub_auth::auth::JwtAuthenticator::new_rs256(pem_content);
+    let auth = didhub_auth::auth::JwtAuthenticator::new_rs256(pem_content)
+        .with_revocation_store(revocations);
 
     Ok((
         Arc::from(Box::new(auth) as Box<dyn didhub_auth::auth::AuthenticatorTrait>),
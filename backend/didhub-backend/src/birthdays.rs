@@ -0,0 +1,306 @@
+//! Recurring alter birthdays.
+//!
+//! Birthdays are stored as text; [`parse_birthday`] reads `YYYY-MM-DD`, `MM-DD`
+//! and `--MM-DD`. Which day it is comes from `birthdays.timezone`, and a 29
+//! February birthday falls on 28 February in other years. The birthday calendar
+//! endpoint and the [`BIRTHDAY_DIGEST_JOB`] both list birthdays with [`upcoming`].
+
+use chrono::{Datelike, NaiveDate, Utc};
+use chrono_tz::Tz;
+use didhub_db::custom::alters::AlterFilter;
+use didhub_db::generated::alters as db_alters;
+use didhub_db::generated::user_emails as db_user_emails;
+use didhub_job_queue::{async_trait, JobExecutor, JobQueueError};
+use serde_json::{json, Value};
+
+use crate::mailer::OutgoingEmail;
+use crate::state::AppState;
+
+/// Job type of the executor emailing upcoming birthdays.
+pub const BIRTHDAY_DIGEST_JOB: &str = "birthdays.digest";
+
+/// Settings taken from `birthdays.timezone` and `birthdays.digest_days`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BirthdaySettings {
+    pub timezone: Tz,
+    pub digest_days: u32,
+}
+
+impl Default for BirthdaySettings {
+    fn default() -> Self {
+        Self {
+            timezone: Tz::UTC,
+            digest_days: 7,
+        }
+    }
+}
+
+impl BirthdaySettings {
+    pub fn from_config(cfg: &didhub_config::Config) -> Self {
+        Self {
+            // Validated when the configuration is loaded
+            timezone: cfg.birthdays.timezone.parse().unwrap_or(Tz::UTC),
+            digest_days: cfg.birthdays.digest_days,
+        }
+    }
+
+    /// The current date in the configured time zone.
+    pub fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.timezone).date_naive()
+    }
+}
+
+/// Month and day of a birthday, with the year when it is known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Birthday {
+    pub year: Option<i32>,
+    pub month: u32,
+    pub day: u32,
+}
+
+/// Read a stored birthday; a time after the date is ignored.
+pub fn parse_birthday(text: &str) -> Option<Birthday> {
+    let text = text.trim();
+    let date = text.split('T').next()?;
+    if let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        return Some(Birthday {
+            year: Some(date.year()),
+            month: date.month(),
+            day: date.day(),
+        });
+    }
+    let (month, day) = date.strip_prefix("--").unwrap_or(date).split_once('-')?;
+    let (month, day) = (month.parse().ok()?, day.parse().ok()?);
+    // A leap year, so 29 February is accepted
+    NaiveDate::from_ymd_opt(2000, month, day)?;
+    Some(Birthday {
+        year: None,
+        month,
+        day,
+    })
+}
+
+/// The date `birthday` is celebrated in `year`.
+fn in_year(birthday: &Birthday, year: i32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, birthday.month, birthday.day)
+        .or_else(|| NaiveDate::from_ymd_opt(year, 2, 28))
+        .expect("28 February exists")
+}
+
+/// The next time a birthday comes round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Occurrence {
+    pub date: NaiveDate,
+    /// 0 when it is today.
+    pub days_until: i64,
+    /// Age reached that day, when the birth year is known.
+    pub turning: Option<i32>,
+}
+
+/// The first celebration of `birthday` on or after `today`.
+pub fn next_occurrence(birthday: &Birthday, today: NaiveDate) -> Occurrence {
+    let mut date = in_year(birthday, today.year());
+    if date < today {
+        date = in_year(birthday, today.year() + 1);
+    }
+    Occurrence {
+        date,
+        days_until: (date - today).num_days(),
+        turning: birthday
+            .year
+            .map(|year| date.year() - year)
+            .filter(|age| *age > 0),
+    }
+}
+
+/// Alters of `rows` with a birthday in the `days` days from `today` (today
+/// included), soonest first.
+pub fn upcoming(
+    rows: &[db_alters::AltersRow],
+    today: NaiveDate,
+    days: u32,
+) -> Vec<(&db_alters::AltersRow, Occurrence)> {
+    let mut items: Vec<_> = rows
+        .iter()
+        .filter_map(|row| {
+            let birthday = parse_birthday(row.birthday.as_deref()?)?;
+            Some((row, next_occurrence(&birthday, today)))
+        })
+        .filter(|(_, occurrence)| occurrence.days_until < i64::from(days))
+        .collect();
+    items.sort_by(|(a, x), (b, y)| x.date.cmp(&y.date).then_with(|| a.name.cmp(&b.name)));
+    items
+}
+
+pub fn occurrence_to_payload(row: &db_alters::AltersRow, occurrence: &Occurrence) -> Value {
+    json!({
+        "id": row.id,
+        "name": row.name,
+        "systemId": row.user_id,
+        "birthday": row.birthday,
+        "date": occurrence.date.to_string(),
+        "daysUntil": occurrence.days_until,
+        "turning": occurrence.turning,
+    })
+}
+
+/// Plain-text digest of `items`.
+fn digest_body(items: &[(&db_alters::AltersRow, Occurrence)], days: u32) -> String {
+    let mut body = format!("Birthdays in your system over the next {days} days:\n\n");
+    for (row, occurrence) in items {
+        let when = match occurrence.days_until {
+            0 => "today".to_string(),
+            1 => "tomorrow".to_string(),
+            n => format!("{} (in {n} days)", occurrence.date.format("%A %-d %B")),
+        };
+        match occurrence.turning {
+            Some(age) => body.push_str(&format!("- {}: {when}, turning {age}\n", row.name)),
+            None => body.push_str(&format!("- {}: {when}\n", row.name)),
+        }
+    }
+    body
+}
+
+/// Executor for [`BIRTHDAY_DIGEST_JOB`]; schedule it under
+/// `[scheduler.jobs."birthdays.digest"]`. Systems with an email address get their
+/// alters' birthdays of the next `birthdays.digest_days` days.
+pub struct BirthdayDigestExecutor {
+    state: AppState,
+}
+
+impl BirthdayDigestExecutor {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl JobExecutor for BirthdayDigestExecutor {
+    fn job_type(&self) -> &str {
+        BIRTHDAY_DIGEST_JOB
+    }
+
+    async fn execute(&self, _payload: Value) -> Result<(), JobQueueError> {
+        let Some(mailer) = self.state.mailer() else {
+            tracing::info!("smtp is not configured; skipping birthday digest");
+            return Ok(());
+        };
+        let failed = |e: sqlx::Error| JobQueueError::ExecutionFailed(e.to_string());
+        let settings = self.state.birthdays();
+        let today = settings.today();
+
+        let mut conn = self
+            .state
+            .db_pool
+            .acquire()
+            .await
+            .map_err(|e| JobQueueError::ExecutionFailed(e.to_string()))?;
+        let mut sent = 0;
+        for email in db_user_emails::list_all(&mut *conn).await.map_err(failed)? {
+            let alters = didhub_db::custom::alters::list_active(
+                &mut *conn,
+                &AlterFilter::system(&email.user_id),
+            )
+            .await
+            .map_err(failed)?;
+            let items = upcoming(&alters, today, settings.digest_days);
+            if items.is_empty() {
+                continue;
+            }
+            let message = OutgoingEmail {
+                to: email.email,
+                subject: "Upcoming birthdays".to_string(),
+                body: digest_body(&items, settings.digest_days),
+            };
+            match mailer.send(message).await {
+                Ok(()) => sent += 1,
+                Err(e) => {
+                    tracing::error!(user_id = %email.user_id, error = %e, "failed to send birthday digest")
+                }
+            }
+        }
+        tracing::info!(sent, "sent birthday digests");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parses_dates_with_and_without_year() {
+        assert_eq!(
+            parse_birthday("1990-03-14"),
+            Some(Birthday {
+                year: Some(1990),
+                month: 3,
+                day: 14
+            })
+        );
+        let no_year = Some(Birthday {
+            year: None,
+            month: 2,
+            day: 29,
+        });
+        assert_eq!(parse_birthday("02-29"), no_year);
+        assert_eq!(parse_birthday("--02-29"), no_year);
+        assert_eq!(parse_birthday("2001-07-01T00:00:00Z").unwrap().month, 7);
+        assert_eq!(parse_birthday("02-30"), None);
+        assert_eq!(parse_birthday("spring"), None);
+    }
+
+    #[test]
+    fn next_occurrence_wraps_into_next_year() {
+        let birthday = parse_birthday("2000-01-02").unwrap();
+        let next = next_occurrence(&birthday, date(2025, 12, 30));
+        assert_eq!(next.date, date(2026, 1, 2));
+        assert_eq!(next.days_until, 3);
+        assert_eq!(next.turning, Some(26));
+
+        let today = next_occurrence(&birthday, date(2026, 1, 2));
+        assert_eq!(today.days_until, 0);
+    }
+
+    #[test]
+    fn leap_day_birthdays_fall_on_28_february_in_other_years() {
+        let birthday = parse_birthday("02-29").unwrap();
+        assert_eq!(
+            next_occurrence(&birthday, date(2025, 2, 1)).date,
+            date(2025, 2, 28)
+        );
+        assert_eq!(
+            next_occurrence(&birthday, date(2028, 2, 1)).date,
+            date(2028, 2, 29)
+        );
+        // Past 28 February in a common year, the next one is in the leap year
+        assert_eq!(
+            next_occurrence(&birthday, date(2027, 3, 1)).date,
+            date(2028, 2, 29)
+        );
+    }
+
+    #[test]
+    fn today_follows_the_configured_time_zone() {
+        let settings = |timezone: Tz| BirthdaySettings {
+            timezone,
+            digest_days: 7,
+        };
+        let utc = Utc::now().date_naive();
+        for tz in [
+            chrono_tz::Pacific::Kiritimati,
+            chrono_tz::Pacific::Pago_Pago,
+        ] {
+            let offset = (settings(tz).today() - utc).num_days();
+            assert!((-1..=1).contains(&offset));
+        }
+        assert!(
+            settings(chrono_tz::Pacific::Kiritimati).today()
+                > settings(chrono_tz::Pacific::Pago_Pago).today()
+        );
+    }
+}
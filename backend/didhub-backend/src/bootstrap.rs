@@ -2,15 +2,15 @@ use didhub_backend::state::AppState;
 use didhub_db::generated::users as db_users;
 
 /// Provision an admin user from environment variables if no admin exists.
-/// Env vars: DIDHUB_ADMIN_USERNAME, DIDHUB_ADMIN_PASSWORD, DIDHUB_ADMIN_DISPLAY_NAME (optional)
+/// Env vars: DIDHUB_ADMIN_USERNAME, DIDHUB_ADMIN_PASSWORD (or DIDHUB_ADMIN_PASSWORD_FILE), DIDHUB_ADMIN_DISPLAY_NAME (optional)
 pub async fn maybe_provision_admin(state: &AppState) -> anyhow::Result<()> {
     // Read env vars
     let username = match std::env::var("DIDHUB_ADMIN_USERNAME") {
         Ok(u) if !u.is_empty() => u,
         _ => return Ok(()),
     };
-    let password = match std::env::var("DIDHUB_ADMIN_PASSWORD") {
-        Ok(p) if !p.is_empty() => p,
+    let password = match didhub_config::env_secret("DIDHUB_ADMIN_PASSWORD")? {
+        Some(p) if !p.is_empty() => p,
         _ => {
             tracing::warn!("DIDHUB_ADMIN_USERNAME set but DIDHUB_ADMIN_PASSWORD missing; skipping admin provisioning");
             return Ok(());
@@ -45,7 +45,7 @@ pub async fn maybe_provision_admin(state: &AppState) -> anyhow::Result<()> {
         display_name,
         created_at: now.clone(),
         updated_at: now,
-        roles: "[\"admin\",\"system\",\"user\"]".to_string(),
+        roles: "[\"owner\",\"admin\",\"system\",\"user\"]".to_string(),
         settings: "{}".to_string(),
     };
 
@@ -53,3 +53,68 @@ pub async fn maybe_provision_admin(state: &AppState) -> anyhow::Result<()> {
     tracing::info!(username=%username, "provisioned initial admin user");
     Ok(())
 }
+
+/// Migrate instances created before roles existed: if nobody holds the `owner`
+/// role, grant it to the oldest admin.
+pub async fn maybe_promote_owner(state: &AppState) -> anyhow::Result<()> {
+    let mut conn = state.db_pool.acquire().await?;
+    let has_owner: Option<i64> =
+        sqlx::query_scalar("SELECT 1 FROM users WHERE roles LIKE '%\"owner\"%' LIMIT 1")
+            .fetch_optional(&mut *conn)
+            .await?;
+    if has_owner.is_some() {
+        return Ok(());
+    }
+
+    let oldest_admin: Option<db_users::UsersRow> = sqlx::query_as(
+        "SELECT * FROM users WHERE roles LIKE '%\"admin\"%' ORDER BY created_at ASC LIMIT 1",
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    let Some(mut admin) = oldest_admin else {
+        return Ok(());
+    };
+
+    let mut roles: Vec<String> = serde_json::from_str(&admin.roles).unwrap_or_default();
+    roles.insert(0, "owner".to_string());
+    admin.roles = serde_json::to_string(&roles)?;
+    admin.updated_at = chrono::Utc::now().to_rfc3339();
+    db_users::update_by_primary_key(&mut *conn, &admin.id, &admin).await?;
+    tracing::info!(username = %admin.username, "granted owner role to oldest admin");
+    Ok(())
+}
+
+/// Insert a seed dataset (`--seed`) and write the files its uploads point at.
+/// Seeded accounts get the password from DIDHUB_SEED_PASSWORD (or
+/// DIDHUB_SEED_PASSWORD_FILE), defaulting to `didhub-demo`.
+pub async fn seed_database(
+    state: &AppState,
+    dataset: didhub_db::seed::Dataset,
+    uploads_dir: &str,
+) -> anyhow::Result<()> {
+    let password = didhub_config::env_secret("DIDHUB_SEED_PASSWORD")?
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| "didhub-demo".to_string());
+    let password_hash =
+        didhub_auth::auth::hash_password(&password).map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    let report = didhub_db::seed::seed(&state.db_pool, dataset, &password_hash).await?;
+
+    let dir = std::path::Path::new(uploads_dir);
+    std::fs::create_dir_all(dir)?;
+    for file in didhub_db::seed::files(dataset) {
+        let path = dir.join(file.id.to_string());
+        if !path.exists() {
+            std::fs::write(&path, file.content)?;
+        }
+    }
+
+    tracing::info!(
+        %dataset,
+        inserted = report.inserted,
+        existing = report.existing,
+        users = ?didhub_db::seed::DEMO_USERNAMES,
+        "seeded database"
+    );
+    Ok(())
+}
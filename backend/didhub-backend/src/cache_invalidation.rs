@@ -0,0 +1,95 @@
+//! Dropping cached entries on every instance when their source changes.
+//!
+//! With Postgres, [`didhub_cache::AppCache::invalidate`] publishes through
+//! `NOTIFY`; with another database it uses Redis pub/sub when `redis_url` points
+//! at Redis. A single instance on another database without Redis needs neither.
+
+use std::time::Duration;
+
+use didhub_cache::{
+    AppCache, CacheError, Invalidation, InvalidationBus, RedisInvalidationBus, INVALIDATION_CHANNEL,
+};
+use didhub_db::DbPool;
+use didhub_job_queue::async_trait;
+
+/// Wait before subscribing again after the subscription failed.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Invalidations sent with Postgres `NOTIFY`.
+pub struct PgNotifyBus {
+    pool: DbPool,
+}
+
+#[async_trait]
+impl InvalidationBus for PgNotifyBus {
+    fn name(&self) -> &'static str {
+        "postgres"
+    }
+
+    async fn publish(&self, invalidation: &Invalidation) -> Result<(), CacheError> {
+        didhub_db::notify::notify(&self.pool, INVALIDATION_CHANNEL, &invalidation.to_payload())
+            .await
+            .map_err(|e| CacheError::Backend(e.to_string()))
+    }
+}
+
+async fn apply(cache: &AppCache, payload: &str) {
+    match Invalidation::from_payload(payload) {
+        Ok(invalidation) => {
+            if let Err(e) = cache.apply(&invalidation).await {
+                tracing::warn!(%e, "failed to apply cache invalidation");
+            }
+        }
+        Err(e) => tracing::warn!(%e, "ignoring malformed cache invalidation"),
+    }
+}
+
+async fn listen_postgres(pool: DbPool, cache: AppCache) {
+    loop {
+        match didhub_db::notify::listen(&pool, INVALIDATION_CHANNEL).await {
+            Ok(Some(mut listener)) => loop {
+                match listener.recv().await {
+                    Ok(payload) => apply(&cache, &payload).await,
+                    Err(e) => {
+                        tracing::warn!(%e, "cache invalidation listener failed");
+                        break;
+                    }
+                }
+            },
+            Ok(None) => return,
+            Err(e) => tracing::warn!(%e, "failed to listen for cache invalidations"),
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+}
+
+async fn listen_redis(bus: RedisInvalidationBus, cache: AppCache) {
+    loop {
+        if let Err(e) = bus.listen(&cache).await {
+            tracing::warn!(%e, "cache invalidation subscription failed");
+        }
+        tokio::time::sleep(RETRY_DELAY).await;
+    }
+}
+
+/// Give `cache` the bus for this deployment and spawn the task applying the
+/// invalidations other instances publish.
+pub async fn attach(cache: AppCache, pool: &DbPool, redis_url: Option<&str>) -> AppCache {
+    if didhub_db::notify::SUPPORTED {
+        tokio::spawn(listen_postgres(pool.clone(), cache.clone()));
+        return cache.with_bus(PgNotifyBus { pool: pool.clone() });
+    }
+    let Some(url) = redis_url.filter(|u| didhub_cache::is_redis_url(u)) else {
+        return cache;
+    };
+    match RedisInvalidationBus::connect(url).await {
+        Ok(bus) => {
+            tokio::spawn(listen_redis(bus.clone(), cache.clone()));
+            cache.with_bus(bus)
+        }
+        Err(e) => {
+            tracing::error!(%e, "failed to connect to redis_url; cache invalidations stay local");
+            cache
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Loading hot cache entries before the server accepts traffic.
+//!
+//! `cache.warm_up` lists the entries as `<namespace>:<key>`, where a key of `*`
+//! loads every entry of the namespace. Only namespaces with a loader here can be
+//! warmed; the others are reported and skipped.
+
+use std::time::{Duration, Instant};
+
+use didhub_db::generated::instance_settings::list_all;
+
+use crate::error::ApiError;
+use crate::handlers::instance_settings::helpers::{
+    cached_instance_setting, prime_instance_setting, SETTINGS_CACHE,
+};
+use crate::state::AppState;
+
+/// Longest the warm-up may delay startup; entries not loaded by then are loaded
+/// on first use.
+const WARM_UP_TIMEOUT: Duration = Duration::from_secs(30);
+
+async fn warm_entry(state: &AppState, namespace: &str, key: &str) -> Result<usize, ApiError> {
+    match (namespace, key) {
+        (SETTINGS_CACHE, "*") => {
+            let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+            let rows = list_all(&mut *conn).await.map_err(ApiError::from)?;
+            let count = rows.len();
+            for row in rows {
+                prime_instance_setting(state, row).await?;
+            }
+            Ok(count)
+        }
+        (SETTINGS_CACHE, key) => Ok(cached_instance_setting(state, key).await?.map_or(0, |_| 1)),
+        _ => {
+            tracing::warn!(
+                namespace,
+                "cache.warm_up names a namespace without a loader"
+            );
+            Ok(0)
+        }
+    }
+}
+
+async fn warm_all(state: &AppState, manifest: &[String]) -> usize {
+    let mut loaded = 0;
+    for entry in manifest {
+        let Some((namespace, key)) = entry.split_once(':') else {
+            tracing::warn!(entry, "ignoring cache.warm_up entry without a namespace");
+            continue;
+        };
+        match warm_entry(state, namespace, key).await {
+            Ok(count) => loaded += count,
+            Err(e) => tracing::warn!(%e, entry, "failed to warm cache entry"),
+        }
+    }
+    loaded
+}
+
+/// Load the entries of `manifest` into the cache, returning how many were loaded.
+/// Failures are logged; the entries are then loaded on first use instead.
+pub async fn warm_up(state: &AppState, manifest: &[String]) -> usize {
+    if manifest.is_empty() {
+        return 0;
+    }
+    let started = Instant::now();
+    match tokio::time::timeout(WARM_UP_TIMEOUT, warm_all(state, manifest)).await {
+        Ok(loaded) => {
+            tracing::info!(
+                loaded,
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                "cache warmed up"
+            );
+            loaded
+        }
+        Err(_) => {
+            tracing::warn!(
+                timeout_secs = WARM_UP_TIMEOUT.as_secs(),
+                "cache warm-up timed out; starting with a partly cold cache"
+            );
+            0
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 
 /// DIDHub Backend
 #[derive(Parser, Debug)]
@@ -9,10 +9,139 @@ pub struct CliArgs {
     #[arg(short, long, name = "PATH")]
     pub config_path: Option<String>,
 
-    /// Override the log filter.
+    /// Overlay configuration file applied on top of the base config.
+    /// May be repeated; later overlays win. Overrides DIDHUB_CONFIG_OVERLAYS env var.
+    #[arg(long = "config-overlay", name = "OVERLAY")]
+    pub config_overlays: Vec<String>,
+
+    /// Print the effective configuration (files + environment, secrets redacted) and exit.
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Populate the database with a seed dataset at startup (currently: demo).
+    /// Existing seed rows are left untouched, so this is safe on every start.
+    #[arg(long, value_name = "DATASET")]
+    pub seed: Option<didhub_db::seed::Dataset>,
+
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// One-off commands run instead of the server.
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// Inspect or change the database schema version, then exit.
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone, Copy)]
+pub enum MigrateAction {
+    /// List applied and pending migrations.
+    Status,
+    /// Apply all pending migrations.
+    Up,
+    /// Revert the most recently applied migrations.
+    Down {
+        /// Number of migrations to revert.
+        #[arg(value_name = "N", default_value_t = 1)]
+        steps: usize,
+    },
+}
+
+/// Config fields settable from the command line.
+///
+/// Flags take precedence over environment variables and config files, and are
+/// re-applied whenever the configuration is reloaded.
+#[derive(Args, Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// Address to bind (server.host).
+    #[arg(long, value_name = "HOST")]
+    pub host: Option<String>,
+
+    /// Port to listen on (server.port).
+    #[arg(short, long, value_name = "PORT")]
+    pub port: Option<u16>,
+
+    /// Database driver: sqlite, postgres or mysql (database.driver).
+    #[arg(long, value_name = "DRIVER")]
+    pub db_driver: Option<String>,
+
+    /// Database URL or SQLite file path (database.path).
+    #[arg(long, value_name = "URL")]
+    pub db_url: Option<String>,
+
+    /// Directory for uploaded files (uploads.directory).
+    #[arg(long, value_name = "DIR")]
+    pub uploads_dir: Option<String>,
+
+    /// Override the log filter (logging.level).
     /// Examples: 'info', 'debug,sqlx=warn', 'didhub_backend=trace'.
-    #[arg(short = 'L', long, name = "FILTER")]
+    #[arg(short = 'L', long, value_name = "FILTER")]
     pub log_level: Option<String>,
+
+    /// Emit JSON logs (logging.json).
+    #[arg(long, value_name = "BOOL")]
+    pub log_json: Option<bool>,
+
+    /// Enable or disable rate limiting (rate_limit.enabled).
+    #[arg(long, value_name = "BOOL")]
+    pub rate_limit_enabled: Option<bool>,
+
+    /// Apply rate limits per client IP (rate_limit.per_ip).
+    #[arg(long, value_name = "BOOL")]
+    pub rate_limit_per_ip: Option<bool>,
+
+    /// Apply rate limits per authenticated user (rate_limit.per_user).
+    #[arg(long, value_name = "BOOL")]
+    pub rate_limit_per_user: Option<bool>,
+
+    /// Sustained requests per second (rate_limit.rate_per_sec).
+    #[arg(long, value_name = "RATE")]
+    pub rate_limit_per_sec: Option<f64>,
+
+    /// Burst size (rate_limit.burst).
+    #[arg(long, value_name = "N")]
+    pub rate_limit_burst: Option<usize>,
+
+    /// Comma-separated paths exempt from rate limiting (rate_limit.exempt_paths).
+    #[arg(long, value_name = "PATHS", value_delimiter = ',')]
+    pub rate_limit_exempt_paths: Option<Vec<String>>,
+}
+
+impl ConfigOverrides {
+    /// Apply every flag that was given on top of `cfg`.
+    pub fn apply(&self, cfg: &mut didhub_config::Config) {
+        fn set<T: Clone>(target: &mut T, value: &Option<T>) {
+            if let Some(v) = value {
+                *target = v.clone();
+            }
+        }
+
+        set(&mut cfg.server.host, &self.host);
+        set(&mut cfg.server.port, &self.port);
+        set(&mut cfg.database.driver, &self.db_driver);
+        if let Some(url) = &self.db_url {
+            cfg.database.path = Some(url.clone());
+        }
+        set(&mut cfg.uploads.directory, &self.uploads_dir);
+        set(&mut cfg.logging.level, &self.log_level);
+        set(&mut cfg.logging.json, &self.log_json);
+        set(&mut cfg.rate_limit.enabled, &self.rate_limit_enabled);
+        set(&mut cfg.rate_limit.per_ip, &self.rate_limit_per_ip);
+        set(&mut cfg.rate_limit.per_user, &self.rate_limit_per_user);
+        set(&mut cfg.rate_limit.rate_per_sec, &self.rate_limit_per_sec);
+        set(&mut cfg.rate_limit.burst, &self.rate_limit_burst);
+        set(
+            &mut cfg.rate_limit.exempt_paths,
+            &self.rate_limit_exempt_paths,
+        );
+    }
 }
 
 impl CliArgs {
@@ -39,3 +39,23 @@ pub async fn service_unavailable_handler() -> impl IntoResponse {
     );
     (StatusCode::SERVICE_UNAVAILABLE, body)
 }
+
+/// Bind a Unix domain socket at `path` with the given permission bits.
+///
+/// A stale socket left behind by a previous run is removed first; any other kind of
+/// file at that path is left alone and reported as an error.
+#[cfg(unix)]
+pub fn bind_unix_socket(path: &str, mode: u32) -> anyhow::Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        anyhow::ensure!(
+            meta.file_type().is_socket(),
+            "refusing to replace non-socket file at {path}"
+        );
+        std::fs::remove_file(path)?;
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    Ok(listener)
+}
@@ -1,28 +1,45 @@
 use std::sync::Arc;
+use std::time::Duration;
 
+use didhub_backend::audit::AuditRetention;
+use didhub_backend::birthdays::BirthdaySettings;
+use didhub_backend::mailer::mailer_from_config;
+use didhub_backend::metrics_access::MetricsAccess;
+use didhub_backend::password_policy::policy_from_config;
+use didhub_backend::password_reset::PasswordResetSettings;
 use didhub_backend::rate_limiter::RateLimiterManager;
 use didhub_backend::state::AppState;
 use didhub_job_queue::JobQueueClient;
 use tokio::sync::RwLock;
 
 use crate::auth_builder::build_authenticator_from_config;
+use crate::cli::ConfigOverrides;
 use crate::tracing_setup::ReloadHandle;
 
+/// Everything needed to apply a new configuration at runtime.
+#[derive(Clone)]
+pub struct ConfigReloadContext {
+    pub shared_config: Arc<RwLock<didhub_config::Config>>,
+    pub reload_handle: Option<ReloadHandle>,
+    pub app_state: Option<Arc<AppState>>,
+    pub shared_limiter: Arc<RwLock<RateLimiterManager>>,
+    pub job_queue: JobQueueClient,
+    /// Command-line flags, re-applied on top of every reloaded config.
+    pub cli_overrides: ConfigOverrides,
+}
+
 /// Spawn the background configuration reloader task.
 ///
 /// This task periodically checks for configuration changes and hot-reloads:
 /// - Log level
 /// - Log client
 /// - Authenticator
+/// - Feature flags and password policy
 /// - Rate limiter
 pub fn spawn_config_reloader(
-    config_path: Option<String>,
+    config_paths: Vec<String>,
     interval_hours: u64,
-    shared_config: Arc<RwLock<didhub_config::Config>>,
-    reload_handle: Option<ReloadHandle>,
-    app_state: Option<Arc<AppState>>,
-    shared_limiter: Arc<RwLock<RateLimiterManager>>,
-    job_queue: JobQueueClient,
+    ctx: ConfigReloadContext,
 ) {
     tokio::spawn(async move {
         let mut interval =
@@ -30,46 +47,130 @@ pub fn spawn_config_reloader(
 
         loop {
             interval.tick().await;
+            reload_from_files(&config_paths, &ctx).await;
+        }
+    });
+}
 
-            match didhub_config::load_config(config_path.as_deref()) {
-                Ok(new_cfg) => {
-                    if let Err(e) = didhub_config::validate_config(&new_cfg) {
-                        tracing::error!(%e, "loaded config failed validation, ignoring");
-                        continue;
-                    }
-
-                    let mut guard = shared_config.write().await;
-                    if *guard != new_cfg {
-                        let old = guard.clone();
-                        *guard = new_cfg.clone();
-                        drop(guard); // Release lock before potentially slow operations
-
-                        tracing::info!("configuration changed, enqueuing reload job");
-
-                        // Hot-reload log level
-                        reload_log_level(&old, &new_cfg, &reload_handle);
-
-                        // Hot-reload state components
-                        if let Some(ref state) = app_state {
-                            reload_authenticator(&new_cfg, state);
-                        }
-
-                        // Hot-reload rate limiter
-                        reload_rate_limiter(&new_cfg, &shared_limiter).await;
-
-                        // Enqueue job for any other reload processing
-                        let payload = serde_json::json!({"old": old, "new": new_cfg});
-                        let _ = job_queue
-                            .enqueue(didhub_job_queue::JobRequest::new("config.reload", payload))
-                            .await;
-                    }
-                }
-                Err(e) => tracing::error!(%e, "failed to reload config file"),
-            }
+/// Spawn a task that applies every config published by a file watcher.
+///
+/// Unlike [`spawn_config_reloader`] this reacts as soon as the file changes;
+/// parsing, command-line overrides and validation already happened in the
+/// watcher (see [`didhub_config::ConfigWatcher::spawn_layered_with_overrides`]).
+pub fn spawn_config_watcher(
+    mut updates: tokio::sync::watch::Receiver<didhub_config::Config>,
+    ctx: ConfigReloadContext,
+) {
+    tokio::spawn(async move {
+        while updates.changed().await.is_ok() {
+            let new_cfg = updates.borrow_and_update().clone();
+            apply_config_change(new_cfg, &ctx).await;
         }
     });
 }
 
+/// Spawn a task that reloads the configuration files whenever the process receives SIGHUP.
+#[cfg(unix)]
+pub fn spawn_sighup_reloader(
+    config_paths: Vec<String>,
+    ctx: ConfigReloadContext,
+) -> std::io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    tokio::spawn(async move {
+        while hangups.recv().await.is_some() {
+            tracing::info!("received SIGHUP, reloading configuration");
+            reload_from_files(&config_paths, &ctx).await;
+        }
+    });
+    Ok(())
+}
+
+/// Re-read and validate the config files, then apply the result.
+async fn reload_from_files(config_paths: &[String], ctx: &ConfigReloadContext) {
+    match didhub_config::load_config_layered(config_paths) {
+        Ok(mut new_cfg) => {
+            ctx.cli_overrides.apply(&mut new_cfg);
+            if let Err(e) = didhub_config::validate_config(&new_cfg) {
+                tracing::error!(%e, "loaded config failed validation, ignoring");
+                return;
+            }
+            apply_config_change(new_cfg, ctx).await;
+        }
+        Err(e) => tracing::error!(%e, "failed to reload config file"),
+    }
+}
+
+/// Store `new_cfg` and hot-reload the affected subsystems if it differs from the current config.
+async fn apply_config_change(new_cfg: didhub_config::Config, ctx: &ConfigReloadContext) {
+    let ConfigReloadContext {
+        shared_config,
+        reload_handle,
+        app_state,
+        shared_limiter,
+        job_queue,
+        ..
+    } = ctx;
+
+    let mut guard = shared_config.write().await;
+    if *guard == new_cfg {
+        return;
+    }
+    let old = guard.clone();
+    *guard = new_cfg.clone();
+    drop(guard); // Release lock before potentially slow operations
+
+    tracing::info!("configuration changed, enqueuing reload job");
+
+    let restart_required = restart_required_fields(&old, &new_cfg);
+    if !restart_required.is_empty() {
+        tracing::warn!(
+            fields = ?restart_required,
+            "changed configuration fields only take effect after a restart"
+        );
+    }
+
+    // Hot-reload log level
+    reload_log_level(&old, &new_cfg, reload_handle);
+
+    // Hot-reload state components
+    if let Some(state) = app_state.as_deref() {
+        reload_authenticator(&new_cfg, state);
+        if old.features != new_cfg.features {
+            state.set_features(new_cfg.features.clone());
+            tracing::info!("feature flags updated at runtime");
+        }
+        if old.password_policy != new_cfg.password_policy {
+            reload_password_policy(&new_cfg, state);
+        }
+        if old.smtp != new_cfg.smtp {
+            state.set_mailer(mailer_from_config(&new_cfg.smtp));
+            tracing::info!("smtp configuration updated at runtime");
+        }
+        state.set_password_reset(PasswordResetSettings::from_config(&new_cfg));
+        state.set_device_token_ttl(Duration::from_secs(
+            new_cfg.auth.device_token_ttl_days * 24 * 60 * 60,
+        ));
+        state.set_trash_retention(Duration::from_secs(
+            new_cfg.trash.retention_days * 24 * 60 * 60,
+        ));
+        state.set_birthdays(BirthdaySettings::from_config(&new_cfg));
+        state.set_audit_retention(AuditRetention::from_config(&new_cfg));
+        state.set_metrics_access(MetricsAccess::from_config(&new_cfg));
+    }
+    didhub_metrics::set_feature_enabled("updater", new_cfg.auto_update.enabled);
+
+    // Hot-reload rate limiter
+    reload_rate_limiter(&new_cfg, shared_limiter).await;
+
+    // Enqueue job for any other reload processing
+    let payload = serde_json::json!({"old": old, "new": new_cfg});
+    let _ = job_queue
+        .enqueue(didhub_job_queue::JobRequest::new("config.reload", payload))
+        .await;
+}
+
 fn reload_log_level(
     old: &didhub_config::Config,
     new: &didhub_config::Config,
@@ -89,7 +190,7 @@ fn reload_log_level(
 }
 
 fn reload_authenticator(new: &didhub_config::Config, state: &AppState) {
-    match build_authenticator_from_config(new) {
+    match build_authenticator_from_config(new, state.revocation_store(), state.api_key_store()) {
         Ok((new_auth, _info)) => {
             let _old_auth = state.swap_authenticator(new_auth);
             tracing::info!("swapped authenticator at runtime");
@@ -100,6 +201,18 @@ fn reload_authenticator(new: &didhub_config::Config, state: &AppState) {
     }
 }
 
+fn reload_password_policy(new: &didhub_config::Config, state: &AppState) {
+    match policy_from_config(&new.password_policy) {
+        Ok(policy) => {
+            state.set_password_policy(policy);
+            tracing::info!("password policy updated at runtime");
+        }
+        Err(e) => {
+            tracing::error!(%e, "failed to load banned password list; leaving existing password policy in place");
+        }
+    }
+}
+
 async fn reload_rate_limiter(
     new: &didhub_config::Config,
     shared_limiter: &RwLock<RateLimiterManager>,
@@ -116,3 +229,69 @@ async fn reload_rate_limiter(
     *guard = new_limiter;
     tracing::info!("rate limiter configuration reloaded");
 }
+
+/// Names of changed fields that cannot be applied without restarting the server.
+fn restart_required_fields(
+    old: &didhub_config::Config,
+    new: &didhub_config::Config,
+) -> Vec<&'static str> {
+    let checks: [(&'static str, bool); 13] = [
+        (
+            "server",
+            // public_url is only read when building links and applies immediately
+            (
+                &old.server.host,
+                old.server.port,
+                &old.server.unix_socket,
+                &old.server.unix_socket_mode,
+            ) != (
+                &new.server.host,
+                new.server.port,
+                &new.server.unix_socket,
+                &new.server.unix_socket_mode,
+            ),
+        ),
+        ("database", old.database != new.database),
+        ("uploads", old.uploads != new.uploads),
+        ("tls", old.tls != new.tls),
+        ("scheduler", old.scheduler != new.scheduler),
+        ("redis_url", old.redis_url != new.redis_url),
+        ("cache", old.cache != new.cache),
+        ("otlp", old.otlp != new.otlp),
+        (
+            "metrics",
+            // bearer_token and allowed_ips are re-read for every scrape
+            (
+                &old.metrics.http_request_duration_buckets,
+                &old.metrics.db_query_duration_buckets,
+                &old.metrics.push_gateway_url,
+                old.metrics.push_interval_seconds,
+                &old.metrics.push_job,
+            ) != (
+                &new.metrics.http_request_duration_buckets,
+                &new.metrics.db_query_duration_buckets,
+                &new.metrics.push_gateway_url,
+                new.metrics.push_interval_seconds,
+                &new.metrics.push_job,
+            ),
+        ),
+        (
+            "auth.password_hash_target_ms",
+            old.auth.password_hash_target_ms != new.auth.password_hash_target_ms,
+        ),
+        (
+            "auth.password_pepper",
+            (&old.auth.password_pepper, old.auth.password_pepper_version)
+                != (&new.auth.password_pepper, new.auth.password_pepper_version),
+        ),
+        ("logging.json", old.logging.json != new.logging.json),
+        (
+            "logging.log_dir",
+            old.logging.log_dir != new.logging.log_dir,
+        ),
+    ];
+    checks
+        .into_iter()
+        .filter_map(|(name, changed)| changed.then_some(name))
+        .collect()
+}
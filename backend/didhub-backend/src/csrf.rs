@@ -45,6 +45,14 @@ pub async fn get_csrf_token(Extension(_state): Extension<Arc<AppState>>) -> impl
     resp
 }
 
+/// Endpoints for non-browser clients that only accept credentials from the
+/// Authorization header, so a forged cross-site request cannot authenticate.
+const CSRF_EXEMPT_PATHS: &[&str] = &[
+    "/oauth/introspect",
+    "/oauth/token",
+    "/oauth/device_authorization",
+];
+
 /// Middleware that enforces CSRF protection for unsafe HTTP methods.
 /// It expects a cookie named `csrf_token` and a header `x-csrf-token` with the same value.
 pub async fn csrf_protect(req: Request<Body>, next: Next) -> Response {
@@ -55,6 +63,9 @@ pub async fn csrf_protect(req: Request<Body>, next: Next) -> Response {
     if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
         return next.run(req).await;
     }
+    if CSRF_EXEMPT_PATHS.contains(&req.uri().path()) {
+        return next.run(req).await;
+    }
 
     // For other methods, check header and cookie
     let header_tok = req
@@ -0,0 +1,99 @@
+//! OAuth2 device authorization grant (RFC 8628) for CLI and bot logins.
+//!
+//! A headless client calls `/oauth/device_authorization` and shows the user a short
+//! user code and the verification page. The user signs in on any browser and
+//! approves the code at `/oauth/device`. Meanwhile the client polls `/oauth/token`
+//! with the device code and, once approved, receives a session token of its own,
+//! listed under `/me/sessions` like any other sign-in. Only the SHA-256 of the
+//! device code is stored, in `device_authorizations`; rows are single use.
+
+use std::time::Duration;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{DateTime, Utc};
+use didhub_db::generated::device_authorizations as db_authorizations;
+
+/// `grant_type` used when polling `/oauth/token`.
+pub const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// How long the user has to approve a code.
+pub const DEVICE_CODE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Minimum time between polls; faster clients are told to `slow_down`.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+pub const MAX_CLIENT_ID_LEN: usize = 100;
+
+pub const STATUS_PENDING: &str = "pending";
+pub const STATUS_APPROVED: &str = "approved";
+pub const STATUS_DENIED: &str = "denied";
+
+/// Consonants only, so codes cannot spell words and are easy to read aloud (RFC 8628 section 6.1).
+const USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+const USER_CODE_LEN: usize = 8;
+
+const DEVICE_CODE_BYTES: usize = 32;
+
+/// A fresh device code and the SHA-256 hex digest stored for it.
+pub fn generate_device_code() -> (String, String) {
+    let mut buf = [0u8; DEVICE_CODE_BYTES];
+    OsRng.fill_bytes(&mut buf);
+    let code = hex::encode(buf);
+    let hash = hash_device_code(&code);
+    (code, hash)
+}
+
+pub fn hash_device_code(code: &str) -> String {
+    didhub_auth::auth::sha256_hex(code.trim())
+}
+
+/// A fresh user code in its stored form, without the separator.
+pub fn generate_user_code() -> String {
+    (0..USER_CODE_LEN)
+        .map(|_| {
+            // 20 letters divide 240 evenly, so rejecting 240..=255 avoids modulo bias
+            loop {
+                let b = (OsRng.next_u32() & 0xff) as u8;
+                if b < 240 {
+                    break USER_CODE_ALPHABET[(b % 20) as usize] as char;
+                }
+            }
+        })
+        .collect()
+}
+
+/// User code as shown to the user, e.g. `BDFG-HJKL`.
+pub fn display_user_code(code: &str) -> String {
+    let (a, b) = code.split_at(code.len() / 2);
+    format!("{}-{}", a, b)
+}
+
+/// Stored form of a code typed by the user: case, spaces and dashes are ignored.
+pub fn normalize_user_code(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Verification page of the frontend, under `server.public_url`.
+pub fn verification_uri(public_url: Option<&str>) -> String {
+    format!("{}/device", public_url.unwrap_or("").trim_end_matches('/'))
+}
+
+pub fn is_expired(row: &db_authorizations::DeviceAuthorizationsRow, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(&row.expires_at).is_ok_and(|exp| exp <= now)
+}
+
+/// Whether the client polled again before [`POLL_INTERVAL`] had passed.
+pub fn polled_too_soon(
+    row: &db_authorizations::DeviceAuthorizationsRow,
+    now: DateTime<Utc>,
+) -> bool {
+    let interval = chrono::Duration::from_std(POLL_INTERVAL).unwrap_or_default();
+    row.last_polled_at
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .is_some_and(|last| now < last + interval)
+}
@@ -0,0 +1,236 @@
+//! "Remember me" device tokens.
+//!
+//! A device token is a long-lived random secret kept in its own HttpOnly cookie,
+//! separate from the session JWT. It cannot authenticate API requests; it can
+//! only be exchanged at `/auth/device-login` for a new session token. Each
+//! exchange rotates the secret and pushes the expiry forward (sliding expiry),
+//! so a device that keeps being used stays signed in while the JWT lifetime is
+//! unchanged. Only a SHA-256 of the secret is stored, in `device_tokens`.
+
+use std::time::Duration;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use cookie::Cookie;
+use didhub_db::generated::device_tokens as db_devices;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// Cookie holding the device token.
+pub const DEVICE_COOKIE_NAME: &str = "didhub_device";
+
+/// Devices remembered per user; signing in on another one forgets the least recently used.
+pub const MAX_DEVICES_PER_USER: usize = 20;
+
+pub const MAX_DEVICE_NAME_LEN: usize = 100;
+
+const MAX_USER_AGENT_LEN: usize = 512;
+
+const TOKEN_BYTES: usize = 32;
+
+fn generate_token() -> (String, String) {
+    let mut buf = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut buf);
+    let token = hex::encode(buf);
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+fn hash_token(token: &str) -> String {
+    didhub_auth::auth::sha256_hex(token)
+}
+
+/// Validate a user-supplied device name.
+pub fn normalize_name(name: &str) -> Result<String, ApiError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(ApiError::bad_request("device name must not be empty"));
+    }
+    if name.chars().count() > MAX_DEVICE_NAME_LEN {
+        return Err(ApiError::bad_request(format!(
+            "device name must be at most {} characters",
+            MAX_DEVICE_NAME_LEN
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect())
+}
+
+/// Public view of a remembered device. The token itself is never returned.
+pub fn device_to_payload(row: &db_devices::DeviceTokensRow) -> Value {
+    json!({
+        "id": row.id,
+        "name": row.name,
+        "userAgent": row.user_agent,
+        "createdAt": row.created_at,
+        "lastUsedAt": row.last_used_at,
+        "expiresAt": row.expires_at,
+    })
+}
+
+/// Whether the device token has run out without being used.
+pub fn is_expired(row: &db_devices::DeviceTokensRow, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(&row.expires_at).is_ok_and(|exp| exp <= now)
+}
+
+fn expiry(ttl: Duration) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(ttl).unwrap_or(chrono::Duration::days(30))
+}
+
+/// Remember a device for `user_id`. Returns the token to put in the device cookie.
+pub async fn remember(
+    state: &AppState,
+    user_id: Uuid,
+    name: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<String, ApiError> {
+    let name = match name {
+        Some(n) => normalize_name(n)?,
+        None => user_agent(headers)
+            .map(|ua| ua.chars().take(MAX_DEVICE_NAME_LEN).collect())
+            .unwrap_or_else(|| "Unnamed device".to_string()),
+    };
+
+    let mut existing = db_devices::find_by_user_id(&*state.db_pool, &user_id)
+        .await
+        .map_err(ApiError::from)?;
+    let now = Utc::now();
+    existing.sort_by(|a, b| b.last_used_at.cmp(&a.last_used_at));
+    for (i, row) in existing.iter().enumerate() {
+        if i + 1 >= MAX_DEVICES_PER_USER || is_expired(row, now) {
+            db_devices::delete_by_primary_key(&*state.db_pool, &row.id)
+                .await
+                .map_err(ApiError::from)?;
+        }
+    }
+
+    let (token, token_hash) = generate_token();
+    let now = now.to_rfc3339();
+    let row = db_devices::DeviceTokensRow {
+        id: Uuid::new_v4(),
+        user_id,
+        name,
+        token_hash,
+        user_agent: user_agent(headers),
+        created_at: now.clone(),
+        last_used_at: now,
+        expires_at: expiry(state.device_token_ttl()).to_rfc3339(),
+    };
+    db_devices::insert_device_token(&*state.db_pool, &row)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(token)
+}
+
+/// Exchange a device token for its device and a rotated token, extending its expiry.
+/// Returns `None` for unknown, expired or concurrently used tokens.
+pub async fn redeem(
+    state: &AppState,
+    token: &str,
+    headers: &HeaderMap,
+) -> Result<Option<(db_devices::DeviceTokensRow, String)>, ApiError> {
+    let old_hash = hash_token(token);
+    let Some(mut row) = db_devices::find_first_by_token_hash(&*state.db_pool, &old_hash)
+        .await
+        .map_err(ApiError::from)?
+    else {
+        return Ok(None);
+    };
+    if is_expired(&row, Utc::now()) {
+        db_devices::delete_by_primary_key(&*state.db_pool, &row.id)
+            .await
+            .map_err(ApiError::from)?;
+        return Ok(None);
+    }
+
+    let (new_token, new_hash) = generate_token();
+    row.token_hash = new_hash;
+    row.last_used_at = Utc::now().to_rfc3339();
+    row.expires_at = expiry(state.device_token_ttl()).to_rfc3339();
+    if let Some(ua) = user_agent(headers) {
+        row.user_agent = Some(ua);
+    }
+    // Only one of two requests racing with the same token may rotate it
+    let affected = sqlx::query(
+        "UPDATE device_tokens SET token_hash = ?, last_used_at = ?, expires_at = ?, user_agent = ? WHERE id = ? AND token_hash = ?",
+    )
+    .bind(&row.token_hash)
+    .bind(&row.last_used_at)
+    .bind(&row.expires_at)
+    .bind(&row.user_agent)
+    .bind(row.id)
+    .bind(&old_hash)
+    .execute(&*state.db_pool)
+    .await
+    .map_err(ApiError::from)?
+    .rows_affected();
+    if affected == 0 {
+        return Ok(None);
+    }
+    Ok(Some((row, new_token)))
+}
+
+/// Forget the device whose token is `token`, if any (logout on that device).
+pub async fn forget_token(state: &AppState, token: &str) -> Result<(), ApiError> {
+    sqlx::query("DELETE FROM device_tokens WHERE token_hash = ?")
+        .bind(hash_token(token))
+        .execute(&*state.db_pool)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+/// Forget every device of a user, e.g. when all their sessions are revoked.
+pub async fn forget_user_devices(state: &AppState, user_id: Uuid) -> Result<(), ApiError> {
+    sqlx::query("DELETE FROM device_tokens WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&*state.db_pool)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+/// Device token sent with the request, if any.
+pub fn token_from_cookie(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(axum::http::header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|c| Cookie::parse(c.trim()).ok())
+        .find(|c| c.name() == DEVICE_COOKIE_NAME)
+        .map(|c| c.value().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Cookie carrying a device token for `ttl`. It is only sent to the auth endpoints.
+pub fn device_cookie(token: String, ttl: Duration) -> Cookie<'static> {
+    Cookie::build((DEVICE_COOKIE_NAME, token))
+        .path("/api/auth")
+        .http_only(true)
+        .secure(true)
+        .same_site(cookie::SameSite::Strict)
+        .max_age(cookie::time::Duration::seconds(ttl.as_secs() as i64))
+        .build()
+}
+
+/// Cookie removing the device token from the browser.
+pub fn clear_device_cookie() -> Cookie<'static> {
+    Cookie::build((DEVICE_COOKIE_NAME, ""))
+        .path("/api/auth")
+        .http_only(true)
+        .secure(true)
+        .same_site(cookie::SameSite::Strict)
+        .max_age(cookie::time::Duration::seconds(0))
+        .build()
+}
@@ -25,6 +25,10 @@ pub enum ApiError {
     BadRequest(String),
     #[error("forbidden: {0}")]
     Forbidden(String),
+    #[error("conflict: {0}")]
+    Conflict(String),
+    #[error("too many requests: {0}")]
+    TooManyRequests(String),
     #[error("validation error")]
     Validation(serde_json::Value),
     #[error("internal error: {0}")]
@@ -54,6 +58,14 @@ impl ApiError {
         Self::Forbidden(msg.into())
     }
 
+    pub fn conflict(msg: impl Into<String>) -> Self {
+        Self::Conflict(msg.into())
+    }
+
+    pub fn too_many_requests(msg: impl Into<String>) -> Self {
+        Self::TooManyRequests(msg.into())
+    }
+
     pub fn internal(msg: impl Into<String>) -> Self {
         Self::Internal(msg.into())
     }
@@ -67,6 +79,12 @@ impl ApiError {
     }
 }
 
+impl didhub_db::RetryableError for ApiError {
+    fn is_retryable(&self) -> bool {
+        matches!(self, Self::Sqlx(e) if didhub_db::RetryableError::is_retryable(e))
+    }
+}
+
 impl IntoResponse for ApiError {
     fn into_response(self) -> axum::response::Response {
         let (status, message) = match self {
@@ -78,6 +96,8 @@ impl IntoResponse for ApiError {
             ApiError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             ApiError::BadRequest(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             ApiError::Forbidden(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            ApiError::Conflict(_) => (StatusCode::CONFLICT, self.to_string()),
+            ApiError::TooManyRequests(_) => (StatusCode::TOO_MANY_REQUESTS, self.to_string()),
             ApiError::Validation(_) => (StatusCode::UNPROCESSABLE_ENTITY, self.to_string()),
             ApiError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             ApiError::Unexpected(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
@@ -0,0 +1,297 @@
+//! Full export of a user's data as a portable ZIP archive.
+//!
+//! The archive holds `manifest.json`, one JSON document per kind of data (profile
+//! and settings, alters with their tags and custom field values, affiliations,
+//! subsystems, relationships, tags, custom field definitions and uploads) and the
+//! referenced uploaded files under `files/<storedFileId>`. `GET /me/export`
+//! streams it directly; the [`SYSTEM_EXPORT_JOB`] job builds it in the background
+//! for `POST /me/exports` and keeps it until the user's next background export.
+
+use std::collections::BTreeSet;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use didhub_job_queue::{async_trait, JobExecutor, JobQueueError};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+use didhub_db::custom::affiliation_members as db_affiliation_members;
+use didhub_db::custom::alters::AlterFilter;
+use didhub_db::custom::subsystem_members as db_subsystem_members;
+use didhub_db::generated::stored_files as db_stored_files;
+use didhub_db::generated::subsystems as db_subsystems;
+use didhub_db::generated::uploads as db_uploads;
+use didhub_db::generated::user_emails as db_user_emails;
+use didhub_db::generated::users as db_users;
+use didhub_db::DbPoolConnection;
+
+use crate::error::ApiError;
+use crate::handlers::relationships::dto::RelationshipResponse;
+use crate::state::AppState;
+use crate::upload_references::stored_file_id;
+
+/// Job type of the executor building background exports.
+pub const SYSTEM_EXPORT_JOB: &str = "system.export";
+
+/// `format` of the archive manifest.
+pub const FORMAT: &str = "didhub-export";
+
+/// Version of the archive layout, bumped on incompatible changes.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// A user's data gathered for an export.
+pub struct Export {
+    user_id: Uuid,
+    /// JSON documents by file name.
+    documents: Vec<(&'static str, Value)>,
+    /// Stored files referenced by the data.
+    files: BTreeSet<Uuid>,
+}
+
+fn parse_json(text: &str) -> Value {
+    serde_json::from_str(text).unwrap_or(Value::Null)
+}
+
+/// Gather everything `user_id` owns that is not in the trash.
+pub async fn collect(conn: &mut DbPoolConnection, user_id: &Uuid) -> Result<Export, ApiError> {
+    let user = db_users::find_by_primary_key(&mut **conn, user_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("user not found"))?;
+    let email = db_user_emails::find_by_primary_key(&mut **conn, user_id).await?;
+    let mut files = BTreeSet::new();
+    files.extend(user.avatar.as_deref().and_then(stored_file_id));
+    let profile = json!({
+        "id": user.id,
+        "username": user.username,
+        "displayName": user.display_name,
+        "aboutMe": user.about_me,
+        "avatar": user.avatar,
+        "email": email.map(|row| row.email),
+        "roles": parse_json(&user.roles),
+        "settings": parse_json(&user.settings),
+        "createdAt": user.created_at,
+        "updatedAt": user.updated_at,
+    });
+
+    let mut alters = Vec::new();
+    for row in
+        didhub_db::custom::alters::list_active(&mut **conn, &AlterFilter::system(user_id)).await?
+    {
+        let images: Vec<String> = serde_json::from_str(&row.images).unwrap_or_default();
+        files.extend(images.iter().filter_map(|image| stored_file_id(image)));
+        let mut alter = crate::handlers::alters::list::alter_list_value(&row)?;
+        let tags = didhub_db::custom::tags::list_for_alter(&mut **conn, &row.id, user_id).await?;
+        alter["tags"] = tags.iter().map(|tag| json!(tag.id)).collect();
+        let values: Map<String, Value> =
+            didhub_db::custom::custom_fields::values_for_alter(&mut **conn, &row.id)
+                .await?
+                .into_iter()
+                .map(|value| (value.field_id.to_string(), parse_json(&value.value)))
+                .collect();
+        alter["customFields"] = Value::Object(values);
+        alters.push(alter);
+    }
+
+    let mut affiliations = Vec::new();
+    for row in didhub_db::custom::affiliations::list_active_for_owner(&mut **conn, user_id).await? {
+        files.extend(row.sigil.as_deref().and_then(stored_file_id));
+        let members =
+            db_affiliation_members::list_active_for_affiliation(&mut **conn, &row.id).await?;
+        let mut affiliation = crate::handlers::utils::affiliation_to_payload(&row);
+        affiliation["members"] = members
+            .iter()
+            .map(|member| {
+                json!({
+                    "alterId": member.alter_id,
+                    "isLeader": member.is_leader != 0,
+                    "addedAt": member.added_at,
+                })
+            })
+            .collect();
+        affiliations.push(affiliation);
+    }
+
+    let mut subsystems = Vec::new();
+    for row in db_subsystems::find_by_owner_user_id(&mut **conn, user_id).await? {
+        let members = db_subsystem_members::list_active_for_subsystem(&mut **conn, &row.id).await?;
+        let mut subsystem = crate::handlers::subsystems::helpers::subsystem_to_payload(&row);
+        subsystem["members"] = members
+            .iter()
+            .map(|member| {
+                json!({
+                    "alterId": member.alter_id,
+                    "isHost": member.is_host != 0,
+                    "addedAt": member.added_at,
+                })
+            })
+            .collect();
+        subsystems.push(subsystem);
+    }
+
+    let relationships: Vec<RelationshipResponse> =
+        didhub_db::custom::relationships::list_for_user(&mut **conn, user_id)
+            .await?
+            .into_iter()
+            .map(RelationshipResponse::from)
+            .collect();
+
+    let tags: Vec<Value> = didhub_db::custom::tags::list_for_user(&mut **conn, user_id)
+        .await?
+        .iter()
+        .map(crate::handlers::tags::tag_to_payload)
+        .collect();
+
+    let custom_fields: Vec<Value> =
+        didhub_db::custom::custom_fields::list_definitions(&mut **conn, user_id)
+            .await?
+            .iter()
+            .map(crate::handlers::custom_fields::definition_to_payload)
+            .collect();
+
+    let mut uploads = Vec::new();
+    for row in db_uploads::find_by_uploaded_by(&mut **conn, user_id).await? {
+        files.insert(row.stored_file_id);
+        let stored = db_stored_files::find_by_primary_key(&mut **conn, &row.stored_file_id).await?;
+        uploads.push(json!({
+            "id": row.id,
+            "storedFileId": row.stored_file_id,
+            "name": row.stored_name,
+            "mimeType": stored.as_ref().and_then(|file| file.mime_type.clone()),
+            "size": stored.as_ref().map(|file| file.size),
+            "createdAt": row.created_at,
+        }));
+    }
+
+    let manifest = json!({
+        "format": FORMAT,
+        "version": FORMAT_VERSION,
+        "exportedAt": Utc::now().to_rfc3339(),
+        "userId": user_id,
+    });
+
+    Ok(Export {
+        user_id: *user_id,
+        documents: vec![
+            ("manifest.json", manifest),
+            ("profile.json", profile),
+            ("alters.json", Value::Array(alters)),
+            ("affiliations.json", Value::Array(affiliations)),
+            ("subsystems.json", Value::Array(subsystems)),
+            ("relationships.json", serde_json::to_value(relationships)?),
+            ("tags.json", Value::Array(tags)),
+            ("custom_fields.json", Value::Array(custom_fields)),
+            ("uploads.json", Value::Array(uploads)),
+        ],
+        files,
+    })
+}
+
+/// Write `export` as a ZIP archive to `writer`, taking the referenced files from
+/// `uploads_dir`. Files missing on disk are left out.
+pub fn write_archive<W: Write + Seek>(
+    export: &Export,
+    uploads_dir: &Path,
+    writer: W,
+) -> zip::result::ZipResult<W> {
+    let mut zip = ZipWriter::new(writer);
+    let options = SimpleFileOptions::default();
+    for (name, document) in &export.documents {
+        zip.start_file(*name, options)?;
+        serde_json::to_writer_pretty(&mut zip, document)
+            .map_err(|e| zip::result::ZipError::Io(e.into()))?;
+    }
+    for file_id in &export.files {
+        let path = uploads_dir.join(file_id.to_string());
+        let Ok(mut file) = std::fs::File::open(&path) else {
+            tracing::warn!(%file_id, "stored file missing on disk; leaving it out of the export");
+            continue;
+        };
+        zip.start_file(format!("files/{file_id}"), options)?;
+        std::io::copy(&mut file, &mut zip)?;
+    }
+    zip.finish()
+}
+
+/// Where the background export `archive_id` of `user_id` is kept.
+pub fn archive_path(uploads_dir: &Path, user_id: &Uuid, archive_id: &Uuid) -> PathBuf {
+    uploads_dir
+        .join("exports")
+        .join(format!("{user_id}-{archive_id}.zip"))
+}
+
+/// Write `export` to its archive path and remove the user's earlier background
+/// exports.
+fn store_archive(export: &Export, uploads_dir: &Path, archive_id: &Uuid) -> std::io::Result<()> {
+    let path = archive_path(uploads_dir, &export.user_id, archive_id);
+    let dir = path.parent().unwrap_or(uploads_dir);
+    std::fs::create_dir_all(dir)?;
+    let partial = path.with_extension("zip.part");
+    write_archive(export, uploads_dir, std::fs::File::create(&partial)?)?;
+    std::fs::rename(&partial, &path)?;
+
+    let prefix = format!("{}-", export.user_id);
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let earlier = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with(&prefix) && name.ends_with(".zip") && earlier != path {
+            if let Err(e) = std::fs::remove_file(&earlier) {
+                tracing::warn!(%e, path = %earlier.display(), "failed to remove earlier export");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Executor for [`SYSTEM_EXPORT_JOB`]; the payload names the `userId` and the
+/// `archiveId` to store the archive under.
+pub struct SystemExportExecutor {
+    state: AppState,
+}
+
+impl SystemExportExecutor {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+fn payload_id(payload: &Value, key: &str) -> Result<Uuid, JobQueueError> {
+    payload
+        .get(key)
+        .and_then(Value::as_str)
+        .and_then(|s| Uuid::parse_str(s).ok())
+        .ok_or_else(|| JobQueueError::ExecutionFailed(format!("missing or invalid {key}")))
+}
+
+#[async_trait]
+impl JobExecutor for SystemExportExecutor {
+    fn job_type(&self) -> &str {
+        SYSTEM_EXPORT_JOB
+    }
+
+    async fn execute(&self, payload: Value) -> Result<(), JobQueueError> {
+        let failed = |e: &dyn std::fmt::Display| JobQueueError::ExecutionFailed(e.to_string());
+        let user_id = payload_id(&payload, "userId")?;
+        let archive_id = payload_id(&payload, "archiveId")?;
+
+        let mut conn = self.state.db_pool.acquire().await.map_err(|e| failed(&e))?;
+        let export = collect(&mut conn, &user_id).await.map_err(|e| failed(&e))?;
+        drop(conn);
+
+        let uploads_dir = didhub_config::load_config::<&Path>(None)
+            .unwrap_or_default()
+            .uploads
+            .directory;
+        tokio::task::spawn_blocking(move || {
+            store_archive(&export, Path::new(&uploads_dir), &archive_id)
+        })
+        .await
+        .map_err(|e| failed(&e))?
+        .map_err(|e| failed(&e))?;
+        tracing::info!(%user_id, %archive_id, "system export written");
+        Ok(())
+    }
+}
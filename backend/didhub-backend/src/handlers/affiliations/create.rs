@@ -8,7 +8,7 @@ use uuid::Uuid;
 
 use didhub_db::generated::affiliations as db_affiliations;
 
-use crate::handlers::utils::{affiliation_to_payload, ensure_system_user};
+use crate::handlers::utils::affiliation_to_payload;
 use crate::{error::ApiError, state::AppState};
 
 pub async fn create(
@@ -18,18 +18,26 @@ pub async fn create(
 ) -> Result<Json<Value>, ApiError> {
     let auth =
         crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
-    let owner_user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
-    let is_admin = auth.is_admin();
-
-    if !is_admin {
-        let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-        ensure_system_user(&mut *conn, owner_user_id, "creating affiliation").await?;
-    }
-
     let payload = body
         .ok_or_else(|| ApiError::bad_request("missing request body"))?
         .0;
 
+    // The caller's own system unless another one it may edit is named
+    let owner_user_id = match payload.get("systemId").and_then(Value::as_str) {
+        Some(system_id) => {
+            Uuid::parse_str(system_id).map_err(|_| ApiError::bad_request("invalid systemId"))?
+        }
+        None => crate::handlers::auth::utils::require_user_id(&auth)?,
+    };
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        Some(&owner_user_id),
+        "creating affiliation",
+    )
+    .await?;
+
     let name_value = payload
         .get("name")
         .cloned()
@@ -49,7 +57,6 @@ pub async fn create(
         created_at: now,
     };
 
-    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
     db_affiliations::insert_affiliation(&mut *conn, &new_row)
         .await
         .map_err(ApiError::from)?;
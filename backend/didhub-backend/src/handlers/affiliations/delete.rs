@@ -6,9 +6,8 @@ use axum::http::HeaderMap;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
-use didhub_db::generated::affiliations as db_affiliations;
+use didhub_db::custom::trash;
 
-use crate::handlers::utils::ensure_system_user;
 use crate::{error::ApiError, state::AppState};
 
 pub async fn delete(
@@ -19,7 +18,6 @@ pub async fn delete(
     let auth =
         crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
     let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
-    let is_admin = auth.is_admin();
 
     let affiliation_id_str = path
         .get("affiliationId")
@@ -28,30 +26,39 @@ pub async fn delete(
         .map_err(|_| ApiError::bad_request("invalid affiliationId"))?;
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let existing = db_affiliations::find_by_primary_key(&mut *conn, &affiliation_id)
+    let existing = didhub_db::custom::affiliations::find_active(&mut *conn, &affiliation_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("affiliation not found"))?;
 
-    let owner_matches = existing
-        .owner_user_id
-        .map(|owner| owner == user_id)
-        .unwrap_or(false);
-    crate::handlers::auth::utils::ensure_admin_or(&auth, owner_matches)?;
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        existing.owner_user_id.as_ref(),
+        "deleting affiliation",
+    )
+    .await?;
 
-    if !is_admin {
-        ensure_system_user(&mut *conn, user_id, "deleting affiliation").await?;
-    }
-
-    sqlx::query("DELETE FROM affiliation_members WHERE affiliation_id = ?")
-        .bind(affiliation_id)
-        .execute(&mut *conn)
-        .await
-        .map_err(ApiError::from)?;
-
-    db_affiliations::delete_by_primary_key(&mut *conn, &affiliation_id)
-        .await
-        .map_err(ApiError::from)?;
+    // Move to the trash with its members; the purge job deletes both once the
+    // retention has passed
+    trash::mark_deleted(
+        &mut *conn,
+        "affiliations",
+        &affiliation_id,
+        &chrono::Utc::now().to_rfc3339(),
+    )
+    .await
+    .map_err(ApiError::from)?;
+    let snapshot = serde_json::to_value(&existing).map_err(ApiError::from)?;
+    state
+        .record_version(
+            "affiliation",
+            affiliation_id,
+            "delete",
+            Some(user_id),
+            &snapshot,
+        )
+        .await;
 
     Ok(Json(json!({ "deleted": true })))
 }
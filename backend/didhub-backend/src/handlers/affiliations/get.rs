@@ -8,7 +8,6 @@ use uuid::Uuid;
 
 use crate::handlers::utils::affiliation_to_payload;
 use crate::{error::ApiError, state::AppState};
-use didhub_db::generated::affiliations as db_affiliations;
 
 pub async fn get(
     Extension(_state): Extension<Arc<AppState>>,
@@ -16,7 +15,8 @@ pub async fn get(
     _path: Path<HashMap<String, String>>,
 ) -> Result<Json<Value>, ApiError> {
     // Only approved users (or admin) may get affiliation
-    crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
 
     _state
         .audit_request(
@@ -35,11 +35,16 @@ pub async fn get(
         .map_err(|_| ApiError::bad_request("invalid affiliationId"))?;
 
     let mut conn = _state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let opt = db_affiliations::find_by_primary_key(&mut *conn, &affiliation_id)
+    let opt = didhub_db::custom::affiliations::find_active(&mut *conn, &affiliation_id)
         .await
         .map_err(ApiError::from)?;
     match opt {
-        Some(row) => Ok(Json(affiliation_to_payload(&row))),
-        None => Err(ApiError::not_found("affiliation not found")),
+        Some(row)
+            if crate::system_access::can_view(&mut conn, &auth, row.owner_user_id.as_ref())
+                .await =>
+        {
+            Ok(Json(affiliation_to_payload(&row)))
+        }
+        _ => Err(ApiError::not_found("affiliation not found")),
     }
 }
@@ -18,7 +18,10 @@ pub async fn list(
     _query: Option<AxumQuery<HashMap<String, String>>>,
 ) -> Result<Json<Value>, ApiError> {
     // Only approved users (or admin) may list affiliations
-    crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
+    // Only the systems the caller belongs to, unless it is an admin
+    let viewer = crate::system_access::list_scope(&auth)?;
 
     let params = _query.map(|v| v.0).unwrap_or_default();
     let page = parse_positive_usize(params.get("page"), 1, "page")?;
@@ -55,10 +58,10 @@ pub async fn list(
         None
     };
 
-    let mut conn = _state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let mut conn = _state.acquire_read().await?;
 
     // Build WHERE clause conditions
-    let mut where_conditions: Vec<String> = Vec::new();
+    let mut where_conditions: Vec<String> = vec!["deleted_at IS NULL".to_string()];
 
     if search_opt.is_some() {
         where_conditions.push("(LOWER(name) LIKE ? OR LOWER(description) LIKE ?)".to_string());
@@ -66,12 +69,13 @@ pub async fn list(
     if parsed_system_id.is_some() {
         where_conditions.push("owner_user_id = ?".to_string());
     }
+    if viewer.is_some() {
+        where_conditions.push(didhub_db::custom::system_members::visible_condition(
+            "owner_user_id",
+        ));
+    }
 
-    let where_clause = if where_conditions.is_empty() {
-        String::new()
-    } else {
-        format!("WHERE {}", where_conditions.join(" AND "))
-    };
+    let where_clause = format!("WHERE {}", where_conditions.join(" AND "));
 
     // Build count query
     let count_sql = format!("SELECT COUNT(*) FROM affiliations {}", where_clause);
@@ -85,6 +89,9 @@ pub async fn list(
     if let Some(sid) = &parsed_system_id {
         count_query = count_query.bind(*sid);
     }
+    if let Some(viewer) = &viewer {
+        count_query = count_query.bind(*viewer).bind(*viewer);
+    }
 
     let total: i64 = count_query
         .fetch_one(&mut *conn)
@@ -108,6 +115,9 @@ pub async fn list(
     if let Some(sid) = &parsed_system_id {
         data_query = data_query.bind(*sid);
     }
+    if let Some(viewer) = &viewer {
+        data_query = data_query.bind(*viewer).bind(*viewer);
+    }
     data_query = data_query.bind(per_page as i64).bind(offset as i64);
 
     let rows: Vec<db_affiliations::AffiliationsRow> = data_query
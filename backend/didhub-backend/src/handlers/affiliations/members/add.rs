@@ -7,9 +7,7 @@ use chrono::Utc;
 use serde_json::Value;
 use uuid::Uuid;
 
-use didhub_db::generated::{affiliations as db_affiliations, alters as db_alters};
-
-use crate::handlers::utils::{affiliation_to_payload, ensure_system_user};
+use crate::handlers::utils::affiliation_to_payload;
 use crate::{error::ApiError, state::AppState};
 
 pub async fn add(
@@ -20,8 +18,6 @@ pub async fn add(
 ) -> Result<Json<Value>, ApiError> {
     let auth =
         crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
-    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
-    let is_admin = auth.is_admin();
 
     let affiliation_id_str = path
         .get("affiliationId")
@@ -30,20 +26,18 @@ pub async fn add(
         .map_err(|_| ApiError::bad_request("invalid affiliationId"))?;
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let affiliation = db_affiliations::find_by_primary_key(&mut *conn, &affiliation_id)
+    let affiliation = didhub_db::custom::affiliations::find_active(&mut *conn, &affiliation_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("affiliation not found"))?;
 
-    let owner_matches = affiliation
-        .owner_user_id
-        .map(|owner| owner == user_id)
-        .unwrap_or(false);
-    crate::handlers::auth::utils::ensure_admin_or(&auth, owner_matches)?;
-
-    if !is_admin {
-        ensure_system_user(&mut *conn, user_id, "adding affiliation member").await?;
-    }
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        affiliation.owner_user_id.as_ref(),
+        "adding affiliation member",
+    )
+    .await?;
 
     let payload = body
         .ok_or_else(|| ApiError::bad_request("missing request body"))?
@@ -60,7 +54,7 @@ pub async fn add(
     .map_err(|_| ApiError::bad_request("invalid alterId"))?;
 
     // Verify alter exists and belongs to the same system as the affiliation
-    let alter = db_alters::find_by_primary_key(&mut *conn, &alter_id)
+    let alter = didhub_db::custom::alters::find_active(&mut *conn, &alter_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("alter not found"))?;
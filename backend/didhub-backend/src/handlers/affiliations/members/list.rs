@@ -6,8 +6,7 @@ use axum::http::HeaderMap;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
-use didhub_db::generated::affiliation_members::find_by_affiliation_id;
-use didhub_db::generated::affiliations as db_affiliations;
+use didhub_db::custom::affiliation_members::list_active_for_affiliation;
 
 use crate::{error::ApiError, state::AppState};
 
@@ -16,7 +15,8 @@ pub async fn list(
     headers: HeaderMap,
     Path(path): Path<HashMap<String, String>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
 
     let affiliation_id_str = path
         .get("affiliationId")
@@ -37,16 +37,17 @@ pub async fn list(
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
 
     // Verify affiliation exists
-    let affiliation = db_affiliations::find_by_primary_key(&mut *conn, &affiliation_id)
+    let affiliation = didhub_db::custom::affiliations::find_active(&mut *conn, &affiliation_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("affiliation not found"))?;
 
-    // Suppress unused variable warning
-    let _ = &affiliation;
+    if !crate::system_access::can_view(&mut conn, &auth, affiliation.owner_user_id.as_ref()).await {
+        return Err(ApiError::not_found("affiliation not found"));
+    }
 
-    // Query the affiliation_members table
-    let members = find_by_affiliation_id(&mut *conn, &affiliation_id)
+    // Query the affiliation_members table, skipping trashed alters
+    let members = list_active_for_affiliation(&mut *conn, &affiliation_id)
         .await
         .map_err(ApiError::from)?;
 
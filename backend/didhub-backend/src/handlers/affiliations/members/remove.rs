@@ -6,9 +6,6 @@ use axum::http::HeaderMap;
 use serde_json::{json, Value};
 use uuid::Uuid;
 
-use didhub_db::generated::affiliations as db_affiliations;
-
-use crate::handlers::utils::ensure_system_user;
 use crate::{error::ApiError, state::AppState};
 
 pub async fn remove(
@@ -18,8 +15,6 @@ pub async fn remove(
 ) -> Result<Json<Value>, ApiError> {
     let auth =
         crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
-    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
-    let is_admin = auth.is_admin();
 
     let affiliation_id_str = path
         .get("affiliationId")
@@ -33,20 +28,18 @@ pub async fn remove(
         Uuid::parse_str(member_id_str).map_err(|_| ApiError::bad_request("invalid memberId"))?;
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let affiliation = db_affiliations::find_by_primary_key(&mut *conn, &affiliation_id)
+    let affiliation = didhub_db::custom::affiliations::find_active(&mut *conn, &affiliation_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("affiliation not found"))?;
 
-    let owner_matches = affiliation
-        .owner_user_id
-        .map(|owner| owner == user_id)
-        .unwrap_or(false);
-    crate::handlers::auth::utils::ensure_admin_or(&auth, owner_matches)?;
-
-    if !is_admin {
-        ensure_system_user(&mut *conn, user_id, "removing affiliation member").await?;
-    }
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        affiliation.owner_user_id.as_ref(),
+        "removing affiliation member",
+    )
+    .await?;
 
     let result =
         sqlx::query("DELETE FROM affiliation_members WHERE affiliation_id = ? AND alter_id = ?")
@@ -7,7 +7,6 @@ use serde_json::{json, Value};
 use uuid::Uuid;
 
 use didhub_db::custom::affiliation_members as db_affiliation_members_custom;
-use didhub_db::generated::affiliations as db_affiliations;
 
 use crate::{error::ApiError, state::AppState};
 
@@ -50,13 +49,25 @@ pub async fn update(
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
 
     // Verify affiliation exists and check ownership
-    let affiliation = db_affiliations::find_by_primary_key(&mut *conn, &affiliation_id)
+    let affiliation = didhub_db::custom::affiliations::find_active(&mut *conn, &affiliation_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("affiliation not found"))?;
 
     // Only owner or admin can update members
-    if !is_admin && affiliation.owner_user_id != Some(user_id) {
+    let is_editor = match affiliation.owner_user_id {
+        Some(system_id) => {
+            crate::system_access::has_role(
+                &mut conn,
+                &system_id,
+                &user_id,
+                crate::system_access::SystemRole::Editor,
+            )
+            .await
+        }
+        None => false,
+    };
+    if !is_admin && !is_editor {
         return Err(ApiError::Authentication(
             didhub_auth::auth::AuthError::AuthenticationFailed,
         ));
@@ -56,19 +56,25 @@ pub async fn upload(
 
     // Verify affiliation exists and user owns it (or is admin)
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let affiliation = db_affiliations::find_by_primary_key(&mut *conn, &affiliation_id)
+    let affiliation = didhub_db::custom::affiliations::find_active(&mut *conn, &affiliation_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("affiliation not found"))?;
 
     let is_admin = auth.scopes.iter().any(|s| s == "admin");
-    if let Some(owner_id) = affiliation.owner_user_id {
-        if owner_id != user_id && !is_admin {
-            return Err(ApiError::Authentication(
-                didhub_auth::auth::AuthError::AuthenticationFailed,
-            ));
+    let is_editor = match affiliation.owner_user_id {
+        Some(system_id) => {
+            crate::system_access::has_role(
+                &mut conn,
+                &system_id,
+                &user_id,
+                crate::system_access::SystemRole::Editor,
+            )
+            .await
         }
-    } else if !is_admin {
+        None => false,
+    };
+    if !is_admin && !is_editor {
         return Err(ApiError::Authentication(
             didhub_auth::auth::AuthError::AuthenticationFailed,
         ));
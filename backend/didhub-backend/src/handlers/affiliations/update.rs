@@ -8,7 +8,7 @@ use uuid::Uuid;
 
 use didhub_db::generated::affiliations as db_affiliations;
 
-use crate::handlers::utils::{affiliation_to_payload, ensure_system_user};
+use crate::handlers::utils::affiliation_to_payload;
 use crate::{error::ApiError, state::AppState};
 
 pub async fn update(
@@ -20,7 +20,6 @@ pub async fn update(
     let auth =
         crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
     let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
-    let is_admin = auth.is_admin();
 
     let affiliation_id_str = path
         .get("affiliationId")
@@ -29,20 +28,19 @@ pub async fn update(
         .map_err(|_| ApiError::bad_request("invalid affiliationId"))?;
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let mut existing = db_affiliations::find_by_primary_key(&mut *conn, &affiliation_id)
+    let mut existing = didhub_db::custom::affiliations::find_active(&mut *conn, &affiliation_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("affiliation not found"))?;
+    let before = serde_json::to_value(&existing).map_err(ApiError::from)?;
 
-    let owner_matches = existing
-        .owner_user_id
-        .map(|owner| owner == user_id)
-        .unwrap_or(false);
-    crate::handlers::auth::utils::ensure_admin_or(&auth, owner_matches)?;
-
-    if !is_admin {
-        ensure_system_user(&mut *conn, user_id, "updating affiliation").await?;
-    }
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        existing.owner_user_id.as_ref(),
+        "updating affiliation",
+    )
+    .await?;
 
     let payload = body
         .ok_or_else(|| ApiError::bad_request("missing request body"))?
@@ -72,6 +70,27 @@ pub async fn update(
     db_affiliations::update_by_primary_key(&mut *conn, &affiliation_id, &existing)
         .await
         .map_err(ApiError::from)?;
+    let after = serde_json::to_value(&existing).map_err(ApiError::from)?;
+    if before != after {
+        state
+            .record_version(
+                "affiliation",
+                affiliation_id,
+                "update",
+                Some(user_id),
+                &before,
+            )
+            .await;
+    }
+    state
+        .audit_change(
+            "affiliation",
+            affiliation_id,
+            Some(user_id),
+            &before,
+            &after,
+        )
+        .await;
 
     Ok(Json(affiliation_to_payload(&existing)))
 }
@@ -4,11 +4,9 @@ use std::sync::Arc;
 use axum::extract::{Extension, Json, Path};
 use axum::http::HeaderMap;
 use serde_json::{json, Value};
-use sqlx::Acquire;
 use uuid::Uuid;
 
 use didhub_db::custom::affiliation_members::{self as db_affiliation_members_custom};
-use didhub_db::generated::alters as db_alters;
 
 use crate::{error::ApiError, state::AppState};
 
@@ -18,7 +16,8 @@ pub async fn get(
     headers: HeaderMap,
     Path(path): Path<HashMap<String, String>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
 
     let alter_id_str = path
         .get("alterId")
@@ -38,11 +37,14 @@ pub async fn get(
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
 
-    // Verify alter exists
-    let _alter = db_alters::find_by_primary_key(&mut *conn, &alter_id)
+    // Verify alter exists and the caller can see its system
+    let alter = didhub_db::custom::alters::find_active(&mut *conn, &alter_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("alter not found"))?;
+    if !crate::system_access::can_view(&mut conn, &auth, Some(&alter.user_id)).await {
+        return Err(ApiError::not_found("alter not found"));
+    }
 
     // Query the affiliation_members table to get all affiliations for this alter
     let affiliations =
@@ -77,7 +79,8 @@ pub async fn set(
     Path(path): Path<HashMap<String, String>>,
     body: Option<Json<Value>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
 
     let alter_id_str = path
         .get("alterId")
@@ -103,11 +106,18 @@ pub async fn set(
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
 
-    // Verify alter exists
-    let _alter = db_alters::find_by_primary_key(&mut *conn, &alter_id)
+    // Verify alter exists and the caller can edit its system
+    let alter = didhub_db::custom::alters::find_active(&mut *conn, &alter_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("alter not found"))?;
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        Some(&alter.user_id),
+        "setting alter affiliations",
+    )
+    .await?;
 
     // Extract affiliation IDs from the request body
     let affiliation_ids: Vec<Uuid> = payload
@@ -125,29 +135,29 @@ pub async fn set(
         })
         .collect::<Result<Vec<_>, _>>()?;
 
-    // Begin transaction
-    let mut tx = conn.begin().await.map_err(ApiError::from)?;
-
-    // Remove all existing affiliations for this alter
-    sqlx::query("DELETE FROM affiliation_members WHERE alter_id = ?")
-        .bind(alter_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(ApiError::from)?;
-
-    // Add new affiliations
-    for affiliation_id in &affiliation_ids {
-        sqlx::query(
-            "INSERT INTO affiliation_members (affiliation_id, alter_id, is_leader, added_at) VALUES (?, ?, 0, datetime('now'))"
-        )
-        .bind(affiliation_id)
-        .bind(alter_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(ApiError::from)?;
-    }
-
-    tx.commit().await.map_err(ApiError::from)?;
+    drop(conn);
+
+    // Replace the memberships atomically; the whole set is retried on conflicts
+    didhub_db::transaction(&state.db_pool, |tx| {
+        let affiliation_ids = affiliation_ids.clone();
+        Box::pin(async move {
+            sqlx::query("DELETE FROM affiliation_members WHERE alter_id = ?")
+                .bind(alter_id)
+                .execute(&mut **tx)
+                .await?;
+            for affiliation_id in &affiliation_ids {
+                sqlx::query(
+                    "INSERT INTO affiliation_members (affiliation_id, alter_id, is_leader, added_at) VALUES (?, ?, 0, datetime('now'))"
+                )
+                .bind(affiliation_id)
+                .bind(alter_id)
+                .execute(&mut **tx)
+                .await?;
+            }
+            Ok::<_, ApiError>(())
+        })
+    })
+    .await?;
 
     // Return the updated affiliations
     get(Extension(state), headers, Path(path)).await
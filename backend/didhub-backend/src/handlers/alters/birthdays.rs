@@ -1,11 +1,13 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use axum::extract::{Extension, Json};
+use axum::extract::{Extension, Json, Query};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+use uuid::Uuid;
 
 use crate::{error::ApiError, state::AppState};
+use didhub_db::custom::alters::AlterFilter;
 use didhub_db::generated::alters as db_alters;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -22,7 +24,12 @@ pub async fn list_birthdays(
     _headers: axum::http::HeaderMap,
 ) -> Result<Json<Value>, ApiError> {
     // Only approved users (or admin) may access birthdays
-    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &_headers).await?;
+    let filter = AlterFilter {
+        visible_to: crate::system_access::list_scope(&auth)?,
+        ..AlterFilter::default()
+    };
 
     state
         .audit_request(
@@ -34,13 +41,16 @@ pub async fn list_birthdays(
         )
         .await?;
 
-    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let mut conn = state.acquire_read().await?;
 
-    // Get all alters with birthdays
+    // Get all alters with birthdays, skipping the trash
     let rows: Vec<db_alters::AltersRow> =
-        db_alters::find_with_birthdays_ordered_by_name(&mut *conn)
+        didhub_db::custom::alters::list_active(&mut *conn, &filter)
             .await
-            .map_err(ApiError::from)?;
+            .map_err(ApiError::from)?
+            .into_iter()
+            .filter(|row| row.birthday.is_some())
+            .collect();
 
     // Convert rows to simplified birthday objects
     let birthdays: Vec<AlterBirthday> = rows
@@ -57,3 +67,51 @@ pub async fn list_birthdays(
         serde_json::to_value(birthdays).map_err(ApiError::from)?,
     ))
 }
+
+/// Days the calendar covers unless `days` is given.
+const DEFAULT_CALENDAR_DAYS: u32 = 30;
+
+/// GET /alters/birthdays/calendar
+/// Birthdays in the next `days` days (today included, in `birthdays.timezone`),
+/// soonest first, optionally only those of one system.
+pub async fn calendar(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    query: Option<Query<HashMap<String, String>>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let query = query.map(|q| q.0).unwrap_or_default();
+    let days = match query.get("days") {
+        Some(days) => days
+            .parse::<u32>()
+            .ok()
+            .filter(|days| (1..=366).contains(days))
+            .ok_or_else(|| ApiError::bad_request("days must be between 1 and 366"))?,
+        None => DEFAULT_CALENDAR_DAYS,
+    };
+    let system_id = query
+        .get("systemId")
+        .map(|id| Uuid::parse_str(id).map_err(|_| ApiError::bad_request("invalid systemId")))
+        .transpose()?;
+
+    let mut conn = state.acquire_read().await?;
+    let filter = AlterFilter {
+        user_id: system_id,
+        visible_to: crate::system_access::list_scope(&auth)?,
+        ..AlterFilter::default()
+    };
+    let rows = didhub_db::custom::alters::list_active(&mut *conn, &filter).await?;
+    let settings = state.birthdays();
+    let today = settings.today();
+    let items: Vec<Value> = crate::birthdays::upcoming(&rows, today, days)
+        .iter()
+        .map(|(row, occurrence)| crate::birthdays::occurrence_to_payload(row, occurrence))
+        .collect();
+
+    Ok(Json(json!({
+        "timezone": settings.timezone.name(),
+        "today": today.to_string(),
+        "items": items,
+    })))
+}
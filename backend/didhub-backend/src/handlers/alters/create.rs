@@ -4,6 +4,7 @@ use std::sync::Arc;
 use axum::extract::{Extension, Json};
 use serde_json::Value;
 
+use crate::handlers::custom_fields;
 use crate::handlers::utils::user_is_system;
 use crate::{error::ApiError, state::AppState};
 use didhub_db::generated::alters as db_alters;
@@ -55,7 +56,8 @@ pub async fn create(
         .get("surname")
         .and_then(|v| serde_json::from_value(v.clone()).ok());
 
-    // Determine owner_user_id: prefer authenticated user; allow admin to supply a user_id/systemId in payload
+    // Determine owner_user_id: prefer authenticated user; allow admins and editors of another
+    // system to supply a user_id/systemId in payload
     let is_admin = auth.scopes.iter().any(|s| s == "admin");
 
     // Check if admin is trying to create for a different system (via user_id or systemId in payload)
@@ -65,9 +67,25 @@ pub async fn create(
         .and_then(|v| v.as_str())
         .and_then(|s| SqlxUuid::parse_str(s).ok());
 
-    let owner_user_id: SqlxUuid = if let Some(target_user_id) = payload_user_id.filter(|_| is_admin)
+    let may_target = match (payload_user_id, auth.user_id) {
+        (Some(_), _) if is_admin => true,
+        (Some(target_user_id), Some(uid)) => {
+            let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+            crate::system_access::has_role(
+                &mut conn,
+                &target_user_id,
+                &uid,
+                crate::system_access::SystemRole::Editor,
+            )
+            .await
+        }
+        _ => false,
+    };
+
+    let owner_user_id: SqlxUuid = if let Some(target_user_id) =
+        payload_user_id.filter(|_| may_target)
     {
-        // Admin is specifying a target system - use that
+        // Admin or system editor is specifying a target system - use that
         // Validate the target user is a system user
         let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
         match didhub_db::generated::users::find_by_primary_key(&mut *conn, &target_user_id).await {
@@ -262,10 +280,16 @@ pub async fn create(
     };
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let custom_field_changes = match payload.get("customFields") {
+        Some(values) => custom_fields::parse_values(&mut conn, &owner_user_id, values).await?,
+        None => Vec::new(),
+    };
     db_alters::insert_alter(&mut *conn, &new_row)
         .await
         .map_err(ApiError::from)?;
-    Ok(Json(
-        serde_json::to_value(&new_row).map_err(ApiError::from)?,
-    ))
+    custom_fields::write_values(&mut conn, &new_row.id, custom_field_changes).await?;
+
+    let mut response = serde_json::to_value(&new_row).map_err(ApiError::from)?;
+    response["customFields"] = custom_fields::alter_values(&mut conn, &new_row, &auth).await?;
+    Ok(Json(response))
 }
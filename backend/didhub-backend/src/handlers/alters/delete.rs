@@ -4,8 +4,8 @@ use std::sync::Arc;
 use axum::extract::{Extension, Json, Path};
 use serde_json::Value;
 
-use crate::{error::ApiError, handlers::utils::user_is_system, state::AppState};
-use didhub_db::generated::alters as db_alters;
+use crate::{error::ApiError, state::AppState};
+use didhub_db::custom::trash;
 use sqlx::types::Uuid as SqlxUuid;
 
 pub async fn delete(
@@ -40,64 +40,37 @@ pub async fn delete(
         SqlxUuid::parse_str(&id_str).map_err(|_| ApiError::bad_request("invalid uuid"))?;
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
 
-    let existing = db_alters::find_by_primary_key(&mut *conn, &id)
+    let existing = didhub_db::custom::alters::find_active(&mut *conn, &id)
         .await
         .map_err(ApiError::from)?;
     let existing = existing.ok_or_else(|| ApiError::not_found("alter not found"))?;
 
     let is_admin = auth.scopes.iter().any(|s| s == "admin");
-    let is_owner = auth
-        .user_id
-        .map(|uid| uid == existing.owner_user_id)
-        .unwrap_or(false);
-    // If caller is not admin, require they are a system user
-    if !is_admin {
-        if let Some(uid) = auth.user_id {
-            let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-            match didhub_db::generated::users::find_by_primary_key(&mut *conn, &uid).await {
-                Ok(opt_user) => match opt_user {
-                    Some(user_row) => {
-                        if !user_is_system(&user_row) {
-                            return Err(ApiError::Authentication(
-                                didhub_auth::auth::AuthError::AuthenticationFailed,
-                            ));
-                        }
-                    }
-                    None => {
-                        return Err(ApiError::Authentication(
-                            didhub_auth::auth::AuthError::AuthenticationFailed,
-                        ))
-                    }
-                },
-                Err(e) => {
-                    tracing::warn!(%e, "could not fetch user row to check system role; allowing request (test or incomplete DB schema?)");
-                    // allow through for tests that don't create users table or have differing schema
-                }
-            }
-        } else {
-            return Err(ApiError::Authentication(
-                didhub_auth::auth::AuthError::AuthenticationFailed,
-            ));
-        }
-    }
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        Some(&existing.user_id),
+        "deleting alter",
+    )
+    .await?;
     // Prevent non-admins from modifying system-owned alters
     if existing.is_system_host == 1 && !is_admin {
         return Err(ApiError::Authentication(
             didhub_auth::auth::AuthError::AuthenticationFailed,
         ));
     }
-    if !is_admin && !is_owner {
-        return Err(ApiError::Authentication(
-            didhub_auth::auth::AuthError::AuthenticationFailed,
-        ));
-    }
 
-    let affected = db_alters::delete_by_primary_key(&mut *conn, &id)
+    // Move to the trash; the purge job deletes it once the retention has passed
+    let affected = trash::mark_deleted(&mut *conn, "alters", &id, &chrono::Utc::now().to_rfc3339())
         .await
         .map_err(ApiError::from)?;
     if affected == 0 {
         return Err(ApiError::not_found("alter not found"));
     }
+    let snapshot = serde_json::to_value(&existing).map_err(ApiError::from)?;
+    state
+        .record_version("alter", id, "delete", auth.user_id, &snapshot)
+        .await;
     Ok(Json(
         serde_json::to_value(serde_json::json!({ "deleted": true })).map_err(ApiError::from)?,
     ))
@@ -6,7 +6,6 @@ use serde_json::Value;
 
 use crate::handlers::utils::parse_json_array_fields;
 use crate::{error::ApiError, state::AppState};
-use didhub_db::generated::alters as db_alters;
 use sqlx::types::Uuid as SqlxUuid;
 
 pub async fn get(
@@ -15,7 +14,8 @@ pub async fn get(
     Path(path): Path<HashMap<String, String>>,
 ) -> Result<Json<Value>, ApiError> {
     // Only approved users (or admin) may get alters
-    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
     state
         .audit_request("GET", "/alters/{id}", &path, &HashMap::new(), &Value::Null)
         .await?;
@@ -26,11 +26,11 @@ pub async fn get(
     let id: SqlxUuid =
         SqlxUuid::parse_str(&id_str).map_err(|_| ApiError::bad_request("invalid uuid"))?;
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let opt = db_alters::find_by_primary_key(&mut *conn, &id)
+    let opt = didhub_db::custom::alters::find_active(&mut *conn, &id)
         .await
         .map_err(ApiError::from)?;
     match opt {
-        Some(row) => {
+        Some(row) if crate::system_access::can_view(&mut conn, &auth, Some(&row.user_id)).await => {
             let mut v = serde_json::to_value(&row).map_err(ApiError::from)?;
             if let Some(obj) = v.as_object_mut() {
                 // Parse JSON array fields
@@ -53,8 +53,21 @@ pub async fn get(
                     }
                 }
             }
+            // The caller's own tags on the alter
+            if let Some(user_id) = auth.user_id {
+                let tags = didhub_db::custom::tags::list_for_alter(&mut *conn, &id, &user_id)
+                    .await
+                    .map_err(ApiError::from)?;
+                v["tags"] = Value::Array(
+                    tags.iter()
+                        .map(crate::handlers::tags::tag_to_payload)
+                        .collect(),
+                );
+            }
+            v["customFields"] =
+                crate::handlers::custom_fields::alter_values(&mut conn, &row, &auth).await?;
             Ok(Json(v))
         }
-        None => Err(ApiError::not_found("alter not found")),
+        _ => Err(ApiError::not_found("alter not found")),
     }
 }
@@ -43,13 +43,21 @@ pub async fn reorder(
     .map_err(ApiError::from)?;
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let alter = db_alters::find_by_primary_key(&mut *conn, &alter_id)
+    let alter = didhub_db::custom::alters::find_active(&mut *conn, &alter_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("alter not found"))?;
 
     let is_admin = auth.scopes.iter().any(|s| s == "admin");
-    if alter.owner_user_id != user_id && !is_admin {
+    if !is_admin
+        && !crate::system_access::has_role(
+            &mut conn,
+            &alter.user_id,
+            &user_id,
+            crate::system_access::SystemRole::Editor,
+        )
+        .await
+    {
         return Err(ApiError::Authentication(
             didhub_auth::auth::AuthError::AuthenticationFailed,
         ));
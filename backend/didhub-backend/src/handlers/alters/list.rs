@@ -2,9 +2,17 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::extract::{Extension, Json, Query};
-use serde_json::Value;
+use serde_json::{json, Value};
 
-use crate::{error::ApiError, handlers::utils::parse_json_array_fields, state::AppState};
+use crate::{
+    error::ApiError,
+    handlers::utils::{
+        encode_cursor, parse_cursor_params, parse_json_array_fields, parse_optional_bool,
+        CursorParams,
+    },
+    state::AppState,
+};
+use didhub_db::custom::alters::AlterFilter;
 use didhub_db::generated::alters as db_alters;
 use sqlx::types::Uuid as SqlxUuid;
 
@@ -14,7 +22,8 @@ pub async fn list(
     query: Option<Query<HashMap<String, String>>>,
 ) -> Result<Json<Value>, ApiError> {
     // Only approved users (or admin) may list alters
-    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &_headers).await?;
 
     let params = query.map(|q| q.0).unwrap_or_default();
 
@@ -22,52 +31,126 @@ pub async fn list(
         .audit_request("GET", "/alters", &HashMap::new(), &params, &Value::Null)
         .await?;
 
-    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let mut conn = state.acquire_read().await?;
 
-    // Check for systemId filter (maps to user_id in the database)
-    let system_id_filter = params
+    let mut filter = parse_filter(&params)?;
+    // Only the systems the caller belongs to, unless it is an admin
+    filter.visible_to = crate::system_access::list_scope(&auth)?;
+
+    // With `cursor` or `limit`, return one keyset page ordered by name
+    if let Some(CursorParams { after, limit }) = parse_cursor_params(&params)? {
+        let mut rows = didhub_db::custom::alters::list_page(
+            &mut *conn,
+            &filter,
+            after.as_ref().map(|(name, id)| (name.as_str(), id)),
+            limit as i64 + 1,
+        )
+        .await
+        .map_err(ApiError::from)?;
+        let next_cursor = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last().map(|row| encode_cursor(&row.name, &row.id))
+        } else {
+            None
+        };
+        let items = rows
+            .iter()
+            .map(alter_list_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        return Ok(Json(json!({ "items": items, "nextCursor": next_cursor })));
+    }
+
+    let rows: Vec<db_alters::AltersRow> =
+        didhub_db::custom::alters::list_active(&mut *conn, &filter)
+            .await
+            .map_err(ApiError::from)?;
+
+    let values = rows
+        .iter()
+        .map(alter_list_value)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Json(serde_json::Value::Array(values)))
+}
+
+fn parse_uuid(raw: Option<&String>, field: &str) -> Result<Option<SqlxUuid>, ApiError> {
+    raw.map(|s| {
+        SqlxUuid::parse_str(s.trim()).map_err(|_| ApiError::bad_request(format!("invalid {field}")))
+    })
+    .transpose()
+}
+
+/// Comma-separated list, with empty entries dropped.
+fn parse_list(raw: Option<&String>) -> Vec<String> {
+    raw.map(|s| {
+        s.split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// The alter filter described by the query parameters. Empty text parameters are
+/// ignored.
+pub fn parse_filter(params: &HashMap<String, String>) -> Result<AlterFilter, ApiError> {
+    let text = |key: &str| {
+        params
+            .get(key)
+            .map(|s| s.trim())
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    // systemId maps to user_id in the database
+    let system_id = params
         .get("systemId")
         .or_else(|| params.get("system_id"))
         .or_else(|| params.get("userId"))
         .or_else(|| params.get("user_id"));
+    // `tags` is a comma-separated list of tag ids; alters must carry all of them
+    let tag_ids = parse_list(params.get("tags"))
+        .iter()
+        .map(|id| SqlxUuid::parse_str(id).map_err(|_| ApiError::bad_request("invalid tags")))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let rows: Vec<db_alters::AltersRow> = if let Some(system_id_str) = system_id_filter {
-        let system_id = SqlxUuid::parse_str(system_id_str)
-            .map_err(|_| ApiError::bad_request("invalid systemId"))?;
-        db_alters::find_by_user_id_ordered_by_name(&mut *conn, &system_id)
-            .await
-            .map_err(ApiError::from)?
-    } else {
-        db_alters::list_all(&mut *conn)
-            .await
-            .map_err(ApiError::from)?
-    };
+    Ok(AlterFilter {
+        user_id: parse_uuid(system_id, "systemId")?,
+        owner_user_id: parse_uuid(params.get("ownerId"), "ownerId")?,
+        name: text("search"),
+        species: text("species"),
+        system_roles: parse_list(params.get("systemRoles")),
+        tag_ids,
+        subsystem_id: parse_uuid(params.get("subsystemId"), "subsystemId")?,
+        is_system_host: parse_optional_bool(params.get("isHost"), "isHost")?,
+        is_dormant: parse_optional_bool(params.get("isDormant"), "isDormant")?,
+        is_merged: parse_optional_bool(params.get("isMerged"), "isMerged")?,
+        has_birthday: parse_optional_bool(params.get("hasBirthday"), "hasBirthday")?,
+        visible_to: None,
+    })
+}
 
-    // Convert rows to JSON and inject primaryUploadId from the images field if present
-    let mut values = Vec::with_capacity(rows.len());
-    for row in rows.into_iter() {
-        let mut v = serde_json::to_value(&row).map_err(ApiError::from)?;
-        if let Some(obj) = v.as_object_mut() {
-            parse_json_array_fields(obj, &row);
-            // Map user_id to systemId for frontend compatibility
-            if let Some(user_id) = obj.get("user_id").cloned() {
-                obj.insert("systemId".to_string(), user_id);
-            }
-            // Extract primaryUploadId from images
-            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&row.images) {
-                if let Some(arr) = parsed.as_array() {
-                    if let Some(first) = arr.first() {
-                        if let Some(s) = first.as_str() {
-                            obj.insert(
-                                "primaryUploadId".to_string(),
-                                serde_json::Value::String(s.to_string()),
-                            );
-                        }
+/// Convert a row to JSON and inject primaryUploadId from the images field if present
+pub fn alter_list_value(row: &db_alters::AltersRow) -> Result<Value, ApiError> {
+    let mut v = serde_json::to_value(row).map_err(ApiError::from)?;
+    if let Some(obj) = v.as_object_mut() {
+        parse_json_array_fields(obj, row);
+        // Map user_id to systemId for frontend compatibility
+        if let Some(user_id) = obj.get("user_id").cloned() {
+            obj.insert("systemId".to_string(), user_id);
+        }
+        // Extract primaryUploadId from images
+        if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&row.images) {
+            if let Some(arr) = parsed.as_array() {
+                if let Some(first) = arr.first() {
+                    if let Some(s) = first.as_str() {
+                        obj.insert(
+                            "primaryUploadId".to_string(),
+                            serde_json::Value::String(s.to_string()),
+                        );
                     }
                 }
             }
         }
-        values.push(v);
     }
-    Ok(Json(serde_json::Value::Array(values)))
+    Ok(v)
 }
@@ -7,4 +7,5 @@ pub mod get;
 pub mod image;
 pub mod list;
 pub mod subsystem;
+pub mod tags;
 pub mod update;
@@ -4,11 +4,9 @@ use std::sync::Arc;
 use axum::extract::{Extension, Json, Path};
 use axum::http::HeaderMap;
 use serde_json::{json, Value};
-use sqlx::Acquire;
 use uuid::Uuid;
 
 use didhub_db::custom::subsystem_members;
-use didhub_db::generated::alters as db_alters;
 use didhub_db::generated::subsystems as db_subsystems;
 
 use crate::{error::ApiError, state::AppState};
@@ -19,7 +17,8 @@ pub async fn set(
     Path(path): Path<HashMap<String, String>>,
     body: Option<Json<Value>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
 
     let alter_id_str = path
         .get("alterId")
@@ -44,25 +43,24 @@ pub async fn set(
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
 
-    let _alter = db_alters::find_by_primary_key(&mut *conn, &alter_id)
+    let alter = didhub_db::custom::alters::find_active(&mut *conn, &alter_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("alter not found"))?;
-
-    let mut tx = conn.begin().await.map_err(ApiError::from)?;
-
-    sqlx::query("DELETE FROM subsystem_members WHERE alter_id = ?")
-        .bind(alter_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(ApiError::from)?;
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        Some(&alter.user_id),
+        "setting alter subsystem",
+    )
+    .await?;
 
     let has_subsystem = payload
         .as_ref()
         .map(|p| !p.is_null() && !p.get("subsystemId").map(|v| v.is_null()).unwrap_or(false))
         .unwrap_or(false);
 
-    if has_subsystem {
+    let membership = if has_subsystem {
         let subsystem_id_str = payload
             .as_ref()
             .and_then(|p| p.get("subsystemId"))
@@ -76,24 +74,39 @@ pub async fn set(
             .and_then(|p| p.get("isHost"))
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
-
-        let _subsystem = db_subsystems::find_by_primary_key(&mut *tx, &subsystem_id)
-            .await
-            .map_err(ApiError::from)?
-            .ok_or_else(|| ApiError::not_found("subsystem not found"))?;
-
-        sqlx::query(
-            "INSERT INTO subsystem_members (subsystem_id, alter_id, is_host, added_at) VALUES (?, ?, ?, datetime('now'))"
-        )
-        .bind(subsystem_id)
-        .bind(alter_id)
-        .bind(if is_host { 1 } else { 0 })
-        .execute(&mut *tx)
-        .await
-        .map_err(ApiError::from)?;
-
-        tx.commit().await.map_err(ApiError::from)?;
-
+        Some((subsystem_id, is_host))
+    } else {
+        None
+    };
+
+    // Swap the membership atomically; retried on conflicts
+    didhub_db::transaction(&state.db_pool, |tx| {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM subsystem_members WHERE alter_id = ?")
+                .bind(alter_id)
+                .execute(&mut **tx)
+                .await?;
+
+            if let Some((subsystem_id, is_host)) = membership {
+                let _subsystem = db_subsystems::find_by_primary_key(&mut **tx, &subsystem_id)
+                    .await?
+                    .ok_or_else(|| ApiError::not_found("subsystem not found"))?;
+
+                sqlx::query(
+                    "INSERT INTO subsystem_members (subsystem_id, alter_id, is_host, added_at) VALUES (?, ?, ?, datetime('now'))"
+                )
+                .bind(subsystem_id)
+                .bind(alter_id)
+                .bind(if is_host { 1 } else { 0 })
+                .execute(&mut **tx)
+                .await?;
+            }
+            Ok::<_, ApiError>(())
+        })
+    })
+    .await?;
+
+    if membership.is_some() {
         let result = subsystem_members::find_subsystem_for_alter(&mut *conn, &alter_id)
             .await
             .map_err(ApiError::from)?;
@@ -109,7 +122,6 @@ pub async fn set(
             Ok(Json(json!(null)))
         }
     } else {
-        tx.commit().await.map_err(ApiError::from)?;
         Ok(Json(json!(null)))
     }
 }
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use didhub_db::generated::{alter_tags as db_alter_tags, tags as db_tags};
+
+use crate::handlers::tags::tag_to_payload;
+use crate::{error::ApiError, state::AppState};
+
+fn parse_alter_id(path: &HashMap<String, String>) -> Result<Uuid, ApiError> {
+    let alter_id_str = path
+        .get("alterId")
+        .ok_or_else(|| ApiError::bad_request("missing alterId"))?;
+    Uuid::parse_str(alter_id_str).map_err(|_| ApiError::bad_request("invalid alterId"))
+}
+
+/// Get the current user's tags on a specific alter
+pub async fn get(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+    let alter_id = parse_alter_id(&path)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let alter = didhub_db::custom::alters::find_active(&mut *conn, &alter_id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("alter not found"))?;
+    if !crate::system_access::can_view(&mut conn, &auth, Some(&alter.user_id)).await {
+        return Err(ApiError::not_found("alter not found"));
+    }
+
+    let tags = didhub_db::custom::tags::list_for_alter(&mut *conn, &alter_id, &user_id).await?;
+    let items: Vec<Value> = tags.iter().map(tag_to_payload).collect();
+    Ok(Json(json!({ "items": items })))
+}
+
+/// Set the current user's tags on a specific alter (replaces their existing tags;
+/// other users' tags stay attached)
+pub async fn set(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+    let alter_id = parse_alter_id(&path)?;
+
+    let payload = body
+        .as_ref()
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0
+        .clone();
+    let mut tag_ids: Vec<Uuid> = payload
+        .get("tagIds")
+        .and_then(|v| v.as_array())
+        .ok_or_else(|| ApiError::bad_request("missing or invalid tagIds array"))?
+        .iter()
+        .map(|v| {
+            v.as_str()
+                .and_then(|s| Uuid::parse_str(s).ok())
+                .ok_or_else(|| ApiError::bad_request("invalid tagId format"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    tag_ids.sort();
+    tag_ids.dedup();
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let alter = didhub_db::custom::alters::find_active(&mut *conn, &alter_id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("alter not found"))?;
+    if !crate::system_access::can_view(&mut conn, &auth, Some(&alter.user_id)).await {
+        return Err(ApiError::not_found("alter not found"));
+    }
+    for tag_id in &tag_ids {
+        db_tags::find_by_primary_key(&mut *conn, tag_id)
+            .await?
+            .filter(|tag| tag.user_id == user_id)
+            .ok_or_else(|| ApiError::bad_request(format!("tag {tag_id} not found")))?;
+    }
+    drop(conn);
+
+    didhub_db::transaction(&state.db_pool, |tx| {
+        let tag_ids = tag_ids.clone();
+        Box::pin(async move {
+            didhub_db::custom::tags::clear_for_alter(&mut **tx, &alter_id, &user_id).await?;
+            let added_at = Utc::now().to_rfc3339();
+            for tag_id in tag_ids {
+                let row = db_alter_tags::AlterTagsRow {
+                    alter_id,
+                    tag_id,
+                    added_at: added_at.clone(),
+                };
+                db_alter_tags::insert_alter_tag(&mut **tx, &row).await?;
+            }
+            Ok::<_, ApiError>(())
+        })
+    })
+    .await?;
+
+    get(Extension(state), headers, Path(path)).await
+}
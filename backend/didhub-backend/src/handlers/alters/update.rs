@@ -4,6 +4,7 @@ use std::sync::Arc;
 use axum::extract::{Extension, Json, Path};
 use serde_json::Value;
 
+use crate::handlers::custom_fields;
 use crate::handlers::utils::user_is_system;
 use crate::{error::ApiError, state::AppState};
 use didhub_db::generated::alters as db_alters;
@@ -42,52 +43,25 @@ pub async fn update(
     let id: SqlxUuid =
         SqlxUuid::parse_str(&id_str).map_err(|_| ApiError::bad_request("invalid uuid"))?;
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let existing = db_alters::find_by_primary_key(&mut *conn, &id)
+    let existing = didhub_db::custom::alters::find_active(&mut *conn, &id)
         .await
         .map_err(ApiError::from)?;
     let mut existing = existing.ok_or_else(|| ApiError::not_found("alter not found"))?;
+    let before = serde_json::to_value(&existing).map_err(ApiError::from)?;
 
     let is_admin = auth.scopes.iter().any(|s| s == "admin");
-    let is_owner = auth
-        .user_id
-        .map(|uid| uid == existing.owner_user_id)
-        .unwrap_or(false);
-    if !is_admin && !is_owner {
-        return Err(ApiError::Authentication(
-            didhub_auth::auth::AuthError::AuthenticationFailed,
-        ));
-    }
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        Some(&existing.user_id),
+        "updating alter",
+    )
+    .await?;
 
-    // If caller is not admin, require they are a system user
-    if !is_admin {
-        if let Some(uid) = auth.user_id {
-            let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-            match didhub_db::generated::users::find_by_primary_key(&mut *conn, &uid).await {
-                Ok(opt_user) => match opt_user {
-                    Some(user_row) => {
-                        if !user_is_system(&user_row) {
-                            return Err(ApiError::Authentication(
-                                didhub_auth::auth::AuthError::AuthenticationFailed,
-                            ));
-                        }
-                    }
-                    None => {
-                        return Err(ApiError::Authentication(
-                            didhub_auth::auth::AuthError::AuthenticationFailed,
-                        ))
-                    }
-                },
-                Err(e) => {
-                    tracing::warn!(%e, "could not fetch user row to check system role; allowing request (test or incomplete DB schema?)");
-                    // allow through for tests that don't create users table or have differing schema
-                }
-            }
-        } else {
-            return Err(ApiError::Authentication(
-                didhub_auth::auth::AuthError::AuthenticationFailed,
-            ));
-        }
-    }
+    let custom_field_changes = match body.as_ref().and_then(|b| b.0.get("customFields")) {
+        Some(values) => custom_fields::parse_values(&mut conn, &existing.user_id, values).await?,
+        None => Vec::new(),
+    };
 
     // apply partial updates via DTO
     let dto: super::dto::UpdateAlter = if let Some(body) = body {
@@ -225,7 +199,20 @@ pub async fn update(
     if affected == 0 {
         return Err(ApiError::not_found("alter not found"));
     }
-    Ok(Json(
-        serde_json::to_value(&existing).map_err(ApiError::from)?,
-    ))
+    custom_fields::write_values(&mut conn, &id, custom_field_changes).await?;
+    let after = serde_json::to_value(&existing).map_err(ApiError::from)?;
+    if before.get("images") != after.get("images") {
+        crate::upload_references::sync_alter(&mut conn, &existing).await?;
+    }
+    if before != after {
+        state
+            .record_version("alter", id, "update", auth.user_id, &before)
+            .await;
+    }
+    state
+        .audit_change("alter", id, auth.user_id, &before, &after)
+        .await;
+    let mut response = after;
+    response["customFields"] = custom_fields::alter_values(&mut conn, &existing, &auth).await?;
+    Ok(Json(response))
 }
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use chrono::{Duration, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use didhub_auth::auth::GeneratedApiKey;
+use didhub_db::generated::api_keys as db_api_keys;
+
+use super::{api_key_to_payload, ensure_session_auth};
+use crate::{error::ApiError, state::AppState};
+
+/// Create an API key for the current user. The plaintext key is only returned here.
+pub async fn create(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+    ensure_session_auth(&auth)?;
+
+    let payload = body
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0;
+
+    let name = payload
+        .get("name")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ApiError::bad_request("missing name"))?
+        .to_string();
+
+    // Keys default to the caller's scopes and may never exceed them.
+    let scopes: Vec<String> = match payload.get("scopes") {
+        None | Some(Value::Null) => auth.scopes.clone(),
+        Some(value) => serde_json::from_value(value.clone()).map_err(ApiError::from)?,
+    };
+    if scopes.is_empty() {
+        return Err(ApiError::bad_request("scopes must not be empty"));
+    }
+    if let Some(scope) = scopes.iter().find(|s| !auth.has_scope(s)) {
+        return Err(ApiError::forbidden(format!(
+            "cannot grant scope `{}` you do not have",
+            scope
+        )));
+    }
+
+    let now = Utc::now();
+    let expires_at = match payload.get("expiresInDays") {
+        None | Some(Value::Null) => None,
+        Some(value) => {
+            let days = value
+                .as_i64()
+                .filter(|d| *d > 0)
+                .ok_or_else(|| ApiError::bad_request("expiresInDays must be a positive integer"))?;
+            Some((now + Duration::days(days)).to_rfc3339())
+        }
+    };
+
+    let generated = GeneratedApiKey::generate();
+    let row = db_api_keys::ApiKeysRow {
+        id: Uuid::new_v4(),
+        user_id,
+        name,
+        key_prefix: generated.prefix,
+        key_hash: generated.hash,
+        scopes: serde_json::to_string(&scopes).map_err(ApiError::from)?,
+        expires_at,
+        last_used_at: None,
+        created_at: now.to_rfc3339(),
+    };
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    db_api_keys::insert_api_key(&mut *conn, &row)
+        .await
+        .map_err(ApiError::from)?;
+    tracing::info!(user_id = %user_id, api_key_id = %row.id, "created API key");
+
+    let mut response = api_key_to_payload(&row);
+    response["key"] = Value::String(generated.key);
+    Ok(Json(response))
+}
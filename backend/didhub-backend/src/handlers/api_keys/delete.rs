@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use didhub_db::generated::api_keys as db_api_keys;
+
+use super::ensure_session_auth;
+use crate::{error::ApiError, state::AppState};
+
+/// Revoke (delete) an API key. Only the owner or an admin may do so.
+pub async fn delete(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    ensure_session_auth(&auth)?;
+
+    let key_id_str = path
+        .get("keyId")
+        .ok_or_else(|| ApiError::bad_request("missing keyId"))?;
+    let key_id = Uuid::parse_str(key_id_str).map_err(|_| ApiError::bad_request("invalid keyId"))?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let existing = db_api_keys::find_by_primary_key(&mut *conn, &key_id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("api key not found"))?;
+    crate::handlers::auth::utils::ensure_admin_or_user(&auth, existing.user_id)?;
+
+    db_api_keys::delete_by_primary_key(&mut *conn, &key_id)
+        .await
+        .map_err(ApiError::from)?;
+    tracing::info!(api_key_id = %key_id, deleted_by = ?auth.user_id, "deleted API key");
+
+    Ok(Json(json!({ "deleted": true })))
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use didhub_db::generated::api_keys as db_api_keys;
+
+use super::api_key_to_payload;
+use crate::{error::ApiError, state::AppState};
+
+/// List the current user's API keys.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let mut rows = db_api_keys::find_by_user_id(&mut *conn, &user_id)
+        .await
+        .map_err(ApiError::from)?;
+    rows.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    let items: Vec<Value> = rows.iter().map(api_key_to_payload).collect();
+    Ok(Json(json!({ "items": items })))
+}
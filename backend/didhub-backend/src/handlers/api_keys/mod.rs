@@ -0,0 +1,41 @@
+pub mod create;
+pub mod delete;
+pub mod list;
+
+use serde_json::{json, Value};
+
+use didhub_auth::auth::AuthContext;
+use didhub_db::generated::api_keys as db_api_keys;
+
+use crate::error::ApiError;
+
+/// Public view of a stored key; the hash is never returned.
+pub fn api_key_to_payload(row: &db_api_keys::ApiKeysRow) -> Value {
+    let scopes: Vec<String> = serde_json::from_str(&row.scopes).unwrap_or_default();
+    json!({
+        "id": row.id,
+        "name": row.name,
+        "prefix": row.key_prefix,
+        "scopes": scopes,
+        "expiresAt": row.expires_at,
+        "lastUsedAt": row.last_used_at,
+        "createdAt": row.created_at,
+    })
+}
+
+/// API keys are managed with a user's own session only, so a leaked key cannot
+/// mint or revoke other keys and an impersonating admin cannot leave a
+/// permanent credential behind.
+fn ensure_session_auth(auth: &AuthContext) -> Result<(), ApiError> {
+    if auth.metadata.get("apiKeyId").is_some() {
+        return Err(ApiError::forbidden(
+            "API keys cannot be managed with an API key",
+        ));
+    }
+    if auth.is_impersonated() {
+        return Err(ApiError::forbidden(
+            "API keys cannot be managed while impersonating",
+        ));
+    }
+    Ok(())
+}
@@ -3,19 +3,40 @@ use axum::{
     http::HeaderMap,
     Json,
 };
+use didhub_db::custom::audit_changes;
+use didhub_db::generated::audit_changes as db_audit_changes;
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::sync::Arc;
+use uuid::Uuid;
 
 use crate::error::ApiError;
+use crate::handlers::utils::{
+    encode_cursor, parse_cursor_params, parse_positive_usize, CursorParams,
+};
 use crate::state::AppState;
 
+/// Largest page of entity history returned at once.
+const MAX_PER_PAGE: usize = 100;
+
+/// GET /admin/audit
+/// With `entityId`, list the recorded field-level changes of that entity, newest
+/// first; `includeArchived=true` adds changes moved to the audit archive, and
+/// `cursor` or `limit` returns one keyset page. The general request audit trail goes to the log pipeline and cannot be
+/// listed here.
 pub async fn list(
-    Extension(_state): Extension<Arc<AppState>>,
-    _headers: HeaderMap,
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
     query: Option<Query<HashMap<String, String>>>,
 ) -> Result<Json<Value>, ApiError> {
-    let _ = query;
+    let params = query.map(|value| value.0).unwrap_or_default();
+    if let Some(entity_id) = params.get("entityId") {
+        crate::handlers::auth::utils::require_admin(&state, &headers).await?;
+        let entity_id =
+            Uuid::parse_str(entity_id).map_err(|_| ApiError::bad_request("invalid entityId"))?;
+        return entity_history(&state, entity_id, &params).await;
+    }
+
     let empty_items: Vec<Value> = Vec::new();
     let response = json!({
         "items": empty_items,
@@ -29,3 +50,74 @@ pub async fn list(
 
     Ok(Json(response))
 }
+
+async fn entity_history(
+    state: &AppState,
+    entity_id: Uuid,
+    params: &HashMap<String, String>,
+) -> Result<Json<Value>, ApiError> {
+    let include_archived = params
+        .get("includeArchived")
+        .is_some_and(|v| v == "true" || v == "1");
+    let mut conn = state.acquire_read().await?;
+
+    // With `cursor` or `limit`, return one keyset page, newest first
+    if let Some(CursorParams { after, limit }) = parse_cursor_params(params)? {
+        let mut rows = audit_changes::history_page(
+            &mut *conn,
+            &entity_id,
+            include_archived,
+            after
+                .as_ref()
+                .map(|(created_at, id)| (created_at.as_str(), id)),
+            limit as i64 + 1,
+        )
+        .await?;
+        let next_cursor = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last()
+                .map(|row| encode_cursor(&row.created_at, &row.id))
+        } else {
+            None
+        };
+        let items: Vec<Value> = rows.into_iter().map(change_entry).collect();
+        return Ok(Json(json!({ "items": items, "nextCursor": next_cursor })));
+    }
+
+    let page = parse_positive_usize(params.get("page"), 1, "page")?;
+    let per_page = parse_positive_usize(params.get("perPage"), 20, "perPage")?.min(MAX_PER_PAGE);
+    let offset = (page - 1) * per_page;
+    let total = audit_changes::count_for_entity(&mut *conn, &entity_id, include_archived).await?;
+    let rows = audit_changes::history_for_entity(
+        &mut *conn,
+        &entity_id,
+        include_archived,
+        per_page as i64,
+        offset as i64,
+    )
+    .await?;
+
+    let items: Vec<Value> = rows.into_iter().map(change_entry).collect();
+
+    Ok(Json(json!({
+        "items": items,
+        "pagination": {
+            "page": page,
+            "perPage": per_page,
+            "total": total,
+        }
+    })))
+}
+
+fn change_entry(row: db_audit_changes::AuditChangesRow) -> Value {
+    json!({
+        "id": row.id,
+        "category": "audit",
+        "message": format!("{} {}", row.action, row.entity_type),
+        "actor": row.actor_user_id,
+        "createdAt": row.created_at,
+        "entityType": row.entity_type,
+        "entityId": row.entity_id,
+        "changes": serde_json::from_str::<Value>(&row.changes).unwrap_or(Value::Null),
+    })
+}
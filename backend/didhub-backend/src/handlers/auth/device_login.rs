@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::http::{header::SET_COOKIE, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use didhub_auth::auth::AuthError;
+use didhub_db::generated::users as db_users;
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::device_tokens;
+use crate::handlers::auth::login::{start_session, user_is_approved};
+use crate::{error::ApiError, state::AppState};
+
+/// POST /auth/device-login
+/// Exchange the remembered device cookie for a new session. The device token is rotated
+/// and its expiry extended; an unknown or expired token clears the cookie and fails.
+pub async fn device_login(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let token = device_tokens::token_from_cookie(&headers)
+        .ok_or(ApiError::Authentication(AuthError::AuthenticationFailed))?;
+
+    let Some((device, rotated)) = device_tokens::redeem(&state, &token, &headers).await? else {
+        warn!("Device login failed: unknown or expired device token");
+        return Ok(forget_device());
+    };
+
+    let user = db_users::find_by_primary_key(&*state.db_pool, &device.user_id)
+        .await
+        .map_err(ApiError::from)?;
+    let Some(user) = user.filter(|u| user_is_approved(&u.roles)) else {
+        warn!(user_id = %device.user_id, "Device login failed: account missing or not approved");
+        return Ok(forget_device());
+    };
+
+    let session = start_session(&state, user.id, &user.roles, &headers).await?;
+    info!(user_id = %user.id, device_id = %device.id, "User logged in with remembered device");
+
+    let device_cookie = device_tokens::device_cookie(rotated, state.device_token_ttl());
+    let mut resp = (StatusCode::OK, Json(json!({"user_id": user.id}))).into_response();
+    for cookie in [session, device_cookie] {
+        resp.headers_mut().append(
+            SET_COOKIE,
+            HeaderValue::from_str(&cookie.to_string()).unwrap(),
+        );
+    }
+    Ok(resp)
+}
+
+fn forget_device() -> Response {
+    let mut resp = ApiError::Authentication(AuthError::AuthenticationFailed).into_response();
+    resp.headers_mut().append(
+        SET_COOKIE,
+        HeaderValue::from_str(&device_tokens::clear_device_cookie().to_string()).unwrap(),
+    );
+    resp
+}
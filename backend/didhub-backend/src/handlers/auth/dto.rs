@@ -4,4 +4,9 @@ pub struct Login {
     /// SHA-256 hash of the password (64 hex characters)
     #[serde(alias = "password")]
     pub password_hash: String,
+    /// Remember this device so it can sign in again without the password.
+    #[serde(default, alias = "rememberMe")]
+    pub remember_me: bool,
+    #[serde(default, alias = "deviceName")]
+    pub device_name: Option<String>,
 }
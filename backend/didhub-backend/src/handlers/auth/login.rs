@@ -1,28 +1,33 @@
 use std::sync::Arc;
 
 use axum::extract::Extension;
-use axum::http::{header::SET_COOKIE, StatusCode};
+use axum::http::{header::SET_COOKIE, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Json, Response};
 use chrono::Utc;
+use cookie::Cookie;
 use didhub_db::generated::users as db_users;
 use jsonwebtoken::{encode, EncodingKey, Header};
 use serde_json::json;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
+use uuid::Uuid;
 
 use crate::handlers::auth::utils::get_jwt_secret;
 use crate::{error::ApiError, state::AppState};
 
-/// Helper to check if a user has a specific role
-fn user_has_role(roles_json: &str, role: &str) -> bool {
+/// Approved accounts hold at least one role; `system` alone does not count.
+pub(crate) fn user_is_approved(roles_json: &str) -> bool {
     serde_json::from_str::<Vec<String>>(roles_json)
-        .map(|roles| roles.iter().any(|r| r == role))
+        .map(|roles| !didhub_auth::auth::roles_from_names(&roles).is_empty())
         .unwrap_or(false)
 }
 
 /// POST /auth/login
-/// Accepts { email, password } and if valid issues an HttpOnly cookie with an HS256 JWT.
+/// Accepts { username, password } and if valid issues an HttpOnly cookie with an HS256 JWT.
+/// With `rememberMe`, the device is also remembered (see [`crate::device_tokens`]) under
+/// `deviceName` or its user agent.
 pub async fn login(
     Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
     body: Option<Json<serde_json::Value>>,
 ) -> Result<Response, ApiError> {
     let payload = body
@@ -32,8 +37,9 @@ pub async fn login(
         .clone();
 
     let dto: super::dto::Login = serde_json::from_value(payload).map_err(ApiError::from)?;
-
-    let secret = get_jwt_secret()?;
+    if let Some(name) = dto.device_name.as_deref().filter(|_| dto.remember_me) {
+        crate::device_tokens::normalize_name(name)?;
+    }
 
     // Lookup user by username
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
@@ -48,37 +54,112 @@ pub async fn login(
     // Verify password using didhub_auth
     // Clients MUST provide a SHA-256 pre-hashed password (64 hex characters)
     didhub_auth::auth::verify_client_password(&dto.password_hash, &user.password_hash).map_err(
-        |_| {
-            warn!(username = %dto.username, user_id = %user.id, "Login failed: password mismatch");
+        |e| {
+            match e {
+                didhub_auth::auth::PasswordError::UnknownPepper(version) => error!(
+                    user_id = %user.id,
+                    version,
+                    "Login failed: password hash uses a pepper version that is not configured"
+                ),
+                _ => warn!(username = %dto.username, user_id = %user.id, "Login failed: password mismatch"),
+            }
             ApiError::Authentication(didhub_auth::auth::AuthError::AuthenticationFailed)
         },
     )?;
 
-    // Check if user is approved (has 'user' role) or is admin (admins bypass approval check)
-    let is_admin = user_has_role(&user.roles, "admin");
-    let is_approved = user_has_role(&user.roles, "user");
-    if !is_approved && !is_admin {
+    // Check if user is approved (holds a role such as 'user', 'viewer' or 'admin')
+    if !user_is_approved(&user.roles) {
         warn!(username = %dto.username, user_id = %user.id, "Login failed: account not approved");
         return Err(ApiError::forbidden("Account awaiting approval"));
     }
 
+    // Upgrade legacy bcrypt, outdated Argon2 or differently peppered hashes now that the password is known to be correct
+    if didhub_auth::auth::password_needs_rehash(&user.password_hash) {
+        match didhub_auth::auth::hash_client_password(&dto.password_hash) {
+            Ok(rehashed) => {
+                sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+                    .bind(&rehashed)
+                    .bind(user.id)
+                    .execute(&mut *conn)
+                    .await
+                    .map_err(ApiError::from)?;
+                info!(user_id = %user.id, "Upgraded stored password hash to Argon2id");
+            }
+            Err(e) => warn!(user_id = %user.id, error = %e, "Failed to rehash password"),
+        }
+    }
+
+    drop(conn);
+    info!(username = %dto.username, user_id = %user.id, "User logged in successfully");
+
+    let session = start_session(&state, user.id, &user.roles, &headers).await?;
+
+    let mut resp = (StatusCode::OK, Json(json!({"user_id": user.id}))).into_response();
+    resp.headers_mut().append(
+        SET_COOKIE,
+        axum::http::HeaderValue::from_str(&session.to_string()).unwrap(),
+    );
+    if dto.remember_me {
+        let token =
+            crate::device_tokens::remember(&state, user.id, dto.device_name.as_deref(), &headers)
+                .await?;
+        let cookie = crate::device_tokens::device_cookie(token, state.device_token_ttl());
+        resp.headers_mut().append(
+            SET_COOKIE,
+            axum::http::HeaderValue::from_str(&cookie.to_string()).unwrap(),
+        );
+    }
+    Ok(resp)
+}
+
+/// Issue a session token for a user who just signed in, record the session and
+/// update `last_login_at`. Returns the session cookie.
+pub(crate) async fn start_session(
+    state: &AppState,
+    user_id: Uuid,
+    roles_json: &str,
+    headers: &HeaderMap,
+) -> Result<Cookie<'static>, ApiError> {
+    let (token, _) = issue_session_token(state, user_id, roles_json, headers).await?;
+    Ok(Cookie::build(("didhub_session", token))
+        .path("/")
+        .http_only(true)
+        .secure(true)
+        .same_site(cookie::SameSite::Lax)
+        .build())
+}
+
+/// Like [`start_session`], but returns the bare token and its lifetime in seconds for
+/// clients that send it as a bearer token instead of a cookie.
+pub(crate) async fn issue_session_token(
+    state: &AppState,
+    user_id: Uuid,
+    roles_json: &str,
+    headers: &HeaderMap,
+) -> Result<(String, u64), ApiError> {
+    let secret = get_jwt_secret()?;
+
     // Update last_login_at
     let now = Utc::now().to_rfc3339();
     sqlx::query("UPDATE users SET last_login_at = ? WHERE id = ?")
         .bind(&now)
-        .bind(user.id)
-        .execute(&mut *conn)
+        .bind(user_id)
+        .execute(&*state.db_pool)
         .await
         .map_err(ApiError::from)?;
 
-    info!(username = %dto.username, user_id = %user.id, "User logged in successfully");
-
     // Build claims - scopes are derived from roles
-    let exp = (Utc::now().timestamp() + 7 * 24 * 60 * 60) as usize; // 7 days expiry
-    let roles: Vec<String> = serde_json::from_str(&user.roles).unwrap_or_default();
+    let now = Utc::now();
+    let iat = now.timestamp();
+    let exp = (iat + 7 * 24 * 60 * 60) as usize; // 7 days expiry
+    let jti = uuid::Uuid::new_v4().to_string();
+    let roles: Vec<String> = serde_json::from_str(roles_json).unwrap_or_default();
     let claims = serde_json::json!({
-        "sub": user.id.to_string(),
+        "sub": user_id.to_string(),
         "exp": exp,
+        "iat": iat,
+        "iat_ms": now.timestamp_millis(),
+        "jti": jti,
         "scopes": roles
     });
 
@@ -89,18 +170,11 @@ pub async fn login(
     )
     .map_err(|e| ApiError::Unexpected(format!("jwt encode failed: {}", e)))?;
 
-    // Set cookie
-    let cookie = cookie::Cookie::build(("didhub_session", token))
-        .path("/")
-        .http_only(true)
-        .secure(true)
-        .same_site(cookie::SameSite::Lax)
-        .build();
+    // Session bookkeeping only; the token is valid whether or not it is recorded
+    let expires_at = chrono::DateTime::from_timestamp(exp as i64, 0).unwrap_or_default();
+    if let Err(e) = crate::sessions::record_login(state, user_id, &jti, expires_at, headers).await {
+        warn!(user_id = %user_id, error = %e, "Failed to record session");
+    }
 
-    let mut resp = (StatusCode::OK, Json(json!({"user_id": user.id}))).into_response();
-    resp.headers_mut().append(
-        SET_COOKIE,
-        axum::http::HeaderValue::from_str(&cookie.to_string()).unwrap(),
-    );
-    Ok(resp)
+    Ok((token, (exp as i64 - iat) as u64))
 }
@@ -1,14 +1,43 @@
 use std::sync::Arc;
 
 use axum::extract::Extension;
-use axum::http::{header::SET_COOKIE, StatusCode};
+use axum::http::{header::SET_COOKIE, HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Json};
 use cookie::Cookie;
 use serde_json::json;
+use tracing::warn;
 
+use crate::handlers::auth::utils::authenticate_optional;
 use crate::state::AppState;
 
-pub async fn logout(Extension(_state): Extension<Arc<AppState>>) -> impl IntoResponse {
+/// POST /auth/logout
+/// Clears the session cookie and revokes the presented token so it cannot be reused.
+/// A remembered device token sent along is forgotten as well.
+pub async fn logout(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    if let Ok(Some(auth)) = authenticate_optional(&state, &headers).await {
+        if let Some(jti) = auth.token_id.as_deref() {
+            if let Err(e) = state
+                .revocation_store()
+                .revoke_token(jti, auth.expires_at)
+                .await
+            {
+                warn!(error = %e, user_id = ?auth.user_id, "failed to revoke token on logout");
+            }
+            if let Err(e) = crate::sessions::forget_token(&state, jti).await {
+                warn!(error = %e, user_id = ?auth.user_id, "failed to delete session on logout");
+            }
+        }
+    }
+
+    if let Some(token) = crate::device_tokens::token_from_cookie(&headers) {
+        if let Err(e) = crate::device_tokens::forget_token(&state, &token).await {
+            warn!(error = %e, "failed to forget device on logout");
+        }
+    }
+
     let cookie = Cookie::build(("didhub_session", ""))
         .path("/")
         .http_only(true)
@@ -18,9 +47,11 @@ pub async fn logout(Extension(_state): Extension<Arc<AppState>>) -> impl IntoRes
         .build();
 
     let mut resp = (StatusCode::OK, Json(json!({ "ok": true }))).into_response();
-    resp.headers_mut().append(
-        SET_COOKIE,
-        axum::http::HeaderValue::from_str(&cookie.to_string()).unwrap(),
-    );
+    for cookie in [cookie, crate::device_tokens::clear_device_cookie()] {
+        resp.headers_mut().append(
+            SET_COOKIE,
+            axum::http::HeaderValue::from_str(&cookie.to_string()).unwrap(),
+        );
+    }
     resp
 }
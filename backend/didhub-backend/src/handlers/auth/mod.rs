@@ -1,6 +1,9 @@
+pub mod device_login;
 pub mod dto;
 pub mod login;
 pub mod logout;
 pub mod me;
+pub mod password_reset_confirm;
+pub mod password_reset_request;
 pub mod refresh;
 pub mod utils;
@@ -0,0 +1,100 @@
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::http::HeaderMap;
+use axum::Json;
+use chrono::{DateTime, Utc};
+use didhub_db::generated::password_reset_tokens as db_tokens;
+use didhub_log_client::LogCategory;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::password_policy::check_submitted_password;
+use crate::password_reset::hash_token;
+use crate::sessions::client_ip;
+use crate::{error::ApiError, state::AppState};
+
+/// POST /auth/password-reset/confirm
+/// Accepts { token, newPasswordHash } and sets the password of the token's account.
+/// The token is consumed and every existing session of the account is revoked.
+pub async fn confirm(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let payload = body
+        .as_ref()
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0
+        .clone();
+    let token = payload
+        .get("token")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ApiError::bad_request("missing token"))?;
+    let new_pass_hash = payload
+        .get("newPasswordHash")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ApiError::bad_request("missing newPasswordHash"))?;
+
+    let invalid = || ApiError::bad_request("invalid or expired reset token");
+    let row = db_tokens::find_first_by_token_hash(&*state.db_pool, &hash_token(token))
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(invalid)?;
+    let expired =
+        DateTime::parse_from_rfc3339(&row.expires_at).map_or(true, |exp| exp <= Utc::now());
+    if row.used_at.is_some() || expired {
+        return Err(invalid());
+    }
+
+    let violations = check_submitted_password(&state.password_policy(), new_pass_hash);
+    if let Some(violation) = violations.first() {
+        return Err(ApiError::bad_request(violation.to_string()));
+    }
+    let password_hash = didhub_auth::auth::hash_client_password(new_pass_hash)
+        .map_err(|e| ApiError::Unexpected(format!("Hashing failed: {}", e)))?;
+
+    // Claim the token first so that concurrent confirmations cannot both succeed
+    let now = Utc::now().to_rfc3339();
+    let claimed = sqlx::query(
+        "UPDATE password_reset_tokens SET used_at = ? WHERE id = ? AND used_at IS NULL",
+    )
+    .bind(&now)
+    .bind(row.id)
+    .execute(&*state.db_pool)
+    .await
+    .map_err(ApiError::from)?
+    .rows_affected();
+    if claimed == 0 {
+        return Err(invalid());
+    }
+
+    sqlx::query(
+        "UPDATE users SET password_hash = ?, must_change_password = 0, updated_at = ? WHERE id = ?",
+    )
+    .bind(&password_hash)
+    .bind(&now)
+    .bind(row.user_id)
+    .execute(&*state.db_pool)
+    .await
+    .map_err(ApiError::from)?;
+
+    state
+        .revocation_store()
+        .revoke_user_tokens(row.user_id)
+        .await?;
+    if let Err(e) = crate::sessions::forget_user_sessions(&state, row.user_id).await {
+        warn!(user_id = %row.user_id, error = %e, "failed to delete revoked sessions");
+    }
+    if let Err(e) = crate::device_tokens::forget_user_devices(&state, row.user_id).await {
+        warn!(user_id = %row.user_id, error = %e, "failed to forget remembered devices");
+    }
+
+    LogCategory::Audit.log(
+        tracing::Level::INFO,
+        "password reset completed",
+        Some(json!({ "user_id": row.user_id, "ip": client_ip(&headers) })),
+    );
+
+    Ok(Json(json!({ "reset": true })))
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::http::HeaderMap;
+use axum::Json;
+use chrono::Utc;
+use didhub_db::generated::{password_reset_tokens as db_tokens, user_emails as db_emails};
+use didhub_log_client::LogCategory;
+use serde_json::{json, Value};
+use tracing::{debug, error, warn};
+
+use crate::mailer::{normalize_address, OutgoingEmail};
+use crate::password_reset::{allow_for_account, allow_from_address, generate_token};
+use crate::sessions::client_ip;
+use crate::{error::ApiError, state::AppState};
+
+/// POST /auth/password-reset/request
+/// Accepts { email } and, if an account uses that address, emails it a reset link.
+/// The response is the same whether or not an account matched.
+pub async fn request(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let mailer = state
+        .mailer()
+        .ok_or_else(|| ApiError::not_implemented("password reset by email"))?;
+
+    let email = body
+        .as_ref()
+        .and_then(|b| b.0.get("email"))
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| ApiError::bad_request("missing email"))?;
+    let email = normalize_address(email).ok_or_else(|| ApiError::bad_request("invalid email"))?;
+
+    let address = client_ip(&headers).unwrap_or_else(|| "unknown".to_string());
+    if !allow_from_address(&state.cache, &address).await {
+        warn!(ip = %address, "password reset requests rate limited");
+        return Err(ApiError::too_many_requests(
+            "too many password reset requests; try again later",
+        ));
+    }
+
+    let accepted = Json(json!({ "requested": true }));
+    let Some(contact) = db_emails::find_first_by_email(&*state.db_pool, &email)
+        .await
+        .map_err(ApiError::from)?
+    else {
+        debug!("password reset requested for unknown email");
+        return Ok(accepted);
+    };
+    let user_id = contact.user_id;
+    if !allow_for_account(&state.cache, user_id).await {
+        warn!(user_id = %user_id, "password reset emails rate limited for account");
+        return Ok(accepted);
+    }
+
+    // Only the newest link works
+    sqlx::query("DELETE FROM password_reset_tokens WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&*state.db_pool)
+        .await
+        .map_err(ApiError::from)?;
+
+    let settings = state.password_reset();
+    let (token, token_hash) = generate_token();
+    let now = Utc::now();
+    let expires_at = now
+        + chrono::Duration::from_std(settings.token_ttl)
+            .map_err(|e| ApiError::internal(e.to_string()))?;
+    let row = db_tokens::PasswordResetTokensRow {
+        id: uuid::Uuid::new_v4(),
+        user_id,
+        token_hash,
+        requested_ip: client_ip(&headers),
+        created_at: now.to_rfc3339(),
+        expires_at: expires_at.to_rfc3339(),
+        used_at: None,
+    };
+    db_tokens::insert_password_reset_token(&*state.db_pool, &row)
+        .await
+        .map_err(ApiError::from)?;
+
+    LogCategory::Audit.log(
+        tracing::Level::INFO,
+        "password reset requested",
+        Some(json!({ "user_id": user_id, "ip": row.requested_ip })),
+    );
+
+    let minutes = settings.token_ttl.as_secs() / 60;
+    let message = OutgoingEmail {
+        to: email,
+        subject: "Reset your DIDHub password".to_string(),
+        body: format!(
+            "Someone asked to reset the password of your DIDHub account.\n\n\
+             Open this link within {} minutes to choose a new password:\n\n{}\n\n\
+             If this wasn't you, you can ignore this email; your password stays unchanged.\n",
+            minutes,
+            settings.reset_link(&token)
+        ),
+    };
+    if let Err(e) = mailer.send(message).await {
+        error!(user_id = %user_id, error = %e, "failed to send password reset email");
+    }
+
+    Ok(accepted)
+}
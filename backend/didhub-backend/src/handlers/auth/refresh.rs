@@ -32,13 +32,31 @@ pub async fn refresh(
             didhub_auth::auth::AuthError::AuthenticationFailed,
         ));
     }
+    // Impersonation tokens are short-lived by design; a refresh would drop the
+    // `act` claim and hand out a full-length session for the target user.
+    if auth.is_impersonated() {
+        return Err(ApiError::forbidden(
+            "impersonation tokens cannot be refreshed",
+        ));
+    }
+    // A JWT minted from an API key would outlive the key's deletion and pass as
+    // a user session.
+    if auth.metadata.get("apiKeyId").is_some() {
+        return Err(ApiError::forbidden("API keys cannot be refreshed"));
+    }
 
     let secret = get_jwt_secret()?;
 
-    let exp = (Utc::now().timestamp() + 7 * 24 * 60 * 60) as usize; // 7 days expiry
+    let now = Utc::now();
+    let iat = now.timestamp();
+    let exp = (iat + 7 * 24 * 60 * 60) as usize; // 7 days expiry
+    let jti = uuid::Uuid::new_v4().to_string();
     let claims = serde_json::json!({
         "sub": auth.user_id.map(|u| u.to_string()),
         "exp": exp,
+        "iat": iat,
+        "iat_ms": now.timestamp_millis(),
+        "jti": jti,
         "scopes": auth.scopes,
     });
 
@@ -49,6 +67,23 @@ pub async fn refresh(
     )
     .map_err(|e| ApiError::Unexpected(format!("jwt encode failed: {}", e)))?;
 
+    // Carry the session over to the new token; tokens without one start a session
+    let expires_at = chrono::DateTime::from_timestamp(exp as i64, 0).unwrap_or_default();
+    let tracked = match crate::sessions::rotate_token(&state, &auth, &jti, expires_at).await {
+        Ok(rotated) => rotated,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to rotate session token");
+            true
+        }
+    };
+    if let (false, Some(user_id)) = (tracked, auth.user_id) {
+        if let Err(e) =
+            crate::sessions::record_login(&state, user_id, &jti, expires_at, &headers).await
+        {
+            tracing::warn!(%user_id, error = %e, "failed to record session");
+        }
+    }
+
     let cookie = cookie::Cookie::build(("didhub_session", token))
         .path("/")
         .http_only(true)
@@ -1,6 +1,6 @@
 use crate::{error::ApiError, state::AppState};
 use axum::http::HeaderMap;
-use didhub_auth::auth::{AuthContext, AuthError};
+use didhub_auth::auth::{AuthContext, AuthError, Permission};
 use tracing::debug;
 use uuid::Uuid;
 
@@ -36,6 +36,57 @@ fn authentication_failed() -> ApiError {
     ApiError::Authentication(AuthError::AuthenticationFailed)
 }
 
+/// Authentication of a request, resolved once by a middleware and stored in the
+/// request extensions. Handlers only see the headers, so [`ResolvedAuth::scope`]
+/// also keeps it for the rest of the request, where the `authenticate_*` helpers
+/// reuse it for the same credentials instead of authenticating again.
+#[derive(Debug, Clone)]
+pub struct ResolvedAuth {
+    token: Option<String>,
+    result: Result<AuthContext, AuthError>,
+}
+
+tokio::task_local! {
+    static RESOLVED_AUTH: ResolvedAuth;
+}
+
+impl ResolvedAuth {
+    /// Authenticate the credentials in `headers`.
+    pub async fn resolve(state: &AppState, headers: &HeaderMap) -> Self {
+        let token = extract_auth_token(headers);
+        let result = authenticate_token(state, token.as_deref()).await;
+        Self { token, result }
+    }
+
+    pub fn auth(&self) -> Option<&AuthContext> {
+        self.result.as_ref().ok()
+    }
+
+    /// Run `fut` with this authentication available to the helpers it calls.
+    pub async fn scope<F: std::future::Future>(self, fut: F) -> F::Output {
+        RESOLVED_AUTH.scope(self, fut).await
+    }
+}
+
+/// Authenticate `token`, reusing the result resolved for it earlier in this request.
+async fn authenticate_token(
+    state: &AppState,
+    token: Option<&str>,
+) -> Result<AuthContext, AuthError> {
+    let resolved = RESOLVED_AUTH
+        .try_with(|resolved| (resolved.token.as_deref() == token).then(|| resolved.result.clone()))
+        .ok()
+        .flatten();
+    if let Some(result) = resolved {
+        return result;
+    }
+    let result = state.authenticator().authenticate(token).await;
+    if let Ok(auth) = &result {
+        crate::sessions::touch(state, auth).await;
+    }
+    result
+}
+
 /// Extract authentication token from headers.
 /// Checks Authorization header first, then falls back to session cookie.
 /// Returns the token in "Bearer <token>" format for use with the authenticator.
@@ -71,6 +122,18 @@ pub async fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<(),
     ensure_admin(&auth)
 }
 
+/// Require that the request is authenticated by an approved user whose roles grant
+/// `permission`.
+pub async fn require_permission(
+    state: &AppState,
+    headers: &HeaderMap,
+    permission: Permission,
+) -> Result<AuthContext, ApiError> {
+    let auth = authenticate_and_require_approved(state, headers).await?;
+    ensure_permission(&auth, permission)?;
+    Ok(auth)
+}
+
 pub async fn authenticate_required(
     state: &AppState,
     headers: &HeaderMap,
@@ -92,6 +155,15 @@ pub fn ensure_admin(auth: &AuthContext) -> Result<(), ApiError> {
     }
 }
 
+pub fn ensure_permission(auth: &AuthContext, permission: Permission) -> Result<(), ApiError> {
+    if auth.has_permission(permission) {
+        Ok(())
+    } else {
+        debug!(user_id = ?auth.user_id, ?permission, "permission denied");
+        Err(ApiError::forbidden("insufficient permissions"))
+    }
+}
+
 pub fn ensure_admin_or(auth: &AuthContext, allowed: bool) -> Result<(), ApiError> {
     if auth.is_admin() || allowed {
         Ok(())
@@ -105,7 +177,7 @@ pub fn ensure_admin_or_user(auth: &AuthContext, user_id: Uuid) -> Result<(), Api
 }
 
 /// Authenticate using the provided optional Authorization header value or session cookie and ensure
-/// the authenticated user is approved (has 'user' role). Admin scoped users bypass the
+/// the authenticated user is approved (holds at least one role). Admin scoped users bypass the
 /// approval check.
 pub async fn authenticate_and_require_approved(
     state: &AppState,
@@ -115,11 +187,7 @@ pub async fn authenticate_and_require_approved(
 
     // Attempt authentication and log failures with enough context to debug without
     // including sensitive token contents.
-    let auth = match state
-        .authenticator()
-        .authenticate(auth_token.as_deref())
-        .await
-    {
+    let auth = match authenticate_token(state, auth_token.as_deref()).await {
         Ok(a) => a,
         Err(e) => {
             debug!(error = ?e, header_present = auth_token.is_some(), "authentication failure");
@@ -132,14 +200,23 @@ pub async fn authenticate_and_require_approved(
         return Ok(auth);
     }
 
+    // Service clients have no user; their scopes are checked like a user's roles
+    if auth.is_service() {
+        if auth.has_permission(Permission::ViewContent) {
+            return Ok(auth);
+        }
+        debug!(client_id = ?auth.client_id, "service client has no role scope");
+        return Err(authentication_failed());
+    }
+
     // Non-admins must be authenticated with a user id
     let user_id = require_user_id(&auth)?;
 
-    // Check that the user has the 'user' role (which means they're approved)
+    // Approved users hold at least one role, and every role can view content.
     // The scopes in the auth context are derived from the user's roles at login time
-    let is_approved = auth.scopes.iter().any(|s| s == "user");
+    let is_approved = auth.has_permission(Permission::ViewContent);
     if !is_approved {
-        debug!(user_id = %user_id, "user not approved (missing 'user' role)");
+        debug!(user_id = %user_id, "user not approved (no role)");
         return Err(authentication_failed());
     }
 
@@ -158,7 +235,7 @@ pub async fn authenticate_optional(
     };
 
     // Attempt authentication
-    match state.authenticator().authenticate(Some(&auth_token)).await {
+    match authenticate_token(state, Some(&auth_token)).await {
         Ok(auth) => Ok(Some(auth)),
         Err(_) => Ok(None), // Authentication failed, but we don't fail the request
     }
@@ -18,6 +18,7 @@ struct RestoreRequest {
 
 /// POST /admin/restore
 /// Restore from a backup. Accepts { uploadId: uuid } referencing an uploaded backup file.
+/// The job validates the file and saves a copy of the current database before restoring.
 pub async fn restore(
     Extension(state): Extension<Arc<AppState>>,
     headers: HeaderMap,
@@ -44,6 +45,7 @@ pub async fn restore(
         "backup.restore",
         json!({
             "upload_id": request.upload_id,
+            "stored_file_id": upload.stored_file_id,
             "stored_name": upload.stored_name,
             "triggered_at": Utc::now().to_rfc3339(),
         }),
@@ -51,7 +53,7 @@ pub async fn restore(
 
     let result = state
         .job_queue
-        .enqueue(job_request)
+        .spawn_job(job_request)
         .await
         .map_err(ApiError::from)?;
 
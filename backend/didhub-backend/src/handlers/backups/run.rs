@@ -10,33 +10,32 @@ use uuid::Uuid;
 use crate::{error::ApiError, state::AppState};
 
 /// POST /admin/backup
-/// Trigger a database backup. Returns information about the backup job.
+/// Trigger a database backup. Returns information about the backup job, whose
+/// progress is listed under /admin/jobs/runs.
 pub async fn run(
     Extension(state): Extension<Arc<AppState>>,
     headers: HeaderMap,
 ) -> Result<Json<Value>, ApiError> {
     crate::handlers::auth::utils::require_admin(&state, &headers).await?;
 
-    // Create a backup job through the job queue
+    // The backup file is named after the backup id
+    let backup_id = Uuid::new_v4();
+    let started_at = Utc::now();
     let job_request = didhub_job_queue::JobRequest::new(
         "backup.create",
         json!({
-            "triggered_at": Utc::now().to_rfc3339(),
-            "type": "full"
+            "triggered_at": started_at.to_rfc3339(),
+            "type": "full",
+            "backup_id": backup_id,
         }),
     );
 
     let result = state
         .job_queue
-        .enqueue(job_request)
+        .spawn_job(job_request)
         .await
         .map_err(ApiError::from)?;
 
-    // For SQLite, we can create an actual backup file
-    // For other databases, this would trigger appropriate backup procedures
-    let backup_id = Uuid::new_v4();
-    let started_at = Utc::now();
-
     // Log the backup attempt
     tracing::info!(
         backup_id = %backup_id,
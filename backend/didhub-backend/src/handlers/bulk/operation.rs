@@ -10,8 +10,7 @@ use crate::error::ApiError;
 use crate::handlers::utils::parse_json_array_fields;
 use crate::state::AppState;
 use didhub_db::generated::subsystems as db_subsystems;
-use didhub_db::generated::{affiliations as db_affiliations, relationships as db_relationships};
-use didhub_db::generated::{alters as db_alters, users as db_users};
+use didhub_db::generated::{relationships as db_relationships, users as db_users};
 
 #[derive(Debug, Deserialize)]
 #[serde(tag = "action")]
@@ -95,7 +94,8 @@ pub async fn bulk_operation(
                 let mut alter_results = Vec::new();
                 for id_str in &alters {
                     if let Ok(id) = SqlxUuid::parse_str(id_str) {
-                        if let Ok(Some(row)) = db_alters::find_by_primary_key(&mut *conn, &id).await
+                        if let Ok(Some(row)) =
+                            didhub_db::custom::alters::find_active(&mut *conn, &id).await
                         {
                             let mut v = serde_json::to_value(&row).map_err(ApiError::from)?;
                             if let Some(obj) = v.as_object_mut() {
@@ -171,7 +171,7 @@ pub async fn bulk_operation(
                 for id_str in &affiliations {
                     if let Ok(id) = SqlxUuid::parse_str(id_str) {
                         if let Ok(Some(row)) =
-                            db_affiliations::find_by_primary_key(&mut *conn, &id).await
+                            didhub_db::custom::affiliations::find_active(&mut *conn, &id).await
                         {
                             let v = serde_json::to_value(&row).map_err(ApiError::from)?;
                             aff_results.push(v);
@@ -219,7 +219,7 @@ pub async fn bulk_operation(
                     if let Some(id) = alter_data.get("id").and_then(|v| v.as_str()) {
                         if let Ok(uuid) = SqlxUuid::parse_str(id) {
                             if let Ok(Some(row)) =
-                                db_alters::find_by_primary_key(&mut *conn, &uuid).await
+                                didhub_db::custom::alters::find_active(&mut *conn, &uuid).await
                             {
                                 alter_results.push(serde_json::to_value(&row).unwrap_or(json!({})));
                             }
@@ -280,7 +280,8 @@ pub async fn bulk_operation(
                     if let Some(id) = aff_data.get("id").and_then(|v| v.as_str()) {
                         if let Ok(uuid) = SqlxUuid::parse_str(id) {
                             if let Ok(Some(row)) =
-                                db_affiliations::find_by_primary_key(&mut *conn, &uuid).await
+                                didhub_db::custom::affiliations::find_active(&mut *conn, &uuid)
+                                    .await
                             {
                                 aff_results.push(serde_json::to_value(&row).unwrap_or(json!({})));
                             }
@@ -321,6 +322,8 @@ pub async fn bulk_operation(
             affiliations,
             subsystems,
         } => {
+            // Alters and affiliations go to the trash like single deletes
+            let deleted_at = chrono::Utc::now().to_rfc3339();
             let mut deleted = DeletedCounts {
                 alters: 0,
                 users: 0,
@@ -332,9 +335,13 @@ pub async fn bulk_operation(
             if !alters.is_empty() {
                 for id_str in &alters {
                     if let Ok(id) = SqlxUuid::parse_str(id_str) {
-                        if db_alters::delete_by_primary_key(&mut *conn, &id)
-                            .await
-                            .is_ok()
+                        if let Ok(1) = didhub_db::custom::trash::mark_deleted(
+                            &mut *conn,
+                            "alters",
+                            &id,
+                            &deleted_at,
+                        )
+                        .await
                         {
                             deleted.alters += 1;
                         }
@@ -371,9 +378,13 @@ pub async fn bulk_operation(
             if !affiliations.is_empty() {
                 for id_str in &affiliations {
                     if let Ok(id) = SqlxUuid::parse_str(id_str) {
-                        if db_affiliations::delete_by_primary_key(&mut *conn, &id)
-                            .await
-                            .is_ok()
+                        if let Ok(1) = didhub_db::custom::trash::mark_deleted(
+                            &mut *conn,
+                            "affiliations",
+                            &id,
+                            &deleted_at,
+                        )
+                        .await
                         {
                             deleted.affiliations += 1;
                         }
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::http::HeaderMap;
+use axum::Json;
+use didhub_cache::LATENCY_BUCKETS_MICROS;
+use serde_json::{json, Value};
+
+use crate::{error::ApiError, state::AppState};
+
+/// GET /admin/cache
+/// Report the cache backend and per-namespace hit, miss, error and latency counters
+/// since startup, so a slow Redis or a poor hit rate is visible. Counters are per
+/// instance.
+pub async fn stats(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    crate::handlers::auth::utils::require_admin(&state, &headers).await?;
+
+    let namespaces: Vec<Value> = state
+        .cache
+        .stats()
+        .into_iter()
+        .map(|ns| {
+            let mut value = serde_json::to_value(&ns).unwrap_or_default();
+            value["hitRatio"] = json!(ns.hit_ratio());
+            value
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "backend": state.cache.backend_name(),
+        "latencyBucketsMicros": LATENCY_BUCKETS_MICROS,
+        "namespaces": namespaces,
+    })))
+}
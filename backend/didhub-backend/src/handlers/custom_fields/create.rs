@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde_json::Value;
+use uuid::Uuid;
+
+use didhub_db::generated::custom_field_definitions as db_definitions;
+
+use super::{
+    definition_to_payload, ensure_name_free, parse_name, parse_order, parse_visibility,
+    target_system, FieldType,
+};
+use crate::{error::ApiError, state::AppState};
+
+/// Define a custom field for the caller's system (admins: any `systemId`).
+/// Fields are public unless `visibility` is `private`.
+pub async fn create(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let payload = body
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0;
+
+    let name = parse_name(payload.get("name"))?;
+    let field_type = payload
+        .get("type")
+        .and_then(Value::as_str)
+        .ok_or_else(|| ApiError::bad_request("missing type"))?;
+    FieldType::parse(field_type)?;
+    let sort_order = payload.get("order").map(parse_order).transpose()?;
+    let visibility = payload
+        .get("visibility")
+        .map(parse_visibility)
+        .transpose()?
+        .unwrap_or_else(|| "public".to_string());
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let system_id = target_system(
+        &mut conn,
+        &auth,
+        payload.get("systemId").and_then(Value::as_str),
+        true,
+    )
+    .await?;
+    ensure_name_free(&mut conn, &system_id, &name, None).await?;
+
+    // New fields go last unless an order is given
+    let sort_order = match sort_order {
+        Some(order) => order,
+        None => didhub_db::custom::custom_fields::list_definitions(&mut *conn, &system_id)
+            .await?
+            .iter()
+            .map(|field| field.sort_order + 1)
+            .max()
+            .unwrap_or(0),
+    };
+
+    let row = db_definitions::CustomFieldDefinitionsRow {
+        id: Uuid::new_v4(),
+        user_id: system_id,
+        name,
+        field_type: field_type.to_string(),
+        sort_order,
+        visibility,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    db_definitions::insert_custom_field_definition(&mut *conn, &row)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(definition_to_payload(&row)))
+}
@@ -0,0 +1,29 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use didhub_db::generated::custom_field_definitions as db_definitions;
+
+use super::find_managed_field;
+use crate::{error::ApiError, state::AppState};
+
+/// Delete a custom field together with the values alters had for it.
+pub async fn delete(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let field = find_managed_field(&mut conn, &auth, &path).await?;
+    db_definitions::delete_by_primary_key(&mut *conn, &field.id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(json!({ "deleted": true })))
+}
@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Query};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use super::{definition_to_payload, target_system};
+use crate::{error::ApiError, state::AppState};
+
+/// List the custom fields of the caller's system (admins: any `systemId`), in
+/// display order.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    query: Option<Query<HashMap<String, String>>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let params = query.map(|q| q.0).unwrap_or_default();
+
+    let mut conn = state.acquire_read().await?;
+    let system_id = target_system(
+        &mut conn,
+        &auth,
+        params.get("systemId").map(String::as_str),
+        false,
+    )
+    .await?;
+    let rows = didhub_db::custom::custom_fields::list_definitions(&mut *conn, &system_id).await?;
+
+    let items: Vec<Value> = rows.iter().map(definition_to_payload).collect();
+    Ok(Json(json!({ "items": items })))
+}
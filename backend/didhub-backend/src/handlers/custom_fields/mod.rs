@@ -0,0 +1,269 @@
+//! Custom fields on alters. A system defines extra fields (name, type, order and
+//! visibility) for its alters; the alter endpoints read and write their values
+//! under `customFields`.
+
+pub mod create;
+pub mod delete;
+pub mod list;
+pub mod update;
+
+use std::collections::HashMap;
+
+use chrono::{NaiveDate, Utc};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use didhub_auth::auth::AuthContext;
+use didhub_db::generated::alter_custom_field_values as db_values;
+use didhub_db::generated::alters as db_alters;
+use didhub_db::generated::custom_field_definitions as db_definitions;
+use didhub_db::DbPoolConnection;
+
+use crate::error::ApiError;
+use crate::handlers::auth::utils::require_user_id;
+use crate::handlers::utils::ensure_system_user;
+
+/// Longest accepted field name, in characters.
+const MAX_NAME_LEN: usize = 100;
+
+/// Field types and the JSON values they accept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    Text,
+    Number,
+    Boolean,
+    /// A `YYYY-MM-DD` string.
+    Date,
+}
+
+impl FieldType {
+    pub fn parse(value: &str) -> Result<Self, ApiError> {
+        match value {
+            "text" => Ok(FieldType::Text),
+            "number" => Ok(FieldType::Number),
+            "boolean" => Ok(FieldType::Boolean),
+            "date" => Ok(FieldType::Date),
+            _ => Err(ApiError::bad_request(
+                "type must be one of text, number, boolean, date",
+            )),
+        }
+    }
+
+    pub fn accepts(self, value: &Value) -> bool {
+        match self {
+            FieldType::Text => value.is_string(),
+            FieldType::Number => value.is_number(),
+            FieldType::Boolean => value.is_boolean(),
+            FieldType::Date => value
+                .as_str()
+                .is_some_and(|s| NaiveDate::parse_from_str(s, "%Y-%m-%d").is_ok()),
+        }
+    }
+}
+
+/// Whether private fields are shown: only to admins and the alter's system or owner.
+fn sees_private(auth: &AuthContext, alter: &db_alters::AltersRow) -> bool {
+    auth.is_admin()
+        || auth
+            .user_id
+            .is_some_and(|id| id == alter.user_id || id == alter.owner_user_id)
+}
+
+pub fn definition_to_payload(row: &db_definitions::CustomFieldDefinitionsRow) -> Value {
+    json!({
+        "id": row.id,
+        "systemId": row.user_id,
+        "name": row.name,
+        "type": row.field_type,
+        "order": row.sort_order,
+        "visibility": row.visibility,
+        "createdAt": row.created_at,
+    })
+}
+
+fn parse_name(value: Option<&Value>) -> Result<String, ApiError> {
+    let name = value
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ApiError::bad_request("missing name"))?;
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(ApiError::bad_request(format!(
+            "name must be at most {MAX_NAME_LEN} characters"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn parse_visibility(value: &Value) -> Result<String, ApiError> {
+    match value.as_str() {
+        Some(v @ ("public" | "private")) => Ok(v.to_string()),
+        _ => Err(ApiError::bad_request(
+            "visibility must be public or private",
+        )),
+    }
+}
+
+fn parse_order(value: &Value) -> Result<i64, ApiError> {
+    value
+        .as_i64()
+        .ok_or_else(|| ApiError::bad_request("order must be an integer"))
+}
+
+/// Reject `name` if the system already has another field called that.
+async fn ensure_name_free(
+    conn: &mut DbPoolConnection,
+    system_id: &Uuid,
+    name: &str,
+    except: Option<&Uuid>,
+) -> Result<(), ApiError> {
+    let existing =
+        didhub_db::custom::custom_fields::find_definition_by_name(&mut **conn, system_id, name)
+            .await?;
+    match existing {
+        Some(field) if Some(&field.id) != except => Err(ApiError::bad_request(
+            "a custom field with this name already exists",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// The system whose fields the caller manages: any `systemId` for admins,
+/// otherwise the caller, who must be a system to change them.
+async fn target_system(
+    conn: &mut DbPoolConnection,
+    auth: &AuthContext,
+    requested: Option<&str>,
+    writing: bool,
+) -> Result<Uuid, ApiError> {
+    if let Some(requested) = requested.filter(|_| auth.is_admin()) {
+        return Uuid::parse_str(requested).map_err(|_| ApiError::bad_request("invalid systemId"));
+    }
+    let user_id = require_user_id(auth)?;
+    if writing && !auth.is_admin() {
+        ensure_system_user(&mut **conn, user_id, "managing custom fields").await?;
+    }
+    Ok(user_id)
+}
+
+/// The field named by the `fieldId` path parameter, if the caller may manage it.
+/// Fields of other systems are reported as not found.
+async fn find_managed_field(
+    conn: &mut DbPoolConnection,
+    auth: &AuthContext,
+    path: &HashMap<String, String>,
+) -> Result<db_definitions::CustomFieldDefinitionsRow, ApiError> {
+    let field_id = path
+        .get("fieldId")
+        .ok_or_else(|| ApiError::bad_request("missing fieldId"))?;
+    let field_id =
+        Uuid::parse_str(field_id).map_err(|_| ApiError::bad_request("invalid fieldId"))?;
+    let field = db_definitions::find_by_primary_key(&mut **conn, &field_id)
+        .await?
+        .filter(|field| auth.is_admin() || auth.user_id == Some(field.user_id))
+        .ok_or_else(|| ApiError::not_found("custom field not found"))?;
+    if !auth.is_admin() {
+        ensure_system_user(&mut **conn, field.user_id, "managing custom fields").await?;
+    }
+    Ok(field)
+}
+
+/// The custom fields of the alter's system with the alter's values, in display
+/// order, as `auth` may see them. Unset fields have a `null` value.
+pub async fn alter_values(
+    conn: &mut DbPoolConnection,
+    alter: &db_alters::AltersRow,
+    auth: &AuthContext,
+) -> Result<Value, ApiError> {
+    let fields =
+        didhub_db::custom::custom_fields::list_definitions(&mut **conn, &alter.user_id).await?;
+    let mut values: HashMap<Uuid, Value> =
+        didhub_db::custom::custom_fields::values_for_alter(&mut **conn, &alter.id)
+            .await?
+            .into_iter()
+            .map(|row| {
+                let value = serde_json::from_str(&row.value).unwrap_or(Value::Null);
+                (row.field_id, value)
+            })
+            .collect();
+    let show_private = sees_private(auth, alter);
+    let items = fields
+        .iter()
+        .filter(|field| show_private || field.visibility != "private")
+        .map(|field| {
+            json!({
+                "id": field.id,
+                "name": field.name,
+                "type": field.field_type,
+                "visibility": field.visibility,
+                "value": values.remove(&field.id).unwrap_or(Value::Null),
+            })
+        })
+        .collect();
+    Ok(Value::Array(items))
+}
+
+/// Validated `customFields` of an alter create or update body: field id and new
+/// value, `None` to clear it.
+pub type ValueChanges = Vec<(Uuid, Option<Value>)>;
+
+/// Check `customFields` (an object keyed by field id) against the fields of the
+/// system `system_id`.
+pub async fn parse_values(
+    conn: &mut DbPoolConnection,
+    system_id: &Uuid,
+    custom_fields: &Value,
+) -> Result<ValueChanges, ApiError> {
+    let entries: &Map<String, Value> = custom_fields
+        .as_object()
+        .ok_or_else(|| ApiError::bad_request("customFields must be an object"))?;
+    let fields: HashMap<Uuid, FieldType> =
+        didhub_db::custom::custom_fields::list_definitions(&mut **conn, system_id)
+            .await?
+            .into_iter()
+            .filter_map(|field| {
+                let kind = FieldType::parse(&field.field_type).ok()?;
+                Some((field.id, kind))
+            })
+            .collect();
+    entries
+        .iter()
+        .map(|(key, value)| {
+            let field_id = Uuid::parse_str(key)
+                .ok()
+                .filter(|id| fields.contains_key(id))
+                .ok_or_else(|| ApiError::bad_request(format!("unknown custom field {key}")))?;
+            if value.is_null() {
+                return Ok((field_id, None));
+            }
+            if !fields[&field_id].accepts(value) {
+                return Err(ApiError::bad_request(format!(
+                    "invalid value for custom field {key}"
+                )));
+            }
+            Ok((field_id, Some(value.clone())))
+        })
+        .collect()
+}
+
+/// Store `changes` on `alter_id`.
+pub async fn write_values(
+    conn: &mut DbPoolConnection,
+    alter_id: &Uuid,
+    changes: ValueChanges,
+) -> Result<(), ApiError> {
+    let updated_at = Utc::now().to_rfc3339();
+    for (field_id, value) in changes {
+        didhub_db::custom::custom_fields::delete_value(&mut **conn, alter_id, &field_id).await?;
+        if let Some(value) = value {
+            let row = db_values::AlterCustomFieldValuesRow {
+                alter_id: *alter_id,
+                field_id,
+                value: value.to_string(),
+                updated_at: updated_at.clone(),
+            };
+            db_values::insert_alter_custom_field_value(&mut **conn, &row).await?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+use didhub_db::generated::custom_field_definitions as db_definitions;
+
+use super::{
+    definition_to_payload, ensure_name_free, find_managed_field, parse_name, parse_order,
+    parse_visibility,
+};
+use crate::{error::ApiError, state::AppState};
+
+/// Rename, reorder or change the visibility of a custom field. The type cannot
+/// change, since existing values would no longer match it.
+pub async fn update(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let payload = body
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0;
+    if payload.get("type").is_some() {
+        return Err(ApiError::bad_request(
+            "the type of a custom field cannot change",
+        ));
+    }
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let mut field = find_managed_field(&mut conn, &auth, &path).await?;
+
+    if payload.get("name").is_some() {
+        let name = parse_name(payload.get("name"))?;
+        ensure_name_free(&mut conn, &field.user_id, &name, Some(&field.id)).await?;
+        field.name = name;
+    }
+    if let Some(order) = payload.get("order") {
+        field.sort_order = parse_order(order)?;
+    }
+    if let Some(visibility) = payload.get("visibility") {
+        field.visibility = parse_visibility(visibility)?;
+    }
+
+    db_definitions::update_by_primary_key(&mut *conn, &field.id, &field)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(definition_to_payload(&field)))
+}
@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use didhub_db::generated::device_tokens as db_devices;
+
+use crate::{error::ApiError, state::AppState};
+
+/// Forget a remembered device so its token can no longer sign in. Sessions already
+/// started from it are left alone; revoke those under `/me/sessions`.
+pub async fn delete(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let (auth, device) = super::load_owned_device(&state, &headers, &path).await?;
+
+    db_devices::delete_by_primary_key(&*state.db_pool, &device.id)
+        .await
+        .map_err(ApiError::from)?;
+    tracing::info!(device_id = %device.id, revoked_by = ?auth.user_id, "forgot remembered device");
+
+    Ok(Json(json!({ "revoked": true })))
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde_json::{json, Value};
+
+use didhub_db::generated::device_tokens as db_devices;
+
+use crate::device_tokens::{device_to_payload, is_expired};
+use crate::{error::ApiError, state::AppState};
+
+/// List the current user's remembered devices, most recently used first.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let rows = db_devices::find_by_user_id(&mut *conn, &user_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    // Expired devices are dropped lazily whenever the list is read.
+    let now = Utc::now();
+    let (expired, mut rows): (Vec<_>, Vec<_>) =
+        rows.into_iter().partition(|row| is_expired(row, now));
+    for row in &expired {
+        db_devices::delete_by_primary_key(&mut *conn, &row.id)
+            .await
+            .map_err(ApiError::from)?;
+    }
+    rows.sort_by(|a, b| b.last_used_at.cmp(&a.last_used_at));
+
+    let items: Vec<Value> = rows.iter().map(device_to_payload).collect();
+    Ok(Json(json!({ "items": items })))
+}
@@ -0,0 +1,34 @@
+pub mod delete;
+pub mod list;
+pub mod rename;
+
+use axum::http::HeaderMap;
+use didhub_db::generated::device_tokens as db_devices;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::{error::ApiError, state::AppState};
+
+/// Load the device named by the `deviceId` path parameter, checking that it belongs
+/// to the caller (or that the caller is an admin).
+async fn load_owned_device(
+    state: &AppState,
+    headers: &HeaderMap,
+    path: &HashMap<String, String>,
+) -> Result<(didhub_auth::auth::AuthContext, db_devices::DeviceTokensRow), ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(state, headers).await?;
+
+    let device_id_str = path
+        .get("deviceId")
+        .ok_or_else(|| ApiError::bad_request("missing deviceId"))?;
+    let device_id =
+        Uuid::parse_str(device_id_str).map_err(|_| ApiError::bad_request("invalid deviceId"))?;
+
+    let device = db_devices::find_by_primary_key(&*state.db_pool, &device_id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("device not found"))?;
+    crate::handlers::auth::utils::ensure_admin_or_user(&auth, device.user_id)?;
+    Ok((auth, device))
+}
@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+use crate::device_tokens::{device_to_payload, normalize_name};
+use crate::{error::ApiError, state::AppState};
+
+/// Rename a remembered device. Body: `{ "name": "..." }`.
+pub async fn rename(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let (_, mut device) = super::load_owned_device(&state, &headers, &path).await?;
+
+    let name = body
+        .as_ref()
+        .and_then(|b| b.0.get("name"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| ApiError::bad_request("name is required"))?;
+    device.name = normalize_name(name)?;
+
+    // Only the name is written, so a token rotated meanwhile is not rolled back
+    sqlx::query("UPDATE device_tokens SET name = ? WHERE id = ?")
+        .bind(&device.name)
+        .bind(device.id)
+        .execute(&*state.db_pool)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(Json(device_to_payload(&device)))
+}
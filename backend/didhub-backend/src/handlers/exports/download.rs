@@ -0,0 +1,36 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path};
+use axum::http::HeaderMap;
+use axum::response::Response;
+use didhub_job_queue::JobStatus;
+
+use crate::handlers::auth::utils::{authenticate_and_require_approved, require_user_id};
+use crate::{error::ApiError, state::AppState};
+
+/// GET /me/exports/{exportId}/download
+/// Download the archive of a completed background export. Only the caller's
+/// latest export is kept.
+pub async fn download(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Response, ApiError> {
+    let auth = authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = require_user_id(&auth)?;
+    let (run, archive_id) = super::find_own_export(&state, &user_id, &path).await?;
+    if run.status != JobStatus::Completed {
+        return Err(ApiError::bad_request("export has not completed"));
+    }
+
+    let uploads_dir = didhub_config::load_config::<&std::path::Path>(None)
+        .unwrap_or_default()
+        .uploads
+        .directory;
+    let archive = crate::export::archive_path(uploads_dir.as_ref(), &user_id, &archive_id);
+    let file = tokio::fs::File::open(&archive)
+        .await
+        .map_err(|_| ApiError::not_found("export is no longer available"))?;
+    super::archive_response(file)
+}
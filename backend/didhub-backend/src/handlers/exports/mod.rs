@@ -0,0 +1,69 @@
+//! Exports of the caller's own data as a ZIP archive, streamed directly or built
+//! by a background job; see [`crate::export`] for the archive layout.
+
+pub mod download;
+pub mod start;
+pub mod status;
+pub mod stream;
+
+use std::collections::HashMap;
+
+use axum::body::Body;
+use axum::http::{header, StatusCode};
+use axum::response::Response;
+use chrono::Utc;
+use didhub_job_queue::JobRun;
+use serde_json::Value;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::export::SYSTEM_EXPORT_JOB;
+use crate::state::AppState;
+
+/// Response streaming the archive in `file` as a download.
+fn archive_response(file: tokio::fs::File) -> Result<Response, ApiError> {
+    let filename = format!("didhub-export-{}.zip", Utc::now().format("%Y-%m-%d"));
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/zip")
+        .header(
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}\""),
+        )
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .map_err(|e| ApiError::Unexpected(format!("failed to build response: {e}")))
+}
+
+/// The background export `exportId` of `user_id`, and the archive id it stores
+/// its file under.
+async fn find_own_export(
+    state: &AppState,
+    user_id: &Uuid,
+    path: &HashMap<String, String>,
+) -> Result<(JobRun, Uuid), ApiError> {
+    let export_id = path
+        .get("exportId")
+        .ok_or_else(|| ApiError::not_found("export id missing"))?;
+    let export_id =
+        Uuid::parse_str(export_id).map_err(|_| ApiError::bad_request("invalid uuid"))?;
+    let run = state
+        .job_queue
+        .get_run(export_id)
+        .await
+        .filter(|run| run.job_name == SYSTEM_EXPORT_JOB)
+        .ok_or_else(|| ApiError::not_found("export not found"))?;
+    let payload_id = |key: &str| {
+        run.payload
+            .as_ref()
+            .and_then(|payload| payload.get(key))
+            .and_then(Value::as_str)
+            .and_then(|s| Uuid::parse_str(s).ok())
+    };
+    if payload_id("userId").as_ref() != Some(user_id) {
+        return Err(ApiError::not_found("export not found"));
+    }
+    let archive_id =
+        payload_id("archiveId").ok_or_else(|| ApiError::not_found("export not found"))?;
+    Ok((run, archive_id))
+}
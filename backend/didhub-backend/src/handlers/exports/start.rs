@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::http::HeaderMap;
+use axum::Json;
+use didhub_job_queue::JobRequest;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::export::SYSTEM_EXPORT_JOB;
+use crate::handlers::auth::utils::{authenticate_and_require_approved, require_user_id};
+use crate::{error::ApiError, state::AppState};
+
+/// POST /me/exports
+/// Start building an archive of the caller's data in the background. The archive
+/// replaces the caller's previous background export once written.
+pub async fn start(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let auth = authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = require_user_id(&auth)?;
+
+    let payload = json!({
+        "userId": user_id,
+        "archiveId": Uuid::new_v4(),
+    });
+    let result = state
+        .job_queue
+        .spawn_job(JobRequest::new(SYSTEM_EXPORT_JOB, payload))
+        .await?;
+
+    Ok(Json(json!({
+        "exportId": result.job_id,
+        "status": "pending",
+    })))
+}
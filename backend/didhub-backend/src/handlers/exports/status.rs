@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path};
+use axum::http::HeaderMap;
+use axum::Json;
+use didhub_job_queue::JobStatus;
+use serde_json::{json, Value};
+
+use crate::handlers::auth::utils::{authenticate_and_require_approved, require_user_id};
+use crate::{error::ApiError, state::AppState};
+
+/// GET /me/exports/{exportId}
+/// Progress of one of the caller's background exports, with a download link once
+/// it has completed.
+pub async fn status(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth = authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = require_user_id(&auth)?;
+    let (run, _) = super::find_own_export(&state, &user_id, &path).await?;
+
+    let download_url = (run.status == JobStatus::Completed)
+        .then(|| format!("/api/me/exports/{}/download", run.id));
+    Ok(Json(json!({
+        "exportId": run.id,
+        "status": run.status,
+        "startedAt": run.started_at.to_rfc3339(),
+        "finishedAt": run.finished_at.map(|at| at.to_rfc3339()),
+        "error": run.error_message,
+        "downloadUrl": download_url,
+    })))
+}
@@ -0,0 +1,45 @@
+use std::io::{Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::http::HeaderMap;
+use axum::response::Response;
+
+use crate::handlers::auth::utils::{authenticate_and_require_approved, require_user_id};
+use crate::{error::ApiError, state::AppState};
+
+/// GET /me/export
+/// Build an archive of the caller's data and stream it back.
+pub async fn stream(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let auth = authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = require_user_id(&auth)?;
+
+    let mut conn = state.db_pool.acquire().await?;
+    let export = crate::export::collect(&mut conn, &user_id).await?;
+    drop(conn);
+
+    let uploads_dir = PathBuf::from(
+        didhub_config::load_config::<&std::path::Path>(None)
+            .unwrap_or_default()
+            .uploads
+            .directory,
+    );
+    // Build it in an anonymous temporary file so large uploads are not held in memory
+    let file = tokio::task::spawn_blocking(move || -> Result<std::fs::File, ApiError> {
+        let file = tempfile::tempfile()
+            .map_err(|e| ApiError::Unexpected(format!("failed to create export file: {e}")))?;
+        let mut file = crate::export::write_archive(&export, &uploads_dir, file)
+            .map_err(|e| ApiError::Unexpected(format!("failed to write export: {e}")))?;
+        file.seek(SeekFrom::Start(0))
+            .map_err(|e| ApiError::Unexpected(format!("failed to read export: {e}")))?;
+        Ok(file)
+    })
+    .await
+    .map_err(|e| ApiError::Unexpected(format!("export task failed: {e}")))??;
+
+    super::archive_response(tokio::fs::File::from_std(file))
+}
@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path, Query};
+use axum::http::HeaderMap;
+use axum::Json;
+use didhub_db::custom::entity_versions;
+use serde_json::{json, Value};
+
+use super::{ensure_can_view, EntityKind};
+use crate::handlers::auth::utils::authenticate_and_require_approved;
+use crate::handlers::utils::parse_positive_usize;
+use crate::{error::ApiError, state::AppState};
+
+/// Largest page of versions returned at once.
+const MAX_PER_PAGE: usize = 100;
+
+/// GET /alters/{alterId}/history, /affiliations/{affiliationId}/history and
+/// /subsystems/{subsystemId}/history
+/// List the stored versions of an entity, newest first. Works for deleted
+/// entities too, so they can be found and restored.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+    query: Option<Query<HashMap<String, String>>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth = authenticate_and_require_approved(&state, &headers).await?;
+    let (kind, id) = EntityKind::from_path(&path)?;
+    let params = query.map(|value| value.0).unwrap_or_default();
+    let page = parse_positive_usize(params.get("page"), 1, "page")?;
+    let per_page = parse_positive_usize(params.get("perPage"), 20, "perPage")?.min(MAX_PER_PAGE);
+    let offset = (page - 1) * per_page;
+
+    let mut conn = state.acquire_read().await?;
+    // Deleted entities are authorized against their last snapshot
+    let current = match kind.current(&mut *conn, &id).await? {
+        Some(current) => current,
+        None => match entity_versions::list_for_entity(&mut *conn, &id, 1, 0)
+            .await?
+            .pop()
+        {
+            Some(latest) if latest.entity_type == kind.as_str() => {
+                serde_json::from_str(&latest.snapshot)?
+            }
+            _ => return Err(ApiError::not_found(format!("{} not found", kind.as_str()))),
+        },
+    };
+    ensure_can_view(&mut conn, &auth, kind, &current).await?;
+
+    let rows =
+        entity_versions::list_for_entity(&mut *conn, &id, per_page as i64, offset as i64).await?;
+    let total = entity_versions::count_for_entity(&mut *conn, &id).await?;
+
+    let items: Vec<Value> = rows
+        .into_iter()
+        .map(|row| {
+            json!({
+                "version": row.version,
+                "action": row.action,
+                "actor": row.actor_user_id,
+                "createdAt": row.created_at,
+                "snapshot": serde_json::from_str::<Value>(&row.snapshot).unwrap_or(Value::Null),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "entityType": kind.as_str(),
+        "entityId": id,
+        "items": items,
+        "pagination": {
+            "page": page,
+            "perPage": per_page,
+            "total": total,
+        }
+    })))
+}
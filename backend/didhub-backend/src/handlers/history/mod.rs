@@ -0,0 +1,164 @@
+//! Version history of alters, affiliations and subsystems.
+//!
+//! Every update, delete and restore of one of these entities stores the full row
+//! as it was beforehand in `entity_versions`. The handlers here are mounted under
+//! each entity's path and tell the entity apart by the path parameter.
+
+use std::collections::HashMap;
+
+use didhub_auth::auth::AuthContext;
+use didhub_db::{DbBackend, DbPoolConnection};
+use serde_json::Value;
+use sqlx::Executor;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+
+pub mod list;
+pub mod restore;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityKind {
+    Alter,
+    Affiliation,
+    Subsystem,
+}
+
+impl EntityKind {
+    /// Entity kind and id named by the path parameters of a history route.
+    pub fn from_path(path: &HashMap<String, String>) -> Result<(Self, Uuid), ApiError> {
+        let (kind, key) = [
+            (EntityKind::Alter, "alterId"),
+            (EntityKind::Affiliation, "affiliationId"),
+            (EntityKind::Subsystem, "subsystemId"),
+        ]
+        .into_iter()
+        .find(|(_, key)| path.contains_key(*key))
+        .ok_or_else(|| ApiError::not_found("entity id missing"))?;
+        let id = Uuid::parse_str(&path[key])
+            .map_err(|_| ApiError::bad_request(format!("invalid {key}")))?;
+        Ok((kind, id))
+    }
+
+    /// Name stored in `entity_versions.entity_type` and `audit_changes`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EntityKind::Alter => "alter",
+            EntityKind::Affiliation => "affiliation",
+            EntityKind::Subsystem => "subsystem",
+        }
+    }
+
+    /// Table whose deleted rows go to the trash, for kinds that have one.
+    pub fn trash_table(self) -> Option<&'static str> {
+        match self {
+            EntityKind::Alter => Some("alters"),
+            EntityKind::Affiliation => Some("affiliations"),
+            EntityKind::Subsystem => None,
+        }
+    }
+
+    /// The entity's current row as JSON, including while it is in the trash, or
+    /// `None` if it has been deleted for good.
+    pub async fn current<'e, E>(self, executor: E, id: &Uuid) -> Result<Option<Value>, ApiError>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        use didhub_db::generated::{affiliations, alters, subsystems};
+
+        let value = match self {
+            EntityKind::Alter => alters::find_by_primary_key(executor, id)
+                .await?
+                .map(serde_json::to_value),
+            EntityKind::Affiliation => affiliations::find_by_primary_key(executor, id)
+                .await?
+                .map(serde_json::to_value),
+            EntityKind::Subsystem => subsystems::find_by_primary_key(executor, id)
+                .await?
+                .map(serde_json::to_value),
+        };
+        Ok(value.transpose()?)
+    }
+
+    /// Write `snapshot` back as the entity's row, taking it out of the trash or
+    /// re-creating it if it was purged.
+    pub async fn write(
+        self,
+        conn: &mut DbPoolConnection,
+        id: &Uuid,
+        snapshot: Value,
+        exists: bool,
+    ) -> Result<(), ApiError> {
+        use didhub_db::generated::{affiliations, alters, subsystems};
+
+        match self {
+            EntityKind::Alter => {
+                let row: alters::AltersRow = serde_json::from_value(snapshot)?;
+                if exists {
+                    alters::update_by_primary_key(&mut **conn, id, &row).await?;
+                } else {
+                    alters::insert_row(&mut **conn, &row).await?;
+                }
+                crate::upload_references::sync_alter(conn, &row).await?;
+            }
+            EntityKind::Affiliation => {
+                let row: affiliations::AffiliationsRow = serde_json::from_value(snapshot)?;
+                if exists {
+                    affiliations::update_by_primary_key(&mut **conn, id, &row).await?;
+                } else {
+                    affiliations::insert_row(&mut **conn, &row).await?;
+                }
+            }
+            EntityKind::Subsystem => {
+                let row: subsystems::SubsystemsRow = serde_json::from_value(snapshot)?;
+                if exists {
+                    subsystems::update_by_primary_key(&mut **conn, id, &row).await?;
+                } else {
+                    subsystems::insert_row(&mut **conn, &row).await?;
+                }
+            }
+        }
+        if let Some(table) = self.trash_table() {
+            didhub_db::custom::trash::restore(&mut **conn, table, id).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The system holding an entity whose state is `snapshot`.
+fn system_of(kind: EntityKind, snapshot: &Value) -> Option<Uuid> {
+    let column = match kind {
+        EntityKind::Alter => "user_id",
+        EntityKind::Affiliation | EntityKind::Subsystem => "owner_user_id",
+    };
+    snapshot
+        .get(column)
+        .and_then(Value::as_str)
+        .and_then(|raw| Uuid::parse_str(raw).ok())
+}
+
+/// Allow whoever can see the system of an entity whose state is `snapshot` to
+/// see its history.
+pub async fn ensure_can_view(
+    conn: &mut DbPoolConnection,
+    auth: &AuthContext,
+    kind: EntityKind,
+    snapshot: &Value,
+) -> Result<(), ApiError> {
+    let system_id = system_of(kind, snapshot);
+    let allowed = crate::system_access::can_view(conn, auth, system_id.as_ref()).await;
+    crate::handlers::auth::utils::ensure_admin_or(auth, allowed)
+}
+
+/// Allow whoever can edit the system of an entity whose state is `snapshot` to
+/// restore it.
+pub async fn ensure_can_edit(
+    conn: &mut DbPoolConnection,
+    auth: &AuthContext,
+    kind: EntityKind,
+    snapshot: &Value,
+    warning_context: &str,
+) -> Result<(), ApiError> {
+    let system_id = system_of(kind, snapshot);
+    crate::system_access::ensure_can_edit(conn, auth, system_id.as_ref(), warning_context).await
+}
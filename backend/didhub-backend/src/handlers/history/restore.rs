@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path};
+use axum::http::HeaderMap;
+use axum::Json;
+use didhub_db::custom::entity_versions;
+use serde_json::Value;
+
+use super::{ensure_can_edit, EntityKind};
+use crate::handlers::auth::utils::authenticate_and_require_approved;
+use crate::{error::ApiError, state::AppState};
+
+/// POST /alters/{alterId}/history/{version}/restore and the affiliation and
+/// subsystem equivalents.
+/// Put an entity back into the state stored as `version`, taking it out of the
+/// trash or re-creating it if it was purged. The state being replaced is stored
+/// as a new version first, so a restore can itself be undone. Memberships removed
+/// along with a purged entity are not part of the snapshot and stay removed.
+pub async fn restore(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth = authenticate_and_require_approved(&state, &headers).await?;
+    let (kind, id) = EntityKind::from_path(&path)?;
+    let version: i64 = path
+        .get("version")
+        .and_then(|v| v.parse().ok())
+        .filter(|v| *v > 0)
+        .ok_or_else(|| ApiError::bad_request("invalid version"))?;
+
+    let mut conn = state.db_pool.acquire().await?;
+    let target = entity_versions::find_version(&mut *conn, &id, version)
+        .await?
+        .filter(|row| row.entity_type == kind.as_str())
+        .ok_or_else(|| ApiError::not_found("version not found"))?;
+    let snapshot: Value = serde_json::from_str(&target.snapshot)?;
+    let current = kind.current(&mut *conn, &id).await?;
+
+    // Non-admins must be able to edit the entity now and in the version they bring back
+    if let Some(current) = &current {
+        ensure_can_edit(&mut conn, &auth, kind, current, "restoring entity version").await?;
+    }
+    ensure_can_edit(
+        &mut conn,
+        &auth,
+        kind,
+        &snapshot,
+        "restoring entity version",
+    )
+    .await?;
+
+    if let Some(current) = &current {
+        state
+            .record_version(kind.as_str(), id, "restore", auth.user_id, current)
+            .await;
+    }
+    kind.write(&mut conn, &id, snapshot.clone(), current.is_some())
+        .await?;
+    if let Some(current) = &current {
+        state
+            .audit_change(kind.as_str(), id, auth.user_id, current, &snapshot)
+            .await;
+    }
+
+    Ok(Json(snapshot))
+}
@@ -0,0 +1,3 @@
+//! Importing PluralKit and Simply Plural exports; see [`crate::import`].
+
+pub mod run;
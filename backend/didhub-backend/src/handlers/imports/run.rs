@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::handlers::auth::utils::{authenticate_and_require_approved, require_user_id};
+use crate::handlers::utils::ensure_system_user;
+use crate::import::{ConflictStrategy, ImportSource};
+use crate::{error::ApiError, state::AppState};
+
+/// POST /imports
+/// Import a PluralKit or Simply Plural export into the caller's system, or with
+/// `systemId` into any system for admins. With `dryRun` nothing is written and
+/// the response previews what the import would do.
+pub async fn run(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth = authenticate_and_require_approved(&state, &headers).await?;
+    let payload = body
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0;
+
+    let source = payload
+        .get("source")
+        .and_then(Value::as_str)
+        .and_then(ImportSource::parse)
+        .ok_or_else(|| ApiError::bad_request("source must be pluralkit or simplyplural"))?;
+    let strategy = match payload.get("conflict") {
+        None | Some(Value::Null) => ConflictStrategy::Skip,
+        Some(value) => value
+            .as_str()
+            .and_then(ConflictStrategy::parse)
+            .ok_or_else(|| ApiError::bad_request("conflict must be skip, merge or rename"))?,
+    };
+    let dry_run = match payload.get("dryRun") {
+        None | Some(Value::Null) => false,
+        Some(value) => value
+            .as_bool()
+            .ok_or_else(|| ApiError::bad_request("dryRun must be a boolean"))?,
+    };
+    let data = payload
+        .get("data")
+        .filter(|data| data.is_object())
+        .ok_or_else(|| ApiError::bad_request("data must be the exported JSON object"))?;
+
+    let system_id = match payload.get("systemId").and_then(Value::as_str) {
+        Some(requested) if auth.is_admin() => {
+            Uuid::parse_str(requested).map_err(|_| ApiError::bad_request("invalid systemId"))?
+        }
+        _ => require_user_id(&auth)?,
+    };
+    let mut conn = state.db_pool.acquire().await?;
+    ensure_system_user(&mut *conn, system_id, "importing an export").await?;
+
+    let imported = source.parse_export(data)?;
+    let plan = crate::import::plan(&mut conn, system_id, source, imported, strategy).await?;
+    drop(conn);
+    if !dry_run {
+        crate::import::apply(&state, &plan, auth.user_id).await?;
+    }
+    Ok(Json(plan.report(dry_run)))
+}
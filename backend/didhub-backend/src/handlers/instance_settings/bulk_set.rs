@@ -7,7 +7,9 @@ use serde_json::{json, Value};
 
 use crate::{error::ApiError, state::AppState};
 
-use super::helpers::{row_to_setting, upsert_instance_setting, InstanceSettingsPayload};
+use super::helpers::{
+    invalidate_instance_setting, row_to_setting, upsert_instance_setting, InstanceSettingsPayload,
+};
 
 pub async fn bulk_set(
     Extension(state): Extension<Arc<AppState>>,
@@ -30,6 +32,7 @@ pub async fn bulk_set(
     let mut items = Vec::with_capacity(payload.items.len());
     for item in payload.items {
         let row = upsert_instance_setting(&mut conn, &item.key, &item.value).await?;
+        invalidate_instance_setting(&state, &item.key).await;
         items.push(row_to_setting(row));
     }
 
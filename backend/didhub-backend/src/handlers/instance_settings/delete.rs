@@ -8,6 +8,8 @@ use serde_json::{json, Value};
 
 use crate::{error::ApiError, state::AppState};
 
+use super::helpers::invalidate_instance_setting;
+
 pub async fn delete(
     Extension(state): Extension<Arc<AppState>>,
     headers: HeaderMap,
@@ -26,6 +28,7 @@ pub async fn delete(
         .execute(&mut *conn)
         .await
         .map_err(ApiError::from)?;
+    invalidate_instance_setting(&state, key).await;
 
     Ok(Json(json!({ "deleted": true })))
 }
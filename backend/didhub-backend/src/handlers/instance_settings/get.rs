@@ -8,7 +8,7 @@ use serde_json::Value;
 
 use crate::{error::ApiError, state::AppState};
 
-use super::helpers::{fetch_instance_setting, row_to_setting};
+use super::helpers::{cached_instance_setting, row_to_setting};
 
 pub async fn get(
     Extension(state): Extension<Arc<AppState>>,
@@ -21,8 +21,7 @@ pub async fn get(
         .get("key")
         .ok_or_else(|| ApiError::bad_request("missing key path parameter"))?;
 
-    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let row = fetch_instance_setting(&mut conn, key)
+    let row = cached_instance_setting(&state, key)
         .await?
         .ok_or_else(|| ApiError::not_found("instance setting not found"))?;
 
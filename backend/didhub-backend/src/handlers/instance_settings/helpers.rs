@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use chrono::Utc;
 use didhub_db::generated::instance_settings::{find_first_by_key, InstanceSettingsRow};
 use serde::Deserialize;
@@ -5,6 +7,18 @@ use serde_json::{json, Value};
 use sqlx::pool::PoolConnection;
 
 use crate::error::ApiError;
+use crate::state::AppState;
+
+/// Cache namespace of instance setting rows, by key.
+pub const SETTINGS_CACHE: &str = "instance_settings";
+
+/// How long a cached setting is served if an invalidation from another
+/// instance is lost.
+const SETTINGS_TTL: Duration = Duration::from_secs(300);
+
+/// Age after which the next lookup reloads a cached setting, so hot settings are
+/// refreshed before they expire and requests never wait on the database together.
+const SETTINGS_REFRESH_AFTER: Duration = Duration::from_secs(240);
 
 #[derive(Deserialize)]
 pub struct InstanceSettingInput {
@@ -159,3 +173,49 @@ pub async fn fetch_instance_setting(
 
     Ok(row)
 }
+
+/// The setting `key`, read through the cache.
+pub async fn cached_instance_setting(
+    state: &AppState,
+    key: &str,
+) -> Result<Option<InstanceSettingsRow>, ApiError> {
+    state
+        .cache
+        .get_or_compute(
+            SETTINGS_CACHE,
+            key,
+            Some(SETTINGS_TTL),
+            Some(SETTINGS_REFRESH_AFTER),
+            || async {
+                let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+                fetch_instance_setting(&mut conn, key).await
+            },
+        )
+        .await
+}
+
+/// Cache `row` unless its setting is cached already, e.g. to warm the cache.
+pub async fn prime_instance_setting(
+    state: &AppState,
+    row: InstanceSettingsRow,
+) -> Result<(), ApiError> {
+    let key = row.key.clone();
+    state
+        .cache
+        .get_or_compute(
+            SETTINGS_CACHE,
+            &key,
+            Some(SETTINGS_TTL),
+            Some(SETTINGS_REFRESH_AFTER),
+            || async { Ok::<_, ApiError>(Some(row)) },
+        )
+        .await?;
+    Ok(())
+}
+
+/// Drop `key` from the settings cache of every instance after changing it.
+pub async fn invalidate_instance_setting(state: &AppState, key: &str) {
+    if let Err(e) = state.cache.invalidate(SETTINGS_CACHE, key).await {
+        tracing::warn!(%e, key, "failed to invalidate cached instance setting");
+    }
+}
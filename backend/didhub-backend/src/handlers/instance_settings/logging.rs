@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::Arc;
 
-use super::helpers::{fetch_instance_setting, upsert_instance_setting};
+use super::helpers::{
+    cached_instance_setting, invalidate_instance_setting, upsert_instance_setting,
+};
 use crate::error::ApiError;
 use crate::state::AppState;
 
@@ -20,8 +22,7 @@ pub async fn get_logging_config(
 ) -> Result<Json<Value>, ApiError> {
     crate::handlers::auth::utils::require_admin(&state, &headers).await?;
 
-    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let row = fetch_instance_setting(&mut conn, "system.log_filter").await?;
+    let row = cached_instance_setting(&state, "system.log_filter").await?;
 
     let filter = row
         .and_then(|r| r.value_string)
@@ -46,6 +47,7 @@ pub async fn set_logging_config(
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
     upsert_instance_setting(&mut conn, "system.log_filter", filter).await?;
+    invalidate_instance_setting(&state, "system.log_filter").await;
 
     // Apply the filter at runtime if the reload handle is available
     if let Some(reload) = &state.reload_handle {
@@ -8,7 +8,9 @@ use serde_json::Value;
 
 use crate::{error::ApiError, state::AppState};
 
-use super::helpers::{row_to_setting, upsert_instance_setting, InstanceSettingInput};
+use super::helpers::{
+    invalidate_instance_setting, row_to_setting, upsert_instance_setting, InstanceSettingInput,
+};
 
 pub async fn set(
     Extension(state): Extension<Arc<AppState>>,
@@ -34,6 +36,7 @@ pub async fn set(
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
     let row = upsert_instance_setting(&mut conn, path_key, &payload.value).await?;
+    invalidate_instance_setting(&state, path_key).await;
 
     Ok(Json(row_to_setting(row)))
 }
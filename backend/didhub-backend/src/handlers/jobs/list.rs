@@ -40,6 +40,10 @@ pub async fn list_runs(
                 "startedAt": run.started_at.to_rfc3339(),
                 "finishedAt": run.finished_at.map(|dt| dt.to_rfc3339()),
                 "errorMessage": run.error_message,
+                "progress": run.progress.map(|progress| json!({
+                    "percent": progress.percent,
+                    "message": progress.message,
+                })),
             })
         })
         .collect();
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::http::HeaderMap;
+use axum::Json;
+use didhub_migrations::{Dialect, MigrationState};
+use serde_json::{json, Value};
+
+use crate::{error::ApiError, state::AppState};
+
+/// GET /admin/migrations
+/// Report which migrations of the compiled-in database backend are applied to the
+/// database and which are pending. Migrations recorded in the database but unknown
+/// to this build (after a downgrade) are listed as `missing`.
+pub async fn status(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    crate::handlers::auth::utils::require_admin(&state, &headers).await?;
+
+    let dialect = Dialect::of::<didhub_db::DbBackend>();
+    let mut conn = state.db_pool.acquire().await?;
+    let statuses = didhub_migrations::status(dialect.migrator(), &mut *conn)
+        .await
+        .map_err(|e| ApiError::internal_error(e.to_string()))?;
+
+    let count = |state: MigrationState| statuses.iter().filter(|s| s.state == state).count();
+    let migrations: Vec<Value> = statuses
+        .iter()
+        .map(|s| {
+            json!({
+                "version": s.version,
+                "description": s.description,
+                "state": s.state.as_str(),
+                "reversible": s.reversible,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "backend": dialect.name(),
+        "applied": count(MigrationState::Applied),
+        "pending": count(MigrationState::Pending),
+        "migrations": migrations,
+    })))
+}
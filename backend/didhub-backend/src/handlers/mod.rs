@@ -1,15 +1,32 @@
 pub mod affiliations;
 pub mod alters;
+pub mod api_keys;
 pub mod audit_logs;
 pub mod auth;
 pub mod backups;
 pub mod bulk;
+pub mod cache;
+pub mod custom_fields;
+pub mod devices;
+pub mod exports;
+pub mod history;
+pub mod imports;
 pub mod instance_settings;
 pub mod jobs;
+pub mod migrations;
+pub mod oauth;
+pub mod password_policy;
 pub mod relationships;
+pub mod roles;
+pub mod scheduler;
+pub mod search;
+pub mod service_clients;
+pub mod sessions;
 pub mod subsystems;
 pub mod system_requests;
 pub mod systems;
+pub mod tags;
+pub mod trash;
 pub mod updates;
 pub mod uploads;
 pub mod users;
@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Query};
+use axum::http::HeaderMap;
+use axum::Json;
+use chrono::Utc;
+use didhub_auth::auth::AuthContext;
+use didhub_db::generated::device_authorizations as db_authorizations;
+use serde_json::{json, Value};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::device_authorization::{
+    display_user_code, is_expired, normalize_user_code, STATUS_APPROVED, STATUS_DENIED,
+    STATUS_PENDING,
+};
+use crate::handlers::auth::utils::{authenticate_and_require_approved, require_user_id};
+use crate::{error::ApiError, state::AppState};
+
+/// Approving hands the device a full session, so only a user's own session may do it:
+/// not an API key with narrower scopes, and not an admin impersonating the user.
+async fn require_user_session(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<(AuthContext, Uuid), ApiError> {
+    let auth = authenticate_and_require_approved(state, headers).await?;
+    let user_id = require_user_id(&auth)?;
+    if auth.metadata.get("apiKeyId").is_some() || auth.is_impersonated() {
+        return Err(ApiError::forbidden(
+            "devices can only be approved from your own session",
+        ));
+    }
+    Ok((auth, user_id))
+}
+
+/// Pending, unexpired authorization for a user code as typed by the user.
+async fn load_pending(
+    state: &AppState,
+    user_code: Option<&str>,
+) -> Result<db_authorizations::DeviceAuthorizationsRow, ApiError> {
+    let code = user_code
+        .map(normalize_user_code)
+        .filter(|c| !c.is_empty())
+        .ok_or_else(|| ApiError::bad_request("missing code"))?;
+    let row = db_authorizations::find_first_by_user_code(&*state.db_pool, &code)
+        .await
+        .map_err(ApiError::from)?
+        .filter(|row| row.status == STATUS_PENDING && !is_expired(row, Utc::now()))
+        .ok_or_else(|| ApiError::not_found("unknown or expired code"))?;
+    Ok(row)
+}
+
+/// GET /oauth/device?code=XXXX-XXXX
+/// Describe a pending device authorization so the user can check which client
+/// is asking before approving it.
+pub async fn describe(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    require_user_session(&state, &headers).await?;
+    let row = load_pending(&state, query.get("code").map(String::as_str)).await?;
+    Ok(Json(json!({
+        "userCode": display_user_code(&row.user_code),
+        "clientId": row.client_id,
+        "createdAt": row.created_at,
+        "expiresAt": row.expires_at,
+    })))
+}
+
+/// POST /oauth/device
+/// Accepts { userCode, approve } and approves or denies the device authorization.
+/// On approval the polling client receives a session for the current user.
+pub async fn decide(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let (_, user_id) = require_user_session(&state, &headers).await?;
+    let payload = body
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0;
+    let approve = payload
+        .get("approve")
+        .and_then(Value::as_bool)
+        .ok_or_else(|| ApiError::bad_request("approve must be a boolean"))?;
+    let row = load_pending(&state, payload.get("userCode").and_then(Value::as_str)).await?;
+
+    let status = if approve {
+        STATUS_APPROVED
+    } else {
+        STATUS_DENIED
+    };
+    // Only one decision is taken, even if two users race on the same code
+    let affected = sqlx::query(
+        "UPDATE device_authorizations SET status = ?, user_id = ? WHERE id = ? AND status = ?",
+    )
+    .bind(status)
+    .bind(user_id)
+    .bind(row.id)
+    .bind(STATUS_PENDING)
+    .execute(&*state.db_pool)
+    .await
+    .map_err(ApiError::from)?
+    .rows_affected();
+    if affected == 0 {
+        return Err(ApiError::not_found("unknown or expired code"));
+    }
+    info!(authorization_id = %row.id, client_id = %row.client_id, user_id = %user_id, approved = approve, "device authorization decided");
+
+    Ok(Json(json!({ "approved": approve })))
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Form};
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use chrono::Utc;
+use didhub_db::generated::device_authorizations as db_authorizations;
+use serde_json::json;
+use tracing::info;
+use uuid::Uuid;
+
+use super::oauth_error;
+use crate::device_authorization::{
+    display_user_code, generate_device_code, generate_user_code, verification_uri, DEVICE_CODE_TTL,
+    MAX_CLIENT_ID_LEN, POLL_INTERVAL, STATUS_PENDING,
+};
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// A fresh user code colliding with a pending one is retried this many times.
+const USER_CODE_ATTEMPTS: usize = 3;
+
+/// POST /oauth/device_authorization
+///
+/// Start a device authorization grant (RFC 8628 section 3.1). The form body carries
+/// `client_id`, a free-form name of the public client (e.g. `didhub-cli`) that is
+/// shown to the user on approval. No credentials are needed; the returned device
+/// code is only useful once a signed-in user approves the user code.
+pub async fn device_authorization(
+    Extension(state): Extension<Arc<AppState>>,
+    Form(params): Form<HashMap<String, String>>,
+) -> Response {
+    let Some(client_id) = params
+        .get("client_id")
+        .map(|c| c.trim())
+        .filter(|c| !c.is_empty() && c.chars().count() <= MAX_CLIENT_ID_LEN)
+    else {
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "client_id is required",
+        );
+    };
+
+    let now = Utc::now();
+    let pruned = sqlx::query("DELETE FROM device_authorizations WHERE expires_at <= ?")
+        .bind(now.to_rfc3339())
+        .execute(&*state.db_pool)
+        .await;
+    if let Err(e) = pruned {
+        return ApiError::from(e).into_response();
+    }
+
+    let (device_code, device_code_hash) = generate_device_code();
+    let expires_at = now + chrono::Duration::from_std(DEVICE_CODE_TTL).unwrap_or_default();
+    let mut row = db_authorizations::DeviceAuthorizationsRow {
+        id: Uuid::new_v4(),
+        device_code_hash,
+        user_code: generate_user_code(),
+        client_id: client_id.to_string(),
+        status: STATUS_PENDING.to_string(),
+        user_id: None,
+        last_polled_at: None,
+        created_at: now.to_rfc3339(),
+        expires_at: expires_at.to_rfc3339(),
+    };
+    let mut attempt = 1;
+    loop {
+        match db_authorizations::insert_device_authorization(&*state.db_pool, &row).await {
+            Ok(_) => break,
+            Err(sqlx::Error::Database(e))
+                if e.is_unique_violation() && attempt < USER_CODE_ATTEMPTS =>
+            {
+                row.user_code = generate_user_code();
+                attempt += 1;
+            }
+            Err(e) => return ApiError::from(e).into_response(),
+        }
+    }
+    info!(authorization_id = %row.id, client_id = %row.client_id, "started device authorization");
+
+    let user_code = display_user_code(&row.user_code);
+    let verification_uri = verification_uri(state.password_reset().public_url.as_deref());
+    let mut resp = Json(json!({
+        "device_code": device_code,
+        "user_code": user_code,
+        "verification_uri": verification_uri,
+        "verification_uri_complete": format!("{}?code={}", verification_uri, user_code),
+        "expires_in": DEVICE_CODE_TTL.as_secs(),
+        "interval": POLL_INTERVAL.as_secs(),
+    }))
+    .into_response();
+    resp.headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    resp
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Form};
+use axum::http::{header, HeaderMap};
+use axum::response::Json;
+use didhub_auth::auth::{AuthContext, AuthError, Permission, API_KEY_PREFIX};
+use didhub_db::custom::users as db_users_custom;
+use didhub_db::generated::service_clients as db_clients;
+use serde_json::{json, Value};
+
+use crate::{error::ApiError, handlers::auth::utils::ensure_permission, state::AppState};
+
+/// POST /oauth/introspect
+///
+/// RFC 7662 token introspection for companion services (bots, exporters), so they can
+/// validate DIDHub tokens and API keys without holding the JWT secret. The form body
+/// carries `token` (and an optional `token_type_hint`, which is ignored).
+///
+/// The caller must authenticate with its own approved bearer token or API key in the
+/// `Authorization` header; cookies are not accepted, which is why this route is exempt
+/// from CSRF checks. Service client tokens are reported with their `client_id`
+/// instead of `sub`. Tokens that are invalid, expired, revoked or whose user no longer
+/// exists are reported as `{"active": false}` without further detail.
+pub async fn introspect(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Form(params): Form<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let caller = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(ApiError::Authentication(AuthError::AuthenticationFailed))?;
+    let caller = state
+        .authenticator()
+        .authenticate(Some(caller))
+        .await
+        .map_err(ApiError::from)?;
+    ensure_permission(&caller, Permission::ViewContent)?;
+
+    let token = params
+        .get("token")
+        .map(|t| t.trim())
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| ApiError::bad_request("token is required"))?;
+
+    let inactive = Json(json!({ "active": false }));
+    let Ok(auth) = state
+        .authenticator()
+        .authenticate(Some(&format!("Bearer {}", token)))
+        .await
+    else {
+        return Ok(inactive);
+    };
+    if let Some(client_id) = auth.client_id {
+        let client = db_clients::find_by_primary_key(&*state.db_pool, &client_id)
+            .await
+            .map_err(ApiError::from)?;
+        return Ok(match client {
+            Some(_) => Json(service_payload(&auth)),
+            None => inactive,
+        });
+    }
+    let Some(user_id) = auth.user_id else {
+        return Ok(inactive);
+    };
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let Some((username, _, _)) = db_users_custom::find_by_id_partial(&mut *conn, &user_id)
+        .await
+        .map_err(ApiError::from)?
+    else {
+        return Ok(inactive);
+    };
+
+    Ok(Json(active_payload(&auth, token, &username)))
+}
+
+fn active_payload(auth: &AuthContext, token: &str, username: &str) -> Value {
+    let mut payload = json!({
+        "active": true,
+        "scope": auth.scopes.join(" "),
+        "sub": auth.user_id,
+        "username": username,
+        "token_type": if token.starts_with(API_KEY_PREFIX) { "api_key" } else { "Bearer" },
+    });
+    if let Some(exp) = auth.expires_at {
+        payload["exp"] = json!(exp);
+    }
+    if let Some(jti) = &auth.token_id {
+        payload["jti"] = json!(jti);
+    }
+    if let Some(actor) = auth.actor {
+        payload["act"] = json!({ "sub": actor });
+    }
+    payload
+}
+
+fn service_payload(auth: &AuthContext) -> Value {
+    json!({
+        "active": true,
+        "scope": auth.scopes.join(" "),
+        "client_id": auth.client_id,
+        "token_type": "Bearer",
+        "exp": auth.expires_at,
+        "jti": auth.token_id,
+    })
+}
@@ -0,0 +1,24 @@
+pub mod device;
+pub mod device_authorization;
+pub mod introspect;
+pub mod token;
+
+use axum::http::{header, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use serde_json::json;
+
+/// Error response in the format of RFC 6749 section 5.2.
+pub(crate) fn oauth_error(status: StatusCode, error: &str, description: &str) -> Response {
+    let mut resp = (
+        status,
+        Json(json!({ "error": error, "error_description": description })),
+    )
+        .into_response();
+    if status == StatusCode::UNAUTHORIZED {
+        resp.headers_mut().insert(
+            header::WWW_AUTHENTICATE,
+            HeaderValue::from_static("Basic realm=\"didhub\""),
+        );
+    }
+    resp
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Form};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+use axum::response::{IntoResponse, Json, Response};
+use base64::Engine;
+use chrono::Utc;
+use didhub_db::generated::device_authorizations as db_authorizations;
+use didhub_db::generated::service_clients as db_clients;
+use didhub_db::generated::users as db_users;
+use serde_json::{json, Value};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use super::oauth_error;
+use crate::device_authorization::{
+    hash_device_code, is_expired, polled_too_soon, DEVICE_CODE_GRANT, STATUS_APPROVED,
+    STATUS_DENIED,
+};
+use crate::error::ApiError;
+use crate::handlers::auth::login::{issue_session_token, user_is_approved};
+use crate::service_clients::{client_scopes, issue_token, verify_secret};
+use crate::state::AppState;
+
+fn invalid_client() -> Response {
+    oauth_error(
+        StatusCode::UNAUTHORIZED,
+        "invalid_client",
+        "client authentication failed",
+    )
+}
+
+/// Client id and secret from HTTP Basic authentication, if present.
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Basic ")?;
+    let decoded = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (id, secret) = decoded.split_once(':')?;
+    Some((id.to_string(), secret.to_string()))
+}
+
+/// Successful token response; tokens must not be cached (RFC 6749 section 5.1).
+fn token_response(body: Value) -> Response {
+    let mut resp = Json(body).into_response();
+    resp.headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    resp
+}
+
+/// POST /oauth/token
+///
+/// Token endpoint for two grants:
+/// - `client_credentials` (RFC 6749 section 4.4) for registered service clients,
+///   see [`client_credentials`];
+/// - `urn:ietf:params:oauth:grant-type:device_code` (RFC 8628 section 3.4) for
+///   CLI and bot logins started at `/oauth/device_authorization`, see [`device_code`].
+pub async fn token(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Form(params): Form<HashMap<String, String>>,
+) -> Response {
+    match params.get("grant_type").map(String::as_str) {
+        Some("client_credentials") => client_credentials(&state, &headers, &params).await,
+        Some(DEVICE_CODE_GRANT) => device_code(&state, &headers, &params).await,
+        Some(_) => oauth_error(
+            StatusCode::BAD_REQUEST,
+            "unsupported_grant_type",
+            "only client_credentials and device_code are supported",
+        ),
+        None => oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "grant_type is required",
+        ),
+    }
+}
+
+/// Client-credentials grant. An optional space-separated `scope` narrows the client's
+/// scopes. The client authenticates with HTTP Basic or with `client_id` and
+/// `client_secret` form fields.
+async fn client_credentials(
+    state: &AppState,
+    headers: &HeaderMap,
+    params: &HashMap<String, String>,
+) -> Response {
+    let form_credentials = params
+        .get("client_id")
+        .zip(params.get("client_secret"))
+        .map(|(id, secret)| (id.clone(), secret.clone()));
+    let (client_id, secret) = match (basic_credentials(headers), form_credentials) {
+        (Some(_), Some(_)) => {
+            return oauth_error(
+                StatusCode::BAD_REQUEST,
+                "invalid_request",
+                "use only one client authentication method",
+            )
+        }
+        (Some(c), None) | (None, Some(c)) => c,
+        (None, None) => return invalid_client(),
+    };
+    let Ok(client_id) = Uuid::parse_str(&client_id) else {
+        return invalid_client();
+    };
+    let client = match db_clients::find_by_primary_key(&*state.db_pool, &client_id).await {
+        Ok(Some(client)) if verify_secret(&client, &secret) => client,
+        Ok(_) => {
+            warn!(%client_id, "client credentials rejected");
+            return invalid_client();
+        }
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+
+    let granted = client_scopes(&client);
+    let scopes: Vec<String> = match params.get("scope").map(|s| s.trim()) {
+        None | Some("") => granted,
+        Some(requested) => {
+            let requested: Vec<String> = requested.split_whitespace().map(String::from).collect();
+            if requested.iter().any(|s| !granted.contains(s)) {
+                return oauth_error(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_scope",
+                    "requested scope exceeds the client's scopes",
+                );
+            }
+            requested
+        }
+    };
+
+    let (token, ttl) = match issue_token(&client, &scopes) {
+        Ok(issued) => issued,
+        Err(e) => return e.into_response(),
+    };
+    let used = sqlx::query("UPDATE service_clients SET last_used_at = ? WHERE id = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(client.id)
+        .execute(&*state.db_pool)
+        .await;
+    if let Err(e) = used {
+        warn!(client_id = %client.id, error = %e, "failed to record service client use");
+    }
+    info!(client_id = %client.id, "issued service client token");
+
+    token_response(json!({
+        "access_token": token,
+        "token_type": "Bearer",
+        "expires_in": ttl.as_secs(),
+        "scope": scopes.join(" "),
+    }))
+}
+
+/// Device-code grant. The client polls with `device_code` and the `client_id` it
+/// started the authorization with until the user approves or denies it. An approved
+/// code is exchanged once for a session token of the approving user.
+async fn device_code(
+    state: &AppState,
+    headers: &HeaderMap,
+    params: &HashMap<String, String>,
+) -> Response {
+    let (Some(code), Some(client_id)) = (params.get("device_code"), params.get("client_id")) else {
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_request",
+            "device_code and client_id are required",
+        );
+    };
+    let invalid_grant = || {
+        oauth_error(
+            StatusCode::BAD_REQUEST,
+            "invalid_grant",
+            "unknown device code",
+        )
+    };
+    let row = match db_authorizations::find_first_by_device_code_hash(
+        &*state.db_pool,
+        &hash_device_code(code),
+    )
+    .await
+    {
+        Ok(Some(row)) if row.client_id == client_id.trim() => row,
+        Ok(_) => return invalid_grant(),
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+
+    let now = Utc::now();
+    if is_expired(&row, now) {
+        let _ = db_authorizations::delete_by_primary_key(&*state.db_pool, &row.id).await;
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "expired_token",
+            "the device code has expired",
+        );
+    }
+    if row.status == STATUS_DENIED {
+        let _ = db_authorizations::delete_by_primary_key(&*state.db_pool, &row.id).await;
+        return oauth_error(
+            StatusCode::BAD_REQUEST,
+            "access_denied",
+            "the user denied the request",
+        );
+    }
+    if row.status != STATUS_APPROVED {
+        let too_soon = polled_too_soon(&row, now);
+        let polled =
+            sqlx::query("UPDATE device_authorizations SET last_polled_at = ? WHERE id = ?")
+                .bind(now.to_rfc3339())
+                .bind(row.id)
+                .execute(&*state.db_pool)
+                .await;
+        if let Err(e) = polled {
+            return ApiError::from(e).into_response();
+        }
+        return if too_soon {
+            oauth_error(StatusCode::BAD_REQUEST, "slow_down", "polling too fast")
+        } else {
+            oauth_error(
+                StatusCode::BAD_REQUEST,
+                "authorization_pending",
+                "waiting for the user to approve the request",
+            )
+        };
+    }
+
+    // Single use: only the poll that deletes the approved row gets a token
+    let consumed = sqlx::query("DELETE FROM device_authorizations WHERE id = ? AND status = ?")
+        .bind(row.id)
+        .bind(STATUS_APPROVED)
+        .execute(&*state.db_pool)
+        .await;
+    match consumed {
+        Ok(r) if r.rows_affected() == 1 => {}
+        Ok(_) => return invalid_grant(),
+        Err(e) => return ApiError::from(e).into_response(),
+    }
+    let Some(user_id) = row.user_id else {
+        return invalid_grant();
+    };
+    let user = match db_users::find_by_primary_key(&*state.db_pool, &user_id).await {
+        Ok(Some(user)) if user_is_approved(&user.roles) => user,
+        Ok(_) => {
+            return oauth_error(
+                StatusCode::BAD_REQUEST,
+                "access_denied",
+                "the approving account is no longer active",
+            )
+        }
+        Err(e) => return ApiError::from(e).into_response(),
+    };
+
+    let (token, expires_in) = match issue_session_token(state, user.id, &user.roles, headers).await
+    {
+        Ok(issued) => issued,
+        Err(e) => return e.into_response(),
+    };
+    info!(user_id = %user.id, client_id = %row.client_id, "User logged in with device authorization");
+
+    token_response(json!({
+        "access_token": token,
+        "token_type": "Bearer",
+        "expires_in": expires_in,
+    }))
+}
@@ -0,0 +1,41 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use didhub_auth::auth::{PwnedPasswordsClient, PwnedPasswordsError};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+
+use crate::{error::ApiError, state::AppState};
+
+static PWNED_PASSWORDS: Lazy<PwnedPasswordsClient> = Lazy::new(PwnedPasswordsClient::default);
+
+/// Proxy a Have I Been Pwned range lookup. The browser cannot call the API
+/// directly because the content security policy only allows same-origin requests.
+pub async fn breached(
+    Extension(state): Extension<Arc<AppState>>,
+    _headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    if !state.password_policy().check_breached {
+        return Err(ApiError::not_found("breached password checks are disabled"));
+    }
+    let prefix = path
+        .get("prefix")
+        .ok_or_else(|| ApiError::bad_request("prefix missing"))?;
+
+    let range = PWNED_PASSWORDS.range(prefix).await.map_err(|e| match e {
+        PwnedPasswordsError::InvalidPrefix => ApiError::bad_request(e.to_string()),
+        PwnedPasswordsError::Request(_) => {
+            tracing::warn!(error = %e, "breached password lookup failed");
+            ApiError::internal_error("breached password lookup failed")
+        }
+    })?;
+
+    let items: Vec<Value> = range
+        .into_iter()
+        .map(|(suffix, count)| json!({ "suffix": suffix, "count": count }))
+        .collect();
+    Ok(Json(json!({ "items": items })))
+}
@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+use crate::{error::ApiError, state::AppState};
+
+/// Return the current password policy. Public, so that registration forms can
+/// enforce it before hashing.
+pub async fn get(
+    Extension(state): Extension<Arc<AppState>>,
+    _headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let policy = state.password_policy();
+    Ok(Json(
+        serde_json::to_value(&*policy).map_err(ApiError::from)?,
+    ))
+}
@@ -0,0 +1,2 @@
+pub mod breached;
+pub mod get;
@@ -45,7 +45,7 @@ pub async fn create(
     )
     .map_err(ApiError::from)?;
 
-    validate_relationship_type(&state.db_pool, &relation_type).await?;
+    validate_relationship_type(&state, &relation_type).await?;
 
     // optional side ids
     let side_a_user_id: Option<SqlxUuid> = match payload.get("side_a_user_id").cloned() {
@@ -14,7 +14,10 @@ pub async fn list(
     _query: Option<Query<HashMap<String, String>>>,
 ) -> Result<Json<Value>, ApiError> {
     // Only approved users (or admin) may list relationships
-    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &_headers).await?;
+    // Only relationships touching the caller's systems, unless it is an admin
+    let viewer = crate::system_access::list_scope(&auth)?;
     state
         .audit_request(
             "GET",
@@ -25,9 +28,13 @@ pub async fn list(
         )
         .await?;
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
-    let rows = db_rels::list_ordered_by_created_at_desc(&mut *conn)
-        .await
-        .map_err(ApiError::from)?;
+    let rows = match viewer {
+        Some(viewer) => {
+            didhub_db::custom::relationships::list_visible_to(&mut *conn, &viewer).await
+        }
+        None => db_rels::list_ordered_by_created_at_desc(&mut *conn).await,
+    }
+    .map_err(ApiError::from)?;
     let responses: Vec<RelationshipResponse> = rows.into_iter().map(Into::into).collect();
     Ok(Json(
         serde_json::to_value(&responses).map_err(ApiError::from)?,
@@ -41,6 +41,7 @@ pub async fn update(
         .await
         .map_err(ApiError::from)?;
     let mut existing = existing.ok_or_else(|| ApiError::not_found("relationship not found"))?;
+    let before = serde_json::to_value(&existing).map_err(ApiError::from)?;
 
     let is_admin = auth.is_admin();
     let is_creator = auth
@@ -72,7 +73,7 @@ pub async fn update(
     }
 
     if let Some(t) = dto.r#type {
-        validate_relationship_type(&state.db_pool, &t).await?;
+        validate_relationship_type(&state, &t).await?;
         existing.r#type = t;
     }
     if let Some(pl) = dto.past_life {
@@ -107,6 +108,10 @@ pub async fn update(
     if affected == 0 {
         return Err(ApiError::not_found("relationship not found"));
     }
+    let after = serde_json::to_value(&existing).map_err(ApiError::from)?;
+    state
+        .audit_change("relationship", id, auth.user_id, &before, &after)
+        .await;
     let response: crate::handlers::relationships::dto::RelationshipResponse = existing.into();
     Ok(Json(
         serde_json::to_value(&response).map_err(ApiError::from)?,
@@ -1,8 +1,8 @@
-use didhub_db::generated::instance_settings::find_first_by_key;
 use serde::Deserialize;
-use sqlx::Pool;
 
 use crate::error::ApiError;
+use crate::handlers::instance_settings::helpers::cached_instance_setting;
+use crate::state::AppState;
 
 #[derive(Deserialize, Debug)]
 pub struct CustomRelationshipType {
@@ -11,7 +11,7 @@ pub struct CustomRelationshipType {
 }
 
 pub async fn validate_relationship_type(
-    db_pool: &Pool<didhub_db::DbBackend>,
+    state: &AppState,
     relation_type: &str,
 ) -> Result<(), ApiError> {
     // 1. Built-in types are always valid
@@ -20,10 +20,7 @@ pub async fn validate_relationship_type(
     }
 
     // 2. Check instance settings for custom types
-    let mut conn = db_pool.acquire().await.map_err(ApiError::from)?;
-    let setting = find_first_by_key(conn.as_mut(), &"custom_relationship_types".to_string())
-        .await
-        .map_err(ApiError::from)?;
+    let setting = cached_instance_setting(state, "custom_relationship_types").await?;
 
     if let Some(row) = setting {
         if let Some(json_str) = row.value_string {
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use didhub_auth::auth::Permission;
+use serde_json::{json, Value};
+use sqlx::types::Uuid as SqlxUuid;
+
+use didhub_db::generated::users as db_users;
+
+use crate::handlers::roles::{apply_role_change, ensure_owner_remains};
+use crate::{error::ApiError, state::AppState};
+
+/// Replace the roles of a user. Body: `{ "roles": ["moderator", ...] }`.
+///
+/// Assigning moderator, member or viewer requires `assign_roles`; admin and owner
+/// require `manage_admins`. The last owner cannot be demoted. The user's existing
+/// tokens are revoked so the new roles apply from their next login.
+pub async fn assign(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::require_permission(&state, &headers, Permission::AssignRoles)
+            .await?;
+
+    let id_str = path
+        .get("userId")
+        .ok_or_else(|| ApiError::not_found("user id missing"))?
+        .to_string();
+    let id: SqlxUuid =
+        SqlxUuid::parse_str(&id_str).map_err(|_| ApiError::bad_request("invalid uuid"))?;
+
+    let payload = body
+        .as_ref()
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0
+        .clone();
+    let requested: Vec<String> = payload
+        .get("roles")
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()
+        .map_err(|_| ApiError::bad_request("roles must be an array of strings"))?
+        .ok_or_else(|| ApiError::bad_request("roles is required"))?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let mut user = db_users::find_by_primary_key(&mut *conn, &id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("user not found"))?;
+
+    let current: Vec<String> = serde_json::from_str(&user.roles).unwrap_or_default();
+    let roles = apply_role_change(&auth, &current, &requested)?;
+
+    ensure_owner_remains(&mut *conn, &current, &roles).await?;
+
+    user.roles = serde_json::to_string(&roles).map_err(ApiError::from)?;
+    user.updated_at = chrono::Utc::now().to_rfc3339();
+    db_users::update_by_primary_key(&mut *conn, &id, &user)
+        .await
+        .map_err(ApiError::from)?;
+    drop(conn);
+
+    if current != roles {
+        state.revocation_store().revoke_user_tokens(id).await?;
+        if let Err(e) = crate::sessions::forget_user_sessions(&state, id).await {
+            tracing::warn!(user_id = %id, error = %e, "failed to delete revoked sessions");
+        }
+    }
+    tracing::info!(user_id = %id, roles = ?roles, assigned_by = ?auth.user_id, "updated user roles");
+
+    Ok(Json(json!({ "id": id, "roles": roles })))
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use didhub_auth::auth::Role;
+use serde_json::{json, Value};
+
+use crate::{error::ApiError, state::AppState};
+
+/// List the assignable roles, most privileged first, with the permissions each grants.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+
+    let roles: Vec<Value> = Role::ALL
+        .iter()
+        .map(|role| {
+            json!({
+                "name": role.as_str(),
+                "permissions": role.permissions(),
+                "assignPermission": role.required_to_assign(),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "items": roles })))
+}
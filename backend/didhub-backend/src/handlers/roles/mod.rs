@@ -0,0 +1,77 @@
+pub mod assign;
+pub mod list;
+
+use didhub_auth::auth::{AuthContext, Role};
+use didhub_db::DbBackend;
+use sqlx::Executor;
+
+use crate::error::ApiError;
+use crate::handlers::auth::utils::ensure_permission;
+
+/// Account types stored alongside roles; they are kept as given and grant no permissions.
+const ACCOUNT_TYPES: &[&str] = &["system"];
+
+/// Validate a requested role list against the caller's permissions and normalize it.
+///
+/// Every role added or removed relative to `current` must be assignable by `auth`
+/// (see [`Role::required_to_assign`]). Unknown names are rejected, `member` is stored
+/// as `user`, and `owner` always carries `admin`.
+pub fn apply_role_change(
+    auth: &AuthContext,
+    current: &[String],
+    requested: &[String],
+) -> Result<Vec<String>, ApiError> {
+    let mut roles = Vec::new();
+    let mut account_types = Vec::new();
+    for name in requested {
+        match Role::from_name(name) {
+            Some(role) => roles.push(role),
+            None if ACCOUNT_TYPES.contains(&name.as_str()) => account_types.push(name.clone()),
+            None => return Err(ApiError::bad_request(format!("unknown role: {}", name))),
+        }
+    }
+    if roles.contains(&Role::Owner) {
+        roles.push(Role::Admin);
+    }
+    roles.sort();
+    roles.dedup();
+    account_types.sort();
+    account_types.dedup();
+
+    let held = didhub_auth::auth::roles_from_names(current);
+    for role in Role::ALL {
+        if held.contains(&role) != roles.contains(&role) {
+            ensure_permission(auth, role.required_to_assign())?;
+        }
+    }
+
+    Ok(roles
+        .iter()
+        .map(|r| r.as_str().to_string())
+        .chain(account_types)
+        .collect())
+}
+
+/// Refuse a change that would leave the instance without an owner.
+pub async fn ensure_owner_remains<'e, E>(
+    executor: E,
+    current: &[String],
+    updated: &[String],
+) -> Result<(), ApiError>
+where
+    E: Executor<'e, Database = DbBackend>,
+{
+    let is_owner = |roles: &[String]| roles.iter().any(|r| r == Role::Owner.as_str());
+    if !is_owner(current) || is_owner(updated) {
+        return Ok(());
+    }
+    let owners: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users WHERE roles LIKE ?")
+        .bind("%\"owner\"%")
+        .fetch_one(executor)
+        .await
+        .map_err(ApiError::from)?;
+    if owners <= 1 {
+        return Err(ApiError::bad_request("cannot remove the last owner"));
+    }
+    Ok(())
+}
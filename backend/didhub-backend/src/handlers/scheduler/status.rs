@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use axum::extract::Extension;
+use axum::http::HeaderMap;
+use axum::Json;
+use serde_json::{json, Value};
+
+use crate::{error::ApiError, state::AppState};
+
+/// GET /admin/scheduler
+/// Report registered scheduled jobs, their schedules and run state.
+pub async fn status(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    crate::handlers::auth::utils::require_admin(&state, &headers).await?;
+
+    let jobs: Vec<Value> = state
+        .scheduler
+        .jobs()
+        .await
+        .into_iter()
+        .map(|job| {
+            json!({
+                "name": job.name,
+                "schedule": job.schedule,
+                "kind": job.kind,
+                "enabled": job.enabled,
+                "running": job.running,
+                "nextFireAt": job.next_fire_at.map(|dt| dt.to_rfc3339()),
+                "lastRun": job.last_run.map(|run| json!({
+                    "id": run.id,
+                    "status": run.status.to_string(),
+                    "startedAt": run.started_at.to_rfc3339(),
+                    "finishedAt": run.finished_at.map(|dt| dt.to_rfc3339()),
+                    "errorMessage": run.error_message,
+                })),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "enabled": state.scheduler.is_started(),
+        "jobs": jobs,
+    })))
+}
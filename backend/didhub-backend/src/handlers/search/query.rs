@@ -0,0 +1,103 @@
+use crate::{error::ApiError, handlers::utils::parse_positive_usize, state::AppState};
+use axum::extract::Query as AxumQuery;
+use axum::http::HeaderMap;
+use axum::{Extension, Json};
+use didhub_db::custom::search as db_search;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+const MAX_QUERY_LEN: usize = 200;
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 50;
+
+/// GET /search?q=&type=&limit=
+///
+/// Ranked full-text search over alter names, descriptions, notes and interests,
+/// affiliation names and descriptions, and subsystem names, limited to the systems
+/// the caller can see. Every word must match; the last one also matches as a
+/// prefix. Snippets are HTML-escaped with matches wrapped in `<mark>`.
+pub async fn search(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    query: Option<AxumQuery<HashMap<String, String>>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    // Only the systems the caller belongs to, unless it is an admin
+    let viewer = crate::system_access::list_scope(&auth)?;
+
+    let params = query.map(|v| v.0).unwrap_or_default();
+    let q = params.get("q").map(|s| s.trim()).unwrap_or_default();
+    if q.is_empty() {
+        return Err(ApiError::bad_request("q is required"));
+    }
+    if q.chars().count() > MAX_QUERY_LEN {
+        return Err(ApiError::bad_request(format!(
+            "q must be at most {MAX_QUERY_LEN} characters"
+        )));
+    }
+    let entity_type = match params.get("type").map(String::as_str) {
+        None | Some("") => None,
+        Some(t) if db_search::ENTITY_TYPES.contains(&t) => Some(t),
+        Some(_) => {
+            return Err(ApiError::bad_request(
+                "type must be one of alter, affiliation, subsystem",
+            ))
+        }
+    };
+    let limit = parse_positive_usize(params.get("limit"), DEFAULT_LIMIT, "limit")?.min(MAX_LIMIT);
+
+    state
+        .audit_request("GET", "/search", &HashMap::new(), &params, &Value::Null)
+        .await?;
+
+    let terms = db_search::query_terms(q);
+    if terms.is_empty() {
+        return Ok(Json(json!({ "items": [] })));
+    }
+    let mut conn = state.acquire_read().await?;
+    let hits = db_search::search(
+        &mut *conn,
+        &terms,
+        entity_type,
+        viewer.as_ref(),
+        limit as i64,
+    )
+    .await
+    .map_err(ApiError::from)?;
+
+    let items: Vec<Value> = hits
+        .iter()
+        .map(|hit| {
+            json!({
+                "type": hit.entity_type,
+                "id": hit.entity_id,
+                "name": hit.name,
+                "systemId": hit.system_id,
+                "snippet": highlight(&hit.snippet),
+                "score": hit.score,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "items": items })))
+}
+
+/// Escape a raw snippet for HTML and turn the highlight markers into `<mark>` tags.
+pub fn highlight(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len() + 16);
+    for c in raw.chars() {
+        match c {
+            db_search::HIGHLIGHT_START => out.push_str("<mark>"),
+            db_search::HIGHLIGHT_END => out.push_str("</mark>"),
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde_json::Value;
+use uuid::Uuid;
+
+use didhub_db::generated::service_clients as db_clients;
+
+use super::{client_to_payload, require_manager, validate_scopes};
+use crate::service_clients::generate_secret;
+use crate::{error::ApiError, state::AppState};
+
+/// Register a service client. The plaintext secret is only returned here.
+pub async fn create(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth = require_manager(&state, &headers).await?;
+
+    let payload = body
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0;
+
+    let name = payload
+        .get("name")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ApiError::bad_request("missing name"))?
+        .to_string();
+    let scopes: Vec<String> = match payload.get("scopes") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(value) => serde_json::from_value(value.clone()).map_err(ApiError::from)?,
+    };
+    let scopes = validate_scopes(&auth, &scopes)?;
+
+    let (secret, secret_hash) = generate_secret();
+    let row = db_clients::ServiceClientsRow {
+        id: Uuid::new_v4(),
+        name,
+        secret_hash,
+        scopes: serde_json::to_string(&scopes).map_err(ApiError::from)?,
+        created_by: auth.user_id,
+        last_used_at: None,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    db_clients::insert_service_client(&*state.db_pool, &row)
+        .await
+        .map_err(ApiError::from)?;
+    tracing::info!(client_id = %row.id, created_by = ?auth.user_id, "registered service client");
+
+    let mut response = client_to_payload(&row);
+    response["clientSecret"] = Value::String(secret);
+    Ok(Json(response))
+}
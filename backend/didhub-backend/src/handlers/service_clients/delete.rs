@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use didhub_db::generated::service_clients as db_clients;
+
+use crate::{error::ApiError, state::AppState};
+
+/// Delete a service client. Tokens already issued to it are revoked.
+pub async fn delete(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let (auth, client) = super::load_client(&state, &headers, &path).await?;
+
+    db_clients::delete_by_primary_key(&*state.db_pool, &client.id)
+        .await
+        .map_err(ApiError::from)?;
+    state
+        .revocation_store()
+        .revoke_user_tokens(client.id)
+        .await?;
+    tracing::info!(client_id = %client.id, deleted_by = ?auth.user_id, "deleted service client");
+
+    Ok(Json(json!({ "deleted": true })))
+}
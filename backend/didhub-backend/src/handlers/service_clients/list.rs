@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use super::{client_to_payload, require_manager};
+use crate::{error::ApiError, state::AppState};
+
+/// List registered service clients, oldest first.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    require_manager(&state, &headers).await?;
+
+    let rows: Vec<didhub_db::generated::service_clients::ServiceClientsRow> =
+        sqlx::query_as("SELECT * FROM service_clients ORDER BY created_at")
+            .fetch_all(&*state.db_pool)
+            .await
+            .map_err(ApiError::from)?;
+
+    let items: Vec<Value> = rows.iter().map(client_to_payload).collect();
+    Ok(Json(json!({ "items": items })))
+}
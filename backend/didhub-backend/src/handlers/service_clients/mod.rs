@@ -0,0 +1,82 @@
+pub mod create;
+pub mod delete;
+pub mod list;
+pub mod rotate_secret;
+
+use std::collections::HashMap;
+
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use didhub_auth::auth::{AuthContext, Permission, Role};
+use didhub_db::generated::service_clients as db_clients;
+
+use crate::handlers::auth::utils::{ensure_permission, require_permission, require_user_id};
+use crate::service_clients::client_scopes;
+use crate::{error::ApiError, state::AppState};
+
+/// Public view of a service client; the secret hash is never returned.
+pub fn client_to_payload(row: &db_clients::ServiceClientsRow) -> Value {
+    json!({
+        "id": row.id,
+        "name": row.name,
+        "scopes": client_scopes(row),
+        "createdBy": row.created_by,
+        "lastUsedAt": row.last_used_at,
+        "createdAt": row.created_at,
+    })
+}
+
+/// Service clients are managed by users with instance permissions. Service clients
+/// themselves cannot register or rotate other clients.
+async fn require_manager(state: &AppState, headers: &HeaderMap) -> Result<AuthContext, ApiError> {
+    let auth = require_permission(state, headers, Permission::ManageInstance).await?;
+    require_user_id(&auth)?;
+    Ok(auth)
+}
+
+/// Role scopes requested for a client. Each must be a role the caller may assign;
+/// `owner` is never granted to a machine.
+fn validate_scopes(auth: &AuthContext, scopes: &[String]) -> Result<Vec<String>, ApiError> {
+    if scopes.is_empty() {
+        return Err(ApiError::bad_request("scopes must not be empty"));
+    }
+    let mut granted = Vec::with_capacity(scopes.len());
+    for scope in scopes {
+        let role = Role::from_name(scope)
+            .ok_or_else(|| ApiError::bad_request(format!("unknown scope `{}`", scope)))?;
+        if role == Role::Owner {
+            return Err(ApiError::bad_request(
+                "service clients cannot hold the owner role",
+            ));
+        }
+        ensure_permission(auth, role.required_to_assign())?;
+        let name = role.as_str().to_string();
+        if !granted.contains(&name) {
+            granted.push(name);
+        }
+    }
+    Ok(granted)
+}
+
+/// Load the client named by the `clientId` path parameter.
+async fn load_client(
+    state: &AppState,
+    headers: &HeaderMap,
+    path: &HashMap<String, String>,
+) -> Result<(AuthContext, db_clients::ServiceClientsRow), ApiError> {
+    let auth = require_manager(state, headers).await?;
+
+    let client_id_str = path
+        .get("clientId")
+        .ok_or_else(|| ApiError::bad_request("missing clientId"))?;
+    let client_id =
+        Uuid::parse_str(client_id_str).map_err(|_| ApiError::bad_request("invalid clientId"))?;
+
+    let client = db_clients::find_by_primary_key(&*state.db_pool, &client_id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("service client not found"))?;
+    Ok((auth, client))
+}
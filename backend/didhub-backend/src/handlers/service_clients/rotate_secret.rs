@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+use super::client_to_payload;
+use crate::service_clients::generate_secret;
+use crate::{error::ApiError, state::AppState};
+
+/// Replace a service client's secret. Tokens issued before the rotation are revoked,
+/// so a leaked secret stops working as soon as this returns.
+pub async fn rotate_secret(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let (auth, mut client) = super::load_client(&state, &headers, &path).await?;
+
+    let (secret, secret_hash) = generate_secret();
+    client.secret_hash = secret_hash;
+    // Only the secret is written, so a concurrent token request cannot undo it
+    sqlx::query("UPDATE service_clients SET secret_hash = ? WHERE id = ?")
+        .bind(&client.secret_hash)
+        .bind(client.id)
+        .execute(&*state.db_pool)
+        .await
+        .map_err(ApiError::from)?;
+    state
+        .revocation_store()
+        .revoke_user_tokens(client.id)
+        .await?;
+    tracing::info!(client_id = %client.id, rotated_by = ?auth.user_id, "rotated service client secret");
+
+    let mut response = client_to_payload(&client);
+    response["clientSecret"] = Value::String(secret);
+    Ok(Json(response))
+}
@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use didhub_db::generated::sessions as db_sessions;
+
+use crate::{error::ApiError, state::AppState};
+
+/// Revoke one session, signing it out. Only the owner or an admin may do so.
+pub async fn delete(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+
+    let session_id_str = path
+        .get("sessionId")
+        .ok_or_else(|| ApiError::bad_request("missing sessionId"))?;
+    let session_id =
+        Uuid::parse_str(session_id_str).map_err(|_| ApiError::bad_request("invalid sessionId"))?;
+
+    let existing = db_sessions::find_by_primary_key(&*state.db_pool, &session_id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("session not found"))?;
+    crate::handlers::auth::utils::ensure_admin_or_user(&auth, existing.user_id)?;
+
+    crate::sessions::revoke(&state, &existing).await?;
+    tracing::info!(session_id = %session_id, revoked_by = ?auth.user_id, "revoked session");
+
+    Ok(Json(json!({ "revoked": true })))
+}
@@ -0,0 +1,44 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde_json::{json, Value};
+
+use didhub_db::generated::sessions as db_sessions;
+
+use crate::sessions::{is_expired, session_to_payload};
+use crate::{error::ApiError, state::AppState};
+
+/// List the current user's active sessions, most recently used first.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let rows = db_sessions::find_by_user_id(&mut *conn, &user_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    // Expired sessions are dropped lazily whenever the list is read.
+    let now = Utc::now();
+    let (expired, mut rows): (Vec<_>, Vec<_>) =
+        rows.into_iter().partition(|row| is_expired(row, now));
+    for row in &expired {
+        db_sessions::delete_by_primary_key(&mut *conn, &row.id)
+            .await
+            .map_err(ApiError::from)?;
+    }
+    rows.sort_by(|a, b| b.last_seen_at.cmp(&a.last_seen_at));
+
+    let current = auth.token_id.as_deref();
+    let items: Vec<Value> = rows
+        .iter()
+        .map(|row| session_to_payload(row, current == Some(row.token_id.as_str())))
+        .collect();
+    Ok(Json(json!({ "items": items })))
+}
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use didhub_db::generated::sessions as db_sessions;
+
+use crate::{error::ApiError, state::AppState};
+
+/// Revoke every session of the current user except the one making the request.
+pub async fn revoke_others(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+    let current = auth.token_id.as_deref();
+
+    let rows = db_sessions::find_by_user_id(&*state.db_pool, &user_id)
+        .await
+        .map_err(ApiError::from)?;
+    let mut revoked = 0;
+    for row in rows
+        .iter()
+        .filter(|row| current != Some(row.token_id.as_str()))
+    {
+        crate::sessions::revoke(&state, row).await?;
+        revoked += 1;
+    }
+    tracing::info!(%user_id, revoked, "revoked other sessions");
+
+    Ok(Json(json!({ "revoked": revoked })))
+}
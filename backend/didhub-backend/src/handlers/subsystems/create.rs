@@ -16,7 +16,8 @@ pub async fn create(
     _headers: HeaderMap,
     _body: Option<Json<Value>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::require_admin(&_state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
 
     let payload = _body
         .as_ref()
@@ -51,10 +52,22 @@ pub async fn create(
             SqlxUuid::parse_str(&s)
                 .map_err(|_| ApiError::bad_request("invalid owner_user_id or systemId"))?,
         )
-    } else {
+    } else if auth.is_admin() {
         None
+    } else {
+        // The caller's own system unless another one it may edit is named
+        Some(crate::handlers::auth::utils::require_user_id(&auth)?)
     };
 
+    let mut conn = _state.db_pool.acquire().await.map_err(ApiError::from)?;
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        owner_user_id.as_ref(),
+        "creating subsystem",
+    )
+    .await?;
+
     let now = Utc::now().to_rfc3339();
     let new_row = db_subsystems::SubsystemsRow {
         id: SqlxUuid::new_v4(),
@@ -63,7 +76,6 @@ pub async fn create(
         created_at: now.clone(),
     };
 
-    let mut conn = _state.db_pool.acquire().await.map_err(ApiError::from)?;
     db_subsystems::insert_subsystem(&mut *conn, &new_row)
         .await
         .map_err(ApiError::from)?;
@@ -15,7 +15,8 @@ pub async fn delete(
     _headers: HeaderMap,
     _path: Path<HashMap<String, String>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::require_admin(&_state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
 
     _state
         .audit_request(
@@ -36,6 +37,17 @@ pub async fn delete(
 
     let id = SqlxUuid::parse_str(&id_str).map_err(|_| ApiError::bad_request("invalid uuid"))?;
     let mut conn = _state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let existing = db_subsystems::find_by_primary_key(&mut *conn, &id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("subsystem not found"))?;
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        existing.owner_user_id.as_ref(),
+        "deleting subsystem",
+    )
+    .await?;
 
     sqlx::query("DELETE FROM subsystem_members WHERE subsystem_id = ?")
         .bind(id)
@@ -49,6 +61,10 @@ pub async fn delete(
     if affected == 0 {
         return Err(ApiError::not_found("subsystem not found"));
     }
+    let snapshot = serde_json::to_value(&existing).map_err(ApiError::from)?;
+    _state
+        .record_version("subsystem", id, "delete", auth.user_id, &snapshot)
+        .await;
     Ok(Json(
         serde_json::to_value(serde_json::json!({ "deleted": true })).map_err(ApiError::from)?,
     ))
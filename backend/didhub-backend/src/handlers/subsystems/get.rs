@@ -16,7 +16,8 @@ pub async fn get(
     _path: Path<HashMap<String, String>>,
 ) -> Result<Json<Value>, ApiError> {
     // Only approved users (or admin) may get
-    crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
 
     _state
         .audit_request(
@@ -41,7 +42,12 @@ pub async fn get(
         .await
         .map_err(ApiError::from)?;
     match opt {
-        Some(row) => Ok(Json(serde_json::to_value(&row).map_err(ApiError::from)?)),
-        None => Err(ApiError::not_found("subsystem not found")),
+        Some(row)
+            if crate::system_access::can_view(&mut conn, &auth, row.owner_user_id.as_ref())
+                .await =>
+        {
+            Ok(Json(serde_json::to_value(&row).map_err(ApiError::from)?))
+        }
+        _ => Err(ApiError::not_found("subsystem not found")),
     }
 }
@@ -14,13 +14,15 @@ use didhub_db::generated::subsystems as db_subsystems;
 
 use super::helpers::{parse_owner_filter, subsystem_to_payload};
 
+#[allow(clippy::unnecessary_cast)]
 pub async fn list(
     Extension(_state): Extension<Arc<AppState>>,
     _headers: HeaderMap,
     _query: Option<Query<HashMap<String, String>>>,
 ) -> Result<Json<Value>, ApiError> {
     // Only approved users (or admin) may list subsystems
-    crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
 
     _state
         .audit_request(
@@ -43,18 +45,25 @@ pub async fn list(
         .or_else(|| params.get("owner_user_id"))
         .map(|s| s.to_string());
 
-    let mut conn = _state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let mut conn = _state.acquire_read().await?;
 
     let mut where_clauses: Vec<String> = Vec::new();
     let mut params_uuid: Vec<SqlxUuid> = Vec::new();
 
-    if name_filter.is_some() {
-        where_clauses.push("LOWER(name) LIKE ?".to_string());
-    }
     if let Some(owner) = parse_owner_filter(owner_filter)? {
         where_clauses.push("owner_user_id = ?".to_string());
         params_uuid.push(owner);
     }
+    // Only the systems the caller belongs to, unless it is an admin
+    if let Some(viewer) = crate::system_access::list_scope(&auth)? {
+        where_clauses.push(didhub_db::custom::system_members::visible_condition(
+            "owner_user_id",
+        ));
+        params_uuid.extend([viewer, viewer]);
+    }
+    if name_filter.is_some() {
+        where_clauses.push("LOWER(name) LIKE ?".to_string());
+    }
 
     let where_sql = if where_clauses.is_empty() {
         "".to_string()
@@ -9,9 +9,7 @@ use serde_json::Value;
 use sqlx::types::Uuid as SqlxUuid;
 
 use crate::{error::ApiError, state::AppState};
-use didhub_db::generated::{
-    alters as db_alters, subsystem_members as db_members, subsystems as db_subsystems,
-};
+use didhub_db::generated::{subsystem_members as db_members, subsystems as db_subsystems};
 
 pub async fn add(
     Extension(_state): Extension<Arc<AppState>>,
@@ -19,7 +17,8 @@ pub async fn add(
     _path: Path<HashMap<String, String>>,
     _body: Option<Json<Value>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::require_admin(&_state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
 
     let payload = _body
         .as_ref()
@@ -63,8 +62,15 @@ pub async fn add(
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("subsystem not found"))?;
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        subsystem.owner_user_id.as_ref(),
+        "adding subsystem member",
+    )
+    .await?;
 
-    let alter = db_alters::find_by_primary_key(&mut *conn, &alter_id)
+    let alter = didhub_db::custom::alters::find_active(&mut *conn, &alter_id)
         .await
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("alter not found"))?;
@@ -8,7 +8,7 @@ use serde_json::{json, Value};
 use uuid::Uuid;
 
 use crate::{error::ApiError, state::AppState};
-use didhub_db::generated::subsystem_members as db_subsystem_members;
+use didhub_db::custom::subsystem_members as db_subsystem_members;
 use didhub_db::generated::subsystems as db_subsystems;
 
 pub async fn list(
@@ -16,7 +16,8 @@ pub async fn list(
     headers: HeaderMap,
     Path(path): Path<HashMap<String, String>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
 
     let subsystem_id_str = path
         .get("subsystemId")
@@ -41,9 +42,12 @@ pub async fn list(
         .map_err(ApiError::from)?
         .ok_or_else(|| ApiError::not_found("subsystem not found"))?;
 
-    let _ = &subsystem;
+    if !crate::system_access::can_view(&mut conn, &auth, subsystem.owner_user_id.as_ref()).await {
+        return Err(ApiError::not_found("subsystem not found"));
+    }
 
-    let members = db_subsystem_members::find_by_subsystem_id(&mut *conn, &subsystem_id)
+    // Trashed alters are left out
+    let members = db_subsystem_members::list_active_for_subsystem(&mut *conn, &subsystem_id)
         .await
         .map_err(ApiError::from)?;
 
@@ -8,13 +8,15 @@ use serde_json::Value;
 use sqlx::types::Uuid as SqlxUuid;
 
 use crate::{error::ApiError, state::AppState};
+use didhub_db::generated::subsystems as db_subsystems;
 
 pub async fn remove(
     Extension(_state): Extension<Arc<AppState>>,
     _headers: HeaderMap,
     _path: Path<HashMap<String, String>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::require_admin(&_state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
 
     _state
         .audit_request(
@@ -46,6 +48,18 @@ pub async fn remove(
         .map_err(|_| ApiError::bad_request("invalid member id"))?;
 
     let mut conn = _state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let subsystem = db_subsystems::find_by_primary_key(&mut *conn, &subsystem_id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("subsystem not found"))?;
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        subsystem.owner_user_id.as_ref(),
+        "removing subsystem member",
+    )
+    .await?;
+
     let res = sqlx::query("DELETE FROM subsystem_members WHERE subsystem_id = ? AND alter_id = ?")
         .bind(subsystem_id)
         .bind(alter_id)
@@ -55,8 +55,15 @@ pub async fn update(
         .ok_or_else(|| ApiError::not_found("subsystem not found"))?;
 
     if !is_admin {
-        if let Some(owner_id) = subsystem.owner_user_id {
-            if owner_id != user_id {
+        if let Some(system_id) = subsystem.owner_user_id {
+            if !crate::system_access::has_role(
+                &mut conn,
+                &system_id,
+                &user_id,
+                crate::system_access::SystemRole::Editor,
+            )
+            .await
+            {
                 return Err(ApiError::Authentication(
                     didhub_auth::auth::AuthError::AuthenticationFailed,
                 ));
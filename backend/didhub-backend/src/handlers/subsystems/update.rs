@@ -16,7 +16,8 @@ pub async fn update(
     _path: Path<HashMap<String, String>>,
     _body: Option<Json<Value>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::require_admin(&_state, &_headers).await?;
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&_state, &_headers).await?;
 
     let payload = _body
         .as_ref()
@@ -48,6 +49,14 @@ pub async fn update(
         .await
         .map_err(ApiError::from)?;
     let mut existing = existing.ok_or_else(|| ApiError::not_found("subsystem not found"))?;
+    crate::system_access::ensure_can_edit(
+        &mut conn,
+        &auth,
+        existing.owner_user_id.as_ref(),
+        "updating subsystem",
+    )
+    .await?;
+    let before = serde_json::to_value(&existing).map_err(ApiError::from)?;
 
     if let Some(name_v) = payload.get("name") {
         existing.name = serde_json::from_value(name_v.clone()).map_err(ApiError::from)?;
@@ -63,6 +72,14 @@ pub async fn update(
         } else {
             None
         };
+        // Moving it to another system needs edit rights there as well
+        crate::system_access::ensure_can_edit(
+            &mut conn,
+            &auth,
+            existing.owner_user_id.as_ref(),
+            "moving subsystem",
+        )
+        .await?;
     }
 
     let affected = db_subsystems::update_by_primary_key(&mut *conn, &id, &existing)
@@ -71,7 +88,14 @@ pub async fn update(
     if affected == 0 {
         return Err(ApiError::not_found("subsystem not found"));
     }
-    Ok(Json(
-        serde_json::to_value(&existing).map_err(ApiError::from)?,
-    ))
+    let after = serde_json::to_value(&existing).map_err(ApiError::from)?;
+    if before != after {
+        _state
+            .record_version("subsystem", id, "update", auth.user_id, &before)
+            .await;
+    }
+    _state
+        .audit_change("subsystem", id, auth.user_id, &before, &after)
+        .await;
+    Ok(Json(after))
 }
@@ -16,7 +16,12 @@ pub async fn decide(
     Path(path): Path<HashMap<String, String>>,
     body: Option<Json<Value>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::require_admin(&state, &headers).await?;
+    crate::handlers::auth::utils::require_permission(
+        &state,
+        &headers,
+        didhub_auth::auth::Permission::ReviewSystemRequests,
+    )
+    .await?;
 
     state
         .audit_request(
@@ -14,7 +14,12 @@ pub async fn list(
     headers: HeaderMap,
     query: Option<Query<HashMap<String, String>>>,
 ) -> Result<Json<Value>, ApiError> {
-    crate::handlers::auth::utils::require_admin(&state, &headers).await?;
+    crate::handlers::auth::utils::require_permission(
+        &state,
+        &headers,
+        didhub_auth::auth::Permission::ReviewSystemRequests,
+    )
+    .await?;
     let query_params = query
         .as_ref()
         .map(|value| value.0.clone())
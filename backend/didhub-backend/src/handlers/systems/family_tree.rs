@@ -17,9 +17,11 @@ pub async fn get(
 ) -> Result<Json<Value>, ApiError> {
     let auth =
         crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
-    let _user_id = auth
+    let user_id = auth
         .user_id
         .ok_or_else(|| ApiError::forbidden("user_id required for family tree access"))?;
+    // Only the systems the caller belongs to, unless it is an admin
+    let viewer = crate::system_access::list_scope(&auth)?;
 
     let params = query.map(|q| q.0).unwrap_or_default();
     let start_id_param = params.get("startId").cloned();
@@ -31,15 +33,30 @@ pub async fn get(
 
     let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
 
-    let users = didhub_db::generated::users::list_all(&mut *conn)
+    let mut users = didhub_db::generated::users::list_all(&mut *conn)
         .await
         .map_err(ApiError::from)?;
-    let alters = didhub_db::generated::alters::list_all(&mut *conn)
-        .await
-        .map_err(ApiError::from)?;
-    let relationships = didhub_db::generated::relationships::list_all(&mut *conn)
+    let alter_filter = didhub_db::custom::alters::AlterFilter {
+        visible_to: viewer,
+        ..Default::default()
+    };
+    let alters = didhub_db::custom::alters::list_active(&mut *conn, &alter_filter)
         .await
         .map_err(ApiError::from)?;
+    let relationships = match viewer {
+        Some(viewer) => {
+            didhub_db::custom::relationships::list_visible_to(&mut *conn, &viewer).await
+        }
+        None => didhub_db::generated::relationships::list_all(&mut *conn).await,
+    }
+    .map_err(ApiError::from)?;
+    if viewer.is_some() {
+        let mut systems = didhub_db::custom::system_members::systems_of(&mut *conn, &user_id)
+            .await
+            .map_err(ApiError::from)?;
+        systems.push(user_id);
+        users.retain(|u| systems.contains(&u.id));
+    }
 
     // Build node map and adjacency
     #[derive(Clone)]
@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use didhub_db::generated::system_members as db_system_members;
+use didhub_db::generated::users as db_users;
+
+use crate::{error::ApiError, state::AppState};
+
+/// List the systems the current user is a member of, with its role in each.
+pub async fn list_own(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let memberships = db_system_members::find_by_user_id(&mut *conn, &user_id).await?;
+    let mut items = Vec::with_capacity(memberships.len());
+    for membership in &memberships {
+        let name = db_users::find_by_primary_key(&mut *conn, &membership.system_id)
+            .await?
+            .map(|system| system.username)
+            .unwrap_or_default();
+        items.push(json!({
+            "systemId": membership.system_id,
+            "name": name,
+            "role": membership.role,
+            "createdAt": membership.created_at,
+        }));
+    }
+
+    Ok(Json(json!({ "items": items })))
+}
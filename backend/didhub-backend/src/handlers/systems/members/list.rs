@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use didhub_db::generated::users as db_users;
+
+use super::{find_system, member_to_payload};
+use crate::handlers::utils::authentication_failed;
+use crate::system_access::{has_role, SystemRole};
+use crate::{error::ApiError, state::AppState};
+
+/// List the members of a system. Admins and anyone with a role in it may call this.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let system = find_system(&mut conn, &path).await?;
+    if !auth.is_admin() && !has_role(&mut conn, &system.id, &user_id, SystemRole::Viewer).await {
+        return Err(authentication_failed());
+    }
+
+    let members =
+        didhub_db::custom::system_members::list_for_system(&mut *conn, &system.id).await?;
+    let mut items = Vec::with_capacity(members.len());
+    for member in &members {
+        let username = db_users::find_by_primary_key(&mut *conn, &member.user_id)
+            .await?
+            .map(|user| user.username)
+            .unwrap_or_default();
+        items.push(member_to_payload(member, &username));
+    }
+
+    Ok(Json(json!({ "items": items })))
+}
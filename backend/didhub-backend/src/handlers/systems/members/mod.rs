@@ -0,0 +1,48 @@
+//! Accounts sharing in a system, with the role each holds there. See
+//! [`crate::system_access`] for what the roles allow.
+
+pub mod list;
+pub mod remove;
+pub mod set;
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use didhub_db::generated::system_members as db_system_members;
+use didhub_db::generated::users as db_users;
+use didhub_db::DbPoolConnection;
+
+use crate::error::ApiError;
+use crate::handlers::utils::user_is_system;
+
+pub fn member_to_payload(row: &db_system_members::SystemMembersRow, username: &str) -> Value {
+    json!({
+        "systemId": row.system_id,
+        "userId": row.user_id,
+        "username": username,
+        "role": row.role,
+        "createdAt": row.created_at,
+    })
+}
+
+/// The `name` path parameter as a uuid.
+fn path_uuid(path: &HashMap<String, String>, name: &str) -> Result<Uuid, ApiError> {
+    let raw = path
+        .get(name)
+        .ok_or_else(|| ApiError::bad_request(format!("missing {name}")))?;
+    Uuid::parse_str(raw).map_err(|_| ApiError::bad_request(format!("invalid {name}")))
+}
+
+/// The system account named by the `systemId` path parameter.
+async fn find_system(
+    conn: &mut DbPoolConnection,
+    path: &HashMap<String, String>,
+) -> Result<db_users::UsersRow, ApiError> {
+    let system_id = path_uuid(path, "systemId")?;
+    db_users::find_by_primary_key(&mut **conn, &system_id)
+        .await?
+        .filter(user_is_system)
+        .ok_or_else(|| ApiError::not_found("system not found"))
+}
@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use super::{find_system, path_uuid};
+use crate::handlers::utils::authentication_failed;
+use crate::system_access::{has_role, SystemRole};
+use crate::{error::ApiError, state::AppState};
+
+/// Remove an account from a system. Admins, owners of the system and the member
+/// itself (leaving) may call this.
+pub async fn remove(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let caller_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let system = find_system(&mut conn, &path).await?;
+    let user_id = path_uuid(&path, "userId")?;
+    if !auth.is_admin()
+        && caller_id != user_id
+        && !has_role(&mut conn, &system.id, &caller_id, SystemRole::Owner).await
+    {
+        return Err(authentication_failed());
+    }
+
+    if !didhub_db::custom::system_members::remove(&mut *conn, &system.id, &user_id).await? {
+        return Err(ApiError::not_found("member not found"));
+    }
+
+    Ok(Json(json!({ "removed": true })))
+}
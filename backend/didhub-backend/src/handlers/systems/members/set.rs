@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde_json::Value;
+
+use didhub_db::generated::system_members as db_system_members;
+use didhub_db::generated::users as db_users;
+
+use super::{find_system, member_to_payload, path_uuid};
+use crate::handlers::utils::authentication_failed;
+use crate::system_access::{has_role, SystemRole};
+use crate::{error::ApiError, state::AppState};
+
+/// Add an account to a system or change its role there. Admins and owners of the
+/// system may call this.
+pub async fn set(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let caller_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let system = find_system(&mut conn, &path).await?;
+    if !auth.is_admin() && !has_role(&mut conn, &system.id, &caller_id, SystemRole::Owner).await {
+        return Err(authentication_failed());
+    }
+
+    let user_id = path_uuid(&path, "userId")?;
+    if user_id == system.id {
+        return Err(ApiError::bad_request(
+            "the system account is always an owner of its system",
+        ));
+    }
+    let user = db_users::find_by_primary_key(&mut *conn, &user_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found("user not found"))?;
+
+    let payload = body
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0;
+    let role = payload
+        .get("role")
+        .and_then(Value::as_str)
+        .and_then(SystemRole::parse)
+        .ok_or_else(|| ApiError::bad_request("role must be owner, editor or viewer"))?;
+
+    let updated = didhub_db::custom::system_members::set_role(
+        &mut *conn,
+        &system.id,
+        &user_id,
+        role.as_str(),
+    )
+    .await?;
+    if !updated {
+        let row = db_system_members::SystemMembersRow {
+            system_id: system.id,
+            user_id,
+            role: role.as_str().to_string(),
+            created_at: Utc::now().to_rfc3339(),
+        };
+        db_system_members::insert_row(&mut *conn, &row).await?;
+    }
+
+    let member = didhub_db::custom::system_members::list_for_system(&mut *conn, &system.id)
+        .await?
+        .into_iter()
+        .find(|member| member.user_id == user_id)
+        .ok_or_else(|| ApiError::not_found("member not found"))?;
+    Ok(Json(member_to_payload(&member, &user.username)))
+}
@@ -1 +1,3 @@
 pub mod family_tree;
+pub mod list_own;
+pub mod members;
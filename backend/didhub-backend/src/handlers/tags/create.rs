@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde_json::Value;
+use uuid::Uuid;
+
+use didhub_db::generated::tags as db_tags;
+
+use super::{ensure_name_free, parse_color, parse_name, tag_to_payload};
+use crate::{error::ApiError, state::AppState};
+
+/// Create a tag for the current user. Names are unique per user.
+pub async fn create(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let payload = body
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0;
+    let name = parse_name(payload.get("name"))?;
+    let color = parse_color(payload.get("color").unwrap_or(&Value::Null))?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    ensure_name_free(&mut conn, &user_id, &name, None).await?;
+
+    let row = db_tags::TagsRow {
+        id: Uuid::new_v4(),
+        user_id,
+        name,
+        color,
+        created_at: Utc::now().to_rfc3339(),
+    };
+    db_tags::insert_tag(&mut *conn, &row)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(tag_to_payload(&row)))
+}
@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use didhub_db::generated::tags as db_tags;
+
+use super::find_own_tag;
+use crate::{error::ApiError, state::AppState};
+
+/// Delete one of the current user's tags; it is detached from all alters.
+pub async fn delete(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let tag = find_own_tag(&mut conn, &path, &user_id).await?;
+
+    db_tags::delete_by_primary_key(&mut *conn, &tag.id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(json!({ "deleted": true })))
+}
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use super::tag_to_payload;
+use crate::{error::ApiError, state::AppState};
+
+/// List the current user's tags, ordered by name.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let mut conn = state.acquire_read().await?;
+    let rows = didhub_db::custom::tags::list_for_user(&mut *conn, &user_id).await?;
+
+    let items: Vec<Value> = rows.iter().map(tag_to_payload).collect();
+    Ok(Json(json!({ "items": items })))
+}
@@ -0,0 +1,92 @@
+//! User-scoped tags for alters. Every user keeps their own set of tags, each with
+//! an optional color, and attaches them to alters via `/alters/{alterId}/tags`.
+
+pub mod create;
+pub mod delete;
+pub mod list;
+pub mod update;
+
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use didhub_db::generated::tags as db_tags;
+use didhub_db::DbPoolConnection;
+
+use crate::error::ApiError;
+
+/// Longest accepted tag name, in characters.
+const MAX_NAME_LEN: usize = 64;
+
+pub fn tag_to_payload(row: &db_tags::TagsRow) -> Value {
+    json!({
+        "id": row.id,
+        "name": row.name,
+        "color": row.color,
+        "createdAt": row.created_at,
+    })
+}
+
+/// Trimmed, non-empty tag name from `value`.
+fn parse_name(value: Option<&Value>) -> Result<String, ApiError> {
+    let name = value
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ApiError::bad_request("missing name"))?;
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(ApiError::bad_request(format!(
+            "name must be at most {MAX_NAME_LEN} characters"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+/// Color from `value`: `null` or a `#rrggbb` hex string, stored lowercase.
+fn parse_color(value: &Value) -> Result<Option<String>, ApiError> {
+    match value {
+        Value::Null => Ok(None),
+        Value::String(s)
+            if s.len() == 7
+                && s.starts_with('#')
+                && s[1..].chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            Ok(Some(s.to_ascii_lowercase()))
+        }
+        _ => Err(ApiError::bad_request("color must be a #rrggbb hex string")),
+    }
+}
+
+/// Reject `name` if `user_id` already has another tag called that.
+async fn ensure_name_free(
+    conn: &mut DbPoolConnection,
+    user_id: &Uuid,
+    name: &str,
+    except: Option<&Uuid>,
+) -> Result<(), ApiError> {
+    let existing = didhub_db::custom::tags::find_by_name(&mut **conn, user_id, name).await?;
+    match existing {
+        Some(tag) if Some(&tag.id) != except => {
+            Err(ApiError::bad_request("a tag with this name already exists"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// The tag named by the `tagId` path parameter, if it belongs to `user_id`. Other
+/// users' tags are reported as not found.
+async fn find_own_tag(
+    conn: &mut DbPoolConnection,
+    path: &HashMap<String, String>,
+    user_id: &Uuid,
+) -> Result<db_tags::TagsRow, ApiError> {
+    let tag_id = path
+        .get("tagId")
+        .ok_or_else(|| ApiError::bad_request("missing tagId"))?;
+    let tag_id = Uuid::parse_str(tag_id).map_err(|_| ApiError::bad_request("invalid tagId"))?;
+    db_tags::find_by_primary_key(&mut **conn, &tag_id)
+        .await?
+        .filter(|tag| &tag.user_id == user_id)
+        .ok_or_else(|| ApiError::not_found("tag not found"))
+}
@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::Value;
+
+use didhub_db::generated::tags as db_tags;
+
+use super::{ensure_name_free, find_own_tag, parse_color, parse_name, tag_to_payload};
+use crate::{error::ApiError, state::AppState};
+
+/// Rename or recolor one of the current user's tags.
+pub async fn update(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::authenticate_and_require_approved(&state, &headers).await?;
+    let user_id = crate::handlers::auth::utils::require_user_id(&auth)?;
+
+    let payload = body
+        .ok_or_else(|| ApiError::bad_request("missing request body"))?
+        .0;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let mut tag = find_own_tag(&mut conn, &path, &user_id).await?;
+
+    if payload.get("name").is_some() {
+        let name = parse_name(payload.get("name"))?;
+        ensure_name_free(&mut conn, &user_id, &name, Some(&tag.id)).await?;
+        tag.name = name;
+    }
+    if let Some(color) = payload.get("color") {
+        tag.color = parse_color(color)?;
+    }
+
+    db_tags::update_by_primary_key(&mut *conn, &tag.id, &tag)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(tag_to_payload(&tag)))
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Query};
+use axum::http::HeaderMap;
+use axum::Json;
+use didhub_db::custom::trash;
+use serde_json::{json, Value};
+
+use crate::handlers::auth::utils::{authenticate_and_require_approved, require_user_id};
+use crate::{error::ApiError, state::AppState};
+
+/// GET /trash
+/// List trashed alters and affiliations, most recently deleted first, with the
+/// time each will be purged. Admins see everything, other users what they own.
+pub async fn list(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    query: Option<Query<HashMap<String, String>>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth = authenticate_and_require_approved(&state, &headers).await?;
+    let params = query.map(|value| value.0).unwrap_or_default();
+    let entity_type = match params.get("type").map(String::as_str) {
+        None | Some("") => None,
+        Some(t @ ("alter" | "affiliation")) => Some(t),
+        Some(_) => return Err(ApiError::bad_request("type must be alter or affiliation")),
+    };
+    let owner = if auth.is_admin() {
+        None
+    } else {
+        Some(require_user_id(&auth)?)
+    };
+
+    let mut conn = state.acquire_read().await?;
+    let rows = trash::list(&mut *conn, owner.as_ref(), entity_type).await?;
+    let retention = state.trash_retention();
+    let items: Vec<Value> = rows
+        .into_iter()
+        .map(|row| {
+            json!({
+                "type": row.entity_type,
+                "id": row.id,
+                "name": row.name,
+                "ownerUserId": row.owner_user_id,
+                "deletedAt": row.deleted_at,
+                "purgeAt": crate::trash::purge_at(&row.deleted_at, retention),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "items": items })))
+}
@@ -0,0 +1,5 @@
+//! Trashed alters and affiliations: listing and restoring them. Deleting either
+//! moves it to the trash; see [`crate::trash`] for how it is purged.
+
+pub mod list;
+pub mod restore;
@@ -0,0 +1,43 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Path};
+use axum::http::HeaderMap;
+use axum::Json;
+use didhub_db::custom::trash;
+use serde_json::Value;
+
+use crate::handlers::auth::utils::authenticate_and_require_approved;
+use crate::handlers::history::{ensure_can_edit, EntityKind};
+use crate::{error::ApiError, state::AppState};
+
+/// POST /alters/{alterId}/restore and /affiliations/{affiliationId}/restore
+/// Take a deleted alter or affiliation out of the trash, with the memberships it
+/// had when it was deleted.
+pub async fn restore(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth = authenticate_and_require_approved(&state, &headers).await?;
+    let (kind, id) = EntityKind::from_path(&path)?;
+    let table = kind
+        .trash_table()
+        .ok_or_else(|| ApiError::not_found("subsystems have no trash"))?;
+
+    let mut conn = state.db_pool.acquire().await?;
+    // Trashed rows are only skipped by the active lookups, so this finds them too
+    let row = kind
+        .current(&mut *conn, &id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("{} not found", kind.as_str())))?;
+    ensure_can_edit(&mut conn, &auth, kind, &row, "restoring from trash").await?;
+
+    if trash::restore(&mut *conn, table, &id).await? == 0 {
+        return Err(ApiError::bad_request(format!(
+            "{} is not in the trash",
+            kind.as_str()
+        )));
+    }
+    Ok(Json(row))
+}
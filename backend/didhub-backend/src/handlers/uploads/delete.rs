@@ -60,6 +60,13 @@ pub async fn delete(
         ));
     }
 
+    // Without force, keep uploads whose file is an avatar or alter image
+    if !force && !crate::upload_references::can_delete(&mut conn, &existing).await? {
+        return Err(ApiError::conflict(
+            "upload is in use as an avatar or alter image",
+        ));
+    }
+
     let affected = db_uploads::delete_by_primary_key(&mut *conn, &id)
         .await
         .map_err(ApiError::from)?;
@@ -67,21 +74,14 @@ pub async fn delete(
         return Err(ApiError::not_found("upload not found"));
     }
 
+    // A file still in use stays until the uploads GC job finds it unused
     if force {
-        let stored_file_count: i64 =
-            sqlx::query_scalar("SELECT COUNT(*) FROM uploads WHERE stored_file_id = ?")
-                .bind(existing.stored_file_id)
-                .fetch_one(&mut *conn)
-                .await
-                .map_err(ApiError::from)?;
-
-        if stored_file_count == 0 {
-            sqlx::query("DELETE FROM stored_files WHERE id = ?")
-                .bind(existing.stored_file_id)
-                .execute(&mut *conn)
-                .await
-                .map_err(ApiError::from)?;
-        }
+        didhub_db::custom::upload_references::delete_if_unused(
+            &mut *conn,
+            &existing.stored_file_id,
+        )
+        .await
+        .map_err(ApiError::from)?;
     }
 
     Ok(Json(
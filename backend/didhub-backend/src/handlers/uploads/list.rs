@@ -2,27 +2,48 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::extract::{Extension, Json, Query};
-use serde_json::Value;
+use serde_json::{json, Value};
 
-use crate::{error::ApiError, state::AppState};
+use crate::{
+    error::ApiError,
+    handlers::utils::{encode_cursor, parse_cursor_params, CursorParams},
+    state::AppState,
+};
 use didhub_db::generated::uploads as db_uploads;
 
 pub async fn list(
     Extension(state): Extension<Arc<AppState>>,
     _headers: axum::http::HeaderMap,
-    _query: Option<Query<HashMap<String, String>>>,
+    query: Option<Query<HashMap<String, String>>>,
 ) -> Result<Json<Value>, ApiError> {
     crate::handlers::auth::utils::require_admin(&state, &_headers).await?;
+    let params = query.map(|q| q.0).unwrap_or_default();
     state
-        .audit_request(
-            "GET",
-            "/uploads",
-            &HashMap::new(),
-            &HashMap::new(),
-            &Value::Null,
-        )
+        .audit_request("GET", "/uploads", &HashMap::new(), &params, &Value::Null)
         .await?;
-    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let mut conn = state.acquire_read().await?;
+
+    // With `cursor` or `limit`, return one keyset page, newest first
+    if let Some(CursorParams { after, limit }) = parse_cursor_params(&params)? {
+        let mut rows = didhub_db::custom::uploads::list_page(
+            &mut *conn,
+            after
+                .as_ref()
+                .map(|(created_at, id)| (created_at.as_str(), id)),
+            limit as i64 + 1,
+        )
+        .await
+        .map_err(ApiError::from)?;
+        let next_cursor = if rows.len() > limit {
+            rows.truncate(limit);
+            rows.last()
+                .map(|row| encode_cursor(&row.created_at, &row.id))
+        } else {
+            None
+        };
+        return Ok(Json(json!({ "items": rows, "nextCursor": next_cursor })));
+    }
+
     let rows = db_uploads::list_all(&mut *conn)
         .await
         .map_err(ApiError::from)?;
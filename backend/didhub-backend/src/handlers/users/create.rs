@@ -6,9 +6,13 @@ use serde_json::Value;
 use sqlx::types::Uuid as SqlxUuid;
 
 use chrono::Utc;
+use didhub_auth::auth::Permission;
 use didhub_db::generated::users as db_users;
 
+use crate::handlers::roles::apply_role_change;
 use crate::handlers::users::dto::CreateUserDto;
+use crate::password_policy::check_submitted_password;
+use crate::validation::ValidationIssue;
 use crate::{error::ApiError, state::AppState};
 
 /// Create a new user. Accepts a JSON body matching UsersRow (minimal validation).
@@ -27,13 +31,23 @@ pub async fn create(
     if let Err(issues) = dto.validate() {
         return Ok(Json(crate::validation::to_payload(&issues)));
     }
+    let violations = check_submitted_password(&state.password_policy(), &dto.password_hash);
+    if let Some(violation) = violations.first() {
+        let issue = ValidationIssue::new("passwordHash", violation.code(), violation.to_string());
+        return Ok(Json(crate::validation::to_payload(&[issue])));
+    }
+
+    // Check if this is an authenticated user manager creating the user
+    let manager = match crate::handlers::auth::utils::authenticate_optional(&state, &_headers).await
+    {
+        Ok(Some(auth)) if auth.has_permission(Permission::ManageUsers) => Some(auth),
+        _ => None,
+    };
+    let is_admin_request = manager.is_some();
 
-    // Check if this is an authenticated admin creating the user
-    let is_admin_request =
-        match crate::handlers::auth::utils::authenticate_optional(&state, &_headers).await {
-            Ok(Some(auth)) => auth.scopes.iter().any(|s| s == "admin"),
-            _ => false,
-        };
+    if !is_admin_request && !state.features().is_enabled("registration_enabled") {
+        return Err(ApiError::forbidden("registration is disabled"));
+    }
 
     // Build UsersRow while stripping fields that should not be client-supplied.
     let now = Utc::now().to_rfc3339();
@@ -43,9 +57,9 @@ pub async fn create(
         .map_err(|e| ApiError::Unexpected(format!("Hashing failed: {}", e)))?;
 
     // Determine roles for the new user
-    let roles: Vec<String> = if is_admin_request {
-        // Admin can set roles via dto.roles
-        dto.roles.unwrap_or_default()
+    let roles: Vec<String> = if let Some(auth) = &manager {
+        // Admins can set roles via dto.roles, within what they may assign
+        apply_role_change(auth, &[], &dto.roles.unwrap_or_default())?
     } else {
         // Non-admin users start with no roles (awaiting approval)
         vec![]
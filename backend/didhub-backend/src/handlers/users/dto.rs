@@ -1,4 +1,5 @@
 use crate::validation::ValidationIssue;
+use didhub_auth::auth::Role;
 use didhub_db::generated::users::UsersRow;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -29,9 +30,10 @@ pub struct UserPublic {
 impl From<UsersRow> for UserPublic {
     fn from(row: UsersRow) -> Self {
         let roles: Vec<String> = serde_json::from_str(&row.roles).unwrap_or_default();
-        let is_admin = roles.iter().any(|r| r == "admin");
+        let held = didhub_auth::auth::roles_from_names(&roles);
+        let is_admin = held.contains(&Role::Owner) || held.contains(&Role::Admin);
         let is_system = roles.iter().any(|r| r == "system");
-        let is_approved = roles.iter().any(|r| r == "user");
+        let is_approved = !held.is_empty();
 
         Self {
             id: row.id.to_string(),
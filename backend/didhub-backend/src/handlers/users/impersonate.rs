@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use chrono::{Duration, Utc};
+use didhub_auth::auth::{roles_from_names, Permission, Role};
+use didhub_log_client::LogCategory;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use serde_json::{json, Value};
+use sqlx::types::Uuid as SqlxUuid;
+
+use didhub_db::generated::users as db_users;
+
+use crate::handlers::auth::utils::{ensure_permission, get_jwt_secret, require_user_id};
+use crate::{error::ApiError, state::AppState};
+
+const DEFAULT_TTL_MINUTES: i64 = 15;
+const MAX_TTL_MINUTES: i64 = 60;
+
+/// Mint a short-lived token to act as another user. Body (optional): `{ "ttlMinutes": 15 }`.
+///
+/// The token carries the target's roles and an `act` claim naming the admin, so every
+/// request made with it is audited with both identities. Impersonating an admin or owner
+/// requires `manage_admins`, and an impersonation token cannot mint another.
+pub async fn impersonate(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth =
+        crate::handlers::auth::utils::require_permission(&state, &headers, Permission::ManageUsers)
+            .await?;
+    let actor_id = require_user_id(&auth)?;
+    if auth.is_impersonated() {
+        return Err(ApiError::forbidden(
+            "cannot impersonate while impersonating",
+        ));
+    }
+
+    let id_str = path
+        .get("userId")
+        .ok_or_else(|| ApiError::not_found("user id missing"))?
+        .to_string();
+    let id: SqlxUuid =
+        SqlxUuid::parse_str(&id_str).map_err(|_| ApiError::bad_request("invalid uuid"))?;
+    if id == actor_id {
+        return Err(ApiError::bad_request("cannot impersonate yourself"));
+    }
+
+    let ttl_minutes = match body.as_ref().and_then(|b| b.0.get("ttlMinutes")) {
+        None | Some(Value::Null) => DEFAULT_TTL_MINUTES,
+        Some(v) => v
+            .as_i64()
+            .filter(|m| (1..=MAX_TTL_MINUTES).contains(m))
+            .ok_or_else(|| {
+                ApiError::bad_request(format!(
+                    "ttlMinutes must be between 1 and {}",
+                    MAX_TTL_MINUTES
+                ))
+            })?,
+    };
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    let user = db_users::find_by_primary_key(&mut *conn, &id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("user not found"))?;
+    drop(conn);
+
+    let roles: Vec<String> = serde_json::from_str(&user.roles).unwrap_or_default();
+    let held = roles_from_names(&roles);
+    if held.contains(&Role::Owner) || held.contains(&Role::Admin) {
+        ensure_permission(&auth, Permission::ManageAdmins)?;
+    }
+
+    let secret = get_jwt_secret()?;
+    let now = Utc::now();
+    let expires_at = now + Duration::minutes(ttl_minutes);
+    let jti = uuid::Uuid::new_v4().to_string();
+    let claims = json!({
+        "sub": user.id.to_string(),
+        "exp": expires_at.timestamp(),
+        "iat": now.timestamp(),
+        "iat_ms": now.timestamp_millis(),
+        "jti": jti,
+        "scopes": roles,
+        "act": { "sub": actor_id.to_string() },
+    });
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .map_err(|e| ApiError::Unexpected(format!("jwt encode failed: {}", e)))?;
+
+    LogCategory::Audit.log(
+        tracing::Level::WARN,
+        "impersonation token issued",
+        Some(json!({
+            "user_id": user.id,
+            "actor_id": actor_id,
+            "token_id": jti,
+            "expires_at": expires_at.to_rfc3339(),
+        })),
+    );
+
+    Ok(Json(json!({
+        "token": token,
+        "userId": user.id,
+        "actorId": actor_id,
+        "expiresAt": expires_at.to_rfc3339(),
+    })))
+}
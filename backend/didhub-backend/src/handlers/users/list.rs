@@ -5,6 +5,7 @@ use axum::extract::{Extension, Json, Query};
 use serde_json::Value;
 
 use crate::{error::ApiError, handlers::utils::parse_positive_usize, state::AppState};
+use didhub_auth::auth::Role;
 use didhub_db::generated::users as db_users;
 
 /// Helper to check if a user has a specific role
@@ -14,6 +15,15 @@ fn user_has_role(roles_json: &str, role: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// SQL condition matching users holding at least one role.
+fn approved_condition() -> String {
+    let alternatives: Vec<String> = Role::ALL
+        .iter()
+        .map(|r| format!("roles LIKE '%\"{}\"%'", r.as_str()))
+        .collect();
+    format!("({})", alternatives.join(" OR "))
+}
+
 /// List all users with optional filters and pagination
 pub async fn list(
     Extension(state): Extension<Arc<AppState>>,
@@ -79,9 +89,9 @@ pub async fn list(
 
     if let Some(is_approved) = is_approved_filter {
         if is_approved {
-            query.push_str(" AND roles LIKE '%\"user\"%'");
+            query.push_str(&format!(" AND {}", approved_condition()));
         } else {
-            query.push_str(" AND roles NOT LIKE '%\"user\"%'");
+            query.push_str(&format!(" AND NOT {}", approved_condition()));
         }
     }
 
@@ -111,9 +121,11 @@ pub async fn list(
     let users: Vec<Value> = rows
         .into_iter()
         .map(|row| {
-            let is_admin = user_has_role(&row.roles, "admin");
+            let is_admin = user_has_role(&row.roles, "admin") || user_has_role(&row.roles, "owner");
             let is_system = user_has_role(&row.roles, "system");
-            let is_approved = user_has_role(&row.roles, "user");
+            let is_approved = Role::ALL
+                .iter()
+                .any(|r| user_has_role(&row.roles, r.as_str()));
             serde_json::json!({
                 "id": row.id,
                 "username": row.username,
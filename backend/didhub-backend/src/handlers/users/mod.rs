@@ -1,12 +1,16 @@
 pub mod create;
 pub mod delete;
 pub mod get;
+pub mod impersonate;
 pub mod list;
 pub mod me_request_system;
 pub mod own_avatar_delete;
 pub mod own_avatar_set;
+pub mod own_email_get;
+pub mod own_email_set;
 pub mod own_profile_get;
 pub mod own_profile_update;
+pub mod revoke_sessions;
 pub mod update;
 pub mod update_password;
 
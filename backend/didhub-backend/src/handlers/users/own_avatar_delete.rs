@@ -29,6 +29,7 @@ pub async fn own_avatar_delete(
     db_users::update_by_primary_key(&mut *conn, &user_id, &existing)
         .await
         .map_err(ApiError::from)?;
+    crate::upload_references::sync_avatar(&mut conn, &user_id, None).await?;
 
     Ok(Json(serde_json::json!({"avatar": null})))
 }
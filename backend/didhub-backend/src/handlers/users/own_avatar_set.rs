@@ -84,6 +84,7 @@ pub async fn own_avatar_set(
     db_users::update_by_primary_key(&mut *conn, &user_id, &existing)
         .await
         .map_err(ApiError::from)?;
+    crate::upload_references::sync_avatar(&mut conn, &user_id, existing.avatar.as_deref()).await?;
 
     Ok(Json(serde_json::json!({"avatar": existing.avatar})))
 }
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+
+use didhub_db::generated::user_emails as db_emails;
+
+use crate::handlers::auth::utils::{authenticate_required, require_user_id};
+use crate::{error::ApiError, state::AppState};
+
+/// Get the current user's email address, used for password resets.
+pub async fn own_email_get(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let auth = authenticate_required(&state, &headers).await?;
+    let user_id = require_user_id(&auth)?;
+
+    let row = db_emails::find_by_primary_key(&*state.db_pool, &user_id)
+        .await
+        .map_err(ApiError::from)?;
+
+    Ok(Json(json!({ "email": row.map(|r| r.email) })))
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json};
+use axum::http::HeaderMap;
+use chrono::Utc;
+use serde_json::{json, Value};
+
+use didhub_db::generated::user_emails as db_emails;
+
+use crate::handlers::auth::utils::{authenticate_required, require_user_id};
+use crate::mailer::normalize_address;
+use crate::{error::ApiError, state::AppState};
+
+/// Set or (with `null`) remove the current user's email address. Body: `{ "email": "..." }`.
+pub async fn own_email_set(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth = authenticate_required(&state, &headers).await?;
+    let user_id = require_user_id(&auth)?;
+    if auth.is_impersonated() {
+        return Err(ApiError::forbidden(
+            "cannot change email while impersonating",
+        ));
+    }
+
+    let requested = body
+        .as_ref()
+        .and_then(|b| b.0.get("email"))
+        .ok_or_else(|| ApiError::bad_request("missing email"))?;
+
+    let Some(email) = requested.as_str() else {
+        if !requested.is_null() {
+            return Err(ApiError::bad_request("email must be a string or null"));
+        }
+        db_emails::delete_by_primary_key(&*state.db_pool, &user_id)
+            .await
+            .map_err(ApiError::from)?;
+        return Ok(Json(json!({ "email": null })));
+    };
+    let email = normalize_address(email).ok_or_else(|| ApiError::bad_request("invalid email"))?;
+
+    let taken = db_emails::find_first_by_email(&*state.db_pool, &email)
+        .await
+        .map_err(ApiError::from)?
+        .is_some_and(|row| row.user_id != user_id);
+    if taken {
+        return Err(ApiError::bad_request("email already in use"));
+    }
+
+    let now = Utc::now().to_rfc3339();
+    let existing = db_emails::find_by_primary_key(&*state.db_pool, &user_id)
+        .await
+        .map_err(ApiError::from)?;
+    match existing {
+        Some(mut row) => {
+            row.email = email.clone();
+            row.updated_at = now;
+            db_emails::update_by_primary_key(&*state.db_pool, &user_id, &row)
+                .await
+                .map_err(ApiError::from)?;
+        }
+        None => {
+            let row = db_emails::UserEmailsRow {
+                user_id,
+                email: email.clone(),
+                created_at: now.clone(),
+                updated_at: now,
+            };
+            db_emails::insert_user_email(&*state.db_pool, &row)
+                .await
+                .map_err(ApiError::from)?;
+        }
+    }
+
+    Ok(Json(json!({ "email": email })))
+}
@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path};
+use axum::http::HeaderMap;
+use serde_json::{json, Value};
+use sqlx::types::Uuid as SqlxUuid;
+
+use didhub_db::generated::users as db_users;
+
+use crate::{error::ApiError, state::AppState};
+
+/// Revoke every token issued to a user so far and forget their remembered devices,
+/// signing them out everywhere.
+/// Only admin or owner may revoke.
+pub async fn revoke_sessions(
+    Extension(state): Extension<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(path): Path<HashMap<String, String>>,
+) -> Result<Json<Value>, ApiError> {
+    let auth = crate::handlers::auth::utils::authenticate_required(&state, &headers).await?;
+
+    let id_str = path
+        .get("userId")
+        .ok_or_else(|| ApiError::not_found("user id missing"))?
+        .to_string();
+    let id: SqlxUuid =
+        SqlxUuid::parse_str(&id_str).map_err(|_| ApiError::bad_request("invalid uuid"))?;
+
+    crate::handlers::auth::utils::ensure_admin_or_user(&auth, id)?;
+
+    let mut conn = state.db_pool.acquire().await.map_err(ApiError::from)?;
+    db_users::find_by_primary_key(&mut *conn, &id)
+        .await
+        .map_err(ApiError::from)?
+        .ok_or_else(|| ApiError::not_found("user not found"))?;
+
+    state.revocation_store().revoke_user_tokens(id).await?;
+    if let Err(e) = crate::sessions::forget_user_sessions(&state, id).await {
+        tracing::warn!(user_id = %id, error = %e, "failed to delete revoked sessions");
+    }
+    if let Err(e) = crate::device_tokens::forget_user_devices(&state, id).await {
+        tracing::warn!(user_id = %id, error = %e, "failed to forget remembered devices");
+    }
+    tracing::info!(user_id = %id, revoked_by = ?auth.user_id, "revoked all sessions for user");
+
+    Ok(Json(json!({ "revoked": true })))
+}
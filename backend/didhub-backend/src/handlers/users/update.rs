@@ -7,6 +7,7 @@ use sqlx::types::Uuid as SqlxUuid;
 
 use didhub_db::generated::users as db_users;
 
+use crate::handlers::roles::{apply_role_change, ensure_owner_remains};
 use crate::handlers::users::dto::UpdateUserDto;
 use crate::{error::ApiError, state::AppState};
 
@@ -27,7 +28,6 @@ pub async fn update(
     let id: SqlxUuid =
         SqlxUuid::parse_str(&id_str).map_err(|_| ApiError::bad_request("invalid uuid"))?;
 
-    let is_admin = auth.is_admin();
     crate::handlers::auth::utils::ensure_admin_or_user(&auth, id)?;
 
     let payload = body
@@ -53,11 +53,14 @@ pub async fn update(
     if let Some(about) = dto.about_me {
         existing.about_me = Some(about);
     }
-    // Only admins can update roles
-    if is_admin {
-        if let Some(roles) = dto.roles {
-            existing.roles = serde_json::to_string(&roles).unwrap_or_else(|_| "[]".to_string());
-        }
+    // Role changes are checked against the caller's permissions
+    let mut roles_changed = false;
+    if let Some(requested) = dto.roles {
+        let current: Vec<String> = serde_json::from_str(&existing.roles).unwrap_or_default();
+        let roles = apply_role_change(&auth, &current, &requested)?;
+        ensure_owner_remains(&mut *conn, &current, &roles).await?;
+        roles_changed = roles != current;
+        existing.roles = serde_json::to_string(&roles).unwrap_or_else(|_| "[]".to_string());
     }
 
     existing.updated_at = chrono::Utc::now().to_rfc3339();
@@ -69,6 +72,12 @@ pub async fn update(
     if affected == 0 {
         return Err(ApiError::not_found("user not found"));
     }
+    drop(conn);
+
+    // Tokens carry roles as scopes; revoke them so the new roles take effect
+    if roles_changed {
+        state.revocation_store().revoke_user_tokens(id).await?;
+    }
 
     Ok(Json(
         serde_json::to_value(&existing).map_err(ApiError::from)?,
@@ -8,6 +8,7 @@ use sqlx::types::Uuid as SqlxUuid;
 
 use didhub_db::generated::users as db_users;
 
+use crate::password_policy::check_submitted_password;
 use crate::{error::ApiError, state::AppState};
 
 /// Update a user's password. Only admin or owner may update.
@@ -44,6 +45,11 @@ pub async fn update_password(
             return Err(ApiError::bad_request("missing newPasswordHash"));
         };
 
+    let violations = check_submitted_password(&state.password_policy(), &new_pass_hash);
+    if let Some(violation) = violations.first() {
+        return Err(ApiError::bad_request(violation.to_string()));
+    }
+
     let password_hash = didhub_auth::auth::hash_client_password(&new_pass_hash)
         .map_err(|e| ApiError::Unexpected(format!("Hashing failed: {}", e)))?;
 
@@ -34,6 +34,62 @@ pub fn parse_positive_usize(
     }
 }
 
+/// Optional boolean query parameter: `true`/`1` or `false`/`0`.
+pub fn parse_optional_bool(raw: Option<&String>, field: &str) -> Result<Option<bool>, ApiError> {
+    match raw.map(String::as_str) {
+        None => Ok(None),
+        Some("true") | Some("1") => Ok(Some(true)),
+        Some("false") | Some("0") => Ok(Some(false)),
+        Some(_) => Err(ApiError::bad_request(format!(
+            "{field} must be true or false"
+        ))),
+    }
+}
+
+/// Default and maximum page sizes for cursor-paginated listings.
+pub const DEFAULT_CURSOR_LIMIT: usize = 50;
+pub const MAX_CURSOR_LIMIT: usize = 200;
+
+/// Opaque keyset cursor: the sort key and id of the last row of a page.
+pub fn encode_cursor(sort_key: &str, id: &Uuid) -> String {
+    use base64::Engine as _;
+    let raw = json!([sort_key, id]).to_string();
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(raw)
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<(String, Uuid), ApiError> {
+    use base64::Engine as _;
+    let invalid = || ApiError::bad_request("invalid cursor");
+    let raw = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(cursor.trim())
+        .map_err(|_| invalid())?;
+    serde_json::from_slice::<(String, Uuid)>(&raw).map_err(|_| invalid())
+}
+
+/// Requested keyset page: the position to continue after, if any, and the page size.
+pub struct CursorParams {
+    pub after: Option<(String, Uuid)>,
+    pub limit: usize,
+}
+
+/// Cursor pagination parameters, or `None` when the request uses neither `cursor`
+/// nor `limit` and expects the unpaginated response.
+pub fn parse_cursor_params(
+    params: &std::collections::HashMap<String, String>,
+) -> Result<Option<CursorParams>, ApiError> {
+    if !params.contains_key("cursor") && !params.contains_key("limit") {
+        return Ok(None);
+    }
+    let after = params
+        .get("cursor")
+        .filter(|c| !c.is_empty())
+        .map(|c| decode_cursor(c))
+        .transpose()?;
+    let limit = parse_positive_usize(params.get("limit"), DEFAULT_CURSOR_LIMIT, "limit")?
+        .min(MAX_CURSOR_LIMIT);
+    Ok(Some(CursorParams { after, limit }))
+}
+
 /// Check if a user has a specific role by parsing the roles JSON
 pub fn user_has_role(user: &db_users::UsersRow, role: &str) -> bool {
     serde_json::from_str::<Vec<String>>(&user.roles)
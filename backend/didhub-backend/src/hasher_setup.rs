@@ -0,0 +1,66 @@
+use didhub_auth::auth::{set_default_hasher, Argon2Hasher, Pepper};
+use didhub_backend::handlers::instance_settings::helpers::upsert_instance_setting;
+use didhub_db::generated::instance_settings::find_first_by_key;
+
+/// Instance setting holding the Argon2 costs in PHC notation (`m=...,t=...,p=...`).
+const PARAMS_KEY: &str = "auth.argon2_params";
+/// Instance setting holding the target the stored costs were tuned for.
+const TARGET_KEY: &str = "auth.argon2_target_ms";
+
+/// The configured password pepper, if any.
+pub fn pepper_from_config(auth: &didhub_config::AuthConfig) -> Option<Pepper> {
+    auth.password_pepper
+        .as_deref()
+        .map(|secret| Pepper::new(auth.password_pepper_version, secret))
+}
+
+/// Choose the Argon2 costs for new password hashes and install them as the default hasher.
+///
+/// Costs stored by an earlier run are reused as long as they were tuned for the
+/// configured `target_ms`; otherwise the hasher is benchmarked again and the
+/// result is stored. Without a target, stored costs (if any) are kept. The
+/// pepper is applied to new hashes; existing hashes are re-peppered on login.
+pub async fn configure_password_hasher(
+    db_pool: &didhub_db::DbPool,
+    target_ms: Option<u64>,
+    pepper: Option<Pepper>,
+) -> anyhow::Result<()> {
+    let mut conn = db_pool.acquire().await?;
+    let stored = find_first_by_key(conn.as_mut(), &PARAMS_KEY.to_string())
+        .await?
+        .and_then(|row| row.value_string)
+        .and_then(|params| match Argon2Hasher::parse_params(&params) {
+            Ok(hasher) => Some(hasher),
+            Err(_) => {
+                tracing::warn!(%params, "ignoring invalid stored Argon2 parameters");
+                None
+            }
+        });
+    let stored_target = find_first_by_key(conn.as_mut(), &TARGET_KEY.to_string())
+        .await?
+        .and_then(|row| row.value_number)
+        .map(|ms| ms as u64);
+
+    let hasher = match (target_ms, stored) {
+        (Some(target), Some(hasher)) if stored_target == Some(target) => hasher,
+        (Some(target), _) => {
+            let hasher = tokio::task::spawn_blocking(move || Argon2Hasher::tune(target)).await?;
+            upsert_instance_setting(&mut conn, PARAMS_KEY, &hasher.params_string()).await?;
+            upsert_instance_setting(&mut conn, TARGET_KEY, &target.to_string()).await?;
+            tracing::info!(target_ms = target, "tuned Argon2 parameters for this host");
+            hasher
+        }
+        (None, Some(hasher)) => hasher,
+        (None, None) => Argon2Hasher::default(),
+    };
+
+    tracing::info!(
+        memory_kib = hasher.memory_cost(),
+        iterations = hasher.time_cost(),
+        parallelism = hasher.parallelism(),
+        pepper_version = ?pepper.as_ref().map(Pepper::version),
+        "password hashing configured"
+    );
+    set_default_hasher(hasher.with_pepper(pepper));
+    Ok(())
+}
@@ -0,0 +1,159 @@
+//! Dependency probes behind `/health?deep=true` and `/ready?deep=true`.
+//!
+//! A deep check pings the database, reads from the cache (Redis when configured),
+//! writes a scratch file to the uploads directory and compares the applied
+//! migrations with this build. Each result also sets the `didhub_dependency_up`
+//! gauge served at `/metrics` next to the `didhub-metrics` registry.
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use didhub_migrations::{Dialect, MigrationState};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::state::AppState;
+
+/// How long a single probe may take before its dependency counts as down.
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of probing one dependency.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyCheck {
+    pub name: &'static str,
+    pub up: bool,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub details: Value,
+}
+
+/// Last deep check result per dependency, exposed as the `didhub_dependency_up`
+/// gauge.
+#[derive(Debug, Default)]
+pub struct DependencyGauge {
+    up: Mutex<BTreeMap<&'static str, bool>>,
+}
+
+impl DependencyGauge {
+    pub fn record(&self, checks: &[DependencyCheck]) {
+        let mut up = self.up.lock().unwrap_or_else(|e| e.into_inner());
+        for check in checks {
+            up.insert(check.name, check.up);
+        }
+    }
+
+    /// The gauge in the Prometheus text format. Dependencies appear once a deep
+    /// check has run.
+    pub fn render(&self) -> String {
+        let up = self.up.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::from(
+            "# HELP didhub_dependency_up Whether the dependency passed the last deep health check.\n\
+             # TYPE didhub_dependency_up gauge\n",
+        );
+        for (name, up) in up.iter() {
+            out.push_str(&format!(
+                "didhub_dependency_up{{dependency=\"{name}\"}} {}\n",
+                u8::from(*up)
+            ));
+        }
+        out
+    }
+}
+
+/// Time `check`, giving up after [`PROBE_TIMEOUT`].
+async fn probe<F>(name: &'static str, check: F) -> DependencyCheck
+where
+    F: Future<Output = Result<Value, String>>,
+{
+    let started = Instant::now();
+    let result = tokio::time::timeout(PROBE_TIMEOUT, check)
+        .await
+        .unwrap_or_else(|_| Err(format!("timed out after {}s", PROBE_TIMEOUT.as_secs())));
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+    match result {
+        Ok(details) => DependencyCheck {
+            name,
+            up: true,
+            latency_ms,
+            error: None,
+            details,
+        },
+        Err(error) => DependencyCheck {
+            name,
+            up: false,
+            latency_ms,
+            error: Some(error),
+            details: Value::Null,
+        },
+    }
+}
+
+async fn database(state: &AppState) -> Result<Value, String> {
+    let mut conn = state.db_pool.acquire().await.map_err(|e| e.to_string())?;
+    sqlx::query("SELECT 1")
+        .execute(&mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(Value::Null)
+}
+
+async fn cache(state: &AppState) -> Result<Value, String> {
+    state
+        .cache
+        .exists("health", "probe")
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(json!({ "backend": state.cache.backend_name() }))
+}
+
+async fn uploads() -> Result<Value, String> {
+    let directory = didhub_config::load_config::<&Path>(None)
+        .unwrap_or_default()
+        .uploads
+        .directory;
+    let dir = directory.clone();
+    // The scratch file is removed when dropped
+    tokio::task::spawn_blocking(move || tempfile::NamedTempFile::new_in(&dir).map(drop))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("{directory} is not writable: {e}"))?;
+    Ok(json!({ "directory": directory }))
+}
+
+async fn migrations(state: &AppState) -> Result<Value, String> {
+    let dialect = Dialect::of::<didhub_db::DbBackend>();
+    let mut conn = state.db_pool.acquire().await.map_err(|e| e.to_string())?;
+    let statuses = didhub_migrations::status(dialect.migrator(), &mut *conn)
+        .await
+        .map_err(|e| e.to_string())?;
+    let count = |state: MigrationState| statuses.iter().filter(|s| s.state == state).count();
+    let (pending, modified) = (
+        count(MigrationState::Pending),
+        count(MigrationState::Modified),
+    );
+    if pending > 0 || modified > 0 {
+        return Err(format!(
+            "{pending} pending and {modified} modified migrations"
+        ));
+    }
+    Ok(json!({ "applied": count(MigrationState::Applied) }))
+}
+
+/// Probe every dependency concurrently and record the results in the gauge.
+pub async fn check_dependencies(state: &AppState) -> Vec<DependencyCheck> {
+    let (database, cache, uploads, migrations) = tokio::join!(
+        probe("database", database(state)),
+        probe("cache", cache(state)),
+        probe("uploads", uploads()),
+        probe("migrations", migrations(state)),
+    );
+    let checks = vec![database, cache, uploads, migrations];
+    state.dependencies().record(&checks);
+    checks
+}
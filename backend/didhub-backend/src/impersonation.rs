@@ -0,0 +1,80 @@
+use axum::body::Body;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::Response;
+use didhub_log_client::LogCategory;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::handlers::auth::utils::{authenticate_optional, extract_auth_token};
+use crate::state::AppState;
+
+#[derive(Deserialize)]
+struct ActClaim {
+    act: Option<serde_json::Value>,
+}
+
+/// Whether `token` is a JWT carrying an `act` claim. The signature is not checked;
+/// this only decides whether the token needs to be authenticated for the audit.
+fn has_act_claim(token: &str) -> bool {
+    jsonwebtoken::dangerous::insecure_decode::<ActClaim>(token)
+        .is_ok_and(|data| data.claims.act.is_some())
+}
+
+/// Middleware that writes an audit entry, naming both the impersonated user and the
+/// acting admin, for every request made with an impersonation token.
+///
+/// Other requests are passed through after peeking at the token's claims, so only
+/// impersonated requests are authenticated here as well as in their handler.
+pub async fn audit_impersonated_requests(req: Request<Body>, next: Next) -> Response {
+    if !extract_auth_token(req.headers()).is_some_and(|token| has_act_claim(&token)) {
+        return next.run(req).await;
+    }
+    let Some(state) = req.extensions().get::<Arc<AppState>>().cloned() else {
+        return next.run(req).await;
+    };
+    let auth = match authenticate_optional(&state, req.headers()).await {
+        Ok(Some(auth)) if auth.is_impersonated() => auth,
+        _ => return next.run(req).await,
+    };
+
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    let response = next.run(req).await;
+
+    LogCategory::Audit.log(
+        tracing::Level::INFO,
+        &format!("{} {} (impersonated)", method, path),
+        Some(json!({
+            "user_id": auth.user_id,
+            "actor_id": auth.actor,
+            "token_id": auth.token_id,
+            "status": response.status().as_u16(),
+        })),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(claims: serde_json::Value) -> String {
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn only_tokens_with_an_act_claim_are_inspected() {
+        assert!(has_act_claim(&token(
+            json!({"sub": "u", "exp": 1, "act": {"sub": "admin"}})
+        )));
+        assert!(!has_act_claim(&token(json!({"sub": "u", "exp": 1}))));
+        assert!(!has_act_claim("dhk_abcdef_0123456789"));
+    }
+}
@@ -0,0 +1,518 @@
+//! Import of PluralKit and Simply Plural exports into a system.
+//!
+//! An export is parsed into members and groups, which become alters and
+//! affiliations of the target system; group membership becomes affiliation
+//! membership. Neither format carries relationships between members. A name that
+//! clashes with an alter or affiliation of the system, or with an earlier entry of
+//! the same export, is resolved with the [`ConflictStrategy`]. [`plan`] works out
+//! what an import would do without writing anything, which is also the dry-run
+//! preview; [`apply`] writes a plan in one transaction.
+
+pub mod pluralkit;
+pub mod simply_plural;
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use didhub_db::custom::alters::AlterFilter;
+use didhub_db::generated::affiliations as db_affiliations;
+use didhub_db::generated::alters as db_alters;
+use didhub_db::DbPoolConnection;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// The application an export comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+    PluralKit,
+    SimplyPlural,
+}
+
+impl ImportSource {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pluralkit" => Some(Self::PluralKit),
+            "simplyplural" => Some(Self::SimplyPlural),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::PluralKit => "pluralkit",
+            Self::SimplyPlural => "simplyplural",
+        }
+    }
+
+    /// Members and groups of the export `data`.
+    pub fn parse_export(self, data: &Value) -> Result<ImportedSystem, ApiError> {
+        match self {
+            Self::PluralKit => pluralkit::parse(data),
+            Self::SimplyPlural => simply_plural::parse(data),
+        }
+    }
+}
+
+/// What to do with an imported member or group whose name is already taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStrategy {
+    /// Leave the existing entity alone and drop the imported one.
+    Skip,
+    /// Fill the existing entity's empty fields from the imported one.
+    Merge,
+    /// Import it under the first free name of the form `Name (2)`.
+    Rename,
+}
+
+impl ConflictStrategy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "skip" => Some(Self::Skip),
+            "merge" => Some(Self::Merge),
+            "rename" => Some(Self::Rename),
+            _ => None,
+        }
+    }
+}
+
+/// Members and groups read from an export.
+#[derive(Debug, Default)]
+pub struct ImportedSystem {
+    pub members: Vec<ImportedMember>,
+    pub groups: Vec<ImportedGroup>,
+    /// Parts of the export that are not imported.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct ImportedMember {
+    /// Id of the member in the source application.
+    pub source_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub pronouns: Option<String>,
+    /// `YYYY-MM-DD`, or `MM-DD` when the year is unknown.
+    pub birthday: Option<String>,
+    /// Fields without an alter column, kept in the alter's metadata.
+    pub metadata: Map<String, Value>,
+}
+
+#[derive(Debug)]
+pub struct ImportedGroup {
+    pub source_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Source ids of the members.
+    pub members: Vec<String>,
+}
+
+/// Trimmed, non-empty string at `key` of `value`.
+fn optional_str(value: &Value, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+/// What an import does with one member or group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Create,
+    Rename,
+    Merge,
+    Skip,
+}
+
+impl Action {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Rename => "rename",
+            Self::Merge => "merge",
+            Self::Skip => "skip",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PlannedEntity {
+    source_id: String,
+    /// Name the entity ends up with.
+    name: String,
+    action: Action,
+    /// The alter or affiliation it becomes or merges into; `None` when skipped.
+    id: Option<Uuid>,
+}
+
+/// Names taken in a system, lowercased, and the entity holding each.
+struct Names(HashMap<String, Uuid>);
+
+impl Names {
+    /// Action and final name for an imported entity called `name`, with the entity
+    /// it clashes with.
+    fn resolve(&self, name: &str, strategy: ConflictStrategy) -> (Action, String, Option<Uuid>) {
+        let Some(existing) = self.0.get(&name.to_lowercase()) else {
+            return (Action::Create, name.to_string(), None);
+        };
+        match strategy {
+            ConflictStrategy::Skip => (Action::Skip, name.to_string(), Some(*existing)),
+            ConflictStrategy::Merge => (Action::Merge, name.to_string(), Some(*existing)),
+            ConflictStrategy::Rename => {
+                let renamed = (2..)
+                    .map(|n| format!("{name} ({n})"))
+                    .find(|candidate| !self.0.contains_key(&candidate.to_lowercase()))
+                    .expect("a free name");
+                (Action::Rename, renamed, None)
+            }
+        }
+    }
+
+    fn take(&mut self, name: &str, id: Uuid) {
+        self.0.insert(name.to_lowercase(), id);
+    }
+}
+
+/// Set `field` to `value` if it is empty.
+fn fill(field: &mut Option<String>, value: &Option<String>) {
+    if field.as_deref().is_none_or(str::is_empty) && value.is_some() {
+        field.clone_from(value);
+    }
+}
+
+/// The writes an import makes to a system.
+#[derive(Debug, Clone)]
+pub struct ImportPlan {
+    pub system_id: Uuid,
+    pub source: ImportSource,
+    alters: Vec<PlannedEntity>,
+    affiliations: Vec<PlannedEntity>,
+    new_alters: Vec<db_alters::AltersRow>,
+    /// Merged alters before and after the import.
+    merged_alters: Vec<(db_alters::AltersRow, db_alters::AltersRow)>,
+    new_affiliations: Vec<db_affiliations::AffiliationsRow>,
+    merged_affiliations: Vec<(
+        db_affiliations::AffiliationsRow,
+        db_affiliations::AffiliationsRow,
+    )>,
+    /// New affiliation memberships as (affiliation, alter).
+    memberships: Vec<(Uuid, Uuid)>,
+    pub warnings: Vec<String>,
+}
+
+/// Work out how `imported` maps onto the system `system_id`.
+pub async fn plan(
+    conn: &mut DbPoolConnection,
+    system_id: Uuid,
+    source: ImportSource,
+    imported: ImportedSystem,
+    strategy: ConflictStrategy,
+) -> Result<ImportPlan, ApiError> {
+    let now = Utc::now().to_rfc3339();
+    let mut warnings = imported.warnings;
+
+    let mut existing_alters: HashMap<Uuid, db_alters::AltersRow> =
+        didhub_db::custom::alters::list_active(&mut **conn, &AlterFilter::system(&system_id))
+            .await?
+            .into_iter()
+            .map(|row| (row.id, row))
+            .collect();
+    let mut names = Names(
+        existing_alters
+            .values()
+            .map(|row| (row.name.to_lowercase(), row.id))
+            .collect(),
+    );
+    let mut alters = Vec::new();
+    let mut new_alters = Vec::new();
+    let mut merged_alters: Vec<(db_alters::AltersRow, db_alters::AltersRow)> = Vec::new();
+    for member in &imported.members {
+        let (action, name, clash) = names.resolve(&member.name, strategy);
+        let id = match action {
+            Action::Skip => None,
+            Action::Merge => {
+                let id = clash.expect("merge has a clash");
+                // Only alters already in the system are changed; an earlier member of
+                // the same export keeps its own fields
+                if let Some(row) = existing_alters.get_mut(&id) {
+                    if !merged_alters.iter().any(|(before, _)| before.id == id) {
+                        merged_alters.push((row.clone(), row.clone()));
+                    }
+                    fill(&mut row.description, &member.description);
+                    fill(&mut row.pronouns, &member.pronouns);
+                    fill(&mut row.birthday, &member.birthday);
+                }
+                Some(id)
+            }
+            Action::Create | Action::Rename => {
+                let id = Uuid::new_v4();
+                names.take(&name, id);
+                let mut metadata = member.metadata.clone();
+                metadata.insert(
+                    "import".to_string(),
+                    json!({ "source": source.as_str(), "id": member.source_id }),
+                );
+                new_alters.push(db_alters::AltersRow {
+                    id,
+                    user_id: system_id,
+                    name: name.clone(),
+                    surname: None,
+                    description: member.description.clone(),
+                    age: None,
+                    gender: None,
+                    pronouns: member.pronouns.clone(),
+                    birthday: member.birthday.clone(),
+                    sexuality: None,
+                    species: None,
+                    alter_type: None,
+                    job: None,
+                    weapon: None,
+                    triggers: "[]".to_string(),
+                    metadata: Value::Object(metadata).to_string(),
+                    soul_songs: "[]".to_string(),
+                    interests: "[]".to_string(),
+                    notes: None,
+                    images: "[]".to_string(),
+                    system_roles: "[]".to_string(),
+                    is_system_host: 0,
+                    is_dormant: 0,
+                    is_merged: 0,
+                    owner_user_id: system_id,
+                    created_at: now.clone(),
+                });
+                Some(id)
+            }
+        };
+        alters.push(PlannedEntity {
+            source_id: member.source_id.clone(),
+            name,
+            action,
+            id,
+        });
+    }
+    for (before, after) in &mut merged_alters {
+        *after = existing_alters[&before.id].clone();
+    }
+
+    let mut existing_affiliations: HashMap<Uuid, db_affiliations::AffiliationsRow> =
+        didhub_db::custom::affiliations::list_active_for_owner(&mut **conn, &system_id)
+            .await?
+            .into_iter()
+            .map(|row| (row.id, row))
+            .collect();
+    let mut names = Names(
+        existing_affiliations
+            .values()
+            .map(|row| (row.name.to_lowercase(), row.id))
+            .collect(),
+    );
+    let alter_ids: HashMap<&str, Option<Uuid>> = alters
+        .iter()
+        .map(|alter| (alter.source_id.as_str(), alter.id))
+        .collect();
+    let mut affiliations = Vec::new();
+    let mut new_affiliations = Vec::new();
+    let mut merged_affiliations: Vec<(
+        db_affiliations::AffiliationsRow,
+        db_affiliations::AffiliationsRow,
+    )> = Vec::new();
+    let mut memberships = Vec::new();
+    for group in &imported.groups {
+        let (action, name, clash) = names.resolve(&group.name, strategy);
+        let id = match action {
+            Action::Skip => None,
+            Action::Merge => {
+                let id = clash.expect("merge has a clash");
+                if let Some(row) = existing_affiliations.get_mut(&id) {
+                    if !merged_affiliations
+                        .iter()
+                        .any(|(before, _)| before.id == id)
+                    {
+                        merged_affiliations.push((row.clone(), row.clone()));
+                    }
+                    fill(&mut row.description, &group.description);
+                }
+                Some(id)
+            }
+            Action::Create | Action::Rename => {
+                let id = Uuid::new_v4();
+                names.take(&name, id);
+                new_affiliations.push(db_affiliations::AffiliationsRow {
+                    id,
+                    name: name.clone(),
+                    description: group.description.clone(),
+                    sigil: None,
+                    owner_user_id: Some(system_id),
+                    created_at: now.clone(),
+                });
+                Some(id)
+            }
+        };
+        if let Some(affiliation_id) = id {
+            for member in &group.members {
+                let Some(alter_id) = alter_ids.get(member.as_str()) else {
+                    warnings.push(format!(
+                        "group {} lists unknown member {member}",
+                        group.name
+                    ));
+                    continue;
+                };
+                let Some(alter_id) = *alter_id else {
+                    continue;
+                };
+                if memberships.contains(&(affiliation_id, alter_id)) {
+                    continue;
+                }
+                let existing =
+                    didhub_db::custom::affiliation_members::find_by_affiliation_id_and_alter_id(
+                        &mut **conn,
+                        &affiliation_id,
+                        &alter_id,
+                    )
+                    .await?;
+                if existing.is_none() {
+                    memberships.push((affiliation_id, alter_id));
+                }
+            }
+        }
+        affiliations.push(PlannedEntity {
+            source_id: group.source_id.clone(),
+            name,
+            action,
+            id,
+        });
+    }
+    for (before, after) in &mut merged_affiliations {
+        *after = existing_affiliations[&before.id].clone();
+    }
+
+    Ok(ImportPlan {
+        system_id,
+        source,
+        alters,
+        affiliations,
+        new_alters,
+        merged_alters,
+        new_affiliations,
+        merged_affiliations,
+        memberships,
+        warnings,
+    })
+}
+
+impl ImportPlan {
+    /// Summary of the plan for the API.
+    pub fn report(&self, dry_run: bool) -> Value {
+        let entity = |entity: &PlannedEntity| {
+            json!({
+                "sourceId": entity.source_id,
+                "name": entity.name,
+                "action": entity.action.as_str(),
+                "id": entity.id,
+            })
+        };
+        let affiliations: Vec<Value> = self
+            .affiliations
+            .iter()
+            .map(|affiliation| {
+                let mut value = entity(affiliation);
+                value["newMembers"] = self
+                    .memberships
+                    .iter()
+                    .filter(|(id, _)| Some(*id) == affiliation.id)
+                    .count()
+                    .into();
+                value
+            })
+            .collect();
+        json!({
+            "dryRun": dry_run,
+            "source": self.source.as_str(),
+            "systemId": self.system_id,
+            "alters": self.alters.iter().map(entity).collect::<Vec<_>>(),
+            "affiliations": affiliations,
+            "memberships": self.memberships.len(),
+            "warnings": self.warnings,
+        })
+    }
+}
+
+/// Write `plan` and record versions and field changes of the merged entities.
+pub async fn apply(
+    state: &AppState,
+    plan: &ImportPlan,
+    actor_user_id: Option<Uuid>,
+) -> Result<(), ApiError> {
+    didhub_db::transaction(&state.db_pool, |tx| {
+        let plan = plan.clone();
+        Box::pin(async move {
+            for row in &plan.new_alters {
+                db_alters::insert_alter(&mut **tx, row).await?;
+            }
+            for (_, row) in &plan.merged_alters {
+                db_alters::update_by_primary_key(&mut **tx, &row.id, row).await?;
+            }
+            for row in &plan.new_affiliations {
+                db_affiliations::insert_affiliation(&mut **tx, row).await?;
+            }
+            for (_, row) in &plan.merged_affiliations {
+                db_affiliations::update_by_primary_key(&mut **tx, &row.id, row).await?;
+            }
+            let now = Utc::now().to_rfc3339();
+            for (affiliation_id, alter_id) in &plan.memberships {
+                sqlx::query(
+                    "INSERT INTO affiliation_members (affiliation_id, alter_id, is_leader, added_at) VALUES (?, ?, 0, ?)",
+                )
+                .bind(affiliation_id)
+                .bind(alter_id)
+                .bind(&now)
+                .execute(&mut **tx)
+                .await?;
+            }
+            Ok::<_, ApiError>(())
+        })
+    })
+    .await?;
+
+    for (before, after) in &plan.merged_alters {
+        record_merge(state, "alter", before.id, actor_user_id, before, after).await?;
+    }
+    for (before, after) in &plan.merged_affiliations {
+        record_merge(
+            state,
+            "affiliation",
+            before.id,
+            actor_user_id,
+            before,
+            after,
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+async fn record_merge<R: serde::Serialize>(
+    state: &AppState,
+    entity_type: &str,
+    id: Uuid,
+    actor_user_id: Option<Uuid>,
+    before: &R,
+    after: &R,
+) -> Result<(), ApiError> {
+    let before = serde_json::to_value(before)?;
+    let after = serde_json::to_value(after)?;
+    if before != after {
+        state
+            .record_version(entity_type, id, "update", actor_user_id, &before)
+            .await;
+        state
+            .audit_change(entity_type, id, actor_user_id, &before, &after)
+            .await;
+    }
+    Ok(())
+}
@@ -0,0 +1,98 @@
+//! PluralKit exports (`pk;export`, format version 2).
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use super::{optional_str, ImportedGroup, ImportedMember, ImportedSystem};
+use crate::error::ApiError;
+
+/// PluralKit stores birthdays without a year as year 0004.
+const HIDDEN_YEAR_PREFIX: &str = "0004-";
+
+pub fn parse(data: &Value) -> Result<ImportedSystem, ApiError> {
+    let members = data
+        .get("members")
+        .and_then(Value::as_array)
+        .ok_or_else(|| ApiError::bad_request("PluralKit export has no members array"))?;
+
+    // Groups may list members by short id or by uuid
+    let mut ids_by_uuid = HashMap::new();
+    let mut imported = ImportedSystem::default();
+    for (index, member) in members.iter().enumerate() {
+        let id = optional_str(member, "id")
+            .ok_or_else(|| ApiError::bad_request(format!("member {index} has no id")))?;
+        let name = optional_str(member, "name")
+            .ok_or_else(|| ApiError::bad_request(format!("member {id} has no name")))?;
+        if let Some(uuid) = optional_str(member, "uuid") {
+            ids_by_uuid.insert(uuid, id.clone());
+        }
+
+        let mut metadata = Map::new();
+        for (from, to) in [
+            ("display_name", "displayName"),
+            ("color", "color"),
+            ("avatar_url", "avatarUrl"),
+        ] {
+            if let Some(value) = optional_str(member, from) {
+                metadata.insert(to.to_string(), Value::String(value));
+            }
+        }
+        imported.members.push(ImportedMember {
+            source_id: id,
+            name,
+            description: optional_str(member, "description"),
+            pronouns: optional_str(member, "pronouns"),
+            birthday: optional_str(member, "birthday").map(|birthday| {
+                birthday
+                    .strip_prefix(HIDDEN_YEAR_PREFIX)
+                    .map(str::to_string)
+                    .unwrap_or(birthday)
+            }),
+            metadata,
+        });
+    }
+
+    for (index, group) in data
+        .get("groups")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let id = optional_str(group, "id")
+            .ok_or_else(|| ApiError::bad_request(format!("group {index} has no id")))?;
+        let name = optional_str(group, "name")
+            .ok_or_else(|| ApiError::bad_request(format!("group {id} has no name")))?;
+        let members = group
+            .get("members")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(|member| {
+                ids_by_uuid
+                    .get(member)
+                    .cloned()
+                    .unwrap_or_else(|| member.to_string())
+            })
+            .collect();
+        imported.groups.push(ImportedGroup {
+            source_id: id,
+            name,
+            description: optional_str(group, "description"),
+            members,
+        });
+    }
+
+    if data
+        .get("switches")
+        .and_then(Value::as_array)
+        .is_some_and(|s| !s.is_empty())
+    {
+        imported
+            .warnings
+            .push("switch history is not imported".to_string());
+    }
+    Ok(imported)
+}
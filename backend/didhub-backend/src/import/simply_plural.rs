@@ -0,0 +1,90 @@
+//! Simply Plural exports. Entries may be plain documents or wrapped as
+//! `{ "id", "content": { ... } }` like the Simply Plural API returns them.
+
+use serde_json::{Map, Value};
+
+use super::{optional_str, ImportedGroup, ImportedMember, ImportedSystem};
+use crate::error::ApiError;
+
+/// The id and fields of an entry.
+fn entry(value: &Value) -> (Option<String>, &Value) {
+    let content = value
+        .get("content")
+        .filter(|c| c.is_object())
+        .unwrap_or(value);
+    let id = optional_str(value, "id")
+        .or_else(|| optional_str(value, "_id"))
+        .or_else(|| optional_str(content, "id"))
+        .or_else(|| optional_str(content, "_id"));
+    (id, content)
+}
+
+pub fn parse(data: &Value) -> Result<ImportedSystem, ApiError> {
+    let members = data
+        .get("members")
+        .and_then(Value::as_array)
+        .ok_or_else(|| ApiError::bad_request("Simply Plural export has no members array"))?;
+
+    let mut imported = ImportedSystem::default();
+    for (index, member) in members.iter().enumerate() {
+        let (id, member) = entry(member);
+        let id = id.ok_or_else(|| ApiError::bad_request(format!("member {index} has no id")))?;
+        let name = optional_str(member, "name")
+            .ok_or_else(|| ApiError::bad_request(format!("member {id} has no name")))?;
+
+        let mut metadata = Map::new();
+        for (from, to) in [
+            ("color", "color"),
+            ("avatarUrl", "avatarUrl"),
+            ("pkId", "pluralKitId"),
+        ] {
+            if let Some(value) = optional_str(member, from) {
+                metadata.insert(to.to_string(), Value::String(value));
+            }
+        }
+        if member
+            .get("info")
+            .and_then(Value::as_object)
+            .is_some_and(|info| !info.is_empty())
+        {
+            imported
+                .warnings
+                .push(format!("custom field values of {name} are not imported"));
+        }
+        imported.members.push(ImportedMember {
+            source_id: id,
+            name,
+            description: optional_str(member, "desc"),
+            pronouns: optional_str(member, "pronouns"),
+            birthday: None,
+            metadata,
+        });
+    }
+
+    for (index, group) in data
+        .get("groups")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+    {
+        let (id, group) = entry(group);
+        let id = id.ok_or_else(|| ApiError::bad_request(format!("group {index} has no id")))?;
+        let name = optional_str(group, "name")
+            .ok_or_else(|| ApiError::bad_request(format!("group {id} has no name")))?;
+        imported.groups.push(ImportedGroup {
+            source_id: id,
+            name,
+            description: optional_str(group, "desc"),
+            members: group
+                .get("members")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect(),
+        });
+    }
+    Ok(imported)
+}
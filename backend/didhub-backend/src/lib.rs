@@ -1,12 +1,34 @@
+pub mod api_keys;
 pub mod app;
+pub mod audit;
+pub mod birthdays;
+pub mod cache_invalidation;
+pub mod cache_warmup;
 pub mod csrf;
+pub mod device_authorization;
+pub mod device_tokens;
 pub mod embedded_assets;
 pub mod error;
+pub mod export;
 pub mod generated;
 pub mod handlers;
+pub mod health;
+pub mod impersonation;
+pub mod import;
+pub mod mailer;
+pub mod metrics_access;
+pub mod password_policy;
+pub mod password_reset;
+pub mod permissions;
 pub mod rate_limiter;
+pub mod revocation;
+pub mod service_clients;
+pub mod sessions;
 pub mod state;
+pub mod system_access;
 pub mod tracing_setup;
+pub mod trash;
+pub mod upload_references;
 pub mod validation;
 
 pub use app::build_router;
@@ -0,0 +1,124 @@
+//! Outgoing email over SMTP.
+//!
+//! Handlers send through the [`Mailer`] held by [`AppState`](crate::state::AppState),
+//! which is `None` while `smtp.host` is unset, so features that need email can
+//! report that they are unavailable.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use didhub_config::SmtpConfig;
+use lettre::message::{header::ContentType, Mailbox};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MailError {
+    #[error("invalid address {0}")]
+    InvalidAddress(String),
+    #[error("invalid smtp configuration: {0}")]
+    Config(String),
+    #[error("failed to build message: {0}")]
+    Message(String),
+    #[error("failed to send message: {0}")]
+    Transport(String),
+}
+
+/// A plain-text email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingEmail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, email: OutgoingEmail) -> Result<(), MailError>;
+}
+
+/// [`Mailer`] delivering through an SMTP relay.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    /// Build a mailer from configuration, or `None` when no relay is configured.
+    pub fn from_config(cfg: &SmtpConfig) -> Result<Option<Self>, MailError> {
+        let Some(host) = cfg.host.as_deref().filter(|h| !h.is_empty()) else {
+            return Ok(None);
+        };
+        let from_address = cfg
+            .from_address
+            .as_deref()
+            .ok_or_else(|| MailError::Config("smtp.from_address is not set".into()))?;
+        let from: Mailbox = from_address
+            .parse()
+            .map_err(|_| MailError::InvalidAddress(from_address.to_string()))?;
+
+        let builder = match cfg.tls.as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(host),
+            "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host),
+            "none" => Ok(AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(
+                host,
+            )),
+            other => return Err(MailError::Config(format!("unknown tls mode {}", other))),
+        }
+        .map_err(|e| MailError::Config(e.to_string()))?;
+        let mut builder = builder.port(cfg.port);
+        if let (Some(user), Some(password)) = (&cfg.username, &cfg.password) {
+            builder = builder.credentials(Credentials::new(user.clone(), password.clone()));
+        }
+
+        Ok(Some(Self {
+            transport: builder.build(),
+            from,
+        }))
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, email: OutgoingEmail) -> Result<(), MailError> {
+        let to: Mailbox = email
+            .to
+            .parse()
+            .map_err(|_| MailError::InvalidAddress(email.to.clone()))?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(email.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(email.body)
+            .map_err(|e| MailError::Message(e.to_string()))?;
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| MailError::Transport(e.to_string()))?;
+        Ok(())
+    }
+}
+
+/// The configured mailer, or `None` if SMTP is unset or its configuration is unusable.
+pub fn mailer_from_config(cfg: &SmtpConfig) -> Option<Arc<dyn Mailer>> {
+    match SmtpMailer::from_config(cfg) {
+        Ok(mailer) => mailer.map(|m| Arc::new(m) as Arc<dyn Mailer>),
+        Err(e) => {
+            tracing::error!(%e, "failed to configure smtp; outgoing email disabled");
+            None
+        }
+    }
+}
+
+/// Trimmed, lowercased form of an email address, or `None` if it is not one.
+pub fn normalize_address(address: &str) -> Option<String> {
+    let address = address.trim().to_ascii_lowercase();
+    let valid = address.len() <= 254
+        && address.parse::<lettre::Address>().is_ok()
+        && address
+            .rsplit_once('@')
+            .is_some_and(|(_, domain)| domain.contains('.'));
+    valid.then_some(address)
+}
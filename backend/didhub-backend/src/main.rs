@@ -3,30 +3,63 @@
 //! Entry point for the didhub-backend server with configuration loading,
 //! database migrations, and HTTP server startup.
 
+use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{http::StatusCode, Router};
+use didhub_auth::auth::{ApiKeyStore, RevocationStore};
+use didhub_cache::{AppCache, Codec, NamespacePolicy};
 use didhub_job_queue::JobQueueClient;
-use didhub_updates::UpdateCoordinator;
+use didhub_jobs::{BackupCreateExecutor, BackupRestoreExecutor, BackupSettings};
+use didhub_updates::{UpdateChannel, UpdateCoordinator};
 use tokio::net::TcpListener;
 
+use didhub_backend::api_keys::DbApiKeyStore;
+use didhub_backend::audit::{AuditRetention, AuditRetentionExecutor};
+use didhub_backend::birthdays::{BirthdayDigestExecutor, BirthdaySettings};
+use didhub_backend::cache_invalidation;
+use didhub_backend::cache_warmup;
+use didhub_backend::export::SystemExportExecutor;
+use didhub_backend::mailer::mailer_from_config;
+use didhub_backend::metrics_access::MetricsAccess;
+use didhub_backend::password_policy::policy_from_config;
+use didhub_backend::password_reset::{ExpiredResetTokensExecutor, PasswordResetSettings};
 use didhub_backend::rate_limiter::RateLimiterManager;
+use didhub_backend::revocation::DbRevocationStore;
 use didhub_backend::state::AppState;
+use didhub_backend::trash::TrashPurgeExecutor;
+use didhub_backend::upload_references::UploadsGcExecutor;
 
 mod auth_builder;
 mod bootstrap;
 mod cli;
 mod config_helpers;
 mod config_reloader;
+mod hasher_setup;
+mod scheduler_setup;
+mod tls;
 mod tracing_setup;
 
 use auth_builder::build_authenticator_from_config;
-use bootstrap::maybe_provision_admin;
-use cli::CliArgs;
+use axum_server::tls_rustls::RustlsConfig;
+use bootstrap::{maybe_promote_owner, maybe_provision_admin, seed_database};
+use cli::{CliArgs, Command, ConfigOverrides, MigrateAction};
 use config_helpers::{
     database_config_from_config, parse_bind_address, service_unavailable_handler,
 };
-use tracing_setup::install_tracing_from_config;
+use config_reloader::ConfigReloadContext;
+use hasher_setup::{configure_password_hasher, pepper_from_config};
+use scheduler_setup::start_scheduler;
+use tls::build_rustls_config;
+use tracing_setup::{install_tracing_from_config, shutdown_tracer_provider};
+
+/// How often the database pool gauges are refreshed.
+const POOL_SAMPLE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// How long TLS connections may take to finish after a shutdown signal.
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -39,8 +72,32 @@ async fn main() -> anyhow::Result<()> {
         .clone()
         .or_else(|| std::env::var("DIDHUB_CONFIG_PATH").ok());
 
-    eprintln!("[STARTUP] Loading config from: {:?}", config_path);
-    let config = load_config(&config_path)?;
+    // Overlays: CLI > environment variable
+    let config_overlays = if args.config_overlays.is_empty() {
+        std::env::var("DIDHUB_CONFIG_OVERLAYS")
+            .map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        args.config_overlays.clone()
+    };
+    let config_paths: Vec<String> = config_path
+        .iter()
+        .chain(config_overlays.iter())
+        .cloned()
+        .collect();
+
+    if args.print_config {
+        return print_config(&config_paths, &args.overrides);
+    }
+
+    eprintln!("[STARTUP] Loading config from: {:?}", config_paths);
+    let config = load_config(&config_paths, &args.overrides)?;
     eprintln!("[STARTUP] Config loaded successfully");
 
     // Propagate config path to environment for downstream code
@@ -51,13 +108,90 @@ async fn main() -> anyhow::Result<()> {
 
     // Initialize tracing
     eprintln!("[STARTUP] Initializing tracing...");
-    let reload_handle = install_tracing_from_config(&config.logging, args.log_level.as_deref());
+    let (reload_handle, tracer_provider) = install_tracing_from_config(
+        &config.logging,
+        &config.otlp,
+        args.overrides.log_level.as_deref(),
+    );
     eprintln!("[STARTUP] Tracing initialized");
 
+    if let Err(e) = didhub_metrics::register_runtime_metrics(tokio::runtime::Handle::current()) {
+        tracing::warn!(error = %e, "failed to register runtime metrics");
+    }
+    if let Err(e) =
+        didhub_metrics::init_http_metrics(config.metrics.http_request_duration_buckets.clone())
+    {
+        tracing::warn!(error = %e, "failed to register request metrics");
+    }
+    if let Err(e) =
+        didhub_metrics::init_db_metrics(config.metrics.db_query_duration_buckets.clone())
+    {
+        tracing::warn!(error = %e, "failed to register query metrics");
+    }
+    if let Err(e) = didhub_metrics::register_build_info(
+        env!("CARGO_PKG_VERSION"),
+        option_env!("DIDHUB_GIT_COMMIT").unwrap_or("unknown"),
+        &format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+    ) {
+        tracing::warn!(error = %e, "failed to register build info");
+    }
+    didhub_metrics::set_feature_enabled("updater", config.auto_update.enabled);
+    didhub_metrics::set_feature_enabled(
+        "embedded_frontend",
+        didhub_backend::embedded_assets::EmbeddedAssets::get("index.html").is_some(),
+    );
+
+    for deprecation in didhub_config::deprecated_env_in_use() {
+        tracing::warn!(
+            deprecated = deprecation.name,
+            replacement = deprecation.replacement,
+            "deprecated environment variable in use; rename it"
+        );
+    }
+
+    if let Some(Command::Migrate { action }) = args.command {
+        let db_cfg = database_config_from_config(&config);
+        let db_pool = didhub_db::create_pool(&db_cfg).await?;
+        let result = migrate_command(&db_cfg, &db_pool, action).await;
+        // A one-off command is never scraped, so push what it recorded once
+        if let Some(gateway) = config.metrics.push_gateway_url.clone() {
+            let job = config.metrics.push_job.clone();
+            match tokio::task::spawn_blocking(move || didhub_metrics::push::push(&gateway, &job))
+                .await
+            {
+                Ok(Err(e)) => tracing::warn!(error = %e, "failed to push metrics"),
+                Err(e) => tracing::warn!(error = %e, "metrics push task failed"),
+                Ok(Ok(())) => {}
+            }
+        }
+        shutdown_tracer_provider(tracer_provider);
+        return result;
+    }
+
+    if let Some(gateway) = &config.metrics.push_gateway_url {
+        didhub_metrics::push::spawn_push(
+            gateway.clone(),
+            config.metrics.push_job.clone(),
+            Duration::from_secs(config.metrics.push_interval_seconds),
+        );
+        tracing::info!(gateway = %gateway, "pushing metrics to the Pushgateway");
+    }
+
     // Initialize services
     eprintln!("[STARTUP] Initializing services...");
     let job_queue = JobQueueClient::new();
-    let updates = UpdateCoordinator::new();
+    let mut updates = UpdateCoordinator::new()
+        .with_channel(UpdateChannel::from_name(&config.auto_update.channel).unwrap_or_default())
+        .with_release_server(
+            &config.auto_update.api_url,
+            &config.auto_update.releases_url,
+        );
+    if let Some(repo) = &config.auto_update.repo {
+        updates = updates.with_repository(repo)?;
+    }
+    if let Some(proxy) = &config.auto_update.proxy {
+        updates = updates.with_proxy(proxy)?;
+    }
 
     // Create and migrate database
     eprintln!("[STARTUP] Setting up database...");
@@ -66,6 +200,41 @@ async fn main() -> anyhow::Result<()> {
     eprintln!("[STARTUP] Database pool created");
     run_migrations(&db_cfg, &db_pool).await?;
     eprintln!("[STARTUP] Database migrations completed");
+    let pepper = pepper_from_config(&config.auth);
+    if let Err(e) = configure_password_hasher(
+        &db_pool,
+        config.auth.password_hash_target_ms,
+        pepper.clone(),
+    )
+    .await
+    {
+        tracing::error!(%e, "failed to configure password hashing; using default Argon2 parameters");
+        didhub_auth::auth::set_default_hasher(
+            didhub_auth::auth::Argon2Hasher::default().with_pepper(pepper),
+        );
+    }
+
+    let read_replicas = didhub_db::create_replica_pools(&db_cfg).await;
+    if !read_replicas.is_empty() {
+        tracing::info!(
+            replicas = read_replicas.len(),
+            "routing read-only queries to read replicas"
+        );
+    }
+
+    // Pool gauges and acquire wait for /metrics
+    didhub_db_connection::spawn_pool_sampler(
+        db_pool.clone(),
+        "primary".to_string(),
+        POOL_SAMPLE_INTERVAL,
+    );
+    for (i, pool) in read_replicas.pools().iter().enumerate() {
+        didhub_db_connection::spawn_pool_sampler(
+            pool.clone(),
+            format!("replica-{i}"),
+            POOL_SAMPLE_INTERVAL,
+        );
+    }
 
     tracing::info!(
         db_url = %db_cfg.url,
@@ -88,9 +257,44 @@ async fn main() -> anyhow::Result<()> {
     let shared_limiter = Arc::new(tokio::sync::RwLock::new(limiter));
     eprintln!("[STARTUP] Rate limiter configured");
 
+    // Build the shared cache (Redis when configured, in-memory otherwise)
+    let cache = match AppCache::from_url(config.redis_url.as_deref()).await {
+        Ok(cache) => cache,
+        Err(e) => {
+            tracing::error!(%e, "failed to connect to redis_url; falling back to in-memory cache");
+            AppCache::memory()
+        }
+    };
+    let cache = cache.with_policies(config.cache.namespaces.iter().map(|(namespace, ns)| {
+        let policy = NamespacePolicy {
+            ttl: ns.ttl_seconds.map(Duration::from_secs),
+            capacity: ns.capacity,
+            codec: ns
+                .codec
+                .as_deref()
+                .and_then(Codec::from_name)
+                .unwrap_or_default(),
+        };
+        (namespace.clone(), policy)
+    }));
+    let cache = cache.with_local_tier(
+        config.cache.local_capacity,
+        Duration::from_secs(config.cache.local_ttl_seconds),
+        &config.cache.local_namespaces,
+    );
+    let cache = cache_invalidation::attach(cache, &db_pool, config.redis_url.as_deref()).await;
+    tracing::info!(backend = cache.backend_name(), "cache configured");
+    let revocations: Arc<dyn RevocationStore> =
+        Arc::new(DbRevocationStore::new(Arc::new(db_pool.clone())));
+    let api_keys: Arc<dyn ApiKeyStore> = Arc::new(DbApiKeyStore::new(Arc::new(db_pool.clone())));
+
     // Build authenticator and app state
     eprintln!("[STARTUP] Building authenticator and app state...");
-    let (startup_app_state, maintenance_msg) = match build_authenticator_from_config(&config) {
+    let (startup_app_state, maintenance_msg) = match build_authenticator_from_config(
+        &config,
+        revocations,
+        api_keys,
+    ) {
         Ok((authenticator, info)) => {
             tracing::info!(
                 auth_mode = %info.mode,
@@ -106,7 +310,57 @@ async fn main() -> anyhow::Result<()> {
                 job_queue.clone(),
                 updates,
                 reload_handle.clone(),
-            );
+            )
+            .with_cache(cache)
+            .with_read_replicas(read_replicas);
+            state.set_features(config.features.clone());
+            match policy_from_config(&config.password_policy) {
+                Ok(policy) => state.set_password_policy(policy),
+                Err(e) => tracing::error!(
+                    %e,
+                    "failed to load banned password list; using the built-in list"
+                ),
+            }
+            state.set_mailer(mailer_from_config(&config.smtp));
+            state.set_password_reset(PasswordResetSettings::from_config(&config));
+            state.set_device_token_ttl(Duration::from_secs(
+                config.auth.device_token_ttl_days * 24 * 60 * 60,
+            ));
+            state.set_trash_retention(Duration::from_secs(
+                config.trash.retention_days * 24 * 60 * 60,
+            ));
+            state.set_birthdays(BirthdaySettings::from_config(&config));
+            state.set_audit_retention(AuditRetention::from_config(&config));
+            state.set_metrics_access(MetricsAccess::from_config(&config));
+            job_queue
+                .register_executor(ExpiredResetTokensExecutor::new(Arc::clone(&state.db_pool)))
+                .await;
+            job_queue
+                .register_executor(TrashPurgeExecutor::new(state.clone()))
+                .await;
+            job_queue
+                .register_executor(UploadsGcExecutor::new(state.clone()))
+                .await;
+            job_queue
+                .register_executor(SystemExportExecutor::new(state.clone()))
+                .await;
+            job_queue
+                .register_executor(BirthdayDigestExecutor::new(state.clone()))
+                .await;
+            job_queue
+                .register_executor(AuditRetentionExecutor::new(state.clone()))
+                .await;
+            let backup = BackupSettings {
+                database_url: db_cfg.url.clone(),
+                backup_dir: Path::new(&config.uploads.directory).join("backups"),
+                uploads_dir: config.uploads.directory.clone().into(),
+            };
+            job_queue
+                .register_executor(BackupCreateExecutor::new(backup.clone()))
+                .await;
+            job_queue
+                .register_executor(BackupRestoreExecutor::new(backup))
+                .await;
             eprintln!("[STARTUP] AppState created");
             (Some(Arc::new(state)), None)
         }
@@ -119,19 +373,52 @@ async fn main() -> anyhow::Result<()> {
 
     // Spawn background config reloader
     eprintln!("[STARTUP] Setting up config reloader...");
+    let reload_ctx = ConfigReloadContext {
+        shared_config: shared_config.clone(),
+        reload_handle: reload_handle.clone(),
+        app_state: startup_app_state.clone(),
+        shared_limiter: shared_limiter.clone(),
+        job_queue: job_queue.clone(),
+        cli_overrides: args.overrides.clone(),
+    };
     if config.auto_update.check_enabled {
         config_reloader::spawn_config_reloader(
-            config_path.clone(),
+            config_paths.clone(),
             config.auto_update.check_interval_hours,
-            shared_config.clone(),
-            reload_handle,
-            startup_app_state.clone(),
-            shared_limiter.clone(),
-            job_queue.clone(),
+            reload_ctx.clone(),
         );
         eprintln!("[STARTUP] Config reloader spawned");
     }
 
+    // Reload on SIGHUP
+    #[cfg(unix)]
+    match config_reloader::spawn_sighup_reloader(config_paths.clone(), reload_ctx.clone()) {
+        Ok(()) => eprintln!("[STARTUP] SIGHUP reload handler installed"),
+        Err(e) => tracing::warn!(%e, "failed to install SIGHUP handler"),
+    }
+
+    // Watch the config file so changes apply without waiting for the poll interval
+    let _config_watcher = if config_paths.is_empty() {
+        None
+    } else {
+        let overrides = args.overrides.clone();
+        match didhub_config::ConfigWatcher::spawn_layered_with_overrides(
+            &config_paths,
+            config.clone(),
+            move |cfg| overrides.apply(cfg),
+        ) {
+            Ok(watcher) => {
+                config_reloader::spawn_config_watcher(watcher.subscribe(), reload_ctx);
+                eprintln!("[STARTUP] Watching config files for changes");
+                Some(watcher)
+            }
+            Err(e) => {
+                tracing::warn!(%e, "failed to watch config files; relying on periodic reload");
+                None
+            }
+        }
+    };
+
     // Provision admin if configured
     eprintln!("[STARTUP] Checking admin provisioning...");
     if let Some(ref state) = startup_app_state {
@@ -139,6 +426,24 @@ async fn main() -> anyhow::Result<()> {
             tracing::error!(%e, "failed to provision admin from environment");
             eprintln!("[STARTUP] Admin provisioning error: {}", e);
         }
+        if let Err(e) = maybe_promote_owner(state).await {
+            tracing::error!(%e, "failed to assign the owner role");
+        }
+        if let Some(dataset) = args.seed {
+            seed_database(state, dataset, &config.uploads.directory).await?;
+        }
+    }
+
+    // Load hot cache entries before accepting traffic
+    if let Some(ref state) = startup_app_state {
+        eprintln!("[STARTUP] Warming up cache...");
+        cache_warmup::warm_up(state, &config.cache.warm_up).await;
+    }
+
+    // Register scheduled jobs and start the scheduler
+    eprintln!("[STARTUP] Starting scheduler...");
+    if let Some(ref state) = startup_app_state {
+        start_scheduler(state, &config.scheduler).await?;
     }
 
     // Build router
@@ -147,38 +452,147 @@ async fn main() -> anyhow::Result<()> {
     eprintln!("[STARTUP] Router built successfully");
 
     // Start server
-    eprintln!(
-        "[STARTUP] Binding to {}:{}",
-        config.server.host, config.server.port
-    );
-    let addr = parse_bind_address(&config.server.host, config.server.port);
-    eprintln!("[STARTUP] Parsed address: {:?}", addr);
+    if let Some(ref socket_path) = config.server.unix_socket {
+        #[cfg(unix)]
+        {
+            let mode = config
+                .server
+                .unix_socket_mode_bits()
+                .ok_or_else(|| anyhow::anyhow!("invalid server.unix_socket_mode"))?;
+            let listener = config_helpers::bind_unix_socket(socket_path, mode)?;
+            eprintln!(
+                "[STARTUP] ✓ Server listening on unix:{} (mode {:o})",
+                socket_path, mode
+            );
+            eprintln!("[STARTUP] ✓ Frontend embedded: YES");
+            eprintln!("[STARTUP] ✓ Ready to accept connections!");
 
-    let listener = TcpListener::bind(addr).await?;
-    eprintln!(
-        "[STARTUP] ✓ Server listening on {}:{}",
-        config.server.host, config.server.port
-    );
-    eprintln!("[STARTUP] ✓ Frontend embedded: YES");
-    eprintln!("[STARTUP] ✓ Ready to accept connections!");
+            axum::serve(listener, app.into_make_service())
+                .with_graceful_shutdown(shutdown_signal())
+                .await?;
+        }
+        #[cfg(not(unix))]
+        anyhow::bail!("server.unix_socket ({socket_path}) is only supported on Unix platforms");
+    } else {
+        eprintln!(
+            "[STARTUP] Binding to {}:{}",
+            config.server.host, config.server.port
+        );
+        let addr = parse_bind_address(&config.server.host, config.server.port);
+        eprintln!("[STARTUP] Parsed address: {:?}", addr);
 
-    axum::serve(listener, app.into_make_service()).await?;
+        if config.tls.enabled {
+            let tls_config = build_rustls_config(&config.tls)?;
+            eprintln!(
+                "[STARTUP] ✓ Server listening on https://{}:{}",
+                config.server.host, config.server.port
+            );
+            eprintln!("[STARTUP] ✓ Frontend embedded: YES");
+            eprintln!("[STARTUP] ✓ Ready to accept connections!");
 
+            let handle = axum_server::Handle::new();
+            tokio::spawn({
+                let handle = handle.clone();
+                async move {
+                    shutdown_signal().await;
+                    handle.graceful_shutdown(Some(SHUTDOWN_GRACE_PERIOD));
+                }
+            });
+            axum_server::bind_rustls(addr, RustlsConfig::from_config(tls_config))
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await?;
+        } else {
+            let listener = TcpListener::bind(addr).await?;
+            eprintln!(
+                "[STARTUP] ✓ Server listening on {}:{}",
+                config.server.host, config.server.port
+            );
+            eprintln!("[STARTUP] ✓ Frontend embedded: YES");
+            eprintln!("[STARTUP] ✓ Ready to accept connections!");
+
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .with_graceful_shutdown(shutdown_signal())
+            .await?;
+        }
+    }
+
+    eprintln!("[SHUTDOWN] Server stopped");
+    shutdown_tracer_provider(tracer_provider);
     Ok(())
 }
 
-/// Load configuration from file or defaults.
-fn load_config(path: &Option<String>) -> anyhow::Result<didhub_config::Config> {
-    match path.as_deref() {
-        Some(p) => didhub_config::load_config(Some(p)).map_err(|e| {
-            eprintln!("failed to load configuration: {e}");
-            anyhow::anyhow!(e.to_string())
-        }),
-        None => didhub_config::load_config::<&std::path::Path>(None).map_err(|e| {
-            eprintln!("failed to load configuration: {e}");
-            anyhow::anyhow!(e.to_string())
-        }),
+/// Resolves on Ctrl-C or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!(%e, "failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(e) => {
+                tracing::error!(%e, "failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
     }
+    eprintln!("[SHUTDOWN] Signal received, finishing open requests...");
+}
+
+/// Print the effective configuration with secrets redacted, then any validation problems.
+fn print_config(paths: &[String], overrides: &ConfigOverrides) -> anyhow::Result<()> {
+    let mut config = didhub_config::load_config_layered(paths)?;
+    overrides.apply(&mut config);
+    println!("{}", serde_json::to_string_pretty(&config.redacted())?);
+    if let Err(e) = didhub_config::validate_config(&config) {
+        eprintln!("warning: {e}");
+    }
+    for deprecation in didhub_config::deprecated_env_in_use() {
+        eprintln!("warning: {deprecation}");
+    }
+    Ok(())
+}
+
+/// Load configuration from the layered files (if any) or defaults, apply CLI overrides and validate.
+fn load_config(
+    paths: &[String],
+    overrides: &ConfigOverrides,
+) -> anyhow::Result<didhub_config::Config> {
+    let mut config = didhub_config::load_config_layered(paths).map_err(|e| {
+        eprintln!("failed to load configuration: {e}");
+        anyhow::anyhow!(e.to_string())
+    })?;
+    overrides.apply(&mut config);
+
+    if let Err(e) = didhub_config::validate_config(&config) {
+        if let didhub_config::ConfigError::ValidationMany(ref issues) = e {
+            eprintln!("invalid configuration ({} problems):", issues.len());
+            for issue in issues {
+                eprintln!("  - {issue}");
+            }
+        } else {
+            eprintln!("invalid configuration: {e}");
+        }
+        return Err(anyhow::anyhow!(e.to_string()));
+    }
+    Ok(config)
 }
 
 /// Run database migrations based on the database type.
@@ -186,23 +600,10 @@ async fn run_migrations(
     db_cfg: &didhub_db::DbConnectionConfig,
     db_pool: &didhub_db::DbPool,
 ) -> anyhow::Result<()> {
-    let url_lower = db_cfg.url.to_lowercase();
+    let dialect = didhub_migrations::Dialect::from_url(&db_cfg.url);
+    tracing::info!(db_url = %db_cfg.url, dialect = dialect.name(), "applying database migrations");
 
-    let migrate_res = if url_lower.starts_with("postgres")
-        || url_lower.contains("postgresql")
-        || url_lower.contains("postgres://")
-    {
-        tracing::info!(db_url = %db_cfg.url, "applying Postgres migrations");
-        didhub_migrations::postgres_migrator().run(db_pool).await
-    } else if url_lower.starts_with("mysql") || url_lower.contains("mysql://") {
-        tracing::info!(db_url = %db_cfg.url, "applying MySQL migrations");
-        didhub_migrations::mysql_migrator().run(db_pool).await
-    } else {
-        tracing::info!(db_url = %db_cfg.url, "applying SQLite migrations");
-        didhub_migrations::sqlite_migrator().run(db_pool).await
-    };
-
-    match migrate_res {
+    match dialect.migrator().run(db_pool).await {
         Ok(_) => {
             tracing::info!("database migrations applied successfully");
             Ok(())
@@ -214,6 +615,47 @@ async fn run_migrations(
     }
 }
 
+/// `migrate status|up|down <n>`: report or change the schema version and exit.
+async fn migrate_command(
+    db_cfg: &didhub_db::DbConnectionConfig,
+    db_pool: &didhub_db::DbPool,
+    action: MigrateAction,
+) -> anyhow::Result<()> {
+    let dialect = didhub_migrations::Dialect::from_url(&db_cfg.url);
+    let migrator = dialect.migrator();
+    match action {
+        MigrateAction::Status => {
+            let mut conn = db_pool.acquire().await?;
+            let statuses = didhub_migrations::status(migrator, &mut *conn).await?;
+            println!("{} migrations:", dialect.name());
+            for s in statuses {
+                println!(
+                    "  {:04}  {:<9} {}{}",
+                    s.version,
+                    s.state.as_str(),
+                    s.description,
+                    if s.reversible { "" } else { " (irreversible)" }
+                );
+            }
+        }
+        MigrateAction::Up => {
+            run_migrations(db_cfg, db_pool).await?;
+            println!("all {} migrations applied", dialect.name());
+        }
+        MigrateAction::Down { steps } => {
+            let mut conn = db_pool.acquire().await?;
+            let reverted = didhub_migrations::revert(migrator, &mut *conn, steps).await?;
+            if reverted.is_empty() {
+                println!("no applied migrations to revert");
+            }
+            for version in reverted {
+                println!("reverted {version:04}");
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Build the application router, either normal or maintenance mode.
 async fn build_app(
     state: Option<Arc<AppState>>,
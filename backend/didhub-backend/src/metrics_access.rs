@@ -0,0 +1,57 @@
+//! Who may scrape `/metrics`.
+//!
+//! The endpoint is open unless `metrics.bearer_token` or `metrics.allowed_ips`
+//! is set. Then a scrape must either send the token or come from an allowed
+//! address; requests without a known peer address (unix sockets) need the token.
+
+use std::net::IpAddr;
+
+use axum::http::{header, HeaderMap, StatusCode};
+
+/// Settings taken from `metrics.bearer_token` and `metrics.allowed_ips`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsAccess {
+    pub bearer_token: Option<String>,
+    pub allowed_networks: Vec<ipnet::IpNet>,
+}
+
+impl MetricsAccess {
+    pub fn from_config(cfg: &didhub_config::Config) -> Self {
+        Self {
+            bearer_token: cfg
+                .metrics
+                .bearer_token
+                .clone()
+                .filter(|token| !token.is_empty()),
+            allowed_networks: cfg.metrics.allowed_networks(),
+        }
+    }
+
+    /// Let the scrape through, or answer 401 when a token would be accepted and
+    /// 403 when only the address counts.
+    pub fn check(&self, headers: &HeaderMap, peer: Option<IpAddr>) -> Result<(), StatusCode> {
+        if self.bearer_token.is_none() && self.allowed_networks.is_empty() {
+            return Ok(());
+        }
+        if let Some(ip) = peer {
+            if self.allowed_networks.iter().any(|net| net.contains(&ip)) {
+                return Ok(());
+            }
+        }
+        let Some(expected) = &self.bearer_token else {
+            return Err(StatusCode::FORBIDDEN);
+        };
+        let presented = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+        match presented {
+            Some(token) if constant_time_eq(token.as_bytes(), expected.as_bytes()) => Ok(()),
+            _ => Err(StatusCode::UNAUTHORIZED),
+        }
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
@@ -0,0 +1,43 @@
+//! Building the password policy from configuration.
+
+use didhub_auth::auth::{is_client_hash, PasswordPolicy, PolicyViolation};
+use didhub_config::PasswordPolicyConfig;
+
+/// Build a [`PasswordPolicy`] from `cfg`, loading the banned password file if one
+/// is configured. The file holds one password per line; blank lines and lines
+/// starting with `#` are skipped.
+pub fn policy_from_config(cfg: &PasswordPolicyConfig) -> std::io::Result<PasswordPolicy> {
+    let mut policy = PasswordPolicy::default();
+    if let Some(path) = &cfg.banned_passwords_path {
+        let contents = std::fs::read_to_string(path)?;
+        policy = policy.with_banned_passwords(
+            contents
+                .lines()
+                .map(|line| line.trim_end_matches('\r'))
+                .filter(|line| !line.starts_with('#')),
+        );
+    }
+    policy.min_length = cfg.min_length;
+    policy.require_lowercase = cfg.require_lowercase;
+    policy.require_uppercase = cfg.require_uppercase;
+    policy.require_digit = cfg.require_digit;
+    policy.require_symbol = cfg.require_symbol;
+    policy.check_breached = cfg.check_breached;
+    Ok(policy)
+}
+
+/// Check a password submitted by a client against `policy`.
+///
+/// SHA-256 pre-hashes can only be checked against the banned list; anything
+/// else is a legacy plaintext password and is checked against every rule.
+pub fn check_submitted_password(policy: &PasswordPolicy, submitted: &str) -> Vec<PolicyViolation> {
+    if is_client_hash(submitted) {
+        policy
+            .check_client_hash(submitted)
+            .err()
+            .into_iter()
+            .collect()
+    } else {
+        policy.check(submitted)
+    }
+}
@@ -0,0 +1,161 @@
+//! Password reset by email.
+//!
+//! `/auth/password-reset/request` mails a link with a random single-use token to the
+//! address on file; only the token's SHA-256 is stored. `/auth/password-reset/confirm`
+//! exchanges the token for a new password and signs the user out everywhere.
+//! Expired and used tokens are deleted by the [`PASSWORD_RESET_CLEANUP_JOB`] job.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::Utc;
+use didhub_cache::AppCache;
+use didhub_job_queue::{async_trait, JobExecutor, JobQueueError};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Job type of the executor deleting expired and used reset tokens.
+pub const PASSWORD_RESET_CLEANUP_JOB: &str = "auth.password_reset_cleanup";
+
+/// Reset requests allowed per account and per client address within [`RATE_WINDOW`].
+pub const REQUESTS_PER_ACCOUNT: u32 = 3;
+pub const REQUESTS_PER_ADDRESS: u32 = 10;
+pub const RATE_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+const ACCOUNT_NAMESPACE: &str = "password_reset_account";
+const ADDRESS_NAMESPACE: &str = "password_reset_address";
+
+const TOKEN_BYTES: usize = 32;
+
+/// Settings of the reset flow, taken from `server.public_url` and
+/// `auth.password_reset_ttl_minutes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordResetSettings {
+    pub public_url: Option<String>,
+    pub token_ttl: Duration,
+}
+
+impl Default for PasswordResetSettings {
+    fn default() -> Self {
+        Self {
+            public_url: None,
+            token_ttl: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+impl PasswordResetSettings {
+    pub fn from_config(cfg: &didhub_config::Config) -> Self {
+        Self {
+            public_url: cfg.server.public_url.clone(),
+            token_ttl: Duration::from_secs(cfg.auth.password_reset_ttl_minutes * 60),
+        }
+    }
+
+    /// Link to the frontend's reset page carrying `token`.
+    pub fn reset_link(&self, token: &str) -> String {
+        let base = self
+            .public_url
+            .as_deref()
+            .unwrap_or("")
+            .trim_end_matches('/');
+        format!("{}/reset-password?token={}", base, token)
+    }
+}
+
+/// A fresh random token and the SHA-256 hex digest stored for it.
+pub fn generate_token() -> (String, String) {
+    let mut buf = [0u8; TOKEN_BYTES];
+    OsRng.fill_bytes(&mut buf);
+    let token = hex::encode(buf);
+    let hash = hash_token(&token);
+    (token, hash)
+}
+
+/// Digest under which a token is stored and looked up.
+pub fn hash_token(token: &str) -> String {
+    didhub_auth::auth::sha256_hex(token.trim())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Window {
+    count: u32,
+    resets_at: i64,
+}
+
+async fn allow(cache: &AppCache, namespace: &str, key: &str, limit: u32) -> bool {
+    let now = Utc::now().timestamp();
+    let window = match cache.get::<Window>(namespace, key).await {
+        Ok(Some(w)) if w.resets_at > now => w,
+        _ => Window {
+            count: 0,
+            resets_at: now + RATE_WINDOW.as_secs() as i64,
+        },
+    };
+    if window.count >= limit {
+        return false;
+    }
+    let ttl = Duration::from_secs((window.resets_at - now).max(1) as u64);
+    let next = Window {
+        count: window.count + 1,
+        ..window
+    };
+    // A cache failure must not lock everyone out of resetting their password
+    let _ = cache.set(namespace, key, &next, Some(ttl)).await;
+    true
+}
+
+/// Count a reset request from `address`; `false` once the address is over its limit.
+pub async fn allow_from_address(cache: &AppCache, address: &str) -> bool {
+    allow(cache, ADDRESS_NAMESPACE, address, REQUESTS_PER_ADDRESS).await
+}
+
+/// Count a reset email for `user_id`; `false` once the account is over its limit.
+pub async fn allow_for_account(cache: &AppCache, user_id: uuid::Uuid) -> bool {
+    allow(
+        cache,
+        ACCOUNT_NAMESPACE,
+        &user_id.to_string(),
+        REQUESTS_PER_ACCOUNT,
+    )
+    .await
+}
+
+/// Delete reset tokens that expired or were used. Returns the number deleted.
+pub async fn purge_expired(pool: &didhub_db::DbPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM password_reset_tokens WHERE expires_at <= ? OR used_at IS NOT NULL",
+    )
+    .bind(Utc::now().to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Executor for [`PASSWORD_RESET_CLEANUP_JOB`]; schedule it under
+/// `[scheduler.jobs."auth.password_reset_cleanup"]`.
+pub struct ExpiredResetTokensExecutor {
+    pool: Arc<didhub_db::DbPool>,
+}
+
+impl ExpiredResetTokensExecutor {
+    pub fn new(pool: Arc<didhub_db::DbPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl JobExecutor for ExpiredResetTokensExecutor {
+    fn job_type(&self) -> &str {
+        PASSWORD_RESET_CLEANUP_JOB
+    }
+
+    async fn execute(&self, _payload: Value) -> Result<(), JobQueueError> {
+        let deleted = purge_expired(&self.pool)
+            .await
+            .map_err(|e| JobQueueError::ExecutionFailed(e.to_string()))?;
+        tracing::info!(deleted, "purged expired password reset tokens");
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use axum::body::Body;
+use axum::http::{Method, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use didhub_auth::auth::Permission;
+use std::sync::Arc;
+
+use crate::error::ApiError;
+use crate::handlers::auth::utils::{extract_auth_token, ResolvedAuth};
+use crate::state::AppState;
+
+/// Paths a read-only account may still write to: its own session, profile and password.
+fn writable_without_edit_permission(path: &str) -> bool {
+    path.starts_with("/auth/") || path.starts_with("/me/") || path.ends_with("/password")
+}
+
+/// Middleware that rejects unsafe methods from accounts whose roles grant
+/// `view_content` but not `edit_own_content` (the viewer role).
+/// Unauthenticated and unapproved requests are left to the handlers, which reuse
+/// the authentication resolved here.
+pub async fn enforce_read_only_roles(mut req: Request<Body>, next: Next) -> Response {
+    let method = req.method();
+    if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+        return next.run(req).await;
+    }
+    if writable_without_edit_permission(req.uri().path()) {
+        return next.run(req).await;
+    }
+    let Some(state) = req.extensions().get::<Arc<AppState>>().cloned() else {
+        return next.run(req).await;
+    };
+    if extract_auth_token(req.headers()).is_none() {
+        return next.run(req).await;
+    }
+
+    let resolved = ResolvedAuth::resolve(&state, req.headers()).await;
+    if let Some(auth) = resolved.auth() {
+        if auth.has_permission(Permission::ViewContent)
+            && !auth.has_permission(Permission::EditOwnContent)
+        {
+            return ApiError::forbidden("read-only role").into_response();
+        }
+    }
+    req.extensions_mut().insert(resolved.clone());
+    resolved.scope(next.run(req)).await
+}
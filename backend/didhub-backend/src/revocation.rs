@@ -0,0 +1,105 @@
+//! Token revocation backed by the `revoked_tokens` and `token_revocation_cutoffs` tables.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use didhub_auth::auth::{AuthError, RevocationStore};
+use didhub_db::generated::revoked_tokens as db_revoked;
+use didhub_db::generated::token_revocation_cutoffs as db_cutoffs;
+use uuid::Uuid;
+
+/// Fallback lifetime for revoked tokens that carry no `exp` claim.
+const DEFAULT_REVOCATION_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// How long past its `exp` the authenticator still accepts a token; revocations
+/// are kept at least that long.
+const EXPIRY_LEEWAY: Duration = Duration::from_secs(60);
+
+fn subsystem(e: impl std::fmt::Display) -> AuthError {
+    AuthError::Subsystem(format!("revocation store: {}", e))
+}
+
+/// [`RevocationStore`] keeping the jti denylist and per-account cut-offs in the
+/// database, so revocations survive restarts and are never evicted.
+///
+/// Revoked jtis are kept until the token would have expired anyway; expired
+/// entries are deleted whenever another token is revoked.
+#[derive(Debug, Clone)]
+pub struct DbRevocationStore {
+    pool: Arc<didhub_db::DbPool>,
+}
+
+impl DbRevocationStore {
+    pub fn new(pool: Arc<didhub_db::DbPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl RevocationStore for DbRevocationStore {
+    async fn revoke_token(&self, jti: &str, expires_at: Option<u64>) -> Result<(), AuthError> {
+        let now = Utc::now();
+        // Tokens that just expired are still accepted within the leeway, so they are
+        // recorded too.
+        let keep_until = match expires_at.and_then(|exp| i64::try_from(exp).ok()) {
+            Some(exp) => chrono::DateTime::from_timestamp(exp, 0)
+                .unwrap_or(now)
+                .max(now),
+            None => now + DEFAULT_REVOCATION_TTL,
+        } + EXPIRY_LEEWAY;
+
+        sqlx::query("DELETE FROM revoked_tokens WHERE expires_at <= ?")
+            .bind(now.to_rfc3339())
+            .execute(&*self.pool)
+            .await
+            .map_err(subsystem)?;
+
+        let row = db_revoked::RevokedTokensRow {
+            jti: jti.to_string(),
+            expires_at: keep_until.to_rfc3339(),
+        };
+        let updated = db_revoked::update_by_primary_key(&*self.pool, &row.jti, &row)
+            .await
+            .map_err(subsystem)?;
+        if updated == 0 {
+            db_revoked::insert_row(&*self.pool, &row)
+                .await
+                .map_err(subsystem)?;
+        }
+        Ok(())
+    }
+
+    async fn is_token_revoked(&self, jti: &str) -> Result<bool, AuthError> {
+        Ok(
+            db_revoked::find_by_primary_key(&*self.pool, &jti.to_string())
+                .await
+                .map_err(subsystem)?
+                .is_some(),
+        )
+    }
+
+    async fn revoke_user_tokens(&self, user_id: Uuid) -> Result<(), AuthError> {
+        let row = db_cutoffs::TokenRevocationCutoffsRow {
+            owner_id: user_id,
+            revoked_before_ms: Utc::now().timestamp_millis(),
+        };
+        let updated = db_cutoffs::update_by_primary_key(&*self.pool, &user_id, &row)
+            .await
+            .map_err(subsystem)?;
+        if updated == 0 {
+            db_cutoffs::insert_row(&*self.pool, &row)
+                .await
+                .map_err(subsystem)?;
+        }
+        Ok(())
+    }
+
+    async fn user_tokens_revoked_before(&self, user_id: Uuid) -> Result<Option<u64>, AuthError> {
+        Ok(db_cutoffs::find_by_primary_key(&*self.pool, &user_id)
+            .await
+            .map_err(subsystem)?
+            .map(|row| row.revoked_before_ms.max(0) as u64))
+    }
+}
@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use didhub_backend::state::AppState;
+use didhub_scheduler::Schedule;
+
+/// Register the jobs from `[scheduler.jobs]` and start the scheduler loop.
+pub async fn start_scheduler(
+    state: &AppState,
+    cfg: &didhub_config::SchedulerConfig,
+) -> anyhow::Result<()> {
+    for (name, job) in &cfg.jobs {
+        let schedule = match (&job.cron, job.interval_seconds) {
+            (Some(expr), _) => Schedule::cron(expr)?,
+            (None, Some(secs)) => Schedule::try_interval(Duration::from_secs(secs))?,
+            (None, None) => anyhow::bail!("scheduler.jobs.{name} has no schedule"),
+        };
+        tracing::info!(job = %name, schedule = %schedule, enabled = job.enabled, "registering scheduled job");
+        state.scheduler.register(name, schedule, job.enabled).await;
+    }
+
+    if cfg.enabled {
+        state.scheduler.spawn();
+        tracing::info!(jobs = cfg.jobs.len(), "scheduler started");
+    } else {
+        tracing::info!("scheduler disabled by configuration");
+    }
+    Ok(())
+}
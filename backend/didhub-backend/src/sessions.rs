@@ -0,0 +1,185 @@
+//! Login sessions: one `sessions` row per session token issued by `/auth/login`.
+//!
+//! A row follows its token through `/auth/refresh` (the old token is revoked and
+//! replaced), so users can list where they are signed in and revoke a session,
+//! which revokes its current token through the [`RevocationStore`].
+//!
+//! [`RevocationStore`]: didhub_auth::auth::RevocationStore
+
+use std::time::Duration;
+
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use didhub_auth::auth::AuthContext;
+use didhub_db::generated::sessions as db_sessions;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// Cache namespace throttling `last_seen_at` updates.
+const SEEN_NAMESPACE: &str = "session_seen";
+
+/// `last_seen_at` is written at most this often per session.
+const LAST_SEEN_RESOLUTION: Duration = Duration::from_secs(60);
+
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// Client address as reported by a reverse proxy (`X-Forwarded-For`, then `X-Real-IP`).
+pub fn client_ip(headers: &HeaderMap) -> Option<String> {
+    let forwarded = headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next());
+    forwarded
+        .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
+        .map(str::trim)
+        .filter(|ip| !ip.is_empty())
+        .map(str::to_string)
+}
+
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|ua| ua.chars().take(MAX_USER_AGENT_LEN).collect())
+}
+
+/// Public view of a session. `current` marks the session the request was made with.
+pub fn session_to_payload(row: &db_sessions::SessionsRow, current: bool) -> Value {
+    json!({
+        "id": row.id,
+        "userAgent": row.user_agent,
+        "ipAddress": row.ip_address,
+        "createdAt": row.created_at,
+        "lastSeenAt": row.last_seen_at,
+        "expiresAt": row.expires_at,
+        "current": current,
+    })
+}
+
+/// Whether the session has expired together with its token.
+pub fn is_expired(row: &db_sessions::SessionsRow, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(&row.expires_at).is_ok_and(|exp| exp <= now)
+}
+
+/// Record a new session for a token issued at login.
+pub async fn record_login(
+    state: &AppState,
+    user_id: Uuid,
+    token_id: &str,
+    expires_at: DateTime<Utc>,
+    headers: &HeaderMap,
+) -> Result<Uuid, ApiError> {
+    let now = Utc::now().to_rfc3339();
+    let row = db_sessions::SessionsRow {
+        id: Uuid::new_v4(),
+        user_id,
+        token_id: token_id.to_string(),
+        user_agent: user_agent(headers),
+        ip_address: client_ip(headers),
+        created_at: now.clone(),
+        last_seen_at: now,
+        expires_at: expires_at.to_rfc3339(),
+    };
+    db_sessions::insert_session(&*state.db_pool, &row)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(row.id)
+}
+
+/// Move the session of `old_token_id` to a refreshed token and revoke the old one.
+/// Returns `false` if the old token had no session.
+pub async fn rotate_token(
+    state: &AppState,
+    old: &AuthContext,
+    new_token_id: &str,
+    expires_at: DateTime<Utc>,
+) -> Result<bool, ApiError> {
+    let Some(old_token_id) = old.token_id.as_deref() else {
+        return Ok(false);
+    };
+    let affected = sqlx::query(
+        "UPDATE sessions SET token_id = ?, expires_at = ?, last_seen_at = ? WHERE token_id = ?",
+    )
+    .bind(new_token_id)
+    .bind(expires_at.to_rfc3339())
+    .bind(Utc::now().to_rfc3339())
+    .bind(old_token_id)
+    .execute(&*state.db_pool)
+    .await
+    .map_err(ApiError::from)?
+    .rows_affected();
+    if affected == 0 {
+        return Ok(false);
+    }
+    state
+        .revocation_store()
+        .revoke_token(old_token_id, old.expires_at)
+        .await?;
+    Ok(true)
+}
+
+/// Update `last_seen_at` of the session behind `auth`, at most once per minute.
+pub async fn touch(state: &AppState, auth: &AuthContext) {
+    let Some(token_id) = auth.token_id.as_deref() else {
+        return;
+    };
+    if state
+        .cache
+        .exists(SEEN_NAMESPACE, token_id)
+        .await
+        .unwrap_or(false)
+    {
+        return;
+    }
+    let updated = sqlx::query("UPDATE sessions SET last_seen_at = ? WHERE token_id = ?")
+        .bind(Utc::now().to_rfc3339())
+        .bind(token_id)
+        .execute(&*state.db_pool)
+        .await;
+    if let Err(e) = updated {
+        tracing::warn!(error = %e, "failed to update session last_seen_at");
+        return;
+    }
+    let _ = state
+        .cache
+        .set(SEEN_NAMESPACE, token_id, &true, Some(LAST_SEEN_RESOLUTION))
+        .await;
+}
+
+/// Revoke the session's current token and delete the session.
+pub async fn revoke(state: &AppState, row: &db_sessions::SessionsRow) -> Result<(), ApiError> {
+    let expires_at = DateTime::parse_from_rfc3339(&row.expires_at)
+        .ok()
+        .map(|exp| exp.timestamp().max(0) as u64);
+    state
+        .revocation_store()
+        .revoke_token(&row.token_id, expires_at)
+        .await?;
+    db_sessions::delete_by_primary_key(&*state.db_pool, &row.id)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+/// Delete the session of a token that was revoked by other means (logout).
+pub async fn forget_token(state: &AppState, token_id: &str) -> Result<(), ApiError> {
+    sqlx::query("DELETE FROM sessions WHERE token_id = ?")
+        .bind(token_id)
+        .execute(&*state.db_pool)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
+
+/// Delete every session of a user whose tokens were all revoked.
+pub async fn forget_user_sessions(state: &AppState, user_id: Uuid) -> Result<(), ApiError> {
+    sqlx::query("DELETE FROM sessions WHERE user_id = ?")
+        .bind(user_id)
+        .execute(&*state.db_pool)
+        .await
+        .map_err(ApiError::from)?;
+    Ok(())
+}
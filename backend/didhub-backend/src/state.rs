@@ -1,14 +1,26 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use didhub_auth::auth::AuthenticatorTrait;
+use didhub_auth::auth::{ApiKeyStore, AuthenticatorTrait, PasswordPolicy, RevocationStore};
+use didhub_cache::AppCache;
+use didhub_config::FeaturesConfig;
 use didhub_job_queue::JobQueueClient;
 use didhub_log_client::LogCategory;
+use didhub_scheduler::CronScheduler;
 use didhub_updates::UpdateCoordinator;
 use serde_json::{json, Value};
 use std::sync::RwLock;
+use std::time::Duration;
 
+use crate::api_keys::DbApiKeyStore;
+use crate::audit::AuditRetention;
+use crate::birthdays::BirthdaySettings;
 use crate::error::ApiError;
+use crate::health::DependencyGauge;
+use crate::mailer::Mailer;
+use crate::metrics_access::MetricsAccess;
+use crate::password_reset::PasswordResetSettings;
+use crate::revocation::DbRevocationStore;
 
 const SENSITIVE_FIELDS: &[&str] = &[
     "password",
@@ -49,20 +61,46 @@ fn redact_sensitive_data(mut value: Value) -> Value {
 
 pub struct AppState {
     pub db_pool: Arc<didhub_db::DbPool>,
+    read_replicas: Arc<didhub_db::ReadReplicas>,
     authenticator: Arc<RwLock<Arc<dyn AuthenticatorTrait>>>,
     pub job_queue: JobQueueClient,
+    pub scheduler: CronScheduler,
     pub updates: UpdateCoordinator,
     pub reload_handle: Option<crate::tracing_setup::ReloadHandle>,
+    pub cache: AppCache,
+    features: Arc<RwLock<FeaturesConfig>>,
+    password_policy: Arc<RwLock<Arc<PasswordPolicy>>>,
+    mailer: Arc<RwLock<Option<Arc<dyn Mailer>>>>,
+    password_reset: Arc<RwLock<PasswordResetSettings>>,
+    device_token_ttl: Arc<RwLock<Duration>>,
+    trash_retention: Arc<RwLock<Duration>>,
+    birthdays: Arc<RwLock<BirthdaySettings>>,
+    audit_retention: Arc<RwLock<AuditRetention>>,
+    metrics_access: Arc<RwLock<MetricsAccess>>,
+    dependencies: Arc<DependencyGauge>,
 }
 
 impl Clone for AppState {
     fn clone(&self) -> Self {
         Self {
             db_pool: Arc::clone(&self.db_pool),
+            read_replicas: Arc::clone(&self.read_replicas),
             authenticator: Arc::clone(&self.authenticator),
             job_queue: self.job_queue.clone(),
+            scheduler: self.scheduler.clone(),
             updates: self.updates.clone(),
             reload_handle: self.reload_handle.clone(),
+            cache: self.cache.clone(),
+            features: Arc::clone(&self.features),
+            password_policy: Arc::clone(&self.password_policy),
+            mailer: Arc::clone(&self.mailer),
+            password_reset: Arc::clone(&self.password_reset),
+            device_token_ttl: Arc::clone(&self.device_token_ttl),
+            trash_retention: Arc::clone(&self.trash_retention),
+            birthdays: Arc::clone(&self.birthdays),
+            audit_retention: Arc::clone(&self.audit_retention),
+            metrics_access: Arc::clone(&self.metrics_access),
+            dependencies: Arc::clone(&self.dependencies),
         }
     }
 }
@@ -78,13 +116,59 @@ impl AppState {
     ) -> Self {
         Self {
             db_pool: Arc::new(db_pool),
+            read_replicas: Arc::new(didhub_db::ReadReplicas::default()),
             authenticator: Arc::new(RwLock::new(authenticator)),
+            scheduler: CronScheduler::new(job_queue.clone()),
             job_queue,
             updates,
             reload_handle,
+            cache: AppCache::memory(),
+            features: Arc::new(RwLock::new(FeaturesConfig::default())),
+            password_policy: Arc::new(RwLock::new(Arc::new(PasswordPolicy::default()))),
+            mailer: Arc::new(RwLock::new(None)),
+            password_reset: Arc::new(RwLock::new(PasswordResetSettings::default())),
+            device_token_ttl: Arc::new(RwLock::new(Duration::from_secs(30 * 24 * 60 * 60))),
+            trash_retention: Arc::new(RwLock::new(Duration::from_secs(30 * 24 * 60 * 60))),
+            birthdays: Arc::new(RwLock::new(BirthdaySettings::default())),
+            audit_retention: Arc::new(RwLock::new(AuditRetention::default())),
+            metrics_access: Arc::new(RwLock::new(MetricsAccess::default())),
+            dependencies: Arc::new(DependencyGauge::default()),
         }
     }
 
+    /// Replace the default in-memory cache, e.g. with a Redis-backed one.
+    #[must_use]
+    pub fn with_cache(mut self, cache: AppCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Route read-only queries to these replicas instead of the primary.
+    #[must_use]
+    pub fn with_read_replicas(mut self, replicas: didhub_db::ReadReplicas) -> Self {
+        self.read_replicas = Arc::new(replicas);
+        self
+    }
+
+    /// Connection for a read-only query that tolerates replication lag: a read
+    /// replica when configured and reachable, the primary otherwise.
+    pub async fn acquire_read(&self) -> Result<didhub_db::DbPoolConnection, ApiError> {
+        self.read_replicas
+            .acquire(&self.db_pool)
+            .await
+            .map_err(ApiError::from)
+    }
+
+    /// Token revocation store backed by this state's database.
+    pub fn revocation_store(&self) -> Arc<dyn RevocationStore> {
+        Arc::new(DbRevocationStore::new(Arc::clone(&self.db_pool)))
+    }
+
+    /// API key store backed by this state's database.
+    pub fn api_key_store(&self) -> Arc<dyn ApiKeyStore> {
+        Arc::new(DbApiKeyStore::new(Arc::clone(&self.db_pool)))
+    }
+
     /// Atomically get a clone of the current authenticator.
     pub fn authenticator(&self) -> Arc<dyn AuthenticatorTrait> {
         let guard = self.authenticator.read().unwrap();
@@ -102,6 +186,101 @@ impl AppState {
         old
     }
 
+    /// Snapshot of the current feature flags.
+    pub fn features(&self) -> FeaturesConfig {
+        self.features.read().unwrap().clone()
+    }
+
+    /// Replace the feature flags (at startup and on config reload).
+    pub fn set_features(&self, features: FeaturesConfig) {
+        *self.features.write().unwrap() = features;
+    }
+
+    /// The password policy new passwords are checked against.
+    pub fn password_policy(&self) -> Arc<PasswordPolicy> {
+        self.password_policy.read().unwrap().clone()
+    }
+
+    /// Replace the password policy (at startup and on config reload).
+    pub fn set_password_policy(&self, policy: PasswordPolicy) {
+        *self.password_policy.write().unwrap() = Arc::new(policy);
+    }
+
+    /// Outgoing mail, if an SMTP relay is configured.
+    pub fn mailer(&self) -> Option<Arc<dyn Mailer>> {
+        self.mailer.read().unwrap().clone()
+    }
+
+    /// Replace the mailer (at startup and on config reload).
+    pub fn set_mailer(&self, mailer: Option<Arc<dyn Mailer>>) {
+        *self.mailer.write().unwrap() = mailer;
+    }
+
+    /// Settings of the password reset flow.
+    pub fn password_reset(&self) -> PasswordResetSettings {
+        self.password_reset.read().unwrap().clone()
+    }
+
+    /// Replace the password reset settings (at startup and on config reload).
+    pub fn set_password_reset(&self, settings: PasswordResetSettings) {
+        *self.password_reset.write().unwrap() = settings;
+    }
+
+    /// Sliding lifetime of "remember me" device tokens.
+    pub fn device_token_ttl(&self) -> Duration {
+        *self.device_token_ttl.read().unwrap()
+    }
+
+    /// Replace the device token lifetime (at startup and on config reload).
+    pub fn set_device_token_ttl(&self, ttl: Duration) {
+        *self.device_token_ttl.write().unwrap() = ttl;
+    }
+
+    /// How long deleted alters and affiliations stay in the trash.
+    pub fn trash_retention(&self) -> Duration {
+        *self.trash_retention.read().unwrap()
+    }
+
+    /// Replace the trash retention (at startup and on config reload).
+    pub fn set_trash_retention(&self, retention: Duration) {
+        *self.trash_retention.write().unwrap() = retention;
+    }
+
+    /// Time zone and digest window for birthdays.
+    pub fn birthdays(&self) -> BirthdaySettings {
+        self.birthdays.read().unwrap().clone()
+    }
+
+    /// Replace the birthday settings (at startup and on config reload).
+    pub fn set_birthdays(&self, settings: BirthdaySettings) {
+        *self.birthdays.write().unwrap() = settings;
+    }
+
+    /// How long audit changes are kept and what happens to them afterwards.
+    pub fn audit_retention(&self) -> AuditRetention {
+        self.audit_retention.read().unwrap().clone()
+    }
+
+    /// Replace the audit retention settings (at startup and on config reload).
+    pub fn set_audit_retention(&self, settings: AuditRetention) {
+        *self.audit_retention.write().unwrap() = settings;
+    }
+
+    /// Who may scrape `/metrics`.
+    pub fn metrics_access(&self) -> MetricsAccess {
+        self.metrics_access.read().unwrap().clone()
+    }
+
+    /// Replace the `/metrics` access settings (at startup and on config reload).
+    pub fn set_metrics_access(&self, access: MetricsAccess) {
+        *self.metrics_access.write().unwrap() = access;
+    }
+
+    /// Results of the latest deep health check.
+    pub fn dependencies(&self) -> &DependencyGauge {
+        &self.dependencies
+    }
+
     pub async fn audit_request(
         &self,
         method: &str,
@@ -129,4 +308,84 @@ impl AppState {
 
         Ok(())
     }
+
+    /// Audit an update of an entity with a field-level diff of its `before` and
+    /// `after` JSON, and record the diff in `audit_changes` so the entity's history
+    /// can be queried. Nothing is recorded when no field changed. The update has
+    /// already been committed, so a failed insert is logged rather than returned.
+    pub async fn audit_change(
+        &self,
+        entity_type: &str,
+        entity_id: uuid::Uuid,
+        actor_user_id: Option<uuid::Uuid>,
+        before: &Value,
+        after: &Value,
+    ) {
+        let changes = crate::audit::field_diff(before, after);
+        if changes.is_empty() {
+            return;
+        }
+        let changes = redact_sensitive_data(Value::Object(changes));
+
+        LogCategory::Audit.log(
+            tracing::Level::INFO,
+            &format!("update {entity_type}"),
+            Some(json!({
+                "entity_type": entity_type,
+                "entity_id": entity_id,
+                "actor_user_id": actor_user_id,
+                "changes": changes,
+            })),
+        );
+
+        let row = didhub_db::generated::audit_changes::AuditChangesRow {
+            id: uuid::Uuid::new_v4(),
+            entity_type: entity_type.to_string(),
+            entity_id,
+            actor_user_id,
+            action: "update".to_string(),
+            changes: changes.to_string(),
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        if let Err(e) =
+            didhub_db::generated::audit_changes::insert_audit_change(&*self.db_pool, &row).await
+        {
+            tracing::warn!(%e, entity_type, %entity_id, "failed to record audit change");
+        }
+    }
+
+    /// Store `snapshot`, the full state of an entity before an update, delete or
+    /// restore, as its next version so it can be restored later. Like
+    /// [`AppState::audit_change`], a failure is logged rather than returned.
+    pub async fn record_version(
+        &self,
+        entity_type: &str,
+        entity_id: uuid::Uuid,
+        action: &str,
+        actor_user_id: Option<uuid::Uuid>,
+        snapshot: &Value,
+    ) {
+        use didhub_db::generated::entity_versions as db_entity_versions;
+
+        let result = async {
+            let mut conn = self.db_pool.acquire().await?;
+            let version =
+                didhub_db::custom::entity_versions::next_version(&mut *conn, &entity_id).await?;
+            let row = db_entity_versions::EntityVersionsRow {
+                id: uuid::Uuid::new_v4(),
+                entity_type: entity_type.to_string(),
+                entity_id,
+                version,
+                action: action.to_string(),
+                snapshot: snapshot.to_string(),
+                actor_user_id,
+                created_at: chrono::Utc::now().to_rfc3339(),
+            };
+            db_entity_versions::insert_entity_version(&mut *conn, &row).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(%e, entity_type, %entity_id, action, "failed to record entity version");
+        }
+    }
 }
@@ -0,0 +1,122 @@
+//! Shared systems.
+//!
+//! A system is a user account holding the `system` role; its id is the `systemId`
+//! of its alters, affiliations and subsystems. Other accounts can be members of a
+//! system with a [`SystemRole`], so several people can manage one system. The
+//! system account itself counts as an owner of its system. Outside admins, only
+//! a system's owner and members see its data.
+
+use didhub_auth::auth::AuthContext;
+use didhub_db::DbPoolConnection;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::handlers::utils::{authentication_failed, ensure_system_user};
+
+/// What a member may do in a system, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SystemRole {
+    /// Reads the system's data.
+    Viewer,
+    /// Also changes its alters, affiliations and subsystems.
+    Editor,
+    /// Also manages its members.
+    Owner,
+}
+
+impl SystemRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Editor => "editor",
+            Self::Owner => "owner",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw {
+            "viewer" => Some(Self::Viewer),
+            "editor" => Some(Self::Editor),
+            "owner" => Some(Self::Owner),
+            _ => None,
+        }
+    }
+}
+
+/// The role of `user_id` in `system_id`: owner for the system account itself,
+/// otherwise its membership role, if any.
+pub async fn role_in(
+    conn: &mut DbPoolConnection,
+    system_id: &Uuid,
+    user_id: &Uuid,
+) -> Option<SystemRole> {
+    if system_id == user_id {
+        return Some(SystemRole::Owner);
+    }
+    match didhub_db::custom::system_members::role_of(&mut **conn, system_id, user_id).await {
+        Ok(role) => role.as_deref().and_then(SystemRole::parse),
+        Err(e) => {
+            tracing::warn!(%e, "failed to load system membership; treating as not a member");
+            None
+        }
+    }
+}
+
+/// Whether `user_id` holds at least `min` in `system_id`.
+pub async fn has_role(
+    conn: &mut DbPoolConnection,
+    system_id: &Uuid,
+    user_id: &Uuid,
+    min: SystemRole,
+) -> bool {
+    role_in(conn, system_id, user_id)
+        .await
+        .is_some_and(|role| role >= min)
+}
+
+/// Whether `auth` may read what `system_id` holds: admins, the system account
+/// and members with any role. Data without a system is visible to admins only.
+pub async fn can_view(
+    conn: &mut DbPoolConnection,
+    auth: &AuthContext,
+    system_id: Option<&Uuid>,
+) -> bool {
+    if auth.is_admin() {
+        return true;
+    }
+    match (system_id, auth.user_id) {
+        (Some(system_id), Some(user_id)) => role_in(conn, system_id, &user_id).await.is_some(),
+        _ => false,
+    }
+}
+
+/// The account whose systems bound the listings `auth` sees, or `None` for
+/// admins, who see every system.
+pub fn list_scope(auth: &AuthContext) -> Result<Option<Uuid>, ApiError> {
+    if auth.is_admin() {
+        return Ok(None);
+    }
+    auth.user_id.map(Some).ok_or_else(authentication_failed)
+}
+
+/// Require an admin, the system account itself (still holding the `system`
+/// role) or a member with at least [`SystemRole::Editor`] to change what
+/// `system_id` holds. Nobody else may change data without a system.
+pub async fn ensure_can_edit(
+    conn: &mut DbPoolConnection,
+    auth: &AuthContext,
+    system_id: Option<&Uuid>,
+    warning_context: &str,
+) -> Result<(), ApiError> {
+    if auth.is_admin() {
+        return Ok(());
+    }
+    let user_id = auth.user_id.ok_or_else(authentication_failed)?;
+    match system_id {
+        Some(system_id) if *system_id == user_id => {
+            ensure_system_user(&mut **conn, user_id, warning_context).await
+        }
+        Some(system_id) if has_role(conn, system_id, &user_id, SystemRole::Editor).await => Ok(()),
+        _ => Err(authentication_failed()),
+    }
+}
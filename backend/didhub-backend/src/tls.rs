@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use anyhow::Context;
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConfig};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+
+/// Build a rustls server config from the `[tls]` section.
+///
+/// When `client_ca_path` is set, clients must present a certificate signed by one of
+/// the CAs in that bundle (mutual TLS).
+pub fn build_rustls_config(cfg: &didhub_config::TlsConfig) -> anyhow::Result<Arc<ServerConfig>> {
+    let cert_path = cfg
+        .cert_path
+        .as_deref()
+        .context("tls.cert_path is not set")?;
+    let key_path = cfg.key_path.as_deref().context("tls.key_path is not set")?;
+
+    let certs = load_certs(cert_path)?;
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .with_context(|| format!("failed to read TLS private key from {key_path}"))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .context("failed to select TLS protocol versions")?;
+
+    let builder = match cfg.client_ca_path.as_deref() {
+        Some(ca_path) => {
+            let mut roots = RootCertStore::empty();
+            for cert in load_certs(ca_path)? {
+                roots
+                    .add(cert)
+                    .with_context(|| format!("invalid client CA certificate in {ca_path}"))?;
+            }
+            let verifier = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider)
+                .build()
+                .context("failed to build client certificate verifier")?;
+            builder.with_client_cert_verifier(verifier)
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    let mut config = builder
+        .with_single_cert(certs, key)
+        .context("TLS certificate and key do not match")?;
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+fn load_certs(path: &str) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .with_context(|| format!("failed to open certificate file {path}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("failed to parse certificates in {path}"))?;
+    anyhow::ensure!(!certs.is_empty(), "no certificates found in {path}");
+    Ok(certs)
+}
@@ -1,17 +1,107 @@
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{WithExportConfig, WithHttpConfig};
+use opentelemetry_sdk::trace::{Sampler, SdkTracer, SdkTracerProvider};
+use opentelemetry_sdk::Resource;
 use tracing_subscriber::prelude::*;
 
 /// Type alias for the reload handle returned by tracing initialization.
 pub type ReloadHandle =
     Arc<dyn Fn(tracing_subscriber::EnvFilter) -> Result<(), String> + Send + Sync>;
 
+/// Build the tracer exporting spans to the OTLP/HTTP endpoint of `cfg` and
+/// register its provider globally.
+fn otlp_tracer(cfg: &didhub_config::OtlpConfig) -> Result<(SdkTracer, SdkTracerProvider), String> {
+    let endpoint = cfg.endpoint.clone().unwrap_or_default();
+    let headers: HashMap<String, String> = cfg.headers.clone().into_iter().collect();
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_endpoint(endpoint)
+        .with_headers(headers)
+        .build()
+        .map_err(|e| e.to_string())?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+            cfg.sample_ratio,
+        ))))
+        .with_resource(
+            Resource::builder()
+                .with_service_name(cfg.service_name.clone())
+                .build(),
+        )
+        .build();
+    let tracer = provider.tracer("didhub");
+    opentelemetry::global::set_tracer_provider(provider.clone());
+    Ok((tracer, provider))
+}
+
+/// Export the spans still buffered and stop the OTLP exporter. The global
+/// provider keeps a reference, so it is never flushed by being dropped.
+pub fn shutdown_tracer_provider(provider: Option<SdkTracerProvider>) {
+    if let Some(provider) = provider {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("[SHUTDOWN] failed to flush OTLP spans: {e}");
+        }
+    }
+}
+
+/// Feeds the `sqlx::query` events sqlx emits after every statement into the
+/// `didhub_db_query_duration_seconds` histogram.
+struct DbQueryMetrics;
+
+#[derive(Default)]
+struct QueryFields {
+    summary: String,
+    elapsed_secs: Option<f64>,
+}
+
+impl tracing::field::Visit for QueryFields {
+    fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = Some(value);
+        }
+    }
+
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        if field.name() == "summary" {
+            self.summary = value.to_owned();
+        }
+    }
+
+    fn record_debug(&mut self, _field: &tracing::field::Field, _value: &dyn std::fmt::Debug) {}
+}
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for DbQueryMetrics {
+    fn on_event(
+        &self,
+        event: &tracing::Event<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let mut fields = QueryFields::default();
+        event.record(&mut fields);
+        if let Some(secs) = fields.elapsed_secs.filter(|s| s.is_finite() && *s >= 0.0) {
+            didhub_metrics::observe_db_query(&fields.summary, Duration::from_secs_f64(secs));
+        }
+    }
+}
+
 /// Initialize tracing from configuration.
 ///
-/// Returns a reload handle that can be used to update the log level at runtime.
+/// Spans are also exported to an OpenTelemetry collector when `otlp` names an
+/// endpoint. The log level only applies to the log output and the exported
+/// spans; statement events from sqlx always reach the query duration histogram.
+/// Returns a reload handle that can be used to update the log level at runtime,
+/// and the tracer provider to pass to [`shutdown_tracer_provider`] on exit.
 pub fn install_tracing_from_config(
     cfg: &didhub_config::LoggingConfig,
+    otlp: &didhub_config::OtlpConfig,
     cli_filter: Option<&str>,
-) -> Option<ReloadHandle> {
+) -> (Option<ReloadHandle>, Option<SdkTracerProvider>) {
+    use tracing_subscriber::filter::Targets;
     use tracing_subscriber::fmt::time::ChronoUtc;
 
     let env_filter_str = cli_filter
@@ -19,43 +109,74 @@ pub fn install_tracing_from_config(
         .or_else(|| std::env::var("RUST_LOG").ok())
         .unwrap_or_else(|| cfg.level.clone());
 
-    // Each branch creates its own reload layer since the subscriber types differ.
-    // We return a type-erased wrapper for runtime reloading.
-    if cfg.json {
-        let env_filter = tracing_subscriber::EnvFilter::new(&env_filter_str);
-        let (reload_layer, reload_handle) =
-            tracing_subscriber::reload::Layer::new(env_filter.clone());
+    let (tracer, provider, otlp_error) = if otlp.is_enabled() {
+        match otlp_tracer(otlp) {
+            Ok((tracer, provider)) => (Some(tracer), Some(provider), None),
+            Err(e) => (None, None, Some(e)),
+        }
+    } else {
+        (None, None, None)
+    };
 
-        tracing_subscriber::fmt()
+    let (env_filter, reload_handle) =
+        tracing_subscriber::reload::Layer::new(tracing_subscriber::EnvFilter::new(&env_filter_str));
+    let fmt_layer = if cfg.json {
+        tracing_subscriber::fmt::layer()
             .json()
-            .with_env_filter(env_filter)
-            .with_max_level(tracing::Level::TRACE)
             .with_timer(ChronoUtc::rfc_3339())
-            .finish()
-            .with(reload_layer)
-            .init();
-
-        Some(Arc::new(move |filter| {
-            reload_handle
-                .reload(filter)
-                .map_err(|e| format!("reload failed: {e}"))
-        }))
+            .boxed()
     } else {
-        let env_filter = tracing_subscriber::EnvFilter::new(&env_filter_str);
-        let (reload_layer, reload_handle) =
-            tracing_subscriber::reload::Layer::new(env_filter.clone());
-
-        tracing_subscriber::fmt()
-            .with_env_filter(env_filter)
-            .with_max_level(tracing::Level::TRACE)
-            .finish()
-            .with(reload_layer)
-            .init();
-
-        Some(Arc::new(move |filter| {
-            reload_handle
-                .reload(filter)
-                .map_err(|e| format!("reload failed: {e}"))
-        }))
+        tracing_subscriber::fmt::layer().boxed()
+    };
+    let otel_layer = tracer.map(|t| tracing_opentelemetry::layer().with_tracer(t));
+
+    tracing_subscriber::registry()
+        .with(fmt_layer.and_then(otel_layer).with_filter(env_filter))
+        .with(
+            DbQueryMetrics
+                .with_filter(Targets::new().with_target("sqlx::query", tracing::Level::TRACE)),
+        )
+        .init();
+    if let Some(e) = otlp_error {
+        tracing::warn!(error = %e, "OTLP exporter unavailable; spans are not exported");
+    }
+
+    let reload: ReloadHandle = Arc::new(move |filter| {
+        reload_handle
+            .reload(filter)
+            .map_err(|e| format!("reload failed: {e}"))
+    });
+    (Some(reload), provider)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::filter::Targets;
+
+    #[test]
+    fn sqlx_statement_events_feed_the_query_histogram() {
+        let subscriber = tracing_subscriber::registry().with(
+            DbQueryMetrics
+                .with_filter(Targets::new().with_target("sqlx::query", tracing::Level::TRACE)),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            let summary = String::from("delete from trash_entries where …");
+            tracing::debug!(target: "sqlx::query", summary, elapsed_secs = 0.002);
+            tracing::debug!(target: "didhub", summary = "select", elapsed_secs = 0.002);
+        });
+
+        let text = didhub_metrics::render();
+        let series = |statement: &str| {
+            format!(
+                "{}_count{{statement=\"{statement}\"}}",
+                didhub_metrics::DB_QUERY_DURATION
+            )
+        };
+        assert!(
+            text.contains(&format!("{} 1\n", series("delete"))),
+            "{text}"
+        );
+        assert!(!text.contains(&series("select")));
     }
 }
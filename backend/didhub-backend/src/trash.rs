@@ -0,0 +1,68 @@
+//! Trash for deleted alters and affiliations.
+//!
+//! Deleting an alter or affiliation only sets its `deleted_at`; it disappears from
+//! listings and lookups but can be restored until it is older than
+//! `trash.retention_days`. The [`TRASH_PURGE_JOB`] job then deletes it for good.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use didhub_db::custom::trash;
+use didhub_job_queue::{async_trait, JobExecutor, JobQueueError};
+use serde_json::Value;
+
+use crate::state::AppState;
+
+/// Job type of the executor purging expired trash.
+pub const TRASH_PURGE_JOB: &str = "trash.purge";
+
+/// When an entity deleted at `deleted_at` (RFC 3339) will be purged, if parseable.
+pub fn purge_at(deleted_at: &str, retention: Duration) -> Option<String> {
+    let deleted_at = chrono::DateTime::parse_from_rfc3339(deleted_at).ok()?;
+    let retention = chrono::Duration::from_std(retention).ok()?;
+    Some((deleted_at + retention).with_timezone(&Utc).to_rfc3339())
+}
+
+/// Delete alters and affiliations trashed longer than `retention` ago. Returns the
+/// number deleted.
+pub async fn purge_expired(
+    pool: &didhub_db::DbPool,
+    retention: Duration,
+) -> Result<u64, sqlx::Error> {
+    let retention = chrono::Duration::from_std(retention).unwrap_or(chrono::Duration::MAX);
+    let cutoff = Utc::now()
+        .checked_sub_signed(retention)
+        .unwrap_or(chrono::DateTime::<Utc>::MIN_UTC)
+        .to_rfc3339();
+    let mut deleted = 0;
+    for table in trash::TABLES {
+        deleted += trash::purge(pool, table, &cutoff).await?;
+    }
+    Ok(deleted)
+}
+
+/// Executor for [`TRASH_PURGE_JOB`]; schedule it under `[scheduler.jobs."trash.purge"]`.
+pub struct TrashPurgeExecutor {
+    state: AppState,
+}
+
+impl TrashPurgeExecutor {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+}
+
+#[async_trait]
+impl JobExecutor for TrashPurgeExecutor {
+    fn job_type(&self) -> &str {
+        TRASH_PURGE_JOB
+    }
+
+    async fn execute(&self, _payload: Value) -> Result<(), JobQueueError> {
+        let deleted = purge_expired(&self.state.db_pool, self.state.trash_retention())
+            .await
+            .map_err(|e| JobQueueError::ExecutionFailed(e.to_string()))?;
+        tracing::info!(deleted, "purged expired trash");
+        Ok(())
+    }
+}
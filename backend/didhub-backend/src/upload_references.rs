@@ -0,0 +1,155 @@
+//! Which stored files are in use.
+//!
+//! Avatars and alter image lists name stored files by id. The handlers changing
+//! them record those references in `upload_references`, so upload deletion can
+//! refuse to remove a file still in use and the [`UPLOADS_GC_JOB`] job can find
+//! unused files with a query instead of reading every user and alter.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use didhub_db::custom::upload_references;
+use didhub_db::generated::alters as db_alters;
+use didhub_db::generated::uploads as db_uploads;
+use didhub_db::generated::users as db_users;
+use didhub_db::DbPoolConnection;
+use didhub_job_queue::{async_trait, JobExecutor, JobQueueError};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::state::AppState;
+
+/// Job type of the executor deleting unused stored files.
+pub const UPLOADS_GC_JOB: &str = "uploads.gc";
+
+/// Unused stored files looked up at once.
+const GC_BATCH: i64 = 500;
+
+/// The stored file id in `reference`, which is either the id itself or a URL
+/// ending with it.
+pub fn stored_file_id(reference: &str) -> Option<Uuid> {
+    let last = reference.rsplit('/').next()?;
+    Uuid::parse_str(last.split('?').next()?).ok()
+}
+
+/// Record the stored files in the alter's images.
+pub async fn sync_alter(
+    conn: &mut DbPoolConnection,
+    row: &db_alters::AltersRow,
+) -> Result<(), sqlx::Error> {
+    let images: Vec<String> = serde_json::from_str(&row.images).unwrap_or_default();
+    let ids: Vec<Uuid> = images.iter().filter_map(|i| stored_file_id(i)).collect();
+    upload_references::replace(conn, upload_references::ALTER, &row.id, &ids).await
+}
+
+/// Record the stored file used as the user's avatar, if any.
+pub async fn sync_avatar(
+    conn: &mut DbPoolConnection,
+    user_id: &Uuid,
+    avatar: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let ids: Vec<Uuid> = avatar.and_then(stored_file_id).into_iter().collect();
+    upload_references::replace(conn, upload_references::USER, user_id, &ids).await
+}
+
+/// Record the references of every user and alter, trashed alters included.
+pub async fn rebuild(pool: &didhub_db::DbPool) -> Result<(), sqlx::Error> {
+    let mut conn = pool.acquire().await?;
+    for user in db_users::list_all(&mut *conn).await? {
+        sync_avatar(&mut conn, &user.id, user.avatar.as_deref()).await?;
+    }
+    for alter in db_alters::list_all(&mut *conn).await? {
+        sync_alter(&mut conn, &alter).await?;
+    }
+    Ok(())
+}
+
+/// Whether removing `upload` leaves every avatar and alter image in place: the
+/// file is unused or another upload keeps it.
+pub async fn can_delete(
+    conn: &mut DbPoolConnection,
+    upload: &db_uploads::UploadsRow,
+) -> Result<bool, sqlx::Error> {
+    if upload_references::count_for_file(&mut **conn, &upload.stored_file_id).await? == 0 {
+        return Ok(true);
+    }
+    let uploads: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM uploads WHERE stored_file_id = ?")
+        .bind(upload.stored_file_id)
+        .fetch_one(&mut **conn)
+        .await?;
+    Ok(uploads > 1)
+}
+
+/// Delete the stored files that no upload, avatar or alter uses, with their
+/// content in `uploads_dir`. Returns the number deleted.
+pub async fn collect_unused(
+    pool: &didhub_db::DbPool,
+    uploads_dir: &Path,
+) -> Result<u64, sqlx::Error> {
+    upload_references::prune_missing_owners(pool).await?;
+    let mut deleted = 0;
+    loop {
+        let unused = upload_references::unused_files(pool, GC_BATCH).await?;
+        let mut progressed = false;
+        for id in &unused {
+            // Skipped when an upload started using it since the lookup
+            if !upload_references::delete_if_unused(pool, id).await? {
+                continue;
+            }
+            progressed = true;
+            deleted += 1;
+            let path = uploads_dir.join(id.to_string());
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    tracing::warn!(%e, path = %path.display(), "failed to remove stored file")
+                }
+            }
+        }
+        if !progressed || (unused.len() as i64) < GC_BATCH {
+            return Ok(deleted);
+        }
+    }
+}
+
+/// Executor for [`UPLOADS_GC_JOB`]; schedule it under `[scheduler.jobs."uploads.gc"]`.
+/// The first run of each process rebuilds the references, picking up data written
+/// before they were tracked; later runs only use the reference table.
+pub struct UploadsGcExecutor {
+    state: AppState,
+    rebuilt: AtomicBool,
+}
+
+impl UploadsGcExecutor {
+    pub fn new(state: AppState) -> Self {
+        Self {
+            state,
+            rebuilt: AtomicBool::new(false),
+        }
+    }
+}
+
+#[async_trait]
+impl JobExecutor for UploadsGcExecutor {
+    fn job_type(&self) -> &str {
+        UPLOADS_GC_JOB
+    }
+
+    async fn execute(&self, _payload: Value) -> Result<(), JobQueueError> {
+        let failed = |e: sqlx::Error| JobQueueError::ExecutionFailed(e.to_string());
+        if !self.rebuilt.load(Ordering::Acquire) {
+            rebuild(&self.state.db_pool).await.map_err(failed)?;
+            self.rebuilt.store(true, Ordering::Release);
+        }
+        let uploads_dir = didhub_config::load_config::<&Path>(None)
+            .unwrap_or_default()
+            .uploads
+            .directory;
+        let deleted = collect_unused(&self.state.db_pool, Path::new(&uploads_dir))
+            .await
+            .map_err(failed)?;
+        tracing::info!(deleted, "deleted unused stored files");
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
+use axum::http::HeaderMap;
+use didhub_auth::auth::RevocationStore;
 use didhub_auth::TestAuthenticator;
 use didhub_backend::handlers::auth;
+use didhub_backend::revocation::DbRevocationStore;
 use didhub_backend::state::AppState;
 use didhub_db::create_pool;
 use didhub_db::DbConnectionConfig;
@@ -9,6 +12,17 @@ use didhub_updates::UpdateCoordinator;
 use sqlx::Executor;
 use std::sync::Arc;
 
+/// Revocation store over the `revoked_tokens` and `token_revocation_cutoffs` tables.
+async fn revocation_store(pool: &didhub_db::DbPool) -> Arc<DbRevocationStore> {
+    for ddl in [
+        "CREATE TABLE IF NOT EXISTS revoked_tokens (jti TEXT PRIMARY KEY, expires_at TEXT NOT NULL)",
+        "CREATE TABLE IF NOT EXISTS token_revocation_cutoffs (owner_id BLOB PRIMARY KEY, revoked_before_ms INTEGER NOT NULL)",
+    ] {
+        pool.execute(ddl).await.expect("create revocation tables");
+    }
+    Arc::new(DbRevocationStore::new(Arc::new(pool.clone())))
+}
+
 #[tokio::test]
 async fn login_me_logout_flow() {
     // Create an in-memory sqlite DB and ensure users table
@@ -62,7 +76,9 @@ async fn login_me_logout_flow() {
     let body = Some(axum::Json(
         serde_json::json!({"username":"testuser","password": didhub_auth::auth::sha256_hex(password)}),
     ));
-    let resp = auth::login::login(ext.clone(), body).await.expect("login");
+    let resp = auth::login::login(ext.clone(), HeaderMap::new(), body)
+        .await
+        .expect("login");
     // Expect 200 and Set-Cookie header present
     let headers = resp.headers();
     assert!(headers.get(&axum::http::header::SET_COOKIE).is_some());
@@ -76,3 +92,1457 @@ async fn login_me_logout_flow() {
         .to_string();
     assert!(cookie.contains("didhub_session"));
 }
+
+#[tokio::test]
+async fn login_upgrades_legacy_bcrypt_hash() {
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    sqlx::query(r#"CREATE TABLE users (id BLOB PRIMARY KEY, username TEXT, password_hash TEXT, created_at TEXT, updated_at TEXT, roles TEXT, settings TEXT, about_me TEXT, avatar TEXT, must_change_password INTEGER, last_login_at TEXT, display_name TEXT)"#)
+        .execute(&pool)
+        .await
+        .expect("create table");
+
+    let client_hash = didhub_auth::auth::sha256_hex("legacy-secret");
+    let legacy_hash = bcrypt::hash(&client_hash, 4).expect("bcrypt");
+    let id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query("INSERT INTO users (id, username, password_hash, created_at, updated_at, roles, settings) VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(id)
+        .bind("legacyuser")
+        .bind(&legacy_hash)
+        .bind(&now)
+        .bind(&now)
+        .bind("[\"user\"]")
+        .bind("{}")
+        .execute(&pool)
+        .await
+        .expect("insert user");
+
+    let state = AppState::new(
+        pool.clone(),
+        Arc::new(TestAuthenticator::default()),
+        JobQueueClient::new(),
+        UpdateCoordinator::new(),
+        None,
+    );
+    let ext = axum::extract::Extension(Arc::new(state));
+    std::env::set_var("DIDHUB_JWT_SECRET", "test-secret");
+
+    let login = || {
+        auth::login::login(
+            ext.clone(),
+            HeaderMap::new(),
+            Some(axum::Json(
+                serde_json::json!({"username": "legacyuser", "password": client_hash}),
+            )),
+        )
+    };
+    login().await.expect("login with bcrypt hash");
+
+    let stored: String = sqlx::query_scalar("SELECT password_hash FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .expect("select hash");
+    assert!(stored.starts_with("$argon2id$"), "hash was not upgraded");
+
+    login().await.expect("login with upgraded hash");
+}
+
+#[tokio::test]
+async fn logout_and_revoke_sessions_invalidate_tokens() {
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    sqlx::query(r#"CREATE TABLE users (id BLOB PRIMARY KEY, username TEXT, password_hash TEXT, created_at TEXT, updated_at TEXT, roles TEXT, settings TEXT, about_me TEXT, avatar TEXT, must_change_password INTEGER, last_login_at TEXT, display_name TEXT)"#)
+        .execute(&pool)
+        .await
+        .expect("create table");
+
+    let password_hash =
+        didhub_auth::auth::hash_client_password(&didhub_auth::auth::sha256_hex("secret123"))
+            .expect("hash");
+    let id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query("INSERT INTO users (id, username, password_hash, created_at, updated_at, roles, settings) VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(id)
+        .bind("revokeuser")
+        .bind(&password_hash)
+        .bind(&now)
+        .bind(&now)
+        .bind("[\"user\"]")
+        .bind("{}")
+        .execute(&pool)
+        .await
+        .expect("insert user");
+
+    std::env::set_var("DIDHUB_JWT_SECRET", "test-secret");
+    let cache = didhub_cache::AppCache::memory();
+    let revocations = revocation_store(&pool).await;
+    let authenticator = Arc::new(
+        didhub_auth::auth::JwtAuthenticator::new_hs256("test-secret")
+            .with_revocation_store(revocations),
+    ) as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let state = Arc::new(
+        AppState::new(
+            pool.clone(),
+            authenticator,
+            JobQueueClient::new(),
+            UpdateCoordinator::new(),
+            None,
+        )
+        .with_cache(cache),
+    );
+    let ext = axum::extract::Extension(state.clone());
+
+    let login = |ext: axum::extract::Extension<Arc<AppState>>| async move {
+        let body = Some(axum::Json(serde_json::json!({
+            "username": "revokeuser",
+            "password": didhub_auth::auth::sha256_hex("secret123"),
+        })));
+        let resp = auth::login::login(ext, HeaderMap::new(), body)
+            .await
+            .expect("login");
+        let set_cookie = resp
+            .headers()
+            .get(axum::http::header::SET_COOKIE)
+            .expect("cookie")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let cookie = set_cookie.split(';').next().unwrap().to_string();
+        let mut headers = HeaderMap::new();
+        headers.insert(axum::http::header::COOKIE, cookie.parse().unwrap());
+        headers
+    };
+
+    // Logout revokes the presented token only.
+    let first = login(ext.clone()).await;
+    let second = login(ext.clone()).await;
+    assert!(auth::utils::authenticate_required(&state, &first)
+        .await
+        .is_ok());
+    let _ = auth::logout::logout(ext.clone(), first.clone()).await;
+    assert!(auth::utils::authenticate_required(&state, &first)
+        .await
+        .is_err());
+    assert!(auth::utils::authenticate_required(&state, &second)
+        .await
+        .is_ok());
+
+    // Revoking sessions invalidates every remaining token of the user.
+    let path = axum::extract::Path(
+        [("userId".to_string(), id.to_string())]
+            .into_iter()
+            .collect::<std::collections::HashMap<_, _>>(),
+    );
+    let axum::Json(body) = didhub_backend::handlers::users::revoke_sessions::revoke_sessions(
+        ext.clone(),
+        second.clone(),
+        path,
+    )
+    .await
+    .expect("revoke sessions");
+    assert_eq!(body["revoked"], true);
+    assert!(auth::utils::authenticate_required(&state, &second)
+        .await
+        .is_err());
+
+    // A token issued straight after the revocation is not caught by it.
+    let third = login(ext.clone()).await;
+    assert!(auth::utils::authenticate_required(&state, &third)
+        .await
+        .is_ok());
+
+    // Revocations live in the database, not the cache.
+    let restarted = Arc::new(AppState::new(
+        pool.clone(),
+        Arc::new(
+            didhub_auth::auth::JwtAuthenticator::new_hs256("test-secret")
+                .with_revocation_store(revocation_store(&pool).await),
+        ),
+        JobQueueClient::new(),
+        UpdateCoordinator::new(),
+        None,
+    ));
+    assert!(auth::utils::authenticate_required(&restarted, &first)
+        .await
+        .is_err());
+    assert!(auth::utils::authenticate_required(&restarted, &second)
+        .await
+        .is_err());
+
+    // Tokens still accepted within the expiry leeway can be revoked.
+    let store = state.revocation_store();
+    let just_expired = chrono::Utc::now().timestamp() as u64 - 5;
+    store
+        .revoke_token("just-expired", Some(just_expired))
+        .await
+        .expect("revoke");
+    assert!(store
+        .is_token_revoked("just-expired")
+        .await
+        .expect("lookup"));
+}
+
+#[tokio::test]
+async fn sessions_can_be_listed_and_revoked() {
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    sqlx::query(r#"CREATE TABLE users (id BLOB PRIMARY KEY, username TEXT, password_hash TEXT, created_at TEXT, updated_at TEXT, roles TEXT, settings TEXT, about_me TEXT, avatar TEXT, must_change_password INTEGER, last_login_at TEXT, display_name TEXT)"#)
+        .execute(&pool)
+        .await
+        .expect("create table");
+    sqlx::query(r#"CREATE TABLE sessions (id BLOB PRIMARY KEY, user_id BLOB NOT NULL, token_id TEXT NOT NULL UNIQUE, user_agent TEXT, ip_address TEXT, created_at TEXT NOT NULL, last_seen_at TEXT NOT NULL, expires_at TEXT NOT NULL)"#)
+        .execute(&pool)
+        .await
+        .expect("create sessions table");
+
+    let password_hash =
+        didhub_auth::auth::hash_client_password(&didhub_auth::auth::sha256_hex("secret123"))
+            .expect("hash");
+    let id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query("INSERT INTO users (id, username, password_hash, created_at, updated_at, roles, settings) VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(id)
+        .bind("sessionuser")
+        .bind(&password_hash)
+        .bind(&now)
+        .bind(&now)
+        .bind("[\"user\"]")
+        .bind("{}")
+        .execute(&pool)
+        .await
+        .expect("insert user");
+
+    std::env::set_var("DIDHUB_JWT_SECRET", "test-secret");
+    let cache = didhub_cache::AppCache::memory();
+    let revocations = revocation_store(&pool).await;
+    let authenticator = Arc::new(
+        didhub_auth::auth::JwtAuthenticator::new_hs256("test-secret")
+            .with_revocation_store(revocations),
+    ) as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let state = Arc::new(
+        AppState::new(
+            pool.clone(),
+            authenticator,
+            JobQueueClient::new(),
+            UpdateCoordinator::new(),
+            None,
+        )
+        .with_cache(cache),
+    );
+    let ext = axum::extract::Extension(state.clone());
+
+    let login = |ext: axum::extract::Extension<Arc<AppState>>, agent: &'static str| async move {
+        let mut request_headers = HeaderMap::new();
+        request_headers.insert(axum::http::header::USER_AGENT, agent.parse().unwrap());
+        request_headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        let body = Some(axum::Json(serde_json::json!({
+            "username": "sessionuser",
+            "password": didhub_auth::auth::sha256_hex("secret123"),
+        })));
+        let resp = auth::login::login(ext, request_headers, body)
+            .await
+            .expect("login");
+        let set_cookie = resp
+            .headers()
+            .get(axum::http::header::SET_COOKIE)
+            .expect("cookie")
+            .to_str()
+            .unwrap()
+            .to_string();
+        let cookie = set_cookie.split(';').next().unwrap().to_string();
+        let mut headers = HeaderMap::new();
+        headers.insert(axum::http::header::COOKIE, cookie.parse().unwrap());
+        headers
+    };
+    let list = |headers: HeaderMap| {
+        let ext = ext.clone();
+        async move {
+            let axum::Json(body) = didhub_backend::handlers::sessions::list::list(ext, headers)
+                .await
+                .expect("list sessions");
+            body["items"].as_array().cloned().unwrap_or_default()
+        }
+    };
+
+    let laptop = login(ext.clone(), "laptop").await;
+    let phone = login(ext.clone(), "phone").await;
+    let tablet = login(ext.clone(), "tablet").await;
+
+    let sessions = list(laptop.clone()).await;
+    assert_eq!(sessions.len(), 3);
+    let current: Vec<_> = sessions.iter().filter(|s| s["current"] == true).collect();
+    assert_eq!(current.len(), 1);
+    assert_eq!(current[0]["userAgent"], "laptop");
+    assert_eq!(current[0]["ipAddress"], "203.0.113.7");
+
+    // Revoking one session signs out only that session.
+    let phone_session = sessions
+        .iter()
+        .find(|s| s["userAgent"] == "phone")
+        .expect("phone session");
+    let path = axum::extract::Path(
+        [(
+            "sessionId".to_string(),
+            phone_session["id"].as_str().unwrap().to_string(),
+        )]
+        .into_iter()
+        .collect::<std::collections::HashMap<_, _>>(),
+    );
+    let axum::Json(body) =
+        didhub_backend::handlers::sessions::delete::delete(ext.clone(), laptop.clone(), path)
+            .await
+            .expect("revoke session");
+    assert_eq!(body["revoked"], true);
+    assert!(auth::utils::authenticate_required(&state, &phone)
+        .await
+        .is_err());
+    assert!(auth::utils::authenticate_required(&state, &tablet)
+        .await
+        .is_ok());
+
+    // Revoking the others keeps only the calling session.
+    let axum::Json(body) = didhub_backend::handlers::sessions::revoke_others::revoke_others(
+        ext.clone(),
+        laptop.clone(),
+    )
+    .await
+    .expect("revoke others");
+    assert_eq!(body["revoked"], 1);
+    assert!(auth::utils::authenticate_required(&state, &tablet)
+        .await
+        .is_err());
+    assert!(auth::utils::authenticate_required(&state, &laptop)
+        .await
+        .is_ok());
+    assert_eq!(list(laptop).await.len(), 1);
+}
+
+#[tokio::test]
+async fn api_keys_authenticate_until_deleted() {
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    sqlx::query(r#"CREATE TABLE users (id BLOB PRIMARY KEY, username TEXT, password_hash TEXT, created_at TEXT, updated_at TEXT, roles TEXT, settings TEXT, about_me TEXT, avatar TEXT, must_change_password INTEGER, last_login_at TEXT, display_name TEXT)"#)
+        .execute(&pool)
+        .await
+        .expect("create users");
+    sqlx::query(r#"CREATE TABLE api_keys (id BLOB PRIMARY KEY, user_id BLOB NOT NULL, name TEXT NOT NULL, key_prefix TEXT NOT NULL UNIQUE, key_hash TEXT NOT NULL, scopes TEXT NOT NULL, expires_at TEXT, last_used_at TEXT, created_at TEXT NOT NULL)"#)
+        .execute(&pool)
+        .await
+        .expect("create api_keys");
+
+    let id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query("INSERT INTO users (id, username, password_hash, created_at, updated_at, roles, settings) VALUES (?, ?, ?, ?, ?, ?, ?)")
+        .bind(id)
+        .bind("keyuser")
+        .bind("unused")
+        .bind(&now)
+        .bind(&now)
+        .bind("[\"user\"]")
+        .bind("{}")
+        .execute(&pool)
+        .await
+        .expect("insert user");
+
+    let jwt = Arc::new(didhub_auth::auth::JwtAuthenticator::new_hs256(
+        "test-secret",
+    )) as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let keys = Arc::new(didhub_backend::api_keys::DbApiKeyStore::new(Arc::new(
+        pool.clone(),
+    )));
+    let authenticator =
+        Arc::new(didhub_auth::auth::ApiKeyAuthenticator::new(keys).with_fallback(jwt))
+            as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let state = Arc::new(AppState::new(
+        pool.clone(),
+        authenticator,
+        JobQueueClient::new(),
+        UpdateCoordinator::new(),
+        None,
+    ));
+    let ext = axum::extract::Extension(state.clone());
+
+    let exp = chrono::Utc::now().timestamp() + 3600;
+    let session = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &serde_json::json!({"sub": id.to_string(), "exp": exp, "scopes": ["user"]}),
+        &jsonwebtoken::EncodingKey::from_secret(b"test-secret"),
+    )
+    .expect("encode");
+    let mut session_headers = HeaderMap::new();
+    session_headers.insert(
+        axum::http::header::AUTHORIZATION,
+        format!("Bearer {}", session).parse().unwrap(),
+    );
+
+    // Scopes beyond the caller's are refused.
+    let denied = didhub_backend::handlers::api_keys::create::create(
+        ext.clone(),
+        session_headers.clone(),
+        Some(axum::Json(
+            serde_json::json!({"name": "ci", "scopes": ["admin"]}),
+        )),
+    )
+    .await;
+    assert!(denied.is_err());
+
+    let axum::Json(created) = didhub_backend::handlers::api_keys::create::create(
+        ext.clone(),
+        session_headers.clone(),
+        Some(axum::Json(
+            serde_json::json!({"name": "ci", "expiresInDays": 30}),
+        )),
+    )
+    .await
+    .expect("create key");
+    let key = created["key"].as_str().expect("key").to_string();
+    assert_eq!(created["scopes"], serde_json::json!(["user"]));
+
+    let mut key_headers = HeaderMap::new();
+    key_headers.insert(
+        axum::http::header::AUTHORIZATION,
+        format!("Bearer {}", key).parse().unwrap(),
+    );
+    let ctx = auth::utils::authenticate_required(&state, &key_headers)
+        .await
+        .expect("key authenticates");
+    assert_eq!(ctx.user_id, Some(id));
+
+    // A key cannot be exchanged for a session token.
+    assert!(matches!(
+        didhub_backend::handlers::auth::refresh::refresh(ext.clone(), key_headers.clone()).await,
+        Err(didhub_backend::error::ApiError::Forbidden(_))
+    ));
+
+    let axum::Json(listed) =
+        didhub_backend::handlers::api_keys::list::list(ext.clone(), session_headers.clone())
+            .await
+            .expect("list keys");
+    assert_eq!(listed["items"].as_array().map(Vec::len), Some(1));
+    assert!(listed["items"][0]["lastUsedAt"].is_string());
+    assert!(listed["items"][0].get("key").is_none());
+
+    // A key cannot be used to manage keys.
+    let path: std::collections::HashMap<_, _> = [(
+        "keyId".to_string(),
+        created["id"].as_str().unwrap().to_string(),
+    )]
+    .into_iter()
+    .collect();
+    assert!(didhub_backend::handlers::api_keys::delete::delete(
+        ext.clone(),
+        key_headers.clone(),
+        axum::extract::Path(path.clone())
+    )
+    .await
+    .is_err());
+
+    let axum::Json(deleted) = didhub_backend::handlers::api_keys::delete::delete(
+        ext.clone(),
+        session_headers,
+        axum::extract::Path(path),
+    )
+    .await
+    .expect("delete key");
+    assert_eq!(deleted["deleted"], true);
+    assert!(auth::utils::authenticate_required(&state, &key_headers)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn admins_can_impersonate_users_with_marked_tokens() {
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    sqlx::query(r#"CREATE TABLE users (id BLOB PRIMARY KEY, username TEXT, password_hash TEXT, created_at TEXT, updated_at TEXT, roles TEXT, settings TEXT, about_me TEXT, avatar TEXT, must_change_password INTEGER, last_login_at TEXT, display_name TEXT)"#)
+        .execute(&pool)
+        .await
+        .expect("create users");
+
+    let admin_id = uuid::Uuid::new_v4();
+    let member_id = uuid::Uuid::new_v4();
+    let other_admin_id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now().to_rfc3339();
+    for (id, name, roles) in [
+        (admin_id, "admin", r#"["admin","user"]"#),
+        (member_id, "member", r#"["user"]"#),
+        (other_admin_id, "other", r#"["admin"]"#),
+    ] {
+        sqlx::query("INSERT INTO users (id, username, password_hash, created_at, updated_at, roles, settings) VALUES (?, ?, 'unused', ?, ?, ?, '{}')")
+            .bind(id)
+            .bind(name)
+            .bind(&now)
+            .bind(&now)
+            .bind(roles)
+            .execute(&pool)
+            .await
+            .expect("insert user");
+    }
+
+    std::env::set_var("DIDHUB_JWT_SECRET", "test-secret");
+    let authenticator = Arc::new(didhub_auth::auth::JwtAuthenticator::new_hs256(
+        "test-secret",
+    )) as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let state = Arc::new(AppState::new(
+        pool.clone(),
+        authenticator,
+        JobQueueClient::new(),
+        UpdateCoordinator::new(),
+        None,
+    ));
+    let ext = axum::extract::Extension(state.clone());
+
+    let bearer = |token: &str| {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
+    };
+    let admin_token = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &serde_json::json!({
+            "sub": admin_id.to_string(),
+            "exp": chrono::Utc::now().timestamp() + 3600,
+            "scopes": ["admin", "user"],
+        }),
+        &jsonwebtoken::EncodingKey::from_secret(b"test-secret"),
+    )
+    .expect("encode");
+    let impersonate = |headers: HeaderMap, target: uuid::Uuid| {
+        let path = axum::extract::Path(
+            [("userId".to_string(), target.to_string())]
+                .into_iter()
+                .collect::<std::collections::HashMap<_, _>>(),
+        );
+        didhub_backend::handlers::users::impersonate::impersonate(
+            ext.clone(),
+            headers,
+            path,
+            Some(axum::Json(serde_json::json!({ "ttlMinutes": 5 }))),
+        )
+    };
+
+    let axum::Json(body) = impersonate(bearer(&admin_token), member_id)
+        .await
+        .expect("impersonate member");
+    assert_eq!(body["actorId"], admin_id.to_string());
+    let token = body["token"].as_str().expect("token").to_string();
+
+    let ctx = auth::utils::authenticate_required(&state, &bearer(&token))
+        .await
+        .expect("impersonation token authenticates");
+    assert_eq!(ctx.user_id, Some(member_id));
+    assert_eq!(ctx.actor, Some(admin_id));
+    assert_eq!(ctx.scopes, vec!["user".to_string()]);
+    let remaining = ctx.expires_at.unwrap() as i64 - chrono::Utc::now().timestamp();
+    assert!(remaining <= 5 * 60);
+
+    // Admins need manage_admins to impersonate another admin.
+    assert!(matches!(
+        impersonate(bearer(&admin_token), other_admin_id).await,
+        Err(didhub_backend::error::ApiError::Forbidden(_))
+    ));
+    assert!(matches!(
+        impersonate(bearer(&admin_token), admin_id).await,
+        Err(didhub_backend::error::ApiError::BadRequest(_))
+    ));
+
+    // The impersonation token can neither be refreshed into a full session nor
+    // used to mint an API key for the member.
+    assert!(matches!(
+        didhub_backend::handlers::auth::refresh::refresh(ext.clone(), bearer(&token)).await,
+        Err(didhub_backend::error::ApiError::Forbidden(_))
+    ));
+    assert!(matches!(
+        didhub_backend::handlers::api_keys::create::create(
+            ext.clone(),
+            bearer(&token),
+            Some(axum::Json(serde_json::json!({"name": "backdoor"}))),
+        )
+        .await,
+        Err(didhub_backend::error::ApiError::Forbidden(_))
+    ));
+}
+
+#[derive(Default)]
+struct CapturingMailer {
+    sent: std::sync::Mutex<Vec<didhub_backend::mailer::OutgoingEmail>>,
+}
+
+#[async_trait::async_trait]
+impl didhub_backend::mailer::Mailer for CapturingMailer {
+    async fn send(
+        &self,
+        email: didhub_backend::mailer::OutgoingEmail,
+    ) -> Result<(), didhub_backend::mailer::MailError> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn password_reset_tokens_are_emailed_and_single_use() {
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    for ddl in [
+        r#"CREATE TABLE users (id BLOB PRIMARY KEY, username TEXT, password_hash TEXT, created_at TEXT, updated_at TEXT, roles TEXT, settings TEXT, about_me TEXT, avatar TEXT, must_change_password INTEGER, last_login_at TEXT, display_name TEXT)"#,
+        r#"CREATE TABLE sessions (id BLOB PRIMARY KEY, user_id BLOB NOT NULL, token_id TEXT NOT NULL UNIQUE, user_agent TEXT, ip_address TEXT, created_at TEXT NOT NULL, last_seen_at TEXT NOT NULL, expires_at TEXT NOT NULL)"#,
+        r#"CREATE TABLE user_emails (user_id BLOB PRIMARY KEY, email TEXT NOT NULL UNIQUE, created_at TEXT NOT NULL, updated_at TEXT NOT NULL)"#,
+        r#"CREATE TABLE password_reset_tokens (id BLOB PRIMARY KEY, user_id BLOB NOT NULL, token_hash TEXT NOT NULL UNIQUE, requested_ip TEXT, created_at TEXT NOT NULL, expires_at TEXT NOT NULL, used_at TEXT)"#,
+    ] {
+        sqlx::query(ddl).execute(&pool).await.expect("create table");
+    }
+
+    let old_password = didhub_auth::auth::sha256_hex("old-secret-1");
+    let new_password = didhub_auth::auth::sha256_hex("new-secret-2");
+    let id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query("INSERT INTO users (id, username, password_hash, created_at, updated_at, roles, settings) VALUES (?, 'forgetful', ?, ?, ?, '[\"user\"]', '{}')")
+        .bind(id)
+        .bind(didhub_auth::auth::hash_client_password(&old_password).expect("hash"))
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("insert user");
+
+    std::env::set_var("DIDHUB_JWT_SECRET", "test-secret");
+    let cache = didhub_cache::AppCache::memory();
+    let authenticator = Arc::new(
+        didhub_auth::auth::JwtAuthenticator::new_hs256("test-secret")
+            .with_revocation_store(revocation_store(&pool).await),
+    ) as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let state = Arc::new(
+        AppState::new(
+            pool.clone(),
+            authenticator,
+            JobQueueClient::new(),
+            UpdateCoordinator::new(),
+            None,
+        )
+        .with_cache(cache),
+    );
+    let ext = axum::extract::Extension(state.clone());
+
+    // Without SMTP the flow is unavailable.
+    let request = |email: &'static str| {
+        auth::password_reset_request::request(
+            ext.clone(),
+            HeaderMap::new(),
+            Some(axum::Json(serde_json::json!({ "email": email }))),
+        )
+    };
+    assert!(matches!(
+        request("someone@example.com").await,
+        Err(didhub_backend::error::ApiError::NotImplemented { .. })
+    ));
+    let mailer = Arc::new(CapturingMailer::default());
+    state.set_mailer(Some(mailer.clone()));
+    state.set_password_reset(didhub_backend::password_reset::PasswordResetSettings {
+        public_url: Some("https://didhub.example.com/".to_string()),
+        ..Default::default()
+    });
+
+    // Unknown addresses get the same answer but no email.
+    let axum::Json(body) = request("nobody@example.com").await.expect("request");
+    assert_eq!(body["requested"], true);
+    assert!(mailer.sent.lock().unwrap().is_empty());
+
+    let session = {
+        let mut headers = HeaderMap::new();
+        let token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({
+                "sub": id.to_string(),
+                "iat": chrono::Utc::now().timestamp() - 10,
+                "exp": chrono::Utc::now().timestamp() + 3600,
+                "scopes": ["user"],
+            }),
+            &jsonwebtoken::EncodingKey::from_secret(b"test-secret"),
+        )
+        .expect("encode");
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
+    };
+    let axum::Json(stored) = didhub_backend::handlers::users::own_email_set::own_email_set(
+        ext.clone(),
+        session.clone(),
+        Some(axum::Json(
+            serde_json::json!({ "email": " Forgetful@Example.com " }),
+        )),
+    )
+    .await
+    .expect("set email");
+    assert_eq!(stored["email"], "forgetful@example.com");
+
+    let _ = request("forgetful@example.com").await.expect("request");
+    let email = mailer.sent.lock().unwrap().pop().expect("reset email");
+    assert_eq!(email.to, "forgetful@example.com");
+    let link = email
+        .body
+        .lines()
+        .find(|l| l.starts_with("https://didhub.example.com/reset-password?token="))
+        .expect("reset link");
+    let token = link.rsplit('=').next().unwrap().to_string();
+
+    let confirm = |token: String, password: String| {
+        auth::password_reset_confirm::confirm(
+            ext.clone(),
+            HeaderMap::new(),
+            Some(axum::Json(
+                serde_json::json!({ "token": token, "newPasswordHash": password }),
+            )),
+        )
+    };
+    assert!(confirm("not-a-token".into(), new_password.clone())
+        .await
+        .is_err());
+    let axum::Json(body) = confirm(token.clone(), new_password.clone())
+        .await
+        .expect("confirm");
+    assert_eq!(body["reset"], true);
+    assert!(matches!(
+        confirm(token, new_password.clone()).await,
+        Err(didhub_backend::error::ApiError::BadRequest(_))
+    ));
+
+    // The password changed and existing sessions were signed out.
+    let stored: String = sqlx::query_scalar("SELECT password_hash FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .expect("password hash");
+    assert!(didhub_auth::auth::verify_client_password(&new_password, &stored).is_ok());
+    assert!(auth::utils::authenticate_required(&state, &session)
+        .await
+        .is_err());
+
+    // Only a few emails per account and hour.
+    for _ in 0..3 {
+        let _ = request("forgetful@example.com").await.expect("request");
+    }
+    assert_eq!(mailer.sent.lock().unwrap().len(), 2);
+
+    // Used and expired tokens are purged by the cleanup job.
+    sqlx::query("UPDATE password_reset_tokens SET expires_at = ?")
+        .bind((chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339())
+        .execute(&pool)
+        .await
+        .expect("expire tokens");
+    let deleted = didhub_backend::password_reset::purge_expired(&pool)
+        .await
+        .expect("purge");
+    assert_eq!(deleted, 1);
+}
+
+#[tokio::test]
+async fn introspection_reports_token_state_to_authenticated_callers() {
+    use tower::util::ServiceExt;
+
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    sqlx::query(r#"CREATE TABLE users (id BLOB PRIMARY KEY, username TEXT, password_hash TEXT, created_at TEXT, updated_at TEXT, roles TEXT, settings TEXT, about_me TEXT, avatar TEXT, must_change_password INTEGER, last_login_at TEXT, display_name TEXT)"#)
+        .execute(&pool)
+        .await
+        .expect("create table");
+    let now = chrono::Utc::now().to_rfc3339();
+    let bot = uuid::Uuid::new_v4();
+    let member = uuid::Uuid::new_v4();
+    for (id, name) in [(bot, "bot"), (member, "member")] {
+        sqlx::query("INSERT INTO users (id, username, created_at, updated_at, roles, settings) VALUES (?, ?, ?, ?, '[\"user\"]', '{}')")
+            .bind(id)
+            .bind(name)
+            .bind(&now)
+            .bind(&now)
+            .execute(&pool)
+            .await
+            .expect("insert user");
+    }
+
+    let cache = didhub_cache::AppCache::memory();
+    let revocations = revocation_store(&pool).await;
+    let authenticator = Arc::new(
+        didhub_auth::auth::JwtAuthenticator::new_hs256("test-secret")
+            .with_revocation_store(revocations.clone()),
+    ) as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let state = Arc::new(
+        AppState::new(
+            pool.clone(),
+            authenticator,
+            JobQueueClient::new(),
+            UpdateCoordinator::new(),
+            None,
+        )
+        .with_cache(cache),
+    );
+    let app = didhub_backend::build_router(state);
+
+    let exp = chrono::Utc::now().timestamp() + 3600;
+    let token = |sub: uuid::Uuid, jti: &str| {
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({
+                "sub": sub.to_string(),
+                "iat": chrono::Utc::now().timestamp() - 10,
+                "exp": exp,
+                "jti": jti,
+                "scopes": ["user"],
+            }),
+            &jsonwebtoken::EncodingKey::from_secret(b"test-secret"),
+        )
+        .expect("encode")
+    };
+    let caller = token(bot, "bot-token");
+    let subject = token(member, "member-token");
+
+    let introspect = |authorization: Option<String>, form: String| {
+        let app = app.clone();
+        async move {
+            let mut req = axum::http::Request::builder()
+                .method(axum::http::Method::POST)
+                .uri("/api/oauth/introspect")
+                .header(
+                    axum::http::header::CONTENT_TYPE,
+                    "application/x-www-form-urlencoded",
+                );
+            if let Some(a) = authorization {
+                req = req.header(axum::http::header::AUTHORIZATION, a);
+            }
+            let resp = app
+                .oneshot(req.body(axum::body::Body::from(form)).unwrap())
+                .await
+                .unwrap();
+            let status = resp.status();
+            let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default(),
+            )
+        }
+    };
+    let bearer = Some(format!("Bearer {}", caller));
+
+    // Callers must authenticate themselves.
+    let (status, _) = introspect(None, format!("token={}", subject)).await;
+    assert_eq!(status, 401);
+
+    let (status, body) = introspect(
+        bearer.clone(),
+        format!("token={}&token_type_hint=access_token", subject),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["active"], true);
+    assert_eq!(body["sub"], member.to_string());
+    assert_eq!(body["username"], "member");
+    assert_eq!(body["scope"], "user");
+    assert_eq!(body["jti"], "member-token");
+    assert_eq!(body["exp"], exp);
+
+    let (_, body) = introspect(bearer.clone(), "token=garbage".to_string()).await;
+    assert_eq!(body, serde_json::json!({ "active": false }));
+
+    revocations
+        .revoke_token("member-token", Some(exp as u64))
+        .await
+        .expect("revoke");
+    let (_, body) = introspect(bearer.clone(), format!("token={}", subject)).await;
+    assert_eq!(body, serde_json::json!({ "active": false }));
+
+    let (status, _) = introspect(bearer, String::new()).await;
+    assert_eq!(status, 400);
+}
+
+#[tokio::test]
+async fn remembered_devices_sign_in_with_rotating_tokens() {
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    for ddl in [
+        r#"CREATE TABLE users (id BLOB PRIMARY KEY, username TEXT, password_hash TEXT, created_at TEXT, updated_at TEXT, roles TEXT, settings TEXT, about_me TEXT, avatar TEXT, must_change_password INTEGER, last_login_at TEXT, display_name TEXT)"#,
+        r#"CREATE TABLE sessions (id BLOB PRIMARY KEY, user_id BLOB NOT NULL, token_id TEXT NOT NULL UNIQUE, user_agent TEXT, ip_address TEXT, created_at TEXT NOT NULL, last_seen_at TEXT NOT NULL, expires_at TEXT NOT NULL)"#,
+        r#"CREATE TABLE device_tokens (id BLOB PRIMARY KEY, user_id BLOB NOT NULL, name TEXT NOT NULL, token_hash TEXT NOT NULL UNIQUE, user_agent TEXT, created_at TEXT NOT NULL, last_used_at TEXT NOT NULL, expires_at TEXT NOT NULL)"#,
+    ] {
+        sqlx::query(ddl).execute(&pool).await.expect("create table");
+    }
+
+    let id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query("INSERT INTO users (id, username, password_hash, created_at, updated_at, roles, settings) VALUES (?, 'laptop', ?, ?, ?, '[\"user\"]', '{}')")
+        .bind(id)
+        .bind(
+            didhub_auth::auth::hash_client_password(&didhub_auth::auth::sha256_hex("secret123"))
+                .expect("hash"),
+        )
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("insert user");
+
+    std::env::set_var("DIDHUB_JWT_SECRET", "test-secret");
+    let cache = didhub_cache::AppCache::memory();
+    let authenticator = Arc::new(
+        didhub_auth::auth::JwtAuthenticator::new_hs256("test-secret")
+            .with_revocation_store(revocation_store(&pool).await),
+    ) as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let state = Arc::new(
+        AppState::new(
+            pool.clone(),
+            authenticator,
+            JobQueueClient::new(),
+            UpdateCoordinator::new(),
+            None,
+        )
+        .with_cache(cache),
+    );
+    let ext = axum::extract::Extension(state.clone());
+
+    // Cookies set by a response, as request headers.
+    let cookies = |resp: &axum::response::Response| {
+        let mut session = HeaderMap::new();
+        let mut device = None;
+        for value in resp.headers().get_all(axum::http::header::SET_COOKIE) {
+            let pair = value
+                .to_str()
+                .unwrap()
+                .split(';')
+                .next()
+                .unwrap()
+                .to_string();
+            if pair.starts_with("didhub_session=") {
+                session.insert(axum::http::header::COOKIE, pair.parse().unwrap());
+            } else if pair.starts_with("didhub_device=") {
+                let mut headers = HeaderMap::new();
+                headers.insert(axum::http::header::COOKIE, pair.parse().unwrap());
+                device = Some(headers);
+            }
+        }
+        (session, device)
+    };
+
+    // Without rememberMe no device cookie is set.
+    let resp = auth::login::login(
+        ext.clone(),
+        HeaderMap::new(),
+        Some(axum::Json(serde_json::json!({
+            "username": "laptop",
+            "password": didhub_auth::auth::sha256_hex("secret123"),
+        }))),
+    )
+    .await
+    .expect("login");
+    assert!(cookies(&resp).1.is_none());
+
+    let resp = auth::login::login(
+        ext.clone(),
+        HeaderMap::new(),
+        Some(axum::Json(serde_json::json!({
+            "username": "laptop",
+            "password": didhub_auth::auth::sha256_hex("secret123"),
+            "rememberMe": true,
+            "deviceName": "Work laptop",
+        }))),
+    )
+    .await
+    .expect("login");
+    let (session, device) = cookies(&resp);
+    let device = device.expect("device cookie");
+    let axum::Json(listed) = didhub_backend::handlers::devices::list::list(ext.clone(), session)
+        .await
+        .expect("list devices");
+    assert_eq!(listed["items"].as_array().unwrap().len(), 1);
+    assert_eq!(listed["items"][0]["name"], "Work laptop");
+    let first_expiry = listed["items"][0]["expiresAt"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // The device token is exchanged for a session and rotated.
+    let resp = auth::device_login::device_login(ext.clone(), device.clone())
+        .await
+        .expect("device login");
+    assert_eq!(resp.status(), 200);
+    let (session, rotated) = cookies(&resp);
+    let rotated = rotated.expect("rotated device cookie");
+    assert_ne!(rotated, device);
+    assert!(auth::utils::authenticate_required(&state, &session)
+        .await
+        .is_ok());
+    let axum::Json(listed) =
+        didhub_backend::handlers::devices::list::list(ext.clone(), session.clone())
+            .await
+            .expect("list devices");
+    assert!(listed["items"][0]["expiresAt"].as_str().unwrap() >= first_expiry.as_str());
+
+    // The previous token no longer works, and the failure clears the cookie.
+    let resp = auth::device_login::device_login(ext.clone(), device)
+        .await
+        .expect("device login response");
+    assert_eq!(resp.status(), 401);
+    assert!(resp
+        .headers()
+        .get(axum::http::header::SET_COOKIE)
+        .is_some_and(|v| v.to_str().unwrap().starts_with("didhub_device=;")));
+
+    // Forgetting the device revokes its token server-side.
+    let device_id = listed["items"][0]["id"].as_str().unwrap().to_string();
+    let path = axum::extract::Path(
+        [("deviceId".to_string(), device_id)]
+            .into_iter()
+            .collect::<std::collections::HashMap<_, _>>(),
+    );
+    let _ = didhub_backend::handlers::devices::delete::delete(ext.clone(), session, path)
+        .await
+        .expect("forget device");
+    let resp = auth::device_login::device_login(ext.clone(), rotated)
+        .await
+        .expect("device login response");
+    assert_eq!(resp.status(), 401);
+}
+
+#[tokio::test]
+async fn service_clients_obtain_revocable_machine_tokens() {
+    use axum::extract::{Extension, Form, Path};
+
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    sqlx::query(r#"CREATE TABLE service_clients (id BLOB PRIMARY KEY, name TEXT NOT NULL, secret_hash TEXT NOT NULL, scopes TEXT NOT NULL, created_by BLOB, last_used_at TEXT, created_at TEXT NOT NULL)"#)
+        .execute(&pool)
+        .await
+        .expect("create service_clients");
+
+    let cache = didhub_cache::AppCache::memory();
+    let revocations = revocation_store(&pool).await;
+    let authenticator = Arc::new(
+        didhub_auth::auth::JwtAuthenticator::new_hs256("test-secret")
+            .with_revocation_store(revocations),
+    ) as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let state = Arc::new(
+        AppState::new(
+            pool.clone(),
+            authenticator,
+            JobQueueClient::new(),
+            UpdateCoordinator::new(),
+            None,
+        )
+        .with_cache(cache),
+    );
+    std::env::set_var("DIDHUB_JWT_SECRET", "test-secret");
+    let ext = Extension(state.clone());
+
+    let bearer = |token: &str| {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            format!("Bearer {}", token).parse().unwrap(),
+        );
+        headers
+    };
+    let admin_token = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &serde_json::json!({
+            "sub": uuid::Uuid::new_v4().to_string(),
+            "exp": chrono::Utc::now().timestamp() + 3600,
+            "scopes": ["admin"],
+        }),
+        &jsonwebtoken::EncodingKey::from_secret(b"test-secret"),
+    )
+    .expect("encode");
+    let admin = bearer(&admin_token);
+
+    // The owner role is never granted to a machine.
+    assert!(didhub_backend::handlers::service_clients::create::create(
+        ext.clone(),
+        admin.clone(),
+        Some(axum::Json(
+            serde_json::json!({"name": "exporter", "scopes": ["owner"]}),
+        )),
+    )
+    .await
+    .is_err());
+
+    let axum::Json(created) = didhub_backend::handlers::service_clients::create::create(
+        ext.clone(),
+        admin.clone(),
+        Some(axum::Json(
+            serde_json::json!({"name": "exporter", "scopes": ["viewer", "user"]}),
+        )),
+    )
+    .await
+    .expect("create client");
+    let client_id = created["id"].as_str().expect("id").to_string();
+    let secret = created["clientSecret"]
+        .as_str()
+        .expect("secret")
+        .to_string();
+    assert!(secret.starts_with(didhub_backend::service_clients::CLIENT_SECRET_PREFIX));
+
+    let request_token = |form: &[(&str, &str)], headers: HeaderMap| {
+        let form = form
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let ext = ext.clone();
+        async move {
+            let resp =
+                didhub_backend::handlers::oauth::token::token(ext, headers, Form(form)).await;
+            let status = resp.status();
+            let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (
+                status,
+                serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default(),
+            )
+        }
+    };
+
+    let (status, body) = request_token(
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_id", &client_id),
+            ("client_secret", "dhs_wrong"),
+        ],
+        HeaderMap::new(),
+    )
+    .await;
+    assert_eq!(status, 401);
+    assert_eq!(body["error"], "invalid_client");
+
+    let (status, body) = request_token(&[("grant_type", "password")], HeaderMap::new()).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "unsupported_grant_type");
+
+    let (status, body) = request_token(
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_id", &client_id),
+            ("client_secret", &secret),
+            ("scope", "admin"),
+        ],
+        HeaderMap::new(),
+    )
+    .await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "invalid_scope");
+
+    // HTTP Basic authentication, narrowed to one scope.
+    let mut basic = HeaderMap::new();
+    basic.insert(
+        axum::http::header::AUTHORIZATION,
+        format!(
+            "Basic {}",
+            base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                format!("{}:{}", client_id, secret)
+            )
+        )
+        .parse()
+        .unwrap(),
+    );
+    let (status, body) = request_token(
+        &[("grant_type", "client_credentials"), ("scope", "viewer")],
+        basic,
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["token_type"], "Bearer");
+    assert_eq!(body["scope"], "viewer");
+    let machine = bearer(body["access_token"].as_str().expect("access token"));
+
+    let ctx = auth::utils::authenticate_and_require_approved(&state, &machine)
+        .await
+        .expect("service token authenticates");
+    assert_eq!(
+        ctx.client_id.map(|id| id.to_string()),
+        Some(client_id.clone())
+    );
+    assert!(ctx.user_id.is_none());
+    assert!(!ctx.has_permission(didhub_auth::auth::Permission::EditOwnContent));
+
+    // Service tokens cannot manage service clients.
+    assert!(
+        didhub_backend::handlers::service_clients::list::list(ext.clone(), machine.clone())
+            .await
+            .is_err()
+    );
+    let axum::Json(listed) =
+        didhub_backend::handlers::service_clients::list::list(ext.clone(), admin.clone())
+            .await
+            .expect("list clients");
+    assert!(listed["items"][0]["lastUsedAt"].is_string());
+    assert!(listed["items"][0].get("clientSecret").is_none());
+
+    // Rotating the secret revokes issued tokens and the old secret.
+    let path: std::collections::HashMap<_, _> = [("clientId".to_string(), client_id.clone())]
+        .into_iter()
+        .collect();
+    let axum::Json(rotated) =
+        didhub_backend::handlers::service_clients::rotate_secret::rotate_secret(
+            ext.clone(),
+            admin.clone(),
+            Path(path.clone()),
+        )
+        .await
+        .expect("rotate secret");
+    assert!(auth::utils::authenticate_required(&state, &machine)
+        .await
+        .is_err());
+    let new_secret = rotated["clientSecret"].as_str().expect("new secret");
+    let (status, _) = request_token(
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_id", &client_id),
+            ("client_secret", &secret),
+        ],
+        HeaderMap::new(),
+    )
+    .await;
+    assert_eq!(status, 401);
+
+    // Revocation cut-offs have second precision.
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    let (status, body) = request_token(
+        &[
+            ("grant_type", "client_credentials"),
+            ("client_id", &client_id),
+            ("client_secret", new_secret),
+        ],
+        HeaderMap::new(),
+    )
+    .await;
+    assert_eq!(status, 200);
+    assert_eq!(body["scope"], "viewer user");
+    let machine = bearer(body["access_token"].as_str().unwrap());
+    assert!(auth::utils::authenticate_required(&state, &machine)
+        .await
+        .is_ok());
+
+    let axum::Json(deleted) =
+        didhub_backend::handlers::service_clients::delete::delete(ext.clone(), admin, Path(path))
+            .await
+            .expect("delete client");
+    assert_eq!(deleted["deleted"], true);
+    assert!(auth::utils::authenticate_required(&state, &machine)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn device_authorization_grant_issues_session_after_approval() {
+    use axum::extract::{Extension, Form, Query};
+
+    let cfg = DbConnectionConfig::new("sqlite::memory:");
+    let pool = create_pool(&cfg).await.expect("create pool");
+    sqlx::query(r#"CREATE TABLE users (id BLOB PRIMARY KEY, username TEXT, password_hash TEXT, created_at TEXT, updated_at TEXT, roles TEXT, settings TEXT, about_me TEXT, avatar TEXT, must_change_password INTEGER, last_login_at TEXT, display_name TEXT)"#)
+        .execute(&pool)
+        .await
+        .expect("create users");
+    sqlx::query(r#"CREATE TABLE device_authorizations (id BLOB PRIMARY KEY, device_code_hash TEXT NOT NULL UNIQUE, user_code TEXT NOT NULL UNIQUE, client_id TEXT NOT NULL, status TEXT NOT NULL, user_id BLOB, last_polled_at TEXT, created_at TEXT NOT NULL, expires_at TEXT NOT NULL)"#)
+        .execute(&pool)
+        .await
+        .expect("create device_authorizations");
+    let user_id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query("INSERT INTO users (id, username, created_at, updated_at, roles, settings) VALUES (?, 'cli-user', ?, ?, '[\"user\"]', '{}')")
+        .bind(user_id)
+        .bind(&now)
+        .bind(&now)
+        .execute(&pool)
+        .await
+        .expect("insert user");
+
+    let authenticator = Arc::new(didhub_auth::auth::JwtAuthenticator::new_hs256(
+        "test-secret",
+    )) as Arc<dyn didhub_auth::auth::AuthenticatorTrait>;
+    let state = Arc::new(AppState::new(
+        pool.clone(),
+        authenticator,
+        JobQueueClient::new(),
+        UpdateCoordinator::new(),
+        None,
+    ));
+    std::env::set_var("DIDHUB_JWT_SECRET", "test-secret");
+    let ext = Extension(state.clone());
+
+    let session = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &serde_json::json!({
+            "sub": user_id.to_string(),
+            "exp": chrono::Utc::now().timestamp() + 3600,
+            "scopes": ["user"],
+        }),
+        &jsonwebtoken::EncodingKey::from_secret(b"test-secret"),
+    )
+    .expect("encode");
+    let mut user_headers = HeaderMap::new();
+    user_headers.insert(
+        axum::http::header::AUTHORIZATION,
+        format!("Bearer {}", session).parse().unwrap(),
+    );
+
+    let form = |pairs: &[(&str, &str)]| {
+        Form(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<std::collections::HashMap<_, _>>(),
+        )
+    };
+    let read = |resp: axum::response::Response| async move {
+        let status = resp.status();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (
+            status,
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap_or_default(),
+        )
+    };
+    let start = || async {
+        read(
+            didhub_backend::handlers::oauth::device_authorization::device_authorization(
+                ext.clone(),
+                form(&[("client_id", "didhub-cli")]),
+            )
+            .await,
+        )
+        .await
+    };
+    let poll = |device_code: String| {
+        let ext = ext.clone();
+        async move {
+            read(
+                didhub_backend::handlers::oauth::token::token(
+                    ext,
+                    HeaderMap::new(),
+                    form(&[
+                        (
+                            "grant_type",
+                            didhub_backend::device_authorization::DEVICE_CODE_GRANT,
+                        ),
+                        ("device_code", &device_code),
+                        ("client_id", "didhub-cli"),
+                    ]),
+                )
+                .await,
+            )
+            .await
+        }
+    };
+    let reset_poll_timer = || async {
+        sqlx::query("UPDATE device_authorizations SET last_polled_at = NULL")
+            .execute(&pool)
+            .await
+            .expect("reset poll timer");
+    };
+
+    let (status, started) = start().await;
+    assert_eq!(status, 200);
+    let device_code = started["device_code"].as_str().unwrap().to_string();
+    let user_code = started["user_code"].as_str().unwrap().to_string();
+    assert_eq!(user_code.len(), 9);
+    assert_eq!(started["interval"], 5);
+    assert!(started["verification_uri"]
+        .as_str()
+        .unwrap()
+        .ends_with("/device"));
+
+    let (status, body) = poll(device_code.clone()).await;
+    assert_eq!(status, 400);
+    assert_eq!(body["error"], "authorization_pending");
+    let (_, body) = poll(device_code.clone()).await;
+    assert_eq!(body["error"], "slow_down");
+
+    // The user sees which client is asking; codes are accepted in any case and without the dash.
+    let typed = user_code.replace('-', "").to_lowercase();
+    let axum::Json(described) = didhub_backend::handlers::oauth::device::describe(
+        ext.clone(),
+        user_headers.clone(),
+        Query([("code".to_string(), typed.clone())].into_iter().collect()),
+    )
+    .await
+    .expect("describe");
+    assert_eq!(described["clientId"], "didhub-cli");
+
+    assert!(didhub_backend::handlers::oauth::device::decide(
+        ext.clone(),
+        HeaderMap::new(),
+        Some(axum::Json(
+            serde_json::json!({"userCode": typed, "approve": true}),
+        )),
+    )
+    .await
+    .is_err());
+    let axum::Json(decided) = didhub_backend::handlers::oauth::device::decide(
+        ext.clone(),
+        user_headers.clone(),
+        Some(axum::Json(
+            serde_json::json!({"userCode": typed, "approve": true}),
+        )),
+    )
+    .await
+    .expect("approve");
+    assert_eq!(decided["approved"], true);
+
+    reset_poll_timer().await;
+    let (status, body) = poll(device_code.clone()).await;
+    assert_eq!(status, 200);
+    assert_eq!(body["token_type"], "Bearer");
+    let mut cli_headers = HeaderMap::new();
+    cli_headers.insert(
+        axum::http::header::AUTHORIZATION,
+        format!("Bearer {}", body["access_token"].as_str().unwrap())
+            .parse()
+            .unwrap(),
+    );
+    let ctx = auth::utils::authenticate_required(&state, &cli_headers)
+        .await
+        .expect("device token authenticates");
+    assert_eq!(ctx.user_id, Some(user_id));
+
+    // Device codes are single use.
+    let (_, body) = poll(device_code).await;
+    assert_eq!(body["error"], "invalid_grant");
+
+    let (_, started) = start().await;
+    let device_code = started["device_code"].as_str().unwrap().to_string();
+    let (_, body) = read(
+        didhub_backend::handlers::oauth::token::token(
+            ext.clone(),
+            HeaderMap::new(),
+            form(&[
+                (
+                    "grant_type",
+                    didhub_backend::device_authorization::DEVICE_CODE_GRANT,
+                ),
+                ("device_code", &device_code),
+                ("client_id", "someone-else"),
+            ]),
+        )
+        .await,
+    )
+    .await;
+    assert_eq!(body["error"], "invalid_grant");
+    let axum::Json(decided) = didhub_backend::handlers::oauth::device::decide(
+        ext.clone(),
+        user_headers,
+        Some(axum::Json(
+            serde_json::json!({"userCode": started["user_code"], "approve": false}),
+        )),
+    )
+    .await
+    .expect("deny");
+    assert_eq!(decided["approved"], false);
+    let (_, body) = poll(device_code).await;
+    assert_eq!(body["error"], "access_denied");
+}
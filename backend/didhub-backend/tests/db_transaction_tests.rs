@@ -0,0 +1,89 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use didhub_db::transaction::MAX_TRANSACTION_ATTEMPTS;
+use didhub_db::{transaction, RetryableError};
+
+mod support;
+
+#[derive(Debug)]
+enum TestError {
+    Conflict,
+    Fatal,
+    Sqlx(#[allow(dead_code)] sqlx::Error),
+}
+
+impl From<sqlx::Error> for TestError {
+    fn from(e: sqlx::Error) -> Self {
+        TestError::Sqlx(e)
+    }
+}
+
+impl RetryableError for TestError {
+    fn is_retryable(&self) -> bool {
+        matches!(self, TestError::Conflict)
+    }
+}
+
+async fn setup() -> didhub_db::DbPool {
+    let pool = support::sqlite_pool().await;
+    sqlx::query("CREATE TABLE items (name TEXT NOT NULL)")
+        .execute(&pool)
+        .await
+        .expect("create table");
+    pool
+}
+
+async fn count(pool: &didhub_db::DbPool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM items")
+        .fetch_one(pool)
+        .await
+        .expect("count")
+}
+
+#[tokio::test]
+async fn transaction_rolls_back_on_error() {
+    let pool = setup().await;
+    let result: Result<(), TestError> = transaction(&pool, |tx| {
+        Box::pin(async move {
+            sqlx::query("INSERT INTO items (name) VALUES ('partial')")
+                .execute(&mut **tx)
+                .await?;
+            Err(TestError::Fatal)
+        })
+    })
+    .await;
+    assert!(matches!(result, Err(TestError::Fatal)));
+    assert_eq!(count(&pool).await, 0);
+}
+
+#[tokio::test]
+async fn transaction_retries_conflicts_from_scratch() {
+    let pool = setup().await;
+    let attempts = AtomicU32::new(0);
+    let value = transaction(&pool, |tx| {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        Box::pin(async move {
+            sqlx::query("INSERT INTO items (name) VALUES ('row')")
+                .execute(&mut **tx)
+                .await?;
+            if attempt == 1 {
+                return Err(TestError::Conflict);
+            }
+            Ok::<_, TestError>(attempt)
+        })
+    })
+    .await
+    .expect("second attempt commits");
+    assert_eq!(value, 2);
+    // The first attempt's insert was rolled back
+    assert_eq!(count(&pool).await, 1);
+
+    let attempts = AtomicU32::new(0);
+    let result: Result<(), TestError> = transaction(&pool, |_tx| {
+        attempts.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async { Err(TestError::Conflict) })
+    })
+    .await;
+    assert!(matches!(result, Err(TestError::Conflict)));
+    assert_eq!(attempts.load(Ordering::SeqCst), MAX_TRANSACTION_ATTEMPTS);
+}
@@ -51,3 +51,35 @@ async fn create_user_returns_structured_validation() {
         Err(_) => panic!("expected Ok with validation payload"),
     }
 }
+
+#[tokio::test]
+async fn create_user_rejects_banned_password() {
+    let pool = support::sqlite_pool().await;
+    let arc_state = support::test_state(&pool, &["admin"], None);
+    let mut policy =
+        didhub_auth::auth::PasswordPolicy::default().with_banned_passwords(["hunter2hunter2"]);
+    policy.min_length = 12;
+    arc_state.set_password_policy(policy);
+
+    let published = didhub_backend::handlers::password_policy::get::get(
+        axum::Extension(arc_state.clone()),
+        axum::http::HeaderMap::new(),
+    )
+    .await
+    .expect("policy")
+    .0;
+    assert_eq!(published["minLength"], 12);
+    assert!(published.get("banned").is_none());
+
+    let hash = didhub_auth::auth::sha256_hex("hunter2hunter2");
+    let body = serde_json::json!({ "username": "alice", "passwordHash": hash });
+    let v = create::create(
+        axum::Extension(arc_state.clone()),
+        axum::http::HeaderMap::new(),
+        Some(axum::Json(body)),
+    )
+    .await
+    .expect("expected Ok with validation payload")
+    .0;
+    assert_eq!(v["validation"]["passwordHash"]["code"], "banned");
+}
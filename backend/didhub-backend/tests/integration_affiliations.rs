@@ -52,7 +52,8 @@ async fn setup() -> TestContext {
             description TEXT,
             sigil TEXT,
             owner_user_id TEXT,
-            created_at TEXT NOT NULL
+            created_at TEXT NOT NULL,
+            deleted_at TEXT
         )"#,
     )
     .execute(&pool)
@@ -100,13 +101,27 @@ async fn setup() -> TestContext {
             is_merged INTEGER NOT NULL DEFAULT 0,
             owner_user_id TEXT NOT NULL,
             created_at TEXT NOT NULL,
-            updated_at TEXT NOT NULL
+            updated_at TEXT NOT NULL,
+            deleted_at TEXT
         )"#,
     )
     .execute(&pool)
     .await
     .expect("create alters table");
 
+    sqlx::query(
+        r#"CREATE TABLE system_members (
+            system_id TEXT NOT NULL,
+            user_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            PRIMARY KEY (system_id, user_id)
+        )"#,
+    )
+    .execute(&pool)
+    .await
+    .expect("create system_members table");
+
     let owner_id = Uuid::new_v4();
     sqlx::query(
         r#"INSERT INTO users (
@@ -241,7 +256,7 @@ async fn update_affiliation_as_owner() {
 }
 
 #[tokio::test]
-async fn delete_affiliation_removes_members() {
+async fn delete_affiliation_moves_it_to_trash() {
     let ctx = setup().await;
     let (affiliation_id, _) = create_sample_affiliation(ctx.state.clone()).await;
 
@@ -289,12 +304,13 @@ async fn delete_affiliation_removes_members() {
     .await
     .expect("delete affiliation");
 
-    let remaining: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM affiliations WHERE id = ?")
-        .bind(affiliation_id)
-        .fetch_one(&ctx.pool)
-        .await
-        .expect("check remaining affiliation");
-    assert_eq!(remaining.0, 0);
+    let trashed: (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM affiliations WHERE id = ? AND deleted_at IS NOT NULL")
+            .bind(affiliation_id)
+            .fetch_one(&ctx.pool)
+            .await
+            .expect("check trashed affiliation");
+    assert_eq!(trashed.0, 1);
 
     let member_count: (i64,) =
         sqlx::query_as("SELECT COUNT(*) FROM affiliation_members WHERE affiliation_id = ?")
@@ -302,7 +318,7 @@ async fn delete_affiliation_removes_members() {
             .fetch_one(&ctx.pool)
             .await
             .expect("remaining members");
-    assert_eq!(member_count.0, 0);
+    assert_eq!(member_count.0, 1);
 }
 
 #[tokio::test]
@@ -1,12 +1,10 @@
 use didhub_backend::generated::routes::{create_alter, delete_alter, get_alter, update_alter};
+use didhub_backend::handlers::alters;
 use std::collections::HashMap;
 
 mod support;
 
-#[tokio::test]
-async fn alters_crud_sqlite_in_memory() {
-    let pool = support::sqlite_pool().await;
-
+async fn create_alters_table(pool: &sqlx::SqlitePool) {
     sqlx::query(
         r#"CREATE TABLE alters (
             id TEXT PRIMARY KEY,
@@ -34,12 +32,21 @@ async fn alters_crud_sqlite_in_memory() {
             is_dormant INTEGER NOT NULL,
             is_merged INTEGER NOT NULL,
             owner_user_id TEXT NOT NULL,
-            created_at TEXT NOT NULL
+            created_at TEXT NOT NULL,
+            deleted_at TEXT
         )"#,
     )
-    .execute(&pool)
+    .execute(pool)
     .await
     .expect("create table");
+    support::create_custom_field_tables(pool).await;
+}
+
+#[tokio::test]
+async fn alters_crud_sqlite_in_memory() {
+    let pool = support::sqlite_pool().await;
+
+    create_alters_table(&pool).await;
 
     let arc_state = support::test_state(&pool, &["admin"], None);
 
@@ -102,3 +109,234 @@ async fn alters_crud_sqlite_in_memory() {
     let del = res.0;
     assert_eq!(del.get("deleted").and_then(|v| v.as_bool()), Some(true));
 }
+
+#[tokio::test]
+async fn alters_cursor_pagination_walks_all_pages() {
+    let pool = support::sqlite_pool().await;
+    create_alters_table(&pool).await;
+    let arc_state = support::test_state(&pool, &["admin"], None);
+    let headers = support::auth_headers();
+
+    let system_a = "00000000-0000-0000-0000-00000000000a";
+    let system_b = "00000000-0000-0000-0000-00000000000b";
+    // Duplicate names exercise the id tie-breaker
+    for (user_id, name) in [
+        (system_a, "Echo"),
+        (system_a, "Alpha"),
+        (system_b, "Bravo"),
+        (system_a, "Delta"),
+        (system_a, "Alpha"),
+    ] {
+        let body = serde_json::json!({ "user_id": user_id, "name": name });
+        let created = create_alter(
+            axum::Extension(arc_state.clone()),
+            headers.clone(),
+            Some(axum::Json(body)),
+        )
+        .await
+        .expect("create");
+        assert_eq!(created.0["name"], name);
+    }
+
+    let page = |params: Vec<(&str, String)>| {
+        let arc_state = arc_state.clone();
+        let headers = headers.clone();
+        let query: HashMap<String, String> = params
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        async move {
+            alters::list::list(
+                axum::Extension(arc_state),
+                headers,
+                Some(axum::extract::Query(query)),
+            )
+            .await
+        }
+    };
+
+    let mut names = Vec::new();
+    let mut ids = std::collections::HashSet::new();
+    let mut cursor: Option<String> = None;
+    let mut pages = 0;
+    loop {
+        let mut params = vec![("limit", "2".to_string())];
+        if let Some(c) = &cursor {
+            params.push(("cursor", c.clone()));
+        }
+        let body = page(params).await.expect("list page").0;
+        let items = body["items"].as_array().expect("items");
+        assert!(items.len() <= 2);
+        for item in items {
+            names.push(item["name"].as_str().unwrap().to_string());
+            assert!(ids.insert(item["id"].as_str().unwrap().to_string()));
+        }
+        pages += 1;
+        match body["nextCursor"].as_str() {
+            Some(next) => cursor = Some(next.to_string()),
+            None => break,
+        }
+    }
+    assert_eq!(pages, 3);
+    assert_eq!(names, ["Alpha", "Alpha", "Bravo", "Delta", "Echo"]);
+
+    // Filtered by system
+    let body = page(vec![
+        ("limit", "10".to_string()),
+        ("systemId", system_a.to_string()),
+    ])
+    .await
+    .expect("filtered page")
+    .0;
+    assert_eq!(body["items"].as_array().unwrap().len(), 4);
+    assert!(body["nextCursor"].is_null());
+
+    // Without cursor parameters the full array is returned as before
+    let body = page(vec![]).await.expect("unpaginated").0;
+    assert_eq!(body.as_array().map(|a| a.len()), Some(5));
+
+    assert!(page(vec![("cursor", "not-a-cursor".to_string())])
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn alters_list_combines_structured_filters() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    let arc_state = support::test_state(&pool, &["admin"], None);
+    let headers = support::auth_headers();
+
+    let system = "00000000-0000-0000-0000-00000000000a";
+    let other = "00000000-0000-0000-0000-00000000000b";
+    for (id, username) in [(system, "grove"), (other, "meadow")] {
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, roles) VALUES (?, ?, 'hash', '[\"system\"]')",
+        )
+        .bind(uuid::Uuid::parse_str(id).unwrap())
+        .bind(username)
+        .execute(&pool)
+        .await
+        .expect("insert system user");
+    }
+    let mut ids = HashMap::new();
+    for (user_id, body) in [
+        (
+            system,
+            serde_json::json!({ "name": "Ash", "species": "Fox", "systemRoles": ["protector", "gatekeeper"], "isSystemHost": true, "birthday": "03-14" }),
+        ),
+        (
+            system,
+            serde_json::json!({ "name": "Birch", "species": "fox", "systemRoles": ["protector"], "isDormant": true }),
+        ),
+        (
+            system,
+            serde_json::json!({ "name": "Cedar_1", "species": "human", "systemRoles": ["little"], "isMerged": true }),
+        ),
+        (
+            other,
+            serde_json::json!({ "name": "Ashwood", "species": "Fox", "systemRoles": ["protectors"] }),
+        ),
+    ] {
+        let mut body = body;
+        body["user_id"] = serde_json::json!(user_id);
+        let created = create_alter(
+            axum::Extension(arc_state.clone()),
+            headers.clone(),
+            Some(axum::Json(body)),
+        )
+        .await
+        .expect("create");
+        ids.insert(
+            created.0["name"].as_str().unwrap().to_string(),
+            created.0["id"].as_str().unwrap().to_string(),
+        );
+    }
+    let subsystem = "00000000-0000-0000-0000-0000000000c1";
+    let subsystem_uuid = uuid::Uuid::parse_str(subsystem).unwrap();
+    sqlx::query("INSERT INTO subsystems (id, name) VALUES (?, 'Grove')")
+        .bind(subsystem_uuid)
+        .execute(&pool)
+        .await
+        .expect("insert subsystem");
+    for name in ["Birch", "Cedar_1"] {
+        sqlx::query("INSERT INTO subsystem_members (subsystem_id, alter_id) VALUES (?, ?)")
+            .bind(subsystem_uuid)
+            .bind(uuid::Uuid::parse_str(&ids[name]).unwrap())
+            .execute(&pool)
+            .await
+            .expect("insert member");
+    }
+
+    let names = |params: &[(&str, &str)]| {
+        let arc_state = arc_state.clone();
+        let headers = headers.clone();
+        let query: HashMap<String, String> = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        async move {
+            let body = alters::list::list(
+                axum::Extension(arc_state),
+                headers,
+                Some(axum::extract::Query(query)),
+            )
+            .await
+            .expect("list")
+            .0;
+            body.as_array()
+                .unwrap()
+                .iter()
+                .map(|a| a["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(
+        names(&[("species", "FOX")]).await,
+        ["Ash", "Ashwood", "Birch"]
+    );
+    assert_eq!(
+        names(&[("species", "fox"), ("systemId", system)]).await,
+        ["Ash", "Birch"]
+    );
+    // Roles match whole entries, all of them required
+    assert_eq!(
+        names(&[("systemRoles", "protector")]).await,
+        ["Ash", "Birch"]
+    );
+    assert_eq!(
+        names(&[("systemRoles", "protector,gatekeeper")]).await,
+        ["Ash"]
+    );
+    assert_eq!(names(&[("isHost", "true")]).await, ["Ash"]);
+    assert_eq!(names(&[("isDormant", "1")]).await, ["Birch"]);
+    assert_eq!(
+        names(&[("isMerged", "false"), ("systemId", system)]).await,
+        ["Ash", "Birch"]
+    );
+    assert_eq!(names(&[("hasBirthday", "true")]).await, ["Ash"]);
+    assert_eq!(
+        names(&[("subsystemId", subsystem)]).await,
+        ["Birch", "Cedar_1"]
+    );
+    assert_eq!(names(&[("ownerId", other)]).await, ["Ashwood"]);
+    // The search is a case-insensitive substring with wildcards taken literally
+    assert_eq!(names(&[("search", "ASH")]).await, ["Ash", "Ashwood"]);
+    assert_eq!(names(&[("search", "r_1")]).await, ["Cedar_1"]);
+    assert!(names(&[("search", "%")]).await.is_empty());
+
+    for (key, value) in [("isHost", "maybe"), ("subsystemId", "nope")] {
+        let query = HashMap::from([(key.to_string(), value.to_string())]);
+        assert!(alters::list::list(
+            axum::Extension(arc_state.clone()),
+            headers.clone(),
+            Some(axum::extract::Query(query)),
+        )
+        .await
+        .is_err());
+    }
+}
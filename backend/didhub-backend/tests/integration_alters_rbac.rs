@@ -34,12 +34,14 @@ async fn alters_rbac_denied_for_non_owner() {
             is_dormant INTEGER NOT NULL,
             is_merged INTEGER NOT NULL,
             owner_user_id TEXT NOT NULL,
-            created_at TEXT NOT NULL
+            created_at TEXT NOT NULL,
+            deleted_at TEXT
         )"#,
     )
     .execute(&pool)
     .await
     .expect("create table");
+    support::create_custom_field_tables(&pool).await;
 
     // Create with admin to set owner
     let arc_state = support::test_state(&pool, &["admin"], None);
@@ -36,12 +36,14 @@ async fn owner_and_admin_can_modify_alter() {
             is_dormant INTEGER NOT NULL,
             is_merged INTEGER NOT NULL,
             owner_user_id TEXT NOT NULL,
-            created_at TEXT NOT NULL
+            created_at TEXT NOT NULL,
+            deleted_at TEXT
         )"#,
     )
     .execute(&pool)
     .await
     .expect("create table");
+    support::create_custom_field_tables(&pool).await;
 
     // create users table using full migrations schema so generated queries match
     sqlx::query(
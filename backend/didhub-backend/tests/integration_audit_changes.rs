@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use axum::extract::{Extension, Json, Path, Query};
+use didhub_backend::audit::{apply_retention, AuditRetention, RetentionMode};
+use didhub_backend::handlers::{alters, audit_logs};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod support;
+
+async fn history(
+    state: &std::sync::Arc<didhub_backend::state::AppState>,
+    entity_id: Uuid,
+) -> Value {
+    history_with(state, entity_id, false).await
+}
+
+async fn history_with(
+    state: &std::sync::Arc<didhub_backend::state::AppState>,
+    entity_id: Uuid,
+    include_archived: bool,
+) -> Value {
+    let query = HashMap::from([
+        ("entityId".to_string(), entity_id.to_string()),
+        ("includeArchived".to_string(), include_archived.to_string()),
+    ]);
+    audit_logs::list::list(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Some(Query(query)),
+    )
+    .await
+    .expect("audit history")
+    .0
+}
+
+#[tokio::test]
+async fn alter_updates_record_field_diffs() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let admin = Uuid::new_v4();
+    let state = support::test_state(&pool, &["admin"], Some(admin));
+    let alter_id: Uuid = sqlx::query_scalar("SELECT id FROM alters WHERE name = 'Juniper'")
+        .fetch_one(&pool)
+        .await
+        .expect("seeded alter");
+
+    let path = HashMap::from([("alterId".to_string(), alter_id.to_string())]);
+    for body in [
+        json!({ "name": "Juno", "job": "ranger" }),
+        json!({ "name": "Juno" }),
+        json!({ "pronouns": "she/they" }),
+    ] {
+        let _updated = alters::update::update(
+            Extension(state.clone()),
+            support::auth_headers(),
+            Path(path.clone()),
+            Some(Json(body)),
+        )
+        .await
+        .expect("update alter");
+    }
+
+    let body = history(&state, alter_id).await;
+    // The no-op update is not recorded
+    assert_eq!(body["pagination"]["total"], 2);
+    let items = body["items"].as_array().expect("items");
+    assert_eq!(items[0]["message"], "update alter");
+    assert_eq!(items[0]["actor"], admin.to_string());
+    assert_eq!(
+        items[0]["changes"],
+        json!({ "pronouns": { "old": "they/them", "new": "she/they" } })
+    );
+    assert_eq!(
+        items[1]["changes"],
+        json!({
+            "name": { "old": "Juniper", "new": "Juno" },
+            "job": { "old": null, "new": "ranger" },
+        })
+    );
+
+    let other = history(&state, Uuid::new_v4()).await;
+    assert_eq!(other["pagination"]["total"], 0);
+
+    // Keyset pages walk the same history one change at a time
+    let mut cursor = String::new();
+    let mut paged = Vec::new();
+    loop {
+        let query = HashMap::from([
+            ("entityId".to_string(), alter_id.to_string()),
+            ("limit".to_string(), "1".to_string()),
+            ("cursor".to_string(), cursor.clone()),
+        ]);
+        let page = audit_logs::list::list(
+            Extension(state.clone()),
+            support::auth_headers(),
+            Some(Query(query)),
+        )
+        .await
+        .expect("audit history page")
+        .0;
+        paged.extend(page["items"].as_array().expect("items").clone());
+        match page["nextCursor"].as_str() {
+            Some(next) => cursor = next.to_string(),
+            None => break,
+        }
+    }
+    assert_eq!(&paged, items);
+}
+
+#[tokio::test]
+async fn retention_archives_or_purges_old_changes() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    let state = support::test_state(&pool, &["admin"], Some(Uuid::new_v4()));
+    let entity_id = Uuid::new_v4();
+    let now = chrono::Utc::now();
+    let days_ago = |days: i64| (now - chrono::Duration::days(days)).to_rfc3339();
+    for created_at in [days_ago(400), days_ago(200), days_ago(1)] {
+        sqlx::query(
+            "INSERT INTO audit_changes (id, entity_type, entity_id, action, changes, created_at) \
+             VALUES (?, 'alter', ?, 'update', '{}', ?)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(entity_id)
+        .bind(created_at)
+        .execute(&pool)
+        .await
+        .expect("insert audit change");
+    }
+
+    let year = Duration::from_secs(365 * 24 * 60 * 60);
+    let archive = AuditRetention {
+        retention: year,
+        mode: RetentionMode::Archive,
+    };
+    assert_eq!(apply_retention(&pool, &archive).await.expect("archive"), 1);
+    assert_eq!(history(&state, entity_id).await["pagination"]["total"], 2);
+    let all = history_with(&state, entity_id, true).await;
+    assert_eq!(all["pagination"]["total"], 3);
+    let items = all["items"].as_array().expect("items");
+    assert_eq!(items[2]["createdAt"], days_ago(400));
+
+    let purge = AuditRetention {
+        retention: Duration::from_secs(100 * 24 * 60 * 60),
+        mode: RetentionMode::Purge,
+    };
+    assert_eq!(apply_retention(&pool, &purge).await.expect("purge"), 1);
+    assert_eq!(history(&state, entity_id).await["pagination"]["total"], 1);
+    // Purging leaves the archive alone
+    assert_eq!(
+        history_with(&state, entity_id, true).await["pagination"]["total"],
+        2
+    );
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Query};
+use chrono::{Datelike, Duration};
+use didhub_backend::birthdays::{BirthdayDigestExecutor, BirthdaySettings};
+use didhub_backend::handlers::alters::birthdays;
+use didhub_job_queue::JobExecutor;
+use uuid::Uuid;
+
+mod support;
+
+#[derive(Default)]
+struct CapturingMailer {
+    sent: std::sync::Mutex<Vec<didhub_backend::mailer::OutgoingEmail>>,
+}
+
+#[async_trait::async_trait]
+impl didhub_backend::mailer::Mailer for CapturingMailer {
+    async fn send(
+        &self,
+        email: didhub_backend::mailer::OutgoingEmail,
+    ) -> Result<(), didhub_backend::mailer::MailError> {
+        self.sent.lock().unwrap().push(email);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn calendar_and_digest_list_upcoming_birthdays() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let system_id: Uuid = sqlx::query_scalar("SELECT user_id FROM alters WHERE name = 'Willow'")
+        .fetch_one(&pool)
+        .await
+        .expect("seeded alter");
+
+    let state = support::test_state(&pool, &["user"], Some(system_id));
+    let settings = BirthdaySettings {
+        timezone: chrono_tz::Pacific::Auckland,
+        digest_days: 7,
+    };
+    state.set_birthdays(settings.clone());
+    let today = settings.today();
+    // 28 years back is a leap year whenever this one is
+    for (name, birthday) in [
+        (
+            "Willow",
+            format!("{}-{}", today.year() - 28, today.format("%m-%d")),
+        ),
+        (
+            "Juniper",
+            (today + Duration::days(1)).format("%m-%d").to_string(),
+        ),
+        (
+            "Moss",
+            (today + Duration::days(60)).format("--%m-%d").to_string(),
+        ),
+    ] {
+        sqlx::query("UPDATE alters SET birthday = ? WHERE name = ?")
+            .bind(birthday)
+            .bind(name)
+            .execute(&pool)
+            .await
+            .expect("set birthday");
+    }
+
+    let calendar = |params: &[(&str, &str)]| {
+        let query: HashMap<String, String> = params
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        birthdays::calendar(
+            Extension(state.clone()),
+            support::auth_headers(),
+            Some(Query(query)),
+        )
+    };
+    let week = calendar(&[("days", "7")]).await.expect("calendar").0;
+    assert_eq!(week["timezone"], "Pacific/Auckland");
+    assert_eq!(week["today"], today.to_string());
+    let items = week["items"].as_array().unwrap();
+    let names: Vec<&str> = items.iter().map(|i| i["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["Willow", "Juniper"]);
+    assert_eq!(items[0]["daysUntil"], 0);
+    assert_eq!(items[0]["turning"], 28);
+    assert_eq!(items[1]["daysUntil"], 1);
+    assert_eq!(items[1]["turning"], serde_json::Value::Null);
+
+    let season = calendar(&[("days", "90")]).await.expect("calendar").0;
+    assert_eq!(season["items"].as_array().unwrap().len(), 3);
+    let other_system = Uuid::new_v4().to_string();
+    let none = calendar(&[("systemId", other_system.as_str())])
+        .await
+        .expect("calendar")
+        .0;
+    assert!(none["items"].as_array().unwrap().is_empty());
+    assert!(calendar(&[("days", "0")]).await.is_err());
+
+    // The digest goes to systems with an address and upcoming birthdays
+    let now = chrono::Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO user_emails (user_id, email, created_at, updated_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(system_id)
+    .bind("willow@example.com")
+    .bind(&now)
+    .bind(&now)
+    .execute(&pool)
+    .await
+    .expect("insert email");
+    let mailer = Arc::new(CapturingMailer::default());
+    state.set_mailer(Some(mailer.clone()));
+    BirthdayDigestExecutor::new((*state).clone())
+        .execute(serde_json::Value::Null)
+        .await
+        .expect("digest");
+    let sent = mailer.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].to, "willow@example.com");
+    assert!(
+        sent[0].body.contains("- Willow: today, turning 28"),
+        "{}",
+        sent[0].body
+    );
+    assert!(
+        sent[0].body.contains("- Juniper: tomorrow"),
+        "{}",
+        sent[0].body
+    );
+    assert!(!sent[0].body.contains("Moss"));
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use axum::extract::{Extension, Json, Path};
+use didhub_backend::handlers::{alters, custom_fields};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod support;
+
+#[tokio::test]
+async fn systems_define_custom_fields_for_their_alters() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let (alter_id, system_id): (Uuid, Uuid) =
+        sqlx::query_as("SELECT id, user_id FROM alters WHERE name = 'Juniper'")
+            .fetch_one(&pool)
+            .await
+            .expect("seeded alter");
+    let sam: Uuid = sqlx::query_scalar("SELECT id FROM users WHERE display_name = 'Sam'")
+        .fetch_one(&pool)
+        .await
+        .expect("seeded user");
+    // Sam can read the system as a viewer but does not own its alters
+    sqlx::query(
+        "INSERT INTO system_members (system_id, user_id, role, created_at) VALUES (?, ?, 'viewer', '2024-01-01T00:00:00Z')",
+    )
+    .bind(system_id)
+    .bind(sam)
+    .execute(&pool)
+    .await
+    .expect("add viewer");
+    let system = support::test_state(&pool, &["user"], Some(system_id));
+    let stranger = support::test_state(&pool, &["user"], Some(sam));
+
+    let mut fields = HashMap::new();
+    for (name, kind, visibility) in [
+        ("Favourite colour", "text", "public"),
+        ("First seen", "date", "private"),
+        ("Height", "number", "public"),
+    ] {
+        let field = custom_fields::create::create(
+            Extension(system.clone()),
+            support::auth_headers(),
+            Some(Json(
+                json!({ "name": name, "type": kind, "visibility": visibility }),
+            )),
+        )
+        .await
+        .expect("create field")
+        .0;
+        fields.insert(name, field["id"].as_str().unwrap().to_string());
+    }
+    for body in [
+        json!({ "name": "Height", "type": "number" }),
+        json!({ "name": "Mood", "type": "colour" }),
+    ] {
+        custom_fields::create::create(
+            Extension(system.clone()),
+            support::auth_headers(),
+            Some(Json(body)),
+        )
+        .await
+        .expect_err("duplicate name or unknown type");
+    }
+    custom_fields::create::create(
+        Extension(stranger.clone()),
+        support::auth_headers(),
+        Some(Json(json!({ "name": "Mood", "type": "text" }))),
+    )
+    .await
+    .expect_err("only systems define fields");
+
+    // Values are type-checked and set through the alter endpoints
+    let path = HashMap::from([("alterId".to_string(), alter_id.to_string())]);
+    let update = |body: Value| {
+        alters::update::update(
+            Extension(system.clone()),
+            support::auth_headers(),
+            Path(path.clone()),
+            Some(Json(body)),
+        )
+    };
+    update(json!({ "customFields": { fields["Height"].clone(): "tall" } }))
+        .await
+        .expect_err("number field");
+    update(json!({ "customFields": { Uuid::new_v4().to_string(): "x" } }))
+        .await
+        .expect_err("unknown field");
+    let updated = update(json!({
+        "job": "gardener",
+        "customFields": {
+            fields["Favourite colour"].clone(): "green",
+            fields["First seen"].clone(): "2019-04-02",
+            fields["Height"].clone(): 172,
+        }
+    }))
+    .await
+    .expect("update alter")
+    .0;
+    assert_eq!(updated["job"], "gardener");
+    let values: Vec<Value> = updated["customFields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["value"].clone())
+        .collect();
+    assert_eq!(values, [json!("green"), json!("2019-04-02"), json!(172)]);
+
+    // Private fields are hidden from other users; cleared values read as null
+    let _cleared = update(json!({ "customFields": { fields["Height"].clone(): null } }))
+        .await
+        .expect("clear value");
+    let seen = alters::get::get(
+        Extension(stranger.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect("get alter")
+    .0;
+    let names: Vec<&str> = seen["customFields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Favourite colour", "Height"]);
+    assert_eq!(seen["customFields"][1]["value"], Value::Null);
+
+    // Reordering and deleting definitions
+    let field_path = |name: &str| HashMap::from([("fieldId".to_string(), fields[name].clone())]);
+    let moved = custom_fields::update::update(
+        Extension(system.clone()),
+        support::auth_headers(),
+        Path(field_path("Height")),
+        Some(Json(json!({ "order": -1 }))),
+    )
+    .await
+    .expect("reorder field")
+    .0;
+    assert_eq!(moved["order"], -1);
+    custom_fields::update::update(
+        Extension(system.clone()),
+        support::auth_headers(),
+        Path(field_path("Height")),
+        Some(Json(json!({ "type": "text" }))),
+    )
+    .await
+    .expect_err("type is fixed");
+    custom_fields::delete::delete(
+        Extension(stranger.clone()),
+        support::auth_headers(),
+        Path(field_path("Favourite colour")),
+    )
+    .await
+    .expect_err("not their field");
+    let _deleted = custom_fields::delete::delete(
+        Extension(system.clone()),
+        support::auth_headers(),
+        Path(field_path("Favourite colour")),
+    )
+    .await
+    .expect("delete field");
+
+    let listed =
+        custom_fields::list::list(Extension(system.clone()), support::auth_headers(), None)
+            .await
+            .expect("list fields")
+            .0;
+    let names: Vec<&str> = listed["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|f| f["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Height", "First seen"]);
+    let values: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM alter_custom_field_values")
+        .fetch_one(&pool)
+        .await
+        .expect("count values");
+    assert_eq!(values, 1);
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::extract::{Extension, Json, Path, Query};
+use didhub_backend::handlers::{alters, history};
+use didhub_backend::state::AppState;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod support;
+
+async fn versions(state: &Arc<AppState>, path: &HashMap<String, String>) -> Value {
+    history::list::list(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+        Some(Query(HashMap::new())),
+    )
+    .await
+    .expect("list history")
+    .0
+}
+
+async fn restore(state: &Arc<AppState>, path: &HashMap<String, String>, version: i64) -> Value {
+    let mut path = path.clone();
+    path.insert("version".to_string(), version.to_string());
+    history::restore::restore(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path),
+    )
+    .await
+    .expect("restore version")
+    .0
+}
+
+#[tokio::test]
+async fn alter_versions_can_be_listed_and_restored() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let admin = Uuid::new_v4();
+    let state = support::test_state(&pool, &["admin"], Some(admin));
+    let alter_id: Uuid = sqlx::query_scalar("SELECT id FROM alters WHERE name = 'Juniper'")
+        .fetch_one(&pool)
+        .await
+        .expect("seeded alter");
+    let path = HashMap::from([("alterId".to_string(), alter_id.to_string())]);
+
+    for body in [
+        json!({ "name": "Juno" }),
+        json!({ "name": "Juno" }),
+        json!({ "job": "ranger" }),
+    ] {
+        let _updated = alters::update::update(
+            Extension(state.clone()),
+            support::auth_headers(),
+            Path(path.clone()),
+            Some(Json(body)),
+        )
+        .await
+        .expect("update alter");
+    }
+    let _deleted = alters::delete::delete(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect("delete alter");
+
+    // The no-op update stores no version; the deleted alter's history stays listable
+    let body = versions(&state, &path).await;
+    assert_eq!(body["entityType"], "alter");
+    assert_eq!(body["pagination"]["total"], 3);
+    let items = body["items"].as_array().expect("items");
+    let actions: Vec<&str> = items
+        .iter()
+        .map(|i| i["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(actions, ["delete", "update", "update"]);
+    assert_eq!(items[2]["version"], 1);
+    assert_eq!(items[2]["snapshot"]["name"], "Juniper");
+    assert_eq!(items[0]["snapshot"]["job"], "ranger");
+    assert_eq!(items[0]["actor"], admin.to_string());
+
+    // Restoring a trashed alter takes it out of the trash
+    let restored = restore(&state, &path, 1).await;
+    assert_eq!(restored["name"], "Juniper");
+    let _alter = alters::get::get(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect("alter restored");
+
+    // Restoring stores the replaced state first
+    let restored = restore(&state, &path, 2).await;
+    assert_eq!(restored["name"], "Juno");
+    let body = versions(&state, &path).await;
+    assert_eq!(body["pagination"]["total"], 5);
+    assert_eq!(body["items"][0]["action"], "restore");
+    assert_eq!(body["items"][0]["snapshot"]["name"], "Juniper");
+
+    // A purged alter is re-created
+    sqlx::query("DELETE FROM alters WHERE id = ?")
+        .bind(alter_id)
+        .execute(&pool)
+        .await
+        .expect("purge alter");
+    let restored = restore(&state, &path, 1).await;
+    assert_eq!(restored["name"], "Juniper");
+    let name: String = sqlx::query_scalar("SELECT name FROM alters WHERE id = ?")
+        .bind(alter_id)
+        .fetch_one(&pool)
+        .await
+        .expect("alter re-created");
+    assert_eq!(name, "Juniper");
+
+    let mut missing = path.clone();
+    missing.insert("version".to_string(), "99".to_string());
+    let err = history::restore::restore(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(missing),
+    )
+    .await
+    .expect_err("unknown version");
+    assert!(err.to_string().contains("version not found"));
+
+    // Only admins and the owner may see the history
+    let stranger = support::test_state(&pool, &["user"], Some(Uuid::new_v4()));
+    history::list::list(
+        Extension(stranger),
+        support::auth_headers(),
+        Path(path.clone()),
+        None,
+    )
+    .await
+    .expect_err("stranger denied");
+}
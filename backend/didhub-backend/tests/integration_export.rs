@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::io::{Cursor, Read};
+use std::time::Duration;
+
+use axum::extract::{Extension, Path};
+use axum::response::Response;
+use didhub_backend::export::SystemExportExecutor;
+use didhub_backend::handlers::exports;
+use serde_json::Value;
+use uuid::Uuid;
+
+mod support;
+
+async fn archive(response: Response) -> zip::ZipArchive<Cursor<Vec<u8>>> {
+    assert_eq!(
+        response.headers()["content-type"],
+        "application/zip",
+        "archive content type"
+    );
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .expect("read body");
+    zip::ZipArchive::new(Cursor::new(body.to_vec())).expect("valid zip")
+}
+
+fn document(archive: &mut zip::ZipArchive<Cursor<Vec<u8>>>, name: &str) -> Value {
+    let mut text = String::new();
+    archive
+        .by_name(name)
+        .expect("document in archive")
+        .read_to_string(&mut text)
+        .expect("read document");
+    serde_json::from_str(&text).expect("document is json")
+}
+
+#[tokio::test]
+async fn export_contains_data_and_files_directly_and_in_background() {
+    let ctx = support::upload_test_context().await;
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let (alter_id, system_id): (Uuid, Uuid) =
+        sqlx::query_as("SELECT id, user_id FROM alters WHERE name = 'Willow'")
+            .fetch_one(&pool)
+            .await
+            .expect("seeded alter");
+    let other_id: Uuid = sqlx::query_scalar("SELECT id FROM users WHERE id <> ? LIMIT 1")
+        .bind(system_id)
+        .fetch_one(&pool)
+        .await
+        .expect("another user");
+
+    let (file_id, file_id_s) = support::write_png_file(&ctx.uploads_dir, [10, 20, 30, 255]);
+    sqlx::query(
+        "INSERT INTO stored_files (id, file_hash, mime_type, size, created_at) VALUES (?, 'hash', 'image/png', 0, ?)",
+    )
+    .bind(file_id)
+    .bind(chrono::Utc::now().to_rfc3339())
+    .execute(&pool)
+    .await
+    .expect("insert stored file");
+    sqlx::query("UPDATE alters SET images = ? WHERE id = ?")
+        .bind(format!("[\"{file_id_s}\"]"))
+        .bind(alter_id)
+        .execute(&pool)
+        .await
+        .expect("set alter image");
+
+    let state = support::test_state(&pool, &["user"], Some(system_id));
+    let response = exports::stream::stream(Extension(state.clone()), support::auth_headers())
+        .await
+        .expect("stream export");
+    let mut zip = archive(response).await;
+    let manifest = document(&mut zip, "manifest.json");
+    assert_eq!(manifest["format"], "didhub-export");
+    assert_eq!(manifest["userId"], system_id.to_string());
+    assert_eq!(
+        document(&mut zip, "profile.json")["id"],
+        system_id.to_string()
+    );
+    let alters = document(&mut zip, "alters.json");
+    let alters = alters.as_array().expect("alters array");
+    assert!(alters
+        .iter()
+        .any(|alter| alter["id"] == alter_id.to_string()));
+    assert!(alters
+        .iter()
+        .all(|alter| alter["user_id"] == system_id.to_string()));
+    for name in [
+        "affiliations.json",
+        "subsystems.json",
+        "relationships.json",
+        "tags.json",
+        "custom_fields.json",
+        "uploads.json",
+    ] {
+        assert!(document(&mut zip, name).is_array(), "{name} is an array");
+    }
+    let mut file = Vec::new();
+    zip.by_name(&format!("files/{file_id_s}"))
+        .expect("image in archive")
+        .read_to_end(&mut file)
+        .expect("read image");
+    assert_eq!(
+        file,
+        std::fs::read(ctx.uploads_dir.join(&file_id_s)).unwrap()
+    );
+
+    // The same archive built by the background job
+    state
+        .job_queue
+        .register_executor(SystemExportExecutor::new((*state).clone()))
+        .await;
+    let started = exports::start::start(Extension(state.clone()), support::auth_headers())
+        .await
+        .expect("start export")
+        .0;
+    let export_id = started["exportId"].as_str().unwrap().to_string();
+    let path = HashMap::from([("exportId".to_string(), export_id.clone())]);
+    let mut status = Value::Null;
+    for _ in 0..100 {
+        status = exports::status::status(
+            Extension(state.clone()),
+            support::auth_headers(),
+            Path(path.clone()),
+        )
+        .await
+        .expect("export status")
+        .0;
+        if status["status"] == "completed" || status["status"] == "failed" {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(status["status"], "completed", "{status}");
+    assert_eq!(
+        status["downloadUrl"],
+        format!("/api/me/exports/{export_id}/download")
+    );
+
+    let other = support::test_state(&pool, &["user"], Some(other_id));
+    assert!(exports::status::status(
+        Extension(other.clone()),
+        support::auth_headers(),
+        Path(path.clone())
+    )
+    .await
+    .is_err());
+    assert!(exports::download::download(
+        Extension(other),
+        support::auth_headers(),
+        Path(path.clone())
+    )
+    .await
+    .is_err());
+
+    let response = exports::download::download(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path),
+    )
+    .await
+    .expect("download export");
+    let mut zip = archive(response).await;
+    assert_eq!(
+        document(&mut zip, "manifest.json")["userId"],
+        system_id.to_string()
+    );
+    assert!(zip.by_name(&format!("files/{file_id_s}")).is_ok());
+}
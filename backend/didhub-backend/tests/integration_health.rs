@@ -0,0 +1,140 @@
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use serde_json::Value;
+use tower::util::ServiceExt;
+
+mod support;
+
+async fn get(app: &axum::Router, uri: &str) -> (StatusCode, String) {
+    let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, String::from_utf8(bytes.to_vec()).unwrap())
+}
+
+fn check<'a>(report: &'a Value, name: &str) -> &'a Value {
+    report["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|check| check["name"] == name)
+        .unwrap_or_else(|| panic!("no {name} check in {report}"))
+}
+
+#[tokio::test]
+async fn deep_checks_probe_dependencies_and_set_the_gauge() {
+    // Holds the uploads directory for the duration of the test
+    let ctx = support::upload_test_context().await;
+    let pool = support::sqlite_pool().await;
+    let state = support::test_state(&pool, &["user"], None);
+    let app = didhub_backend::build_router(state);
+
+    // Without ?deep the endpoints stay cheap
+    assert_eq!(get(&app, "/api/ready").await, (StatusCode::OK, "OK".into()));
+    let (status, _) = get(&app, &format!("/api/alters/{}", uuid::Uuid::new_v4())).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (_, metrics) = get(&app, "/api/metrics").await;
+    assert!(metrics.contains("# TYPE didhub_dependency_up gauge"));
+    assert!(!metrics.contains("didhub_dependency_up{"));
+    #[cfg(target_os = "linux")]
+    assert!(metrics.contains("# TYPE process_resident_memory_bytes gauge"));
+    assert!(metrics.contains(
+        "didhub_http_request_duration_seconds_count{method=\"GET\",route=\"/api/ready\"} 1\n"
+    ));
+    // Latency is labelled by route template, never by the raw path
+    assert!(metrics.contains(
+        "didhub_http_request_duration_seconds_count{method=\"GET\",route=\"/api/alters/{alterId}\"} 1\n"
+    ));
+
+    // A database that was never migrated is not ready
+    let (status, body) = get(&app, "/api/ready?deep=true").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let report: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(report["status"], "fail");
+    assert_eq!(check(&report, "database")["up"], true);
+    assert!(check(&report, "database")["latencyMs"].is_number());
+    assert_eq!(check(&report, "cache")["details"]["backend"], "memory");
+    assert_eq!(
+        check(&report, "uploads")["details"]["directory"],
+        ctx.uploads_dir.to_str().unwrap()
+    );
+    let migrations = check(&report, "migrations");
+    assert_eq!(migrations["up"], false);
+    assert!(migrations["error"].as_str().unwrap().contains("pending"));
+    let (_, metrics) = get(&app, "/api/metrics").await;
+    assert!(metrics.contains("didhub_dependency_up{dependency=\"database\"} 1"));
+    assert!(metrics.contains("didhub_dependency_up{dependency=\"migrations\"} 0"));
+
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    let (status, body) = get(&app, "/api/health?deep=1").await;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let report: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(report["status"], "ok");
+    let (_, metrics) = get(&app, "/api/metrics").await;
+    assert!(metrics.contains("didhub_dependency_up{dependency=\"migrations\"} 1"));
+
+    // An uploads directory that cannot be written to fails the check
+    std::env::set_var(
+        "DIDHUB_UPLOADS_DIRECTORY",
+        ctx.uploads_dir.join("missing").to_str().unwrap(),
+    );
+    let (status, body) = get(&app, "/api/health?deep=true").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    let report: Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(check(&report, "uploads")["up"], false);
+}
+
+#[tokio::test]
+async fn metrics_scrapes_need_the_token_or_an_allowed_address() {
+    use axum::extract::ConnectInfo;
+    use didhub_backend::metrics_access::MetricsAccess;
+
+    let pool = support::sqlite_pool().await;
+    let state = support::test_state(&pool, &["admin"], None);
+    state.set_metrics_access(MetricsAccess {
+        bearer_token: Some("scrape-secret".to_string()),
+        allowed_networks: vec!["10.0.0.0/8".parse().unwrap()],
+    });
+    let app = didhub_backend::build_router(state);
+
+    let scrape = |authorization: Option<&str>, peer: &str| {
+        let mut request = Request::builder().uri("/api/metrics");
+        if let Some(value) = authorization {
+            request = request.header("authorization", value);
+        }
+        let mut request = request.body(Body::empty()).unwrap();
+        let peer: std::net::SocketAddr = peer.parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(peer));
+        let app = app.clone();
+        async move { app.oneshot(request).await.unwrap().status() }
+    };
+
+    assert_eq!(
+        scrape(None, "192.0.2.7:4000").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        scrape(Some("Bearer wrong"), "192.0.2.7:4000").await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        scrape(Some("Bearer scrape-secret"), "192.0.2.7:4000").await,
+        StatusCode::OK
+    );
+    assert_eq!(scrape(None, "10.1.2.3:4000").await, StatusCode::OK);
+
+    // Without a token only the address counts
+    let state = support::test_state(&pool, &["admin"], None);
+    state.set_metrics_access(MetricsAccess {
+        bearer_token: None,
+        allowed_networks: vec!["127.0.0.1/32".parse().unwrap()],
+    });
+    let (status, _) = get(&didhub_backend::build_router(state), "/api/metrics").await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,198 @@
+use axum::extract::{Extension, Json};
+use didhub_backend::handlers::imports;
+use didhub_db::DbPool;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod support;
+
+async fn alter_names(pool: &DbPool, system_id: Uuid) -> Vec<String> {
+    sqlx::query_scalar("SELECT name FROM alters WHERE user_id = ? ORDER BY name")
+        .bind(system_id)
+        .fetch_all(pool)
+        .await
+        .expect("alter names")
+}
+
+fn actions(report: &Value, key: &str) -> Vec<(String, String)> {
+    report[key]
+        .as_array()
+        .expect("entities")
+        .iter()
+        .map(|entity| {
+            (
+                entity["name"].as_str().unwrap().to_string(),
+                entity["action"].as_str().unwrap().to_string(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn pluralkit_and_simply_plural_exports_are_imported_with_conflict_strategies() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let (affiliation_id, system_id): (Uuid, Uuid) =
+        sqlx::query_as("SELECT id, owner_user_id FROM affiliations WHERE name = 'Garden Keepers'")
+            .fetch_one(&pool)
+            .await
+            .expect("seeded affiliation");
+    let existing: String =
+        sqlx::query_scalar("SELECT name FROM alters WHERE user_id = ? ORDER BY name LIMIT 1")
+            .bind(system_id)
+            .fetch_one(&pool)
+            .await
+            .expect("seeded alter");
+    let state = support::test_state(&pool, &["user"], Some(system_id));
+    let import = |body: Value| {
+        let state = state.clone();
+        async move {
+            imports::run::run(Extension(state), support::auth_headers(), Some(Json(body))).await
+        }
+    };
+
+    let pluralkit = json!({
+        "version": 2,
+        "members": [
+            { "id": "aaaaa", "uuid": "9f0c1e52-0000-4000-8000-000000000001", "name": existing, "pronouns": "they/them" },
+            { "id": "bbbbb", "name": "Rowan", "birthday": "0004-03-14", "color": "ff0000" },
+        ],
+        "groups": [
+            {
+                "id": "ggggg",
+                "name": "garden keepers",
+                "members": ["9f0c1e52-0000-4000-8000-000000000001", "bbbbb", "zzzzz"],
+            },
+        ],
+        "switches": [{ "timestamp": "2024-01-01T00:00:00Z", "members": ["aaaaa"] }],
+    });
+
+    // A dry run reports renames without writing anything
+    let before = alter_names(&pool, system_id).await;
+    let preview = import(json!({
+        "source": "pluralkit",
+        "data": pluralkit,
+        "conflict": "rename",
+        "dryRun": true,
+    }))
+    .await
+    .expect("dry run")
+    .0;
+    assert_eq!(preview["dryRun"], true);
+    assert_eq!(
+        actions(&preview, "alters"),
+        vec![
+            (format!("{existing} (2)"), "rename".to_string()),
+            ("Rowan".to_string(), "create".to_string()),
+        ]
+    );
+    assert_eq!(
+        actions(&preview, "affiliations"),
+        vec![("garden keepers (2)".to_string(), "rename".to_string())]
+    );
+    assert_eq!(preview["affiliations"][0]["newMembers"], 2);
+    let warnings = preview["warnings"].to_string();
+    assert!(warnings.contains("zzzzz"), "{warnings}");
+    assert!(warnings.contains("switch"), "{warnings}");
+    assert_eq!(alter_names(&pool, system_id).await, before);
+
+    // Merging reuses the existing alter and affiliation
+    let report = import(json!({
+        "source": "pluralkit",
+        "data": pluralkit,
+        "conflict": "merge",
+    }))
+    .await
+    .expect("import")
+    .0;
+    assert_eq!(
+        actions(&report, "alters"),
+        vec![
+            (existing.clone(), "merge".to_string()),
+            ("Rowan".to_string(), "create".to_string()),
+        ]
+    );
+    assert_eq!(report["affiliations"][0]["id"], affiliation_id.to_string());
+    let (rowan_id, birthday, metadata): (Uuid, Option<String>, String) = sqlx::query_as(
+        "SELECT id, birthday, metadata FROM alters WHERE user_id = ? AND name = 'Rowan'",
+    )
+    .bind(system_id)
+    .fetch_one(&pool)
+    .await
+    .expect("imported alter");
+    assert_eq!(birthday.as_deref(), Some("03-14"));
+    let metadata: Value = serde_json::from_str(&metadata).unwrap();
+    assert_eq!(metadata["color"], "ff0000");
+    assert_eq!(
+        metadata["import"],
+        json!({ "source": "pluralkit", "id": "bbbbb" })
+    );
+    let is_member: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM affiliation_members WHERE affiliation_id = ? AND alter_id = ?",
+    )
+    .bind(affiliation_id)
+    .bind(rowan_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(is_member, 1);
+
+    // Simply Plural entries may be wrapped; skipped members stay out of groups
+    let simply_plural = json!({
+        "members": [
+            { "id": "sp-1", "content": { "name": "Rowan", "desc": "again" } },
+            { "_id": "sp-2", "name": "Sage", "desc": "New here", "info": { "f1": "x" } },
+        ],
+        "groups": [
+            { "id": "sp-g", "content": { "name": "Choir", "members": ["sp-1", "sp-2"] } },
+        ],
+    });
+    let report = import(json!({ "source": "simplyplural", "data": simply_plural }))
+        .await
+        .expect("import")
+        .0;
+    assert_eq!(
+        actions(&report, "alters"),
+        vec![
+            ("Rowan".to_string(), "skip".to_string()),
+            ("Sage".to_string(), "create".to_string()),
+        ]
+    );
+    assert_eq!(report["memberships"], 1);
+    assert!(report["warnings"].to_string().contains("Sage"));
+    let description: Option<String> =
+        sqlx::query_scalar("SELECT description FROM alters WHERE id = ?")
+            .bind(rowan_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(description, None);
+
+    // Invalid requests and non-system callers are rejected
+    assert!(import(json!({ "source": "tupperbox", "data": {} }))
+        .await
+        .is_err());
+    assert!(
+        import(json!({ "source": "pluralkit", "data": { "members": [{ "id": "x" }] } }))
+            .await
+            .is_err()
+    );
+    let sam: Uuid = sqlx::query_scalar("SELECT id FROM users WHERE display_name = 'Sam'")
+        .fetch_one(&pool)
+        .await
+        .expect("seeded user");
+    let user = support::test_state(&pool, &["user"], Some(sam));
+    assert!(imports::run::run(
+        Extension(user),
+        support::auth_headers(),
+        Some(Json(json!({ "source": "pluralkit", "data": pluralkit }))),
+    )
+    .await
+    .is_err());
+}
@@ -6,6 +6,7 @@ use axum::{extract::Path, http::HeaderMap, Json};
 use chrono::Utc;
 use didhub_auth::{AuthenticatorTrait, TestAuthenticator};
 use didhub_backend::{
+    cache_warmup,
     generated::routes::{
         bulk_get_instance_settings, bulk_set_instance_settings, get_instance_setting,
         set_instance_setting,
@@ -306,3 +307,75 @@ async fn get_instance_setting_returns_value() {
 
     assert_eq!(response.0.get("value").unwrap(), "10");
 }
+
+#[tokio::test]
+async fn get_instance_setting_is_cached_until_changed() {
+    let ctx = setup(vec!["admin".to_string()]).await;
+    let path = || Path(HashMap::from([("key".to_string(), "theme".to_string())]));
+    let set = |value: &str| {
+        set_instance_setting(
+            axum::extract::Extension(ctx.state.clone()),
+            admin_headers(),
+            path(),
+            Some(Json(json!({ "value": value }))),
+        )
+    };
+    let get = || {
+        get_instance_setting(
+            axum::extract::Extension(ctx.state.clone()),
+            admin_headers(),
+            path(),
+        )
+    };
+
+    let _saved = set("dark").await.unwrap();
+    assert_eq!(get().await.unwrap().0["value"], "dark");
+
+    // A write bypassing the handlers is not seen while the entry is cached
+    sqlx::query("UPDATE instance_settings SET value_string = ? WHERE key = ?")
+        .bind("light")
+        .bind("theme")
+        .execute(&ctx.pool)
+        .await
+        .unwrap();
+    assert_eq!(get().await.unwrap().0["value"], "dark");
+
+    let _saved = set("contrast").await.unwrap();
+    assert_eq!(get().await.unwrap().0["value"], "contrast");
+}
+
+#[tokio::test]
+async fn warm_up_loads_settings_named_by_the_manifest() {
+    let ctx = setup(vec!["admin".to_string()]).await;
+    let now = Utc::now().to_rfc3339();
+    for key in ["theme", "motd"] {
+        sqlx::query("INSERT INTO instance_settings (key, value_type, value_bool, value_number, value_string, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?)")
+            .bind(key)
+            .bind("string")
+            .bind::<Option<i32>>(None)
+            .bind::<Option<f64>>(None)
+            .bind(Some("warm"))
+            .bind(&now)
+            .bind(&now)
+            .execute(&ctx.pool)
+            .await
+            .unwrap();
+    }
+
+    let manifest = vec!["instance_settings:*".to_string(), "unknown:key".to_string()];
+    assert_eq!(cache_warmup::warm_up(&ctx.state, &manifest).await, 2);
+
+    // Served from the cache from now on
+    sqlx::query("DELETE FROM instance_settings")
+        .execute(&ctx.pool)
+        .await
+        .unwrap();
+    let response = get_instance_setting(
+        axum::extract::Extension(ctx.state.clone()),
+        admin_headers(),
+        Path(HashMap::from([("key".to_string(), "motd".to_string())])),
+    )
+    .await
+    .unwrap();
+    assert_eq!(response.0["value"], "warm");
+}
@@ -0,0 +1,75 @@
+use axum::extract::Extension;
+use didhub_backend::handlers::migrations;
+use didhub_migrations::{sqlite_migrator, MigrationState};
+
+mod support;
+
+async fn schema_objects(pool: &didhub_db::DbPool) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master")
+        .fetch_one(pool)
+        .await
+        .expect("query schema")
+}
+
+#[tokio::test]
+async fn migrations_report_status_and_roll_back() {
+    let pool = support::sqlite_pool().await;
+    let migrator = sqlite_migrator();
+    let total = migrator
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .count();
+
+    let mut conn = pool.acquire().await.expect("acquire");
+    let statuses = didhub_migrations::status(migrator, &mut *conn)
+        .await
+        .expect("status");
+    assert_eq!(statuses.len(), total);
+    assert!(statuses.iter().all(|s| s.state == MigrationState::Pending));
+    // Every generated migration ships its down script
+    assert!(statuses.iter().all(|s| s.reversible));
+    drop(conn);
+
+    migrator.run(&pool).await.expect("run migrations");
+    let state = support::test_state(&pool, &["admin"], None);
+    let body = migrations::status::status(Extension(state.clone()), support::auth_headers())
+        .await
+        .expect("status endpoint")
+        .0;
+    assert_eq!(body["backend"], "sqlite");
+    assert_eq!(body["applied"], total);
+    assert_eq!(body["pending"], 0);
+
+    // Roll back the two newest migrations; their schema objects go with them
+    let objects_before = schema_objects(&pool).await;
+    let mut conn = pool.acquire().await.expect("acquire");
+    let reverted = didhub_migrations::revert(migrator, &mut *conn, 2)
+        .await
+        .expect("revert");
+    let newest = statuses.last().expect("migrations").version;
+    assert_eq!(reverted, vec![newest, newest - 1]);
+    drop(conn);
+    assert!(schema_objects(&pool).await < objects_before);
+
+    let body = migrations::status::status(Extension(state.clone()), support::auth_headers())
+        .await
+        .expect("status endpoint")
+        .0;
+    assert_eq!(body["pending"], 2);
+    assert_eq!(body["migrations"][total - 1]["state"], "pending");
+
+    // Reverting everything empties the schema, and `up` restores it
+    let mut conn = pool.acquire().await.expect("acquire");
+    let reverted = didhub_migrations::revert(migrator, &mut *conn, usize::MAX)
+        .await
+        .expect("revert all");
+    assert_eq!(reverted.len(), total - 2);
+    drop(conn);
+    let users_tables: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE name = 'users'")
+            .fetch_one(&pool)
+            .await
+            .expect("query schema");
+    assert_eq!(users_tables, 0);
+    migrator.run(&pool).await.expect("re-apply migrations");
+}
@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use axum::extract::{Extension, Query};
+use didhub_backend::handlers::search;
+use uuid::Uuid;
+
+mod support;
+
+async fn run_search(
+    state: &std::sync::Arc<didhub_backend::state::AppState>,
+    params: &[(&str, &str)],
+) -> Vec<serde_json::Value> {
+    let query: HashMap<String, String> = params
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let res = search::query::search(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Some(Query(query)),
+    )
+    .await
+    .expect("search");
+    res.0["items"].as_array().expect("items array").clone()
+}
+
+#[tokio::test]
+async fn search_ranks_and_highlights_across_entities() {
+    let pool = support::sqlite_pool().await;
+    // Full migrations, so the FTS table and its triggers are exercised
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    let state = support::test_state(&pool, &["admin"], None);
+
+    let owner = Uuid::new_v4();
+    sqlx::query("INSERT INTO users (id, username, password_hash) VALUES (?, ?, ?)")
+        .bind(owner)
+        .bind("owner")
+        .bind("hash")
+        .execute(&pool)
+        .await
+        .expect("insert user");
+
+    let gardener = Uuid::new_v4();
+    let other = Uuid::new_v4();
+    for (id, name, description, notes, interests) in [
+        (
+            gardener,
+            "Rowan",
+            "Keeps the <garden> tidy",
+            "Fronts in spring",
+            "[\"gardening\",\"tea\"]",
+        ),
+        (other, "Gardenia", "Quiet protector", "", "[]"),
+    ] {
+        sqlx::query(
+            "INSERT INTO alters (id, user_id, name, description, notes, interests, owner_user_id) VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(owner)
+        .bind(name)
+        .bind(description)
+        .bind(notes)
+        .bind(interests)
+        .bind(owner)
+        .execute(&pool)
+        .await
+        .expect("insert alter");
+    }
+    let affiliation = Uuid::new_v4();
+    sqlx::query(
+        "INSERT INTO affiliations (id, name, description, owner_user_id) VALUES (?, ?, ?, ?)",
+    )
+    .bind(affiliation)
+    .bind("Garden Club")
+    .bind("Everyone who tends the garden")
+    .bind(owner)
+    .execute(&pool)
+    .await
+    .expect("insert affiliation");
+    sqlx::query("INSERT INTO subsystems (id, name, owner_user_id) VALUES (?, ?, ?)")
+        .bind(Uuid::new_v4())
+        .bind("Night Shift")
+        .bind(owner)
+        .execute(&pool)
+        .await
+        .expect("insert subsystem");
+
+    // Body text, name prefixes and the description all match "garden"
+    let items = run_search(&state, &[("q", "garden")]).await;
+    assert_eq!(items.len(), 3, "unexpected results: {items:?}");
+    // Name matches outrank the body-only match
+    assert_eq!(items[2]["id"], gardener.to_string());
+    assert!(items.iter().any(|i| i["id"] == affiliation.to_string()));
+    let rowan = items
+        .iter()
+        .find(|i| i["id"] == gardener.to_string())
+        .expect("body match");
+    assert_eq!(rowan["systemId"], owner.to_string());
+    let snippet = rowan["snippet"].as_str().unwrap();
+    assert!(
+        snippet.contains("&lt;<mark>garden</mark>&gt;"),
+        "snippet not escaped and highlighted: {snippet}"
+    );
+
+    // All terms must match; notes are indexed
+    let items = run_search(&state, &[("q", "fronts spr")]).await;
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["id"], gardener.to_string());
+
+    // Type filter
+    let items = run_search(&state, &[("q", "garden"), ("type", "alter")]).await;
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(|i| i["type"] == "alter"));
+
+    // Updates and deletes keep the index in sync
+    sqlx::query("UPDATE subsystems SET name = ? WHERE name = ?")
+        .bind("Garden Shift")
+        .bind("Night Shift")
+        .execute(&pool)
+        .await
+        .expect("rename subsystem");
+    sqlx::query("DELETE FROM alters WHERE id = ?")
+        .bind(other)
+        .execute(&pool)
+        .await
+        .expect("delete alter");
+    let items = run_search(&state, &[("q", "garden")]).await;
+    assert_eq!(items.len(), 3);
+    assert!(items.iter().any(|i| i["type"] == "subsystem"));
+    assert!(items.iter().all(|i| i["id"] != other.to_string()));
+    assert!(run_search(&state, &[("q", "night")]).await.is_empty());
+
+    // Query syntax is neutralised rather than passed through
+    assert_eq!(run_search(&state, &[("q", "garden:* -(")]).await.len(), 3);
+
+    let err = search::query::search(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Some(Query(HashMap::from([(
+            "type".to_string(),
+            "user".to_string(),
+        )]))),
+    )
+    .await
+    .expect_err("missing q");
+    assert!(err.to_string().contains("q is required"));
+}
+
+#[tokio::test]
+async fn search_only_returns_visible_systems() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+
+    let (owner, member, outsider) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+    for (id, name, roles) in [
+        (owner, "owner", "[\"user\",\"system\"]"),
+        (member, "member", "[\"user\"]"),
+        (outsider, "outsider", "[\"user\"]"),
+    ] {
+        sqlx::query("INSERT INTO users (id, username, password_hash, roles) VALUES (?, ?, ?, ?)")
+            .bind(id)
+            .bind(name)
+            .bind("hash")
+            .bind(roles)
+            .execute(&pool)
+            .await
+            .expect("insert user");
+    }
+    sqlx::query("INSERT INTO system_members (system_id, user_id, role) VALUES (?, ?, 'viewer')")
+        .bind(owner)
+        .bind(member)
+        .execute(&pool)
+        .await
+        .expect("add member");
+    sqlx::query(
+        "INSERT INTO alters (id, user_id, name, notes, owner_user_id) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4())
+    .bind(owner)
+    .bind("Rowan")
+    .bind("Private garden notes")
+    .bind(owner)
+    .execute(&pool)
+    .await
+    .expect("insert alter");
+    sqlx::query(
+        "INSERT INTO affiliations (id, name, description, owner_user_id) VALUES (?, ?, ?, ?)",
+    )
+    .bind(Uuid::new_v4())
+    .bind("Garden Club")
+    .bind("")
+    .bind(owner)
+    .execute(&pool)
+    .await
+    .expect("insert affiliation");
+
+    for (user, expected) in [(owner, 2), (member, 2), (outsider, 0)] {
+        let state = support::test_state(&pool, &["user"], Some(user));
+        let items = run_search(&state, &[("q", "garden")]).await;
+        assert_eq!(
+            items.len(),
+            expected,
+            "unexpected results for {user}: {items:?}"
+        );
+    }
+}
@@ -0,0 +1,56 @@
+use didhub_db::seed::{self, Dataset};
+use sha2::{Digest, Sha256};
+
+mod support;
+
+async fn count(pool: &didhub_db::DbPool, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+        .fetch_one(pool)
+        .await
+        .expect("count rows")
+}
+
+#[tokio::test]
+async fn demo_seed_is_idempotent() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+
+    let first = seed::seed(&pool, Dataset::Demo, "hash")
+        .await
+        .expect("seed demo");
+    assert!(first.inserted > 0);
+    assert_eq!(first.existing, 0);
+    assert_eq!(count(&pool, "users").await, 4);
+    assert_eq!(count(&pool, "alters").await, 6);
+    assert_eq!(count(&pool, "affiliation_members").await, 4);
+    assert_eq!(count(&pool, "relationships").await, 2);
+    assert_eq!(count(&pool, "uploads").await, 2);
+
+    // Edits to seeded rows survive a reseed, and nothing is duplicated
+    sqlx::query("UPDATE alters SET name = 'Willow (edited)' WHERE name = 'Willow'")
+        .execute(&pool)
+        .await
+        .expect("edit alter");
+    let second = seed::seed(&pool, Dataset::Demo, "hash")
+        .await
+        .expect("reseed demo");
+    assert_eq!(second.inserted, 0);
+    assert_eq!(second.existing, first.inserted);
+    assert_eq!(count(&pool, "alters").await, 6);
+    let renamed: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM alters WHERE name = 'Willow (edited)'")
+            .fetch_one(&pool)
+            .await
+            .expect("query alters");
+    assert_eq!(renamed, 1);
+
+    // The recorded hashes match the file content, so real uploads deduplicate against them
+    for file in seed::files(Dataset::Demo) {
+        assert_eq!(hex::encode(Sha256::digest(file.content)), file.sha256);
+    }
+    assert_eq!("demo".parse::<Dataset>(), Ok(Dataset::Demo));
+    assert!("prod".parse::<Dataset>().is_err());
+}
@@ -0,0 +1,356 @@
+use std::collections::HashMap;
+
+use axum::extract::Path;
+use axum::{Extension, Json};
+use didhub_backend::generated::routes::{
+    add_subsystem_member, create_affiliation, create_alter, create_subsystem, delete_subsystem,
+    get_affiliation, get_alter, get_subsystem, list_own_systems, list_subsystem_members,
+    list_system_members, remove_subsystem_member, remove_system_member, set_system_member,
+    update_alter, update_subsystem,
+};
+use didhub_backend::handlers::{affiliations, alters, history, subsystems};
+use serde_json::json;
+use uuid::Uuid;
+
+mod support;
+
+const SYSTEM: Uuid = Uuid::from_u128(0xa);
+const FRIEND: Uuid = Uuid::from_u128(0xb);
+const STRANGER: Uuid = Uuid::from_u128(0xc);
+
+async fn setup() -> didhub_db::DbPool {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    for (id, username, roles) in [
+        (SYSTEM, "grove", r#"["system"]"#),
+        (FRIEND, "robin", r#"["user"]"#),
+        (STRANGER, "wren", r#"["user"]"#),
+    ] {
+        sqlx::query(
+            "INSERT INTO users (id, username, password_hash, roles) VALUES (?, ?, 'hash', ?)",
+        )
+        .bind(id)
+        .bind(username)
+        .bind(roles)
+        .execute(&pool)
+        .await
+        .expect("insert user");
+    }
+    pool
+}
+
+fn member_path(user_id: Uuid) -> Path<HashMap<String, String>> {
+    Path(HashMap::from([
+        ("systemId".to_string(), SYSTEM.to_string()),
+        ("userId".to_string(), user_id.to_string()),
+    ]))
+}
+
+#[tokio::test]
+async fn members_edit_the_system_with_their_role() {
+    let pool = setup().await;
+    let headers = support::auth_headers();
+    let as_user = |id: Uuid| Extension(support::test_state(&pool, &["user"], Some(id)));
+
+    let alter = create_alter(
+        as_user(SYSTEM),
+        headers.clone(),
+        Some(Json(json!({ "name": "Ash" }))),
+    )
+    .await
+    .expect("create alter")
+    .0;
+    let alter_path = || {
+        Path(HashMap::from([(
+            "alterId".to_string(),
+            alter["id"].as_str().unwrap().to_string(),
+        )]))
+    };
+    let rename = |id: Uuid, name: &str| {
+        update_alter(
+            as_user(id),
+            headers.clone(),
+            alter_path(),
+            Some(Json(json!({ "name": name }))),
+        )
+    };
+
+    assert!(rename(FRIEND, "Birch").await.is_err());
+
+    // The system account is an owner and adds an editor
+    let member = set_system_member(
+        as_user(SYSTEM),
+        headers.clone(),
+        member_path(FRIEND),
+        Some(Json(json!({ "role": "editor" }))),
+    )
+    .await
+    .expect("add member")
+    .0;
+    assert_eq!(member["role"], "editor");
+    assert_eq!(member["username"], "robin");
+
+    let renamed = rename(FRIEND, "Birch").await.expect("editor renames").0;
+    assert_eq!(renamed["name"], "Birch");
+    assert!(rename(STRANGER, "Cedar").await.is_err());
+
+    let created = create_alter(
+        as_user(FRIEND),
+        headers.clone(),
+        Some(Json(json!({ "name": "Fern", "systemId": SYSTEM }))),
+    )
+    .await
+    .expect("editor creates in the system")
+    .0;
+    assert_eq!(created["user_id"], SYSTEM.to_string());
+
+    let own = list_own_systems(as_user(FRIEND), headers.clone())
+        .await
+        .expect("own systems")
+        .0;
+    assert_eq!(own["items"][0]["systemId"], SYSTEM.to_string());
+    assert_eq!(own["items"][0]["name"], "grove");
+
+    // Editors cannot manage members; viewers only read the list
+    assert!(set_system_member(
+        as_user(FRIEND),
+        headers.clone(),
+        member_path(STRANGER),
+        Some(Json(json!({ "role": "viewer" }))),
+    )
+    .await
+    .is_err());
+    let _viewer = set_system_member(
+        as_user(SYSTEM),
+        headers.clone(),
+        member_path(STRANGER),
+        Some(Json(json!({ "role": "viewer" }))),
+    )
+    .await
+    .expect("add viewer");
+    let members = list_system_members(
+        as_user(STRANGER),
+        headers.clone(),
+        Path(HashMap::from([(
+            "systemId".to_string(),
+            SYSTEM.to_string(),
+        )])),
+    )
+    .await
+    .expect("viewer lists members")
+    .0;
+    assert_eq!(members["items"].as_array().unwrap().len(), 2);
+    assert!(rename(STRANGER, "Cedar").await.is_err());
+
+    assert!(set_system_member(
+        as_user(SYSTEM),
+        headers.clone(),
+        member_path(SYSTEM),
+        Some(Json(json!({ "role": "viewer" }))),
+    )
+    .await
+    .is_err());
+
+    // Leaving the system takes the access away
+    let _left = remove_system_member(as_user(FRIEND), headers.clone(), member_path(FRIEND))
+        .await
+        .expect("member leaves");
+    assert!(rename(FRIEND, "Dogwood").await.is_err());
+}
+
+#[tokio::test]
+async fn only_members_see_and_manage_the_system() {
+    let pool = setup().await;
+    let headers = support::auth_headers();
+    let as_user = |id: Uuid| Extension(support::test_state(&pool, &["user"], Some(id)));
+
+    let alter = create_alter(
+        as_user(SYSTEM),
+        headers.clone(),
+        Some(Json(json!({ "name": "Ash" }))),
+    )
+    .await
+    .expect("create alter")
+    .0;
+    let affiliation = create_affiliation(
+        as_user(SYSTEM),
+        headers.clone(),
+        Some(Json(json!({ "name": "Grove keepers" }))),
+    )
+    .await
+    .expect("create affiliation")
+    .0;
+    let _viewer = set_system_member(
+        as_user(SYSTEM),
+        headers.clone(),
+        member_path(FRIEND),
+        Some(Json(json!({ "role": "viewer" }))),
+    )
+    .await
+    .expect("add viewer");
+
+    // Viewers cannot create subsystems, the system account can
+    assert!(create_subsystem(
+        as_user(FRIEND),
+        headers.clone(),
+        Some(Json(json!({ "name": "Little ones", "systemId": SYSTEM }))),
+    )
+    .await
+    .is_err());
+    let subsystem = create_subsystem(
+        as_user(SYSTEM),
+        headers.clone(),
+        Some(Json(json!({ "name": "Little ones" }))),
+    )
+    .await
+    .expect("system creates subsystem")
+    .0;
+    assert_eq!(subsystem["owner_user_id"], SYSTEM.to_string());
+
+    let path = |key: &str, value: &serde_json::Value| {
+        Path(HashMap::from([(
+            key.to_string(),
+            value.as_str().unwrap().to_string(),
+        )]))
+    };
+    let alter_path = || path("alterId", &alter["id"]);
+    let affiliation_path = || path("affiliationId", &affiliation["id"]);
+    let subsystem_path = || path("subsystemId", &subsystem["id"]);
+
+    // Members read the system, strangers find nothing
+    for (id, visible) in [(SYSTEM, true), (FRIEND, true), (STRANGER, false)] {
+        let listed = |value: &serde_json::Value| {
+            value
+                .get("items")
+                .unwrap_or(value)
+                .as_array()
+                .unwrap()
+                .len()
+        };
+        let count = if visible { 1 } else { 0 };
+        let listed_alters = alters::list::list(as_user(id), headers.clone(), None)
+            .await
+            .expect("list alters")
+            .0;
+        assert_eq!(listed(&listed_alters), count);
+        let listed_affiliations = affiliations::list::list(as_user(id), headers.clone(), None)
+            .await
+            .expect("list affiliations")
+            .0;
+        assert_eq!(listed(&listed_affiliations), count);
+        assert_eq!(listed_affiliations["pagination"]["total"], count);
+        let listed_subsystems = subsystems::list::list(as_user(id), headers.clone(), None)
+            .await
+            .expect("list subsystems")
+            .0;
+        assert_eq!(listed(&listed_subsystems), count);
+
+        assert_eq!(
+            get_alter(as_user(id), headers.clone(), alter_path())
+                .await
+                .is_ok(),
+            visible
+        );
+        assert_eq!(
+            get_affiliation(as_user(id), headers.clone(), affiliation_path())
+                .await
+                .is_ok(),
+            visible
+        );
+        assert_eq!(
+            get_subsystem(as_user(id), headers.clone(), subsystem_path())
+                .await
+                .is_ok(),
+            visible
+        );
+        assert_eq!(
+            list_subsystem_members(as_user(id), headers.clone(), subsystem_path())
+                .await
+                .is_ok(),
+            visible
+        );
+    }
+
+    // Editors manage the system's subsystems
+    let rename = |id: Uuid| {
+        update_subsystem(
+            as_user(id),
+            headers.clone(),
+            subsystem_path(),
+            Some(Json(json!({ "name": "Sprouts" }))),
+        )
+    };
+    assert!(rename(FRIEND).await.is_err());
+    let _editor = set_system_member(
+        as_user(SYSTEM),
+        headers.clone(),
+        member_path(FRIEND),
+        Some(Json(json!({ "role": "editor" }))),
+    )
+    .await
+    .expect("promote to editor");
+    let renamed = rename(FRIEND).await.expect("editor renames subsystem").0;
+    assert_eq!(renamed["name"], "Sprouts");
+    assert!(update_subsystem(
+        as_user(FRIEND),
+        headers.clone(),
+        subsystem_path(),
+        Some(Json(json!({ "owner_user_id": STRANGER }))),
+    )
+    .await
+    .is_err());
+
+    // ...including their members and history
+    let member_of_subsystem = || {
+        Path(HashMap::from([
+            (
+                "subsystemId".to_string(),
+                subsystem["id"].as_str().unwrap().to_string(),
+            ),
+            (
+                "memberId".to_string(),
+                alter["id"].as_str().unwrap().to_string(),
+            ),
+        ]))
+    };
+    let add_member = |id: Uuid| {
+        add_subsystem_member(
+            as_user(id),
+            headers.clone(),
+            subsystem_path(),
+            Some(Json(json!({ "alterId": alter["id"] }))),
+        )
+    };
+    assert!(add_member(STRANGER).await.is_err());
+    let _added = add_member(FRIEND).await.expect("editor adds member");
+    assert!(
+        remove_subsystem_member(as_user(STRANGER), headers.clone(), member_of_subsystem())
+            .await
+            .is_err()
+    );
+    let removed = remove_subsystem_member(as_user(FRIEND), headers.clone(), member_of_subsystem())
+        .await
+        .expect("editor removes member")
+        .0;
+    assert_eq!(removed["deleted"], true);
+    for (id, visible) in [(FRIEND, true), (STRANGER, false)] {
+        assert_eq!(
+            history::list::list(as_user(id), headers.clone(), subsystem_path(), None)
+                .await
+                .is_ok(),
+            visible
+        );
+    }
+
+    assert!(
+        delete_subsystem(as_user(STRANGER), headers.clone(), subsystem_path())
+            .await
+            .is_err()
+    );
+    let _deleted = delete_subsystem(as_user(FRIEND), headers.clone(), subsystem_path())
+        .await
+        .expect("editor deletes subsystem");
+}
@@ -10,6 +10,8 @@ use uuid::Uuid;
 
 use didhub_backend::handlers::{alters, relationships, uploads};
 
+mod support;
+
 fn auth_headers() -> axum::http::HeaderMap {
     let mut headers = axum::http::HeaderMap::new();
     headers.insert(
@@ -72,12 +74,14 @@ async fn system_user_restrictions() {
             is_dormant INTEGER NOT NULL,
             is_merged INTEGER NOT NULL,
             owner_user_id TEXT NOT NULL,
-            created_at TEXT NOT NULL
+            created_at TEXT NOT NULL,
+            deleted_at TEXT
         )"#,
     )
     .execute(&pool)
     .await
     .expect("create alters table");
+    support::create_custom_field_tables(&pool).await;
 
     sqlx::query(
         r#"CREATE TABLE uploads (
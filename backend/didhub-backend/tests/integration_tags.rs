@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use axum::extract::{Extension, Json, Path, Query};
+use didhub_backend::handlers::{alters, tags};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+mod support;
+
+#[tokio::test]
+async fn tags_can_be_attached_to_alters_and_filtered_on() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let user_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM users ORDER BY username")
+        .fetch_all(&pool)
+        .await
+        .expect("seeded users");
+    // demo-willow owns the alters, demo-ember is another system
+    let state = support::test_state(&pool, &["user"], Some(user_ids[3]));
+    let other = support::test_state(&pool, &["user"], Some(user_ids[1]));
+    let alter_id = |name: &'static str| {
+        let pool = pool.clone();
+        async move {
+            sqlx::query_scalar::<_, Uuid>("SELECT id FROM alters WHERE name = ?")
+                .bind(name)
+                .fetch_one(&pool)
+                .await
+                .expect("seeded alter")
+        }
+    };
+    let (juniper, moss) = (alter_id("Juniper").await, alter_id("Moss").await);
+
+    let mut created = Vec::new();
+    for body in [
+        json!({ "name": "front", "color": "#FF8800" }),
+        json!({ "name": " calm " }),
+    ] {
+        let tag = tags::create::create(
+            Extension(state.clone()),
+            support::auth_headers(),
+            Some(Json(body)),
+        )
+        .await
+        .expect("create tag")
+        .0;
+        created.push(tag["id"].as_str().unwrap().to_string());
+    }
+    let (front, calm) = (created[0].clone(), created[1].clone());
+    for body in [
+        json!({ "name": "front" }),
+        json!({ "name": "loud", "color": "orange" }),
+    ] {
+        tags::create::create(
+            Extension(state.clone()),
+            support::auth_headers(),
+            Some(Json(body)),
+        )
+        .await
+        .expect_err("duplicate name or invalid color");
+    }
+
+    let listed = tags::list::list(Extension(state.clone()), support::auth_headers())
+        .await
+        .expect("list tags")
+        .0;
+    let names: Vec<&str> = listed["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["calm", "front"]);
+    assert_eq!(listed["items"][1]["color"], "#ff8800");
+
+    // Another user neither sees nor may use these tags
+    let theirs = tags::list::list(Extension(other.clone()), support::auth_headers())
+        .await
+        .expect("list other tags")
+        .0;
+    assert_eq!(theirs["items"].as_array().unwrap().len(), 0);
+    let set = |state, alter: Uuid, ids: Vec<&String>| {
+        alters::tags::set(
+            Extension(state),
+            support::auth_headers(),
+            Path(HashMap::from([("alterId".to_string(), alter.to_string())])),
+            Some(Json(json!({ "tagIds": ids }))),
+        )
+    };
+    set(other.clone(), juniper, vec![&front])
+        .await
+        .expect_err("foreign tag");
+
+    let tagged = set(state.clone(), juniper, vec![&front, &calm, &front])
+        .await
+        .expect("tag juniper")
+        .0;
+    assert_eq!(tagged["items"].as_array().unwrap().len(), 2);
+    let _tagged = set(state.clone(), moss, vec![&front])
+        .await
+        .expect("tag moss");
+
+    let list_tagged = |tag_ids: String| {
+        let state = state.clone();
+        async move {
+            let listed = alters::list::list(
+                Extension(state),
+                support::auth_headers(),
+                Some(Query(HashMap::from([("tags".to_string(), tag_ids)]))),
+            )
+            .await
+            .expect("list alters")
+            .0;
+            listed
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|a| a["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(list_tagged(front.clone()).await, ["Juniper", "Moss"]);
+    assert_eq!(list_tagged(format!("{front},{calm}")).await, ["Juniper"]);
+
+    let alter = alters::get::get(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(HashMap::from([(
+            "alterId".to_string(),
+            juniper.to_string(),
+        )])),
+    )
+    .await
+    .expect("get alter")
+    .0;
+    assert_eq!(alter["tags"].as_array().unwrap().len(), 2);
+
+    // Renaming keeps the tag attached; deleting detaches it
+    let tag_path = HashMap::from([("tagId".to_string(), front.clone())]);
+    let renamed = tags::update::update(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(tag_path.clone()),
+        Some(Json(json!({ "name": "fronting", "color": null }))),
+    )
+    .await
+    .expect("update tag")
+    .0;
+    assert_eq!(renamed["name"], "fronting");
+    assert_eq!(renamed["color"], Value::Null);
+    tags::delete::delete(
+        Extension(other.clone()),
+        support::auth_headers(),
+        Path(tag_path.clone()),
+    )
+    .await
+    .expect_err("not their tag");
+    let _deleted = tags::delete::delete(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(tag_path),
+    )
+    .await
+    .expect("delete tag");
+    assert_eq!(list_tagged(calm).await, ["Juniper"]);
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM alter_tags")
+        .fetch_one(&pool)
+        .await
+        .expect("count alter tags");
+    assert_eq!(remaining, 1);
+}
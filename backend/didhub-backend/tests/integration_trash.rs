@@ -0,0 +1,287 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use axum::extract::{Extension, Path, Query};
+use didhub_backend::handlers::{affiliations, alters, bulk, subsystems, trash};
+use uuid::Uuid;
+
+mod support;
+
+#[tokio::test]
+async fn deleted_alters_go_to_the_trash_until_purged() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let state = support::test_state(&pool, &["admin"], Some(Uuid::new_v4()));
+    let alter_id: Uuid = sqlx::query_scalar("SELECT id FROM alters WHERE name = 'Juniper'")
+        .fetch_one(&pool)
+        .await
+        .expect("seeded alter");
+    let memberships = || async {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM affiliation_members WHERE alter_id = ?")
+            .bind(alter_id)
+            .fetch_one(&pool)
+            .await
+            .expect("count memberships")
+    };
+    let member_of = memberships().await;
+    let path = HashMap::from([("alterId".to_string(), alter_id.to_string())]);
+
+    let _deleted = alters::delete::delete(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect("delete alter");
+
+    // Gone from lookups and listings, but kept with its memberships
+    alters::get::get(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect_err("trashed alter is not found");
+    let listed = alters::list::list(Extension(state.clone()), support::auth_headers(), None)
+        .await
+        .expect("list alters")
+        .0;
+    assert!(listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|a| a["id"] != alter_id.to_string()));
+    assert_eq!(memberships().await, member_of);
+
+    let items = trash::list::list(Extension(state.clone()), support::auth_headers(), None)
+        .await
+        .expect("list trash")
+        .0["items"]
+        .as_array()
+        .unwrap()
+        .clone();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["type"], "alter");
+    assert_eq!(items[0]["name"], "Juniper");
+    assert!(items[0]["purgeAt"].is_string());
+
+    let restored = trash::restore::restore(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect("restore alter")
+    .0;
+    assert_eq!(restored["name"], "Juniper");
+    let _alter = alters::get::get(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect("restored alter is found");
+    trash::restore::restore(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect_err("alter is no longer in the trash");
+
+    // Trash an affiliation as well, then let one expire
+    let affiliation_id: Uuid = sqlx::query_scalar("SELECT id FROM affiliations LIMIT 1")
+        .fetch_one(&pool)
+        .await
+        .expect("seeded affiliation");
+    let _deleted = alters::delete::delete(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect("delete alter again");
+    let _deleted = affiliations::delete::delete(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(HashMap::from([(
+            "affiliationId".to_string(),
+            affiliation_id.to_string(),
+        )])),
+    )
+    .await
+    .expect("delete affiliation");
+    let only_affiliations = trash::list::list(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Some(Query(HashMap::from([(
+            "type".to_string(),
+            "affiliation".to_string(),
+        )]))),
+    )
+    .await
+    .expect("filter trash")
+    .0;
+    assert_eq!(only_affiliations["items"].as_array().unwrap().len(), 1);
+
+    sqlx::query("UPDATE alters SET deleted_at = ? WHERE id = ?")
+        .bind("2000-01-01T00:00:00+00:00")
+        .bind(alter_id)
+        .execute(&pool)
+        .await
+        .expect("age trashed alter");
+    let purged = didhub_backend::trash::purge_expired(&pool, Duration::from_secs(30 * 86400))
+        .await
+        .expect("purge");
+    assert_eq!(purged, 1);
+    let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM alters WHERE id = ?")
+        .bind(alter_id)
+        .fetch_one(&pool)
+        .await
+        .expect("count alters");
+    assert_eq!(remaining, 0);
+    assert_eq!(memberships().await, 0);
+}
+
+#[tokio::test]
+async fn trashed_alters_are_left_out_of_memberships_and_bulk_lookups() {
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let state = support::test_state(&pool, &["admin"], Some(Uuid::new_v4()));
+    // Moss is in an affiliation, a subsystem and a relationship
+    let (alter_id, system_id): (Uuid, Uuid) =
+        sqlx::query_as("SELECT id, user_id FROM alters WHERE name = 'Moss'")
+            .fetch_one(&pool)
+            .await
+            .expect("seeded alter");
+    let affiliation_id: Uuid =
+        sqlx::query_scalar("SELECT affiliation_id FROM affiliation_members WHERE alter_id = ?")
+            .bind(alter_id)
+            .fetch_one(&pool)
+            .await
+            .expect("seeded membership");
+    let subsystem_id: Uuid =
+        sqlx::query_scalar("SELECT subsystem_id FROM subsystem_members WHERE alter_id = ?")
+            .bind(alter_id)
+            .fetch_one(&pool)
+            .await
+            .expect("seeded subsystem membership");
+    let mut conn = pool.acquire().await.expect("acquire");
+    let relationships = didhub_db::custom::relationships::list_for_user(&mut *conn, &system_id)
+        .await
+        .expect("list relationships")
+        .len();
+    drop(conn);
+
+    let path = HashMap::from([("alterId".to_string(), alter_id.to_string())]);
+    let _deleted = alters::delete::delete(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+    )
+    .await
+    .expect("delete alter");
+
+    let members = affiliations::members::list::list(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(HashMap::from([(
+            "affiliationId".to_string(),
+            affiliation_id.to_string(),
+        )])),
+    )
+    .await
+    .expect("list affiliation members")
+    .0;
+    assert!(members
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|m| m["alterId"] != alter_id.to_string()));
+
+    let members = subsystems::members::list::list(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(HashMap::from([(
+            "subsystemId".to_string(),
+            subsystem_id.to_string(),
+        )])),
+    )
+    .await
+    .expect("list subsystem members")
+    .0;
+    assert!(members
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|m| m["alterId"] != alter_id.to_string()));
+
+    alters::subsystem::set(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+        None,
+    )
+    .await
+    .expect_err("trashed alter cannot change subsystem");
+
+    let mut conn = pool.acquire().await.expect("acquire");
+    let remaining = didhub_db::custom::relationships::list_for_user(&mut *conn, &system_id)
+        .await
+        .expect("list relationships");
+    drop(conn);
+    assert_eq!(remaining.len(), relationships - 1);
+    assert!(remaining
+        .iter()
+        .all(|r| r.side_a_alter_id != Some(alter_id) && r.side_b_alter_id != Some(alter_id)));
+
+    // Bulk removal trashes the affiliation, after which bulk lookups skip both
+    let removed = bulk::operation::bulk_operation(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Some(axum::Json(serde_json::json!({
+            "action": "remove",
+            "alters": [alter_id],
+            "affiliations": [affiliation_id],
+        }))),
+    )
+    .await
+    .expect("bulk remove")
+    .0;
+    assert_eq!(removed["deleted"]["alters"], 0);
+    assert_eq!(removed["deleted"]["affiliations"], 1);
+    let trashed: Option<String> =
+        sqlx::query_scalar("SELECT deleted_at FROM affiliations WHERE id = ?")
+            .bind(affiliation_id)
+            .fetch_one(&pool)
+            .await
+            .expect("affiliation is kept in the trash");
+    assert!(trashed.is_some());
+
+    let found = bulk::operation::bulk_operation(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Some(axum::Json(serde_json::json!({
+            "action": "get",
+            "alters": [alter_id],
+            "affiliations": [affiliation_id],
+        }))),
+    )
+    .await
+    .expect("bulk get")
+    .0;
+    assert_eq!(found["alters"], serde_json::json!([]));
+    assert_eq!(found["affiliations"], serde_json::json!([]));
+}
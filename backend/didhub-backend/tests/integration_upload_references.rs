@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use axum::extract::{Extension, Json, Path, Query};
+use base64::Engine as _;
+use didhub_backend::error::ApiError;
+use didhub_backend::handlers::{alters, uploads, users};
+use didhub_backend::upload_references;
+use serde_json::json;
+use uuid::Uuid;
+
+mod support;
+
+async fn references(pool: &didhub_db::DbPool, stored_file_id: Uuid) -> i64 {
+    didhub_db::custom::upload_references::count_for_file(pool, &stored_file_id)
+        .await
+        .expect("count references")
+}
+
+#[tokio::test]
+async fn referenced_files_survive_deletion_and_gc() {
+    let ctx = support::upload_test_context().await;
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let (alter_id, system_id): (Uuid, Uuid) =
+        sqlx::query_as("SELECT id, user_id FROM alters WHERE name = 'Willow'")
+            .fetch_one(&pool)
+            .await
+            .expect("seeded alter");
+    let state = support::test_state(&pool, &["user"], Some(system_id));
+
+    // Setting the avatar stores the file, an upload and a reference
+    let (png_id, _) = support::write_png_file(&ctx.uploads_dir, [10, 20, 30, 255]);
+    let png = std::fs::read(ctx.uploads_dir.join(png_id.to_string())).expect("read png");
+    let content = format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png)
+    );
+    let avatar = users::own_avatar_set::own_avatar_set(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Some(Json(json!({ "filename": "me.png", "content": content }))),
+    )
+    .await
+    .expect("set avatar")
+    .0;
+    let file_id = Uuid::parse_str(avatar["avatar"].as_str().unwrap()).unwrap();
+    assert_eq!(references(&pool, file_id).await, 1);
+
+    // The alter uses the same file
+    let path = HashMap::from([("alterId".to_string(), alter_id.to_string())]);
+    let _updated = alters::update::update(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path.clone()),
+        Some(Json(json!({ "images": [format!("/files/{file_id}")] }))),
+    )
+    .await
+    .expect("set alter images");
+    assert_eq!(references(&pool, file_id).await, 2);
+
+    let upload_id: Uuid = sqlx::query_scalar("SELECT id FROM uploads WHERE stored_file_id = ?")
+        .bind(file_id)
+        .fetch_one(&pool)
+        .await
+        .expect("upload row");
+    let delete_upload = || {
+        uploads::delete::delete(
+            Extension(state.clone()),
+            support::auth_headers(),
+            Path(HashMap::from([(
+                "uploadId".to_string(),
+                upload_id.to_string(),
+            )])),
+            Some(Query(HashMap::new())),
+        )
+    };
+    assert!(matches!(delete_upload().await, Err(ApiError::Conflict(_))));
+
+    let _removed = users::own_avatar_delete::own_avatar_delete(
+        Extension(state.clone()),
+        support::auth_headers(),
+    )
+    .await
+    .expect("remove avatar");
+    assert!(matches!(delete_upload().await, Err(ApiError::Conflict(_))));
+    let _cleared = alters::image::delete::delete(
+        Extension(state.clone()),
+        support::auth_headers(),
+        Path(path),
+    )
+    .await
+    .expect("clear alter images");
+    assert_eq!(references(&pool, file_id).await, 0);
+    let _deleted = delete_upload().await.expect("delete unused upload");
+
+    // Only now does the GC remove the file
+    let file_path = ctx.uploads_dir.join(file_id.to_string());
+    assert!(file_path.exists());
+    let deleted = upload_references::collect_unused(&pool, &ctx.uploads_dir)
+        .await
+        .expect("collect unused files");
+    assert_eq!(deleted, 1);
+    assert!(!file_path.exists());
+    let stored: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM stored_files WHERE id = ?")
+        .bind(file_id)
+        .fetch_one(&pool)
+        .await
+        .expect("count stored files");
+    assert_eq!(stored, 0);
+}
+
+#[tokio::test]
+async fn gc_keeps_files_referenced_before_tracking_after_a_rebuild() {
+    let ctx = support::upload_test_context().await;
+    let pool = support::sqlite_pool().await;
+    didhub_migrations::sqlite_migrator()
+        .run(&pool)
+        .await
+        .expect("run migrations");
+    didhub_db::seed::seed(&pool, didhub_db::seed::Dataset::Demo, "hash")
+        .await
+        .expect("seed demo data");
+    let alter_id: Uuid = sqlx::query_scalar("SELECT id FROM alters WHERE name = 'Willow'")
+        .fetch_one(&pool)
+        .await
+        .expect("seeded alter");
+
+    let mut files = Vec::new();
+    for rgba in [[1, 2, 3, 255], [4, 5, 6, 255]] {
+        let (file_id, _) = support::write_png_file(&ctx.uploads_dir, rgba);
+        sqlx::query(
+            "INSERT INTO stored_files (id, file_hash, mime_type, size, created_at) VALUES (?, ?, 'image/png', 0, ?)",
+        )
+        .bind(file_id)
+        .bind(file_id.to_string())
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&pool)
+        .await
+        .expect("insert stored file");
+        files.push(file_id);
+    }
+    // Written directly, as before references were tracked
+    sqlx::query("UPDATE alters SET images = ? WHERE id = ?")
+        .bind(json!([files[0]]).to_string())
+        .bind(alter_id)
+        .execute(&pool)
+        .await
+        .expect("set alter image");
+
+    upload_references::rebuild(&pool).await.expect("rebuild");
+    let deleted = upload_references::collect_unused(&pool, &ctx.uploads_dir)
+        .await
+        .expect("collect unused files");
+    assert_eq!(deleted, 1);
+    assert!(ctx.uploads_dir.join(files[0].to_string()).exists());
+    assert!(!ctx.uploads_dir.join(files[1].to_string()).exists());
+}
@@ -20,6 +20,7 @@ async fn uploads_crud_sqlite_in_memory() {
     .execute(&pool)
     .await
     .expect("create table");
+    support::create_upload_references_table(&pool).await;
 
     // give the test authenticator a fixed user id so handlers that require auth.user_id succeed
     let test_user_id = uuid::Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
@@ -1,4 +1,6 @@
-use didhub_backend::generated::routes::{create_user, delete_user, get_user_by_id, update_user};
+use didhub_backend::generated::routes::{
+    create_user, delete_user, get_user_by_id, set_user_roles, update_user,
+};
 use didhub_backend::handlers::users::dto::CreateUserDto;
 use std::collections::HashMap;
 
@@ -103,3 +105,145 @@ async fn users_crud_sqlite_in_memory() {
     let del = res.0;
     assert_eq!(del.get("deleted").and_then(|v| v.as_bool()), Some(true));
 }
+
+#[tokio::test]
+async fn public_registration_can_be_disabled() {
+    let pool = support::sqlite_pool().await;
+    let arc_state = support::test_state(&pool, &[], None);
+
+    let mut features = didhub_config::FeaturesConfig::default();
+    features
+        .flags
+        .insert("registration_enabled".to_string(), false);
+    arc_state.set_features(features);
+
+    let dto = CreateUserDto {
+        username: "signup".into(),
+        password_hash: didhub_auth::auth::sha256_hex("longpassword"),
+        display_name: None,
+        about_me: None,
+        roles: None,
+    };
+    let body = serde_json::to_value(&dto).unwrap();
+    let err = create_user(
+        axum::Extension(arc_state),
+        axum::http::HeaderMap::new(),
+        Some(axum::Json(body)),
+    )
+    .await
+    .expect_err("registration should be rejected");
+    assert!(matches!(err, didhub_backend::error::ApiError::Forbidden(_)));
+}
+
+#[tokio::test]
+async fn role_assignment_respects_permission_matrix() {
+    let pool = support::sqlite_pool().await;
+    sqlx::query(
+        r#"CREATE TABLE users (
+            id TEXT PRIMARY KEY,
+            username TEXT NOT NULL,
+            about_me TEXT,
+            password_hash TEXT NOT NULL,
+            avatar TEXT,
+            must_change_password INTEGER NOT NULL,
+            last_login_at TEXT,
+            display_name TEXT,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            roles TEXT NOT NULL,
+            settings TEXT NOT NULL
+        )"#,
+    )
+    .execute(&pool)
+    .await
+    .expect("create table");
+    // Role changes revoke the account's tokens
+    sqlx::query(
+        "CREATE TABLE token_revocation_cutoffs (owner_id BLOB PRIMARY KEY, revoked_before_ms INTEGER NOT NULL)",
+    )
+    .execute(&pool)
+    .await
+    .expect("create revocation table");
+
+    let owner_id = uuid::Uuid::new_v4();
+    let member_id = uuid::Uuid::new_v4();
+    let now = chrono::Utc::now().to_rfc3339();
+    for (id, name, roles) in [
+        (owner_id, "owner", r#"["owner","admin","user"]"#),
+        (member_id, "member", r#"["user"]"#),
+    ] {
+        sqlx::query("INSERT INTO users (id, username, password_hash, must_change_password, created_at, updated_at, roles, settings) VALUES (?, ?, 'x', 0, ?, ?, ?, '{}')")
+            .bind(id)
+            .bind(name)
+            .bind(&now)
+            .bind(&now)
+            .bind(roles)
+            .execute(&pool)
+            .await
+            .expect("insert user");
+    }
+
+    let set_roles = |state, id: uuid::Uuid, roles: serde_json::Value| {
+        let path = HashMap::from([("userId".to_string(), id.to_string())]);
+        set_user_roles(
+            axum::Extension(state),
+            support::auth_headers(),
+            axum::extract::Path(path),
+            Some(axum::Json(serde_json::json!({ "roles": roles }))),
+        )
+    };
+
+    // Admins may hand out moderator, but not admin
+    let admin_state = support::test_state(&pool, &["admin", "user"], Some(uuid::Uuid::new_v4()));
+    let res = set_roles(
+        admin_state.clone(),
+        member_id,
+        serde_json::json!(["moderator", "member"]),
+    )
+    .await
+    .expect("assign moderator");
+    assert_eq!(res.0["roles"], serde_json::json!(["moderator", "user"]));
+    let err = set_roles(admin_state, member_id, serde_json::json!(["admin"]))
+        .await
+        .expect_err("admin cannot grant admin");
+    assert!(matches!(err, didhub_backend::error::ApiError::Forbidden(_)));
+
+    // Moderators cannot assign roles at all
+    let moderator_state = support::test_state(&pool, &["moderator"], Some(member_id));
+    let err = set_roles(moderator_state, member_id, serde_json::json!(["viewer"]))
+        .await
+        .expect_err("moderator cannot assign roles");
+    assert!(matches!(err, didhub_backend::error::ApiError::Forbidden(_)));
+
+    // Owners may grant owner, which always carries admin
+    let owner_state = support::test_state(&pool, &["owner", "admin", "user"], Some(owner_id));
+    let res = set_roles(owner_state.clone(), member_id, serde_json::json!(["owner"]))
+        .await
+        .expect("grant owner");
+    assert_eq!(res.0["roles"], serde_json::json!(["owner", "admin"]));
+
+    let err = set_roles(
+        owner_state.clone(),
+        member_id,
+        serde_json::json!(["wizard"]),
+    )
+    .await
+    .expect_err("unknown role");
+    assert!(matches!(
+        err,
+        didhub_backend::error::ApiError::BadRequest(_)
+    ));
+
+    // With two owners one may step down, but not the last one
+    let res = set_roles(owner_state.clone(), owner_id, serde_json::json!(["user"]))
+        .await
+        .expect("demote first owner");
+    assert_eq!(res.0["roles"], serde_json::json!(["user"]));
+    let err = set_roles(owner_state, member_id, serde_json::json!(["user"]))
+        .await
+        .expect_err("last owner stays");
+    assert!(matches!(
+        err,
+        didhub_backend::error::ApiError::BadRequest(_)
+    ));
+}
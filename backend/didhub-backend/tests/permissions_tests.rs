@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::Extension;
+use axum::http::{HeaderMap, Request, StatusCode};
+use axum::routing::post;
+use axum::Router;
+use didhub_auth::auth::{AuthContext, AuthError, AuthenticatorTrait};
+use didhub_auth::TestAuthenticator;
+use didhub_backend::handlers::auth::utils::authenticate_and_require_approved;
+use didhub_backend::permissions::enforce_read_only_roles;
+use didhub_backend::state::AppState;
+use tower::ServiceExt;
+
+/// Counts how often requests are authenticated.
+struct CountingAuthenticator {
+    inner: TestAuthenticator,
+    calls: Arc<AtomicUsize>,
+}
+
+#[async_trait::async_trait]
+impl AuthenticatorTrait for CountingAuthenticator {
+    async fn authenticate(&self, token: Option<&str>) -> Result<AuthContext, AuthError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        self.inner.authenticate(token).await
+    }
+}
+
+async fn handler(Extension(state): Extension<Arc<AppState>>, headers: HeaderMap) -> StatusCode {
+    match authenticate_and_require_approved(&state, &headers).await {
+        Ok(_) => StatusCode::NO_CONTENT,
+        Err(_) => StatusCode::UNAUTHORIZED,
+    }
+}
+
+#[tokio::test]
+async fn handlers_reuse_the_authentication_of_the_read_only_guard() {
+    let pool = didhub_db::create_pool(&didhub_db::DbConnectionConfig::new("sqlite::memory:"))
+        .await
+        .expect("create pool");
+    let calls = Arc::new(AtomicUsize::new(0));
+    let authenticator = Arc::new(CountingAuthenticator {
+        inner: TestAuthenticator::new_with(vec!["admin".to_string()], None),
+        calls: calls.clone(),
+    }) as Arc<dyn AuthenticatorTrait>;
+    let state = Arc::new(AppState::new(
+        pool,
+        authenticator,
+        didhub_job_queue::JobQueueClient::new(),
+        didhub_updates::UpdateCoordinator::new(),
+        None,
+    ));
+    let app = Router::new()
+        .route("/alters", post(handler))
+        .layer(axum::middleware::from_fn(enforce_read_only_roles))
+        .layer(Extension(state));
+
+    let request = Request::post("/alters")
+        .header("authorization", "Bearer test-token")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
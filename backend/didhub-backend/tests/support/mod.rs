@@ -93,6 +93,49 @@ pub async fn create_stored_files_table(pool: &DbPool) {
     .expect("create stored_files table");
 }
 
+/// The upload reference table for tests that build the uploads schema by hand.
+pub async fn create_upload_references_table(pool: &DbPool) {
+    sqlx::query(
+        r#"CREATE TABLE upload_references (
+            stored_file_id TEXT NOT NULL,
+            owner_type TEXT NOT NULL,
+            owner_id TEXT NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            PRIMARY KEY (stored_file_id, owner_type, owner_id)
+        )"#,
+    )
+    .execute(pool)
+    .await
+    .expect("create upload_references table");
+}
+
+/// Custom field tables for tests that build the alters schema by hand.
+pub async fn create_custom_field_tables(pool: &DbPool) {
+    for sql in [
+        r#"CREATE TABLE custom_field_definitions (
+            id TEXT PRIMARY KEY,
+            user_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            field_type TEXT NOT NULL,
+            sort_order INTEGER NOT NULL DEFAULT 0,
+            visibility TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )"#,
+        r#"CREATE TABLE alter_custom_field_values (
+            alter_id TEXT NOT NULL,
+            field_id TEXT NOT NULL,
+            value TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (alter_id, field_id)
+        )"#,
+    ] {
+        sqlx::query(sql)
+            .execute(pool)
+            .await
+            .expect("create custom field tables");
+    }
+}
+
 pub fn write_png_file(uploads_dir: &std::path::Path, rgba: [u8; 4]) -> (Uuid, String) {
     let file_id = Uuid::new_v4();
     let file_id_s = file_id.to_string();
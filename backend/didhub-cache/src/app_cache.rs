@@ -0,0 +1,659 @@
+//! Typed cache handle.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::backend::CacheBackend;
+use crate::codec::Codec;
+use crate::error::CacheError;
+use crate::invalidation::{Invalidation, InvalidationBus};
+use crate::memory::MemoryCache;
+use crate::policy::NamespacePolicy;
+use crate::single_flight::SingleFlight;
+use crate::stats::{CacheStats, NamespaceStats, Operation};
+use crate::tiered::TieredCache;
+
+/// Typed, namespaced cache shared across the backend.
+///
+/// Cloning is cheap; all clones share the same backend and statistics.
+#[derive(Clone)]
+pub struct AppCache {
+    backend: Arc<dyn CacheBackend>,
+    stats: Arc<CacheStats>,
+    bus: Option<Arc<dyn InvalidationBus>>,
+    inflight: Arc<SingleFlight>,
+    policies: Arc<HashMap<String, NamespacePolicy>>,
+}
+
+/// How [`AppCache::get_or_compute`] stores a value.
+#[derive(Serialize, Deserialize)]
+struct Computed<T> {
+    value: T,
+    /// Unix time in milliseconds after which the next reader recomputes it.
+    #[serde(rename = "refreshAt", default, skip_serializing_if = "Option::is_none")]
+    refresh_at: Option<u64>,
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+impl<T> Computed<T> {
+    fn is_due(&self) -> bool {
+        self.refresh_at.is_some_and(|at| unix_millis() >= at)
+    }
+}
+
+impl std::fmt::Debug for AppCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppCache")
+            .field("backend", &self.backend.name())
+            .field("bus", &self.bus.as_ref().map(|bus| bus.name()))
+            .finish()
+    }
+}
+
+impl Default for AppCache {
+    fn default() -> Self {
+        Self::memory()
+    }
+}
+
+impl AppCache {
+    pub fn new(backend: impl CacheBackend) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            stats: Arc::new(CacheStats::default()),
+            bus: None,
+            inflight: Arc::new(SingleFlight::default()),
+            policies: Arc::default(),
+        }
+    }
+
+    /// Publish [`AppCache::invalidate`] calls on `bus`.
+    pub fn with_bus(mut self, bus: impl InvalidationBus) -> Self {
+        self.bus = Some(Arc::new(bus));
+        self
+    }
+
+    /// Apply a [`NamespacePolicy`] to each of `policies` (namespace, policy). Call
+    /// before storing anything and before [`AppCache::with_local_tier`]: the
+    /// in-process backend is replaced by an empty one with the default capacity.
+    pub fn with_policies(
+        mut self,
+        policies: impl IntoIterator<Item = (impl Into<String>, NamespacePolicy)>,
+    ) -> Self {
+        self.policies = Arc::new(
+            policies
+                .into_iter()
+                .map(|(namespace, policy)| (namespace.into(), policy))
+                .collect(),
+        );
+        if self.backend.name() == "memory" {
+            self.backend = Arc::new(
+                MemoryCache::default().with_namespace_capacities(self.namespace_capacities()),
+            );
+        }
+        self
+    }
+
+    /// Keep up to `capacity` entries of `namespaces` in process for at most `ttl`,
+    /// in front of the shared backend, see [`TieredCache`]. Does nothing for the
+    /// in-process backend or a `capacity` of zero.
+    pub fn with_local_tier(
+        mut self,
+        capacity: u64,
+        ttl: Duration,
+        namespaces: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        if capacity > 0 && self.backend.name() != "memory" {
+            let capacities = self.namespace_capacities();
+            let tiered = TieredCache::over(self.backend, capacity, ttl, namespaces)
+                .with_local_capacities(capacities);
+            self.backend = Arc::new(tiered);
+        }
+        self
+    }
+
+    fn namespace_capacities(&self) -> Vec<(String, u64)> {
+        self.policies
+            .iter()
+            .filter_map(|(namespace, policy)| Some((namespace.clone(), policy.capacity?)))
+            .collect()
+    }
+
+    #[inline]
+    fn codec_for(&self, namespace: &str) -> Codec {
+        self.policies
+            .get(namespace)
+            .map_or(Codec::Json, |policy| policy.codec)
+    }
+
+    /// Lifetime of an entry stored in `namespace` when the caller asks for `ttl`.
+    #[inline]
+    fn ttl_for(&self, namespace: &str, ttl: Option<Duration>) -> Option<Duration> {
+        self.policies
+            .get(namespace)
+            .and_then(|policy| policy.ttl)
+            .or(ttl)
+    }
+
+    /// In-process cache with the default capacity.
+    pub fn memory() -> Self {
+        Self::new(MemoryCache::default())
+    }
+
+    /// Redis (`redis://`, `rediss://`, `redis-sentinel://`, `redis-cluster://`, see
+    /// [`crate::RedisCache::connect`]) or memcached (`memcache://`) cache if `url` is
+    /// given, otherwise an in-process cache.
+    pub async fn from_url(url: Option<&str>) -> Result<Self, CacheError> {
+        match url {
+            None => Ok(Self::memory()),
+            #[cfg(feature = "redis")]
+            Some(url) if crate::redis::is_redis_url(url) => {
+                Ok(Self::new(crate::redis::RedisCache::connect(url).await?))
+            }
+            #[cfg(feature = "memcached")]
+            Some(url) if url.starts_with("memcache://") || url.starts_with("memcached://") => Ok(
+                Self::new(crate::memcached::MemcachedCache::connect(url).await?),
+            ),
+            Some(url) => Err(CacheError::UnsupportedUrl(url.to_string())),
+        }
+    }
+
+    /// Name of the active backend (`memory`, `redis`, ...).
+    #[inline]
+    pub fn backend_name(&self) -> &'static str {
+        self.backend.name()
+    }
+
+    #[inline]
+    fn full_key(namespace: &str, key: &str) -> String {
+        format!("{}:{}", namespace, key)
+    }
+
+    /// Hit, miss and latency statistics per namespace since startup, by namespace name.
+    pub fn stats(&self) -> Vec<NamespaceStats> {
+        self.stats.snapshot()
+    }
+
+    fn record<T>(
+        &self,
+        namespace: &str,
+        started: Instant,
+        result: &Result<T, CacheError>,
+        op: impl FnOnce(&T) -> Operation,
+    ) {
+        let elapsed = started.elapsed();
+        let op = result.as_ref().ok().map(op);
+        if op.is_none() {
+            tracing::debug!(
+                namespace,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "cache operation failed"
+            );
+        }
+        self.stats.record(namespace, op, elapsed);
+    }
+
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        namespace: &str,
+        key: &str,
+    ) -> Result<Option<T>, CacheError> {
+        let started = Instant::now();
+        let result = match self.backend.get(&Self::full_key(namespace, key)).await {
+            Ok(Some(bytes)) => Codec::decode(&bytes).map(Some),
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+        self.record(namespace, started, &result, |v| Operation::Get {
+            hit: v.is_some(),
+        });
+        result
+    }
+
+    /// Store `value`; `ttl` of `None` keeps it until evicted or deleted.
+    pub async fn set<T: Serialize + ?Sized>(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+    ) -> Result<(), CacheError> {
+        let started = Instant::now();
+        let ttl = self.ttl_for(namespace, ttl);
+        let result = match self.codec_for(namespace).encode(value) {
+            Ok(bytes) => {
+                self.backend
+                    .set(&Self::full_key(namespace, key), bytes, ttl)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        self.record(namespace, started, &result, |_| Operation::Set);
+        result
+    }
+
+    /// The value stored under `key`, or the result of `compute`, stored for `ttl`.
+    ///
+    /// Concurrent callers of this process missing the same key wait for the one
+    /// computing it instead of all computing it. With `refresh_after`, the first
+    /// caller reading a value older than that recomputes it while the others keep
+    /// receiving the stored one, which is also served if recomputing fails. Values
+    /// are stored in an envelope, so read them through this method only.
+    /// Cache errors are logged and the value computed.
+    pub async fn get_or_compute<T, E, F, Fut>(
+        &self,
+        namespace: &str,
+        key: &str,
+        ttl: Option<Duration>,
+        refresh_after: Option<Duration>,
+        compute: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let stale = match self.get::<Computed<T>>(namespace, key).await {
+            Ok(Some(computed)) if !computed.is_due() => return Ok(computed.value),
+            Ok(Some(computed)) => Some(computed.value),
+            Ok(None) => None,
+            Err(e) => {
+                tracing::debug!(%e, namespace, "cache read failed; computing value");
+                None
+            }
+        };
+
+        let flight = self.inflight.join(&Self::full_key(namespace, key));
+        if let Some(value) = stale {
+            // Somebody else is already refreshing it
+            let Some(_guard) = flight.try_lock() else {
+                return Ok(value);
+            };
+            return match self
+                .compute_and_store(namespace, key, ttl, refresh_after, compute)
+                .await
+            {
+                Ok(fresh) => Ok(fresh),
+                Err(_) => {
+                    tracing::warn!(
+                        namespace,
+                        "recomputing cached value failed; serving the stored one"
+                    );
+                    Ok(value)
+                }
+            };
+        }
+
+        let _guard = flight.lock().await;
+        // Computed by the caller this one waited for
+        if let Ok(Some(computed)) = self.get::<Computed<T>>(namespace, key).await {
+            return Ok(computed.value);
+        }
+        self.compute_and_store(namespace, key, ttl, refresh_after, compute)
+            .await
+    }
+
+    async fn compute_and_store<T, E, F, Fut>(
+        &self,
+        namespace: &str,
+        key: &str,
+        ttl: Option<Duration>,
+        refresh_after: Option<Duration>,
+        compute: F,
+    ) -> Result<T, E>
+    where
+        T: Serialize,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let value = compute().await?;
+        let computed = Computed {
+            value: &value,
+            refresh_at: refresh_after.map(|after| unix_millis() + after.as_millis() as u64),
+        };
+        if let Err(e) = self.set(namespace, key, &computed, ttl).await {
+            tracing::debug!(%e, namespace, "failed to store computed value");
+        }
+        Ok(value)
+    }
+
+    /// Store `value` like [`AppCache::set`], filed under each of `tags` (such as
+    /// `alters:user:<id>`) so [`AppCache::invalidate_tag`] drops it together with
+    /// every other entry sharing a tag. Tags are not namespaced.
+    pub async fn set_with_tags<T: Serialize + ?Sized>(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: &T,
+        ttl: Option<Duration>,
+        tags: &[&str],
+    ) -> Result<(), CacheError> {
+        let started = Instant::now();
+        let ttl = self.ttl_for(namespace, ttl);
+        let tags: Vec<String> = tags.iter().map(|tag| tag.to_string()).collect();
+        let result = match self.codec_for(namespace).encode(value) {
+            Ok(bytes) => {
+                self.backend
+                    .set_tagged(&Self::full_key(namespace, key), bytes, ttl, &tags)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        self.record(namespace, started, &result, |_| Operation::Set);
+        result
+    }
+
+    /// Remove an entry, returning whether it existed.
+    pub async fn delete(&self, namespace: &str, key: &str) -> Result<bool, CacheError> {
+        let started = Instant::now();
+        let result = self.backend.delete(&Self::full_key(namespace, key)).await;
+        self.record(namespace, started, &result, |_| Operation::Delete);
+        result
+    }
+
+    pub async fn exists(&self, namespace: &str, key: &str) -> Result<bool, CacheError> {
+        let started = Instant::now();
+        let result = self.backend.exists(&Self::full_key(namespace, key)).await;
+        self.record(namespace, started, &result, |_| Operation::Exists);
+        result
+    }
+
+    /// Remove an entry whose source changed, here and, through the bus, on every
+    /// other instance. A failed publish is logged; the other instances then keep
+    /// their copy until it expires.
+    pub async fn invalidate(&self, namespace: &str, key: &str) -> Result<bool, CacheError> {
+        let existed = self.delete(namespace, key).await?;
+        if let Some(bus) = &self.bus {
+            if let Err(e) = bus.publish(&Invalidation::new(namespace, key)).await {
+                tracing::warn!(%e, namespace, bus = bus.name(), "failed to publish cache invalidation");
+            }
+        }
+        Ok(existed)
+    }
+
+    /// Remove every entry filed under `tag` like [`AppCache::invalidate`] does for
+    /// one entry, returning how many existed here.
+    pub async fn invalidate_tag(&self, tag: &str) -> Result<u64, CacheError> {
+        let removed = self.backend.invalidate_tag(tag).await?;
+        if let Some(bus) = &self.bus {
+            if let Err(e) = bus.publish(&Invalidation::tag(tag)).await {
+                tracing::warn!(%e, tag, bus = bus.name(), "failed to publish cache invalidation");
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Remove the entries named by an invalidation received from the bus,
+    /// returning whether any existed.
+    pub async fn apply(&self, invalidation: &Invalidation) -> Result<bool, CacheError> {
+        match invalidation {
+            Invalidation::Key { namespace, key } => {
+                let started = Instant::now();
+                let result = self.backend.evict(&Self::full_key(namespace, key)).await;
+                self.record(namespace, started, &result, |_| Operation::Delete);
+                result
+            }
+            Invalidation::Tag { tag } => Ok(self.backend.evict_tag(tag).await? > 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn values_round_trip_per_namespace() {
+        let cache = AppCache::memory();
+        cache.set("a", "k", &vec![1, 2, 3], None).await.unwrap();
+        cache.set("b", "k", &"other", None).await.unwrap();
+
+        assert_eq!(
+            cache.get::<Vec<u32>>("a", "k").await.unwrap(),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(
+            cache.get::<String>("b", "k").await.unwrap().as_deref(),
+            Some("other")
+        );
+        assert!(cache.delete("a", "k").await.unwrap());
+        assert!(!cache.exists("a", "k").await.unwrap());
+        assert!(cache.exists("b", "k").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn entries_expire_after_ttl() {
+        let cache = AppCache::memory();
+        cache
+            .set("ns", "short", &true, Some(Duration::from_millis(50)))
+            .await
+            .unwrap();
+        cache.set("ns", "long", &true, None).await.unwrap();
+        assert!(cache.exists("ns", "short").await.unwrap());
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert_eq!(cache.get::<bool>("ns", "short").await.unwrap(), None);
+        assert_eq!(cache.get::<bool>("ns", "long").await.unwrap(), Some(true));
+    }
+
+    #[tokio::test]
+    async fn operations_are_counted_per_namespace() {
+        let cache = AppCache::memory();
+        cache.set("tokens", "a", &1, None).await.unwrap();
+        assert_eq!(cache.get::<u32>("tokens", "a").await.unwrap(), Some(1));
+        assert_eq!(cache.get::<u32>("tokens", "b").await.unwrap(), None);
+        assert!(cache.get::<String>("tokens", "a").await.is_err());
+        cache.delete("flows", "a").await.unwrap();
+
+        let stats = cache.stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].namespace, "flows");
+        assert_eq!(stats[0].deletes, 1);
+        let tokens = &stats[1];
+        assert_eq!(
+            (tokens.sets, tokens.hits, tokens.misses, tokens.errors),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(tokens.hit_ratio(), Some(0.5));
+        assert_eq!(tokens.latency_buckets.iter().sum::<u64>(), 4);
+
+        let text = didhub_metrics::render();
+        for operation in ["set", "hit", "miss", "error"] {
+            assert!(text.contains(&format!(
+                "{}_count{{namespace=\"tokens\",operation=\"{operation}\"}} 1\n",
+                didhub_metrics::CACHE_OPERATION_DURATION
+            )));
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingBus(std::sync::Mutex<Vec<Invalidation>>);
+
+    #[async_trait::async_trait]
+    impl InvalidationBus for Arc<RecordingBus> {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn publish(&self, invalidation: &Invalidation) -> Result<(), CacheError> {
+            self.0.lock().unwrap().push(invalidation.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn invalidations_are_published_and_applied() {
+        let bus = Arc::new(RecordingBus::default());
+        let local = AppCache::memory().with_bus(bus.clone());
+        let remote = AppCache::memory();
+        local.set("settings", "theme", &"dark", None).await.unwrap();
+        remote
+            .set("settings", "theme", &"dark", None)
+            .await
+            .unwrap();
+
+        assert!(local.invalidate("settings", "theme").await.unwrap());
+        assert!(!local.exists("settings", "theme").await.unwrap());
+        let published = bus.0.lock().unwrap().clone();
+        assert_eq!(published, vec![Invalidation::new("settings", "theme")]);
+
+        let received = Invalidation::from_payload(&published[0].to_payload()).unwrap();
+        assert!(remote.apply(&received).await.unwrap());
+        assert!(!remote.exists("settings", "theme").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn tagged_entries_are_invalidated_together() {
+        let bus = Arc::new(RecordingBus::default());
+        let local = AppCache::memory().with_bus(bus.clone());
+        let remote = AppCache::memory();
+        for cache in [&local, &remote] {
+            cache
+                .set_with_tags("alters", "list:1", &[1], None, &["alters:user:1"])
+                .await
+                .unwrap();
+            cache
+                .set_with_tags(
+                    "alters",
+                    "search:1",
+                    &[1],
+                    Some(Duration::from_secs(60)),
+                    &["alters:user:1", "search"],
+                )
+                .await
+                .unwrap();
+            cache
+                .set_with_tags("alters", "list:2", &[2], None, &["alters:user:2"])
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(local.invalidate_tag("alters:user:1").await.unwrap(), 2);
+        assert!(!local.exists("alters", "list:1").await.unwrap());
+        assert!(!local.exists("alters", "search:1").await.unwrap());
+        assert!(local.exists("alters", "list:2").await.unwrap());
+        assert_eq!(local.invalidate_tag("search").await.unwrap(), 0);
+
+        let published = bus.0.lock().unwrap().clone();
+        assert_eq!(
+            published,
+            vec![
+                Invalidation::tag("alters:user:1"),
+                Invalidation::tag("search")
+            ]
+        );
+        let received = Invalidation::from_payload(&published[0].to_payload()).unwrap();
+        assert!(remote.apply(&received).await.unwrap());
+        assert!(!remote.exists("alters", "search:1").await.unwrap());
+        assert!(remote.exists("alters", "list:2").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn concurrent_misses_compute_once() {
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let cache = AppCache::memory();
+        let computed = Arc::new(AtomicU32::new(0));
+        let callers = (0..50).map(|_| {
+            let cache = cache.clone();
+            let computed = computed.clone();
+            tokio::spawn(async move {
+                cache
+                    .get_or_compute("settings", "theme", None, None, || async {
+                        computed.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(50)).await;
+                        Ok::<_, CacheError>("dark".to_string())
+                    })
+                    .await
+                    .unwrap()
+            })
+        });
+        for caller in callers.collect::<Vec<_>>() {
+            assert_eq!(caller.await.unwrap(), "dark");
+        }
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn values_past_refresh_after_are_recomputed_or_served_stale() {
+        let cache = AppCache::memory();
+        let load = |value: Result<u32, &'static str>| {
+            cache.get_or_compute(
+                "settings",
+                "limit",
+                None,
+                Some(Duration::from_millis(30)),
+                move || async move { value },
+            )
+        };
+        assert_eq!(load(Ok(1)).await, Ok(1));
+        assert_eq!(load(Ok(2)).await, Ok(1));
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        // A failed refresh keeps the stored value
+        assert_eq!(load(Err("db down")).await, Ok(1));
+        assert_eq!(load(Ok(3)).await, Ok(3));
+        assert_eq!(load(Ok(4)).await, Ok(3));
+
+        assert_eq!(
+            cache
+                .get_or_compute("settings", "other", None, None, || async {
+                    Err::<u32, _>("db down")
+                })
+                .await,
+            Err("db down")
+        );
+    }
+
+    #[tokio::test]
+    async fn namespace_policies_set_lifetime_and_capacity() {
+        let cache = AppCache::memory().with_policies([
+            (
+                "flows",
+                NamespacePolicy {
+                    ttl: Some(Duration::from_millis(50)),
+                    ..NamespacePolicy::default()
+                },
+            ),
+            (
+                "seen",
+                NamespacePolicy {
+                    capacity: Some(1),
+                    ..NamespacePolicy::default()
+                },
+            ),
+        ]);
+        cache
+            .set("flows", "a", &1, Some(Duration::from_secs(60)))
+            .await
+            .unwrap();
+        cache.set("other", "a", &1, None).await.unwrap();
+        for i in 0..20 {
+            cache.set("seen", &i.to_string(), &i, None).await.unwrap();
+        }
+
+        tokio::time::sleep(Duration::from_millis(120)).await;
+        assert!(!cache.exists("flows", "a").await.unwrap());
+        // Evicting the busy namespace leaves the others alone
+        assert!(cache.exists("other", "a").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn unsupported_url_is_rejected() {
+        assert!(matches!(
+            AppCache::from_url(Some("couchbase://localhost")).await,
+            Err(CacheError::UnsupportedUrl(_))
+        ));
+    }
+}
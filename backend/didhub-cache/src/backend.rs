@@ -0,0 +1,57 @@
+//! Storage backend trait.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::error::CacheError;
+
+/// Raw byte storage behind an [`crate::AppCache`].
+///
+/// Keys arrive fully namespaced (`<namespace>:<key>`). Backends must treat an expired
+/// entry exactly like a missing one.
+#[async_trait]
+pub trait CacheBackend: Send + Sync + 'static {
+    /// Short backend name for logs and diagnostics, e.g. `memory` or `redis`.
+    fn name(&self) -> &'static str;
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError>;
+
+    /// Store `value`, replacing any existing entry. `None` means no expiry.
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Option<Duration>)
+        -> Result<(), CacheError>;
+
+    /// Remove an entry, returning whether it existed.
+    async fn delete(&self, key: &str) -> Result<bool, CacheError>;
+
+    /// Store `value` like [`CacheBackend::set`] and file its key under each of
+    /// `tags`. The key stays filed until the tag is invalidated; removing a key
+    /// that already expired is harmless.
+    async fn set_tagged(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        tags: &[String],
+    ) -> Result<(), CacheError>;
+
+    /// Remove every entry filed under `tag` and the tag itself, returning how many
+    /// entries existed.
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64, CacheError>;
+
+    async fn exists(&self, key: &str) -> Result<bool, CacheError> {
+        Ok(self.get(key).await?.is_some())
+    }
+
+    /// Drop this instance's copy of an entry another instance invalidated,
+    /// returning whether it existed. Deletes the entry unless overridden.
+    async fn evict(&self, key: &str) -> Result<bool, CacheError> {
+        self.delete(key).await
+    }
+
+    /// Drop this instance's copies of the entries filed under `tag` like
+    /// [`CacheBackend::evict`].
+    async fn evict_tag(&self, tag: &str) -> Result<u64, CacheError> {
+        self.invalidate_tag(tag).await
+    }
+}
@@ -0,0 +1,91 @@
+//! Encodings of cached values.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::error::CacheError;
+
+/// First byte of MessagePack-encoded values. MessagePack never uses it and no
+/// JSON document starts with it, so entries written under either codec can be
+/// read whichever one a namespace is configured with now.
+#[cfg(feature = "msgpack")]
+const MSGPACK_MARKER: u8 = 0xc1;
+
+/// How values of a namespace are encoded, see [`crate::NamespacePolicy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Codec {
+    /// Readable and understood by every tool; the default.
+    #[default]
+    Json,
+    /// Smaller and cheaper to (de)serialize, for large values such as entity
+    /// lists (feature `msgpack`).
+    #[cfg(feature = "msgpack")]
+    MessagePack,
+}
+
+impl Codec {
+    /// The codec named `json` or `msgpack`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(Self::Json),
+            #[cfg(feature = "msgpack")]
+            "msgpack" => Some(Self::MessagePack),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn encode<T: Serialize + ?Sized>(self, value: &T) -> Result<Vec<u8>, CacheError> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(value)?),
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => {
+                let mut bytes = vec![MSGPACK_MARKER];
+                rmp_serde::encode::write_named(&mut bytes, value)
+                    .map_err(|e| CacheError::Codec(e.to_string()))?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    /// Decode `bytes` written by any codec.
+    pub(crate) fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, CacheError> {
+        match bytes {
+            #[cfg(feature = "msgpack")]
+            [MSGPACK_MARKER, rest @ ..] => {
+                rmp_serde::from_slice(rest).map_err(|e| CacheError::Codec(e.to_string()))
+            }
+            _ => Ok(serde_json::from_slice(bytes)?),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "msgpack"))]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        id: u32,
+        #[serde(skip_serializing_if = "Option::is_none", default)]
+        note: Option<String>,
+        tags: Vec<String>,
+    }
+
+    #[test]
+    fn values_decode_whichever_codec_wrote_them() {
+        let row = Row {
+            id: 7,
+            note: None,
+            tags: vec!["a".into(), "b".into()],
+        };
+        let json = Codec::Json.encode(&row).unwrap();
+        let msgpack = Codec::MessagePack.encode(&row).unwrap();
+        assert!(msgpack.len() < json.len());
+        assert_eq!(Codec::decode::<Row>(&json).unwrap(), row);
+        assert_eq!(Codec::decode::<Row>(&msgpack).unwrap(), row);
+        assert_eq!(Codec::from_name("msgpack"), Some(Codec::MessagePack));
+        assert_eq!(Codec::from_name("bincode"), None);
+    }
+}
@@ -0,0 +1,19 @@
+//! Error types for the cache.
+
+use thiserror::Error;
+
+/// Errors that may occur while reading from or writing to a cache backend.
+#[derive(Debug, Error)]
+pub enum CacheError {
+    #[error("cache backend error: {0}")]
+    Backend(String),
+
+    #[error("failed to (de)serialize cached value: {0}")]
+    Serialization(#[from] serde_json::Error),
+
+    #[error("failed to (de)serialize cached value: {0}")]
+    Codec(String),
+
+    #[error("unsupported cache url: {0}")]
+    UnsupportedUrl(String),
+}
@@ -0,0 +1,52 @@
+//! Cache invalidation shared between instances.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::error::CacheError;
+
+/// Channel invalidations are published on, as a Postgres `NOTIFY` channel or a
+/// Redis pub/sub channel (with the `didhub:` key prefix).
+pub const INVALIDATION_CHANNEL: &str = "didhub_cache_invalidation";
+
+/// Entries another instance changed, so every instance should drop its copy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Invalidation {
+    /// A single entry.
+    Key { namespace: String, key: String },
+    /// Every entry filed under a tag, see [`crate::AppCache::set_with_tags`].
+    Tag { tag: String },
+}
+
+impl Invalidation {
+    pub fn new(namespace: impl Into<String>, key: impl Into<String>) -> Self {
+        Self::Key {
+            namespace: namespace.into(),
+            key: key.into(),
+        }
+    }
+
+    pub fn tag(tag: impl Into<String>) -> Self {
+        Self::Tag { tag: tag.into() }
+    }
+
+    /// JSON form sent over the channel.
+    pub fn to_payload(&self) -> String {
+        serde_json::to_string(self).expect("invalidations serialize")
+    }
+
+    pub fn from_payload(payload: &str) -> Result<Self, CacheError> {
+        Ok(serde_json::from_str(payload)?)
+    }
+}
+
+/// Delivers invalidations to the other instances, which hand them to
+/// [`crate::AppCache::apply`].
+#[async_trait]
+pub trait InvalidationBus: Send + Sync + 'static {
+    /// Short bus name for logs, e.g. `postgres` or `redis`.
+    fn name(&self) -> &'static str;
+
+    async fn publish(&self, invalidation: &Invalidation) -> Result<(), CacheError>;
+}
@@ -0,0 +1,77 @@
+//! Namespaced key/value cache for the DIDHub backend.
+//!
+//! Values are serialized as JSON (or, per namespace, MessagePack) and stored under `<namespace>:<key>` with an optional
+//! time-to-live. A single process can use the in-memory backend; deployments running
+//! several instances should point `redis_url` at a shared Redis (or, with a
+//! `memcache://` URL, a memcached) so that cached state
+//! (revoked tokens, flow state, settings) is visible to every instance. When an entry's
+//! source changes, [`AppCache::invalidate`] also tells the other instances to drop it
+//! through an [`InvalidationBus`] (Postgres `NOTIFY` or Redis pub/sub). Entries
+//! stored with [`AppCache::set_with_tags`] can be dropped as a group, such as every
+//! listing of one system's alters, with [`AppCache::invalidate_tag`]. Hot entries
+//! loaded through [`AppCache::get_or_compute`] are computed by one caller at a time,
+//! optionally refreshed ahead of their expiry. With [`AppCache::with_local_tier`],
+//! hot namespaces such as settings are also kept in process in front of Redis.
+//! [`AppCache::with_policies`] sets the lifetime and in-process capacity of
+//! individual namespaces.
+//!
+//! # Architecture
+//!
+//! - [`AppCache`] - Typed, cheaply clonable handle used by the rest of the backend
+//! - [`CacheBackend`] - Raw byte storage implemented by each backend
+//! - [`MemoryCache`] - Bounded in-process backend
+//! - [`RedisCache`] - Redis backend for a server, sentinel group or cluster (feature `redis`)
+//! - [`MemcachedCache`] - Memcached backend (feature `memcached`)
+//! - [`TieredCache`] - In-process copy of some namespaces in front of a shared backend
+//! - [`NamespacePolicy`] - Lifetime, in-process capacity and [`Codec`] of one namespace
+//! - [`InvalidationBus`] - Tells other instances to drop entries, see [`AppCache::invalidate`]
+//! - [`NamespaceStats`] - Hit, miss and latency counters per namespace, from [`AppCache::stats`]
+//!   and as the `didhub_cache_operation_duration_seconds` Prometheus histogram
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use didhub_cache::AppCache;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), didhub_cache::CacheError> {
+//!     let cache = AppCache::from_url(None).await?;
+//!     cache
+//!         .set("settings", "theme", &"dark", Some(Duration::from_secs(60)))
+//!         .await?;
+//!     let theme: Option<String> = cache.get("settings", "theme").await?;
+//!     assert_eq!(theme.as_deref(), Some("dark"));
+//!     Ok(())
+//! }
+//! ```
+
+mod app_cache;
+mod backend;
+mod codec;
+mod error;
+mod invalidation;
+#[cfg(feature = "memcached")]
+mod memcached;
+mod memory;
+mod policy;
+#[cfg(feature = "redis")]
+mod redis;
+mod single_flight;
+mod stats;
+mod tiered;
+
+pub use app_cache::AppCache;
+pub use backend::CacheBackend;
+pub use codec::Codec;
+pub use error::CacheError;
+pub use invalidation::{Invalidation, InvalidationBus, INVALIDATION_CHANNEL};
+#[cfg(feature = "memcached")]
+pub use memcached::MemcachedCache;
+pub use memory::MemoryCache;
+pub use policy::NamespacePolicy;
+#[cfg(feature = "redis")]
+pub use redis::{is_redis_url, RedisCache, RedisInvalidationBus};
+pub use stats::{NamespaceStats, LATENCY_BUCKETS_MICROS};
+pub use tiered::TieredCache;
@@ -0,0 +1,476 @@
+//! Memcached cache backend, speaking the memcached text protocol.
+
+use std::fmt::Write as _;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufStream};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+
+use crate::backend::CacheBackend;
+use crate::error::CacheError;
+
+/// Prefix applied to every key so DIDHub can share a memcached with other apps.
+const KEY_PREFIX: &str = "didhub:";
+
+/// Longest key memcached accepts, in bytes.
+const MAX_KEY_LEN: usize = 250;
+
+/// Memcached reads relative expiry times longer than this as Unix timestamps.
+const MAX_RELATIVE_EXPIRY: u64 = 30 * 24 * 60 * 60;
+
+const DEFAULT_PORT: u16 = 11211;
+
+/// Idle connections kept for reuse.
+const MAX_IDLE: usize = 8;
+
+type Connection = BufStream<TcpStream>;
+
+impl From<std::io::Error> for CacheError {
+    fn from(e: std::io::Error) -> Self {
+        CacheError::Backend(e.to_string())
+    }
+}
+
+/// Cache stored in memcached, shared by every instance pointing at the same server.
+///
+/// Connections are opened on demand and kept for reuse; one that fails is dropped.
+pub struct MemcachedCache {
+    addr: String,
+    idle: Mutex<Vec<Connection>>,
+}
+
+impl std::fmt::Debug for MemcachedCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemcachedCache")
+            .field("addr", &self.addr)
+            .finish_non_exhaustive()
+    }
+}
+
+/// `host:port` from a `memcache://` or `memcached://` URL.
+fn server_addr(url: &str) -> Result<String, CacheError> {
+    let host = url
+        .strip_prefix("memcache://")
+        .or_else(|| url.strip_prefix("memcached://"))
+        .ok_or_else(|| CacheError::UnsupportedUrl(url.to_string()))?
+        .trim_end_matches('/');
+    if host.is_empty() {
+        return Err(CacheError::UnsupportedUrl(url.to_string()));
+    }
+    let has_port = match host.rfind(']') {
+        Some(end) => host[end..].contains(':'),
+        None => host.contains(':'),
+    };
+    Ok(if has_port {
+        host.to_string()
+    } else {
+        format!("{host}:{DEFAULT_PORT}")
+    })
+}
+
+/// The memcached key for `key`. Spaces, control characters, non-ASCII and `%`
+/// are percent-encoded since memcached keys cannot hold them.
+fn encode_key(key: &str) -> Result<String, CacheError> {
+    let mut out = String::from(KEY_PREFIX);
+    for byte in key.bytes() {
+        if byte.is_ascii_graphic() && byte != b'%' {
+            out.push(byte as char);
+        } else {
+            let _ = write!(out, "%{byte:02X}");
+        }
+    }
+    if out.len() > MAX_KEY_LEN {
+        return Err(CacheError::Backend(format!(
+            "cache key longer than {MAX_KEY_LEN} bytes"
+        )));
+    }
+    Ok(out)
+}
+
+/// Expiry field of a `set`: 0 for none, whole seconds (at least one) otherwise,
+/// as a Unix timestamp past 30 days.
+fn expiry(ttl: Option<Duration>) -> u64 {
+    let Some(ttl) = ttl else {
+        return 0;
+    };
+    let secs = (ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0)).max(1);
+    if secs <= MAX_RELATIVE_EXPIRY {
+        return secs;
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    now + secs
+}
+
+fn unexpected(reply: &str) -> CacheError {
+    CacheError::Backend(format!("unexpected memcached reply: {reply}"))
+}
+
+async fn read_line(conn: &mut Connection) -> Result<String, CacheError> {
+    let mut line = String::new();
+    if conn.read_line(&mut line).await? == 0 {
+        return Err(CacheError::Backend(
+            "memcached closed the connection".to_string(),
+        ));
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+async fn send(conn: &mut Connection, command: &[u8]) -> Result<(), CacheError> {
+    conn.write_all(command).await?;
+    conn.flush().await?;
+    Ok(())
+}
+
+async fn get_on(conn: &mut Connection, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+    send(conn, format!("get {key}\r\n").as_bytes()).await?;
+    let header = read_line(conn).await?;
+    if header == "END" {
+        return Ok(None);
+    }
+    // VALUE <key> <flags> <bytes>
+    let len = match header.split(' ').collect::<Vec<_>>().as_slice() {
+        ["VALUE", _, _, len] => len.parse::<usize>().map_err(|_| unexpected(&header))?,
+        _ => return Err(unexpected(&header)),
+    };
+    let mut data = vec![0; len + 2];
+    conn.read_exact(&mut data).await?;
+    data.truncate(len);
+    let end = read_line(conn).await?;
+    if end != "END" {
+        return Err(unexpected(&end));
+    }
+    Ok(Some(data))
+}
+
+/// Run a storage command (`set`, `add`, `append`), returning whether the value
+/// was stored.
+async fn store_on(
+    conn: &mut Connection,
+    verb: &str,
+    key: &str,
+    value: &[u8],
+    exptime: u64,
+) -> Result<bool, CacheError> {
+    let mut command = format!("{verb} {key} 0 {exptime} {}\r\n", value.len()).into_bytes();
+    command.extend_from_slice(value);
+    command.extend_from_slice(b"\r\n");
+    send(conn, &command).await?;
+    match read_line(conn).await?.as_str() {
+        "STORED" => Ok(true),
+        "NOT_STORED" => Ok(false),
+        reply => Err(unexpected(reply)),
+    }
+}
+
+async fn set_on(
+    conn: &mut Connection,
+    key: &str,
+    value: &[u8],
+    exptime: u64,
+) -> Result<(), CacheError> {
+    match store_on(conn, "set", key, value, exptime).await? {
+        true => Ok(()),
+        false => Err(unexpected("NOT_STORED")),
+    }
+}
+
+/// Add `key` to the space-separated key list of `tag_key` unless it is listed.
+/// `append` only works on an existing item and `add` only creates one, so
+/// together they never lose a concurrent writer's key.
+async fn file_on(conn: &mut Connection, tag_key: &str, key: &str) -> Result<(), CacheError> {
+    if let Some(list) = get_on(conn, tag_key).await? {
+        if list
+            .split(|b| *b == b' ')
+            .any(|listed| listed == key.as_bytes())
+        {
+            return Ok(());
+        }
+    }
+    let entry = format!(" {key}");
+    loop {
+        if store_on(conn, "append", tag_key, entry.as_bytes(), 0).await?
+            || store_on(conn, "add", tag_key, entry.as_bytes(), 0).await?
+        {
+            return Ok(());
+        }
+    }
+}
+
+async fn delete_on(conn: &mut Connection, key: &str) -> Result<bool, CacheError> {
+    send(conn, format!("delete {key}\r\n").as_bytes()).await?;
+    match read_line(conn).await?.as_str() {
+        "DELETED" => Ok(true),
+        "NOT_FOUND" => Ok(false),
+        reply => Err(unexpected(reply)),
+    }
+}
+
+impl MemcachedCache {
+    /// Connect to `url` (`memcache://host[:port]`, port 11211 by default).
+    pub async fn connect(url: &str) -> Result<Self, CacheError> {
+        let cache = Self {
+            addr: server_addr(url)?,
+            idle: Mutex::new(Vec::new()),
+        };
+        // Fail at startup rather than on the first request
+        let conn = cache.checkout().await?;
+        cache.checkin(conn).await;
+        Ok(cache)
+    }
+
+    async fn checkout(&self) -> Result<Connection, CacheError> {
+        if let Some(conn) = self.idle.lock().await.pop() {
+            return Ok(conn);
+        }
+        let stream = TcpStream::connect(&self.addr).await?;
+        stream.set_nodelay(true)?;
+        Ok(BufStream::new(stream))
+    }
+
+    async fn checkin(&self, conn: Connection) {
+        let mut idle = self.idle.lock().await;
+        if idle.len() < MAX_IDLE {
+            idle.push(conn);
+        }
+    }
+}
+
+/// Key of the item listing the keys filed under `tag`.
+#[inline]
+fn tag_key(tag: &str) -> Result<String, CacheError> {
+    encode_key(&format!("tag:{tag}"))
+}
+
+#[async_trait]
+impl CacheBackend for MemcachedCache {
+    fn name(&self) -> &'static str {
+        "memcached"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        let key = encode_key(key)?;
+        let mut conn = self.checkout().await?;
+        let result = get_on(&mut conn, &key).await;
+        if result.is_ok() {
+            self.checkin(conn).await;
+        }
+        result
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    ) -> Result<(), CacheError> {
+        let key = encode_key(key)?;
+        let mut conn = self.checkout().await?;
+        let result = set_on(&mut conn, &key, &value, expiry(ttl)).await;
+        if result.is_ok() {
+            self.checkin(conn).await;
+        }
+        result
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, CacheError> {
+        let key = encode_key(key)?;
+        let mut conn = self.checkout().await?;
+        let result = delete_on(&mut conn, &key).await;
+        if result.is_ok() {
+            self.checkin(conn).await;
+        }
+        result
+    }
+
+    /// Tag lists have no expiry; memcached evicts them like any other item.
+    async fn set_tagged(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        tags: &[String],
+    ) -> Result<(), CacheError> {
+        let key = encode_key(key)?;
+        let tag_keys = tags
+            .iter()
+            .map(|tag| tag_key(tag))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut conn = self.checkout().await?;
+        let result = async {
+            set_on(&mut conn, &key, &value, expiry(ttl)).await?;
+            for tag_key in &tag_keys {
+                file_on(&mut conn, tag_key, &key).await?;
+            }
+            Ok(())
+        }
+        .await;
+        if result.is_ok() {
+            self.checkin(conn).await;
+        }
+        result
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64, CacheError> {
+        let tag_key = tag_key(tag)?;
+        let mut conn = self.checkout().await?;
+        let result = async {
+            let Some(list) = get_on(&mut conn, &tag_key).await? else {
+                return Ok(0);
+            };
+            delete_on(&mut conn, &tag_key).await?;
+            let list = String::from_utf8_lossy(&list).into_owned();
+            let mut removed = 0;
+            for key in list.split_whitespace() {
+                if delete_on(&mut conn, key).await? {
+                    removed += 1;
+                }
+            }
+            Ok(removed)
+        }
+        .await;
+        if result.is_ok() {
+            self.checkin(conn).await;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use tokio::io::BufReader;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// A memcached answering `get`, `set` and `delete` from a map, ignoring expiry.
+    async fn fake_server() -> (String, Arc<Mutex<HashMap<String, Vec<u8>>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let store = Arc::new(Mutex::new(HashMap::<String, Vec<u8>>::new()));
+        let shared = store.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let store = shared.clone();
+                tokio::spawn(async move {
+                    let mut conn = BufReader::new(stream);
+                    let mut line = String::new();
+                    while conn.read_line(&mut line).await.unwrap_or(0) > 0 {
+                        let parts: Vec<String> =
+                            line.split_whitespace().map(str::to_string).collect();
+                        line.clear();
+                        let reply = match parts.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+                            ["get", key] => match store.lock().await.get(key) {
+                                Some(data) => {
+                                    let mut out =
+                                        format!("VALUE {key} 0 {}\r\n", data.len()).into_bytes();
+                                    out.extend_from_slice(data);
+                                    out.extend_from_slice(b"\r\nEND\r\n");
+                                    out
+                                }
+                                None => b"END\r\n".to_vec(),
+                            },
+                            [verb @ ("set" | "add" | "append"), key, _, _, len] => {
+                                let mut data = vec![0; len.parse::<usize>().unwrap() + 2];
+                                conn.read_exact(&mut data).await.unwrap();
+                                data.truncate(data.len() - 2);
+                                let mut store = store.lock().await;
+                                match (verb, store.get_mut(key)) {
+                                    ("add", Some(_)) | ("append", None) => {
+                                        b"NOT_STORED\r\n".to_vec()
+                                    }
+                                    ("append", Some(existing)) => {
+                                        existing.extend_from_slice(&data);
+                                        b"STORED\r\n".to_vec()
+                                    }
+                                    _ => {
+                                        store.insert(key.to_string(), data);
+                                        b"STORED\r\n".to_vec()
+                                    }
+                                }
+                            }
+                            ["delete", key] => match store.lock().await.remove(key) {
+                                Some(_) => b"DELETED\r\n".to_vec(),
+                                None => b"NOT_FOUND\r\n".to_vec(),
+                            },
+                            _ => b"ERROR\r\n".to_vec(),
+                        };
+                        conn.get_mut().write_all(&reply).await.unwrap();
+                    }
+                });
+            }
+        });
+        (addr, store)
+    }
+
+    #[tokio::test]
+    async fn values_round_trip_through_memcached() {
+        let (addr, store) = fake_server().await;
+        let cache = MemcachedCache::connect(&format!("memcache://{addr}"))
+            .await
+            .unwrap();
+
+        cache
+            .set("flows:a b", b"line\r\nbreak".to_vec(), None)
+            .await
+            .unwrap();
+        assert!(store.lock().await.contains_key("didhub:flows:a%20b"));
+        assert_eq!(
+            cache.get("flows:a b").await.unwrap().as_deref(),
+            Some(&b"line\r\nbreak"[..])
+        );
+        assert_eq!(cache.get("flows:other").await.unwrap(), None);
+        assert!(cache.delete("flows:a b").await.unwrap());
+        assert!(!cache.exists("flows:a b").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn tagged_entries_are_listed_once_and_invalidated_together() {
+        let (addr, store) = fake_server().await;
+        let cache = MemcachedCache::connect(&format!("memcache://{addr}"))
+            .await
+            .unwrap();
+        let tags = ["alters:user:1".to_string()];
+        for key in ["alters:a", "alters:b", "alters:a"] {
+            cache
+                .set_tagged(key, b"[]".to_vec(), None, &tags)
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            store.lock().await["didhub:tag:alters:user:1"],
+            b" didhub:alters:a didhub:alters:b"
+        );
+
+        assert_eq!(cache.invalidate_tag("alters:user:1").await.unwrap(), 2);
+        assert!(store.lock().await.is_empty());
+        assert_eq!(cache.invalidate_tag("alters:user:1").await.unwrap(), 0);
+    }
+
+    #[test]
+    fn urls_expiry_and_keys_follow_memcached_rules() {
+        assert_eq!(server_addr("memcache://cache").unwrap(), "cache:11211");
+        assert_eq!(
+            server_addr("memcached://cache:1234/").unwrap(),
+            "cache:1234"
+        );
+        assert_eq!(server_addr("memcache://[::1]").unwrap(), "[::1]:11211");
+        assert!(server_addr("redis://cache").is_err());
+
+        assert_eq!(expiry(None), 0);
+        assert_eq!(expiry(Some(Duration::from_millis(10))), 1);
+        assert_eq!(expiry(Some(Duration::from_millis(1500))), 2);
+        assert!(expiry(Some(Duration::from_secs(MAX_RELATIVE_EXPIRY + 1))) > 1_000_000_000);
+
+        assert_eq!(encode_key("ns:100%").unwrap(), "didhub:ns:100%25");
+        assert!(encode_key(&"k".repeat(MAX_KEY_LEN)).is_err());
+    }
+}
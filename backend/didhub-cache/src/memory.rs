@@ -0,0 +1,254 @@
+//! In-process cache backend.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use moka::future::Cache;
+use moka::Expiry;
+
+use crate::backend::CacheBackend;
+use crate::error::CacheError;
+
+/// Default maximum number of entries held by [`MemoryCache::default`].
+pub(crate) const DEFAULT_CAPACITY: u64 = 10_000;
+
+#[derive(Clone)]
+struct Entry {
+    data: Arc<[u8]>,
+    ttl: Option<Duration>,
+}
+
+/// Expires each entry after its own TTL, restarting the clock when it is overwritten.
+struct PerEntryTtl;
+
+impl Expiry<String, Entry> for PerEntryTtl {
+    fn expire_after_create(&self, _key: &String, value: &Entry, _now: Instant) -> Option<Duration> {
+        value.ttl
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        value: &Entry,
+        _now: Instant,
+        _current: Option<Duration>,
+    ) -> Option<Duration> {
+        value.ttl
+    }
+}
+
+/// Keys filed under each tag. Keys evicted from the cache stay filed until the
+/// index grows past twice the cache capacity, when they are pruned.
+#[derive(Default)]
+struct TagIndex {
+    tags: HashMap<String, HashSet<String>>,
+    filed: usize,
+    prune_at: usize,
+}
+
+fn bounded(capacity: u64) -> Cache<String, Entry> {
+    Cache::builder()
+        .max_capacity(capacity)
+        .expire_after(PerEntryTtl)
+        .build()
+}
+
+/// Bounded in-process cache. Entries are evicted once `capacity` is reached;
+/// namespaces given their own capacity are bounded separately.
+///
+/// Contents are lost on restart and are not shared between instances.
+#[derive(Clone)]
+pub struct MemoryCache {
+    inner: Cache<String, Entry>,
+    namespaces: Arc<HashMap<String, Cache<String, Entry>>>,
+    tags: Arc<Mutex<TagIndex>>,
+}
+
+impl std::fmt::Debug for MemoryCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let entries = self.inner.entry_count()
+            + self
+                .namespaces
+                .values()
+                .map(Cache::entry_count)
+                .sum::<u64>();
+        f.debug_struct("MemoryCache")
+            .field("entries", &entries)
+            .finish()
+    }
+}
+
+impl MemoryCache {
+    pub fn new(capacity: u64) -> Self {
+        Self {
+            inner: bounded(capacity),
+            namespaces: Arc::default(),
+            tags: Arc::new(Mutex::new(TagIndex {
+                prune_at: prune_threshold(capacity),
+                ..TagIndex::default()
+            })),
+        }
+    }
+
+    /// Bound each of `capacities` (namespace, entries) separately from the rest,
+    /// so a busy namespace cannot evict the entries of another. Call before
+    /// storing anything; the cache is emptied.
+    pub fn with_namespace_capacities(
+        self,
+        capacities: impl IntoIterator<Item = (impl Into<String>, u64)>,
+    ) -> Self {
+        let base = self
+            .inner
+            .policy()
+            .max_capacity()
+            .unwrap_or(DEFAULT_CAPACITY);
+        let mut total = base;
+        let namespaces = capacities
+            .into_iter()
+            .map(|(namespace, capacity)| {
+                total = total.saturating_add(capacity);
+                (namespace.into(), bounded(capacity))
+            })
+            .collect();
+        Self {
+            inner: bounded(base),
+            namespaces: Arc::new(namespaces),
+            tags: Arc::new(Mutex::new(TagIndex {
+                prune_at: prune_threshold(total),
+                ..TagIndex::default()
+            })),
+        }
+    }
+
+    /// The cache holding the fully namespaced `key`.
+    fn shard(&self, key: &str) -> &Cache<String, Entry> {
+        key.split_once(':')
+            .and_then(|(namespace, _)| self.namespaces.get(namespace))
+            .unwrap_or(&self.inner)
+    }
+
+    fn file_under_tags(&self, key: &str, tags: &[String]) {
+        let mut index = self.tags.lock().expect("tag index poisoned");
+        for tag in tags {
+            if index
+                .tags
+                .entry(tag.clone())
+                .or_default()
+                .insert(key.to_owned())
+            {
+                index.filed += 1;
+            }
+        }
+        if index.filed > index.prune_at {
+            index.tags.retain(|_, keys| {
+                keys.retain(|key| self.shard(key).contains_key(key));
+                !keys.is_empty()
+            });
+            index.filed = index.tags.values().map(HashSet::len).sum();
+            // Entries carrying many tags can keep the index above the threshold
+            index.prune_at = index.prune_at.max(index.filed.saturating_mul(2));
+        }
+    }
+}
+
+fn prune_threshold(capacity: u64) -> usize {
+    usize::try_from(capacity)
+        .unwrap_or(usize::MAX)
+        .saturating_mul(2)
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+#[async_trait]
+impl CacheBackend for MemoryCache {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        Ok(self.shard(key).get(key).await.map(|e| e.data.to_vec()))
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    ) -> Result<(), CacheError> {
+        self.shard(key)
+            .insert(
+                key.to_owned(),
+                Entry {
+                    data: value.into(),
+                    ttl,
+                },
+            )
+            .await;
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, CacheError> {
+        Ok(self.shard(key).remove(key).await.is_some())
+    }
+
+    async fn set_tagged(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        tags: &[String],
+    ) -> Result<(), CacheError> {
+        self.set(key, value, ttl).await?;
+        self.file_under_tags(key, tags);
+        Ok(())
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64, CacheError> {
+        let keys = {
+            let mut index = self.tags.lock().expect("tag index poisoned");
+            let keys = index.tags.remove(tag).unwrap_or_default();
+            index.filed -= keys.len();
+            keys
+        };
+        let mut removed = 0;
+        for key in keys {
+            if self.shard(&key).remove(&key).await.is_some() {
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, CacheError> {
+        Ok(self.shard(key).contains_key(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tag_index_prunes_evicted_keys() {
+        let cache = MemoryCache::new(4);
+        let tags = ["t".to_string()];
+        for i in 0..100 {
+            cache
+                .set_tagged(&format!("ns:{i}"), vec![], None, &tags)
+                .await
+                .unwrap();
+            cache.inner.run_pending_tasks().await;
+        }
+        assert!(cache.tags.lock().unwrap().filed <= 8);
+        assert!(cache.invalidate_tag("t").await.unwrap() <= 4);
+        cache.inner.run_pending_tasks().await;
+        assert_eq!(cache.inner.entry_count(), 0);
+        assert_eq!(cache.tags.lock().unwrap().filed, 0);
+    }
+}
@@ -0,0 +1,18 @@
+//! Per-namespace cache policies.
+
+use std::time::Duration;
+
+use crate::codec::Codec;
+
+/// How the entries of one namespace are kept, see [`crate::AppCache::with_policies`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NamespacePolicy {
+    /// Lifetime of every entry stored in the namespace, replacing the one the
+    /// caller asks for.
+    pub ttl: Option<Duration>,
+    /// Entries of the namespace held in process, bounded separately from the
+    /// other namespaces. Only applies to in-process storage.
+    pub capacity: Option<u64>,
+    /// Encoding of the values stored in the namespace.
+    pub codec: Codec,
+}
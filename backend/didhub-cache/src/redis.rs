@@ -0,0 +1,522 @@
+//! Redis cache backend.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use redis::aio::{ConnectionLike, ConnectionManager, MultiplexedConnection};
+use redis::cluster::ClusterClient;
+use redis::cluster_async::ClusterConnection;
+use redis::sentinel::{Sentinel, SentinelNodeConnectionInfo};
+use redis::{AsyncCommands, Cmd, IntoConnectionInfo, Pipeline, RedisError, RedisFuture, Value};
+
+use crate::app_cache::AppCache;
+use crate::backend::CacheBackend;
+use crate::error::CacheError;
+use crate::invalidation::{Invalidation, InvalidationBus, INVALIDATION_CHANNEL};
+
+/// Prefix applied to every key so DIDHub can share a Redis database with other apps.
+const KEY_PREFIX: &str = "didhub:";
+
+impl From<redis::RedisError> for CacheError {
+    fn from(e: redis::RedisError) -> Self {
+        CacheError::Backend(e.to_string())
+    }
+}
+
+/// URL schemes of the Redis deployments [`RedisCache`] connects to.
+const SCHEMES: [&str; 4] = [
+    "redis://",
+    "rediss://",
+    "redis-sentinel://",
+    "redis-cluster://",
+];
+
+/// Whether `url` names a Redis deployment, see [`RedisCache::connect`].
+pub fn is_redis_url(url: &str) -> bool {
+    SCHEMES.iter().any(|scheme| url.starts_with(scheme))
+}
+
+/// Credentials, hosts and path of a URL listing several hosts, such as
+/// `redis-cluster://:pw@a:6379,b:6379`.
+struct HostList<'a> {
+    /// `user:password@`, or empty.
+    userinfo: &'a str,
+    hosts: Vec<&'a str>,
+    /// What follows the hosts, without the leading `/`.
+    path: &'a str,
+}
+
+impl<'a> HostList<'a> {
+    fn parse(url: &'a str, scheme: &str) -> Result<Self, CacheError> {
+        let invalid = || CacheError::UnsupportedUrl(url.to_string());
+        let rest = url.strip_prefix(scheme).ok_or_else(invalid)?;
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        let (userinfo, hosts) = match authority.rfind('@') {
+            Some(at) => authority.split_at(at + 1),
+            None => ("", authority),
+        };
+        let hosts: Vec<&str> = hosts.split(',').filter(|h| !h.is_empty()).collect();
+        if hosts.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            userinfo,
+            hosts,
+            path,
+        })
+    }
+
+    /// `redis://` URL of each host, carrying the credentials and `db`.
+    fn node_urls(&self, db: &str) -> Vec<String> {
+        self.hosts
+            .iter()
+            .map(|host| format!("redis://{}{}/{}", self.userinfo, host, db))
+            .collect()
+    }
+}
+
+/// The master elected by a group of sentinels. The connection to it is dropped
+/// when it fails or turns read-only, so the next command asks the sentinels again.
+struct SentinelMaster {
+    sentinel: tokio::sync::Mutex<Sentinel>,
+    service: String,
+    node: SentinelNodeConnectionInfo,
+    current: Mutex<Option<MultiplexedConnection>>,
+}
+
+impl SentinelMaster {
+    /// `redis-sentinel://[user:password@]host:port,.../<service>[/<db>]`. The
+    /// credentials and database apply to the master, not to the sentinels.
+    fn open(url: &str) -> Result<Self, CacheError> {
+        let list = HostList::parse(url, "redis-sentinel://")?;
+        let (service, db) = list.path.split_once('/').unwrap_or((list.path, "0"));
+        if service.is_empty() {
+            return Err(CacheError::UnsupportedUrl(url.to_string()));
+        }
+        let sentinels: Vec<String> = list
+            .hosts
+            .iter()
+            .map(|host| format!("redis://{}", host))
+            .collect();
+        let master = format!("redis://{}localhost/{}", list.userinfo, db).into_connection_info()?;
+        Ok(Self {
+            sentinel: tokio::sync::Mutex::new(Sentinel::build(sentinels)?),
+            service: service.to_string(),
+            node: SentinelNodeConnectionInfo {
+                tls_mode: None,
+                redis_connection_info: Some(master.redis),
+            },
+            current: Mutex::new(None),
+        })
+    }
+
+    async fn client(&self) -> Result<redis::Client, CacheError> {
+        let mut sentinel = self.sentinel.lock().await;
+        Ok(sentinel
+            .async_master_for(&self.service, Some(&self.node))
+            .await?)
+    }
+
+    async fn connection(&self) -> Result<MultiplexedConnection, CacheError> {
+        if let Some(conn) = self
+            .current
+            .lock()
+            .expect("sentinel master poisoned")
+            .clone()
+        {
+            return Ok(conn);
+        }
+        let conn = self
+            .client()
+            .await?
+            .get_multiplexed_async_connection()
+            .await?;
+        *self.current.lock().expect("sentinel master poisoned") = Some(conn.clone());
+        Ok(conn)
+    }
+
+    fn check<T>(&self, result: &Result<T, RedisError>) {
+        if let Err(e) = result {
+            if e.is_io_error()
+                || e.is_connection_dropped()
+                || e.is_unrecoverable_error()
+                || e.kind() == redis::ErrorKind::ReadOnly
+            {
+                tracing::warn!(%e, service = %self.service, "lost the redis master; asking the sentinels again");
+                self.current
+                    .lock()
+                    .expect("sentinel master poisoned")
+                    .take();
+            }
+        }
+    }
+}
+
+/// The Redis deployment named by a URL.
+#[derive(Clone)]
+enum Target {
+    /// A single server, through a connection manager that reconnects by itself.
+    Node(redis::Client, Box<ConnectionManager>),
+    /// A cluster, whose connection follows slot moves and failovers by itself.
+    /// The client is the first node's, for pub/sub.
+    Cluster(redis::Client, ClusterConnection),
+    Sentinel(Arc<SentinelMaster>),
+}
+
+impl Target {
+    async fn connect(url: &str) -> Result<Self, CacheError> {
+        if url.starts_with("redis-sentinel://") {
+            let master = SentinelMaster::open(url)?;
+            master.connection().await?;
+            return Ok(Self::Sentinel(Arc::new(master)));
+        }
+        if url.starts_with("redis-cluster://") {
+            let list = HostList::parse(url, "redis-cluster://")?;
+            if !list.path.is_empty() {
+                // Clusters only have database 0
+                return Err(CacheError::UnsupportedUrl(url.to_string()));
+            }
+            let nodes = list.node_urls("");
+            let first = redis::Client::open(nodes[0].as_str())?;
+            let conn = ClusterClient::new(nodes)?.get_async_connection().await?;
+            return Ok(Self::Cluster(first, conn));
+        }
+        let client = redis::Client::open(url)?;
+        let conn = ConnectionManager::new(client.clone()).await?;
+        Ok(Self::Node(client, Box::new(conn)))
+    }
+
+    async fn conn(&self) -> Result<Conn, CacheError> {
+        Ok(match self {
+            Self::Node(_, conn) => Conn::Node(conn.as_ref().clone()),
+            Self::Cluster(_, conn) => Conn::Cluster(conn.clone()),
+            Self::Sentinel(master) => Conn::Sentinel(master.connection().await?, master.clone()),
+        })
+    }
+
+    /// Client to subscribe with; published messages reach every node of a cluster.
+    async fn pubsub_client(&self) -> Result<redis::Client, CacheError> {
+        match self {
+            Self::Node(client, _) | Self::Cluster(client, _) => Ok(client.clone()),
+            Self::Sentinel(master) => master.client().await,
+        }
+    }
+}
+
+/// Connection of a [`Target`] for one operation.
+#[derive(Clone)]
+enum Conn {
+    Node(ConnectionManager),
+    Cluster(ClusterConnection),
+    Sentinel(MultiplexedConnection, Arc<SentinelMaster>),
+}
+
+impl ConnectionLike for Conn {
+    fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+        match self {
+            Self::Node(conn) => conn.req_packed_command(cmd),
+            Self::Cluster(conn) => conn.req_packed_command(cmd),
+            Self::Sentinel(conn, master) => Box::pin(async move {
+                let result = conn.req_packed_command(cmd).await;
+                master.check(&result);
+                result
+            }),
+        }
+    }
+
+    fn req_packed_commands<'a>(
+        &'a mut self,
+        cmd: &'a Pipeline,
+        offset: usize,
+        count: usize,
+    ) -> RedisFuture<'a, Vec<Value>> {
+        match self {
+            Self::Node(conn) => conn.req_packed_commands(cmd, offset, count),
+            Self::Cluster(conn) => conn.req_packed_commands(cmd, offset, count),
+            Self::Sentinel(conn, master) => Box::pin(async move {
+                let result = conn.req_packed_commands(cmd, offset, count).await;
+                master.check(&result);
+                result
+            }),
+        }
+    }
+
+    fn get_db(&self) -> i64 {
+        match self {
+            Self::Node(conn) => conn.get_db(),
+            Self::Cluster(conn) => conn.get_db(),
+            Self::Sentinel(conn, _) => conn.get_db(),
+        }
+    }
+}
+
+/// Cache stored in Redis, shared by every instance pointing at the same server,
+/// sentinel group or cluster.
+///
+/// Connections recover by themselves, following a sentinel or cluster failover;
+/// cloning is cheap.
+#[derive(Clone)]
+pub struct RedisCache {
+    target: Target,
+}
+
+impl std::fmt::Debug for RedisCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisCache").finish_non_exhaustive()
+    }
+}
+
+impl RedisCache {
+    /// Connect to `url`: a single server (`redis://`, `rediss://`), a sentinel
+    /// group (`redis-sentinel://[user:password@]host:port,.../<master>[/<db>]`)
+    /// or a cluster (`redis-cluster://[user:password@]host:port,...`).
+    pub async fn connect(url: &str) -> Result<Self, CacheError> {
+        Ok(Self {
+            target: Target::connect(url).await?,
+        })
+    }
+
+    /// Run each command of `pipe` on its own when the keys may live on different
+    /// cluster nodes, which a pipeline cannot span.
+    async fn run(&self, conn: &mut Conn, pipe: redis::Pipeline) -> Result<(), CacheError> {
+        if matches!(self.target, Target::Cluster(..)) {
+            for cmd in pipe.cmd_iter() {
+                cmd.query_async::<Value>(conn).await?;
+            }
+        } else {
+            pipe.query_async::<()>(conn).await?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn key(key: &str) -> String {
+        format!("{}{}", KEY_PREFIX, key)
+    }
+
+    /// Set of the keys filed under `tag`.
+    #[inline]
+    fn tag_key(tag: &str) -> String {
+        format!("{}tag:{}", KEY_PREFIX, tag)
+    }
+}
+
+/// `ttl` in milliseconds; PX needs at least one.
+#[inline]
+fn ttl_millis(ttl: Duration) -> u64 {
+    ttl.as_millis().clamp(1, u64::MAX as u128) as u64
+}
+
+#[async_trait]
+impl CacheBackend for RedisCache {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        let mut conn = self.target.conn().await?;
+        Ok(conn.get(Self::key(key)).await?)
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    ) -> Result<(), CacheError> {
+        let mut conn = self.target.conn().await?;
+        match ttl {
+            Some(ttl) => {
+                conn.pset_ex::<_, _, ()>(Self::key(key), value, ttl_millis(ttl))
+                    .await?
+            }
+            None => conn.set::<_, _, ()>(Self::key(key), value).await?,
+        }
+        Ok(())
+    }
+
+    /// Tag sets live as long as their longest-lived entry: a set without expiry
+    /// keeps none, a new set takes the entry's TTL and a shorter one is extended.
+    async fn set_tagged(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        tags: &[String],
+    ) -> Result<(), CacheError> {
+        let mut conn = self.target.conn().await?;
+        let full_key = Self::key(key);
+        let remaining: Vec<i64> = if matches!(self.target, Target::Cluster(..)) {
+            let mut remaining = Vec::with_capacity(tags.len());
+            for tag in tags {
+                remaining.push(conn.pttl(Self::tag_key(tag)).await?);
+            }
+            let mut writes = redis::pipe();
+            match ttl {
+                Some(ttl) => writes.pset_ex(&full_key, value, ttl_millis(ttl)),
+                None => writes.set(&full_key, value),
+            };
+            for tag in tags {
+                writes.sadd(Self::tag_key(tag), &full_key);
+            }
+            self.run(&mut conn, writes).await?;
+            remaining
+        } else {
+            let mut pipe = redis::pipe();
+            pipe.atomic();
+            for tag in tags {
+                pipe.pttl(Self::tag_key(tag));
+            }
+            match ttl {
+                Some(ttl) => pipe.pset_ex(&full_key, value, ttl_millis(ttl)).ignore(),
+                None => pipe.set(&full_key, value).ignore(),
+            };
+            for tag in tags {
+                pipe.sadd(Self::tag_key(tag), &full_key).ignore();
+            }
+            pipe.query_async(&mut conn).await?
+        };
+
+        let mut expiry = redis::pipe();
+        for (tag, remaining) in tags.iter().zip(remaining) {
+            match ttl {
+                // -1: the set has no expiry
+                None if remaining != -1 => {
+                    expiry.persist(Self::tag_key(tag)).ignore();
+                }
+                // -2: the set did not exist
+                Some(ttl)
+                    if remaining == -2 || (0..ttl_millis(ttl) as i64).contains(&remaining) =>
+                {
+                    expiry
+                        .pexpire(Self::tag_key(tag), ttl_millis(ttl) as i64)
+                        .ignore();
+                }
+                _ => {}
+            }
+        }
+        self.run(&mut conn, expiry).await
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64, CacheError> {
+        let mut conn = self.target.conn().await?;
+        let (keys,): (Vec<String>,) = redis::pipe()
+            .atomic()
+            .smembers(Self::tag_key(tag))
+            .del(Self::tag_key(tag))
+            .ignore()
+            .query_async(&mut conn)
+            .await?;
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        Ok(conn.del(keys).await?)
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, CacheError> {
+        let mut conn = self.target.conn().await?;
+        let removed: u64 = conn.del(Self::key(key)).await?;
+        Ok(removed > 0)
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, CacheError> {
+        let mut conn = self.target.conn().await?;
+        Ok(conn.exists(Self::key(key)).await?)
+    }
+}
+
+/// Invalidations over Redis pub/sub, for deployments without Postgres `NOTIFY`.
+#[derive(Clone)]
+pub struct RedisInvalidationBus {
+    target: Target,
+}
+
+impl std::fmt::Debug for RedisInvalidationBus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisInvalidationBus")
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedisInvalidationBus {
+    /// Connect to `url`, any URL [`RedisCache::connect`] accepts.
+    pub async fn connect(url: &str) -> Result<Self, CacheError> {
+        Ok(Self {
+            target: Target::connect(url).await?,
+        })
+    }
+
+    #[inline]
+    fn channel() -> String {
+        format!("{}{}", KEY_PREFIX, INVALIDATION_CHANNEL)
+    }
+
+    /// Apply the invalidations published by every instance to `cache` until the
+    /// subscription drops; the caller decides when to subscribe again.
+    pub async fn listen(&self, cache: &AppCache) -> Result<(), CacheError> {
+        let mut pubsub = self
+            .target
+            .pubsub_client()
+            .await?
+            .get_async_pubsub()
+            .await?;
+        pubsub.subscribe(Self::channel()).await?;
+        let mut messages = pubsub.on_message();
+        while let Some(message) = messages.next().await {
+            let payload: String = message.get_payload()?;
+            match Invalidation::from_payload(&payload) {
+                Ok(invalidation) => {
+                    cache.apply(&invalidation).await?;
+                }
+                Err(e) => tracing::warn!(%e, "ignoring malformed cache invalidation"),
+            }
+        }
+        Err(CacheError::Backend(
+            "invalidation subscription closed".to_string(),
+        ))
+    }
+}
+
+#[async_trait]
+impl InvalidationBus for RedisInvalidationBus {
+    fn name(&self) -> &'static str {
+        "redis"
+    }
+
+    async fn publish(&self, invalidation: &Invalidation) -> Result<(), CacheError> {
+        let mut conn = self.target.conn().await?;
+        conn.publish::<_, _, ()>(Self::channel(), invalidation.to_payload())
+            .await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_host_urls_are_split_into_nodes() {
+        let list =
+            HostList::parse("redis-cluster://:pw@a:7000,b:7001", "redis-cluster://").unwrap();
+        assert_eq!(
+            list.node_urls(""),
+            ["redis://:pw@a:7000/", "redis://:pw@b:7001/"]
+        );
+        assert!(HostList::parse("redis-cluster://:pw@/", "redis-cluster://").is_err());
+
+        let master =
+            SentinelMaster::open("redis-sentinel://u:pw@s1:26379,s2:26379/main/2").unwrap();
+        assert_eq!(master.service, "main");
+        let node = master.node.redis_connection_info.unwrap();
+        assert_eq!(
+            (node.db, node.username.as_deref(), node.password.as_deref()),
+            (2, Some("u"), Some("pw"))
+        );
+        assert!(SentinelMaster::open("redis-sentinel://s1:26379").is_err());
+        assert!(is_redis_url("redis-sentinel://s1:26379/main"));
+        assert!(!is_redis_url("memcache://m:11211"));
+    }
+}
@@ -0,0 +1,81 @@
+//! Per-key locks behind [`crate::AppCache::get_or_compute`].
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::MutexGuard;
+
+type KeyLock = Arc<tokio::sync::Mutex<()>>;
+
+/// Locks of the keys being computed, so one caller computes a missing value
+/// while the others wait for it. Entries are dropped with their last [`Flight`].
+#[derive(Default)]
+pub(crate) struct SingleFlight {
+    keys: Mutex<HashMap<String, KeyLock>>,
+}
+
+impl SingleFlight {
+    pub(crate) fn join(&self, key: &str) -> Flight<'_> {
+        let lock = self
+            .keys
+            .lock()
+            .expect("single-flight map poisoned")
+            .entry(key.to_owned())
+            .or_default()
+            .clone();
+        Flight {
+            owner: self,
+            key: key.to_owned(),
+            lock,
+        }
+    }
+}
+
+/// A caller's interest in computing one key.
+pub(crate) struct Flight<'a> {
+    owner: &'a SingleFlight,
+    key: String,
+    lock: KeyLock,
+}
+
+impl Flight<'_> {
+    pub(crate) async fn lock(&self) -> MutexGuard<'_, ()> {
+        self.lock.lock().await
+    }
+
+    /// The lock if nobody is computing the key.
+    pub(crate) fn try_lock(&self) -> Option<MutexGuard<'_, ()>> {
+        self.lock.try_lock().ok()
+    }
+}
+
+impl Drop for Flight<'_> {
+    fn drop(&mut self) {
+        let mut keys = self.owner.keys.lock().expect("single-flight map poisoned");
+        // Only the map and this flight still hold the lock
+        if Arc::strong_count(&self.lock) == 2 {
+            keys.remove(&self.key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn keys_are_dropped_with_their_last_flight() {
+        let flights = SingleFlight::default();
+        let first = flights.join("ns:a");
+        let guard = first.lock().await;
+        let second = flights.join("ns:a");
+        assert!(second.try_lock().is_none());
+
+        drop(guard);
+        drop(first);
+        assert!(second.try_lock().is_some());
+        assert_eq!(flights.keys.lock().unwrap().len(), 1);
+        drop(second);
+        assert!(flights.keys.lock().unwrap().is_empty());
+    }
+}
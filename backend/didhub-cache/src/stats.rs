@@ -0,0 +1,105 @@
+//! Per-namespace operation counters and latency histograms.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+
+/// Upper bounds of the latency buckets in microseconds. The last bucket is unbounded,
+/// covering anything from sub-millisecond memory lookups to a struggling Redis.
+pub const LATENCY_BUCKETS_MICROS: [u64; 6] = [100, 500, 1_000, 5_000, 25_000, 100_000];
+
+/// Kind of cache operation being recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Get { hit: bool },
+    Set,
+    Delete,
+    Exists,
+}
+
+impl Operation {
+    /// `operation` label of the Prometheus histogram.
+    fn label(self) -> &'static str {
+        match self {
+            Operation::Get { hit: true } => "hit",
+            Operation::Get { hit: false } => "miss",
+            Operation::Set => "set",
+            Operation::Delete => "delete",
+            Operation::Exists => "exists",
+        }
+    }
+}
+
+/// Snapshot of one namespace's counters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NamespaceStats {
+    pub namespace: String,
+    pub hits: u64,
+    pub misses: u64,
+    pub sets: u64,
+    pub deletes: u64,
+    pub exists: u64,
+    pub errors: u64,
+    /// Operations per latency bucket, aligned with [`LATENCY_BUCKETS_MICROS`] plus a
+    /// final overflow bucket.
+    pub latency_buckets: Vec<u64>,
+    pub total_latency_micros: u64,
+    pub max_latency_micros: u64,
+}
+
+impl NamespaceStats {
+    /// Share of reads that found a value, or `None` before the first read.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let reads = self.hits + self.misses;
+        (reads > 0).then(|| self.hits as f64 / reads as f64)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CacheStats {
+    namespaces: Mutex<BTreeMap<String, NamespaceStats>>,
+}
+
+impl CacheStats {
+    /// Record an operation on `namespace` that took `elapsed`; `None` for failures.
+    /// It also goes to the `didhub_cache_operation_duration_seconds` histogram.
+    pub(crate) fn record(&self, namespace: &str, op: Option<Operation>, elapsed: Duration) {
+        didhub_metrics::record_cache_operation(
+            namespace,
+            op.map_or("error", Operation::label),
+            elapsed,
+        );
+        let mut namespaces = self.namespaces.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = namespaces
+            .entry(namespace.to_string())
+            .or_insert_with(|| NamespaceStats {
+                namespace: namespace.to_string(),
+                latency_buckets: vec![0; LATENCY_BUCKETS_MICROS.len() + 1],
+                ..Default::default()
+            });
+        match op {
+            Some(Operation::Get { hit: true }) => stats.hits += 1,
+            Some(Operation::Get { hit: false }) => stats.misses += 1,
+            Some(Operation::Set) => stats.sets += 1,
+            Some(Operation::Delete) => stats.deletes += 1,
+            Some(Operation::Exists) => stats.exists += 1,
+            None => stats.errors += 1,
+        }
+        let micros = elapsed.as_micros().min(u64::MAX as u128) as u64;
+        let bucket = LATENCY_BUCKETS_MICROS
+            .iter()
+            .position(|bound| micros <= *bound)
+            .unwrap_or(LATENCY_BUCKETS_MICROS.len());
+        stats.latency_buckets[bucket] += 1;
+        stats.total_latency_micros = stats.total_latency_micros.saturating_add(micros);
+        stats.max_latency_micros = stats.max_latency_micros.max(micros);
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<NamespaceStats> {
+        let namespaces = self.namespaces.lock().unwrap_or_else(|e| e.into_inner());
+        namespaces.values().cloned().collect()
+    }
+}
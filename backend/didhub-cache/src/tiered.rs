@@ -0,0 +1,196 @@
+//! In-process cache in front of a shared backend.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::backend::CacheBackend;
+use crate::error::CacheError;
+use crate::memory::MemoryCache;
+
+/// Shared backend (Redis, memcached) with a small in-process copy of the entries of
+/// some namespaces, so reading them does not cost a round trip.
+///
+/// Writes go to the shared backend and then to the local copy. Entries another
+/// instance changes through [`crate::AppCache::invalidate`] are dropped here when
+/// the invalidation arrives; local copies of entries overwritten without an
+/// invalidation are served for at most `local_ttl`. Misses are not kept locally.
+pub struct TieredCache {
+    local: MemoryCache,
+    local_ttl: Duration,
+    namespaces: Vec<String>,
+    shared: Arc<dyn CacheBackend>,
+}
+
+impl std::fmt::Debug for TieredCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TieredCache")
+            .field("shared", &self.shared.name())
+            .field("local", &self.local)
+            .field("local_ttl", &self.local_ttl)
+            .field("namespaces", &self.namespaces)
+            .finish()
+    }
+}
+
+impl TieredCache {
+    /// Keep up to `local_capacity` entries of `namespaces` in process for at most
+    /// `local_ttl` each, in front of `shared`.
+    pub fn new(
+        shared: impl CacheBackend,
+        local_capacity: u64,
+        local_ttl: Duration,
+        namespaces: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self::over(Arc::new(shared), local_capacity, local_ttl, namespaces)
+    }
+
+    pub(crate) fn over(
+        shared: Arc<dyn CacheBackend>,
+        local_capacity: u64,
+        local_ttl: Duration,
+        namespaces: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            local: MemoryCache::new(local_capacity),
+            local_ttl,
+            namespaces: namespaces.into_iter().map(Into::into).collect(),
+            shared,
+        }
+    }
+
+    /// Bound the local copies of each of `capacities` (namespace, entries)
+    /// separately, see [`MemoryCache::with_namespace_capacities`].
+    pub fn with_local_capacities(
+        mut self,
+        capacities: impl IntoIterator<Item = (impl Into<String>, u64)>,
+    ) -> Self {
+        self.local = self.local.with_namespace_capacities(capacities);
+        self
+    }
+
+    /// Whether entries under the fully namespaced `key` are kept locally.
+    fn is_local(&self, key: &str) -> bool {
+        key.split_once(':')
+            .is_some_and(|(namespace, _)| self.namespaces.iter().any(|n| n == namespace))
+    }
+
+    fn local_ttl(&self, ttl: Option<Duration>) -> Option<Duration> {
+        Some(ttl.map_or(self.local_ttl, |ttl| ttl.min(self.local_ttl)))
+    }
+}
+
+#[async_trait]
+impl CacheBackend for TieredCache {
+    fn name(&self) -> &'static str {
+        "tiered"
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        if !self.is_local(key) {
+            return self.shared.get(key).await;
+        }
+        if let Some(value) = self.local.get(key).await? {
+            return Ok(Some(value));
+        }
+        let value = self.shared.get(key).await?;
+        if let Some(value) = &value {
+            self.local
+                .set(key, value.clone(), Some(self.local_ttl))
+                .await?;
+        }
+        Ok(value)
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+    ) -> Result<(), CacheError> {
+        if !self.is_local(key) {
+            return self.shared.set(key, value, ttl).await;
+        }
+        self.shared.set(key, value.clone(), ttl).await?;
+        self.local.set(key, value, self.local_ttl(ttl)).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool, CacheError> {
+        self.local.delete(key).await?;
+        self.shared.delete(key).await
+    }
+
+    async fn set_tagged(
+        &self,
+        key: &str,
+        value: Vec<u8>,
+        ttl: Option<Duration>,
+        tags: &[String],
+    ) -> Result<(), CacheError> {
+        if !self.is_local(key) {
+            return self.shared.set_tagged(key, value, ttl, tags).await;
+        }
+        self.shared
+            .set_tagged(key, value.clone(), ttl, tags)
+            .await?;
+        self.local
+            .set_tagged(key, value, self.local_ttl(ttl), tags)
+            .await
+    }
+
+    async fn invalidate_tag(&self, tag: &str) -> Result<u64, CacheError> {
+        self.local.invalidate_tag(tag).await?;
+        self.shared.invalidate_tag(tag).await
+    }
+
+    async fn exists(&self, key: &str) -> Result<bool, CacheError> {
+        if self.is_local(key) && self.local.exists(key).await? {
+            return Ok(true);
+        }
+        self.shared.exists(key).await
+    }
+
+    async fn evict(&self, key: &str) -> Result<bool, CacheError> {
+        self.local.delete(key).await
+    }
+
+    async fn evict_tag(&self, tag: &str) -> Result<u64, CacheError> {
+        self.local.invalidate_tag(tag).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn listed_namespaces_are_served_locally_until_evicted() {
+        let shared = MemoryCache::default();
+        let cache = TieredCache::new(shared.clone(), 100, Duration::from_secs(60), ["settings"]);
+        cache
+            .set("settings:theme", b"dark".to_vec(), None)
+            .await
+            .unwrap();
+        cache.set("flows:a", b"state".to_vec(), None).await.unwrap();
+        assert!(cache.local.exists("settings:theme").await.unwrap());
+        assert!(!cache.local.exists("flows:a").await.unwrap());
+
+        // Another instance overwrote it without an invalidation
+        shared
+            .set("settings:theme", b"light".to_vec(), None)
+            .await
+            .unwrap();
+        assert_eq!(cache.get("settings:theme").await.unwrap().unwrap(), b"dark");
+
+        assert!(cache.evict("settings:theme").await.unwrap());
+        assert_eq!(
+            cache.get("settings:theme").await.unwrap().unwrap(),
+            b"light"
+        );
+        assert!(shared.exists("settings:theme").await.unwrap());
+
+        assert!(cache.delete("settings:theme").await.unwrap());
+        assert_eq!(cache.get("settings:theme").await.unwrap(), None);
+    }
+}
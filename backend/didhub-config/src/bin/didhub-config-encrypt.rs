@@ -0,0 +1,40 @@
+//! Encrypt a secret for use as an `enc:` value in a DIDHub config file.
+//!
+//! Reads the plaintext from stdin and the key from `DIDHUB_CONFIG_KEY`.
+//! Run with `--generate-key` to print a new random key instead.
+
+use std::io::Read;
+use std::process::ExitCode;
+
+use didhub_config::{ConfigKey, CONFIG_KEY_ENV};
+
+fn main() -> ExitCode {
+    if std::env::args().nth(1).as_deref() == Some("--generate-key") {
+        println!("{}", ConfigKey::generate().to_base64());
+        return ExitCode::SUCCESS;
+    }
+
+    let key = match ConfigKey::from_env() {
+        Ok(Some(key)) => key,
+        Ok(None) => {
+            eprintln!(
+                "{} is not set (use --generate-key to create one)",
+                CONFIG_KEY_ENV
+            );
+            return ExitCode::FAILURE;
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut plaintext = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut plaintext) {
+        eprintln!("failed to read stdin: {}", e);
+        return ExitCode::FAILURE;
+    }
+    let plaintext = plaintext.strip_suffix('\n').unwrap_or(&plaintext);
+    println!("{}", key.encrypt(plaintext));
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,9 @@
+//! Print the JSON Schema for DIDHub configuration files to stdout.
+
+fn main() {
+    let schema = didhub_config::config_json_schema();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema).expect("schema serializes")
+    );
+}
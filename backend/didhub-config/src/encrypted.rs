@@ -0,0 +1,177 @@
+//! Encrypted secret values (`enc:<base64>`) in config files.
+//!
+//! Secret fields may hold `enc:` followed by the base64 encoding of a 12-byte nonce
+//! and the AES-256-GCM ciphertext. They are decrypted at load time with the key from
+//! `DIDHUB_CONFIG_KEY` (or `DIDHUB_CONFIG_KEY_FILE`), itself a base64-encoded 32-byte key.
+//! Use the `didhub-config-encrypt` binary to produce values.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, Nonce, OsRng};
+use aes_gcm::{Aes256Gcm, Key};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use crate::{env_secret, Config, ConfigError};
+
+/// Prefix marking an encrypted config value.
+pub const ENCRYPTED_PREFIX: &str = "enc:";
+
+/// Environment variable holding the base64-encoded decryption key.
+pub const CONFIG_KEY_ENV: &str = "DIDHUB_CONFIG_KEY";
+
+const NONCE_LEN: usize = 12;
+
+/// A 256-bit key used to encrypt and decrypt config values.
+#[derive(Clone)]
+pub struct ConfigKey(Key<Aes256Gcm>);
+
+impl std::fmt::Debug for ConfigKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ConfigKey(..)")
+    }
+}
+
+impl ConfigKey {
+    /// Generate a new random key.
+    pub fn generate() -> Self {
+        Self(Aes256Gcm::generate_key(OsRng))
+    }
+
+    /// Parse a base64-encoded 32-byte key.
+    pub fn from_base64(s: &str) -> Result<Self, ConfigError> {
+        let bytes = STANDARD
+            .decode(s.trim())
+            .map_err(|e| ConfigError::Parse(format!("invalid {}: {}", CONFIG_KEY_ENV, e)))?;
+        let bytes: [u8; 32] = bytes.as_slice().try_into().map_err(|_| {
+            ConfigError::Parse(format!(
+                "invalid {}: expected 32 bytes, got {}",
+                CONFIG_KEY_ENV,
+                bytes.len()
+            ))
+        })?;
+        Ok(Self(Key::<Aes256Gcm>::from(bytes)))
+    }
+
+    /// Read the key from `DIDHUB_CONFIG_KEY` / `DIDHUB_CONFIG_KEY_FILE`, if set.
+    pub fn from_env() -> Result<Option<Self>, ConfigError> {
+        env_secret(CONFIG_KEY_ENV)?
+            .map(|s| Self::from_base64(&s))
+            .transpose()
+    }
+
+    /// Base64 encoding of the key, suitable for `DIDHUB_CONFIG_KEY`.
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.0)
+    }
+
+    /// Encrypt `plaintext` into an `enc:` config value.
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        let nonce = Aes256Gcm::generate_nonce(OsRng);
+        let ciphertext = Aes256Gcm::new(&self.0)
+            .encrypt(&nonce, plaintext.as_bytes())
+            .expect("AES-GCM encryption of an in-memory buffer cannot fail");
+        let mut blob = nonce.to_vec();
+        blob.extend_from_slice(&ciphertext);
+        format!("{}{}", ENCRYPTED_PREFIX, STANDARD.encode(blob))
+    }
+
+    /// Decrypt an `enc:` config value. Values without the prefix are returned unchanged.
+    pub fn decrypt(&self, value: &str) -> Result<String, ConfigError> {
+        let Some(encoded) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let blob = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| ConfigError::Parse(format!("invalid encrypted value: {}", e)))?;
+        if blob.len() <= NONCE_LEN {
+            return Err(ConfigError::Parse("encrypted value is too short".into()));
+        }
+        let (nonce, ciphertext) = blob.split_at(NONCE_LEN);
+        let nonce: [u8; NONCE_LEN] = nonce.try_into().expect("split at nonce length");
+        let plaintext = Aes256Gcm::new(&self.0)
+            .decrypt(&Nonce::<Aes256Gcm>::from(nonce), ciphertext)
+            .map_err(|_| {
+                ConfigError::Parse("failed to decrypt value; wrong key or corrupted data".into())
+            })?;
+        String::from_utf8(plaintext)
+            .map_err(|_| ConfigError::Parse("decrypted value is not valid UTF-8".into()))
+    }
+}
+
+/// Decrypt every `enc:` secret field of `cfg` in place.
+///
+/// The key is only required when at least one field is encrypted.
+pub(crate) fn decrypt_secrets(cfg: &mut Config) -> Result<(), ConfigError> {
+    let mut key: Option<ConfigKey> = None;
+    let fields: [(&str, &mut Option<String>); 7] = [
+        ("database.path", &mut cfg.database.path),
+        ("database.username", &mut cfg.database.username),
+        ("database.password", &mut cfg.database.password),
+        ("redis_url", &mut cfg.redis_url),
+        ("auth.jwt_secret", &mut cfg.auth.jwt_secret),
+        ("auth.jwt_pem", &mut cfg.auth.jwt_pem),
+        ("auth.password_pepper", &mut cfg.auth.password_pepper),
+    ];
+    for (path, field) in fields {
+        let Some(value) = field.as_deref() else {
+            continue;
+        };
+        if !value.starts_with(ENCRYPTED_PREFIX) {
+            continue;
+        }
+        if key.is_none() {
+            key = Some(ConfigKey::from_env()?.ok_or_else(|| {
+                ConfigError::Parse(format!(
+                    "{} is encrypted but {} is not set",
+                    path, CONFIG_KEY_ENV
+                ))
+            })?);
+        }
+        let plaintext = key
+            .as_ref()
+            .expect("key loaded above")
+            .decrypt(value)
+            .map_err(|e| match e {
+                ConfigError::Parse(msg) => ConfigError::Parse(format!("{}: {}", path, msg)),
+                other => other,
+            })?;
+        *field = Some(plaintext);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_and_wrong_key() {
+        let key = ConfigKey::generate();
+        let encrypted = key.encrypt("s3cret");
+        assert!(encrypted.starts_with(ENCRYPTED_PREFIX));
+        assert_eq!(key.decrypt(&encrypted).unwrap(), "s3cret");
+        assert_eq!(key.decrypt("plain").unwrap(), "plain");
+
+        let parsed = ConfigKey::from_base64(&key.to_base64()).unwrap();
+        assert_eq!(parsed.decrypt(&encrypted).unwrap(), "s3cret");
+        assert!(ConfigKey::generate().decrypt(&encrypted).is_err());
+        assert!(ConfigKey::from_base64("dG9vIHNob3J0").is_err());
+    }
+
+    #[test]
+    fn secret_fields_are_decrypted_with_env_key() {
+        let key = ConfigKey::generate();
+        let mut cfg = Config {
+            redis_url: Some(key.encrypt("redis://:pw@cache:6379")),
+            ..Config::default()
+        };
+
+        std::env::remove_var(CONFIG_KEY_ENV);
+        let err = decrypt_secrets(&mut cfg.clone()).expect_err("key is required");
+        assert!(err.to_string().contains("redis_url"));
+
+        std::env::set_var(CONFIG_KEY_ENV, key.to_base64());
+        decrypt_secrets(&mut cfg).expect("decrypt");
+        std::env::remove_var(CONFIG_KEY_ENV);
+        assert_eq!(cfg.redis_url.as_deref(), Some("redis://:pw@cache:6379"));
+    }
+}
@@ -0,0 +1,306 @@
+//! Secret references (`env:`, `file:`, `vault:`) resolved when the config is loaded.
+//!
+//! A secret field whose value starts with the scheme of a registered [`SecretResolver`]
+//! is replaced by whatever that resolver returns, e.g. `vault:secret/didhub#db_password`.
+//! Values with any other prefix are kept as literals.
+
+use std::env;
+use std::fs;
+
+#[cfg(feature = "vault")]
+use crate::env_secret;
+use crate::{Config, ConfigError};
+
+/// Resolves secret references of one scheme (the part before the first `:`).
+pub trait SecretResolver: Send + Sync {
+    /// Scheme handled by this resolver, without the trailing colon.
+    fn scheme(&self) -> &str;
+
+    /// Resolve `reference` (the value with `<scheme>:` removed) to the secret itself.
+    fn resolve(&self, reference: &str) -> Result<String, ConfigError>;
+}
+
+/// `env:NAME` reads the secret from an environment variable.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct EnvResolver;
+
+impl SecretResolver for EnvResolver {
+    fn scheme(&self) -> &str {
+        "env"
+    }
+
+    fn resolve(&self, reference: &str) -> Result<String, ConfigError> {
+        env::var(reference).map_err(|_| {
+            ConfigError::Parse(format!("environment variable {} is not set", reference))
+        })
+    }
+}
+
+/// `file:/path/to/secret` reads the secret from a file, stripping one trailing newline.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileResolver;
+
+impl SecretResolver for FileResolver {
+    fn scheme(&self) -> &str {
+        "file"
+    }
+
+    fn resolve(&self, reference: &str) -> Result<String, ConfigError> {
+        let mut contents = fs::read_to_string(reference)
+            .map_err(|e| ConfigError::Parse(format!("failed to read {}: {}", reference, e)))?;
+        if contents.ends_with('\n') {
+            contents.pop();
+            if contents.ends_with('\r') {
+                contents.pop();
+            }
+        }
+        Ok(contents)
+    }
+}
+
+/// `vault:<mount>/<path>#<field>` reads a field from a HashiCorp Vault KV v2 secret.
+///
+/// `vault:secret/didhub#db_password` fetches `GET /v1/secret/data/didhub` and returns
+/// `data.data.db_password`.
+#[cfg(feature = "vault")]
+#[derive(Debug, Clone)]
+pub struct VaultResolver {
+    addr: String,
+    token: String,
+    namespace: Option<String>,
+    agent: ureq::Agent,
+}
+
+#[cfg(feature = "vault")]
+impl VaultResolver {
+    pub fn new(addr: impl Into<String>, token: impl Into<String>) -> Self {
+        Self {
+            addr: addr.into().trim_end_matches('/').to_string(),
+            token: token.into(),
+            namespace: None,
+            agent: ureq::AgentBuilder::new()
+                .timeout(std::time::Duration::from_secs(10))
+                .build(),
+        }
+    }
+
+    /// Send requests to a Vault Enterprise namespace.
+    #[must_use]
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    /// Build from `VAULT_ADDR`, `VAULT_TOKEN` (or `VAULT_TOKEN_FILE`) and `VAULT_NAMESPACE`.
+    ///
+    /// Returns `None` when `VAULT_ADDR` is unset.
+    pub fn from_env() -> Result<Option<Self>, ConfigError> {
+        let Ok(addr) = env::var("VAULT_ADDR") else {
+            return Ok(None);
+        };
+        let token = env_secret("VAULT_TOKEN")?.ok_or_else(|| {
+            ConfigError::Validation("VAULT_ADDR is set but VAULT_TOKEN is not".into())
+        })?;
+        let resolver = Self::new(addr, token);
+        Ok(Some(match env::var("VAULT_NAMESPACE") {
+            Ok(ns) => resolver.with_namespace(ns),
+            Err(_) => resolver,
+        }))
+    }
+}
+
+#[cfg(feature = "vault")]
+impl SecretResolver for VaultResolver {
+    fn scheme(&self) -> &str {
+        "vault"
+    }
+
+    fn resolve(&self, reference: &str) -> Result<String, ConfigError> {
+        let invalid = || {
+            ConfigError::Parse(format!(
+                "invalid vault reference `{}`; expected <mount>/<path>#<field>",
+                reference
+            ))
+        };
+        let (path, field) = reference.split_once('#').ok_or_else(invalid)?;
+        let (mount, secret) = path.split_once('/').ok_or_else(invalid)?;
+        if field.is_empty() || secret.is_empty() {
+            return Err(invalid());
+        }
+
+        let url = format!("{}/v1/{}/data/{}", self.addr, mount, secret);
+        let mut request = self.agent.get(&url).set("X-Vault-Token", &self.token);
+        if let Some(ns) = &self.namespace {
+            request = request.set("X-Vault-Namespace", ns);
+        }
+        let body: serde_json::Value = request
+            .call()
+            .map_err(|e| ConfigError::Parse(format!("vault request for {} failed: {}", path, e)))?
+            .into_json()
+            .map_err(|e| ConfigError::Parse(format!("invalid vault response: {}", e)))?;
+
+        match body.pointer(&format!("/data/data/{}", field)) {
+            Some(serde_json::Value::String(s)) => Ok(s.clone()),
+            Some(other) => Ok(other.to_string()),
+            None => Err(ConfigError::Parse(format!(
+                "vault secret {} has no field {}",
+                path, field
+            ))),
+        }
+    }
+}
+
+/// The set of resolvers applied to a config's secret fields.
+#[derive(Default)]
+pub struct SecretResolvers {
+    resolvers: Vec<Box<dyn SecretResolver>>,
+}
+
+impl std::fmt::Debug for SecretResolvers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.resolvers.iter().map(|r| r.scheme()))
+            .finish()
+    }
+}
+
+impl SecretResolvers {
+    /// An empty set; every value is treated as a literal.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a resolver, replacing any earlier one for the same scheme.
+    #[must_use]
+    pub fn with(mut self, resolver: impl SecretResolver + 'static) -> Self {
+        self.resolvers.retain(|r| r.scheme() != resolver.scheme());
+        self.resolvers.push(Box::new(resolver));
+        self
+    }
+
+    /// The resolvers used by [`crate::load_config_layered`]: `env:` and `file:`, plus
+    /// `vault:` when the `vault` feature is enabled and `VAULT_ADDR` is set.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        #[allow(unused_mut)]
+        let mut resolvers = Self::new().with(EnvResolver).with(FileResolver);
+        #[cfg(feature = "vault")]
+        if let Some(vault) = VaultResolver::from_env()? {
+            resolvers = resolvers.with(vault);
+        }
+        Ok(resolvers)
+    }
+
+    /// Resolve `value` if it is a reference for a registered scheme; `None` if it is a literal.
+    pub fn resolve(&self, value: &str) -> Result<Option<String>, ConfigError> {
+        let Some((scheme, reference)) = value.split_once(':') else {
+            return Ok(None);
+        };
+        self.resolvers
+            .iter()
+            .find(|r| r.scheme() == scheme)
+            .map(|r| r.resolve(reference))
+            .transpose()
+    }
+
+    /// Replace every secret reference in `cfg` with its resolved value.
+    ///
+    /// `database.path` is not resolved because SQLite accepts `file:` URIs there.
+    pub fn resolve_config(&self, cfg: &mut Config) -> Result<(), ConfigError> {
+        let fields: [(&str, &mut Option<String>); 6] = [
+            ("database.username", &mut cfg.database.username),
+            ("database.password", &mut cfg.database.password),
+            ("redis_url", &mut cfg.redis_url),
+            ("auth.jwt_secret", &mut cfg.auth.jwt_secret),
+            ("auth.jwt_pem", &mut cfg.auth.jwt_pem),
+            ("auth.password_pepper", &mut cfg.auth.password_pepper),
+        ];
+        for (path, field) in fields {
+            let Some(value) = field.as_deref() else {
+                continue;
+            };
+            let resolved = self.resolve(value).map_err(|e| match e {
+                ConfigError::Parse(msg) => ConfigError::Parse(format!("{}: {}", path, msg)),
+                other => other,
+            })?;
+            if let Some(secret) = resolved {
+                *field = Some(secret);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn env_and_file_references_are_resolved() {
+        std::env::set_var("DIDHUB_TEST_SECRET_REF", "from-env");
+        let f = tempfile::NamedTempFile::new().expect("tmpfile");
+        std::fs::write(f.path(), "from-file\n").expect("write");
+
+        let mut cfg = Config {
+            redis_url: Some("redis://cache:6379".into()),
+            ..Config::default()
+        };
+        cfg.database.password = Some("env:DIDHUB_TEST_SECRET_REF".into());
+        cfg.auth.jwt_secret = Some(format!("file:{}", f.path().display()));
+
+        let resolvers = SecretResolvers::new().with(EnvResolver).with(FileResolver);
+        resolvers.resolve_config(&mut cfg).expect("resolve");
+        assert_eq!(cfg.database.password.as_deref(), Some("from-env"));
+        assert_eq!(cfg.auth.jwt_secret.as_deref(), Some("from-file"));
+        assert_eq!(cfg.redis_url.as_deref(), Some("redis://cache:6379"));
+
+        cfg.database.password = Some("env:DIDHUB_TEST_SECRET_REF_UNSET".into());
+        let err = resolvers.resolve_config(&mut cfg).expect_err("unset");
+        assert!(err.to_string().contains("database.password"));
+    }
+
+    #[cfg(feature = "vault")]
+    #[test]
+    fn vault_reference_reads_kv2_field() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            let mut lines = BufReader::new(stream.try_clone().expect("clone")).lines();
+            let request_line = lines.next().expect("request").expect("read");
+            let mut token = None;
+            for line in lines {
+                let line = line.expect("read");
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(": ") {
+                    if name.eq_ignore_ascii_case("x-vault-token") {
+                        token = Some(value.to_string());
+                    }
+                }
+            }
+            let body = r#"{"data":{"data":{"db_password":"from-vault"}}}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .expect("respond");
+            (request_line, token)
+        });
+
+        let vault = VaultResolver::new(format!("http://{}", addr), "test-token");
+        assert_eq!(
+            vault.resolve("secret/didhub#db_password").expect("resolve"),
+            "from-vault"
+        );
+        let (request_line, token) = server.join().expect("server");
+        assert_eq!(request_line, "GET /v1/secret/data/didhub HTTP/1.1");
+        assert_eq!(token.as_deref(), Some("test-token"));
+
+        assert!(vault.resolve("no-field").is_err());
+    }
+}
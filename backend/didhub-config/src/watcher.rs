@@ -0,0 +1,208 @@
+//! File watcher that republishes the configuration whenever the file changes.
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::watch;
+
+use crate::{load_config_layered, validate_config, Config, ConfigError};
+
+/// Quiet period after the last file event before the file is re-read, so that
+/// truncate-then-write sequences are not observed half-written.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Watches a config file and publishes every valid new `Config` on a `watch` channel.
+///
+/// The parent directory is watched rather than the file itself so that editors and
+/// secret mounts that replace the file via rename are still picked up. Changes that
+/// fail to parse or validate are logged and ignored; the last good config stays
+/// published. Bursts of events are debounced. Dropping the watcher stops watching.
+pub struct ConfigWatcher {
+    _watcher: RecommendedWatcher,
+    receiver: watch::Receiver<Config>,
+}
+
+impl ConfigWatcher {
+    /// Start watching `path`, publishing `initial` until the file changes.
+    pub fn spawn<P: AsRef<Path>>(path: P, initial: Config) -> Result<Self, ConfigError> {
+        Self::spawn_layered(&[path], initial)
+    }
+
+    /// Start watching every file of a layered config (see [`crate::load_config_layered`]).
+    ///
+    /// A change to any layer reloads the whole stack.
+    pub fn spawn_layered<P: AsRef<Path>>(
+        paths: &[P],
+        initial: Config,
+    ) -> Result<Self, ConfigError> {
+        Self::spawn_layered_with_overrides(paths, initial, |_| {})
+    }
+
+    /// Like [`Self::spawn_layered`], applying `overrides` (e.g. command-line flags)
+    /// to every reloaded config before it is validated and published.
+    pub fn spawn_layered_with_overrides<P: AsRef<Path>>(
+        paths: &[P],
+        initial: Config,
+        overrides: impl Fn(&mut Config) + Send + 'static,
+    ) -> Result<Self, ConfigError> {
+        let paths: Vec<PathBuf> = paths.iter().map(|p| p.as_ref().to_path_buf()).collect();
+        let mut file_names = HashSet::new();
+        let mut dirs = BTreeSet::new();
+        for path in &paths {
+            let file_name = path.file_name().map(|n| n.to_os_string()).ok_or_else(|| {
+                ConfigError::Watch(format!("not a file path: {}", path.display()))
+            })?;
+            file_names.insert(file_name);
+            dirs.insert(match path.parent() {
+                Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+                _ => PathBuf::from("."),
+            });
+        }
+
+        let (sender, receiver) = watch::channel(initial);
+        let (event_tx, event_rx) = mpsc::channel::<()>();
+        std::thread::Builder::new()
+            .name("didhub-config-watch".into())
+            .spawn(move || {
+                // Exits once the notify watcher (and with it `event_tx`) is dropped.
+                while event_rx.recv().is_ok() {
+                    while event_rx.recv_timeout(DEBOUNCE).is_ok() {}
+                    reload(&paths, &overrides, &sender);
+                }
+            })
+            .map_err(ConfigError::Io)?;
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::warn!(%e, "config watcher error");
+                    return;
+                }
+            };
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Any
+            ) {
+                return;
+            }
+            if !event
+                .paths
+                .iter()
+                .any(|p| p.file_name().is_some_and(|n| file_names.contains(n)))
+            {
+                return;
+            }
+            let _ = event_tx.send(());
+        })
+        .map_err(|e| ConfigError::Watch(e.to_string()))?;
+
+        for dir in &dirs {
+            watcher
+                .watch(dir, RecursiveMode::NonRecursive)
+                .map_err(|e| ConfigError::Watch(e.to_string()))?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+        })
+    }
+
+    /// Get a new receiver for config updates.
+    pub fn subscribe(&self) -> watch::Receiver<Config> {
+        self.receiver.clone()
+    }
+}
+
+fn reload(paths: &[PathBuf], overrides: &dyn Fn(&mut Config), sender: &watch::Sender<Config>) {
+    let mut new_cfg = match load_config_layered(paths) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            tracing::warn!(%e, "changed config file failed to load, ignoring");
+            return;
+        }
+    };
+    overrides(&mut new_cfg);
+    if let Err(e) = validate_config(&new_cfg) {
+        tracing::warn!(%e, "changed config file failed validation, ignoring");
+        return;
+    }
+    let changed = sender.send_if_modified(|current| {
+        if *current == new_cfg {
+            false
+        } else {
+            *current = new_cfg;
+            true
+        }
+    });
+    if changed {
+        tracing::info!("configuration file changed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_config;
+
+    #[tokio::test]
+    async fn publishes_changed_config() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[auto_update]\ncheck_interval_hours = 12\n").expect("write");
+
+        let initial = load_config(Some(&path)).expect("load");
+        let watcher = ConfigWatcher::spawn(&path, initial).expect("watch");
+        let mut rx = watcher.subscribe();
+
+        std::fs::write(&path, "[auto_update]\ncheck_interval_hours = 6\n").expect("write");
+        tokio::time::timeout(Duration::from_secs(5), rx.changed())
+            .await
+            .expect("config change not observed")
+            .expect("sender dropped");
+        assert_eq!(rx.borrow().auto_update.check_interval_hours, 6);
+    }
+
+    #[tokio::test]
+    async fn invalid_change_keeps_previous_config() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[cors]\nallowed_origins = [\"https://a.example\"]\n")
+            .expect("write");
+
+        let initial = load_config(Some(&path)).expect("load");
+        let watcher = ConfigWatcher::spawn(&path, initial).expect("watch");
+        let mut rx = watcher.subscribe();
+
+        std::fs::write(&path, "[cors]\nallowed_origins = [\"ftp://a.example\"]\n").expect("write");
+        let res = tokio::time::timeout(Duration::from_millis(500), rx.changed()).await;
+        assert!(res.is_err(), "invalid config must not be published");
+        assert_eq!(rx.borrow().cors.allowed_origins, vec!["https://a.example"]);
+    }
+
+    #[tokio::test]
+    async fn overrides_are_validated_with_the_file() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[auto_update]\ncheck_interval_hours = 12\n").expect("write");
+
+        let initial = load_config(Some(&path)).expect("load");
+        let watcher = ConfigWatcher::spawn_layered_with_overrides(&[&path], initial, |cfg| {
+            cfg.audit.retention_days = 0;
+        })
+        .expect("watch");
+        let mut rx = watcher.subscribe();
+
+        std::fs::write(&path, "[auto_update]\ncheck_interval_hours = 6\n").expect("write");
+        let res = tokio::time::timeout(Duration::from_millis(500), rx.changed()).await;
+        assert!(
+            res.is_err(),
+            "config invalid after overrides must not be published"
+        );
+        assert_eq!(rx.borrow().auto_update.check_interval_hours, 12);
+    }
+}
@@ -11,6 +11,9 @@ pub const DEFAULT_MIN_CONNECTIONS: u32 = 1;
 const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 30;
 const DEFAULT_IDLE_TIMEOUT_SECS: u64 = 600;
 const DEFAULT_TEST_BEFORE_ACQUIRE: bool = true;
+const DEFAULT_SLOW_QUERY_MS: u64 = 1000;
+/// Replicas give up quickly so a dead one does not stall reads that can go to the primary.
+const REPLICA_CONNECT_TIMEOUT_SECS: u64 = 2;
 
 /// Basic configuration for creating a SQLx connection pool.
 #[derive(Debug, Clone, Deserialize)]
@@ -22,6 +25,12 @@ pub struct DbConnectionConfig {
     pub connect_timeout_secs: u64,
     pub idle_timeout_secs: Option<u64>,
     pub test_before_acquire: bool,
+    /// Read replicas for read-only queries; empty to read from the primary.
+    pub replica_urls: Vec<String>,
+    /// Server-side limit on a single statement (Postgres and MySQL); `None` for no limit.
+    pub statement_timeout_ms: Option<u64>,
+    /// Statements running longer than this are logged as warnings.
+    pub slow_query_ms: u64,
 }
 
 impl Default for DbConnectionConfig {
@@ -34,6 +43,9 @@ impl Default for DbConnectionConfig {
             connect_timeout_secs: DEFAULT_CONNECT_TIMEOUT_SECS,
             idle_timeout_secs: Some(DEFAULT_IDLE_TIMEOUT_SECS),
             test_before_acquire: DEFAULT_TEST_BEFORE_ACQUIRE,
+            replica_urls: Vec::new(),
+            statement_timeout_ms: None,
+            slow_query_ms: DEFAULT_SLOW_QUERY_MS,
         }
     }
 }
@@ -57,6 +69,9 @@ impl DbConnectionConfig {
     /// - `{PREFIX}_DB_CONNECT_TIMEOUT_SECS` (optional)
     /// - `{PREFIX}_DB_IDLE_TIMEOUT_SECS` (optional)
     /// - `{PREFIX}_DB_TEST_BEFORE_ACQUIRE` (optional, bool)
+    /// - `{PREFIX}_DATABASE_REPLICA_URLS` (optional, comma-separated)
+    /// - `{PREFIX}_DB_STATEMENT_TIMEOUT_MS` (optional, 0 disables)
+    /// - `{PREFIX}_DB_SLOW_QUERY_MS` (optional)
     pub fn from_env(prefix: &str) -> Result<Self, DbConnectionError> {
         let url_var = format!("{}_DATABASE_URL", prefix);
         let url =
@@ -96,10 +111,42 @@ impl DbConnectionConfig {
         if let Some(value) = maybe_parse_bool(prefix, "DB_TEST_BEFORE_ACQUIRE")? {
             config.test_before_acquire = value;
         }
+        if let Some(timeout) = maybe_parse_u64(prefix, "DB_STATEMENT_TIMEOUT_MS")? {
+            config.statement_timeout_ms = (timeout > 0).then_some(timeout);
+        }
+        if let Some(slow) = maybe_parse_u64(prefix, "DB_SLOW_QUERY_MS")? {
+            config.slow_query_ms = slow;
+        }
+        let replicas_var = format!("{}_DATABASE_REPLICA_URLS", prefix);
+        match env::var(&replicas_var) {
+            Ok(value) => {
+                config.replica_urls = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|url| !url.is_empty())
+                    .map(str::to_owned)
+                    .collect();
+            }
+            Err(VarError::NotPresent) => {}
+            Err(VarError::NotUnicode(_)) => {
+                return Err(DbConnectionError::InvalidUnicode(replicas_var));
+            }
+        }
 
         Ok(config)
     }
 
+    /// Configuration for the replica at `url`: the primary's pool settings with a short
+    /// connect timeout and no replicas of its own.
+    pub fn for_replica(&self, url: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            connect_timeout_secs: self.connect_timeout_secs.min(REPLICA_CONNECT_TIMEOUT_SECS),
+            replica_urls: Vec::new(),
+            ..self.clone()
+        }
+    }
+
     #[inline]
     pub const fn connect_timeout(&self) -> Duration {
         Duration::from_secs(self.connect_timeout_secs)
@@ -109,6 +156,16 @@ impl DbConnectionConfig {
     pub fn idle_timeout(&self) -> Option<Duration> {
         self.idle_timeout_secs.map(Duration::from_secs)
     }
+
+    #[inline]
+    pub fn statement_timeout(&self) -> Option<Duration> {
+        self.statement_timeout_ms.map(Duration::from_millis)
+    }
+
+    #[inline]
+    pub const fn slow_query_threshold(&self) -> Duration {
+        Duration::from_millis(self.slow_query_ms)
+    }
 }
 
 fn maybe_parse_u32(prefix: &str, suffix: &str) -> Result<Option<u32>, DbConnectionError> {
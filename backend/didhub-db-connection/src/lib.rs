@@ -1,7 +1,9 @@
 pub mod config;
 pub mod error;
 pub mod logger;
+pub mod metrics;
 pub mod pool;
+pub mod replicas;
 #[cfg(test)]
 mod test;
 pub mod utils;
@@ -10,4 +12,6 @@ pub mod utils;
 pub use config::DbConnectionConfig;
 pub use error::{DbConnectionError, DbConnectionErrorKind};
 pub use logger::ConnectionLogger;
-pub use pool::{create_pool, create_pool_with_logging, DbPool};
+pub use metrics::{sample_pool, spawn_pool_sampler};
+pub use pool::{create_pool, create_pool_with_logging, DbPool, DbPoolConnection};
+pub use replicas::{create_replica_pools, ReadReplicas};
@@ -0,0 +1,42 @@
+use std::time::{Duration, Instant};
+
+use tokio::task::JoinHandle;
+
+use crate::pool::DbPool;
+
+/// Record the pool gauges for `pool` under the label `name`, then time checking
+/// out a connection for the acquire-wait histogram.
+pub async fn sample_pool(pool: &DbPool, name: &str) {
+    if pool.is_closed() {
+        return;
+    }
+    let size = pool.size();
+    let idle = u32::try_from(pool.num_idle()).unwrap_or(u32::MAX).min(size);
+    didhub_metrics::observe_db_pool(
+        name,
+        idle,
+        size - idle,
+        pool.options().get_max_connections(),
+    );
+
+    let started = Instant::now();
+    match pool.acquire().await {
+        Ok(conn) => {
+            didhub_metrics::observe_db_pool_acquire(name, started.elapsed());
+            drop(conn);
+        }
+        Err(error) => tracing::debug!(pool = name, %error, "pool sample could not acquire"),
+    }
+}
+
+/// Sample `pool` every `interval` until it is closed.
+pub fn spawn_pool_sampler(pool: DbPool, name: String, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        while !pool.is_closed() {
+            ticker.tick().await;
+            sample_pool(&pool, &name).await;
+        }
+    })
+}
@@ -1,9 +1,14 @@
+use std::str::FromStr;
+
 #[cfg(feature = "mysql")]
-use sqlx::mysql::{MySqlPool, MySqlPoolOptions};
+use sqlx::mysql::{MySqlConnectOptions, MySqlPool, MySqlPoolOptions};
 #[cfg(feature = "postgres")]
-use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::postgres::{PgConnectOptions, PgPool, PgPoolOptions};
 #[cfg(feature = "sqlite")]
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::ConnectOptions;
+
+use sqlx::pool::PoolConnection;
 
 use crate::config::DbConnectionConfig;
 use crate::error::DbConnectionError;
@@ -28,6 +33,14 @@ pub type DbPool = MySqlPool;
 #[cfg(feature = "sqlite")]
 pub type DbPool = SqlitePool;
 
+/// A connection checked out of a [`DbPool`].
+#[cfg(feature = "postgres")]
+pub type DbPoolConnection = PoolConnection<sqlx::Postgres>;
+#[cfg(feature = "mysql")]
+pub type DbPoolConnection = PoolConnection<sqlx::MySql>;
+#[cfg(feature = "sqlite")]
+pub type DbPoolConnection = PoolConnection<sqlx::Sqlite>;
+
 #[cfg(feature = "postgres")]
 type DbPoolOptions = PgPoolOptions;
 #[cfg(feature = "mysql")]
@@ -35,6 +48,13 @@ type DbPoolOptions = MySqlPoolOptions;
 #[cfg(feature = "sqlite")]
 type DbPoolOptions = SqlitePoolOptions;
 
+#[cfg(feature = "postgres")]
+type DbConnectOptions = PgConnectOptions;
+#[cfg(feature = "mysql")]
+type DbConnectOptions = MySqlConnectOptions;
+#[cfg(feature = "sqlite")]
+type DbConnectOptions = SqliteConnectOptions;
+
 #[cfg(feature = "sqlite")]
 pub const SQLITE_MEMORY_PATTERNS: [&[u8]; 2] = [b":memory:", b"mode=memory"];
 
@@ -85,7 +105,43 @@ async fn create_pool_inner(config: &DbConnectionConfig) -> Result<DbPool, DbConn
         opts = opts.idle_timeout(idle);
     }
 
-    opts.connect(url).await.map_err(DbConnectionError::from)
+    // sqlx logs statements over the threshold as tracing events (target `sqlx::query`)
+    // with the statement summary, elapsed time and row counts
+    let connect_opts = DbConnectOptions::from_str(url)
+        .map_err(DbConnectionError::from)?
+        .log_slow_statements(log::LevelFilter::Warn, config.slow_query_threshold());
+
+    #[cfg(feature = "postgres")]
+    let connect_opts = match config.statement_timeout() {
+        Some(timeout) => {
+            connect_opts.options([("statement_timeout", timeout.as_millis().to_string())])
+        }
+        None => connect_opts,
+    };
+    #[cfg(feature = "mysql")]
+    if let Some(timeout) = config.statement_timeout() {
+        // Applies to SELECT statements only; MySQL has no general statement limit
+        let millis = timeout.as_millis();
+        opts = opts.after_connect(move |conn, _meta| {
+            Box::pin(async move {
+                sqlx::query(&format!("SET SESSION max_execution_time = {millis}"))
+                    .execute(conn)
+                    .await
+                    .map(|_| ())
+            })
+        });
+    }
+    #[cfg(feature = "sqlite")]
+    if let Some(timeout) = config.statement_timeout() {
+        tracing::warn!(
+            timeout_ms = timeout.as_millis() as u64,
+            "statement timeouts are not supported on SQLite; ignoring"
+        );
+    }
+
+    opts.connect_with(connect_opts)
+        .await
+        .map_err(DbConnectionError::from)
 }
 
 #[cfg(feature = "sqlite")]
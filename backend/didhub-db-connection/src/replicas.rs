@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::config::DbConnectionConfig;
+use crate::pool::{create_pool, DbPool, DbPoolConnection};
+use crate::utils::sanitize_database_url;
+
+/// Read replicas for read-only queries, used in round-robin order.
+///
+/// Replication lag means a replica may not yet see a write made moments ago, so only
+/// reads that tolerate slightly stale data (listings, search) should go through here.
+#[derive(Debug, Default)]
+pub struct ReadReplicas {
+    pools: Vec<DbPool>,
+    next: AtomicUsize,
+}
+
+impl ReadReplicas {
+    pub fn new(pools: Vec<DbPool>) -> Self {
+        Self {
+            pools,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+
+    /// The replica pools, in the order of `replica_urls`.
+    pub fn pools(&self) -> &[DbPool] {
+        &self.pools
+    }
+
+    /// Connection for a read-only query. Tries each replica once, starting with the
+    /// next in turn, and falls back to `primary` when none hands out a connection.
+    pub async fn acquire(&self, primary: &DbPool) -> Result<DbPoolConnection, sqlx::Error> {
+        let count = self.pools.len();
+        if count > 0 {
+            let start = self.next.fetch_add(1, Ordering::Relaxed);
+            for offset in 0..count {
+                let pool = &self.pools[(start + offset) % count];
+                if pool.is_closed() {
+                    continue;
+                }
+                match pool.acquire().await {
+                    Ok(conn) => return Ok(conn),
+                    Err(error) => {
+                        tracing::warn!(%error, "read replica unavailable; trying next");
+                    }
+                }
+            }
+        }
+        primary.acquire().await
+    }
+}
+
+/// Connect to every replica in `config.replica_urls`. Replicas that cannot be reached
+/// at startup are logged and left out, so reads fall back to the primary.
+pub async fn create_replica_pools(config: &DbConnectionConfig) -> ReadReplicas {
+    let mut pools = Vec::with_capacity(config.replica_urls.len());
+    for url in &config.replica_urls {
+        let replica = config.for_replica(url);
+        match create_pool(&replica).await {
+            Ok(pool) => pools.push(pool),
+            Err(error) => tracing::warn!(
+                replica = %sanitize_database_url(url),
+                %error,
+                "failed to connect to read replica; skipping it"
+            ),
+        }
+    }
+    ReadReplicas::new(pools)
+}
@@ -62,4 +62,107 @@ mod tests {
         // Test that connect_timeout works as expected
         assert_eq!(config.connect_timeout(), std::time::Duration::from_secs(42));
     }
+
+    #[test]
+    fn test_replica_config() {
+        let config = DbConnectionConfig {
+            replica_urls: vec!["postgres://replica/db".to_owned()],
+            ..DbConnectionConfig::new("postgres://primary/db")
+        };
+        let replica = config.for_replica("postgres://replica/db");
+        assert_eq!(replica.url, "postgres://replica/db");
+        assert_eq!(replica.max_connections, config.max_connections);
+        assert!(replica.connect_timeout_secs <= 2);
+        assert!(replica.replica_urls.is_empty());
+    }
+
+    #[test]
+    fn test_query_timing_defaults() {
+        let config = DbConnectionConfig::new("sqlite::memory:");
+        assert_eq!(config.statement_timeout(), None);
+        assert_eq!(
+            config.slow_query_threshold(),
+            std::time::Duration::from_millis(1000)
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sqlite_pool_ignores_statement_timeout() {
+        let config = DbConnectionConfig {
+            statement_timeout_ms: Some(50),
+            slow_query_ms: 0,
+            ..DbConnectionConfig::new("sqlite::memory:")
+        };
+        let pool = create_pool(&config).await.expect("create pool");
+        sqlx::query("SELECT 1")
+            .execute(&pool)
+            .await
+            .expect("query runs");
+    }
+
+    #[cfg(feature = "sqlite")]
+    async fn pool_named(name: &str) -> DbPool {
+        let pool = create_pool(&DbConnectionConfig::new("sqlite::memory:"))
+            .await
+            .expect("create pool");
+        sqlx::query(&format!("CREATE TABLE {name} (id INTEGER)"))
+            .execute(&pool)
+            .await
+            .expect("create marker table");
+        pool
+    }
+
+    #[cfg(feature = "sqlite")]
+    async fn has_table(conn: &mut DbPoolConnection, name: &str) -> bool {
+        sqlx::query("SELECT 1 FROM sqlite_master WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&mut **conn)
+            .await
+            .expect("query sqlite_master")
+            .is_some()
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_read_replicas_round_robin_and_fallback() {
+        let primary = pool_named("primary_db").await;
+
+        let none = ReadReplicas::default();
+        let mut conn = none.acquire(&primary).await.expect("acquire");
+        assert!(has_table(&mut conn, "primary_db").await);
+        drop(conn);
+
+        let a = pool_named("replica_a").await;
+        let b = pool_named("replica_b").await;
+        let replicas = ReadReplicas::new(vec![a.clone(), b]);
+        let mut first = replicas.acquire(&primary).await.expect("acquire");
+        let mut second = replicas.acquire(&primary).await.expect("acquire");
+        assert!(has_table(&mut first, "replica_a").await);
+        assert!(has_table(&mut second, "replica_b").await);
+        drop((first, second));
+
+        // A closed replica is skipped; with none left the primary serves reads
+        a.close().await;
+        let only_closed = ReadReplicas::new(vec![a]);
+        let mut conn = only_closed.acquire(&primary).await.expect("acquire");
+        assert!(has_table(&mut conn, "primary_db").await);
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_sample_pool_records_gauges() {
+        let pool = pool_named("sampled").await;
+        let held = pool.acquire().await.expect("acquire");
+        sample_pool(&pool, "sampled").await;
+        drop(held);
+
+        let text = didhub_metrics::render();
+        let max = pool.options().get_max_connections();
+        assert!(text.contains(&format!(
+            "didhub_db_pool_max_connections{{pool=\"sampled\"}} {max}\n"
+        )));
+        assert!(text.contains("didhub_db_pool_connections{pool=\"sampled\",state=\"in_use\"} 1\n"));
+        assert!(text.contains("didhub_db_pool_acquire_wait_seconds_count{pool=\"sampled\"} 1\n"));
+    }
 }
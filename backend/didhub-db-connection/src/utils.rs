@@ -12,6 +12,9 @@ pub fn config_metadata(config: &DbConnectionConfig) -> serde_json::Value {
         "connect_timeout_secs": config.connect_timeout_secs,
         "idle_timeout_secs": config.idle_timeout_secs,
         "test_before_acquire": config.test_before_acquire,
+        "replica_count": config.replica_urls.len(),
+        "statement_timeout_ms": config.statement_timeout_ms,
+        "slow_query_ms": config.slow_query_ms,
     })
 }
 
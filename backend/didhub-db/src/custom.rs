@@ -2,7 +2,7 @@
 // These functions provide common queries that are not auto-generated
 
 use crate::DbBackend;
-use sqlx::Executor;
+use sqlx::{Executor, QueryBuilder};
 
 pub mod users {
     use super::*;
@@ -52,6 +52,27 @@ pub mod affiliation_members {
         .await
     }
 
+    /// Members of `affiliation_id`, leaving out alters in the trash.
+    pub async fn list_active_for_affiliation<'e, E>(
+        executor: E,
+        affiliation_id: &uuid::Uuid,
+    ) -> Result<Vec<db_affiliation_members::AffiliationMembersRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        sqlx::query_as::<_, db_affiliation_members::AffiliationMembersRow>(
+            r#"
+            SELECT am.affiliation_id, am.alter_id, am.is_leader, am.added_at
+            FROM affiliation_members am
+            INNER JOIN alters a ON a.id = am.alter_id
+            WHERE am.affiliation_id = ? AND a.deleted_at IS NULL
+            "#,
+        )
+        .bind(affiliation_id)
+        .fetch_all(executor)
+        .await
+    }
+
     pub async fn find_affiliations_for_alter<'e, E>(
         executor: E,
         alter_id: &uuid::Uuid,
@@ -64,7 +85,7 @@ pub mod affiliation_members {
             SELECT a.id, a.name, a.description, a.sigil, am.is_leader, am.added_at
             FROM affiliations a
             INNER JOIN affiliation_members am ON a.id = am.affiliation_id
-            WHERE am.alter_id = ?
+            WHERE am.alter_id = ? AND a.deleted_at IS NULL
             ORDER BY a.name
             "#,
         )
@@ -95,6 +116,27 @@ pub mod subsystem_members {
         .await
     }
 
+    /// Members of `subsystem_id`, leaving out alters in the trash.
+    pub async fn list_active_for_subsystem<'e, E>(
+        executor: E,
+        subsystem_id: &uuid::Uuid,
+    ) -> Result<Vec<db_subsystem_members::SubsystemMembersRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        sqlx::query_as::<_, db_subsystem_members::SubsystemMembersRow>(
+            r#"
+            SELECT sm.subsystem_id, sm.alter_id, sm.is_host, sm.added_at
+            FROM subsystem_members sm
+            INNER JOIN alters a ON a.id = sm.alter_id
+            WHERE sm.subsystem_id = ? AND a.deleted_at IS NULL
+            "#,
+        )
+        .bind(subsystem_id)
+        .fetch_all(executor)
+        .await
+    }
+
     #[derive(Debug, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
     pub struct SubsystemWithMemberInfo {
         pub id: uuid::Uuid,
@@ -123,3 +165,1258 @@ pub mod subsystem_members {
         .await
     }
 }
+
+/// Full-text search over alters, affiliations and subsystems.
+///
+/// SQLite queries the FTS5 `search_index` table kept in sync by triggers, Postgres the
+/// `to_tsvector` expression indexes and MySQL the FULLTEXT indexes (see migration
+/// 0008). Highlighted terms in snippets are wrapped in [`HIGHLIGHT_START`] and
+/// [`HIGHLIGHT_END`] so callers can escape the text before adding markup. MySQL has no
+/// snippet function; its snippets are the start of the description, unhighlighted.
+pub mod search {
+    use super::*;
+
+    /// Marks the start of a highlighted term in [`SearchHit::snippet`].
+    pub const HIGHLIGHT_START: char = '\u{2}';
+    /// Marks the end of a highlighted term in [`SearchHit::snippet`].
+    pub const HIGHLIGHT_END: char = '\u{3}';
+
+    /// Entity types stored in the index.
+    pub const ENTITY_TYPES: [&str; 3] = ["alter", "affiliation", "subsystem"];
+
+    const MAX_TERMS: usize = 8;
+
+    /// Condition skipping trashed rows of `table`, for tables that have a trash.
+    #[cfg(any(feature = "postgres", feature = "mysql"))]
+    fn live_filter(table: &str) -> &'static str {
+        if super::trash::TABLES.contains(&table) {
+            " AND deleted_at IS NULL"
+        } else {
+            ""
+        }
+    }
+
+    /// `WHERE` clause keeping the hits of systems `visible_to` can see; binds the
+    /// viewer twice. Empty when every system is visible.
+    #[cfg(any(feature = "sqlite", feature = "mysql"))]
+    fn visibility_filter(visible_to: Option<&uuid::Uuid>) -> String {
+        match visible_to {
+            Some(_) => format!(
+                "WHERE {}",
+                super::system_members::visible_condition("system_id")
+            ),
+            None => String::new(),
+        }
+    }
+
+    #[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+    pub struct SearchHit {
+        pub entity_type: String,
+        pub entity_id: uuid::Uuid,
+        pub name: String,
+        pub system_id: Option<uuid::Uuid>,
+        pub snippet: String,
+        /// Backend-specific relevance; higher is better.
+        pub score: f64,
+    }
+
+    /// Words of a user query, stripped of characters with meaning in the backend's
+    /// query syntax. Empty when there is nothing to search for.
+    pub fn query_terms(input: &str) -> Vec<String> {
+        input
+            .split_whitespace()
+            .map(|word| {
+                word.chars()
+                    .filter(|c| c.is_alphanumeric())
+                    .collect::<String>()
+            })
+            .filter(|word| !word.is_empty())
+            .take(MAX_TERMS)
+            .collect()
+    }
+
+    /// Backend query for `terms`: all terms must match, the last one as a prefix so
+    /// results show up while the user is still typing.
+    pub fn match_expression(terms: &[String]) -> String {
+        let last = terms.len().saturating_sub(1);
+        terms
+            .iter()
+            .enumerate()
+            .map(|(i, term)| {
+                let prefix = i == last;
+                if cfg!(feature = "postgres") {
+                    format!("{}{}", term, if prefix { ":*" } else { "" })
+                } else if cfg!(feature = "mysql") {
+                    format!("+{}{}", term, if prefix { "*" } else { "" })
+                } else {
+                    format!("\"{}\"{}", term, if prefix { "*" } else { "" })
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(if cfg!(feature = "postgres") {
+                " & "
+            } else {
+                " "
+            })
+    }
+
+    #[cfg(feature = "sqlite")]
+    pub async fn search<'e, E>(
+        executor: E,
+        terms: &[String],
+        entity_type: Option<&str>,
+        visible_to: Option<&uuid::Uuid>,
+        limit: i64,
+    ) -> Result<Vec<SearchHit>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        // bm25 takes one weight per column, including the unindexed ones
+        let sql = format!(
+            r#"
+            SELECT * FROM (
+            SELECT entity_type, entity_id, name,
+                CASE entity_type
+                    WHEN 'alter' THEN (SELECT user_id FROM alters WHERE id = search_index.entity_id)
+                    WHEN 'affiliation' THEN (SELECT owner_user_id FROM affiliations WHERE id = search_index.entity_id)
+                    ELSE (SELECT owner_user_id FROM subsystems WHERE id = search_index.entity_id)
+                END AS system_id,
+                snippet(search_index, -1, char(2), char(3), '…', 12) AS snippet,
+                -bm25(search_index, 0.0, 0.0, 10.0, 1.0) AS score
+            FROM search_index
+            WHERE search_index MATCH ? {}
+                AND entity_id NOT IN (
+                    SELECT id FROM alters WHERE deleted_at IS NOT NULL
+                    UNION ALL SELECT id FROM affiliations WHERE deleted_at IS NOT NULL
+                )
+            ) AS hits {}
+            ORDER BY score DESC
+            LIMIT ?
+            "#,
+            if entity_type.is_some() {
+                "AND entity_type = ?"
+            } else {
+                ""
+            },
+            visibility_filter(visible_to)
+        );
+        let mut query = sqlx::query_as::<_, SearchHit>(&sql).bind(match_expression(terms));
+        if let Some(entity_type) = entity_type {
+            query = query.bind(entity_type);
+        }
+        if let Some(viewer) = visible_to {
+            query = query.bind(*viewer).bind(*viewer);
+        }
+        query.bind(limit).fetch_all(executor).await
+    }
+
+    #[cfg(feature = "postgres")]
+    pub async fn search<'e, E>(
+        executor: E,
+        terms: &[String],
+        entity_type: Option<&str>,
+        visible_to: Option<&uuid::Uuid>,
+        limit: i64,
+    ) -> Result<Vec<SearchHit>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        // Each document expression matches its GIN index in migration 0008
+        let sources = [
+            ("alter", "alters", "user_id", "coalesce(name, '') || ' ' || coalesce(surname, '') || ' ' || coalesce(description, '') || ' ' || coalesce(notes, '') || ' ' || coalesce(interests, '')"),
+            ("affiliation", "affiliations", "owner_user_id", "coalesce(name, '') || ' ' || coalesce(description, '')"),
+            ("subsystem", "subsystems", "owner_user_id", "coalesce(name, '')"),
+        ];
+        let branches: Vec<String> = sources
+            .iter()
+            .filter(|(kind, ..)| entity_type.map_or(true, |t| t == *kind))
+            .map(|(kind, table, owner, doc)| {
+                format!(
+                    "SELECT '{kind}'::text AS entity_type, id AS entity_id, name, {owner} AS system_id, \
+                     ts_headline('simple', {doc}, q, 'StartSel=\"' || chr(2) || '\", StopSel=\"' || chr(3) || '\", MaxWords=24, MinWords=8') AS snippet, \
+                     ts_rank(to_tsvector('simple', {doc}), q)::float8 AS score \
+                     FROM {table}, to_tsquery('simple', $1) AS q \
+                     WHERE to_tsvector('simple', {doc}) @@ q{}",
+                    live_filter(table)
+                )
+            })
+            .collect();
+        let visibility = if visible_to.is_some() {
+            "WHERE (system_id = $3 OR system_id IN (SELECT system_id FROM system_members WHERE user_id = $3))"
+        } else {
+            ""
+        };
+        let sql = format!(
+            "SELECT * FROM ({}) AS hits {visibility} ORDER BY score DESC LIMIT $2",
+            branches.join(" UNION ALL ")
+        );
+        let mut query = sqlx::query_as::<_, SearchHit>(&sql)
+            .bind(match_expression(terms))
+            .bind(limit);
+        if let Some(viewer) = visible_to {
+            query = query.bind(*viewer);
+        }
+        query.fetch_all(executor).await
+    }
+
+    #[cfg(feature = "mysql")]
+    pub async fn search<'e, E>(
+        executor: E,
+        terms: &[String],
+        entity_type: Option<&str>,
+        visible_to: Option<&uuid::Uuid>,
+        limit: i64,
+    ) -> Result<Vec<SearchHit>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        // MATCH column lists must equal the FULLTEXT index definitions in migration 0008
+        let sources = [
+            (
+                "alter",
+                "alters",
+                "user_id",
+                "name, surname, description, notes, interests",
+                "description",
+            ),
+            (
+                "affiliation",
+                "affiliations",
+                "owner_user_id",
+                "name, description",
+                "description",
+            ),
+            ("subsystem", "subsystems", "owner_user_id", "name", "NULL"),
+        ];
+        let branches: Vec<String> = sources
+            .iter()
+            .filter(|(kind, ..)| entity_type.map_or(true, |t| t == *kind))
+            .map(|(kind, table, owner, columns, body)| {
+                format!(
+                    "SELECT '{kind}' AS entity_type, id AS entity_id, name, {owner} AS system_id, \
+                     COALESCE(LEFT({body}, 160), '') AS snippet, \
+                     CAST(MATCH({columns}) AGAINST (? IN BOOLEAN MODE) AS DOUBLE) AS score \
+                     FROM {table} WHERE MATCH({columns}) AGAINST (? IN BOOLEAN MODE){}",
+                    live_filter(table)
+                )
+            })
+            .collect();
+        let sql = format!(
+            "SELECT * FROM ({}) AS hits {} ORDER BY score DESC LIMIT ?",
+            branches.join(" UNION ALL "),
+            visibility_filter(visible_to)
+        );
+        let expression = match_expression(terms);
+        let mut query = sqlx::query_as::<_, SearchHit>(&sql);
+        // Each branch uses the expression twice, for the score and the filter
+        for _ in 0..branches.len() * 2 {
+            query = query.bind(expression.clone());
+        }
+        if let Some(viewer) = visible_to {
+            query = query.bind(*viewer).bind(*viewer);
+        }
+        query.bind(limit).fetch_all(executor).await
+    }
+}
+
+/// Keyset pagination over alters, ordered by name then id.
+pub mod alters {
+    use super::*;
+    use crate::generated::alters as db_alters;
+
+    /// Which alters a listing returns. Every condition that is set must hold;
+    /// trashed alters are always left out.
+    #[derive(Debug, Clone, Default, PartialEq, Eq)]
+    pub struct AlterFilter {
+        /// Alters of this system.
+        pub user_id: Option<uuid::Uuid>,
+        /// Alters owned by this user.
+        pub owner_user_id: Option<uuid::Uuid>,
+        /// Case-insensitive substring of the name.
+        pub name: Option<String>,
+        /// Case-insensitive species.
+        pub species: Option<String>,
+        /// System roles the alter must all hold.
+        pub system_roles: Vec<String>,
+        /// Tags the alter must all carry.
+        pub tag_ids: Vec<uuid::Uuid>,
+        /// Alters in this subsystem.
+        pub subsystem_id: Option<uuid::Uuid>,
+        pub is_system_host: Option<bool>,
+        pub is_dormant: Option<bool>,
+        pub is_merged: Option<bool>,
+        /// Whether a birthday is recorded.
+        pub has_birthday: Option<bool>,
+        /// Only alters of systems this user is, or is a member of.
+        pub visible_to: Option<uuid::Uuid>,
+    }
+
+    impl AlterFilter {
+        /// All alters of the system `user_id`.
+        pub fn system(user_id: &uuid::Uuid) -> Self {
+            Self {
+                user_id: Some(*user_id),
+                ..Self::default()
+            }
+        }
+    }
+
+    /// `text` as a `LIKE` pattern matching it anywhere, with `!` escaping the
+    /// wildcards; use with `ESCAPE '!'`.
+    fn contains_pattern(text: &str) -> String {
+        let mut pattern = String::from("%");
+        for c in text.chars() {
+            if matches!(c, '!' | '%' | '_') {
+                pattern.push('!');
+            }
+            pattern.push(c);
+        }
+        pattern.push('%');
+        pattern
+    }
+
+    /// Append the `WHERE` clause selecting the alters matching `filter`. Values
+    /// are bound, never spliced into the SQL.
+    fn push_conditions<'a>(query: &mut QueryBuilder<'a, DbBackend>, filter: &'a AlterFilter) {
+        query.push(" WHERE deleted_at IS NULL");
+        if let Some(user_id) = filter.user_id {
+            query.push(" AND user_id = ").push_bind(user_id);
+        }
+        if let Some(owner_user_id) = filter.owner_user_id {
+            query.push(" AND owner_user_id = ").push_bind(owner_user_id);
+        }
+        if let Some(viewer) = filter.visible_to {
+            query
+                .push(" AND (user_id = ")
+                .push_bind(viewer)
+                .push(" OR user_id IN (SELECT system_id FROM system_members WHERE user_id = ")
+                .push_bind(viewer)
+                .push("))");
+        }
+        if let Some(name) = &filter.name {
+            query
+                .push(" AND LOWER(name) LIKE ")
+                .push_bind(contains_pattern(&name.to_lowercase()))
+                .push(" ESCAPE '!'");
+        }
+        if let Some(species) = &filter.species {
+            query
+                .push(" AND LOWER(species) = ")
+                .push_bind(species.to_lowercase());
+        }
+        // `system_roles` is a JSON array of strings, so look for the quoted role
+        for role in &filter.system_roles {
+            let quoted = serde_json::Value::from(role.as_str()).to_string();
+            query
+                .push(" AND system_roles LIKE ")
+                .push_bind(contains_pattern(&quoted))
+                .push(" ESCAPE '!'");
+        }
+        if !filter.tag_ids.is_empty() {
+            query.push(" AND id IN (SELECT alter_id FROM alter_tags WHERE tag_id IN (");
+            let mut ids = query.separated(", ");
+            for tag_id in &filter.tag_ids {
+                ids.push_bind(*tag_id);
+            }
+            query
+                .push(") GROUP BY alter_id HAVING COUNT(*) = ")
+                .push_bind(filter.tag_ids.len() as i64)
+                .push(")");
+        }
+        if let Some(subsystem_id) = filter.subsystem_id {
+            query
+                .push(" AND id IN (SELECT alter_id FROM subsystem_members WHERE subsystem_id = ")
+                .push_bind(subsystem_id)
+                .push(")");
+        }
+        for (column, value) in [
+            ("is_system_host", filter.is_system_host),
+            ("is_dormant", filter.is_dormant),
+            ("is_merged", filter.is_merged),
+        ] {
+            if let Some(value) = value {
+                query
+                    .push(format_args!(" AND {column} = "))
+                    .push_bind(i32::from(value));
+            }
+        }
+        match filter.has_birthday {
+            Some(true) => query.push(" AND birthday IS NOT NULL AND birthday <> ''"),
+            Some(false) => query.push(" AND (birthday IS NULL OR birthday = '')"),
+            None => query,
+        };
+    }
+
+    /// Up to `limit` alters matching `filter` after the `(name, id)` position
+    /// `after`.
+    pub async fn list_page<'e, E>(
+        executor: E,
+        filter: &AlterFilter,
+        after: Option<(&str, &uuid::Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<db_alters::AltersRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let mut query = QueryBuilder::new(format!("SELECT {} FROM alters", db_alters::COLUMN_LIST));
+        push_conditions(&mut query, filter);
+        if let Some((name, id)) = after {
+            query
+                .push(" AND (name > ")
+                .push_bind(name)
+                .push(" OR (name = ")
+                .push_bind(name)
+                .push(" AND id > ")
+                .push_bind(*id)
+                .push("))");
+        }
+        query.push(" ORDER BY name, id LIMIT ").push_bind(limit);
+        query
+            .build_query_as::<db_alters::AltersRow>()
+            .fetch_all(executor)
+            .await
+    }
+
+    /// All alters matching `filter`, ordered by name.
+    pub async fn list_active<'e, E>(
+        executor: E,
+        filter: &AlterFilter,
+    ) -> Result<Vec<db_alters::AltersRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let mut query = QueryBuilder::new(format!("SELECT {} FROM alters", db_alters::COLUMN_LIST));
+        push_conditions(&mut query, filter);
+        query.push(" ORDER BY name");
+        query
+            .build_query_as::<db_alters::AltersRow>()
+            .fetch_all(executor)
+            .await
+    }
+
+    /// The alter with `id`, unless it is in the trash.
+    pub async fn find_active<'e, E>(
+        executor: E,
+        id: &uuid::Uuid,
+    ) -> Result<Option<db_alters::AltersRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM alters WHERE id = ? AND deleted_at IS NULL",
+            db_alters::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_alters::AltersRow>(&sql)
+            .bind(id)
+            .fetch_optional(executor)
+            .await
+    }
+}
+
+pub mod affiliations {
+    use super::*;
+    use crate::generated::affiliations as db_affiliations;
+
+    /// Affiliations owned by `owner_user_id` that are not in the trash, ordered by
+    /// name.
+    pub async fn list_active_for_owner<'e, E>(
+        executor: E,
+        owner_user_id: &uuid::Uuid,
+    ) -> Result<Vec<db_affiliations::AffiliationsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM affiliations WHERE owner_user_id = ? AND deleted_at IS NULL ORDER BY name",
+            db_affiliations::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_affiliations::AffiliationsRow>(&sql)
+            .bind(owner_user_id)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// The affiliation with `id`, unless it is in the trash.
+    pub async fn find_active<'e, E>(
+        executor: E,
+        id: &uuid::Uuid,
+    ) -> Result<Option<db_affiliations::AffiliationsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM affiliations WHERE id = ? AND deleted_at IS NULL",
+            db_affiliations::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_affiliations::AffiliationsRow>(&sql)
+            .bind(id)
+            .fetch_optional(executor)
+            .await
+    }
+}
+
+pub mod relationships {
+    use super::*;
+    use crate::generated::relationships as db_relationships;
+
+    /// Relationships with `user_id` or one of its alters on either side, oldest
+    /// first. Relationships involving a trashed alter are left out.
+    pub async fn list_for_user<'e, E>(
+        executor: E,
+        user_id: &uuid::Uuid,
+    ) -> Result<Vec<db_relationships::RelationshipsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM relationships WHERE (side_a_user_id = ? OR side_b_user_id = ? \
+             OR side_a_alter_id IN (SELECT id FROM alters WHERE user_id = ? AND deleted_at IS NULL) \
+             OR side_b_alter_id IN (SELECT id FROM alters WHERE user_id = ? AND deleted_at IS NULL)) \
+             AND NOT EXISTS (SELECT 1 FROM alters WHERE deleted_at IS NOT NULL \
+             AND id IN (side_a_alter_id, side_b_alter_id)) \
+             ORDER BY created_at, id",
+            db_relationships::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_relationships::RelationshipsRow>(&sql)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(user_id)
+            .bind(user_id)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Relationships with a side in a system `viewer` can see, either the system
+    /// itself or one of its alters, newest first.
+    pub async fn list_visible_to<'e, E>(
+        executor: E,
+        viewer: &uuid::Uuid,
+    ) -> Result<Vec<db_relationships::RelationshipsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let visible = super::system_members::visible_condition;
+        let sql = format!(
+            "SELECT {} FROM relationships WHERE {} OR {} \
+             OR side_a_alter_id IN (SELECT id FROM alters WHERE {}) \
+             OR side_b_alter_id IN (SELECT id FROM alters WHERE {}) \
+             ORDER BY created_at DESC",
+            db_relationships::COLUMN_LIST,
+            visible("side_a_user_id"),
+            visible("side_b_user_id"),
+            visible("user_id"),
+            visible("user_id"),
+        );
+        let mut query = sqlx::query_as::<_, db_relationships::RelationshipsRow>(&sql);
+        for _ in 0..8 {
+            query = query.bind(viewer);
+        }
+        query.fetch_all(executor).await
+    }
+}
+
+/// Tags users define for themselves and attach to alters.
+pub mod tags {
+    use super::*;
+    use crate::generated::tags as db_tags;
+
+    /// The tags of `user_id`, ordered by name.
+    pub async fn list_for_user<'e, E>(
+        executor: E,
+        user_id: &uuid::Uuid,
+    ) -> Result<Vec<db_tags::TagsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM tags WHERE user_id = ? ORDER BY name",
+            db_tags::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_tags::TagsRow>(&sql)
+            .bind(user_id)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// The tag of `user_id` called `name`, if any.
+    pub async fn find_by_name<'e, E>(
+        executor: E,
+        user_id: &uuid::Uuid,
+        name: &str,
+    ) -> Result<Option<db_tags::TagsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM tags WHERE user_id = ? AND name = ?",
+            db_tags::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_tags::TagsRow>(&sql)
+            .bind(user_id)
+            .bind(name)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// The tags of `user_id` attached to `alter_id`, ordered by name.
+    pub async fn list_for_alter<'e, E>(
+        executor: E,
+        alter_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> Result<Vec<db_tags::TagsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        sqlx::query_as::<_, db_tags::TagsRow>(
+            "SELECT t.id, t.user_id, t.name, t.color, t.created_at \
+             FROM tags t JOIN alter_tags at ON at.tag_id = t.id \
+             WHERE at.alter_id = ? AND t.user_id = ? ORDER BY t.name",
+        )
+        .bind(alter_id)
+        .bind(user_id)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Detach all tags of `user_id` from `alter_id`, leaving other users' tags.
+    pub async fn clear_for_alter<'e, E>(
+        executor: E,
+        alter_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        sqlx::query(
+            "DELETE FROM alter_tags WHERE alter_id = ? \
+             AND tag_id IN (SELECT id FROM tags WHERE user_id = ?)",
+        )
+        .bind(alter_id)
+        .bind(user_id)
+        .execute(executor)
+        .await
+        .map(|r| r.rows_affected())
+    }
+}
+
+/// Custom fields systems define for their alters and the alters' values for them.
+pub mod custom_fields {
+    use super::*;
+    use crate::generated::alter_custom_field_values as db_values;
+    use crate::generated::custom_field_definitions as db_definitions;
+
+    /// The field definitions of the system `user_id`, in display order.
+    pub async fn list_definitions<'e, E>(
+        executor: E,
+        user_id: &uuid::Uuid,
+    ) -> Result<Vec<db_definitions::CustomFieldDefinitionsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM custom_field_definitions WHERE user_id = ? ORDER BY sort_order, name",
+            db_definitions::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_definitions::CustomFieldDefinitionsRow>(&sql)
+            .bind(user_id)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// The field of the system `user_id` called `name`, if any.
+    pub async fn find_definition_by_name<'e, E>(
+        executor: E,
+        user_id: &uuid::Uuid,
+        name: &str,
+    ) -> Result<Option<db_definitions::CustomFieldDefinitionsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM custom_field_definitions WHERE user_id = ? AND name = ?",
+            db_definitions::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_definitions::CustomFieldDefinitionsRow>(&sql)
+            .bind(user_id)
+            .bind(name)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// The custom field values of `alter_id`.
+    pub async fn values_for_alter<'e, E>(
+        executor: E,
+        alter_id: &uuid::Uuid,
+    ) -> Result<Vec<db_values::AlterCustomFieldValuesRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM alter_custom_field_values WHERE alter_id = ?",
+            db_values::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_values::AlterCustomFieldValuesRow>(&sql)
+            .bind(alter_id)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Remove the value of `field_id` from `alter_id`, if it has one.
+    pub async fn delete_value<'e, E>(
+        executor: E,
+        alter_id: &uuid::Uuid,
+        field_id: &uuid::Uuid,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        sqlx::query("DELETE FROM alter_custom_field_values WHERE alter_id = ? AND field_id = ?")
+            .bind(alter_id)
+            .bind(field_id)
+            .execute(executor)
+            .await
+            .map(|r| r.rows_affected())
+    }
+}
+
+/// Soft-deleted alters and affiliations. Deleting one sets its `deleted_at`; it
+/// can be restored until the purge job removes it for good.
+pub mod trash {
+    use super::*;
+
+    /// Tables whose rows are moved to the trash instead of being deleted.
+    pub const TABLES: [&str; 2] = ["alters", "affiliations"];
+
+    #[derive(Debug, Clone, serde::Serialize, sqlx::FromRow)]
+    pub struct TrashedEntity {
+        pub entity_type: String,
+        pub id: uuid::Uuid,
+        pub name: String,
+        pub owner_user_id: Option<uuid::Uuid>,
+        pub deleted_at: String,
+    }
+
+    /// Trashed alters and affiliations, most recently deleted first, optionally
+    /// only those owned by `owner` or of one entity type.
+    pub async fn list<'e, E>(
+        executor: E,
+        owner: Option<&uuid::Uuid>,
+        entity_type: Option<&str>,
+    ) -> Result<Vec<TrashedEntity>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let branches: Vec<String> = [("alter", "alters"), ("affiliation", "affiliations")]
+            .iter()
+            .filter(|(kind, _)| entity_type.map_or(true, |t| t == *kind))
+            .map(|(kind, table)| {
+                format!(
+                    "SELECT '{kind}' AS entity_type, id, name, owner_user_id, deleted_at \
+                     FROM {table} WHERE deleted_at IS NOT NULL{}",
+                    if owner.is_some() {
+                        " AND owner_user_id = ?"
+                    } else {
+                        ""
+                    }
+                )
+            })
+            .collect();
+        let sql = format!(
+            "SELECT * FROM ({}) AS trashed ORDER BY deleted_at DESC",
+            branches.join(" UNION ALL ")
+        );
+        let mut query = sqlx::query_as::<_, TrashedEntity>(&sql);
+        if let Some(owner) = owner {
+            for _ in 0..branches.len() {
+                query = query.bind(owner);
+            }
+        }
+        query.fetch_all(executor).await
+    }
+
+    /// Move the row with `id` in `table` to the trash. Returns 0 if there is no such
+    /// row or it is already trashed.
+    pub async fn mark_deleted<'e, E>(
+        executor: E,
+        table: &str,
+        id: &uuid::Uuid,
+        deleted_at: &str,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!("UPDATE {table} SET deleted_at = ? WHERE id = ? AND deleted_at IS NULL");
+        let result = sqlx::query(&sql)
+            .bind(deleted_at)
+            .bind(id)
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Take the row with `id` in `table` out of the trash. Returns 0 if it is not
+    /// trashed.
+    pub async fn restore<'e, E>(
+        executor: E,
+        table: &str,
+        id: &uuid::Uuid,
+    ) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql =
+            format!("UPDATE {table} SET deleted_at = NULL WHERE id = ? AND deleted_at IS NOT NULL");
+        let result = sqlx::query(&sql).bind(id).execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete the rows of `table` trashed at or before `cutoff` for good.
+    pub async fn purge<'e, E>(executor: E, table: &str, cutoff: &str) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!("DELETE FROM {table} WHERE deleted_at IS NOT NULL AND deleted_at <= ?");
+        let result = sqlx::query(&sql).bind(cutoff).execute(executor).await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Keyset pagination over uploads, newest first.
+pub mod uploads {
+    use super::*;
+    use crate::generated::uploads as db_uploads;
+
+    /// Up to `limit` uploads older than the `(created_at, id)` position `after`.
+    pub async fn list_page<'e, E>(
+        executor: E,
+        after: Option<(&str, &uuid::Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<db_uploads::UploadsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM uploads {} ORDER BY created_at DESC, id DESC LIMIT ?",
+            db_uploads::COLUMN_LIST,
+            if after.is_some() {
+                "WHERE (created_at < ? OR (created_at = ? AND id < ?))"
+            } else {
+                ""
+            }
+        );
+        let mut query = sqlx::query_as::<_, db_uploads::UploadsRow>(&sql);
+        if let Some((created_at, id)) = after {
+            query = query.bind(created_at).bind(created_at).bind(id);
+        }
+        query.bind(limit).fetch_all(executor).await
+    }
+}
+
+/// References from avatars and alter images to stored files.
+pub mod upload_references {
+    use super::*;
+
+    /// `owner_type` of an alter's images.
+    pub const ALTER: &str = "alter";
+    /// `owner_type` of a user's avatar.
+    pub const USER: &str = "user";
+
+    /// Make `stored_file_ids` the files referenced by the owner, dropping its
+    /// earlier references. Ids of files that are not stored are skipped.
+    pub async fn replace(
+        conn: &mut crate::DbPoolConnection,
+        owner_type: &str,
+        owner_id: &uuid::Uuid,
+        stored_file_ids: &[uuid::Uuid],
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM upload_references WHERE owner_type = ? AND owner_id = ?")
+            .bind(owner_type)
+            .bind(owner_id)
+            .execute(&mut **conn)
+            .await?;
+        let mut seen = std::collections::HashSet::new();
+        for stored_file_id in stored_file_ids.iter().filter(|id| seen.insert(**id)) {
+            sqlx::query(
+                "INSERT INTO upload_references (stored_file_id, owner_type, owner_id) \
+                 SELECT id, ?, ? FROM stored_files WHERE id = ?",
+            )
+            .bind(owner_type)
+            .bind(owner_id)
+            .bind(stored_file_id)
+            .execute(&mut **conn)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// How many avatars and alters use the stored file.
+    pub async fn count_for_file<'e, E>(
+        executor: E,
+        stored_file_id: &uuid::Uuid,
+    ) -> Result<i64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        sqlx::query_scalar("SELECT COUNT(*) FROM upload_references WHERE stored_file_id = ?")
+            .bind(stored_file_id)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// Condition on `stored_files` rows that no upload or reference points to.
+    const UNUSED: &str = "NOT EXISTS (SELECT 1 FROM uploads WHERE uploads.stored_file_id = stored_files.id) \
+         AND NOT EXISTS (SELECT 1 FROM upload_references WHERE upload_references.stored_file_id = stored_files.id)";
+
+    /// Up to `limit` stored files that no upload, avatar or alter uses.
+    pub async fn unused_files<'e, E>(
+        executor: E,
+        limit: i64,
+    ) -> Result<Vec<uuid::Uuid>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!("SELECT id FROM stored_files WHERE {UNUSED} LIMIT ?");
+        sqlx::query_scalar(&sql)
+            .bind(limit)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Delete the stored file row if it is still unused. Returns whether it was
+    /// deleted.
+    pub async fn delete_if_unused<'e, E>(
+        executor: E,
+        stored_file_id: &uuid::Uuid,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!("DELETE FROM stored_files WHERE id = ? AND {UNUSED}");
+        let result = sqlx::query(&sql)
+            .bind(stored_file_id)
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Drop the references of alters and users that have been deleted.
+    pub async fn prune_missing_owners<'e, E>(executor: E) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let result = sqlx::query(
+            "DELETE FROM upload_references WHERE \
+             (owner_type = ? AND owner_id NOT IN (SELECT id FROM alters)) \
+             OR (owner_type = ? AND owner_id NOT IN (SELECT id FROM users))",
+        )
+        .bind(ALTER)
+        .bind(USER)
+        .execute(executor)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Field-level change history recorded for audited entity updates.
+pub mod audit_changes {
+    use super::*;
+    use crate::generated::audit_changes as db_audit_changes;
+
+    /// Source of an entity's history: `audit_changes`, plus `audit_archive` when
+    /// `include_archived` is set.
+    fn history_source(include_archived: bool) -> String {
+        if include_archived {
+            format!(
+                "(SELECT {cols} FROM audit_changes UNION ALL SELECT {cols} FROM audit_archive) AS history",
+                cols = db_audit_changes::COLUMN_LIST
+            )
+        } else {
+            "audit_changes".to_string()
+        }
+    }
+
+    /// Up to `limit` changes recorded for `entity_id` after skipping `offset`,
+    /// newest first, optionally including archived changes.
+    pub async fn history_for_entity<'e, E>(
+        executor: E,
+        entity_id: &uuid::Uuid,
+        include_archived: bool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<db_audit_changes::AuditChangesRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM {} WHERE entity_id = ? ORDER BY created_at DESC, id DESC LIMIT ? OFFSET ?",
+            db_audit_changes::COLUMN_LIST,
+            history_source(include_archived)
+        );
+        sqlx::query_as::<_, db_audit_changes::AuditChangesRow>(&sql)
+            .bind(entity_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Up to `limit` changes recorded for `entity_id` older than the
+    /// `(created_at, id)` position `after`, newest first, optionally including
+    /// archived changes.
+    pub async fn history_page<'e, E>(
+        executor: E,
+        entity_id: &uuid::Uuid,
+        include_archived: bool,
+        after: Option<(&str, &uuid::Uuid)>,
+        limit: i64,
+    ) -> Result<Vec<db_audit_changes::AuditChangesRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM {} WHERE entity_id = ? {} ORDER BY created_at DESC, id DESC LIMIT ?",
+            db_audit_changes::COLUMN_LIST,
+            history_source(include_archived),
+            if after.is_some() {
+                "AND (created_at < ? OR (created_at = ? AND id < ?))"
+            } else {
+                ""
+            }
+        );
+        let mut query =
+            sqlx::query_as::<_, db_audit_changes::AuditChangesRow>(&sql).bind(entity_id);
+        if let Some((created_at, id)) = after {
+            query = query.bind(created_at).bind(created_at).bind(id);
+        }
+        query.bind(limit).fetch_all(executor).await
+    }
+
+    pub async fn count_for_entity<'e, E>(
+        executor: E,
+        entity_id: &uuid::Uuid,
+        include_archived: bool,
+    ) -> Result<i64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT COUNT(*) FROM {} WHERE entity_id = ?",
+            history_source(include_archived)
+        );
+        sqlx::query_scalar(&sql)
+            .bind(entity_id)
+            .fetch_one(executor)
+            .await
+    }
+
+    /// Move the changes recorded before `cutoff` to `audit_archive`, stamped with
+    /// `archived_at`. Returns the number moved.
+    pub async fn archive_older_than(
+        pool: &crate::DbPool,
+        cutoff: &str,
+        archived_at: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let sql = format!(
+            "INSERT INTO audit_archive ({cols}, archived_at) SELECT {cols}, ? FROM audit_changes WHERE created_at < ?",
+            cols = db_audit_changes::COLUMN_LIST
+        );
+        sqlx::query(&sql)
+            .bind(archived_at)
+            .bind(cutoff)
+            .execute(&mut *tx)
+            .await?;
+        let moved = sqlx::query("DELETE FROM audit_changes WHERE created_at < ?")
+            .bind(cutoff)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        tx.commit().await?;
+        Ok(moved)
+    }
+
+    /// Delete the changes recorded before `cutoff` for good.
+    pub async fn purge_older_than<'e, E>(executor: E, cutoff: &str) -> Result<u64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let result = sqlx::query("DELETE FROM audit_changes WHERE created_at < ?")
+            .bind(cutoff)
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+pub mod entity_versions {
+    use super::*;
+    use crate::generated::entity_versions as db_entity_versions;
+
+    /// Up to `limit` snapshots of `entity_id` after skipping `offset`, newest first.
+    pub async fn list_for_entity<'e, E>(
+        executor: E,
+        entity_id: &uuid::Uuid,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<db_entity_versions::EntityVersionsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM entity_versions WHERE entity_id = ? ORDER BY version DESC LIMIT ? OFFSET ?",
+            db_entity_versions::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_entity_versions::EntityVersionsRow>(&sql)
+            .bind(entity_id)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(executor)
+            .await
+    }
+
+    pub async fn count_for_entity<'e, E>(
+        executor: E,
+        entity_id: &uuid::Uuid,
+    ) -> Result<i64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        sqlx::query_scalar("SELECT COUNT(*) FROM entity_versions WHERE entity_id = ?")
+            .bind(entity_id)
+            .fetch_one(executor)
+            .await
+    }
+
+    pub async fn find_version<'e, E>(
+        executor: E,
+        entity_id: &uuid::Uuid,
+        version: i64,
+    ) -> Result<Option<db_entity_versions::EntityVersionsRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM entity_versions WHERE entity_id = ? AND version = ?",
+            db_entity_versions::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_entity_versions::EntityVersionsRow>(&sql)
+            .bind(entity_id)
+            .bind(version)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// Version number the next snapshot of `entity_id` should get, starting at 1.
+    pub async fn next_version<'e, E>(
+        executor: E,
+        entity_id: &uuid::Uuid,
+    ) -> Result<i64, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let current: Option<i64> =
+            sqlx::query_scalar("SELECT MAX(version) FROM entity_versions WHERE entity_id = ?")
+                .bind(entity_id)
+                .fetch_one(executor)
+                .await?;
+        Ok(current.unwrap_or(0) + 1)
+    }
+}
+
+pub mod system_members {
+    use super::*;
+    use crate::generated::system_members as db_system_members;
+
+    /// Condition holding when the system id in `column` is the user bound to it or
+    /// a system that user is a member of. Bind the user id twice.
+    pub fn visible_condition(column: &str) -> String {
+        format!(
+            "({column} = ? OR {column} IN (SELECT system_id FROM system_members WHERE user_id = ?))"
+        )
+    }
+
+    /// The role of `user_id` in `system_id`, if it is a member.
+    pub async fn role_of<'e, E>(
+        executor: E,
+        system_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> Result<Option<String>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        sqlx::query_scalar("SELECT role FROM system_members WHERE system_id = ? AND user_id = ?")
+            .bind(system_id)
+            .bind(user_id)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// The systems `user_id` is a member of.
+    pub async fn systems_of<'e, E>(
+        executor: E,
+        user_id: &uuid::Uuid,
+    ) -> Result<Vec<uuid::Uuid>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        sqlx::query_scalar("SELECT system_id FROM system_members WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// The members of `system_id`, oldest first.
+    pub async fn list_for_system<'e, E>(
+        executor: E,
+        system_id: &uuid::Uuid,
+    ) -> Result<Vec<db_system_members::SystemMembersRow>, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let sql = format!(
+            "SELECT {} FROM system_members WHERE system_id = ? ORDER BY created_at, user_id",
+            db_system_members::COLUMN_LIST
+        );
+        sqlx::query_as::<_, db_system_members::SystemMembersRow>(&sql)
+            .bind(system_id)
+            .fetch_all(executor)
+            .await
+    }
+
+    /// Change the role of an existing member. Returns whether it was a member.
+    pub async fn set_role<'e, E>(
+        executor: E,
+        system_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+        role: &str,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let result =
+            sqlx::query("UPDATE system_members SET role = ? WHERE system_id = ? AND user_id = ?")
+                .bind(role)
+                .bind(system_id)
+                .bind(user_id)
+                .execute(executor)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Remove `user_id` from `system_id`. Returns whether it was a member.
+    pub async fn remove<'e, E>(
+        executor: E,
+        system_id: &uuid::Uuid,
+        user_id: &uuid::Uuid,
+    ) -> Result<bool, sqlx::Error>
+    where
+        E: Executor<'e, Database = DbBackend>,
+    {
+        let result = sqlx::query("DELETE FROM system_members WHERE system_id = ? AND user_id = ?")
+            .bind(system_id)
+            .bind(user_id)
+            .execute(executor)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
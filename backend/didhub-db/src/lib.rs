@@ -23,5 +23,12 @@ pub type DbBackend = sqlx::Sqlite;
 
 pub mod custom;
 pub mod generated;
+pub mod notify;
+pub mod seed;
+pub mod transaction;
 
-pub use didhub_db_connection::{create_pool, DbConnectionConfig, DbConnectionError, DbPool};
+pub use didhub_db_connection::{
+    create_pool, create_replica_pools, DbConnectionConfig, DbConnectionError, DbPool,
+    DbPoolConnection, ReadReplicas,
+};
+pub use transaction::{transaction, RetryableError, Tx, TxFuture};
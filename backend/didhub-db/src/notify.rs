@@ -0,0 +1,61 @@
+//! Notifications between instances sharing the database.
+//!
+//! Only Postgres delivers them (`LISTEN`/`NOTIFY`); with the other backends
+//! [`SUPPORTED`] is false, [`notify`] does nothing and [`listen`] returns `None`.
+
+use crate::DbPool;
+
+/// Whether this build's database delivers notifications.
+pub const SUPPORTED: bool = cfg!(feature = "postgres");
+
+/// Send `payload` to every connection listening on `channel`. Postgres limits
+/// payloads to 8000 bytes.
+#[cfg(feature = "postgres")]
+pub async fn notify(pool: &DbPool, channel: &str, payload: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(channel)
+        .bind(payload)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[cfg(not(feature = "postgres"))]
+pub async fn notify(_pool: &DbPool, _channel: &str, _payload: &str) -> Result<(), sqlx::Error> {
+    Ok(())
+}
+
+/// A connection listening on one channel.
+pub struct Listener {
+    #[cfg(feature = "postgres")]
+    inner: sqlx::postgres::PgListener,
+    #[cfg(not(feature = "postgres"))]
+    never: std::convert::Infallible,
+}
+
+impl Listener {
+    /// Wait for the next payload. The connection is re-established when it drops,
+    /// and notifications sent meanwhile are lost.
+    #[cfg(feature = "postgres")]
+    pub async fn recv(&mut self) -> Result<String, sqlx::Error> {
+        Ok(self.inner.recv().await?.payload().to_string())
+    }
+
+    #[cfg(not(feature = "postgres"))]
+    pub async fn recv(&mut self) -> Result<String, sqlx::Error> {
+        match self.never {}
+    }
+}
+
+/// Listen on `channel` with a dedicated connection from `pool`'s settings.
+#[cfg(feature = "postgres")]
+pub async fn listen(pool: &DbPool, channel: &str) -> Result<Option<Listener>, sqlx::Error> {
+    let mut inner = sqlx::postgres::PgListener::connect_with(pool).await?;
+    inner.listen(channel).await?;
+    Ok(Some(Listener { inner }))
+}
+
+#[cfg(not(feature = "postgres"))]
+pub async fn listen(_pool: &DbPool, _channel: &str) -> Result<Option<Listener>, sqlx::Error> {
+    Ok(None)
+}
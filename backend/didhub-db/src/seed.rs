@@ -0,0 +1,459 @@
+//! Seed data for development and demo instances.
+//!
+//! Every seeded row has a fixed id, so seeding is idempotent: rows that already
+//! exist are left untouched, including any edits made to them since.
+
+use std::fmt;
+use std::str::FromStr;
+
+use uuid::Uuid;
+
+use crate::generated::{
+    affiliation_members, affiliations, alters, relationships, stored_files, subsystem_members,
+    subsystems, uploads, users,
+};
+use crate::{transaction, DbPool, Tx};
+
+/// Timestamp recorded on seeded rows, fixed so reseeding never changes them.
+const SEEDED_AT: &str = "2024-01-01T00:00:00+00:00";
+
+/// A named set of seed data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dataset {
+    /// Two systems with alters, groups, a subsystem, relationships and avatar
+    /// uploads, plus an admin and a singlet user.
+    Demo,
+}
+
+impl FromStr for Dataset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "demo" => Ok(Dataset::Demo),
+            other => Err(format!("unknown seed dataset '{other}' (expected: demo)")),
+        }
+    }
+}
+
+impl fmt::Display for Dataset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dataset::Demo => f.write_str("demo"),
+        }
+    }
+}
+
+/// File content referenced by seeded uploads. The database only records it; the
+/// caller writes `content` to the uploads directory under `id`.
+#[derive(Debug, Clone, Copy)]
+pub struct SeedFile {
+    pub id: Uuid,
+    pub mime_type: &'static str,
+    /// Lowercase hex SHA-256 of `content`, as stored in `stored_files.file_hash`.
+    pub sha256: &'static str,
+    pub content: &'static [u8],
+}
+
+/// Rows inserted by a seeding run and rows that were already present.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeedReport {
+    pub inserted: u64,
+    pub existing: u64,
+}
+
+impl SeedReport {
+    fn record(&mut self, inserted: bool) {
+        if inserted {
+            self.inserted += 1;
+        } else {
+            self.existing += 1;
+        }
+    }
+}
+
+/// Usernames of the seeded accounts; they all share the password passed to [`seed`].
+pub const DEMO_USERNAMES: [&str; 4] = ["demo-admin", "demo-willow", "demo-ember", "demo-sam"];
+
+const fn demo_id(kind: u128, n: u128) -> Uuid {
+    Uuid::from_u128(0xd1d0_0000_0000_4000_8000_0000_0000_0000 | (kind << 16) | n)
+}
+
+const ADMIN: Uuid = demo_id(1, 1);
+const WILLOW_SYSTEM: Uuid = demo_id(1, 2);
+const EMBER_SYSTEM: Uuid = demo_id(1, 3);
+const SAM: Uuid = demo_id(1, 4);
+
+const WILLOW: Uuid = demo_id(2, 1);
+const JUNIPER: Uuid = demo_id(2, 2);
+const MOSS: Uuid = demo_id(2, 3);
+const ASH: Uuid = demo_id(2, 4);
+const EMBER: Uuid = demo_id(2, 5);
+const FLINT: Uuid = demo_id(2, 6);
+
+const GARDEN_KEEPERS: Uuid = demo_id(3, 1);
+const NIGHT_WATCH: Uuid = demo_id(3, 2);
+const LITTLE_ONES: Uuid = demo_id(4, 1);
+
+const WILLOW_AVATAR: SeedFile = SeedFile {
+    id: demo_id(5, 1),
+    mime_type: "image/svg+xml",
+    sha256: "ab485d76601e549a19d385dd519c16a25ce31cb420761ad73865cadb1d46e730",
+    content: b"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 64 64\"><rect width=\"64\" height=\"64\" fill=\"#6a9f5b\"/><circle cx=\"32\" cy=\"26\" r=\"12\" fill=\"#f2e8cf\"/><rect x=\"14\" y=\"42\" width=\"36\" height=\"22\" rx=\"11\" fill=\"#f2e8cf\"/></svg>\n",
+};
+const EMBER_AVATAR: SeedFile = SeedFile {
+    id: demo_id(5, 2),
+    mime_type: "image/svg+xml",
+    sha256: "0e38103061d3fa4d8fbff5abaf43072acac7e2069f3af7529685daf1a8f91868",
+    content: b"<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 64 64\"><rect width=\"64\" height=\"64\" fill=\"#c8553d\"/><circle cx=\"32\" cy=\"26\" r=\"12\" fill=\"#ffd5c2\"/><rect x=\"14\" y=\"42\" width=\"36\" height=\"22\" rx=\"11\" fill=\"#ffd5c2\"/></svg>\n",
+};
+
+const DEMO_FILES: [SeedFile; 2] = [WILLOW_AVATAR, EMBER_AVATAR];
+
+/// Files the dataset's uploads point at.
+pub fn files(dataset: Dataset) -> &'static [SeedFile] {
+    match dataset {
+        Dataset::Demo => &DEMO_FILES,
+    }
+}
+
+/// Insert `dataset` into the database in one transaction, skipping rows that
+/// already exist. Seeded users get `password_hash`.
+pub async fn seed(
+    pool: &DbPool,
+    dataset: Dataset,
+    password_hash: &str,
+) -> Result<SeedReport, sqlx::Error> {
+    match dataset {
+        Dataset::Demo => {
+            transaction(pool, |tx| {
+                Box::pin(seed_demo(tx, password_hash.to_string()))
+            })
+            .await
+        }
+    }
+}
+
+async fn seed_demo(tx: &mut Tx, password_hash: String) -> Result<SeedReport, sqlx::Error> {
+    let mut report = SeedReport::default();
+
+    let accounts = [
+        (
+            ADMIN,
+            DEMO_USERNAMES[0],
+            "Demo Admin",
+            r#"["admin","user"]"#,
+        ),
+        (
+            WILLOW_SYSTEM,
+            DEMO_USERNAMES[1],
+            "Willow System",
+            r#"["system","user"]"#,
+        ),
+        (
+            EMBER_SYSTEM,
+            DEMO_USERNAMES[2],
+            "Ember Collective",
+            r#"["system","user"]"#,
+        ),
+        (SAM, DEMO_USERNAMES[3], "Sam", r#"["user"]"#),
+    ];
+    for (id, username, display_name, roles) in accounts {
+        let exists = users::find_by_primary_key(&mut **tx, &id).await?.is_some();
+        if !exists {
+            let avatar = match id {
+                WILLOW_SYSTEM => Some(WILLOW_AVATAR.id.to_string()),
+                EMBER_SYSTEM => Some(EMBER_AVATAR.id.to_string()),
+                _ => None,
+            };
+            let row = users::UsersRow {
+                id,
+                username: username.to_string(),
+                about_me: None,
+                password_hash: password_hash.clone(),
+                avatar,
+                must_change_password: 0,
+                last_login_at: None,
+                display_name: Some(display_name.to_string()),
+                created_at: SEEDED_AT.to_string(),
+                updated_at: SEEDED_AT.to_string(),
+                roles: roles.to_string(),
+                settings: "{}".to_string(),
+            };
+            users::insert_user(&mut **tx, &row).await?;
+        }
+        report.record(!exists);
+    }
+
+    for file in DEMO_FILES {
+        let exists = stored_files::find_by_primary_key(&mut **tx, &file.id)
+            .await?
+            .is_some();
+        if !exists {
+            let row = stored_files::StoredFilesRow {
+                id: file.id,
+                file_hash: file.sha256.to_string(),
+                mime_type: Some(file.mime_type.to_string()),
+                size: Some(file.content.len() as f64),
+                created_at: SEEDED_AT.to_string(),
+            };
+            stored_files::insert_stored_file(&mut **tx, &row).await?;
+        }
+        report.record(!exists);
+    }
+    let demo_uploads = [
+        (demo_id(6, 1), WILLOW_AVATAR.id, "willow.svg", WILLOW_SYSTEM),
+        (demo_id(6, 2), EMBER_AVATAR.id, "ember.svg", EMBER_SYSTEM),
+    ];
+    for (id, stored_file_id, stored_name, uploaded_by) in demo_uploads {
+        let exists = uploads::find_by_primary_key(&mut **tx, &id)
+            .await?
+            .is_some();
+        if !exists {
+            let row = uploads::UploadsRow {
+                id,
+                stored_file_id,
+                stored_name: stored_name.to_string(),
+                uploaded_by,
+                created_at: SEEDED_AT.to_string(),
+            };
+            uploads::insert_upload(&mut **tx, &row).await?;
+        }
+        report.record(!exists);
+    }
+
+    let demo_alters = [
+        demo_alter(
+            WILLOW,
+            WILLOW_SYSTEM,
+            "Willow",
+            "Host who keeps the garden and the calendar in order.",
+            "she/her",
+        )
+        .host()
+        .image(WILLOW_AVATAR.id)
+        .interests(r#"["gardening","tea","journaling"]"#),
+        demo_alter(
+            JUNIPER,
+            WILLOW_SYSTEM,
+            "Juniper",
+            "Protector; steps in when things get loud.",
+            "they/them",
+        )
+        .interests(r#"["hiking","martial arts"]"#),
+        demo_alter(
+            MOSS,
+            WILLOW_SYSTEM,
+            "Moss",
+            "A little who loves drawing frogs.",
+            "he/him",
+        )
+        .age("7")
+        .interests(r#"["drawing","frogs"]"#),
+        demo_alter(
+            ASH,
+            WILLOW_SYSTEM,
+            "Ash",
+            "Has not fronted since last winter.",
+            "xe/xem",
+        )
+        .dormant(),
+        demo_alter(
+            EMBER,
+            EMBER_SYSTEM,
+            "Ember",
+            "Host of the collective and its loudest cook.",
+            "she/they",
+        )
+        .host()
+        .image(EMBER_AVATAR.id)
+        .interests(r#"["cooking","music"]"#),
+        demo_alter(
+            FLINT,
+            EMBER_SYSTEM,
+            "Flint",
+            "Night-shift caretaker.",
+            "he/they",
+        )
+        .interests(r#"["astronomy"]"#),
+    ];
+    for alter in demo_alters {
+        let exists = alters::find_by_primary_key(&mut **tx, &alter.0.id)
+            .await?
+            .is_some();
+        if !exists {
+            alters::insert_alter(&mut **tx, &alter.0).await?;
+        }
+        report.record(!exists);
+    }
+
+    let groups = [
+        (
+            GARDEN_KEEPERS,
+            "Garden Keepers",
+            "Alters who look after the shared garden.",
+            WILLOW_SYSTEM,
+        ),
+        (
+            NIGHT_WATCH,
+            "Night Watch",
+            "Whoever is awake after midnight.",
+            EMBER_SYSTEM,
+        ),
+    ];
+    for (id, name, description, owner) in groups {
+        let exists = affiliations::find_by_primary_key(&mut **tx, &id)
+            .await?
+            .is_some();
+        if !exists {
+            let row = affiliations::AffiliationsRow {
+                id,
+                name: name.to_string(),
+                description: Some(description.to_string()),
+                sigil: None,
+                owner_user_id: Some(owner),
+                created_at: SEEDED_AT.to_string(),
+            };
+            affiliations::insert_affiliation(&mut **tx, &row).await?;
+        }
+        report.record(!exists);
+    }
+    let group_members = [
+        (GARDEN_KEEPERS, WILLOW, true),
+        (GARDEN_KEEPERS, JUNIPER, false),
+        (GARDEN_KEEPERS, MOSS, false),
+        (NIGHT_WATCH, FLINT, true),
+    ];
+    for (affiliation_id, alter_id, is_leader) in group_members {
+        let exists = affiliation_members::find_by_affiliation_id(&mut **tx, &affiliation_id)
+            .await?
+            .iter()
+            .any(|m| m.alter_id == alter_id);
+        if !exists {
+            let row = affiliation_members::AffiliationMembersRow {
+                affiliation_id,
+                alter_id,
+                is_leader: is_leader as i32,
+                added_at: SEEDED_AT.to_string(),
+            };
+            affiliation_members::insert_affiliation_member(&mut **tx, &row).await?;
+        }
+        report.record(!exists);
+    }
+
+    let exists = subsystems::find_by_primary_key(&mut **tx, &LITTLE_ONES)
+        .await?
+        .is_some();
+    if !exists {
+        let row = subsystems::SubsystemsRow {
+            id: LITTLE_ONES,
+            name: "Little Ones".to_string(),
+            owner_user_id: Some(WILLOW_SYSTEM),
+            created_at: SEEDED_AT.to_string(),
+        };
+        subsystems::insert_subsystem(&mut **tx, &row).await?;
+    }
+    report.record(!exists);
+    let exists = subsystem_members::find_by_subsystem_id(&mut **tx, &LITTLE_ONES)
+        .await?
+        .iter()
+        .any(|m| m.alter_id == MOSS);
+    if !exists {
+        let row = subsystem_members::SubsystemMembersRow {
+            subsystem_id: LITTLE_ONES,
+            alter_id: MOSS,
+            is_host: 0,
+            added_at: SEEDED_AT.to_string(),
+        };
+        subsystem_members::insert_subsystem_member(&mut **tx, &row).await?;
+    }
+    report.record(!exists);
+
+    let demo_relationships = [
+        (demo_id(7, 1), "parent", WILLOW, MOSS, WILLOW_SYSTEM),
+        (demo_id(7, 2), "spouse", JUNIPER, EMBER, WILLOW_SYSTEM),
+    ];
+    for (id, kind, side_a, side_b, created_by) in demo_relationships {
+        let exists = relationships::find_by_primary_key(&mut **tx, &id)
+            .await?
+            .is_some();
+        if !exists {
+            let row = relationships::RelationshipsRow {
+                id,
+                r#type: kind.to_string(),
+                side_a_user_id: None,
+                side_a_alter_id: Some(side_a),
+                side_b_user_id: None,
+                side_b_alter_id: Some(side_b),
+                past_life: 0,
+                created_by: Some(created_by),
+                created_at: SEEDED_AT.to_string(),
+            };
+            relationships::insert_relationship(&mut **tx, &row).await?;
+        }
+        report.record(!exists);
+    }
+
+    Ok(report)
+}
+
+/// Builder for the few alter fields the demo data varies.
+struct DemoAlter(alters::AltersRow);
+
+fn demo_alter(id: Uuid, system: Uuid, name: &str, description: &str, pronouns: &str) -> DemoAlter {
+    DemoAlter(alters::AltersRow {
+        id,
+        user_id: system,
+        name: name.to_string(),
+        surname: None,
+        description: Some(description.to_string()),
+        age: None,
+        gender: None,
+        pronouns: Some(pronouns.to_string()),
+        birthday: None,
+        sexuality: None,
+        species: None,
+        alter_type: None,
+        job: None,
+        weapon: None,
+        triggers: "[]".to_string(),
+        metadata: "{}".to_string(),
+        soul_songs: "[]".to_string(),
+        interests: "[]".to_string(),
+        notes: None,
+        images: "[]".to_string(),
+        system_roles: "[]".to_string(),
+        is_system_host: 0,
+        is_dormant: 0,
+        is_merged: 0,
+        owner_user_id: system,
+        created_at: SEEDED_AT.to_string(),
+    })
+}
+
+impl DemoAlter {
+    fn host(mut self) -> Self {
+        self.0.is_system_host = 1;
+        self
+    }
+
+    fn dormant(mut self) -> Self {
+        self.0.is_dormant = 1;
+        self
+    }
+
+    fn age(mut self, age: &str) -> Self {
+        self.0.age = Some(age.to_string());
+        self
+    }
+
+    fn image(mut self, stored_file_id: Uuid) -> Self {
+        self.0.images = format!(r#"["{stored_file_id}"]"#);
+        self
+    }
+
+    fn interests(mut self, interests: &str) -> Self {
+        self.0.interests = interests.to_string();
+        self
+    }
+}
@@ -0,0 +1,81 @@
+//! Transactions with retry on serialization failures.
+
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::{DbBackend, DbPool};
+
+/// Attempts made before a serialization failure is returned to the caller.
+pub const MAX_TRANSACTION_ATTEMPTS: u32 = 3;
+
+/// Future returned by the body of [`transaction`], borrowing the open transaction.
+pub type TxFuture<'c, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'c>>;
+
+/// Open transaction handed to the body of [`transaction`].
+pub type Tx = sqlx::Transaction<'static, DbBackend>;
+
+/// Errors that can tell whether retrying the whole transaction may succeed.
+pub trait RetryableError: From<sqlx::Error> {
+    fn is_retryable(&self) -> bool;
+}
+
+impl RetryableError for sqlx::Error {
+    fn is_retryable(&self) -> bool {
+        is_serialization_failure(self)
+    }
+}
+
+/// Whether the database aborted the statement because of a conflict with a concurrent
+/// transaction (serialization failure, deadlock, or a busy SQLite database).
+pub fn is_serialization_failure(err: &sqlx::Error) -> bool {
+    let Some(code) = err.as_database_error().and_then(|e| e.code()) else {
+        return false;
+    };
+    #[cfg(feature = "postgres")]
+    let retryable = matches!(code.as_ref(), "40001" | "40P01");
+    // MySQL reports deadlocks (1213) with SQLSTATE 40001
+    #[cfg(feature = "mysql")]
+    let retryable = code.as_ref() == "40001";
+    // SQLITE_BUSY, SQLITE_LOCKED and their shared-cache and snapshot variants
+    #[cfg(feature = "sqlite")]
+    let retryable = matches!(code.as_ref(), "5" | "6" | "262" | "517");
+    retryable
+}
+
+/// Run `body` in a transaction and commit it. Returning an error rolls everything
+/// back, so a multi-step write is applied completely or not at all. When the body or
+/// the commit fails with a retryable error, the whole body runs again in a fresh
+/// transaction, up to [`MAX_TRANSACTION_ATTEMPTS`] times, so it must not have side
+/// effects outside the database. The returned future may only borrow the transaction;
+/// clone other data into it.
+///
+/// ```ignore
+/// let count = transaction(&pool, |tx| {
+///     Box::pin(async move {
+///         sqlx::query("DELETE FROM subsystem_members WHERE alter_id = ?")
+///             .bind(alter_id)
+///             .execute(&mut **tx)
+///             .await
+///     })
+/// })
+/// .await?;
+/// ```
+pub async fn transaction<T, E, F>(pool: &DbPool, mut body: F) -> Result<T, E>
+where
+    F: for<'c> FnMut(&'c mut Tx) -> TxFuture<'c, T, E>,
+    E: RetryableError,
+{
+    let mut attempt = 1;
+    loop {
+        let mut tx = pool.begin().await?;
+        let result = match body(&mut tx).await {
+            Ok(value) => tx.commit().await.map(|()| value).map_err(E::from),
+            // Dropping the transaction rolls it back
+            Err(err) => Err(err),
+        };
+        match result {
+            Err(err) if err.is_retryable() && attempt < MAX_TRANSACTION_ATTEMPTS => attempt += 1,
+            result => return result,
+        }
+    }
+}
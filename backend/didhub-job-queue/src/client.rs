@@ -8,8 +8,8 @@ use tokio::sync::RwLock;
 use uuid::Uuid;
 
 use crate::error::JobQueueError;
-use crate::executor::JobExecutor;
-use crate::types::{EnqueueResult, JobRequest, JobRun, JobStatus};
+use crate::executor::{JobExecutor, ProgressReporter};
+use crate::types::{EnqueueResult, JobProgress, JobRequest, JobRun, JobStatus};
 
 /// Maximum number of job runs to keep in memory.
 const MAX_JOB_RUNS: usize = 1000;
@@ -123,6 +123,54 @@ impl JobQueueClient {
         Ok(EnqueueResult { job_id })
     }
 
+    /// Enqueue a job and run it in the background with its registered executor.
+    ///
+    /// The run can be followed with [`get_run`](Self::get_run) using the returned
+    /// id; it fails if no executor is registered for the job type. Must be called
+    /// from within a Tokio runtime.
+    pub async fn spawn_job(&self, request: JobRequest) -> Result<EnqueueResult, JobQueueError> {
+        let payload = request.payload.clone();
+        let job_type = request.job_type.clone();
+        let result = self.enqueue(request).await?;
+        let job_id = result.job_id;
+
+        let client = self.clone();
+        tokio::spawn(async move {
+            let executor = {
+                let executors = client.executors.read().await;
+                executors.get(&job_type).cloned()
+            };
+            let Some(executor) = executor else {
+                client
+                    .update_run_status(
+                        job_id,
+                        JobStatus::Failed,
+                        Some(format!("no executor registered for {job_type}")),
+                    )
+                    .await;
+                return;
+            };
+            client
+                .update_run_status(job_id, JobStatus::Running, None)
+                .await;
+            let progress = ProgressReporter::new(client.clone(), job_id);
+            match executor.execute_with_progress(payload, progress).await {
+                Ok(()) => {
+                    client
+                        .update_run_status(job_id, JobStatus::Completed, None)
+                        .await
+                }
+                Err(e) => {
+                    client
+                        .update_run_status(job_id, JobStatus::Failed, Some(e.to_string()))
+                        .await
+                }
+            };
+        });
+
+        Ok(result)
+    }
+
     /// Run a named job immediately and track it.
     ///
     /// If an executor is registered for this job type, it will be used.
@@ -135,6 +183,7 @@ impl JobQueueClient {
         let job_name = job_name.into();
         let mut run = JobRun::new(&job_name, payload.clone());
         run.start();
+        let run_id = run.id;
 
         // Try to find an executor
         let executor = {
@@ -142,23 +191,30 @@ impl JobQueueClient {
             executors.get(&job_name).cloned()
         };
 
+        // Store the run up front so progress reports have somewhere to go
+        self.state.write().await.insert(run);
+
         // Execute if we have an executor, otherwise just complete (stub)
-        if let Some(executor) = executor {
+        let run = if let Some(executor) = executor {
             let exec_payload = payload.unwrap_or(serde_json::Value::Null);
-            match executor.execute(exec_payload).await {
-                Ok(()) => run.complete(),
-                Err(e) => run.fail(e.to_string()),
+            let progress = ProgressReporter::new(self.clone(), run_id);
+            match executor.execute_with_progress(exec_payload, progress).await {
+                Ok(()) => {
+                    self.update_run_status(run_id, JobStatus::Completed, None)
+                        .await
+                }
+                Err(e) => {
+                    self.update_run_status(run_id, JobStatus::Failed, Some(e.to_string()))
+                        .await
+                }
             }
         } else {
             // Stub: complete immediately
-            run.complete();
-        }
-
-        // Store the run
-        let mut state = self.state.write().await;
-        state.insert(run.clone());
+            self.update_run_status(run_id, JobStatus::Completed, None)
+                .await
+        };
 
-        Ok(run)
+        run.ok_or(JobQueueError::NotFound(run_id))
     }
 
     /// List all job runs, optionally filtered by job name.
@@ -201,6 +257,19 @@ impl JobQueueClient {
         state.get(&id).cloned()
     }
 
+    /// Record the progress of a running job.
+    pub async fn update_run_progress(
+        &self,
+        id: Uuid,
+        percent: u8,
+        message: Option<String>,
+    ) -> Option<JobRun> {
+        let mut state = self.state.write().await;
+        let run = state.get_mut(&id)?;
+        run.progress = Some(JobProgress { percent, message });
+        Some(run.clone())
+    }
+
     /// Update the status of a job run.
     pub async fn update_run_status(
         &self,
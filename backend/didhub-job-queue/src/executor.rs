@@ -2,9 +2,39 @@
 
 use async_trait::async_trait;
 use serde_json::Value;
+use uuid::Uuid;
 
+use crate::client::JobQueueClient;
 use crate::error::JobQueueError;
 
+/// Handle through which a running job reports its progress on its [`JobRun`](crate::JobRun).
+#[derive(Debug, Clone, Default)]
+pub struct ProgressReporter {
+    run: Option<(JobQueueClient, Uuid)>,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new(client: JobQueueClient, run_id: Uuid) -> Self {
+        Self {
+            run: Some((client, run_id)),
+        }
+    }
+
+    /// A reporter that discards progress, for jobs executed outside the queue.
+    pub fn detached() -> Self {
+        Self::default()
+    }
+
+    /// Record `percent` (clamped to 100) and what the job is doing.
+    pub async fn report(&self, percent: u8, message: impl Into<String>) {
+        if let Some((client, run_id)) = &self.run {
+            client
+                .update_run_progress(*run_id, percent.min(100), Some(message.into()))
+                .await;
+        }
+    }
+}
+
 /// Trait for implementing job executors.
 ///
 /// Job executors handle the actual execution of jobs. Each job type should have
@@ -18,6 +48,19 @@ pub trait JobExecutor: Send + Sync {
     ///
     /// Returns `Ok(())` on success, or an error describing the failure.
     async fn execute(&self, payload: Value) -> Result<(), JobQueueError>;
+
+    /// Execute the job, reporting progress through `progress`.
+    ///
+    /// The queue always calls this method. The default ignores `progress` and
+    /// calls [`execute`](Self::execute); long-running executors override it.
+    async fn execute_with_progress(
+        &self,
+        payload: Value,
+        progress: ProgressReporter,
+    ) -> Result<(), JobQueueError> {
+        let _ = progress;
+        self.execute(payload).await
+    }
 }
 
 /// A no-op executor that immediately completes jobs.
@@ -54,8 +54,8 @@ mod types;
 
 pub use client::JobQueueClient;
 pub use error::JobQueueError;
-pub use executor::{JobExecutor, NoOpExecutor};
-pub use types::{EnqueueResult, JobRequest, JobRun, JobStatus};
+pub use executor::{JobExecutor, NoOpExecutor, ProgressReporter};
+pub use types::{EnqueueResult, JobProgress, JobRequest, JobRun, JobStatus};
 
 // Re-export async_trait for convenience when implementing JobExecutor
 pub use async_trait::async_trait;
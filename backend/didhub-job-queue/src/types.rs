@@ -56,6 +56,15 @@ impl std::fmt::Display for JobStatus {
     }
 }
 
+/// Progress reported by a running job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobProgress {
+    /// 0 to 100.
+    pub percent: u8,
+    /// What the job is doing, e.g. `copying database`.
+    pub message: Option<String>,
+}
+
 /// A record of a job execution.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobRun {
@@ -66,6 +75,9 @@ pub struct JobRun {
     pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
     pub error_message: Option<String>,
     pub payload: Option<Value>,
+    /// Latest progress reported by the executor, if it reports any.
+    #[serde(default)]
+    pub progress: Option<JobProgress>,
 }
 
 impl JobRun {
@@ -80,6 +92,7 @@ impl JobRun {
             finished_at: None,
             error_message: None,
             payload,
+            progress: None,
         }
     }
 
@@ -94,6 +107,7 @@ impl JobRun {
             finished_at: None,
             error_message: None,
             payload,
+            progress: None,
         }
     }
 
@@ -1,33 +1,375 @@
 //! Backup job implementations.
+//!
+//! `backup.create` copies the database into the backup directory: SQLite through
+//! the online backup API, PostgreSQL and MySQL by running `pg_dump` and
+//! `mysqldump`. `backup.restore` validates a backup file, saves a safety copy of
+//! the current database and restores the backup, putting the safety copy back if
+//! the restore fails. Both report their progress on the job run.
 
-use didhub_job_queue::{async_trait, JobExecutor, JobQueueError};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use chrono::Utc;
+use didhub_job_queue::{async_trait, JobExecutor, JobQueueError, ProgressReporter};
+use percent_encoding::percent_decode_str;
+use rusqlite::backup::{Backup, StepResult};
+use rusqlite::{Connection, OpenFlags};
 use serde::Deserialize;
 use serde_json::Value;
+use tokio::process::Command;
+use tokio::sync::mpsc;
 use tracing::{info, warn};
+use uuid::Uuid;
 
+use crate::error::JobError;
 use crate::job_types;
 
+/// Pages copied per step of an SQLite backup; progress is reported between steps.
+const SQLITE_PAGES_PER_STEP: i32 = 256;
+
+/// Where backups are written to and restored from.
+#[derive(Debug, Clone)]
+pub struct BackupSettings {
+    /// URL of the database to back up and restore into.
+    pub database_url: String,
+    /// Directory for new backups and pre-restore safety copies.
+    pub backup_dir: PathBuf,
+    /// Directory of uploaded files, where backups to restore are read from.
+    pub uploads_dir: PathBuf,
+}
+
+/// The database behind a connection URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Database {
+    Sqlite(PathBuf),
+    Postgres(url::Url),
+    MySql(url::Url),
+}
+
+impl Database {
+    fn from_url(url: &str) -> Result<Self, JobError> {
+        let lower = url.to_lowercase();
+        let parse = |url: &str| {
+            url::Url::parse(url)
+                .map_err(|e| JobError::ConfigError(format!("invalid database url: {e}")))
+        };
+        if lower.starts_with("postgres") {
+            return parse(url).map(Database::Postgres);
+        }
+        if lower.starts_with("mysql") || lower.starts_with("mariadb") {
+            return parse(url).map(Database::MySql);
+        }
+        let path = url
+            .strip_prefix("sqlite://")
+            .or_else(|| url.strip_prefix("sqlite:"))
+            .unwrap_or(url);
+        let path = path.split('?').next().unwrap_or_default();
+        if path.is_empty() || path == ":memory:" {
+            return Err(JobError::ConfigError(
+                "in-memory sqlite databases cannot be backed up".to_string(),
+            ));
+        }
+        Ok(Database::Sqlite(PathBuf::from(path)))
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Database::Sqlite(_) => "sqlite3",
+            Database::Postgres(_) | Database::MySql(_) => "sql",
+        }
+    }
+}
+
+/// Copy the SQLite database `from` into `to` with the online backup API, sending
+/// the share copied in percent after each step.
+fn sqlite_copy(
+    from: &Path,
+    to: &Path,
+    progress: &mpsc::UnboundedSender<u8>,
+) -> rusqlite::Result<()> {
+    let source = Connection::open_with_flags(from, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let mut destination = Connection::open(to)?;
+    destination.busy_timeout(Duration::from_secs(5))?;
+    let backup = Backup::new(&source, &mut destination)?;
+    loop {
+        match backup.step(SQLITE_PAGES_PER_STEP)? {
+            StepResult::Done => {
+                let _ = progress.send(100);
+                return Ok(());
+            }
+            StepResult::More => {}
+            _ => std::thread::sleep(Duration::from_millis(100)),
+        }
+        let pages = backup.progress();
+        if pages.pagecount > 0 {
+            let copied = 100 * (pages.pagecount - pages.remaining) / pages.pagecount;
+            let _ = progress.send(copied as u8);
+        }
+    }
+}
+
+/// Run [`sqlite_copy`] off the runtime, reporting its progress scaled into
+/// `start..=end`.
+async fn copy_sqlite(
+    from: PathBuf,
+    to: PathBuf,
+    progress: &ProgressReporter,
+    (start, end): (u8, u8),
+    message: &str,
+) -> Result<(), String> {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    let task = tokio::task::spawn_blocking(move || sqlite_copy(&from, &to, &sender));
+    while let Some(percent) = receiver.recv().await {
+        let scaled = u32::from(start) + u32::from(end - start) * u32::from(percent) / 100;
+        progress.report(scaled as u8, message).await;
+    }
+    task.await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+fn decode(s: &str) -> String {
+    percent_decode_str(s).decode_utf8_lossy().into_owned()
+}
+
+/// Command line of a PostgreSQL client tool connecting as described by `url`.
+/// The password is passed in the environment to keep it out of the process list.
+fn postgres_command(program: &str, url: &url::Url) -> Command {
+    let mut command = Command::new(program);
+    let mut url = url.clone();
+    if let Some(password) = url.password() {
+        command.env("PGPASSWORD", decode(password));
+        let _ = url.set_password(None);
+    }
+    command.arg(url.as_str());
+    command
+}
+
+/// Command line of a MySQL client tool connecting as described by `url`. The
+/// password is passed in the environment to keep it out of the process list.
+fn mysql_command(program: &str, url: &url::Url) -> Command {
+    let mut command = Command::new(program);
+    if let Some(host) = url.host_str() {
+        command.arg(format!("--host={host}"));
+    }
+    if let Some(port) = url.port() {
+        command.arg(format!("--port={port}"));
+    }
+    if !url.username().is_empty() {
+        command.arg(format!("--user={}", decode(url.username())));
+    }
+    if let Some(password) = url.password() {
+        command.env("MYSQL_PWD", decode(password));
+    }
+    command
+}
+
+fn mysql_database(url: &url::Url) -> String {
+    url.path().trim_start_matches('/').to_string()
+}
+
+/// Run a database tool, failing with its error output when it does not succeed.
+async fn run_tool(mut command: Command, stdin: Option<&Path>) -> Result<(), String> {
+    let program = command
+        .as_std()
+        .get_program()
+        .to_string_lossy()
+        .into_owned();
+    if let Some(path) = stdin {
+        let file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+        command.stdin(file);
+    }
+    let output = command
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("failed to run {program}: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{program} exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Write a backup of `database` to `destination`, reporting progress within
+/// `range`. The file only appears once it is complete.
+async fn write_backup(
+    database: &Database,
+    destination: &Path,
+    progress: &ProgressReporter,
+    range: (u8, u8),
+    message: &str,
+) -> Result<(), String> {
+    let partial = destination.with_extension("part");
+    let result = match database {
+        Database::Sqlite(path) => {
+            copy_sqlite(path.clone(), partial.clone(), progress, range, message).await
+        }
+        Database::Postgres(url) => {
+            progress.report(range.0, message).await;
+            let mut command = postgres_command("pg_dump", url);
+            command
+                .arg("--clean")
+                .arg("--if-exists")
+                .arg("--no-owner")
+                .arg(format!("--file={}", partial.display()));
+            run_tool(command, None).await
+        }
+        Database::MySql(url) => {
+            progress.report(range.0, message).await;
+            let mut command = mysql_command("mysqldump", url);
+            command
+                .arg("--single-transaction")
+                .arg("--routines")
+                .arg(format!("--result-file={}", partial.display()))
+                .arg(mysql_database(url));
+            run_tool(command, None).await
+        }
+    };
+    if let Err(e) = result {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(e);
+    }
+    tokio::fs::rename(&partial, destination)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Replace the contents of `database` with the backup at `source`, reporting
+/// progress within `range`.
+async fn load_backup(
+    database: &Database,
+    source: &Path,
+    progress: &ProgressReporter,
+    range: (u8, u8),
+    message: &str,
+) -> Result<(), String> {
+    match database {
+        Database::Sqlite(path) => {
+            copy_sqlite(source.to_path_buf(), path.clone(), progress, range, message).await
+        }
+        Database::Postgres(url) => {
+            progress.report(range.0, message).await;
+            let mut command = postgres_command("psql", url);
+            command
+                .arg("--quiet")
+                .arg("--set=ON_ERROR_STOP=1")
+                .arg("--single-transaction")
+                .arg(format!("--file={}", source.display()));
+            run_tool(command, None).await
+        }
+        Database::MySql(url) => {
+            progress.report(range.0, message).await;
+            let mut command = mysql_command("mysql", url);
+            command.arg(mysql_database(url));
+            run_tool(command, Some(source)).await
+        }
+    }
+}
+
+/// Applied migration versions of an SQLite database.
+fn sqlite_migrations(conn: &Connection) -> rusqlite::Result<Vec<i64>> {
+    let mut statement =
+        conn.prepare("SELECT version FROM _sqlx_migrations WHERE success = 1 ORDER BY version")?;
+    let versions = statement.query_map([], |row| row.get(0))?;
+    versions.collect()
+}
+
+/// The first and last few KiB of a text dump.
+fn dump_ends(path: &Path) -> std::io::Result<(String, String)> {
+    const LEN: u64 = 4096;
+    let mut file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut head = Vec::new();
+    (&mut file).take(LEN).read_to_end(&mut head)?;
+    file.seek(SeekFrom::Start(size.saturating_sub(LEN)))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    Ok((
+        String::from_utf8_lossy(&head).into_owned(),
+        String::from_utf8_lossy(&tail).into_owned(),
+    ))
+}
+
+/// Check that `backup` is a complete backup that can be restored into
+/// `database`. SQLite backups must pass an integrity check and carry the same
+/// migrations as the database; dumps must be complete `pg_dump` or `mysqldump`
+/// output.
+fn validate_backup(database: &Database, backup: &Path) -> Result<(), String> {
+    match database {
+        Database::Sqlite(live) => {
+            let flags = OpenFlags::SQLITE_OPEN_READ_ONLY;
+            let conn = Connection::open_with_flags(backup, flags).map_err(|e| e.to_string())?;
+            let check: String = conn
+                .query_row("PRAGMA integrity_check", [], |row| row.get(0))
+                .map_err(|e| format!("not a usable SQLite database: {e}"))?;
+            if check != "ok" {
+                return Err(format!("integrity check failed: {check}"));
+            }
+            let backup_versions =
+                sqlite_migrations(&conn).map_err(|e| format!("not a DIDHub database: {e}"))?;
+            let live = Connection::open_with_flags(live, flags).map_err(|e| e.to_string())?;
+            let live_versions = sqlite_migrations(&live).unwrap_or_default();
+            if backup_versions != live_versions {
+                return Err(format!(
+                    "the backup is at migration {} but the database is at {}; restore it into a matching version",
+                    backup_versions.last().unwrap_or(&0),
+                    live_versions.last().unwrap_or(&0)
+                ));
+            }
+            Ok(())
+        }
+        Database::Postgres(_) => {
+            let (head, tail) = dump_ends(backup).map_err(|e| e.to_string())?;
+            if !head.contains("-- PostgreSQL database dump") {
+                return Err("not a pg_dump file".to_string());
+            }
+            if !tail.contains("-- PostgreSQL database dump complete") {
+                return Err("the dump is incomplete".to_string());
+            }
+            Ok(())
+        }
+        Database::MySql(_) => {
+            let (head, tail) = dump_ends(backup).map_err(|e| e.to_string())?;
+            if !head.contains("-- MySQL dump") && !head.contains("-- MariaDB dump") {
+                return Err("not a mysqldump file".to_string());
+            }
+            if !tail.contains("-- Dump completed") {
+                return Err("the dump is incomplete".to_string());
+            }
+            Ok(())
+        }
+    }
+}
+
 /// Payload for the backup.create job.
 #[derive(Debug, Deserialize)]
 pub struct BackupCreatePayload {
     pub triggered_at: Option<String>,
     #[serde(rename = "type")]
     pub backup_type: Option<String>,
+    /// Names the backup file; a new id is used when absent.
+    pub backup_id: Option<Uuid>,
 }
 
 /// Executor for backup.create jobs.
 ///
-/// Creates a database backup. The actual backup logic should be injected
-/// or configured based on the database backend in use.
-#[derive(Debug, Default)]
+/// Writes `didhub-<backupId>.sqlite3` (or `.sql` for PostgreSQL and MySQL) to
+/// the backup directory.
+#[derive(Debug)]
 pub struct BackupCreateExecutor {
-    // In a real implementation, this would hold a reference to the
-    // database connection or backup service
+    settings: BackupSettings,
 }
 
 impl BackupCreateExecutor {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(settings: BackupSettings) -> Self {
+        Self { settings }
     }
 }
 
@@ -38,10 +380,25 @@ impl JobExecutor for BackupCreateExecutor {
     }
 
     async fn execute(&self, payload: Value) -> Result<(), JobQueueError> {
+        self.execute_with_progress(payload, ProgressReporter::detached())
+            .await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        payload: Value,
+        progress: ProgressReporter,
+    ) -> Result<(), JobQueueError> {
+        let failed = |e: JobError| JobQueueError::ExecutionFailed(e.to_string());
         let parsed: BackupCreatePayload = serde_json::from_value(payload)
-            .map_err(|e| JobQueueError::ExecutionFailed(format!("invalid payload: {}", e)))?;
+            .map_err(|e| failed(JobError::InvalidPayload(e.to_string())))?;
 
         let backup_type = parsed.backup_type.as_deref().unwrap_or("full");
+        if backup_type != "full" {
+            return Err(failed(JobError::InvalidPayload(format!(
+                "unsupported backup type {backup_type}"
+            ))));
+        }
 
         info!(
             backup_type = backup_type,
@@ -49,12 +406,22 @@ impl JobExecutor for BackupCreateExecutor {
             "executing backup.create job"
         );
 
-        // TODO: Implement actual backup logic
-        // For SQLite: Use VACUUM INTO or file copy
-        // For PostgreSQL: Use pg_dump or similar
-        // For now, this is a stub that logs and completes
+        let database = Database::from_url(&self.settings.database_url).map_err(failed)?;
+        tokio::fs::create_dir_all(&self.settings.backup_dir)
+            .await
+            .map_err(|e| failed(e.into()))?;
+        let backup_id = parsed.backup_id.unwrap_or_else(Uuid::new_v4);
+        let path = self
+            .settings
+            .backup_dir
+            .join(format!("didhub-{backup_id}.{}", database.extension()));
 
-        info!("backup.create job completed successfully (stub)");
+        write_backup(&database, &path, &progress, (0, 100), "backing up database")
+            .await
+            .map_err(|e| failed(JobError::BackupFailed(e)))?;
+        progress.report(100, "backup complete").await;
+
+        info!(%backup_id, path = %path.display(), "backup.create job completed");
         Ok(())
     }
 }
@@ -63,6 +430,8 @@ impl JobExecutor for BackupCreateExecutor {
 #[derive(Debug, Deserialize)]
 pub struct BackupRestorePayload {
     pub upload_id: uuid::Uuid,
+    /// The uploaded backup file in the uploads directory.
+    pub stored_file_id: uuid::Uuid,
     pub stored_name: Option<String>,
     #[allow(dead_code)]
     pub triggered_at: Option<String>,
@@ -70,18 +439,17 @@ pub struct BackupRestorePayload {
 
 /// Executor for backup.restore jobs.
 ///
-/// Restores a database from a backup file. The actual restore logic should
-/// be injected or configured based on the database backend in use.
-#[derive(Debug, Default)]
+/// Restores the database from an uploaded backup file after validating it. A
+/// safety copy of the current database is written to the backup directory first
+/// as `didhub-pre-restore-<timestamp>`, and put back if the restore fails.
+#[derive(Debug)]
 pub struct BackupRestoreExecutor {
-    // In a real implementation, this would hold references to:
-    // - Database connection
-    // - Upload storage service
+    settings: BackupSettings,
 }
 
 impl BackupRestoreExecutor {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(settings: BackupSettings) -> Self {
+        Self { settings }
     }
 }
 
@@ -92,8 +460,18 @@ impl JobExecutor for BackupRestoreExecutor {
     }
 
     async fn execute(&self, payload: Value) -> Result<(), JobQueueError> {
+        self.execute_with_progress(payload, ProgressReporter::detached())
+            .await
+    }
+
+    async fn execute_with_progress(
+        &self,
+        payload: Value,
+        progress: ProgressReporter,
+    ) -> Result<(), JobQueueError> {
+        let failed = |e: JobError| JobQueueError::ExecutionFailed(e.to_string());
         let parsed: BackupRestorePayload = serde_json::from_value(payload)
-            .map_err(|e| JobQueueError::ExecutionFailed(format!("invalid payload: {}", e)))?;
+            .map_err(|e| failed(JobError::InvalidPayload(e.to_string())))?;
 
         info!(
             upload_id = %parsed.upload_id,
@@ -101,15 +479,68 @@ impl JobExecutor for BackupRestoreExecutor {
             "executing backup.restore job"
         );
 
-        // TODO: Implement actual restore logic
-        // 1. Locate the backup file from uploads
-        // 2. Verify backup integrity
-        // 3. Stop accepting new connections
-        // 4. Restore the database
-        // 5. Resume operations
+        let database = Database::from_url(&self.settings.database_url).map_err(failed)?;
+        let backup = self
+            .settings
+            .uploads_dir
+            .join(parsed.stored_file_id.to_string());
 
-        warn!("backup.restore job is a stub - no actual restore performed");
-        info!("backup.restore job completed (stub)");
+        progress.report(0, "validating backup").await;
+        let (checked, to_check) = (database.clone(), backup.clone());
+        tokio::task::spawn_blocking(move || validate_backup(&checked, &to_check))
+            .await
+            .map_err(|e| failed(JobError::RestoreFailed(e.to_string())))?
+            .map_err(|e| failed(JobError::RestoreFailed(format!("invalid backup: {e}"))))?;
+
+        tokio::fs::create_dir_all(&self.settings.backup_dir)
+            .await
+            .map_err(|e| failed(e.into()))?;
+        let safety = self.settings.backup_dir.join(format!(
+            "didhub-pre-restore-{}.{}",
+            Utc::now().format("%Y%m%dT%H%M%SZ"),
+            database.extension()
+        ));
+        write_backup(
+            &database,
+            &safety,
+            &progress,
+            (10, 40),
+            "saving pre-restore copy",
+        )
+        .await
+        .map_err(|e| {
+            failed(JobError::RestoreFailed(format!(
+                "could not save a pre-restore copy: {e}"
+            )))
+        })?;
+
+        if let Err(e) = load_backup(&database, &backup, &progress, (40, 100), "restoring").await {
+            warn!(error = %e, safety = %safety.display(), "restore failed; putting the pre-restore copy back");
+            progress.report(40, "putting pre-restore copy back").await;
+            let message = match load_backup(
+                &database,
+                &safety,
+                &progress,
+                (40, 100),
+                "putting pre-restore copy back",
+            )
+            .await
+            {
+                Ok(()) => format!("{e}; the database was put back from {}", safety.display()),
+                Err(rollback) => format!(
+                    "{e}; putting back {} also failed: {rollback}",
+                    safety.display()
+                ),
+            };
+            return Err(failed(JobError::RestoreFailed(message)));
+        }
+        progress.report(100, "restore complete").await;
+
+        info!(
+            upload_id = %parsed.upload_id,
+            safety = %safety.display(),
+            "backup.restore job completed"
+        );
         Ok(())
     }
 }
@@ -117,34 +548,253 @@ impl JobExecutor for BackupRestoreExecutor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use didhub_job_queue::{JobQueueClient, JobStatus};
     use serde_json::json;
 
+    struct Fixture {
+        dir: tempfile::TempDir,
+        settings: BackupSettings,
+    }
+
+    impl Fixture {
+        /// A migrated SQLite database holding one alter named `name`.
+        fn new(name: &str) -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let database = dir.path().join("didhub.sqlite");
+            let conn = Connection::open(&database).unwrap();
+            conn.execute_batch(
+                "CREATE TABLE _sqlx_migrations (version INTEGER PRIMARY KEY, success INTEGER NOT NULL);
+                 INSERT INTO _sqlx_migrations VALUES (1, 1), (2, 1);
+                 CREATE TABLE alters (name TEXT NOT NULL);",
+            )
+            .unwrap();
+            conn.execute("INSERT INTO alters VALUES (?1)", [name])
+                .unwrap();
+            let settings = BackupSettings {
+                database_url: format!("sqlite://{}", database.display()),
+                backup_dir: dir.path().join("backups"),
+                uploads_dir: dir.path().join("uploads"),
+            };
+            std::fs::create_dir_all(&settings.uploads_dir).unwrap();
+            Self { dir, settings }
+        }
+
+        fn execute(&self, sql: &str) {
+            let conn = Connection::open(self.dir.path().join("didhub.sqlite")).unwrap();
+            conn.execute_batch(sql).unwrap();
+        }
+
+        fn alters(&self) -> Vec<String> {
+            let conn = Connection::open(self.dir.path().join("didhub.sqlite")).unwrap();
+            let mut statement = conn
+                .prepare("SELECT name FROM alters ORDER BY name")
+                .unwrap();
+            let names = statement.query_map([], |row| row.get(0)).unwrap();
+            names.collect::<rusqlite::Result<_>>().unwrap()
+        }
+
+        /// Put `path` in the uploads directory as the backup to restore.
+        fn upload(&self, path: &Path) -> Value {
+            let stored_file_id = Uuid::new_v4();
+            std::fs::copy(
+                path,
+                self.settings.uploads_dir.join(stored_file_id.to_string()),
+            )
+            .unwrap();
+            json!({
+                "upload_id": Uuid::new_v4(),
+                "stored_file_id": stored_file_id,
+                "stored_name": "backup.sqlite3",
+            })
+        }
+
+        fn backups(&self, prefix: &str) -> Vec<PathBuf> {
+            std::fs::read_dir(&self.settings.backup_dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().path())
+                .filter(|path| {
+                    path.file_name()
+                        .unwrap()
+                        .to_string_lossy()
+                        .starts_with(prefix)
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_database_from_url() {
+        assert_eq!(
+            Database::from_url("sqlite://data/didhub.db?mode=rwc").unwrap(),
+            Database::Sqlite(PathBuf::from("data/didhub.db"))
+        );
+        assert_eq!(
+            Database::from_url("didhub.db").unwrap(),
+            Database::Sqlite(PathBuf::from("didhub.db"))
+        );
+        assert!(Database::from_url("sqlite::memory:").is_err());
+        let Database::Postgres(url) =
+            Database::from_url("postgres://me:p%40ss@db:5433/didhub?sslmode=require").unwrap()
+        else {
+            panic!("expected postgres");
+        };
+        let command = postgres_command("pg_dump", &url);
+        let args: Vec<_> = command.as_std().get_args().collect();
+        assert_eq!(args, ["postgres://me@db:5433/didhub?sslmode=require"]);
+        assert!(command
+            .as_std()
+            .get_envs()
+            .any(|(k, v)| k == "PGPASSWORD" && v == Some("p@ss".as_ref())));
+        let Database::MySql(url) = Database::from_url("mysql://me:p%40ss@db:3307/didhub").unwrap()
+        else {
+            panic!("expected mysql");
+        };
+        assert_eq!(mysql_database(&url), "didhub");
+        let command = mysql_command("mysqldump", &url);
+        let args: Vec<_> = command.as_std().get_args().collect();
+        assert_eq!(args, ["--host=db", "--port=3307", "--user=me"]);
+        assert!(command
+            .as_std()
+            .get_envs()
+            .any(|(k, v)| k == "MYSQL_PWD" && v == Some("p@ss".as_ref())));
+    }
+
     #[tokio::test]
     async fn test_backup_create_executor() {
-        let executor = BackupCreateExecutor::new();
+        let fixture = Fixture::new("Willow");
+        let executor = BackupCreateExecutor::new(fixture.settings.clone());
         assert_eq!(executor.job_type(), "backup.create");
 
+        let backup_id = Uuid::new_v4();
         let payload = json!({
             "triggered_at": "2024-01-01T00:00:00Z",
-            "type": "full"
+            "type": "full",
+            "backup_id": backup_id,
         });
 
         let result = executor.execute(payload).await;
-        assert!(result.is_ok());
+        assert!(result.is_ok(), "{result:?}");
+        let path = fixture
+            .settings
+            .backup_dir
+            .join(format!("didhub-{backup_id}.sqlite3"));
+        let conn = Connection::open(path).unwrap();
+        let name: String = conn
+            .query_row("SELECT name FROM alters", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(name, "Willow");
+    }
+
+    #[tokio::test]
+    async fn test_backup_create_reports_progress() {
+        let fixture = Fixture::new("Willow");
+        let client = JobQueueClient::new();
+        client
+            .register_executor(BackupCreateExecutor::new(fixture.settings.clone()))
+            .await;
+
+        let run = client
+            .run_job("backup.create", Some(json!({})))
+            .await
+            .unwrap();
+        assert_eq!(run.status, JobStatus::Completed, "{:?}", run.error_message);
+        let progress = run.progress.expect("progress");
+        assert_eq!(progress.percent, 100);
+        assert_eq!(fixture.backups("didhub-").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_backup_create_rejects_in_memory_database() {
+        let executor = BackupCreateExecutor::new(BackupSettings {
+            database_url: "sqlite::memory:".to_string(),
+            backup_dir: PathBuf::from("unused"),
+            uploads_dir: PathBuf::from("unused"),
+        });
+        assert!(executor.execute(json!({})).await.is_err());
     }
 
     #[tokio::test]
     async fn test_backup_restore_executor() {
-        let executor = BackupRestoreExecutor::new();
+        let fixture = Fixture::new("Willow");
+        BackupCreateExecutor::new(fixture.settings.clone())
+            .execute(json!({}))
+            .await
+            .unwrap();
+        let backup = fixture.backups("didhub-").remove(0);
+        fixture.execute("INSERT INTO alters VALUES ('Juniper')");
+
+        let executor = BackupRestoreExecutor::new(fixture.settings.clone());
         assert_eq!(executor.job_type(), "backup.restore");
+        let result = executor.execute(fixture.upload(&backup)).await;
+        assert!(result.is_ok(), "{result:?}");
+        assert_eq!(fixture.alters(), ["Willow"]);
 
-        let payload = json!({
-            "upload_id": "550e8400-e29b-41d4-a716-446655440000",
-            "stored_name": "backup-2024.db",
-            "triggered_at": "2024-01-01T00:00:00Z"
-        });
+        // The database as it was before the restore is kept
+        let safety = fixture.backups("didhub-pre-restore-");
+        assert_eq!(safety.len(), 1);
+        let conn = Connection::open(&safety[0]).unwrap();
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM alters", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 2);
+    }
 
-        let result = executor.execute(payload).await;
-        assert!(result.is_ok());
+    #[tokio::test]
+    async fn test_backup_restore_rejects_invalid_backups() {
+        let fixture = Fixture::new("Willow");
+        let executor = BackupRestoreExecutor::new(fixture.settings.clone());
+
+        let garbage = fixture.dir.path().join("garbage");
+        std::fs::write(&garbage, "not a database").unwrap();
+        let error = executor
+            .execute(fixture.upload(&garbage))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("invalid backup"), "{error}");
+
+        // A backup from another schema version is refused
+        BackupCreateExecutor::new(fixture.settings.clone())
+            .execute(json!({}))
+            .await
+            .unwrap();
+        let backup = fixture.backups("didhub-").remove(0);
+        fixture.execute(
+            "INSERT INTO _sqlx_migrations VALUES (3, 1); INSERT INTO alters VALUES ('Juniper');",
+        );
+        let error = executor.execute(fixture.upload(&backup)).await.unwrap_err();
+        assert!(error.to_string().contains("migration 2"), "{error}");
+        assert_eq!(fixture.alters(), ["Juniper", "Willow"]);
+        assert!(fixture.backups("didhub-pre-restore-").is_empty());
+    }
+
+    #[test]
+    fn test_validate_dumps() {
+        let dir = tempfile::tempdir().unwrap();
+        let dump = dir.path().join("dump.sql");
+        let postgres = Database::from_url("postgres://localhost/didhub").unwrap();
+
+        std::fs::write(
+            &dump,
+            "--\n-- PostgreSQL database dump\n--\nCREATE TABLE t ();\n",
+        )
+        .unwrap();
+        assert!(validate_backup(&postgres, &dump)
+            .unwrap_err()
+            .contains("incomplete"));
+        std::fs::write(
+            &dump,
+            "--\n-- PostgreSQL database dump\n--\nCREATE TABLE t ();\n--\n-- PostgreSQL database dump complete\n--\n",
+        )
+        .unwrap();
+        assert!(validate_backup(&postgres, &dump).is_ok());
+
+        let mysql = Database::from_url("mysql://localhost/didhub").unwrap();
+        assert!(validate_backup(&mysql, &dump).is_err());
+        std::fs::write(
+            &dump,
+            "-- MySQL dump 10.13\nCREATE TABLE t (id int);\n-- Dump completed on 2024-01-01\n",
+        )
+        .unwrap();
+        assert!(validate_backup(&mysql, &dump).is_ok());
     }
 }
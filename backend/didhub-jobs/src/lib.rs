@@ -14,12 +14,17 @@
 //!
 //! ```rust,no_run
 //! use didhub_job_queue::JobQueueClient;
-//! use didhub_jobs::register_all_executors;
+//! use didhub_jobs::{register_all_executors, BackupSettings};
 //!
 //! #[tokio::main]
 //! async fn main() {
 //!     let client = JobQueueClient::new();
-//!     register_all_executors(&client).await;
+//!     let backup = BackupSettings {
+//!         database_url: "sqlite://didhub.sqlite".into(),
+//!         backup_dir: "uploads/backups".into(),
+//!         uploads_dir: "uploads".into(),
+//!     };
+//!     register_all_executors(&client, backup).await;
 //! }
 //! ```
 
@@ -28,7 +33,7 @@ mod config;
 mod error;
 mod update;
 
-pub use backup::{BackupCreateExecutor, BackupRestoreExecutor};
+pub use backup::{BackupCreateExecutor, BackupRestoreExecutor, BackupSettings};
 pub use config::ConfigReloadExecutor;
 pub use error::JobError;
 pub use update::UpdateExecuteExecutor;
@@ -36,9 +41,13 @@ pub use update::UpdateExecuteExecutor;
 use didhub_job_queue::JobQueueClient;
 
 /// Register all available job executors with the job queue client.
-pub async fn register_all_executors(client: &JobQueueClient) {
-    client.register_executor(BackupCreateExecutor::new()).await;
-    client.register_executor(BackupRestoreExecutor::new()).await;
+pub async fn register_all_executors(client: &JobQueueClient, backup: BackupSettings) {
+    client
+        .register_executor(BackupCreateExecutor::new(backup.clone()))
+        .await;
+    client
+        .register_executor(BackupRestoreExecutor::new(backup))
+        .await;
     client.register_executor(UpdateExecuteExecutor::new()).await;
     client.register_executor(ConfigReloadExecutor::new()).await;
 }
@@ -0,0 +1,39 @@
+use std::sync::OnceLock;
+
+use prometheus::{IntGaugeVec, Opts};
+
+static FEATURES: OnceLock<IntGaugeVec> = OnceLock::new();
+
+/// Register `didhub_build_info`, a constant 1 labelled with the running build.
+pub fn register_build_info(version: &str, commit: &str, target: &str) -> prometheus::Result<()> {
+    let info = IntGaugeVec::new(
+        Opts::new(
+            "didhub_build_info",
+            "Always 1; labelled with the version, commit and target of the running build.",
+        ),
+        &["version", "commit", "target"],
+    )?;
+    info.with_label_values(&[version, commit, target]).set(1);
+    prometheus::default_registry().register(Box::new(info))
+}
+
+/// Set `didhub_feature_enabled{feature}` to 1 or 0.
+pub fn set_feature_enabled(feature: &str, enabled: bool) {
+    FEATURES
+        .get_or_init(|| {
+            let gauge = IntGaugeVec::new(
+                Opts::new(
+                    "didhub_feature_enabled",
+                    "Whether an optional feature is enabled (1) or not (0).",
+                ),
+                &["feature"],
+            )
+            .expect("valid gauge options");
+            prometheus::default_registry()
+                .register(Box::new(gauge.clone()))
+                .expect("feature gauge registered once");
+            gauge
+        })
+        .with_label_values(&[feature])
+        .set(i64::from(enabled));
+}
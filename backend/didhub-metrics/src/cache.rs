@@ -0,0 +1,36 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use prometheus::{HistogramOpts, HistogramVec};
+
+/// Name of the cache operation latency histogram.
+pub const CACHE_OPERATION_DURATION: &str = "didhub_cache_operation_duration_seconds";
+
+static HISTOGRAM: OnceLock<HistogramVec> = OnceLock::new();
+
+fn histogram() -> &'static HistogramVec {
+    HISTOGRAM.get_or_init(|| {
+        let histogram = HistogramVec::new(
+            HistogramOpts::new(
+                CACHE_OPERATION_DURATION,
+                "Time taken by cache operations, by namespace and outcome.",
+            )
+            .buckets(vec![0.0001, 0.0005, 0.001, 0.005, 0.025, 0.1, 0.5, 1.0]),
+            &["namespace", "operation"],
+        )
+        .expect("valid histogram options");
+        prometheus::default_registry()
+            .register(Box::new(histogram.clone()))
+            .expect("cache histogram registered once");
+        histogram
+    })
+}
+
+/// Record one cache operation on `namespace`. `operation` is `hit`, `miss`,
+/// `set`, `delete`, `exists` or `error`, so the `_count` series double as the
+/// hit and miss counters.
+pub fn record_cache_operation(namespace: &str, operation: &str, elapsed: Duration) {
+    histogram()
+        .with_label_values(&[namespace, operation])
+        .observe(elapsed.as_secs_f64());
+}
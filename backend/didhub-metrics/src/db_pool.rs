@@ -0,0 +1,87 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use prometheus::{HistogramOpts, HistogramVec, IntGaugeVec, Opts};
+
+/// Name of the histogram for the time spent waiting on a pooled connection.
+pub const DB_POOL_ACQUIRE_WAIT: &str = "didhub_db_pool_acquire_wait_seconds";
+
+struct PoolMetrics {
+    size: IntGaugeVec,
+    connections: IntGaugeVec,
+    max_connections: IntGaugeVec,
+    acquire_wait: HistogramVec,
+}
+
+static POOL_METRICS: OnceLock<PoolMetrics> = OnceLock::new();
+
+fn register() -> prometheus::Result<PoolMetrics> {
+    let registry = prometheus::default_registry();
+    let size = IntGaugeVec::new(
+        Opts::new("didhub_db_pool_size", "Open connections in the pool."),
+        &["pool"],
+    )?;
+    let connections = IntGaugeVec::new(
+        Opts::new(
+            "didhub_db_pool_connections",
+            "Open connections in the pool, by state (idle or in_use).",
+        ),
+        &["pool", "state"],
+    )?;
+    let max_connections = IntGaugeVec::new(
+        Opts::new(
+            "didhub_db_pool_max_connections",
+            "Configured connection limit of the pool.",
+        ),
+        &["pool"],
+    )?;
+    let acquire_wait = HistogramVec::new(
+        HistogramOpts::new(
+            DB_POOL_ACQUIRE_WAIT,
+            "Time taken to check a connection out of the pool.",
+        )
+        .buckets(vec![
+            0.0005, 0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 5.0, 30.0,
+        ]),
+        &["pool"],
+    )?;
+    registry.register(Box::new(size.clone()))?;
+    registry.register(Box::new(connections.clone()))?;
+    registry.register(Box::new(max_connections.clone()))?;
+    registry.register(Box::new(acquire_wait.clone()))?;
+    Ok(PoolMetrics {
+        size,
+        connections,
+        max_connections,
+        acquire_wait,
+    })
+}
+
+fn metrics() -> &'static PoolMetrics {
+    POOL_METRICS.get_or_init(|| register().expect("pool metrics registered once"))
+}
+
+/// Record the current state of the pool called `pool` (`primary`, `replica-0`, ...).
+pub fn observe_db_pool(pool: &str, idle: u32, in_use: u32, max_connections: u32) {
+    let m = metrics();
+    m.size
+        .with_label_values(&[pool])
+        .set(i64::from(idle) + i64::from(in_use));
+    m.connections
+        .with_label_values(&[pool, "idle"])
+        .set(i64::from(idle));
+    m.connections
+        .with_label_values(&[pool, "in_use"])
+        .set(i64::from(in_use));
+    m.max_connections
+        .with_label_values(&[pool])
+        .set(i64::from(max_connections));
+}
+
+/// Record how long checking a connection out of `pool` took.
+pub fn observe_db_pool_acquire(pool: &str, elapsed: Duration) {
+    metrics()
+        .acquire_wait
+        .with_label_values(&[pool])
+        .observe(elapsed.as_secs_f64());
+}
@@ -0,0 +1,51 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use prometheus::{HistogramOpts, HistogramVec};
+
+/// Name of the query latency histogram.
+pub const DB_QUERY_DURATION: &str = "didhub_db_query_duration_seconds";
+
+static HISTOGRAM: OnceLock<HistogramVec> = OnceLock::new();
+
+fn register(buckets: Vec<f64>) -> prometheus::Result<HistogramVec> {
+    let histogram = HistogramVec::new(
+        HistogramOpts::new(
+            DB_QUERY_DURATION,
+            "Time taken by database statements, by statement kind.",
+        )
+        .buckets(buckets),
+        &["statement"],
+    )?;
+    prometheus::default_registry().register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+/// Register the query latency histogram with `buckets` (upper bounds in
+/// seconds). Has to run before the first statement is recorded, which otherwise
+/// registers it with the default Prometheus buckets.
+pub fn init_db_metrics(buckets: Vec<f64>) -> prometheus::Result<()> {
+    HISTOGRAM
+        .set(register(buckets)?)
+        .map_err(|_| prometheus::Error::AlreadyReg)
+}
+
+fn histogram() -> &'static HistogramVec {
+    HISTOGRAM.get_or_init(|| {
+        register(prometheus::DEFAULT_BUCKETS.to_vec())
+            .expect("query duration histogram registered once")
+    })
+}
+
+/// Record one statement. `sql` only decides the `statement` label: `select`,
+/// `insert`, `update`, `delete` or `other`.
+pub fn observe_db_query(sql: &str, elapsed: Duration) {
+    let keyword = sql.split_whitespace().next().unwrap_or_default();
+    let statement = ["select", "insert", "update", "delete"]
+        .into_iter()
+        .find(|kind| keyword.eq_ignore_ascii_case(kind))
+        .unwrap_or("other");
+    histogram()
+        .with_label_values(&[statement])
+        .observe(elapsed.as_secs_f64());
+}
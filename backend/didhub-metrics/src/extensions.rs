@@ -0,0 +1,31 @@
+//! Registration for metrics owned by other crates (webhooks, importers, ...).
+//!
+//! Names are namespaced as `didhub_<namespace>_<name>`, so extensions cannot
+//! collide with each other or with the built-in metrics.
+
+pub use prometheus::{CounterVec, Error, Gauge};
+
+fn opts(namespace: &str, name: &str, help: &str) -> prometheus::Opts {
+    prometheus::Opts::new(name, help)
+        .namespace("didhub")
+        .subsystem(namespace)
+}
+
+/// Register a counter labelled by `labels` as `didhub_<namespace>_<name>`.
+pub fn register_counter_vec(
+    namespace: &str,
+    name: &str,
+    help: &str,
+    labels: &[&str],
+) -> Result<CounterVec, Error> {
+    let counter = CounterVec::new(opts(namespace, name, help), labels)?;
+    prometheus::default_registry().register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
+/// Register a gauge as `didhub_<namespace>_<name>`.
+pub fn register_gauge(namespace: &str, name: &str, help: &str) -> Result<Gauge, Error> {
+    let gauge = Gauge::with_opts(opts(namespace, name, help))?;
+    prometheus::default_registry().register(Box::new(gauge.clone()))?;
+    Ok(gauge)
+}
@@ -0,0 +1,102 @@
+use std::collections::HashSet;
+use std::sync::{OnceLock, RwLock};
+use std::time::Duration;
+
+use prometheus::{HistogramOpts, HistogramVec};
+
+/// Name of the request latency histogram.
+pub const HTTP_REQUEST_DURATION: &str = "didhub_http_request_duration_seconds";
+
+/// Label value for requests that matched no route.
+pub const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// Label value for routes seen after [`MAX_ROUTE_LABELS`] distinct ones.
+pub const OTHER_ROUTE: &str = "other";
+
+/// Most distinct `route` label values kept. Route templates are bounded by the
+/// router, this only guards against a router that hands out raw paths.
+pub const MAX_ROUTE_LABELS: usize = 256;
+
+static HISTOGRAM: OnceLock<HistogramVec> = OnceLock::new();
+static ROUTES: OnceLock<RwLock<HashSet<String>>> = OnceLock::new();
+
+fn register(buckets: Vec<f64>) -> prometheus::Result<HistogramVec> {
+    let histogram = HistogramVec::new(
+        HistogramOpts::new(
+            HTTP_REQUEST_DURATION,
+            "Time taken to answer HTTP requests, by method and route template.",
+        )
+        .buckets(buckets),
+        &["method", "route"],
+    )?;
+    prometheus::default_registry().register(Box::new(histogram.clone()))?;
+    Ok(histogram)
+}
+
+/// Register the request latency histogram with `buckets` (upper bounds in
+/// seconds). Has to run before the first request is recorded, which otherwise
+/// registers it with the default Prometheus buckets.
+pub fn init_http_metrics(buckets: Vec<f64>) -> prometheus::Result<()> {
+    HISTOGRAM
+        .set(register(buckets)?)
+        .map_err(|_| prometheus::Error::AlreadyReg)
+}
+
+fn histogram() -> &'static HistogramVec {
+    HISTOGRAM.get_or_init(|| {
+        register(prometheus::DEFAULT_BUCKETS.to_vec())
+            .expect("request duration histogram registered once")
+    })
+}
+
+/// Whether `route` may be used as a label value: it was seen before, or fewer
+/// than `limit` distinct routes have been.
+fn admit(routes: &RwLock<HashSet<String>>, route: &str, limit: usize) -> bool {
+    if routes
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .contains(route)
+    {
+        return true;
+    }
+    let mut routes = routes.write().unwrap_or_else(|e| e.into_inner());
+    if routes.len() >= limit {
+        return routes.contains(route);
+    }
+    routes.insert(route.to_owned());
+    true
+}
+
+/// Record one request. `route` is the matched route template such as
+/// `/api/alters/{alterId}`, never the raw path, so the label stays bounded.
+/// Past [`MAX_ROUTE_LABELS`] distinct routes, new ones count as [`OTHER_ROUTE`].
+pub fn observe_http_request(method: &str, route: &str, elapsed: Duration) {
+    let method = match method {
+        "GET" | "HEAD" | "POST" | "PUT" | "PATCH" | "DELETE" | "OPTIONS" => method,
+        _ => "other",
+    };
+    let routes = ROUTES.get_or_init(Default::default);
+    let route = if admit(routes, route, MAX_ROUTE_LABELS) {
+        route
+    } else {
+        OTHER_ROUTE
+    };
+    histogram()
+        .with_label_values(&[method, route])
+        .observe(elapsed.as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_past_the_limit_are_bucketed() {
+        let routes = RwLock::new(HashSet::new());
+        assert!(admit(&routes, "/api/alters", 2));
+        assert!(admit(&routes, "/api/systems", 2));
+        assert!(!admit(&routes, "/wp-login.php", 2));
+        assert!(admit(&routes, "/api/alters", 2));
+        assert_eq!(routes.read().unwrap().len(), 2);
+    }
+}
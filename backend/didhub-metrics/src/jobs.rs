@@ -0,0 +1,38 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use prometheus::{HistogramOpts, HistogramVec};
+
+/// Name of the scheduled job duration histogram.
+pub const SCHEDULED_JOB_DURATION: &str = "didhub_scheduled_job_duration_seconds";
+
+static HISTOGRAM: OnceLock<HistogramVec> = OnceLock::new();
+
+fn histogram() -> &'static HistogramVec {
+    HISTOGRAM.get_or_init(|| {
+        let histogram = HistogramVec::new(
+            HistogramOpts::new(
+                SCHEDULED_JOB_DURATION,
+                "Time taken by scheduled job runs, by job and result.",
+            )
+            .buckets(vec![
+                0.01, 0.1, 0.5, 1.0, 5.0, 15.0, 60.0, 300.0, 900.0, 3600.0,
+            ]),
+            &["job_name", "result"],
+        )
+        .expect("valid histogram options");
+        prometheus::default_registry()
+            .register(Box::new(histogram.clone()))
+            .expect("scheduled job histogram registered once");
+        histogram
+    })
+}
+
+/// Record one scheduled run of `job`. The label is `job_name` because Prometheus
+/// reserves `job` for the scrape target. `result` is `success`, `failure` (the job
+/// returned an error) or `error` (the job could not be started).
+pub fn observe_scheduled_job(job: &str, result: &str, elapsed: Duration) {
+    histogram()
+        .with_label_values(&[job, result])
+        .observe(elapsed.as_secs_f64());
+}
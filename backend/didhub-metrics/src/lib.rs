@@ -0,0 +1,168 @@
+//! Prometheus metrics for DIDHub.
+//!
+//! Metrics live in the prometheus default registry. On Linux it also carries the
+//! process collector (`process_resident_memory_bytes`, `process_open_fds`,
+//! `process_cpu_seconds_total`, ...). [`render`] encodes everything for the
+//! backend's `/metrics` endpoint. Other crates add their own metrics through
+//! [`extensions`] without depending on prometheus. With the `push` feature,
+//! `push` sends the same metrics to a Pushgateway for processes that are not
+//! scraped.
+
+mod build_info;
+mod cache;
+mod db_pool;
+mod db_query;
+pub mod extensions;
+mod http;
+mod jobs;
+#[cfg(feature = "push")]
+pub mod push;
+mod runtime;
+
+pub use build_info::{register_build_info, set_feature_enabled};
+pub use cache::{record_cache_operation, CACHE_OPERATION_DURATION};
+pub use db_pool::{observe_db_pool, observe_db_pool_acquire, DB_POOL_ACQUIRE_WAIT};
+pub use db_query::{init_db_metrics, observe_db_query, DB_QUERY_DURATION};
+pub use http::{
+    init_http_metrics, observe_http_request, HTTP_REQUEST_DURATION, MAX_ROUTE_LABELS, OTHER_ROUTE,
+    UNMATCHED_ROUTE,
+};
+pub use jobs::{observe_scheduled_job, SCHEDULED_JOB_DURATION};
+pub use runtime::RuntimeCollector;
+
+/// Register [`RuntimeCollector`] for the runtime behind `handle` in the default
+/// registry.
+pub fn register_runtime_metrics(handle: tokio::runtime::Handle) -> prometheus::Result<()> {
+    prometheus::default_registry().register(Box::new(RuntimeCollector::new(handle)?))
+}
+
+/// The default registry in the Prometheus text format.
+pub fn render() -> String {
+    use prometheus::Encoder;
+
+    let mut out = Vec::new();
+    if let Err(e) = prometheus::TextEncoder::new().encode(&prometheus::gather(), &mut out) {
+        return format!("# failed to encode metrics: {e}\n");
+    }
+    String::from_utf8(out).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn runtime_metrics_are_rendered() {
+        register_runtime_metrics(tokio::runtime::Handle::current()).expect("register");
+        let _task = tokio::spawn(std::future::pending::<()>());
+
+        let text = render();
+        assert!(text.contains("tokio_workers 2\n"), "{text}");
+        assert!(text.contains("# TYPE tokio_alive_tasks gauge"));
+        assert!(text.contains("# TYPE tokio_worker_busy_seconds_total counter"));
+        #[cfg(target_os = "linux")]
+        assert!(text.contains("process_resident_memory_bytes"));
+
+        // Each runtime registers once
+        assert!(register_runtime_metrics(tokio::runtime::Handle::current()).is_err());
+    }
+
+    #[test]
+    fn requests_are_labelled_by_route_template() {
+        init_http_metrics(vec![0.001, 0.05, 1.0]).expect("register histogram");
+        assert!(init_http_metrics(vec![1.0]).is_err());
+        let route = "/api/alters/{alterId}";
+        observe_http_request("GET", route, std::time::Duration::from_millis(30));
+        observe_http_request("GET", route, std::time::Duration::from_millis(70));
+        observe_http_request("BREW", route, std::time::Duration::from_millis(5));
+
+        let text = render();
+        assert!(text.contains(&format!(
+            "{HTTP_REQUEST_DURATION}_count{{method=\"GET\",route=\"{route}\"}} 2\n"
+        )));
+        assert!(text.contains(&format!(
+            "{HTTP_REQUEST_DURATION}_count{{method=\"other\",route=\"{route}\"}} 1\n"
+        )));
+        // Configured buckets
+        assert!(text.contains(&format!(
+            "{HTTP_REQUEST_DURATION}_bucket{{method=\"GET\",route=\"{route}\",le=\"0.05\"}} 1\n"
+        )));
+        assert!(!text.contains(&format!(
+            "{HTTP_REQUEST_DURATION}_bucket{{method=\"GET\",route=\"{route}\",le=\"0.005\"}}"
+        )));
+    }
+
+    #[test]
+    fn statements_are_labelled_by_kind() {
+        init_db_metrics(vec![0.0001, 0.001, 0.01]).expect("register histogram");
+        assert!(init_db_metrics(vec![1.0]).is_err());
+        let ms = std::time::Duration::from_millis;
+        observe_db_query("SELECT * FROM alters WHERE id = ?", ms(2));
+        observe_db_query("select 1", ms(0));
+        observe_db_query("PRAGMA foreign_keys = ON", ms(0));
+
+        let text = render();
+        assert!(text.contains(&format!(
+            "{DB_QUERY_DURATION}_count{{statement=\"select\"}} 2\n"
+        )));
+        assert!(text.contains(&format!(
+            "{DB_QUERY_DURATION}_count{{statement=\"other\"}} 1\n"
+        )));
+        assert!(text.contains(&format!(
+            "{DB_QUERY_DURATION}_bucket{{statement=\"select\",le=\"0.001\"}} 1\n"
+        )));
+    }
+
+    #[test]
+    fn build_info_and_features_are_rendered() {
+        register_build_info("1.2.3", "abc1234", "x86_64-linux").expect("register build info");
+        set_feature_enabled("updater", true);
+        set_feature_enabled("embedded_frontend", false);
+
+        let text = render();
+        assert!(text.contains(
+            "didhub_build_info{commit=\"abc1234\",target=\"x86_64-linux\",version=\"1.2.3\"} 1\n"
+        ));
+        assert!(text.contains("didhub_feature_enabled{feature=\"updater\"} 1\n"));
+        assert!(text.contains("didhub_feature_enabled{feature=\"embedded_frontend\"} 0\n"));
+    }
+
+    #[test]
+    fn pool_samples_are_labelled_by_pool() {
+        observe_db_pool("replica-0", 3, 2, 10);
+        observe_db_pool_acquire("replica-0", std::time::Duration::from_millis(2));
+
+        let text = render();
+        assert!(text.contains("didhub_db_pool_size{pool=\"replica-0\"} 5\n"));
+        assert!(text.contains("didhub_db_pool_connections{pool=\"replica-0\",state=\"idle\"} 3\n"));
+        assert!(
+            text.contains("didhub_db_pool_connections{pool=\"replica-0\",state=\"in_use\"} 2\n")
+        );
+        assert!(text.contains("didhub_db_pool_max_connections{pool=\"replica-0\"} 10\n"));
+        assert!(text.contains(&format!(
+            "{DB_POOL_ACQUIRE_WAIT}_count{{pool=\"replica-0\"}} 1\n"
+        )));
+    }
+
+    #[test]
+    fn extensions_register_namespaced_metrics() {
+        let deliveries = extensions::register_counter_vec(
+            "webhooks",
+            "deliveries_total",
+            "Webhook deliveries by outcome.",
+            &["outcome"],
+        )
+        .expect("register counter");
+        deliveries.with_label_values(&["ok"]).inc_by(3.0);
+        let pending = extensions::register_gauge("importers", "pending", "Queued imports.")
+            .expect("register gauge");
+        pending.set(2.0);
+
+        let text = render();
+        assert!(text.contains("didhub_webhooks_deliveries_total{outcome=\"ok\"} 3\n"));
+        assert!(text.contains("didhub_importers_pending 2\n"));
+
+        assert!(extensions::register_gauge("importers", "pending", "Again.").is_err());
+        assert!(extensions::register_gauge("bad name", "pending", "Invalid.").is_err());
+    }
+}
@@ -0,0 +1,70 @@
+//! Push mode for processes Prometheus cannot scrape.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+/// Push the default registry to the Pushgateway at `gateway_url`, replacing what
+/// was pushed before under `job`. Blocks on the HTTP request.
+pub fn push(gateway_url: &str, job: &str) -> prometheus::Result<()> {
+    prometheus::push_metrics(
+        job,
+        HashMap::<String, String>::new(),
+        gateway_url,
+        prometheus::gather(),
+        None,
+    )
+}
+
+/// Push every `interval` on the blocking pool. Failed pushes are logged and
+/// retried at the next tick.
+pub fn spawn_push(gateway_url: String, job: String, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let (url, name) = (gateway_url.clone(), job.clone());
+            match tokio::task::spawn_blocking(move || push(&url, &name)).await {
+                Ok(Ok(())) => {}
+                Ok(Err(error)) => {
+                    tracing::warn!(%error, gateway = %gateway_url, "failed to push metrics")
+                }
+                Err(error) => tracing::warn!(%error, "metrics push task failed"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn push_puts_to_the_job_path() {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+        let addr = listener.local_addr().expect("addr");
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("accept");
+            let mut head = Vec::new();
+            let mut byte = [0u8; 1];
+            while !head.ends_with(b"\r\n\r\n") {
+                stream.read_exact(&mut byte).expect("read");
+                head.push(byte[0]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\n\r\n")
+                .expect("write");
+            String::from_utf8(head).expect("utf-8")
+        });
+
+        super::push(&format!("http://{addr}/"), "didhub-cli").expect("push");
+        let request = server.join().expect("server");
+        assert!(
+            request.starts_with("PUT /metrics/job/didhub-cli HTTP/1.1\r\n"),
+            "{request}"
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
+use prometheus::{IntCounter, IntGauge, Opts};
+use tokio::runtime::Handle;
+
+/// Tokio executor metrics, sampled from the runtime whenever the registry is
+/// gathered.
+///
+/// Blocking pool metrics are only exposed by builds with `--cfg tokio_unstable`.
+pub struct RuntimeCollector {
+    handle: Handle,
+    workers: IntGauge,
+    alive_tasks: IntGauge,
+    global_queue_depth: IntGauge,
+    busy_seconds: prometheus::Counter,
+    parks: IntCounter,
+    #[cfg(tokio_unstable)]
+    blocking_threads: IntGauge,
+    #[cfg(tokio_unstable)]
+    idle_blocking_threads: IntGauge,
+    #[cfg(tokio_unstable)]
+    blocking_queue_depth: IntGauge,
+}
+
+impl RuntimeCollector {
+    pub fn new(handle: Handle) -> prometheus::Result<Self> {
+        Ok(Self {
+            handle,
+            workers: IntGauge::with_opts(Opts::new(
+                "tokio_workers",
+                "Worker threads of the Tokio runtime.",
+            ))?,
+            alive_tasks: IntGauge::with_opts(Opts::new(
+                "tokio_alive_tasks",
+                "Tasks currently alive on the Tokio runtime.",
+            ))?,
+            global_queue_depth: IntGauge::with_opts(Opts::new(
+                "tokio_global_queue_depth",
+                "Tasks waiting in the runtime's global queue.",
+            ))?,
+            busy_seconds: prometheus::Counter::with_opts(Opts::new(
+                "tokio_worker_busy_seconds_total",
+                "Time the worker threads spent running tasks, summed over workers.",
+            ))?,
+            parks: IntCounter::with_opts(Opts::new(
+                "tokio_worker_parks_total",
+                "Times a worker thread parked for lack of work, summed over workers.",
+            ))?,
+            #[cfg(tokio_unstable)]
+            blocking_threads: IntGauge::with_opts(Opts::new(
+                "tokio_blocking_threads",
+                "Threads in the blocking pool.",
+            ))?,
+            #[cfg(tokio_unstable)]
+            idle_blocking_threads: IntGauge::with_opts(Opts::new(
+                "tokio_idle_blocking_threads",
+                "Idle threads in the blocking pool.",
+            ))?,
+            #[cfg(tokio_unstable)]
+            blocking_queue_depth: IntGauge::with_opts(Opts::new(
+                "tokio_blocking_queue_depth",
+                "Tasks waiting for a blocking pool thread.",
+            ))?,
+        })
+    }
+
+    fn sample(&self) {
+        let metrics = self.handle.metrics();
+        let workers = metrics.num_workers();
+        self.workers.set(workers as i64);
+        self.alive_tasks.set(metrics.num_alive_tasks() as i64);
+        self.global_queue_depth
+            .set(metrics.global_queue_depth() as i64);
+
+        // The runtime keeps running totals; the counters catch up to them
+        let busy: f64 = (0..workers)
+            .map(|w| metrics.worker_total_busy_duration(w).as_secs_f64())
+            .sum();
+        let busy_delta = busy - self.busy_seconds.get();
+        if busy_delta > 0.0 {
+            self.busy_seconds.inc_by(busy_delta);
+        }
+        let parks: u64 = (0..workers).map(|w| metrics.worker_park_count(w)).sum();
+        self.parks.inc_by(parks.saturating_sub(self.parks.get()));
+
+        #[cfg(tokio_unstable)]
+        {
+            self.blocking_threads
+                .set(metrics.num_blocking_threads() as i64);
+            self.idle_blocking_threads
+                .set(metrics.num_idle_blocking_threads() as i64);
+            self.blocking_queue_depth
+                .set(metrics.blocking_queue_depth() as i64);
+        }
+    }
+
+    fn collectors(&self) -> Vec<&dyn Collector> {
+        vec![
+            &self.workers,
+            &self.alive_tasks,
+            &self.global_queue_depth,
+            &self.busy_seconds,
+            &self.parks,
+            #[cfg(tokio_unstable)]
+            &self.blocking_threads,
+            #[cfg(tokio_unstable)]
+            &self.idle_blocking_threads,
+            #[cfg(tokio_unstable)]
+            &self.blocking_queue_depth,
+        ]
+    }
+}
+
+impl Collector for RuntimeCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        self.collectors()
+            .into_iter()
+            .flat_map(|c| c.desc())
+            .collect()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        self.sample();
+        self.collectors()
+            .into_iter()
+            .flat_map(|c| c.collect())
+            .collect()
+    }
+}
@@ -1,4 +1,6 @@
-use sqlx::migrate::Migrator;
+use std::collections::HashMap;
+
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
 
 pub static SQLITE_MIGRATOR: Migrator = sqlx_macros::migrate!("src/migrations_sqlite");
 pub static POSTGRES_MIGRATOR: Migrator = sqlx_macros::migrate!("src/migrations_postgres");
@@ -15,3 +17,173 @@ pub fn postgres_migrator() -> &'static Migrator {
 pub fn mysql_migrator() -> &'static Migrator {
     &MYSQL_MIGRATOR
 }
+
+/// SQL dialect a set of migrations is written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Sqlite,
+    Postgres,
+    MySql,
+}
+
+impl Dialect {
+    /// Dialect for a database URL; anything that is not Postgres or MySQL is SQLite.
+    pub fn from_url(url: &str) -> Self {
+        let url = url.to_lowercase();
+        if url.starts_with("postgres") || url.contains("postgresql") {
+            Dialect::Postgres
+        } else if url.starts_with("mysql") || url.contains("mysql://") {
+            Dialect::MySql
+        } else {
+            Dialect::Sqlite
+        }
+    }
+
+    /// Dialect of a sqlx database driver, e.g. the one a pool was compiled for.
+    pub fn of<DB: sqlx::Database>() -> Self {
+        match DB::NAME {
+            "PostgreSQL" => Dialect::Postgres,
+            "MySQL" => Dialect::MySql,
+            _ => Dialect::Sqlite,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dialect::Sqlite => "sqlite",
+            Dialect::Postgres => "postgres",
+            Dialect::MySql => "mysql",
+        }
+    }
+
+    pub fn migrator(self) -> &'static Migrator {
+        match self {
+            Dialect::Sqlite => sqlite_migrator(),
+            Dialect::Postgres => postgres_migrator(),
+            Dialect::MySql => mysql_migrator(),
+        }
+    }
+}
+
+/// Where a migration stands relative to the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the SQL in this build differs from what was run.
+    Modified,
+    /// Recorded in the database but unknown to this build, e.g. after a downgrade.
+    Missing,
+}
+
+impl MigrationState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Modified => "modified",
+            MigrationState::Missing => "missing",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+    pub version: i64,
+    /// Empty for [`MigrationState::Missing`] migrations.
+    pub description: String,
+    pub state: MigrationState,
+    /// Whether this build ships a down migration for it.
+    pub reversible: bool,
+}
+
+/// Compare `migrator` against the migrations recorded in the database, ordered by
+/// version. Creates the bookkeeping table if the database has never been migrated.
+pub async fn status<C>(
+    migrator: &Migrator,
+    conn: &mut C,
+) -> Result<Vec<MigrationStatus>, MigrateError>
+where
+    C: Migrate + ?Sized,
+{
+    conn.ensure_migrations_table().await?;
+    let mut applied: HashMap<i64, _> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|m| (m.version, m.checksum))
+        .collect();
+
+    let mut statuses: Vec<MigrationStatus> = migrator
+        .iter()
+        .filter(|m| m.migration_type.is_up_migration())
+        .map(|m| {
+            let state = match applied.remove(&m.version) {
+                Some(checksum) if checksum == m.checksum => MigrationState::Applied,
+                Some(_) => MigrationState::Modified,
+                None => MigrationState::Pending,
+            };
+            MigrationStatus {
+                version: m.version,
+                description: m.description.to_string(),
+                state,
+                reversible: migrator
+                    .iter()
+                    .any(|d| d.version == m.version && d.migration_type.is_down_migration()),
+            }
+        })
+        .collect();
+    statuses.extend(applied.into_keys().map(|version| MigrationStatus {
+        version,
+        description: String::new(),
+        state: MigrationState::Missing,
+        reversible: false,
+    }));
+    statuses.sort_by_key(|s| s.version);
+    Ok(statuses)
+}
+
+/// Revert the `steps` most recently applied migrations, newest first, and return
+/// the reverted versions. Fails without reverting anything if one of them has no
+/// down migration.
+pub async fn revert<C>(
+    migrator: &Migrator,
+    conn: &mut C,
+    steps: usize,
+) -> Result<Vec<i64>, MigrateError>
+where
+    C: Migrate + ?Sized,
+{
+    let mut applied: Vec<i64> = status(migrator, conn)
+        .await?
+        .into_iter()
+        .filter(|s| s.state != MigrationState::Pending)
+        .map(|s| s.version)
+        .collect();
+    applied.reverse();
+    let targets: Vec<i64> = applied.into_iter().take(steps).collect();
+
+    let mut downs = Vec::with_capacity(targets.len());
+    for version in &targets {
+        let down = migrator
+            .iter()
+            .find(|m| m.version == *version && m.migration_type.is_down_migration())
+            .ok_or(MigrateError::VersionNotPresent(*version))?;
+        downs.push(down);
+    }
+
+    if migrator.locking {
+        conn.lock().await?;
+    }
+    let mut result = Ok(());
+    for down in downs {
+        result = conn.revert(down).await.map(|_| ());
+        if result.is_err() {
+            break;
+        }
+    }
+    if migrator.locking {
+        conn.unlock().await?;
+    }
+    result.map(|_| targets)
+}
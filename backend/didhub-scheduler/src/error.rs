@@ -0,0 +1,16 @@
+//! Error types for the scheduler.
+
+use thiserror::Error;
+
+/// Errors that may occur while configuring or driving the scheduler.
+#[derive(Debug, Error)]
+pub enum SchedulerError {
+    #[error("invalid cron expression `{expression}`: {message}")]
+    InvalidCron { expression: String, message: String },
+
+    #[error("invalid interval: {0}")]
+    InvalidInterval(String),
+
+    #[error("job is not registered: {0}")]
+    UnknownJob(String),
+}
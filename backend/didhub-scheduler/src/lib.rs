@@ -0,0 +1,43 @@
+//! Periodic job scheduling for the DIDHub backend.
+//!
+//! The scheduler sits on top of [`didhub_job_queue::JobQueueClient`]: it does not
+//! execute anything itself, it only decides *when* a registered job should run and
+//! hands the run over to the queue, which dispatches it to the matching executor.
+//!
+//! # Architecture
+//!
+//! - [`CronScheduler`] - Holds the registered jobs and fires them when they are due
+//! - [`Schedule`] - Either a fixed interval or a cron expression
+//! - [`ScheduledJobStatus`] - Point-in-time view of a registered job for introspection
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::time::Duration;
+//!
+//! use didhub_job_queue::JobQueueClient;
+//! use didhub_scheduler::{CronScheduler, Schedule};
+//!
+//! #[tokio::main]
+//! async fn main() {
+//!     let queue = JobQueueClient::new();
+//!     let scheduler = CronScheduler::new(queue);
+//!
+//!     scheduler
+//!         .register("backup.create", Schedule::cron("0 0 3 * * *").unwrap(), true)
+//!         .await;
+//!     scheduler
+//!         .register("config.reload", Schedule::interval(Duration::from_secs(3600)), true)
+//!         .await;
+//!
+//!     let _handle = scheduler.spawn();
+//! }
+//! ```
+
+mod error;
+mod schedule;
+mod scheduler;
+
+pub use error::SchedulerError;
+pub use schedule::Schedule;
+pub use scheduler::{CronScheduler, ScheduledJobStatus};
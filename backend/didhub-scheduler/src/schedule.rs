@@ -0,0 +1,98 @@
+//! Schedule definitions.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::SchedulerError;
+
+/// When a scheduled job should fire.
+#[derive(Clone)]
+pub enum Schedule {
+    /// Fire every `Duration`, measured from the previous fire time.
+    Interval(Duration),
+    /// Fire according to a cron expression (seconds field included, evaluated in UTC).
+    Cron {
+        expression: String,
+        schedule: Box<cron::Schedule>,
+    },
+}
+
+impl Schedule {
+    /// Build an interval schedule.
+    #[inline]
+    pub fn interval(every: Duration) -> Self {
+        Self::Interval(every)
+    }
+
+    /// Build an interval schedule, rejecting a zero interval.
+    pub fn try_interval(every: Duration) -> Result<Self, SchedulerError> {
+        if every.is_zero() {
+            return Err(SchedulerError::InvalidInterval(
+                "interval must be greater than zero".into(),
+            ));
+        }
+        Ok(Self::Interval(every))
+    }
+
+    /// Parse a cron expression.
+    ///
+    /// Both the six-field form (`sec min hour dom month dow`) and the seven-field
+    /// form with a trailing year are accepted.
+    pub fn cron(expression: &str) -> Result<Self, SchedulerError> {
+        let expression = expression.trim();
+        let schedule =
+            cron::Schedule::from_str(expression).map_err(|e| SchedulerError::InvalidCron {
+                expression: expression.to_string(),
+                message: e.to_string(),
+            })?;
+        Ok(Self::Cron {
+            expression: expression.to_string(),
+            schedule: Box::new(schedule),
+        })
+    }
+
+    /// Compute the next fire time strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Interval(every) => chrono::Duration::from_std(*every)
+                .ok()
+                .and_then(|d| after.checked_add_signed(d)),
+            Self::Cron { schedule, .. } => schedule.after(&after).next(),
+        }
+    }
+
+    /// Human-readable description used by introspection endpoints.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Interval(every) => format!("every {}s", every.as_secs()),
+            Self::Cron { expression, .. } => expression.clone(),
+        }
+    }
+
+    /// Short label for the schedule kind (`interval` or `cron`).
+    #[inline]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Interval(_) => "interval",
+            Self::Cron { .. } => "cron",
+        }
+    }
+}
+
+impl fmt::Debug for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Interval(every) => f.debug_tuple("Interval").field(every).finish(),
+            Self::Cron { expression, .. } => f.debug_tuple("Cron").field(expression).finish(),
+        }
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.describe())
+    }
+}
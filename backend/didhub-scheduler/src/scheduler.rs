@@ -0,0 +1,340 @@
+//! Scheduler implementation.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use didhub_job_queue::{JobQueueClient, JobRun};
+use serde::Serialize;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+
+use crate::error::SchedulerError;
+use crate::schedule::Schedule;
+
+/// How often the background loop checks for due jobs.
+const DEFAULT_TICK: Duration = Duration::from_secs(1);
+
+/// Internal bookkeeping for a registered job.
+#[derive(Debug)]
+struct JobEntry {
+    schedule: Schedule,
+    enabled: bool,
+    next_fire_at: Option<DateTime<Utc>>,
+    last_run: Option<JobRun>,
+    running: bool,
+}
+
+impl JobEntry {
+    fn status(&self, name: &str) -> ScheduledJobStatus {
+        ScheduledJobStatus {
+            name: name.to_owned(),
+            schedule: self.schedule.describe(),
+            kind: self.schedule.kind(),
+            enabled: self.enabled,
+            last_run: self.last_run.clone(),
+            next_fire_at: if self.enabled {
+                self.next_fire_at
+            } else {
+                None
+            },
+            running: self.running,
+        }
+    }
+}
+
+/// Point-in-time view of a registered job.
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduledJobStatus {
+    pub name: String,
+    pub schedule: String,
+    pub kind: &'static str,
+    pub enabled: bool,
+    pub last_run: Option<JobRun>,
+    pub next_fire_at: Option<DateTime<Utc>>,
+    pub running: bool,
+}
+
+/// Fires registered jobs on the job queue according to their schedules.
+///
+/// Cloning is cheap; all clones share the same set of registered jobs.
+#[derive(Clone)]
+pub struct CronScheduler {
+    queue: JobQueueClient,
+    jobs: Arc<RwLock<BTreeMap<String, JobEntry>>>,
+    tick: Duration,
+    started: Arc<AtomicBool>,
+}
+
+impl fmt::Debug for CronScheduler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CronScheduler")
+            .field("jobs", &"<RwLock<BTreeMap<String, JobEntry>>>")
+            .field("tick", &self.tick)
+            .field("started", &self.is_started())
+            .finish()
+    }
+}
+
+impl CronScheduler {
+    pub fn new(queue: JobQueueClient) -> Self {
+        Self {
+            queue,
+            jobs: Arc::new(RwLock::new(BTreeMap::new())),
+            tick: DEFAULT_TICK,
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Override how often the background loop checks for due jobs.
+    #[must_use]
+    pub fn with_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    /// Register (or replace) a job. The job name must match an executor's job type.
+    pub async fn register(&self, name: impl Into<String>, schedule: Schedule, enabled: bool) {
+        let name = name.into();
+        let next_fire_at = schedule.next_after(Utc::now());
+        let mut jobs = self.jobs.write().await;
+        let previous = jobs.remove(&name);
+        jobs.insert(
+            name,
+            JobEntry {
+                schedule,
+                enabled,
+                next_fire_at,
+                last_run: previous.as_ref().and_then(|p| p.last_run.clone()),
+                running: previous.is_some_and(|p| p.running),
+            },
+        );
+    }
+
+    /// Enable or disable a registered job.
+    pub async fn set_enabled(&self, name: &str, enabled: bool) -> Result<(), SchedulerError> {
+        let mut jobs = self.jobs.write().await;
+        let entry = jobs
+            .get_mut(name)
+            .ok_or_else(|| SchedulerError::UnknownJob(name.to_owned()))?;
+        if enabled && !entry.enabled {
+            entry.next_fire_at = entry.schedule.next_after(Utc::now());
+        }
+        entry.enabled = enabled;
+        Ok(())
+    }
+
+    /// Status of every registered job, ordered by name.
+    pub async fn jobs(&self) -> Vec<ScheduledJobStatus> {
+        let jobs = self.jobs.read().await;
+        jobs.iter()
+            .map(|(name, entry)| entry.status(name))
+            .collect()
+    }
+
+    /// Status of a single registered job.
+    pub async fn job(&self, name: &str) -> Option<ScheduledJobStatus> {
+        let jobs = self.jobs.read().await;
+        jobs.get(name).map(|entry| entry.status(name))
+    }
+
+    /// Fire every enabled job whose next fire time is at or before `now`.
+    ///
+    /// Jobs that are still running from a previous fire are skipped and rescheduled.
+    /// Returns the names of the jobs that were started.
+    pub async fn run_due(&self, now: DateTime<Utc>) -> Vec<String> {
+        let mut started = Vec::new();
+        {
+            let mut jobs = self.jobs.write().await;
+            for (name, entry) in jobs.iter_mut() {
+                if !entry.enabled || entry.next_fire_at.is_none_or(|at| at > now) {
+                    continue;
+                }
+                entry.next_fire_at = entry.schedule.next_after(now);
+                if entry.running {
+                    tracing::warn!(job = %name, "scheduled job still running; skipping this fire");
+                    continue;
+                }
+                entry.running = true;
+                started.push(name.clone());
+            }
+        }
+
+        for name in &started {
+            let this = self.clone();
+            let name = name.clone();
+            tokio::spawn(async move { this.execute(name).await });
+        }
+        started
+    }
+
+    async fn execute(&self, name: String) {
+        tracing::debug!(job = %name, "firing scheduled job");
+        let started = Instant::now();
+        let result = self.queue.run_job(&name, None).await;
+        let outcome = match &result {
+            Ok(run) if run.error_message.is_none() => "success",
+            Ok(_) => "failure",
+            Err(_) => "error",
+        };
+        didhub_metrics::observe_scheduled_job(&name, outcome, started.elapsed());
+
+        let mut jobs = self.jobs.write().await;
+        let Some(entry) = jobs.get_mut(&name) else {
+            return;
+        };
+        entry.running = false;
+        match result {
+            Ok(run) => {
+                if let Some(message) = &run.error_message {
+                    tracing::warn!(job = %name, error = %message, "scheduled job failed");
+                }
+                entry.last_run = Some(run);
+            }
+            Err(e) => tracing::error!(job = %name, error = %e, "failed to run scheduled job"),
+        }
+    }
+
+    /// Whether the background loop has been spawned.
+    #[inline]
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::Relaxed)
+    }
+
+    /// Spawn the background loop that fires due jobs.
+    pub fn spawn(&self) -> JoinHandle<()> {
+        self.started.store(true, Ordering::Relaxed);
+        let this = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(this.tick);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                ticker.tick().await;
+                this.run_due(Utc::now()).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use didhub_job_queue::{async_trait, JobExecutor, JobQueueError, JobStatus};
+
+    struct SlowExecutor;
+
+    #[async_trait]
+    impl JobExecutor for SlowExecutor {
+        fn job_type(&self) -> &str {
+            "test.slow"
+        }
+
+        async fn execute(&self, _payload: serde_json::Value) -> Result<(), JobQueueError> {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn cron_schedule_rejects_garbage() {
+        assert!(matches!(
+            Schedule::cron("not a cron"),
+            Err(SchedulerError::InvalidCron { .. })
+        ));
+        assert!(Schedule::cron("0 */5 * * * *").is_ok());
+    }
+
+    #[test]
+    fn zero_interval_is_rejected() {
+        assert!(Schedule::try_interval(Duration::ZERO).is_err());
+    }
+
+    #[tokio::test]
+    async fn jobs_are_listed_with_next_fire_time() {
+        let scheduler = CronScheduler::new(JobQueueClient::new());
+        scheduler
+            .register("b.job", Schedule::interval(Duration::from_secs(60)), true)
+            .await;
+        scheduler
+            .register("a.job", Schedule::cron("0 0 3 * * *").unwrap(), false)
+            .await;
+
+        let jobs = scheduler.jobs().await;
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].name, "a.job");
+        assert_eq!(jobs[0].kind, "cron");
+        assert!(!jobs[0].enabled);
+        assert!(jobs[0].next_fire_at.is_none());
+        assert_eq!(jobs[1].schedule, "every 60s");
+        assert!(jobs[1].next_fire_at.is_some());
+        assert!(jobs[1].last_run.is_none());
+    }
+
+    #[tokio::test]
+    async fn due_job_runs_and_records_last_run() {
+        let queue = JobQueueClient::new();
+        let scheduler = CronScheduler::new(queue.clone());
+        scheduler
+            .register(
+                "test.noop",
+                Schedule::interval(Duration::from_secs(60)),
+                true,
+            )
+            .await;
+
+        let started = scheduler
+            .run_due(Utc::now() + chrono::Duration::seconds(61))
+            .await;
+        assert_eq!(started, vec!["test.noop".to_string()]);
+
+        for _ in 0..50 {
+            if scheduler.job("test.noop").await.unwrap().last_run.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let status = scheduler.job("test.noop").await.unwrap();
+        assert!(!status.running);
+        assert_eq!(status.last_run.unwrap().status, JobStatus::Completed);
+        assert_eq!(queue.count_runs(Some("test.noop")).await, 1);
+        assert!(didhub_metrics::render().contains(&format!(
+            "{}_count{{job_name=\"test.noop\",result=\"success\"}} 1\n",
+            didhub_metrics::SCHEDULED_JOB_DURATION
+        )));
+    }
+
+    #[tokio::test]
+    async fn running_job_is_not_fired_twice() {
+        let queue = JobQueueClient::new();
+        queue.register_executor(SlowExecutor).await;
+        let scheduler = CronScheduler::new(queue);
+        scheduler
+            .register(
+                "test.slow",
+                Schedule::interval(Duration::from_secs(1)),
+                true,
+            )
+            .await;
+
+        let later = Utc::now() + chrono::Duration::seconds(5);
+        assert_eq!(scheduler.run_due(later).await.len(), 1);
+        assert!(scheduler.job("test.slow").await.unwrap().running);
+        assert!(scheduler
+            .run_due(later + chrono::Duration::seconds(5))
+            .await
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn set_enabled_on_unknown_job_fails() {
+        let scheduler = CronScheduler::new(JobQueueClient::new());
+        assert!(matches!(
+            scheduler.set_enabled("missing", true).await,
+            Err(SchedulerError::UnknownJob(_))
+        ));
+    }
+}
@@ -71,6 +71,10 @@ DEFAULT_TYPES: Final[dict[str, dict[str, str]]] = {
 
 DEFAULT_HEADER: Final[str] = "-- Auto-generated by migration_generator. Do not modify."
 
+# Outputs ending in the reversible suffix also get a matching down migration
+UP_SUFFIX: Final[str] = ".up.sql"
+DOWN_SUFFIX: Final[str] = ".down.sql"
+
 DEFAULT_AUTO_INCREMENT: Final[dict[str, str]] = {
     "postgres": "GENERATED BY DEFAULT AS IDENTITY",
     "mysql": "AUTO_INCREMENT",
@@ -88,6 +92,14 @@ class DialectConfig:
     footer: str | None
     statement_terminator: str = ";"
 
+    @property
+    def down_output_path(self) -> Path | None:
+        """Path of the down migration, for reversible (``.up.sql``) outputs."""
+        name = self.output_path.name
+        if not name.endswith(UP_SUFFIX):
+            return None
+        return self.output_path.with_name(name[: -len(UP_SUFFIX)] + DOWN_SUFFIX)
+
 
 class MigrationGenerator:
     """Generates SQL migrations for multiple database dialects.
@@ -252,6 +264,54 @@ class MigrationGenerator:
 
         return "\n\n".join(s for s in sections if s.strip()) + "\n"
 
+    def generate_down(self, dialect_name: str) -> str:
+        """Generate the down migration reverting :meth:`generate`.
+
+        Explicit ``global_statements.<dialect>.down`` statements run first, then
+        the migration's tables are dropped in reverse order (their indexes go with
+        them). Global statements cannot be reversed automatically, so a dialect
+        that has any must list its ``down`` statements.
+
+        Raises:
+            DialectError: If the dialect is not defined.
+            SchemaValidationError: If global statements lack a ``down`` list.
+        """
+        if dialect_name not in self.dialects:
+            raise DialectError(
+                "is not defined in schema",
+                dialect_name,
+                self._schema_path,
+            )
+
+        dialect_cfg = self.dialects[dialect_name]
+        globals_cfg = self.schema.get("global_statements", {}).get(dialect_name, {})
+        down = globals_cfg.get("down")
+        if down is None and (
+            globals_cfg.get("before_tables") or globals_cfg.get("after_tables")
+        ):
+            raise SchemaValidationError(
+                f"global_statements for '{dialect_name}' need a 'down' list "
+                "to generate a reversible migration",
+                self._schema_path,
+            )
+
+        sections: list[str] = []
+        header = dialect_cfg.header or self.schema.get("default_header")
+        if header:
+            sections.append(header.strip())
+        if down:
+            sections.append(self._join_statements(down))
+
+        drops = [
+            f"DROP TABLE IF EXISTS {table['name']}{dialect_cfg.statement_terminator}"
+            for table in reversed(self.schema["tables"])
+            if not table.get("dialects") or dialect_name in table["dialects"]
+        ]
+        if drops:
+            sections.append("\n".join(drops))
+
+        return "\n\n".join(s for s in sections if s.strip()) + "\n"
+
     def _render_indexes(
         self,
         table: dict[str, Any],
@@ -267,16 +327,28 @@ class MigrationGenerator:
     def write(self, dialect_name: str) -> Path:
         """Write migration to file for a specific dialect.
 
+        Reversible outputs also get their down migration written next to them,
+        and a stale non-reversible file for the same migration is removed so
+        the migrator does not see the version twice.
+
         Args:
             dialect_name: Name of the target dialect.
 
         Returns:
-            Path to the written file.
+            Path to the written (up) file.
         """
         dialect_cfg = self.dialects[dialect_name]
         content = self.generate(dialect_name)
         dialect_cfg.output_path.parent.mkdir(parents=True, exist_ok=True)
         dialect_cfg.output_path.write_text(content, encoding="utf-8")
+
+        down_path = dialect_cfg.down_output_path
+        if down_path is not None:
+            down_path.write_text(self.generate_down(dialect_name), encoding="utf-8")
+            simple_path = down_path.with_name(
+                down_path.name[: -len(DOWN_SUFFIX)] + ".sql"
+            )
+            simple_path.unlink(missing_ok=True)
         return dialect_cfg.output_path
 
     def _join_statements(self, statements: Iterator[Any] | list[Any]) -> str:
@@ -6,6 +6,7 @@ import pytest
 from build_tools.migration_generator.main import (
     DialectConfig,
     MigrationGenerator,
+    SchemaValidationError,
     generate_migrations,
     main,
 )
@@ -90,6 +91,48 @@ class TestMigrationGenerator:
         content = output_path.read_text()
         assert "CREATE TABLE IF NOT EXISTS users" in content
 
+    def test_write_reversible_pair(self, tmp_path):
+        (tmp_path / "0001_init.sql").write_text("-- stale")
+        schema = {
+            "dialects": {"sqlite": {"output": "0001_init.up.sql"}},
+            "tables": [
+                {"name": "users", "columns": [{"name": "id", "type": "integer"}]},
+                {"name": "posts", "columns": [{"name": "id", "type": "integer"}]},
+            ],
+            "global_statements": {
+                "sqlite": {
+                    "after_tables": ["CREATE VIEW names AS SELECT id FROM users;"],
+                    "down": ["DROP VIEW IF EXISTS names;"],
+                }
+            },
+        }
+
+        generator = MigrationGenerator(schema, tmp_path, "test.yaml")
+        output_path = generator.write("sqlite")
+
+        assert output_path.name == "0001_init.up.sql"
+        down = (tmp_path / "0001_init.down.sql").read_text()
+        assert down.index("DROP VIEW IF EXISTS names;") < down.index(
+            "DROP TABLE IF EXISTS posts;"
+        )
+        assert down.index("DROP TABLE IF EXISTS posts;") < down.index(
+            "DROP TABLE IF EXISTS users;"
+        )
+        assert not (tmp_path / "0001_init.sql").exists()
+
+    def test_generate_down_requires_global_down(self, tmp_path):
+        schema = {
+            "dialects": {"sqlite": {"output": "0002_idx.up.sql"}},
+            "tables": [],
+            "global_statements": {
+                "sqlite": {"after_tables": ["CREATE INDEX idx ON users(id);"]}
+            },
+        }
+
+        generator = MigrationGenerator(schema, tmp_path, "test.yaml")
+        with pytest.raises(SchemaValidationError):
+            generator.generate_down("sqlite")
+
     def test_join_statements(self, tmp_path):
         schema = {
             "dialects": {"sqlite": {"output": "migrations_sqlite.sql"}},
@@ -9,6 +9,8 @@
  * 2. Backend: Argon2id(SHA-256(password)) -> stored hash
  */
 
+import type { PasswordPolicy } from './types';
+
 /**
  * Hash a password using SHA-256 and return as lowercase hex string.
  * This should be used before sending any password to the backend.
@@ -61,6 +63,53 @@ export async function hashPasswordForRegistration(
   return hashPassword(password);
 }
 
+/**
+ * Check a password against the server's password policy
+ * (`GET /password-policy`). The server only receives the SHA-256 hash, so
+ * length and character class rules have to be enforced here; banned passwords
+ * are rejected by the server as well.
+ *
+ * @param password - The plaintext password
+ * @param policy - The policy returned by the server
+ * @returns Messages for every rule the password fails; empty if it passes
+ */
+export function checkPasswordPolicy(password: string, policy: PasswordPolicy): string[] {
+  const problems: string[] = [];
+  if (Array.from(password).length < policy.minLength) {
+    problems.push(`Password must be at least ${policy.minLength} characters`);
+  }
+  if (policy.requireLowercase && !/\p{Ll}/u.test(password)) {
+    problems.push('Password must contain a lowercase letter');
+  }
+  if (policy.requireUppercase && !/\p{Lu}/u.test(password)) {
+    problems.push('Password must contain an uppercase letter');
+  }
+  if (policy.requireDigit && !/[0-9]/.test(password)) {
+    problems.push('Password must contain a digit');
+  }
+  if (policy.requireSymbol && !/[^\p{L}\p{N}\s]/u.test(password)) {
+    problems.push('Password must contain a symbol');
+  }
+  return problems;
+}
+
+/**
+ * Compute the uppercase SHA-1 hex digest used by the Have I Been Pwned range API.
+ * Only the first five characters are ever sent to the server
+ * (`GET /password-policy/breached/{prefix}`); the rest is compared locally.
+ *
+ * @param password - The plaintext password
+ * @returns The `prefix` to request and the `suffix` to look for in the response
+ */
+export async function breachLookupKey(password: string): Promise<{ prefix: string; suffix: string }> {
+  const digest = await crypto.subtle.digest('SHA-1', new TextEncoder().encode(password));
+  const hex = Array.from(new Uint8Array(digest))
+    .map(b => b.toString(16).padStart(2, '0'))
+    .join('')
+    .toUpperCase();
+  return { prefix: hex.slice(0, 5), suffix: hex.slice(5) };
+}
+
 /**
  * Create password change request payload.
  * 